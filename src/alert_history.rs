@@ -0,0 +1,252 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::alerts::AlertPayload;
+use crate::models::{EventType, Severity};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+/// Outcome of sending one alert to one channel (after retries).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub channel: String,
+    pub delivered: bool,
+    pub error: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub id: uuid::Uuid,
+    pub raised_at: DateTime<Utc>,
+    pub event_type: EventType,
+    pub severity: Severity,
+    pub state: AlertState,
+    pub service_id: Option<String>,
+    pub service_name: Option<String>,
+    /// `data.kind` of system alerts (rule, escalation, reminder, prometheus_alert, ...)
+    pub kind: Option<String>,
+    pub message: String,
+    /// Firing alerts: when the matching resolution arrived
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Firing alerts once resolved, and resolutions: how long the alert was open
+    pub duration_seconds: Option<i64>,
+    pub deliveries: Vec<Delivery>,
+    /// Why no notification went out ("silenced", "duplicate", "held", "dry_run")
+    pub suppressed: Option<String>,
+    pub data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryQuery {
+    pub service: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryPage {
+    /// Records matching the filters, before pagination
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Newest first
+    pub items: Vec<AlertRecord>,
+}
+
+/// Raised and resolved alerts, persisted as a JSON array and capped at `capacity` (oldest dropped).
+pub struct AlertHistory {
+    path: Option<PathBuf>,
+    capacity: usize,
+    entries: Mutex<Vec<AlertRecord>>,
+}
+
+impl AlertHistory {
+    pub fn open(path: Option<PathBuf>, capacity: usize) -> Self {
+        let entries = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|raw| match serde_json::from_str::<Vec<AlertRecord>>(&raw) {
+                Ok(v) => Some(v),
+                Err(e) => { warn!(error=%e, "ignoring unreadable alert history file"); None }
+            })
+            .unwrap_or_default();
+        Self { path, capacity, entries: Mutex::new(entries) }
+    }
+
+    /// Append an alert. A resolution closes the open alerts of the same service and kind.
+    pub fn record(&self, payload: &AlertPayload, deliveries: Vec<Delivery>, suppressed: Option<&str>) {
+        let Ok(mut entries) = self.entries.lock() else { return };
+        let at = payload.timestamp;
+        let key = alert_key(payload);
+        let state = if is_resolution(payload) { AlertState::Resolved } else { AlertState::Firing };
+        let mut duration_seconds = None;
+        if state == AlertState::Resolved {
+            for open in entries.iter_mut().filter(|r| r.state == AlertState::Firing && r.resolved_at.is_none() && record_key(r) == key) {
+                let open_for = at.signed_duration_since(open.raised_at).num_seconds();
+                open.resolved_at = Some(at);
+                open.duration_seconds = Some(open_for);
+                duration_seconds = Some(duration_seconds.map_or(open_for, |d: i64| d.max(open_for)));
+            }
+        }
+        entries.push(AlertRecord {
+            id: uuid::Uuid::new_v4(),
+            raised_at: at,
+            event_type: payload.event_type.clone(),
+            severity: payload.severity,
+            state,
+            service_id: payload.service_id.clone(),
+            service_name: payload.service_name.clone(),
+            kind: kind(payload).map(str::to_string),
+            message: payload.message.clone(),
+            resolved_at: None,
+            duration_seconds,
+            deliveries,
+            suppressed: suppressed.map(str::to_string),
+            data: payload.data.clone(),
+        });
+        if entries.len() > self.capacity {
+            let overflow = entries.len() - self.capacity;
+            entries.drain(0..overflow);
+        }
+        self.persist(&entries);
+    }
+
+    pub fn query(&self, q: &HistoryQuery) -> HistoryPage {
+        let limit = q.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let entries = self.entries.lock().map(|e| e.clone()).unwrap_or_default();
+        let matching: Vec<AlertRecord> = entries
+            .into_iter()
+            .rev()
+            .filter(|r| q.service.as_ref().is_none_or(|s| r.service_id.as_ref() == Some(s)))
+            .filter(|r| q.from.is_none_or(|from| r.raised_at >= from))
+            .filter(|r| q.to.is_none_or(|to| r.raised_at < to))
+            .collect();
+        HistoryPage {
+            total: matching.len(),
+            offset: q.offset,
+            limit,
+            items: matching.into_iter().skip(q.offset).take(limit).collect(),
+        }
+    }
+
+    fn persist(&self, entries: &[AlertRecord]) {
+        let Some(path) = &self.path else { return };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match serde_json::to_vec(entries) {
+            Ok(bytes) => if let Err(e) = std::fs::write(path, bytes) { warn!(error=%e, path=%path.display(), "failed to persist alert history") },
+            Err(e) => warn!(error=%e, "failed to serialize alert history"),
+        }
+    }
+}
+
+fn field<'a>(data: Option<&'a serde_json::Value>, key: &str) -> Option<&'a str> {
+    data.and_then(|d| d.get(key)).and_then(|v| v.as_str())
+}
+
+fn kind(payload: &AlertPayload) -> Option<&str> {
+    field(payload.data.as_ref(), "kind")
+}
+
+fn is_resolution(payload: &AlertPayload) -> bool {
+    payload.event_type == EventType::ServiceUp || field(payload.data.as_ref(), "state") == Some("resolved")
+}
+
+/// ServiceDown/ServiceUp pair per service; system alerts pair by kind and rule.
+fn alert_key(payload: &AlertPayload) -> (Option<String>, String) {
+    let family = match payload.event_type {
+        EventType::ServiceDown | EventType::ServiceUp => "status".to_string(),
+        _ => format!("{}:{}", kind(payload).unwrap_or_default(), field(payload.data.as_ref(), "rule").unwrap_or_default()),
+    };
+    (payload.service_id.clone(), family)
+}
+
+fn record_key(record: &AlertRecord) -> (Option<String>, String) {
+    let family = match record.event_type {
+        EventType::ServiceDown | EventType::ServiceUp => "status".to_string(),
+        _ => format!("{}:{}", record.kind.as_deref().unwrap_or_default(), field(record.data.as_ref(), "rule").unwrap_or_default()),
+    };
+    (record.service_id.clone(), family)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(event_type: EventType, service: &str, minute: i64, data: Option<serde_json::Value>) -> AlertPayload {
+        let t0 = DateTime::parse_from_rfc3339("2025-09-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        AlertPayload {
+            event_type,
+            severity: Severity::Warning,
+            service_id: Some(service.into()),
+            service_name: None,
+            labels: Default::default(),
+            metadata: Default::default(),
+            old_status: None,
+            new_status: None,
+            message: format!("{service} {minute}"),
+            timestamp: t0 + chrono::Duration::minutes(minute),
+            data,
+        }
+    }
+
+    #[test]
+    fn resolutions_close_open_alerts_and_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        let history = AlertHistory::open(Some(path.clone()), 100);
+        let delivered = vec![Delivery { channel: "slack".into(), delivered: true, error: None, at: Utc::now() }];
+        history.record(&payload(EventType::ServiceDown, "fks_api", 0, None), delivered, None);
+        let rule = |state| Some(serde_json::json!({"kind": "rule", "rule": "hot", "state": state}));
+        history.record(&payload(EventType::SystemAlert, "fks_api", 5, rule("firing")), vec![], Some("held"));
+        history.record(&payload(EventType::ServiceUp, "fks_api", 12, None), vec![], None);
+
+        let reopened = AlertHistory::open(Some(path), 100);
+        let page = reopened.query(&HistoryQuery::default());
+        assert_eq!(page.total, 3);
+        let [up, rule_alert, down] = &page.items[..] else { panic!("three records") };
+        assert_eq!((up.state, up.duration_seconds), (AlertState::Resolved, Some(12 * 60)));
+        assert_eq!(down.resolved_at, Some(up.raised_at));
+        assert_eq!(down.deliveries.len(), 1);
+        assert!(rule_alert.resolved_at.is_none(), "ServiceUp does not resolve rule alerts");
+        assert_eq!(rule_alert.suppressed.as_deref(), Some("held"));
+
+        history.record(&payload(EventType::SystemAlert, "fks_api", 20, rule("resolved")), vec![], None);
+        assert_eq!(history.query(&HistoryQuery::default()).items[0].duration_seconds, Some(15 * 60));
+    }
+
+    #[test]
+    fn query_filters_and_paginates_newest_first() {
+        let history = AlertHistory::open(None, 3);
+        for minute in 0..4 {
+            let service = if minute % 2 == 0 { "fks_api" } else { "fks_web" };
+            history.record(&payload(EventType::HighLatency, service, minute, None), vec![], None);
+        }
+        let all = history.query(&HistoryQuery::default());
+        assert_eq!(all.total, 3, "capacity drops the oldest");
+        assert_eq!(all.items[0].message, "fks_web 3");
+
+        let api = history.query(&HistoryQuery { service: Some("fks_api".into()), ..Default::default() });
+        assert_eq!(api.items.iter().map(|r| r.message.as_str()).collect::<Vec<_>>(), vec!["fks_api 2"]);
+
+        let from = all.items[1].raised_at;
+        let window = history.query(&HistoryQuery { from: Some(from), limit: Some(1), offset: 1, ..Default::default() });
+        assert_eq!((window.total, window.items.len()), (2, 1));
+        assert_eq!(window.items[0].message, "fks_api 2");
+    }
+}
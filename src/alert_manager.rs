@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+
+use crate::alerts::AlertPayload;
+use crate::config::AlertThrottleConfig;
+use crate::models::EventType;
+
+/// What the dispatcher should do with an alert offered to the [`AlertManager`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// Deliver now
+    Send,
+    /// Same alert as the service's previous one within the dedup window; drop it
+    Duplicate,
+    /// Service is cooling down or alerts are bursting; folded into the next summary
+    Held,
+}
+
+/// Sits between monitor events and notifiers: drops repeated alerts, enforces a per-service
+/// cooldown and batches bursts into a single summary so a flapping service cannot page dozens of times.
+pub struct AlertManager {
+    config: AlertThrottleConfig,
+    /// Dedup key and time of the latest alert (sent or held) per service
+    last_alert: HashMap<String, (String, DateTime<Utc>)>,
+    /// Time of the latest notification delivered per service
+    last_sent: HashMap<String, DateTime<Utc>>,
+    recent_sends: VecDeque<DateTime<Utc>>,
+    held: Vec<AlertPayload>,
+    held_since: Option<DateTime<Utc>>,
+}
+
+impl AlertManager {
+    pub fn new(config: AlertThrottleConfig) -> Self {
+        Self {
+            config,
+            last_alert: HashMap::new(),
+            last_sent: HashMap::new(),
+            recent_sends: VecDeque::new(),
+            held: Vec::new(),
+            held_since: None,
+        }
+    }
+
+    /// Decide what to do with `payload`. `urgent` alerts (critical outages) are never held, only deduplicated.
+    pub fn admit(&mut self, payload: &AlertPayload, now: DateTime<Utc>, urgent: bool) -> Admission {
+        let service = service_key(payload);
+        let key = dedup_key(payload);
+        if let Some((last_key, at)) = self.last_alert.get(&service) {
+            if *last_key == key && seconds_between(*at, now) < self.config.dedup_window_seconds as i64 {
+                return Admission::Duplicate;
+            }
+        }
+        self.last_alert.insert(service.clone(), (key, now));
+
+        if !urgent && (self.cooling_down(&service, now) || self.bursting(now)) {
+            self.held.push(payload.clone());
+            self.held_since.get_or_insert(now);
+            return Admission::Held;
+        }
+        self.note_sent(&service, now);
+        Admission::Send
+    }
+
+    /// Once the batch window has elapsed, release held alerts: a lone alert as-is, several as one summary.
+    pub fn flush_due(&mut self, now: DateTime<Utc>) -> Option<AlertPayload> {
+        let since = self.held_since?;
+        if seconds_between(since, now) < self.config.batch_window_seconds as i64 { return None; }
+        self.held_since = None;
+        let held = std::mem::take(&mut self.held);
+        let payload = if held.len() == 1 { held.into_iter().next()? } else { summarize(held, now) };
+        self.note_sent(&service_key(&payload), now);
+        Some(payload)
+    }
+
+    fn cooling_down(&self, service: &str, now: DateTime<Utc>) -> bool {
+        self.last_sent
+            .get(service)
+            .is_some_and(|at| seconds_between(*at, now) < self.config.service_cooldown_seconds as i64)
+    }
+
+    fn bursting(&mut self, now: DateTime<Utc>) -> bool {
+        let window = self.config.batch_window_seconds as i64;
+        while self.recent_sends.front().is_some_and(|at| seconds_between(*at, now) >= window) {
+            self.recent_sends.pop_front();
+        }
+        self.config.burst_threshold > 0 && self.recent_sends.len() >= self.config.burst_threshold
+    }
+
+    fn note_sent(&mut self, service: &str, now: DateTime<Utc>) {
+        self.last_sent.insert(service.to_string(), now);
+        self.recent_sends.push_back(now);
+        // Keep maps bounded to services seen within the longest window
+        let horizon = self.config.dedup_window_seconds.max(self.config.service_cooldown_seconds) as i64;
+        self.last_sent.retain(|_, at| seconds_between(*at, now) < horizon);
+        self.last_alert.retain(|_, (_, at)| seconds_between(*at, now) < horizon);
+    }
+}
+
+fn seconds_between(earlier: DateTime<Utc>, later: DateTime<Utc>) -> i64 {
+    later.signed_duration_since(earlier).num_seconds()
+}
+
+fn service_key(payload: &AlertPayload) -> String {
+    payload.service_id.clone().unwrap_or_else(|| "system".to_string())
+}
+
+/// Alerts are identical when type, service and (for system/rule alerts) kind, rule and state match.
+/// The message is ignored since it embeds changing values such as latency.
+fn dedup_key(payload: &AlertPayload) -> String {
+    let field = |k: &str| payload.data.as_ref().and_then(|d| d.get(k)).and_then(|v| v.as_str()).unwrap_or("");
+    format!("{:?}|{}|{}|{}|{}", payload.event_type, service_key(payload), field("kind"), field("rule"), field("state"))
+}
+
+fn summarize(held: Vec<AlertPayload>, now: DateTime<Utc>) -> AlertPayload {
+    let mut per_service: Vec<(String, usize, &AlertPayload)> = Vec::new();
+    for p in &held {
+        let service = p.service_name.clone().or_else(|| p.service_id.clone()).unwrap_or_else(|| "system".into());
+        match per_service.iter_mut().find(|(s, _, _)| *s == service) {
+            Some(entry) => { entry.1 += 1; entry.2 = p; }
+            None => per_service.push((service, 1, p)),
+        }
+    }
+    let lines: Vec<String> = per_service
+        .iter()
+        .map(|(service, count, latest)| format!("{service}: {count} alert(s), latest: {}", latest.message))
+        .collect();
+    let single_service = held.iter().all(|p| p.service_id == held[0].service_id);
+    AlertPayload {
+        event_type: EventType::SystemAlert,
+        severity: held.iter().map(|p| p.severity).max().unwrap_or_default(),
+        service_id: if single_service { held[0].service_id.clone() } else { None },
+        service_name: if single_service { held[0].service_name.clone() } else { None },
+        labels: if single_service { held[0].labels.clone() } else { Default::default() },
+        metadata: if single_service { held[0].metadata.clone() } else { Default::default() },
+        old_status: if single_service { held.iter().find_map(|p| p.old_status.clone()) } else { None },
+        new_status: if single_service { held.iter().rev().find_map(|p| p.new_status.clone()) } else { None },
+        message: format!("{} alerts batched during cooldown/burst — {}", held.len(), lines.join("; ")),
+        timestamp: now,
+        data: Some(serde_json::json!({
+            "kind": "alert_summary",
+            "count": held.len(),
+            "alerts": held,
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Severity;
+
+    fn cfg() -> AlertThrottleConfig {
+        AlertThrottleConfig { dedup_window_seconds: 300, service_cooldown_seconds: 120, burst_threshold: 3, batch_window_seconds: 60 }
+    }
+
+    fn alert(event_type: EventType, service: &str, status: &str) -> AlertPayload {
+        AlertPayload {
+            event_type,
+            severity: Severity::Warning,
+            service_id: Some(service.into()),
+            service_name: None,
+            labels: Default::default(),
+            metadata: Default::default(),
+            old_status: None,
+            new_status: Some(status.into()),
+            message: format!("{service} is {status}"),
+            timestamp: Utc::now(),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn flapping_service_is_collapsed_into_one_summary() {
+        let mut m = AlertManager::new(cfg());
+        let t0 = Utc::now();
+        let at = |s| t0 + chrono::Duration::seconds(s);
+        assert_eq!(m.admit(&alert(EventType::ServiceDown, "api", "Unhealthy"), at(0), false), Admission::Send);
+        assert_eq!(m.admit(&alert(EventType::ServiceDown, "api", "Unhealthy"), at(5), false), Admission::Duplicate);
+        assert_eq!(m.admit(&alert(EventType::ServiceUp, "api", "Healthy"), at(10), false), Admission::Held);
+        assert_eq!(m.admit(&alert(EventType::ServiceDown, "api", "Unhealthy"), at(20), false), Admission::Held);
+        assert!(m.flush_due(at(30)).is_none());
+        let summary = m.flush_due(at(70)).expect("summary after batch window");
+        assert_eq!(summary.data.as_ref().unwrap()["count"], 2);
+        assert_eq!(summary.service_id.as_deref(), Some("api"));
+        assert_eq!(summary.new_status.as_deref(), Some("Unhealthy"));
+        assert!(m.flush_due(at(200)).is_none(), "held alerts are released once");
+    }
+
+    #[test]
+    fn bursts_across_services_are_batched_but_urgent_alerts_pass() {
+        let mut m = AlertManager::new(cfg());
+        let t0 = Utc::now();
+        for svc in ["a", "b", "c"] {
+            assert_eq!(m.admit(&alert(EventType::HighLatency, svc, "Degraded"), t0, false), Admission::Send);
+        }
+        assert_eq!(m.admit(&alert(EventType::HighLatency, "d", "Degraded"), t0, false), Admission::Held);
+        assert_eq!(m.admit(&alert(EventType::ServiceDown, "e", "Unhealthy"), t0, true), Admission::Send);
+        let lone = m.flush_due(t0 + chrono::Duration::seconds(60)).unwrap();
+        assert_eq!(lone.service_id.as_deref(), Some("d"));
+        assert_eq!(lone.event_type, EventType::HighLatency);
+    }
+
+    #[test]
+    fn zero_windows_disable_throttling() {
+        let mut m = AlertManager::new(AlertThrottleConfig { dedup_window_seconds: 0, service_cooldown_seconds: 0, burst_threshold: 0, batch_window_seconds: 0 });
+        let t0 = Utc::now();
+        for _ in 0..10 {
+            assert_eq!(m.admit(&alert(EventType::ServiceDown, "api", "Unhealthy"), t0, false), Admission::Send);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+use crate::models::{EventType, MonitorEvent, ServiceConfig};
+
+/// Labels tried, in order, to attribute a Prometheus alert to a configured service.
+const SERVICE_LABELS: [&str; 4] = ["service", "job", "container", "container_name"];
+
+/// Prometheus Alertmanager webhook body (`version: "4"`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookMessage {
+    #[serde(default)]
+    pub receiver: String,
+    #[serde(default)]
+    pub status: String,
+    pub alerts: Vec<Alert>,
+    #[serde(default)]
+    pub external_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub status: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default, rename = "generatorURL")]
+    pub generator_url: Option<String>,
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+}
+
+/// Convert every alert in the message to a `SystemAlert` event (`kind = "prometheus_alert"`),
+/// attributed to a configured service when one of its labels names it.
+pub fn to_events(message: &WebhookMessage, services: &[ServiceConfig], now: DateTime<Utc>) -> Vec<MonitorEvent> {
+    message.alerts.iter().map(|alert| {
+        let alertname = alert.labels.get("alertname").cloned().unwrap_or_else(|| "PrometheusAlert".into());
+        let state = if alert.status == "resolved" { "resolved" } else { "firing" };
+        let service_id = SERVICE_LABELS
+            .iter()
+            .filter_map(|l| alert.labels.get(*l))
+            .find_map(|v| services.iter().find(|s| &s.id == v || s.docker_container.as_ref() == Some(v)))
+            .map(|s| s.id.clone());
+        let summary = alert.annotations.get("summary").or_else(|| alert.annotations.get("description"));
+        let text = match (state, summary) {
+            ("resolved", Some(text)) => format!("[resolved] {alertname}: {text}"),
+            ("resolved", None) => format!("[resolved] {alertname}"),
+            (_, Some(text)) => format!("{alertname}: {text}"),
+            (_, None) => alertname.clone(),
+        };
+        MonitorEvent {
+            event_type: EventType::SystemAlert,
+            service_id,
+            message: text,
+            timestamp: now,
+            data: Some(json!({
+                "kind": "prometheus_alert",
+                "source": "alertmanager",
+                "receiver": message.receiver,
+                "rule": alertname,
+                "state": state,
+                "severity": alert.labels.get("severity").map(String::as_str).unwrap_or("warning"),
+                "labels": alert.labels,
+                "annotations": alert.annotations,
+                "starts_at": alert.starts_at,
+                "ends_at": alert.ends_at,
+                "generator_url": alert.generator_url,
+                "fingerprint": alert.fingerprint,
+                "external_url": message.external_url,
+            })),
+            metadata: Default::default(),
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProbeMode, ServiceType};
+
+    #[test]
+    fn converts_alertmanager_payload_to_events() {
+        let body = r#"{
+            "version": "4",
+            "groupKey": "{}:{alertname=\"HighErrorRate\"}",
+            "status": "firing",
+            "receiver": "fks_master",
+            "groupLabels": {"alertname": "HighErrorRate"},
+            "commonLabels": {},
+            "commonAnnotations": {},
+            "externalURL": "http://alertmanager:9093",
+            "alerts": [
+                {"status": "firing", "labels": {"alertname": "HighErrorRate", "job": "fks_api", "severity": "critical"},
+                 "annotations": {"summary": "5xx above 5%"}, "startsAt": "2025-08-27T10:00:00Z", "endsAt": "0001-01-01T00:00:00Z",
+                 "generatorURL": "http://prometheus:9090/graph", "fingerprint": "abc123"},
+                {"status": "resolved", "labels": {"alertname": "DiskFull", "instance": "node-1"}, "annotations": {}}
+            ]
+        }"#;
+        let message: WebhookMessage = serde_json::from_str(body).unwrap();
+        let services = vec![ServiceConfig {
+            id: "fks_api".into(),
+            name: "FKS API".into(),
+            health_endpoint: String::new(),
+            service_type: ServiceType::Api,
+            docker_container: None,
+            expected_response_time_ms: 100,
+            critical: true,
+            check_interval_seconds: None,
+            heartbeat_window_seconds: None,
+            depends_on: vec![],
+            tags: vec![],
+            probes: vec![],
+            probe_mode: ProbeMode::All,
+            labels: Default::default(),
+            group: None,
+            metadata: Default::default(),
+            auto_update: false,
+            kubernetes: None,
+            systemd_unit: None,
+            auto_restart: None,
+            drain: None,
+            metrics: None,
+            docker_host: None,
+        }];
+        let events = to_events(&message, &services, Utc::now());
+        assert_eq!(events.len(), 2);
+
+        let firing = &events[0];
+        assert_eq!(firing.event_type, EventType::SystemAlert);
+        assert_eq!(firing.service_id.as_deref(), Some("fks_api"));
+        assert_eq!(firing.message, "HighErrorRate: 5xx above 5%");
+        let data = firing.data.as_ref().unwrap();
+        assert_eq!((data["kind"].as_str(), data["state"].as_str(), data["severity"].as_str()), (Some("prometheus_alert"), Some("firing"), Some("critical")));
+        assert_eq!(data["fingerprint"], "abc123");
+
+        let resolved = &events[1];
+        assert!(resolved.service_id.is_none(), "unknown instance is not attributed to a service");
+        assert_eq!(resolved.message, "[resolved] DiskFull");
+        assert_eq!(resolved.data.as_ref().unwrap()["state"], "resolved");
+    }
+}
@@ -0,0 +1,917 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::alert_history::{AlertHistory, Delivery, HistoryPage, HistoryQuery};
+use crate::alert_manager::{Admission, AlertManager};
+use crate::config::{AlertRoute, Config, EmailConfig, SharedConfig, SlackConfig, SmtpTls};
+use crate::metrics;
+use crate::models::{EventType, HealthStatus, MonitorEvent, ServiceConfig, Severity};
+use crate::monitor::MonitorHandle;
+use crate::outbound::{self, Destination};
+use crate::silence::{Silences, MAINTENANCE};
+
+const DELIVERY_ATTEMPTS: u32 = 3;
+const DEAD_LETTER_CAPACITY: usize = 1000;
+const SLACK_POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
+/// JSON body POSTed to `alerts.webhook_url`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertPayload {
+    pub event_type: EventType,
+    #[serde(default)]
+    pub severity: Severity,
+    pub service_id: Option<String>,
+    pub service_name: Option<String>,
+    /// The service's `labels` (team, environment, ...), also available to templates as `{label.<key>}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// The service's `metadata`, passed through untouched
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    pub old_status: Option<String>,
+    pub new_status: Option<String>,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+    pub data: Option<serde_json::Value>,
+}
+
+impl AlertPayload {
+    /// Build a payload for alertable events (ServiceDown / ServiceUp / HighLatency / SystemAlert); other events yield `None`.
+    pub fn from_event(event: &MonitorEvent, config: &Config) -> Option<Self> {
+        if !matches!(event.event_type, EventType::ServiceDown | EventType::ServiceUp | EventType::HighLatency | EventType::SystemAlert) {
+            return None;
+        }
+        Some(Self::for_event(event, config))
+    }
+
+    /// Payload of any event, e.g. as the template context of a hook.
+    pub fn for_event(event: &MonitorEvent, config: &Config) -> Self {
+        let service = event.service_id.as_ref().and_then(|id| config.services.iter().find(|s| &s.id == id));
+        let service_name = service.map(|s| s.name.clone());
+        let labels = service.map(|s| s.labels.clone()).unwrap_or_default();
+        let metadata = service.map(|s| s.metadata.clone()).unwrap_or_default();
+        let status_field = |key: &str| event.data.as_ref()
+            .and_then(|d| d.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        Self {
+            event_type: event.event_type.clone(),
+            severity: event_severity(event, service),
+            service_id: event.service_id.clone(),
+            service_name,
+            labels,
+            metadata,
+            old_status: status_field("previous_status"),
+            new_status: status_field("status"),
+            message: event.message.clone(),
+            timestamp: event.timestamp,
+            data: event.data.clone(),
+        }
+    }
+}
+
+/// Severity given explicitly in the event data (rules, escalations, Prometheus alerts) wins; otherwise
+/// outages of critical services are critical, other outages and degradations warnings, recoveries info.
+pub fn event_severity(event: &MonitorEvent, service: Option<&ServiceConfig>) -> Severity {
+    if let Some(explicit) = event.data.as_ref().and_then(|d| d.get("severity")).and_then(|s| s.as_str()).and_then(Severity::parse) {
+        return explicit;
+    }
+    match event.event_type {
+        EventType::ServiceDown if service.is_some_and(|s| s.critical) => Severity::Critical,
+        EventType::ServiceUp => Severity::Info,
+        _ => Severity::Warning,
+    }
+}
+
+/// Notification that could not be delivered after all retries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: uuid::Uuid,
+    pub channel: String,
+    pub payload: AlertPayload,
+    pub error: String,
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Dead-letter queue persisted as a JSON array so undelivered alerts survive restarts.
+pub struct DeadLetterQueue {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<DeadLetter>>,
+}
+
+impl DeadLetterQueue {
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let entries = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|raw| match serde_json::from_str::<Vec<DeadLetter>>(&raw) {
+                Ok(v) => Some(v),
+                Err(e) => { warn!(error=%e, "ignoring unreadable alert dead-letter file"); None }
+            })
+            .unwrap_or_default();
+        Self { path, entries: Mutex::new(entries) }
+    }
+
+    pub fn list(&self) -> Vec<DeadLetter> {
+        self.entries.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    fn push(&self, letter: DeadLetter) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(letter);
+            if entries.len() > DEAD_LETTER_CAPACITY {
+                let overflow = entries.len() - DEAD_LETTER_CAPACITY;
+                for dropped in entries.drain(0..overflow) {
+                    metrics::increment_alert_dropped(&dropped.channel);
+                }
+            }
+            self.persist(&entries);
+        }
+    }
+
+    /// Remove and return entries (all when `ids` is empty).
+    fn take(&self, ids: &[uuid::Uuid]) -> Vec<DeadLetter> {
+        let Ok(mut entries) = self.entries.lock() else { return vec![] };
+        let (taken, kept): (Vec<_>, Vec<_>) = entries.drain(..).partition(|e| ids.is_empty() || ids.contains(&e.id));
+        *entries = kept;
+        self.persist(&entries);
+        taken
+    }
+
+    fn persist(&self, entries: &[DeadLetter]) {
+        let Some(path) = &self.path else { return };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match serde_json::to_vec_pretty(entries) {
+            Ok(bytes) => if let Err(e) = std::fs::write(path, bytes) { warn!(error=%e, path=%path.display(), "failed to persist alert dead-letter queue") },
+            Err(e) => warn!(error=%e, "failed to serialize alert dead-letter queue"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RedeliveryReport {
+    pub redelivered: usize,
+    pub failed: usize,
+    pub remaining: usize,
+}
+
+pub struct AlertDispatcher {
+    client: Client,
+    config: SharedConfig,
+    dead_letters: DeadLetterQueue,
+    history: AlertHistory,
+    manager: Mutex<AlertManager>,
+    dry_run: bool,
+    /// Set while handing over to an upgraded process so both do not notify for the same events
+    paused: AtomicBool,
+    silences: Silences,
+}
+
+impl AlertDispatcher {
+    pub fn new(config: Config) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+        let dead_letters = DeadLetterQueue::open(config.alerts.dead_letter_path.as_ref().map(PathBuf::from));
+        let history = AlertHistory::open(config.alerts.history_path.as_ref().map(PathBuf::from), config.alerts.history_capacity);
+        let manager = Mutex::new(AlertManager::new(config.alerts.throttle.clone()));
+        Self { client, config: SharedConfig::new(config), dead_letters, history, manager, dry_run: false, paused: AtomicBool::new(false), silences: Silences::default() }
+    }
+
+    /// Log alert payloads instead of delivering them (used by replay mode).
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Follow `config` (typically the monitor's) so reloaded channels, routes and services apply.
+    /// Throttling, dead-letter and history settings stay as they were at startup.
+    pub fn with_shared_config(mut self, config: SharedConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Drop notifications for services covered by these silences / maintenance windows.
+    pub fn with_silences(mut self, silences: Silences) -> Self {
+        self.silences = silences;
+        self
+    }
+
+    /// Stop delivering notifications for the rest of this process's life.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    fn config(&self) -> Arc<Config> { self.config.get() }
+
+    fn has_channels(&self) -> bool {
+        ["webhook", "slack", "email", "pagerduty"].iter().any(|c| self.channel_configured(c))
+    }
+
+    pub fn dead_letters(&self) -> Vec<DeadLetter> { self.dead_letters.list() }
+
+    pub fn history(&self, query: &HistoryQuery) -> HistoryPage { self.history.query(query) }
+
+    /// Spawn the dispatcher loop if notifications are enabled and at least one channel is configured.
+    pub fn spawn(self: Arc<Self>, mut events: broadcast::Receiver<MonitorEvent>) -> Option<tokio::task::JoinHandle<()>> {
+        // A persisted history is kept even when nothing is notified
+        let notifies = self.config().alerts.enable_notifications && self.has_channels();
+        if !self.dry_run && !notifies && self.config().alerts.history_path.is_none() {
+            debug!("alert dispatcher disabled (notifications off or no channels configured, no alert history)");
+            return None;
+        }
+        info!("📣 Alert dispatcher started");
+        Some(tokio::spawn(async move {
+            let mut flush_tick = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                tokio::select! {
+                    received = events.recv() => match received {
+                        Ok(event) => self.dispatch(&event).await,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!(skipped, "alert dispatcher lagged; events dropped");
+                            for _ in 0..skipped { metrics::increment_alert_dropped("dispatcher"); }
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = flush_tick.tick() => self.flush_held().await,
+                }
+            }
+        }))
+    }
+
+    /// Channels that should receive this payload.
+    fn channels_for(&self, payload: &AlertPayload) -> Vec<&'static str> {
+        let mut channels = Vec::new();
+        // Escalations go only to the channels named by the policy step
+        if payload_kind(payload) == Some("escalation") {
+            let requested = payload.data.as_ref().and_then(|d| d.get("channels")).and_then(|c| c.as_array()).cloned().unwrap_or_default();
+            for channel in ["webhook", "slack", "email", "pagerduty"] {
+                if requested.iter().any(|c| c.as_str() == Some(channel)) && self.channel_configured(channel) {
+                    channels.push(channel);
+                }
+            }
+            return channels;
+        }
+        let config = self.config();
+        let routed = self.matching_routes(&config, payload);
+        if !routed.is_empty() {
+            // A route may bring its own webhook URL
+            let route_webhook = routed.iter().any(|r| r.webhook_url.is_some());
+            for channel in ["webhook", "slack", "email", "pagerduty"] {
+                let configured = self.channel_configured(channel) || (channel == "webhook" && route_webhook);
+                if routed.iter().any(|r| r.channels.iter().any(|c| c == channel)) && configured {
+                    channels.push(channel);
+                }
+            }
+            return channels;
+        }
+        if self.config().alerts.webhook_url.is_some() { channels.push("webhook"); }
+        let is_summary = payload_kind(payload) == Some("alert_summary");
+        // Reminders follow the outage notification they repeat
+        let is_reminder = payload_kind(payload) == Some("reminder");
+        if self.config().alerts.slack.is_some() && (matches!(payload.event_type, EventType::ServiceDown | EventType::ServiceUp) || is_summary || is_reminder) {
+            channels.push("slack");
+        }
+        let is_outage = matches!(payload.event_type, EventType::ServiceDown) || (is_reminder && payload.severity != Severity::Info);
+        if self.config().alerts.email.is_some() && is_outage && self.is_critical(payload.service_id.as_deref()) {
+            channels.push("email");
+        }
+        channels
+    }
+
+    /// Routes matching the payload's severity and service, honouring `continue`.
+    fn matching_routes<'a>(&self, config: &'a Config, payload: &AlertPayload) -> Vec<&'a AlertRoute> {
+        let service = config.services.iter().find(|s| Some(s.id.as_str()) == payload.service_id.as_deref());
+        let mut matched = Vec::new();
+        for route in config.alerts.routes.iter().filter(|r| r.matches(payload.severity, service)) {
+            matched.push(route);
+            if !route.continue_matching { break; }
+        }
+        matched
+    }
+
+    fn channel_configured(&self, channel: &str) -> bool {
+        match channel {
+            "webhook" => self.config().alerts.webhook_url.is_some(),
+            "slack" => self.config().alerts.slack.is_some(),
+            "email" => self.config().alerts.email.is_some(),
+            "pagerduty" => self.config().alerts.pagerduty.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Global maintenance covers alerts about no service in particular too.
+    fn silenced_by(&self, service_id: Option<&str>) -> Option<String> {
+        let now = Utc::now();
+        if self.silences.maintenance(now).is_some() {
+            return Some(MAINTENANCE.to_string());
+        }
+        let config = self.config();
+        let service = config.services.iter().find(|s| Some(s.id.as_str()) == service_id)?;
+        self.silences.silenced_by(service, now)
+    }
+
+    fn is_critical(&self, service_id: Option<&str>) -> bool {
+        service_id
+            .and_then(|id| self.config().services.iter().find(|s| s.id == id).map(|s| s.critical))
+            .unwrap_or(false)
+    }
+
+    /// Run an event through dedup/cooldown/batching and deliver it if admitted.
+    pub async fn dispatch(&self, event: &MonitorEvent) {
+        if self.paused.load(Ordering::Relaxed) { return; }
+        let Some(payload) = AlertPayload::from_event(event, &self.config()) else { return };
+        if let Some(by) = self.silenced_by(payload.service_id.as_deref()) {
+            debug!(event_type=?payload.event_type, service_id=?payload.service_id, silenced_by=%by, "alert silenced");
+            metrics::increment_alert_suppressed("silenced");
+            self.history.record(&payload, vec![], Some("silenced"));
+            return;
+        }
+        let urgent = (payload.event_type == EventType::ServiceDown && self.is_critical(payload.service_id.as_deref()))
+            || payload_kind(&payload) == Some("escalation");
+        let admission = match self.manager.lock() {
+            Ok(mut manager) => manager.admit(&payload, Utc::now(), urgent),
+            Err(_) => Admission::Send,
+        };
+        match admission {
+            Admission::Send => self.deliver_and_record(&payload).await,
+            Admission::Duplicate => {
+                debug!(event_type=?payload.event_type, service_id=?payload.service_id, "duplicate alert suppressed");
+                metrics::increment_alert_suppressed("duplicate");
+                self.history.record(&payload, vec![], Some("duplicate"));
+            }
+            Admission::Held => {
+                debug!(event_type=?payload.event_type, service_id=?payload.service_id, "alert held for batch summary");
+                metrics::increment_alert_suppressed("held");
+                self.history.record(&payload, vec![], Some("held"));
+            }
+        }
+    }
+
+    /// Deliver the batch summary of held alerts once its window has elapsed.
+    pub async fn flush_held(&self) {
+        if self.paused.load(Ordering::Relaxed) { return; }
+        let summary = self.manager.lock().ok().and_then(|mut m| m.flush_due(Utc::now()));
+        if let Some(payload) = summary {
+            self.deliver_and_record(&payload).await;
+        }
+    }
+
+    async fn deliver_and_record(&self, payload: &AlertPayload) {
+        if self.dry_run {
+            let channels = self.channels_for(payload);
+            info!(?channels, event_type=?payload.event_type, service_id=?payload.service_id, message=%payload.message, "🧪 [dry-run] alert not delivered");
+            self.history.record(payload, vec![], Some("dry_run"));
+            return;
+        }
+        if !self.config().alerts.enable_notifications {
+            self.history.record(payload, vec![], Some("notifications_disabled"));
+            return;
+        }
+        let deliveries = self.deliver_all(payload).await;
+        self.history.record(payload, deliveries, None);
+    }
+
+    async fn deliver_all(&self, payload: &AlertPayload) -> Vec<Delivery> {
+        let mut deliveries = Vec::new();
+        for channel in self.channels_for(payload) {
+            let error = self.deliver(channel, payload).await.err().map(|e| e.to_string());
+            if let Some(e) = &error {
+                warn!(error=%e, channel, event_type=?payload.event_type, service_id=?payload.service_id, "alert delivery failed; dead-lettered");
+                metrics::increment_alert_delivery_failure(channel);
+                self.dead_letters.push(DeadLetter {
+                    id: uuid::Uuid::new_v4(),
+                    channel: channel.to_string(),
+                    payload: payload.clone(),
+                    error: e.clone(),
+                    attempts: DELIVERY_ATTEMPTS,
+                    failed_at: Utc::now(),
+                });
+            }
+            deliveries.push(Delivery { channel: channel.to_string(), delivered: error.is_none(), error, at: Utc::now() });
+        }
+        deliveries
+    }
+
+    /// Retry dead-lettered notifications (all when `ids` is empty); failures go back on the queue.
+    pub async fn redeliver(&self, ids: &[uuid::Uuid]) -> RedeliveryReport {
+        let mut report = RedeliveryReport::default();
+        for mut letter in self.dead_letters.take(ids) {
+            match self.deliver(&letter.channel, &letter.payload).await {
+                Ok(()) => report.redelivered += 1,
+                Err(e) => {
+                    report.failed += 1;
+                    letter.error = e.to_string();
+                    letter.attempts += DELIVERY_ATTEMPTS;
+                    letter.failed_at = Utc::now();
+                    self.dead_letters.push(letter);
+                }
+            }
+        }
+        report.remaining = self.dead_letters.list().len();
+        report
+    }
+
+    /// Deliver to one channel with retries, recording per-channel delivery metrics.
+    async fn deliver(&self, channel: &str, payload: &AlertPayload) -> Result<()> {
+        let start = Instant::now();
+        let mut last_error = None;
+        for attempt in 1..=DELIVERY_ATTEMPTS {
+            if attempt > 1 { metrics::increment_alert_delivery_retry(channel); }
+            match self.send_once(channel, payload).await {
+                Ok(()) => {
+                    metrics::record_alert_delivery(channel, true, start.elapsed().as_secs_f64());
+                    return Ok(());
+                }
+                Err(e) => last_error = Some(e),
+            }
+            if attempt < DELIVERY_ATTEMPTS {
+                tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+            }
+        }
+        metrics::record_alert_delivery(channel, false, start.elapsed().as_secs_f64());
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("{channel} delivery failed")))
+    }
+
+    async fn send_once(&self, channel: &str, payload: &AlertPayload) -> Result<()> {
+        let config = self.config();
+        match channel {
+            "webhook" => {
+                // The first matching route with webhook settings overrides the global ones
+                let route = self.matching_routes(&config, payload).into_iter().find(|r| r.webhook_url.is_some() || r.webhook_body.is_some());
+                let url = route.and_then(|r| r.webhook_url.as_deref())
+                    .or(config.alerts.webhook_url.as_deref())
+                    .ok_or_else(|| anyhow::anyhow!("webhook_url no longer configured"))?;
+                let body = route.and_then(|r| r.webhook_body.as_ref()).or(config.alerts.webhook_body.as_ref());
+                self.send_webhook(&render_url_template(url, payload), body.map(|b| render_json_template(b, payload)), payload).await
+            }
+            "slack" => {
+                let slack = config.alerts.slack.as_ref().ok_or_else(|| anyhow::anyhow!("slack no longer configured"))?;
+                self.send_slack(slack, payload).await
+            }
+            "email" => {
+                let email = config.alerts.email.as_ref().ok_or_else(|| anyhow::anyhow!("email no longer configured"))?;
+                let subject = render_template(&email.subject_template, payload);
+                let body = render_template(&email.body_template, payload);
+                send_email(email, &subject, body).await
+            }
+            "pagerduty" => {
+                let pd = config.alerts.pagerduty.as_ref().ok_or_else(|| anyhow::anyhow!("pagerduty no longer configured"))?;
+                let request = self.client.post(&pd.events_url).json(&pagerduty_event(&pd.routing_key, payload));
+                let resp = outbound::send(Destination::PagerDuty, request).await?;
+                if !resp.status().is_success() { anyhow::bail!("HTTP {}", resp.status()); }
+                Ok(())
+            }
+            other => anyhow::bail!("unknown alert channel {other}"),
+        }
+    }
+
+    /// POST `body` (a rendered `webhook_body` template) or, without one, the alert payload itself.
+    async fn send_webhook(&self, url: &str, body: Option<serde_json::Value>, payload: &AlertPayload) -> Result<()> {
+        let request = self.client.post(url);
+        let request = match &body {
+            Some(body) => request.json(body),
+            None => request.json(payload),
+        };
+        let resp = outbound::send(Destination::Webhook, request).await?;
+        if !resp.status().is_success() { anyhow::bail!("HTTP {}", resp.status()); }
+        Ok(())
+    }
+
+    async fn send_slack(&self, slack: &SlackConfig, payload: &AlertPayload) -> Result<()> {
+        let mut message = slack_message(payload, slack.dashboard_url.as_deref());
+        let config = self.config();
+        let routed = self.matching_routes(&config, payload).into_iter().find_map(|r| r.slack_channel.as_deref());
+        if let Some(channel) = routed.or_else(|| slack.channel_for(payload.service_id.as_deref())) {
+            message["channel"] = serde_json::json!(channel);
+        }
+        if let Some(token) = &slack.bot_token {
+            let request = self.client.post(SLACK_POST_MESSAGE_URL).bearer_auth(token).json(&message);
+            let resp: serde_json::Value = outbound::send(Destination::Slack, request).await?.json().await?;
+            if resp.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+                anyhow::bail!("slack API error: {}", resp.get("error").and_then(|v| v.as_str()).unwrap_or("unknown"));
+            }
+            Ok(())
+        } else if let Some(url) = &slack.webhook_url {
+            let resp = outbound::send(Destination::Slack, self.client.post(url).json(&message)).await?;
+            if !resp.status().is_success() { anyhow::bail!("HTTP {}", resp.status()); }
+            Ok(())
+        } else {
+            anyhow::bail!("slack configured without webhook_url or bot_token")
+        }
+    }
+}
+
+/// Send the daily health summary at `email.digest_hour_utc`, if configured.
+pub fn spawn_daily_digest(config: &Config, monitor: MonitorHandle) -> Option<tokio::task::JoinHandle<()>> {
+    let email = config.alerts.email.clone().filter(|_| config.alerts.enable_notifications)?;
+    let hour = email.digest_hour_utc?.min(23);
+    info!(hour, "📬 Daily email digest scheduled");
+    Some(tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(until_next_hour(Utc::now(), hour)).await;
+            let services = monitor.get_all_services().await;
+            let (subject, body) = digest_message(&services, Utc::now());
+            let start = Instant::now();
+            match send_email(&email, &subject, body).await {
+                Ok(()) => metrics::record_alert_delivery("email", true, start.elapsed().as_secs_f64()),
+                Err(e) => {
+                    warn!(error=%e, "daily digest delivery failed");
+                    metrics::record_alert_delivery("email", false, start.elapsed().as_secs_f64());
+                    metrics::increment_alert_delivery_failure("email");
+                }
+            }
+        }
+    }))
+}
+
+fn until_next_hour(now: DateTime<Utc>, hour: u32) -> Duration {
+    let today = now.date_naive().and_hms_opt(hour, 0, 0).expect("valid hour").and_utc();
+    let next = if today > now { today } else { today + chrono::Duration::days(1) };
+    (next - now).to_std().unwrap_or(Duration::from_secs(60))
+}
+
+fn digest_message(services: &[crate::models::ServiceStatus], now: DateTime<Utc>) -> (String, String) {
+    let healthy = services.iter().filter(|s| s.status == HealthStatus::Healthy).count();
+    let subject = format!("[FKS] Daily health summary: {}/{} healthy", healthy, services.len());
+    let mut body = format!("FKS health summary for {}\n\n", now.format("%Y-%m-%d"));
+    let mut sorted: Vec<_> = services.iter().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+    for s in sorted {
+        let latency = s.response_time_ms.map(|ms| format!("{ms}ms")).unwrap_or_else(|| "-".into());
+        body.push_str(&format!("{:<10} {:<30} {:>8}{}\n", format!("{:?}", s.status), s.name, latency, if s.critical { "  (critical)" } else { "" }));
+    }
+    (subject, body)
+}
+
+/// Substitute `{placeholder}` fields of an alert into a subject/body template.
+pub fn render_template(template: &str, payload: &AlertPayload) -> String {
+    render_with(template, payload, str::to_string)
+}
+
+/// Like [`render_template`], percent-encoding each substituted value so labels can go in query strings.
+pub fn render_url_template(template: &str, payload: &AlertPayload) -> String {
+    render_with(template, payload, percent_encode)
+}
+
+/// Render every string in a JSON template (objects and arrays are walked recursively).
+pub fn render_json_template(template: &serde_json::Value, payload: &AlertPayload) -> serde_json::Value {
+    match template {
+        serde_json::Value::String(s) => serde_json::Value::String(render_template(s, payload)),
+        serde_json::Value::Array(items) => items.iter().map(|v| render_json_template(v, payload)).collect(),
+        serde_json::Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), render_json_template(v, payload))).collect(),
+        other => other.clone(),
+    }
+}
+
+/// Replace `{placeholder}`s in one pass; unknown placeholders are left as written.
+fn render_with(template: &str, payload: &AlertPayload, escape: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').and_then(|end| placeholder(&after[..end], payload).map(|value| (end, value))) {
+            Some((end, value)) => {
+                out.push_str(&escape(&value));
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn placeholder(key: &str, payload: &AlertPayload) -> Option<String> {
+    let value = match key {
+        "event_type" => format!("{:?}", payload.event_type),
+        "severity" => payload.severity.as_str().to_string(),
+        "service_id" => payload.service_id.clone().unwrap_or_else(|| "system".into()),
+        "service_name" => payload.service_name.clone().or_else(|| payload.service_id.clone()).unwrap_or_else(|| "system".into()),
+        "old_status" => payload.old_status.clone().unwrap_or_else(|| "?".into()),
+        "new_status" => payload.new_status.clone().unwrap_or_else(|| "?".into()),
+        "message" => payload.message.clone(),
+        "timestamp" => payload.timestamp.to_rfc3339(),
+        // Missing labels render empty so one template serves services with and without them
+        _ => return key.strip_prefix("label.").map(|label| payload.labels.get(label).cloned().unwrap_or_default()),
+    };
+    Some(value)
+}
+
+fn percent_encode(value: &str) -> String {
+    value.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        _ => format!("%{b:02X}"),
+    }).collect()
+}
+
+async fn send_email(email: &EmailConfig, subject: &str, body: String) -> Result<()> {
+    use lettre::message::{header::ContentType, Mailbox};
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+    let mut builder = Message::builder()
+        .from(email.from.parse::<Mailbox>()?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in &email.to {
+        builder = builder.to(to.parse::<Mailbox>()?);
+    }
+    let message = builder.body(body)?;
+
+    let mut transport = match email.tls {
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&email.smtp_host)?,
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&email.smtp_host)?,
+        SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&email.smtp_host),
+    };
+    if let Some(port) = email.smtp_port { transport = transport.port(port); }
+    if let (Some(user), Some(pass)) = (&email.username, &email.password) {
+        transport = transport.credentials(Credentials::new(user.clone(), pass.clone()));
+    }
+    transport.build().send(message).await?;
+    Ok(())
+}
+
+/// Format a ServiceDown / ServiceUp alert as a Slack Block Kit message.
+fn payload_kind(payload: &AlertPayload) -> Option<&str> {
+    payload.data.as_ref().and_then(|d| d.get("kind")).and_then(|k| k.as_str())
+}
+
+/// PagerDuty Events API v2 body; a resolved escalation resolves the incident opened for the service.
+pub fn pagerduty_event(routing_key: &str, payload: &AlertPayload) -> serde_json::Value {
+    let dedup_key = format!("fks-{}", payload.service_id.as_deref().unwrap_or("system"));
+    let resolved = payload.data.as_ref().and_then(|d| d.get("state")).and_then(|s| s.as_str()) == Some("resolved");
+    if resolved {
+        return serde_json::json!({"routing_key": routing_key, "event_action": "resolve", "dedup_key": dedup_key});
+    }
+    let severity = payload.severity.as_str();
+    serde_json::json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": dedup_key,
+        "payload": {
+            "summary": payload.message,
+            "source": payload.service_name.as_deref().or(payload.service_id.as_deref()).unwrap_or("fks_master"),
+            "severity": severity,
+            "timestamp": payload.timestamp.to_rfc3339(),
+            "custom_details": payload.data,
+        }
+    })
+}
+
+pub fn slack_message(payload: &AlertPayload, dashboard_url: Option<&str>) -> serde_json::Value {
+    use serde_json::json;
+    let (emoji, headline) = match payload.event_type {
+        EventType::ServiceDown => (":red_circle:", "Service down"),
+        EventType::ServiceUp => (":large_green_circle:", "Service recovered"),
+        _ => (":warning:", "Service alert"),
+    };
+    let name = payload.service_name.as_deref().or(payload.service_id.as_deref()).unwrap_or("system");
+    let mut fields = vec![
+        json!({"type": "mrkdwn", "text": format!("*Service:*\n{name}")}),
+        json!({"type": "mrkdwn", "text": format!("*Status:*\n{} → {}", payload.old_status.as_deref().unwrap_or("?"), payload.new_status.as_deref().unwrap_or("?"))}),
+    ];
+    if let Some(err) = payload.data.as_ref().and_then(|d| d.get("error")).and_then(|v| v.as_str()) {
+        fields.push(json!({"type": "mrkdwn", "text": format!("*Error:*\n```{err}```")}));
+    }
+    let mut blocks = vec![
+        json!({"type": "header", "text": {"type": "plain_text", "text": format!("{headline}: {name}")}}),
+        json!({"type": "section", "fields": fields}),
+        json!({"type": "context", "elements": [{"type": "mrkdwn", "text": payload.timestamp.to_rfc3339()}]}),
+    ];
+    if let Some(url) = dashboard_url {
+        blocks.push(json!({"type": "actions", "elements": [{"type": "button", "text": {"type": "plain_text", "text": "Open dashboard"}, "url": url}]}));
+    }
+    json!({
+        "text": format!("{emoji} {headline}: {name} — {}", payload.message),
+        "blocks": blocks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: EventType, data: Option<serde_json::Value>) -> MonitorEvent {
+        MonitorEvent { event_type, service_id: Some("fks_api".into()), message: "msg".into(), timestamp: Utc::now(), data, metadata: Default::default() }
+    }
+
+    #[test]
+    fn payload_carries_status_transition() {
+        let cfg = Config::default();
+        let ev = event(EventType::ServiceDown, Some(serde_json::json!({"error": "boom", "previous_status": "Healthy", "status": "Unhealthy"})));
+        let p = AlertPayload::from_event(&ev, &cfg).expect("alertable");
+        assert_eq!(p.service_name.as_deref(), Some("FKS API Service"));
+        assert_eq!(p.old_status.as_deref(), Some("Healthy"));
+        assert_eq!(p.new_status.as_deref(), Some("Unhealthy"));
+        assert!(AlertPayload::from_event(&event(EventType::MetricsUpdate, None), &cfg).is_none());
+    }
+
+    #[tokio::test]
+    async fn webhook_receives_payload() {
+        use axum::{routing::post, Json, Router};
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<AlertPayload>(1);
+        let app = Router::new().route("/hook", post(move |Json(p): Json<AlertPayload>| {
+            let tx = tx.clone();
+            async move { let _ = tx.send(p).await; }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap(); });
+
+        let mut cfg = Config::default();
+        cfg.alerts.webhook_url = Some(format!("http://{addr}/hook"));
+        let dispatcher = AlertDispatcher::new(cfg);
+        dispatcher.dispatch(&event(EventType::ServiceUp, Some(serde_json::json!({"previous_status": "Unhealthy", "status": "Healthy"})))).await;
+        let got = rx.recv().await.expect("payload delivered");
+        assert_eq!(got.event_type, EventType::ServiceUp);
+        assert_eq!(got.new_status.as_deref(), Some("Healthy"));
+    }
+
+    #[tokio::test]
+    async fn route_webhook_templates_use_service_labels() {
+        use axum::{extract::{Path, RawQuery}, routing::post, Json, Router};
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<(String, Option<String>, serde_json::Value)>(1);
+        let app = Router::new().route("/hook/{team}", post(move |Path(team): Path<String>, RawQuery(query): RawQuery, Json(body): Json<serde_json::Value>| {
+            let tx = tx.clone();
+            async move { let _ = tx.send((team, query, body)).await; }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap(); });
+
+        let mut cfg = Config::default();
+        let api = cfg.services.iter_mut().find(|s| s.id == "fks_api").unwrap();
+        api.labels = [("team".to_string(), "data".to_string()), ("environment".to_string(), "prod eu".to_string())].into();
+        #[derive(serde::Deserialize)]
+        struct Routes { routes: Vec<AlertRoute> }
+        cfg.alerts.routes = toml::from_str::<Routes>(&format!(r#"
+            [[routes]]
+            channels = ["webhook"]
+            webhook_url = "http://{addr}/hook/{{label.team}}?env={{label.environment}}&severity={{severity}}&owner={{label.owner}}"
+            webhook_body = {{ team = "{{label.team}}", text = "{{service_name}}: {{new_status}}", tags = ["{{label.environment}}", "{{unknown}}"], count = 1 }}
+        "#)).unwrap().routes;
+        let dispatcher = AlertDispatcher::new(cfg);
+        dispatcher.dispatch(&event(EventType::ServiceDown, Some(serde_json::json!({"status": "Unhealthy"})))).await;
+
+        let (team, query, body) = rx.recv().await.expect("webhook delivered");
+        assert_eq!(team, "data");
+        assert_eq!(query.as_deref(), Some("env=prod%20eu&severity=critical&owner="));
+        assert_eq!(body, serde_json::json!({"team": "data", "text": "FKS API Service: Unhealthy", "tags": ["prod eu", "{unknown}"], "count": 1}));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_dead_lettered_and_redelivered() {
+        use axum::{routing::post, Router};
+        let dir = tempfile::tempdir().unwrap();
+        let dlq_path = dir.path().join("dlq.json");
+        // Reserve a port, then close it so the first delivery fails
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let mut cfg = Config::default();
+        cfg.alerts.webhook_url = Some(format!("http://{addr}/hook"));
+        cfg.alerts.dead_letter_path = Some(dlq_path.to_string_lossy().into_owned());
+        let dispatcher = AlertDispatcher::new(cfg.clone());
+        dispatcher.dispatch(&event(EventType::ServiceDown, None)).await;
+        assert_eq!(dispatcher.dead_letters().len(), 1);
+        // Persisted queue survives a dispatcher restart
+        let reopened = AlertDispatcher::new(cfg);
+        assert_eq!(reopened.dead_letters().len(), 1);
+
+        let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+        tokio::spawn(async move { axum::serve(listener, Router::new().route("/hook", post(|| async {}))).await.unwrap(); });
+        let report = reopened.redeliver(&[]).await;
+        assert_eq!((report.redelivered, report.failed, report.remaining), (1, 0, 0));
+    }
+
+    #[test]
+    fn email_template_and_critical_routing() {
+        let mut cfg = Config::default();
+        cfg.alerts.email = Some(EmailConfig {
+            smtp_host: "smtp.example.com".into(), smtp_port: None, tls: SmtpTls::Starttls, username: None, password: None,
+            from: "fks@example.com".into(), to: vec!["ops@example.com".into()],
+            subject_template: "[FKS] {event_type}: {service_name}".into(), body_template: "{old_status} -> {new_status}".into(),
+            digest_hour_utc: None,
+        });
+        let ev = event(EventType::ServiceDown, Some(serde_json::json!({"previous_status": "Healthy", "status": "Unhealthy"})));
+        let p = AlertPayload::from_event(&ev, &cfg).unwrap();
+        assert_eq!(render_template("[FKS] {event_type}: {service_name}", &p), "[FKS] ServiceDown: FKS API Service");
+        assert_eq!(render_template("{old_status} -> {new_status}", &p), "Healthy -> Unhealthy");
+
+        let dispatcher = AlertDispatcher::new(cfg);
+        assert!(dispatcher.channels_for(&p).contains(&"email"));
+        // fks_worker is not critical -> no email
+        let mut non_critical = p.clone();
+        non_critical.service_id = Some("fks_worker".into());
+        assert!(!dispatcher.channels_for(&non_critical).contains(&"email"));
+    }
+
+    #[test]
+    fn escalations_route_only_to_policy_channels() {
+        let mut cfg = Config::default();
+        cfg.alerts.webhook_url = Some("http://hooks.local/alert".into());
+        cfg.alerts.pagerduty = Some(crate::config::PagerDutyConfig { routing_key: "rk".into(), events_url: "http://pd.local".into() });
+        let dispatcher = AlertDispatcher::new(cfg.clone());
+        let data = serde_json::json!({"kind": "escalation", "state": "level_1", "severity": "critical", "channels": ["pagerduty", "email"]});
+        let p = AlertPayload::from_event(&event(EventType::SystemAlert, Some(data)), &cfg).unwrap();
+        // email is requested but not configured
+        assert_eq!(dispatcher.channels_for(&p), vec!["pagerduty"]);
+        let down = AlertPayload::from_event(&event(EventType::ServiceDown, None), &cfg).unwrap();
+        assert!(!dispatcher.channels_for(&down).contains(&"pagerduty"));
+
+        let trigger = pagerduty_event("rk", &p);
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["dedup_key"], "fks-fks_api");
+        assert_eq!(trigger["payload"]["severity"], "critical");
+        let mut resolved = p.clone();
+        resolved.data = Some(serde_json::json!({"kind": "escalation", "state": "resolved"}));
+        assert_eq!(pagerduty_event("rk", &resolved)["event_action"], "resolve");
+    }
+
+    #[test]
+    fn severity_routes_select_channels() {
+        let mut cfg = Config::default();
+        cfg.alerts.webhook_url = Some("http://hooks.local/alert".into());
+        cfg.alerts.slack = Some(SlackConfig { channel: Some("#ops".into()), ..Default::default() });
+        cfg.alerts.pagerduty = Some(crate::config::PagerDutyConfig { routing_key: "rk".into(), events_url: "http://pd.local".into() });
+        cfg.services.iter_mut().find(|s| s.id == "fks_worker").unwrap().tags = vec!["batch".into()];
+        #[derive(serde::Deserialize)]
+        struct Routes { routes: Vec<AlertRoute> }
+        cfg.alerts.routes = toml::from_str::<Routes>(r##"
+            [[routes]]
+            severity = ["critical"]
+            channels = ["pagerduty", "slack"]
+            continue = true
+            [[routes]]
+            severity = ["critical", "warning"]
+            channels = ["webhook"]
+            [[routes]]
+            tags = ["batch"]
+            channels = ["slack"]
+            slack_channel = "#ops-quiet"
+        "##).unwrap().routes;
+        let dispatcher = AlertDispatcher::new(cfg.clone());
+
+        let down = AlertPayload::from_event(&event(EventType::ServiceDown, None), &cfg).unwrap();
+        assert_eq!(down.severity, Severity::Critical, "fks_api is critical");
+        assert_eq!(dispatcher.channels_for(&down), vec!["webhook", "slack", "pagerduty"]);
+        assert_eq!(pagerduty_event("rk", &down)["payload"]["severity"], "critical");
+
+        let mut worker = event(EventType::ServiceUp, None);
+        worker.service_id = Some("fks_worker".into());
+        let up = AlertPayload::from_event(&worker, &cfg).unwrap();
+        assert_eq!(up.severity, Severity::Info);
+        assert_eq!(dispatcher.channels_for(&up), vec!["slack"]);
+        assert_eq!(dispatcher.matching_routes(&dispatcher.config(), &up)[0].slack_channel.as_deref(), Some("#ops-quiet"));
+
+        let explicit = event(EventType::SystemAlert, Some(serde_json::json!({"severity": "info"})));
+        let info = AlertPayload::from_event(&explicit, &cfg).unwrap();
+        assert_eq!(info.severity, Severity::Info);
+        assert_eq!(dispatcher.channels_for(&info), vec!["webhook"], "unrouted payloads use the default channels");
+    }
+
+    #[test]
+    fn digest_waits_until_next_configured_hour() {
+        let now = chrono::DateTime::parse_from_rfc3339("2025-01-01T10:30:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(until_next_hour(now, 11), Duration::from_secs(30 * 60));
+        assert_eq!(until_next_hour(now, 10), Duration::from_secs(23 * 3600 + 30 * 60));
+    }
+
+    #[test]
+    fn slack_message_blocks_and_channel_override() {
+        let cfg = Config::default();
+        let ev = event(EventType::ServiceDown, Some(serde_json::json!({"error": "connection refused", "previous_status": "Healthy", "status": "Unhealthy"})));
+        let p = AlertPayload::from_event(&ev, &cfg).unwrap();
+        let msg = slack_message(&p, Some("http://fks_master:9090"));
+        let blocks = msg["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["type"], "header");
+        assert!(blocks[1]["fields"].to_string().contains("connection refused"));
+        assert_eq!(blocks.last().unwrap()["elements"][0]["url"], "http://fks_master:9090");
+
+        let slack = SlackConfig {
+            channel: Some("#ops".into()),
+            channel_overrides: [("fks_api".to_string(), "#api-oncall".to_string())].into_iter().collect(),
+            ..Default::default()
+        };
+        assert_eq!(slack.channel_for(Some("fks_api")), Some("#api-oncall"));
+        assert_eq!(slack.channel_for(Some("fks_web")), Some("#ops"));
+    }
+}
@@ -35,11 +35,27 @@ fn roles_authorized(claims: &Claims) -> bool {
 }
 
 pub fn authorize_jwt(token: Option<&str>) -> bool {
-    let secret = match std::env::var("FKS_WS_JWT_SECRET") { Ok(s) => s, Err(_) => return true }; // secret unset -> allow all
+    let secret = match crate::secrets::env_var("FKS_WS_JWT_SECRET") {
+        Ok(Some(s)) => s,
+        Ok(None) => return true, // secret unset -> allow all
+        Err(e) => { tracing::error!(error = ?e, "JWT secret unavailable; rejecting token"); return false }
+    };
     let token = match token { Some(t) => t, None => return false }; // require token if secret set
     if let Some(claims) = decode_jwt(token, &secret) { roles_authorized(&claims) } else { false }
 }
 
+/// Whether bearer tokens are checked at all, i.e. `FKS_WS_JWT_SECRET` is set (an unresolvable
+/// value counts as set, so tokens are rejected rather than waved through).
+pub fn jwt_configured() -> bool {
+    !matches!(crate::secrets::env_var("FKS_WS_JWT_SECRET"), Ok(None))
+}
+
+/// Subject (`sub`) of a valid token, used to label WebSocket clients; None when no secret is configured.
+pub fn jwt_subject(token: &str) -> Option<String> {
+    let secret = crate::secrets::env_var("FKS_WS_JWT_SECRET").ok().flatten()?;
+    decode_jwt(token, &secret).map(|c| c.sub)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,6 +66,7 @@ mod tests {
     }
 
     #[test]
+    #[serial_test::serial(jwt_env)]
     fn jwt_authorization_flow() {
         std::env::set_var("FKS_WS_JWT_SECRET", "testsecret");
         std::env::set_var("FKS_WS_JWT_ALLOWED_ROLES", "admin,orchestrate");
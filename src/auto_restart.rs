@@ -0,0 +1,103 @@
+use crate::models::AutoRestartPolicy;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// What a failed check means for a service with an `auto_restart` policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// Fewer consecutive failures than the threshold
+    Wait,
+    /// Threshold reached, but automation is frozen or restarts are suppressed; nothing is counted
+    Held,
+    /// Restart now, as the `attempt`-th auto-restart within the hour
+    Restart { consecutive_failures: u32, attempt: u32 },
+    /// The hour's restarts did not help; raised once until the service recovers or an attempt frees up
+    GiveUp { attempts: u32 },
+    /// Still given up, already reported
+    GivenUp,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    consecutive_failures: u32,
+    attempts: VecDeque<DateTime<Utc>>,
+    gave_up: bool,
+}
+
+/// Consecutive failed checks and recent auto-restarts per service.
+#[derive(Debug, Clone, Default)]
+pub struct AutoRestarts {
+    services: Arc<Mutex<HashMap<String, State>>>,
+}
+
+impl AutoRestarts {
+    /// A passing check starts the count over.
+    pub fn success(&self, service_id: &str) {
+        if let Some(state) = self.services.lock().unwrap().get_mut(service_id) {
+            state.consecutive_failures = 0;
+            state.gave_up = false;
+        }
+    }
+
+    /// Count a failed check at `now`. `held` is whether automatic actions are off right now.
+    pub fn failure(&self, service_id: &str, policy: &AutoRestartPolicy, now: DateTime<Utc>, held: bool) -> Decision {
+        let mut services = self.services.lock().unwrap();
+        let state = services.entry(service_id.to_string()).or_default();
+        state.consecutive_failures += 1;
+        while state.attempts.front().is_some_and(|at| now.signed_duration_since(*at) >= Duration::hours(1)) {
+            state.attempts.pop_front();
+        }
+        if state.consecutive_failures < policy.failure_threshold.max(1) {
+            return Decision::Wait;
+        }
+        if held {
+            return Decision::Held;
+        }
+        if state.attempts.len() >= policy.max_attempts_per_hour as usize {
+            return if std::mem::replace(&mut state.gave_up, true) {
+                Decision::GivenUp
+            } else {
+                Decision::GiveUp { attempts: state.attempts.len() as u32 }
+            };
+        }
+        state.gave_up = false;
+        state.attempts.push_back(now);
+        let consecutive_failures = std::mem::take(&mut state.consecutive_failures);
+        Decision::Restart { consecutive_failures, attempt: state.attempts.len() as u32 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> AutoRestartPolicy {
+        AutoRestartPolicy { enabled: true, failure_threshold: 2, max_attempts_per_hour: 2 }
+    }
+
+    #[test]
+    fn restarts_after_the_threshold_and_gives_up_after_the_hourly_budget() {
+        let restarts = AutoRestarts::default();
+        let start = Utc::now();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+        let policy = policy();
+
+        assert_eq!(restarts.failure("api", &policy, at(0), false), Decision::Wait);
+        assert_eq!(restarts.failure("api", &policy, at(1), false), Decision::Restart { consecutive_failures: 2, attempt: 1 });
+        // A passing check in between starts the count over
+        assert_eq!(restarts.failure("api", &policy, at(2), false), Decision::Wait);
+        restarts.success("api");
+        assert_eq!(restarts.failure("api", &policy, at(3), false), Decision::Wait);
+        // Held restarts are not counted against the hour
+        assert_eq!(restarts.failure("api", &policy, at(4), true), Decision::Held);
+        assert_eq!(restarts.failure("api", &policy, at(5), false), Decision::Restart { consecutive_failures: 3, attempt: 2 });
+        restarts.failure("api", &policy, at(6), false);
+        assert_eq!(restarts.failure("api", &policy, at(7), false), Decision::GiveUp { attempts: 2 });
+        assert_eq!(restarts.failure("api", &policy, at(8), false), Decision::GivenUp);
+        // Other services keep their own count
+        assert_eq!(restarts.failure("db", &policy, at(8), false), Decision::Wait);
+        // An hour after the first restart one attempt is free again
+        assert_eq!(restarts.failure("api", &policy, at(61), false), Decision::Restart { consecutive_failures: 4, attempt: 2 });
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// Set to `1`/`true` to start with automation frozen (same as `--freeze-automation`).
+pub const FREEZE_ENV: &str = "FKS_AUTOMATION_FREEZE";
+
+/// An active automation freeze: auto-restarts, playbooks, scheduled actions and deploy webhooks are
+/// skipped while monitoring and alerting keep running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Freeze {
+    pub since: DateTime<Utc>,
+    /// Lifts itself at this time; `None` = until resumed
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub frozen_by: Option<String>,
+}
+
+impl Freeze {
+    fn active_at(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_none_or(|until| now < until)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FreezeRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Lift the freeze automatically after this many seconds
+    #[serde(default)]
+    pub duration_seconds: Option<u64>,
+    #[serde(default)]
+    pub frozen_by: Option<String>,
+}
+
+/// Global safety switch shared by everything that acts on services without a human asking.
+#[derive(Debug, Clone, Default)]
+pub struct AutomationSwitch {
+    freeze: Arc<RwLock<Option<Freeze>>>,
+}
+
+impl AutomationSwitch {
+    /// Freeze (or re-freeze with new details). Returns the freeze now in effect.
+    pub fn freeze(&self, req: FreezeRequest, now: DateTime<Utc>) -> Freeze {
+        let freeze = Freeze {
+            since: now,
+            until: req.duration_seconds.map(|secs| now + Duration::seconds(secs as i64)),
+            reason: req.reason.filter(|r| !r.trim().is_empty()),
+            frozen_by: req.frozen_by,
+        };
+        if let Ok(mut current) = self.freeze.write() {
+            *current = Some(freeze.clone());
+        }
+        freeze
+    }
+
+    /// Lift the freeze. Returns the one that was active, if any.
+    pub fn resume(&self, now: DateTime<Utc>) -> Option<Freeze> {
+        let previous = self.freeze.write().ok()?.take();
+        previous.filter(|f| f.active_at(now))
+    }
+
+    /// The freeze in effect at `now`; an expired one counts as lifted.
+    pub fn current(&self, now: DateTime<Utc>) -> Option<Freeze> {
+        self.freeze.read().ok()?.clone().filter(|f| f.active_at(now))
+    }
+
+    pub fn is_frozen(&self, now: DateTime<Utc>) -> bool {
+        self.current(now).is_some()
+    }
+
+    /// Carry a freeze over from a previous process (see `MonitorSnapshot`).
+    pub fn restore(&self, freeze: Option<Freeze>) {
+        if let (Some(freeze), Ok(mut current)) = (freeze, self.freeze.write()) {
+            *current = Some(freeze);
+        }
+    }
+}
+
+/// Whether `FKS_AUTOMATION_FREEZE` asks to start frozen.
+pub fn freeze_requested_by_env() -> bool {
+    matches!(std::env::var(FREEZE_ENV).as_deref(), Ok("1") | Ok("true"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freeze_resume_and_expiry() {
+        let switch = AutomationSwitch::default();
+        let t0 = Utc::now();
+        assert!(!switch.is_frozen(t0));
+
+        let freeze = switch.freeze(FreezeRequest { reason: Some("db failover".into()), duration_seconds: Some(60), frozen_by: None }, t0);
+        assert_eq!(freeze.until, Some(t0 + Duration::seconds(60)));
+        assert!(switch.is_frozen(t0 + Duration::seconds(59)));
+        assert!(!switch.is_frozen(t0 + Duration::seconds(60)), "expired freezes lift themselves");
+
+        switch.freeze(FreezeRequest::default(), t0);
+        assert!(switch.is_frozen(t0 + Duration::days(30)), "no duration = until resumed");
+        assert!(switch.resume(t0).is_some());
+        assert!(!switch.is_frozen(t0));
+        assert!(switch.resume(t0).is_none());
+    }
+}
@@ -0,0 +1,289 @@
+use anyhow::{bail, Context, Result};
+use flate2::write::GzEncoder;
+use std::io::Write;
+use std::path::Path;
+
+/// Size of tar headers and the unit entries are padded to
+const BLOCK: usize = 512;
+/// Longest name that fits a plain ustar header; longer ones get a GNU long-name entry first
+const NAME_LEN: usize = 100;
+
+/// Gzipped tar of the build context `dir`, as `docker build` sends it: everything except what
+/// `.dockerignore` excludes, plus `dockerfile` (relative to `dir`) in any case. With `target`, the
+/// Dockerfile is cut after that stage, so the daemon's builder stops there.
+pub fn archive(dir: &Path, dockerfile: &str, target: Option<&str>) -> Result<Vec<u8>> {
+    let ignore = match std::fs::read_to_string(dir.join(".dockerignore")) {
+        Ok(text) => DockerIgnore::parse(&text),
+        Err(_) => DockerIgnore::default(),
+    };
+    let dockerfile = dockerfile.trim_start_matches("./");
+    let mut tar = Tar::default();
+    walk(dir, dir, &ignore, dockerfile, &mut tar)?;
+    let content = std::fs::read_to_string(dir.join(dockerfile)).with_context(|| format!("reading {dockerfile}"))?;
+    let content = match target {
+        Some(stage) => truncate_to_stage(&content, stage)?,
+        None => content,
+    };
+    tar.file(dockerfile, content.as_bytes(), 0o644, 0)?;
+    let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    gz.write_all(&tar.finish())?;
+    Ok(gz.finish()?)
+}
+
+fn walk(root: &Path, dir: &Path, ignore: &DockerIgnore, dockerfile: &str, tar: &mut Tar) -> Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))?
+        .collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let relative = path.strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+        // The Dockerfile is added last, possibly cut to a stage
+        if relative == dockerfile { continue; }
+        let meta = std::fs::symlink_metadata(&path)?;
+        let excluded = ignore.excludes(&relative);
+        let mtime = meta.modified().ok().and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map_or(0, |d| d.as_secs());
+        if meta.is_dir() {
+            if excluded && !ignore.may_include_below(&relative) { continue; }
+            if !excluded { tar.entry(&format!("{relative}/"), b'5', &[], mode(&meta, 0o755), mtime, "")?; }
+            walk(root, &path, ignore, dockerfile, tar)?;
+        } else if excluded {
+            continue;
+        } else if meta.file_type().is_symlink() {
+            let target = std::fs::read_link(&path)?;
+            tar.entry(&relative, b'2', &[], mode(&meta, 0o777), mtime, &target.to_string_lossy())?;
+        } else if meta.is_file() {
+            let content = std::fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+            tar.file(&relative, &content, mode(&meta, 0o644), mtime)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn mode(meta: &std::fs::Metadata, _default: u32) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn mode(_meta: &std::fs::Metadata, default: u32) -> u32 {
+    default
+}
+
+/// `dockerfile` up to the end of stage `target` (its `FROM … AS <target>` and what follows until the
+/// next `FROM`). The classic builder runs stages in order, so this is where it stops.
+pub fn truncate_to_stage(dockerfile: &str, target: &str) -> Result<String> {
+    let mut out = String::new();
+    let mut found = false;
+    for line in dockerfile.lines() {
+        if let Some(name) = stage(line) {
+            if found { return Ok(out); }
+            found = name.is_some_and(|n| n.eq_ignore_ascii_case(target));
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if !found { bail!("the Dockerfile has no stage `{target}`"); }
+    Ok(out)
+}
+
+/// For a `FROM` line, the name of the stage it starts (`FROM image AS name`), if it has one.
+fn stage(line: &str) -> Option<Option<&str>> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if !words.first()?.eq_ignore_ascii_case("FROM") { return None; }
+    Some(words.windows(2).find(|w| w[0].eq_ignore_ascii_case("AS")).map(|w| w[1]))
+}
+
+/// Patterns of a `.dockerignore`; the last one matching a path decides, and `!` re-includes.
+#[derive(Debug, Default)]
+struct DockerIgnore {
+    patterns: Vec<(bool, Vec<String>)>,
+}
+
+impl DockerIgnore {
+    fn parse(text: &str) -> Self {
+        let patterns = text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| match l.strip_prefix('!') {
+                Some(rest) => (false, segments(rest)),
+                None => (true, segments(l)),
+            })
+            .filter(|(_, p)| !p.is_empty())
+            .collect();
+        Self { patterns }
+    }
+
+    fn excludes(&self, path: &str) -> bool {
+        let path: Vec<&str> = path.split('/').collect();
+        let mut excluded = false;
+        for (exclude, pattern) in &self.patterns {
+            // A pattern that matches a directory covers everything in it
+            if (1..=path.len()).any(|n| glob(pattern, &path[..n])) {
+                excluded = *exclude;
+            }
+        }
+        excluded
+    }
+
+    /// Whether an exception could re-include something inside the excluded directory `dir`.
+    fn may_include_below(&self, dir: &str) -> bool {
+        let depth = dir.split('/').count();
+        self.patterns.iter().any(|(exclude, pattern)| !exclude && (pattern.len() > depth || pattern.iter().any(|s| s == "**")))
+    }
+}
+
+fn segments(pattern: &str) -> Vec<String> {
+    pattern.trim_start_matches('/').split('/').filter(|s| !s.is_empty() && *s != ".").map(str::to_string).collect()
+}
+
+/// Match path segments against pattern segments, where `**` spans any number of segments.
+fn glob(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| glob(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, tail)| wildcard(first.as_bytes(), segment.as_bytes()) && glob(rest, tail)),
+    }
+}
+
+/// `*` and `?` within one path segment.
+fn wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|skip| wildcard(rest, &text[skip..])),
+        Some((b'?', rest)) => !text.is_empty() && wildcard(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && wildcard(rest, &text[1..]),
+    }
+}
+
+/// Minimal ustar writer with GNU long names.
+#[derive(Default)]
+struct Tar {
+    out: Vec<u8>,
+}
+
+impl Tar {
+    fn file(&mut self, name: &str, content: &[u8], mode: u32, mtime: u64) -> Result<()> {
+        self.entry(name, b'0', content, mode, mtime, "")
+    }
+
+    fn entry(&mut self, name: &str, kind: u8, content: &[u8], mode: u32, mtime: u64, link: &str) -> Result<()> {
+        if link.len() > NAME_LEN { self.long_name(b'K', link)?; }
+        if name.len() > NAME_LEN { self.long_name(b'L', name)?; }
+        self.header(name, kind, content.len() as u64, mode, mtime, link)?;
+        self.data(content);
+        Ok(())
+    }
+
+    fn long_name(&mut self, kind: u8, name: &str) -> Result<()> {
+        let mut data = name.as_bytes().to_vec();
+        data.push(0);
+        self.header("././@LongLink", kind, data.len() as u64, 0o644, 0, "")?;
+        self.data(&data);
+        Ok(())
+    }
+
+    fn header(&mut self, name: &str, kind: u8, size: u64, mode: u32, mtime: u64, link: &str) -> Result<()> {
+        if size >= 0o77777777777 { bail!("{name} is too large for the build context"); }
+        let mut header = [0u8; BLOCK];
+        header[..truncated(name).len()].copy_from_slice(truncated(name));
+        octal(&mut header[100..108], mode as u64);
+        octal(&mut header[108..116], 0);
+        octal(&mut header[116..124], 0);
+        octal(&mut header[124..136], size);
+        octal(&mut header[136..148], mtime);
+        header[148..156].fill(b' ');
+        header[156] = kind;
+        header[157..157 + truncated(link).len()].copy_from_slice(truncated(link));
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        let checksum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+        self.out.extend_from_slice(&header);
+        Ok(())
+    }
+
+    fn data(&mut self, data: &[u8]) {
+        self.out.extend_from_slice(data);
+        let padding = (BLOCK - data.len() % BLOCK) % BLOCK;
+        self.out.resize(self.out.len() + padding, 0);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.out.resize(self.out.len() + 2 * BLOCK, 0);
+        self.out
+    }
+}
+
+/// The part of a name that fits a header field; longer names are carried by a long-name entry.
+fn truncated(name: &str) -> &[u8] {
+    &name.as_bytes()[..name.len().min(NAME_LEN)]
+}
+
+/// Zero-padded octal number filling `field` but its last byte, which stays NUL.
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{value:0width$o}", width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// Names of the entries in a gzipped tar, resolving long names.
+    fn names(archive: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut tar = Vec::new();
+        flate2::read::GzDecoder::new(archive).read_to_end(&mut tar).unwrap();
+        let mut out = Vec::new();
+        let (mut offset, mut long) = (0, None);
+        while offset + BLOCK <= tar.len() && tar[offset] != 0 {
+            let header = &tar[offset..offset + BLOCK];
+            let field = |r: std::ops::Range<usize>| String::from_utf8_lossy(&header[r]).trim_end_matches('\0').to_string();
+            let size = u64::from_str_radix(field(124..135).trim(), 8).unwrap() as usize;
+            let data = tar[offset + BLOCK..offset + BLOCK + size].to_vec();
+            offset += BLOCK + size.div_ceil(BLOCK) * BLOCK;
+            match header[156] {
+                b'L' => long = Some(String::from_utf8_lossy(&data).trim_end_matches('\0').to_string()),
+                _ => out.push((long.take().unwrap_or_else(|| field(0..100)), data)),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn contexts_leave_out_what_dockerignore_excludes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let long = "a".repeat(120);
+        for (path, content) in [
+            ("Dockerfile", "FROM python:3.12 AS base\nRUN pip install uv\nFROM base AS test\nRUN pytest\nFROM base AS prod\nCMD [\"app\"]\n"),
+            (".dockerignore", "# local state\n.git\n**/__pycache__\n*.log\n!keep.log\nnode_modules\n"),
+            ("app/main.py", "print()"),
+            ("app/__pycache__/main.pyc", "x"),
+            ("debug.log", "x"),
+            ("keep.log", "x"),
+            (".git/HEAD", "ref"),
+            ("node_modules/left-pad/index.js", "x"),
+        ] {
+            std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            std::fs::write(root.join(path), content).unwrap();
+        }
+        std::fs::create_dir_all(root.join("deep")).unwrap();
+        std::fs::write(root.join("deep").join(&long), "long").unwrap();
+
+        let entries = names(&archive(root, "Dockerfile", Some("test")).unwrap());
+        let paths: Vec<&str> = entries.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(paths, [".dockerignore", "app/", "app/main.py", "deep/", &format!("deep/{long}"), "keep.log", "Dockerfile"]);
+        let dockerfile = &entries.last().unwrap().1;
+        assert_eq!(String::from_utf8_lossy(dockerfile), "FROM python:3.12 AS base\nRUN pip install uv\nFROM base AS test\nRUN pytest\n");
+    }
+
+    #[test]
+    fn targets_must_name_a_stage() {
+        let dockerfile = "FROM rust:1 AS build\nRUN cargo build\n\nfrom debian AS runtime\nCOPY --from=build /app /app\n";
+        assert_eq!(truncate_to_stage(dockerfile, "build").unwrap(), "FROM rust:1 AS build\nRUN cargo build\n\n");
+        assert_eq!(truncate_to_stage(dockerfile, "runtime").unwrap(), dockerfile);
+        assert!(truncate_to_stage(dockerfile, "tests").unwrap_err().to_string().contains("no stage `tests`"));
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Source of wall-clock time and timers for the monitor. Production uses [`SystemClock`];
+/// tests drive intervals, heartbeat windows and cooldowns with a [`MockClock`] instead of sleeping.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+pub fn system() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Manually advanced clock; pending sleeps complete once `advance` moves time past their deadline.
+// Library/test API; the binary always runs on the system clock
+#[allow(dead_code)]
+pub struct MockClock {
+    now: watch::Sender<DateTime<Utc>>,
+}
+
+#[allow(dead_code)]
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Arc<Self> {
+        Arc::new(Self { now: watch::Sender::new(start) })
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let by = chrono::Duration::from_std(by).unwrap_or_else(|_| chrono::Duration::zero());
+        self.now.send_modify(|t| *t += by);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.borrow()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let deadline = self.now() + chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::zero());
+        let mut rx = self.now.subscribe();
+        Box::pin(async move {
+            while *rx.borrow_and_update() < deadline {
+                if rx.changed().await.is_err() { return; }
+            }
+        })
+    }
+}
+
+/// Clock-driven equivalent of `tokio::time::interval`: the first tick completes immediately and
+/// missed ticks are skipped rather than bursted.
+pub struct Ticker {
+    clock: SharedClock,
+    period: chrono::Duration,
+    next: Option<DateTime<Utc>>,
+}
+
+impl Ticker {
+    pub fn new(clock: SharedClock, period: Duration) -> Self {
+        let period = chrono::Duration::from_std(period.max(Duration::from_millis(1))).unwrap_or_else(|_| chrono::Duration::seconds(1));
+        Self { clock, period, next: None }
+    }
+
+    /// Change the period from the next tick on (e.g. after a config reload).
+    pub fn set_period(&mut self, period: Duration) {
+        let period = chrono::Duration::from_std(period.max(Duration::from_millis(1))).unwrap_or_else(|_| chrono::Duration::seconds(1));
+        if period != self.period {
+            if let Some(next) = self.next { self.next = Some(next - self.period + period); }
+            self.period = period;
+        }
+    }
+
+    pub async fn tick(&mut self) {
+        let now = self.clock.now();
+        let due = self.next.unwrap_or(now);
+        if let Ok(wait) = (due - now).to_std() {
+            if !wait.is_zero() { self.clock.sleep(wait).await; }
+        }
+        let now = self.clock.now();
+        let next = due + self.period;
+        self.next = Some(if next <= now { now + self.period } else { next });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mock_sleep_completes_only_after_advance() {
+        let clock = MockClock::new(Utc::now());
+        let mut sleep = clock.sleep(Duration::from_secs(30));
+        assert!(futures::poll!(&mut sleep).is_pending());
+        clock.advance(Duration::from_secs(29));
+        assert!(futures::poll!(&mut sleep).is_pending());
+        clock.advance(Duration::from_secs(1));
+        assert!(futures::poll!(&mut sleep).is_ready());
+    }
+
+    #[tokio::test]
+    async fn ticker_fires_immediately_then_per_period() {
+        let clock = MockClock::new(Utc::now());
+        let mut ticker = Ticker::new(clock.clone(), Duration::from_secs(10));
+        ticker.tick().await;
+        let mut second = Box::pin(ticker.tick());
+        assert!(futures::poll!(&mut second).is_pending());
+        clock.advance(Duration::from_secs(10));
+        assert!(futures::poll!(&mut second).is_ready());
+    }
+}
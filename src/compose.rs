@@ -1,23 +1,33 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::ValueEnum;
+use schemars::JsonSchema;
+use utoipa::ToSchema;
 use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::Duration;
 use tracing::{debug, info, warn};
+use crate::config::{Config, RegistryConfig};
+use bollard::auth::DockerCredentials;
+use crate::health::HealthChecker;
 use crate::metrics;
 use bollard::Docker;
-use bollard::service::ContainerSummary;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, ValueEnum, Serialize, Deserialize, JsonSchema, ToSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum ComposeAction {
     Build,
     Pull,
     Up,
+    Down,
     Start,
     Stop,
     Restart,
     Push,
     Ps,
     Logs,
+    Exec,
 }
 
 impl ComposeAction {
@@ -26,17 +36,19 @@ impl ComposeAction {
             Self::Build => "build",
             Self::Pull => "pull",
             Self::Up => "up",
+            Self::Down => "down",
             Self::Start => "start",
             Self::Stop => "stop",
             Self::Restart => "restart",
             Self::Push => "push",
             Self::Ps => "ps",
             Self::Logs => "logs",
+            Self::Exec => "exec",
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, ToSchema)]
 pub struct ComposeResult {
     pub action: String,
     pub services: Vec<String>,
@@ -44,9 +56,26 @@ pub struct ComposeResult {
     pub status_code: Option<i32>,
     pub stdout: String,
     pub stderr: String,
+    // Structured listing (populated by `ps`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub containers: Option<Vec<ContainerInfo>>,
+    /// Place in line on arrival, for an action that had to wait for another to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_position: Option<usize>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub state: String,
+    pub health: Option<String>,
+    pub ports: Vec<String>,
+    pub uptime_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, ToSchema)]
 pub struct ComposeRequest {
     pub action: ComposeAction,
     #[serde(default)]
@@ -54,87 +83,461 @@ pub struct ComposeRequest {
     #[serde(default = "default_compose_file")] 
     pub file: String,
     pub project: Option<String>,
-    #[serde(default)]
+    /// `up -d`, or for logs: keep following new output
+    #[serde(default, alias = "follow")]
     pub detach: bool,
     pub tail: Option<u32>,
+    /// Logs: stream lines as they are written instead of returning them at the end
+    #[serde(default)]
+    pub stream: bool,
     #[serde(default)]
     pub dry_run: bool,
+    #[serde(default, flatten)]
+    pub down: DownOptions,
+    /// Env file compose reads variables from (`--env-file`), e.g. `envs/paper.env`
+    #[serde(default)]
+    pub env_file: Option<String>,
+    /// Variables for the compose file, overriding the env file and the host's environment
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Command `exec` runs in the single service's container, e.g. `["alembic", "upgrade", "head"]`
+    #[serde(default)]
+    pub command: Vec<String>,
+    /// Build: ignore cached layers
+    #[serde(default)]
+    pub no_cache: bool,
+    /// Build: arguments overriding the `build.args` of the compose file
+    #[serde(default)]
+    pub build_args: BTreeMap<String, String>,
+    /// Build: stage to stop at, overriding the `build.target` of the compose file
+    #[serde(default)]
+    pub target: Option<String>,
+    /// How long `up`/`start` wait for a service to become ready before starting its dependents
+    #[serde(default)]
+    pub ready_timeout_seconds: Option<u64>,
+    /// Health endpoint of each monitored container, which decides when it is ready for its dependents
+    #[serde(skip)]
+    pub ready_endpoints: HashMap<String, String>,
+    /// Registry logins `pull`/`push` use instead of the host's `docker login` state
+    #[serde(skip)]
+    pub registries: Vec<RegistryConfig>,
 }
 
+/// Flags of `down`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, ToSchema, clap::Args)]
+pub struct DownOptions {
+    /// Down: also remove named volumes declared in the file and anonymous volumes of the containers
+    #[serde(default)]
+    #[arg(long)]
+    pub volumes: bool,
+    /// Down: also remove containers of the project that the file no longer defines
+    #[serde(default)]
+    #[arg(long)]
+    pub remove_orphans: bool,
+    /// Down: seconds containers get to stop before they are killed (compose's default is 10)
+    #[serde(default)]
+    #[arg(long)]
+    pub timeout: Option<u64>,
+}
+
+impl DownOptions {
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.volumes { args.push("--volumes".into()); }
+        if self.remove_orphans { args.push("--remove-orphans".into()); }
+        if let Some(t) = self.timeout { args.push("--timeout".into()); args.push(t.to_string()); }
+        args
+    }
+}
+
+/// Longest an `exec` command may run, since migrations and the like outlast the client's default
+const EXEC_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Longest a single image build, pull or push may take
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Default of `ready_timeout_seconds`
+const READY_TIMEOUT: Duration = Duration::from_secs(120);
+/// Interval of readiness polls while dependents wait
+const READY_POLL: Duration = Duration::from_secs(2);
+
 fn default_compose_file() -> String { "docker-compose.yml".into() }
 
+/// Services of a compose file, in file order.
+#[derive(Debug, Clone, Default)]
+pub struct ComposeProject {
+    /// Project the containers are named after
+    pub name: String,
+    pub services: Vec<(String, ComposeService)>,
+}
+
+/// The parts of a compose service definition fks_master uses.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ComposeService {
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub container_name: Option<String>,
+    #[serde(default)]
+    pub ports: Vec<serde_yaml::Value>,
+    #[serde(default)]
+    pub expose: Vec<serde_yaml::Value>,
+    #[serde(default)]
+    pub depends_on: Option<serde_yaml::Value>,
+    #[serde(default)]
+    pub healthcheck: Option<ComposeHealthCheck>,
+    #[serde(default)]
+    pub build: Option<ComposeBuild>,
+}
+
+/// `build:` of a service, given either as just the context or in long form.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "BuildSyntax")]
+pub struct ComposeBuild {
+    /// Directory sent to the daemon, relative to the compose file
+    pub context: String,
+    /// Relative to `context`; `Dockerfile` when unset
+    pub dockerfile: Option<String>,
+    pub args: BTreeMap<String, String>,
+    pub target: Option<String>,
+    pub no_cache: bool,
+    pub cache_from: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BuildSyntax {
+    Context(String),
+    Long {
+        #[serde(default)]
+        context: Option<String>,
+        #[serde(default)]
+        dockerfile: Option<String>,
+        #[serde(default)]
+        args: Option<serde_yaml::Value>,
+        #[serde(default)]
+        target: Option<String>,
+        #[serde(default)]
+        no_cache: bool,
+        #[serde(default)]
+        cache_from: Vec<String>,
+    },
+}
+
+impl From<BuildSyntax> for ComposeBuild {
+    fn from(syntax: BuildSyntax) -> Self {
+        match syntax {
+            BuildSyntax::Context(context) => Self { context, ..Default::default() },
+            BuildSyntax::Long { context, dockerfile, args, target, no_cache, cache_from } => Self {
+                context: context.unwrap_or_else(|| ".".into()),
+                dockerfile,
+                args: args.as_ref().map(build_args).unwrap_or_default(),
+                target,
+                no_cache,
+                cache_from,
+            },
+        }
+    }
+}
+
+/// `args` as a mapping or a `KEY=VALUE` list. A key without a value takes it from the environment, as
+/// compose does, and is left out when the variable is unset.
+fn build_args(args: &serde_yaml::Value) -> BTreeMap<String, String> {
+    let pairs: Vec<(String, Option<String>)> = match args {
+        serde_yaml::Value::Mapping(map) => map.iter()
+            .filter_map(|(k, v)| {
+                let value = match v {
+                    serde_yaml::Value::Null => None,
+                    serde_yaml::Value::String(s) => Some(s.clone()),
+                    other => serde_yaml::to_string(other).ok().map(|s| s.trim_end().to_string()),
+                };
+                Some((k.as_str()?.to_string(), value))
+            })
+            .collect(),
+        serde_yaml::Value::Sequence(items) => items.iter()
+            .filter_map(|item| item.as_str())
+            .map(|item| match item.split_once('=') {
+                Some((k, v)) => (k.to_string(), Some(v.to_string())),
+                None => (item.to_string(), None),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    pairs.into_iter()
+        .filter_map(|(k, v)| Some((k.clone(), v.or_else(|| std::env::var(&k).ok())?)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ComposeHealthCheck {
+    #[serde(default)]
+    pub test: Option<serde_yaml::Value>,
+    #[serde(default)]
+    pub disable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComposeFile {
+    #[serde(default)]
+    services: serde_yaml::Mapping,
+}
+
+impl ComposeProject {
+    /// Parse compose YAML whose containers belong to project `name`.
+    pub fn parse(yaml: &str, name: &str) -> Result<Self> {
+        let file: ComposeFile = serde_yaml::from_str(yaml).context("parsing compose file")?;
+        let mut services = Vec::new();
+        for (key, value) in &file.services {
+            let Some(id) = key.as_str() else { continue };
+            let service: ComposeService = serde_yaml::from_value(value.clone()).with_context(|| format!("compose service `{id}`"))?;
+            services.push((id.to_string(), service));
+        }
+        Ok(Self { name: name.to_string(), services })
+    }
+
+    /// Read and parse `path`; the project defaults to compose's own default (see [`default_project`]).
+    pub fn load(path: &Path, project: Option<&str>) -> Result<Self> {
+        let yaml = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&yaml, project.filter(|p| !p.is_empty()).unwrap_or(&default_project(path)))
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.services.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    pub fn service(&self, name: &str) -> Option<&ComposeService> {
+        self.services.iter().find(|(n, _)| n == name).map(|(_, service)| service)
+    }
+
+    /// `services` ordered so each comes after those of its dependencies that are also in `services`,
+    /// keeping the given order otherwise. Fails on a dependency cycle.
+    pub fn start_order(&self, services: &[String]) -> Result<Vec<String>> {
+        let dependencies = |name: &str| -> Vec<String> {
+            self.service(name).map(|s| s.dependencies()).unwrap_or_default().into_iter().filter(|d| services.contains(d) && d != name).collect()
+        };
+        let mut order: Vec<String> = Vec::new();
+        let mut pending: Vec<&String> = services.iter().collect();
+        pending.dedup();
+        while !pending.is_empty() {
+            match pending.iter().position(|name| dependencies(name).iter().all(|d| order.contains(d))) {
+                Some(next) => order.push(pending.remove(next).clone()),
+                None => anyhow::bail!("dependency cycle between {}", pending.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")),
+            }
+        }
+        Ok(order)
+    }
+
+    /// Image `service` is built as: its `image`, or compose's `<project>-<service>`.
+    pub fn image(&self, service: &str) -> String {
+        self.service(service).and_then(|s| s.image.clone()).unwrap_or_else(|| format!("{}-{service}", self.name))
+    }
+
+    /// Container compose creates for `service`: its `container_name`, or `<project>-<service>-1`.
+    pub fn container(&self, service: &str) -> String {
+        self.service(service)
+            .and_then(|s| s.container_name.clone())
+            .unwrap_or_else(|| format!("{}-{service}-1", self.name))
+    }
+}
+
+impl ComposeService {
+    /// `depends_on` is either a list of names or a map of name -> condition.
+    pub fn dependencies(&self) -> Vec<String> {
+        match &self.depends_on {
+            Some(serde_yaml::Value::Sequence(items)) => items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+            Some(serde_yaml::Value::Mapping(map)) => map.keys().filter_map(|k| k.as_str().map(str::to_string)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The healthcheck command, unless the healthcheck is disabled (`disable: true` or `test: ["NONE"]`).
+    pub fn healthcheck_test(&self) -> Option<&serde_yaml::Value> {
+        let test = self.healthcheck.as_ref().filter(|h| !h.disable)?.test.as_ref()?;
+        let disabled = matches!(test, serde_yaml::Value::Sequence(parts) if parts.first().and_then(|p| p.as_str()) == Some("NONE"));
+        (!disabled).then_some(test)
+    }
+}
+
+/// Compose's own default project name: the compose file's directory name, lowercased.
+pub fn default_project(path: &Path) -> String {
+    path.canonicalize()
+        .ok()
+        .and_then(|p| p.parent().and_then(|d| d.file_name()).map(|n| n.to_string_lossy().to_lowercase()))
+        .unwrap_or_else(|| "default".into())
+}
+
 impl ComposeRequest {
+    /// Fill in what the monitor knows: its compose project when the request names none, the health
+    /// endpoints that decide readiness and the registry logins.
+    pub fn with_monitor_config(mut self, config: &Config) -> Self {
+        if self.project.is_none() {
+            self.project = config.monitoring.compose_project.clone().filter(|p| !p.is_empty());
+        }
+        // Monitored containers are ready for their dependents once their health endpoint passes
+        self.ready_endpoints = config.services.iter()
+            .filter(|s| s.docker_host.is_none() && !s.health_endpoint.is_empty())
+            .filter_map(|s| Some((s.docker_container.clone()?, s.health_endpoint.clone())))
+            .collect();
+        self.registries = config.registries.clone();
+        self
+    }
+
+    /// Project the request acts on, as the compose queue knows it.
+    pub fn project_name(&self) -> String {
+        self.project.clone().filter(|p| !p.is_empty()).unwrap_or_else(|| default_project(Path::new(&self.file)))
+    }
+
+    /// Mistakes in the request itself, found before anything runs.
+    pub fn validate(&self) -> Result<()> {
+        if self.action == ComposeAction::Exec && (self.services.len() != 1 || self.command.is_empty()) {
+            return Err(anyhow!("exec needs exactly one service and a command"));
+        }
+        if let Some(name) = self.env.keys().find(|k| !is_variable_name(k)) {
+            return Err(anyhow!("`{name}` is not a valid environment variable name"));
+        }
+        Ok(())
+    }
+
     pub async fn execute(self) -> Result<ComposeResult> {
+        self.validate()?;
         if self.dry_run {
             metrics::increment_compose_action(self.action.as_str(), true);
-            return Ok(ComposeResult { action: self.action.as_str().into(), services: self.services, success: true, status_code: Some(0), stdout: "dry-run".into(), stderr: String::new() });
+            return Ok(ComposeResult { action: self.action.as_str().into(), services: self.services, success: true, status_code: Some(0), stdout: "dry-run".into(), stderr: String::new(), containers: None, queue_position: None });
+        }
+        // An environment overlay only takes effect when compose recreates the containers
+        if self.action == ComposeAction::Up && (self.env_file.is_some() || !self.env.is_empty()) {
+            return run_compose_cli(&self).await;
         }
+        let per_service = matches!(self.action, ComposeAction::Logs | ComposeAction::Build | ComposeAction::Pull | ComposeAction::Push | ComposeAction::Up | ComposeAction::Start | ComposeAction::Stop | ComposeAction::Restart | ComposeAction::Exec);
+        let (definitions, services) = if per_service { self.services()? } else { (None, self.services.clone()) };
         // Initialize Docker client (uses DOCKER_HOST / default socket)
         let docker = Docker::connect_with_local_defaults().map_err(|e| anyhow!("Docker connect failed: {e}"))?;
         let action_str = self.action.as_str();
         let mut stdout = String::new();
         let mut stderr = String::new();
         let mut success = true;
-    let status_code: Option<i32> = Some(0);
+    let mut status_code: Option<i32> = Some(0);
+        let mut containers: Option<Vec<ContainerInfo>> = None;
 
-        // Helper closures
-        let services = if self.services.is_empty() { vec![] } else { self.services.clone() };
+        let targets = if per_service { self.containers(&docker, definitions.as_ref(), &services).await } else { Vec::new() };
+        let container_of = |svc: &String| services.iter().position(|s| s == svc).map_or_else(|| svc.clone(), |i| targets[i].clone());
+        let dependencies = |svc: &str| definitions.as_ref().and_then(|d| d.service(svc)).map(|s| s.dependencies()).unwrap_or_default();
 
     let start_time = std::time::Instant::now();
     match self.action {
             ComposeAction::Ps => {
-                let containers: Vec<ContainerSummary> = docker.list_containers::<String>(None).await.map_err(|e| anyhow!("list containers: {e}"))?;
-                let mut table = String::new();
-                for c in containers.iter() {
-                    if let Some(names) = &c.names {
-                        let name = names.get(0).cloned().unwrap_or_default();
-                        if services.is_empty() || services.iter().any(|s| name.contains(s)) {
-                            table.push_str(&format!("{name}\t{:?}\t{:?}\n", c.state, c.status));
-                        }
-                    }
-                }
-                stdout = table;
+                let listing = list_containers(&docker, self.project.as_deref(), &services).await?;
+                stdout = format_ps_table(&listing);
+                containers = Some(listing);
             }
             ComposeAction::Logs => {
-                // For logs we stream each specified container sequentially; if none specified we skip (cannot infer compose set w/out parsing file)
-                if services.is_empty() { stderr.push_str("no services specified for logs; provide service names\n"); success=false; }
-                for svc in services.iter() {
-                    let tail = self.tail.unwrap_or(100); // default tail lines
-                    let mut logs = docker.logs(svc, Some(bollard::container::LogsOptions::<String>{ follow: false, stdout: true, stderr: true, tail: tail.to_string(), ..Default::default() }))
+                let tail = self.tail.unwrap_or(100); // default tail lines
+                for container in &targets {
+                    let mut logs = docker.logs(container, Some(bollard::container::LogsOptions::<String>{ follow: false, stdout: true, stderr: true, tail: tail.to_string(), ..Default::default() }))
                         .map(|chunk| match chunk { Ok(bollard::container::LogOutput::StdOut { message }) | Ok(bollard::container::LogOutput::StdErr { message }) => Ok(String::from_utf8_lossy(&message).to_string()), Ok(_) => Ok(String::new()), Err(e)=>Err(e) });
                     while let Some(line) = logs.next().await { match line { Ok(l) => { stdout.push_str(&l); }, Err(e)=> { stderr.push_str(&format!("{e}\n")); success=false; } } }
                 }
             }
             ComposeAction::Build => {
-                // Compose build semantics (multi-service) are non-trivial; we fallback to CLI for now until full build context parsing is implemented.
+                let definitions = definitions.as_ref().ok_or_else(|| anyhow!("build needs the compose file for the services' build contexts"))?;
+                // Base images may come from private registries too
+                let logins = crate::registry_auth::all(&self.registries).await?;
+                let mut built = 0;
+                for svc in &services {
+                    let Some(build) = definitions.service(svc).and_then(|s| s.build.as_ref()) else {
+                        stderr.push_str(&format!("{svc}: no build section in the compose file, skipped\n"));
+                        continue;
+                    };
+                    let image = definitions.image(svc);
+                    match self.build_image(&docker, build, &image, &logins).await {
+                        Ok(output) => { stdout.push_str(&output); built += 1; }
+                        Err(e) => { stderr.push_str(&format!("build {svc}: {e:#}\n")); success = false; }
+                    }
+                }
+                stdout.push_str(&format!("Built {built} images"));
+            }
+            ComposeAction::Exec => {
+                let container = container_of(&services[0]);
+                let options = bollard::exec::CreateExecOptions { attach_stdout: Some(true), attach_stderr: Some(true), cmd: Some(self.command.clone()), ..Default::default() };
+                let docker = docker.clone().with_timeout(EXEC_TIMEOUT);
+                let exec = docker.create_exec(&container, options).await.map_err(|e| anyhow!("exec in {container}: {e}"))?;
+                if let bollard::exec::StartExecResults::Attached { mut output, .. } = docker.start_exec(&exec.id, None).await? {
+                    while let Some(chunk) = output.next().await {
+                        match chunk {
+                            Ok(bollard::container::LogOutput::StdErr { message }) => stderr.push_str(&String::from_utf8_lossy(&message)),
+                            Ok(bollard::container::LogOutput::StdOut { message }) | Ok(bollard::container::LogOutput::Console { message }) => stdout.push_str(&String::from_utf8_lossy(&message)),
+                            Ok(_) => {}
+                            Err(e) => { stderr.push_str(&format!("{e}\n")); success = false; }
+                        }
+                    }
+                }
+                let exit_code = docker.inspect_exec(&exec.id).await?.exit_code;
+                status_code = exit_code.map(|c| c as i32);
+                success &= exit_code == Some(0);
+            }
+            ComposeAction::Down => {
+                // Removing the project's containers, networks and volumes is left to compose itself
                 let fallback = run_compose_cli(&self).await?;
                 return Ok(fallback);
             }
             ComposeAction::Pull | ComposeAction::Push => {
-                // For simplicity fallback to CLI (registry auth / compose semantics out of scope initial refactor)
-                let fallback = run_compose_cli(&self).await?;
-                return Ok(fallback);
+                // Through the API, so the configured registry logins apply rather than the host's
+                let definitions = definitions.as_ref().ok_or_else(|| anyhow!("{action_str} needs the compose file for the services' images"))?;
+                let mut transferred = 0;
+                for svc in &services {
+                    let Some(image) = definitions.service(svc).and_then(|s| s.image.as_deref()) else {
+                        stderr.push_str(&format!("{svc}: no image in the compose file, skipped\n"));
+                        continue;
+                    };
+                    match transfer_image(&docker, self.action, image, &self.registries).await {
+                        Ok(()) => transferred += 1,
+                        Err(e) => { stderr.push_str(&format!("{action_str} {svc} ({image}): {e:#}\n")); success = false; }
+                    }
+                }
+                stdout = format!("{} {transferred} images", if self.action == ComposeAction::Pull { "Pulled" } else { "Pushed" });
             }
             ComposeAction::Up | ComposeAction::Start => {
-                // Start (create if needed) containers by name
-                for svc in services.iter() {
-                    // Attempt start; if missing we cannot create without compose file parsing -> fallback to CLI
-                    if let Err(e) = docker.start_container::<String>(svc, None).await {
+                // Dependencies first; a dependent waits until they are ready and is skipped if one never is
+                let order = match &definitions { Some(d) => d.start_order(&services)?, None => services.clone() };
+                let checker = HealthChecker::new(Duration::from_secs(5), 1);
+                let timeout = self.ready_timeout_seconds.map_or(READY_TIMEOUT, Duration::from_secs);
+                let mut not_ready: Vec<&String> = Vec::new();
+                let mut started = 0;
+                for svc in &order {
+                    if let Some(dep) = dependencies(svc).iter().find(|d| not_ready.contains(d)) {
+                        stderr.push_str(&format!("{svc}: not started, {dep} is not ready\n"));
+                        success = false;
+                        not_ready.push(svc);
+                        continue;
+                    }
+                    let container = container_of(svc);
+                    if let Err(e) = docker.start_container::<String>(&container, None).await {
                         warn!(service=%svc, error=%e, "start via API failed, falling back to compose CLI");
                         let fallback = run_compose_cli(&self).await?;
                         return Ok(fallback);
                     }
+                    started += 1;
+                    if order.iter().any(|other| dependencies(other).contains(svc)) {
+                        let endpoint = self.ready_endpoints.get(&container).map(String::as_str);
+                        if let Err(e) = wait_ready(&docker, &checker, &container, endpoint, timeout).await {
+                            stderr.push_str(&format!("{svc}: {e:#}\n"));
+                            success = false;
+                            not_ready.push(svc);
+                        }
+                    }
                 }
-                stdout = format!("Started {} containers", services.len());
+                stdout = format!("Started {started} containers");
             }
             ComposeAction::Stop => {
-                for svc in services.iter() {
-                    if let Err(e) = docker.stop_container(svc, None).await { stderr.push_str(&format!("stop {svc}: {e}\n")); success=false; }
+                // Dependents first, the reverse of the start order
+                let order = match &definitions { Some(d) => d.start_order(&services)?, None => services.clone() };
+                for svc in order.iter().rev() {
+                    if let Err(e) = docker.stop_container(&container_of(svc), None).await { stderr.push_str(&format!("stop {svc}: {e}\n")); success=false; }
                 }
             }
             ComposeAction::Restart => {
-                for svc in services.iter() {
-                    if let Err(e) = docker.restart_container(svc, None).await { stderr.push_str(&format!("restart {svc}: {e}\n")); success=false; }
+                for (svc, container) in services.iter().zip(&targets) {
+                    if let Err(e) = docker.restart_container(container, None).await { stderr.push_str(&format!("restart {svc}: {e}\n")); success=false; }
                 }
             }
         }
@@ -143,25 +546,300 @@ impl ComposeRequest {
     crate::metrics::observe_compose_action_duration(action_str, elapsed);
     if success { info!(action=action_str, services=?services, elapsed=?elapsed, "Compose action (API) ok"); } else { warn!(action=action_str, services=?services, stderr, elapsed=?elapsed, "Compose action (API) partial/failed"); }
         metrics::increment_compose_action(action_str, success);
-        Ok(ComposeResult { action: action_str.into(), services, success, status_code, stdout, stderr })
+        Ok(ComposeResult { action: action_str.into(), services, success, status_code, stdout, stderr, containers, queue_position: None })
+    }
+
+    /// The compose file (when readable) and the services the request covers. Without service names that
+    /// is every service of the compose file, so an unusable file fails the request before Docker is asked.
+    fn services(&self) -> Result<(Option<ComposeProject>, Vec<String>)> {
+        let definitions = match ComposeProject::load(Path::new(&self.file), self.project.as_deref()) {
+            Ok(definitions) => Some(definitions),
+            Err(e) if self.services.is_empty() => return Err(anyhow!("no services given and the compose file is unusable: {e:#}")),
+            Err(e) => {
+                debug!(error = format!("{e:#}"), "compose file unavailable; treating service names as container names");
+                None
+            }
+        };
+        let services = match &definitions {
+            Some(definitions) if self.services.is_empty() => definitions.names(),
+            _ => self.services.clone(),
+        };
+        Ok((definitions, services))
+    }
+
+    /// Container of each of `services`; the compose file supplies names when the project's labels cannot
+    /// be looked up.
+    async fn containers(&self, docker: &Docker, definitions: Option<&ComposeProject>, services: &[String]) -> Vec<String> {
+        let project = self.project.clone().filter(|p| !p.is_empty()).or_else(|| definitions.map(|d| d.name.clone()));
+        let mut targets = Vec::new();
+        for svc in services {
+            targets.push(resolve_service_container(docker, project.as_deref(), svc, definitions).await);
+        }
+        targets
+    }
+
+    /// Build `build` through the API and tag it `image`, returning the builder's output. The request's
+    /// `no_cache`, `build_args` and `target` apply on top of the compose file's.
+    async fn build_image(&self, docker: &Docker, build: &ComposeBuild, image: &str, logins: &HashMap<String, DockerCredentials>) -> Result<String> {
+        let context = Path::new(&self.file).parent().unwrap_or(Path::new("")).join(&build.context);
+        let dockerfile = build.dockerfile.clone().unwrap_or_else(|| "Dockerfile".into());
+        let target = self.target.clone().or_else(|| build.target.clone());
+        let packing = format!("packing {}", context.display());
+        let archive = {
+            let dockerfile = dockerfile.clone();
+            tokio::task::spawn_blocking(move || crate::build_context::archive(&context, &dockerfile, target.as_deref())).await?.context(packing)?
+        };
+        let mut buildargs: HashMap<String, String> = build.args.clone().into_iter().collect();
+        buildargs.extend(self.build_args.clone());
+        let options = bollard::image::BuildImageOptions {
+            dockerfile,
+            t: image.to_string(),
+            buildargs,
+            nocache: self.no_cache || build.no_cache,
+            cachefrom: build.cache_from.clone(),
+            rm: true,
+            ..Default::default()
+        };
+        let docker = docker.clone().with_timeout(TRANSFER_TIMEOUT);
+        let mut progress = docker.build_image(options, Some(logins.clone()), Some(archive.into()));
+        let mut output = String::new();
+        while let Some(step) = progress.next().await {
+            if let Some(line) = step?.stream { output.push_str(&line); }
+        }
+        Ok(output)
+    }
+
+    /// Logs of the request's services as they are written, each line prefixed with `<service> | `. Starts
+    /// with the last `tail` lines of each and, with `detach`/`follow`, stays open until every container
+    /// stops or the stream is dropped. Fails up front if a container does not exist.
+    pub async fn stream_logs(self) -> Result<impl Stream<Item = Result<String>> + Send + 'static> {
+        let (definitions, services) = self.services()?;
+        let docker = Docker::connect_with_local_defaults().map_err(|e| anyhow!("Docker connect failed: {e}"))?;
+        let targets = self.containers(&docker, definitions.as_ref(), &services).await;
+        let width = services.iter().map(|s| s.len()).max().unwrap_or_default();
+        let mut streams = Vec::new();
+        for (svc, container) in services.into_iter().zip(targets) {
+            let lines = crate::logs::container_logs(docker.clone(), &container, self.tail.unwrap_or(100), self.detach).await
+                .with_context(|| format!("logs of {svc}"))?;
+            streams.push(lines.map(move |line| line.map(|line| format!("{svc:width$} | {}\n", line.line))).boxed());
+        }
+        metrics::increment_compose_action(self.action.as_str(), true);
+        Ok(futures::stream::select_all(streams))
     }
 }
 
-async fn run_compose_cli(req: &ComposeRequest) -> Result<ComposeResult> {
-    use std::process::Command;
-    let start_time = std::time::Instant::now();
+/// Pull or push `image` (`[registry/]repository[:tag|@digest]`), logged in to its registry with the
+/// configured credentials when there are any.
+async fn transfer_image(docker: &Docker, action: ComposeAction, image: &str, registries: &[RegistryConfig]) -> Result<()> {
+    let login = crate::registry_auth::credentials(registries, crate::registry_auth::registry_of(image)).await?;
+    let (name, tag) = split_reference(image);
+    // Layers of large images outlast the client's default timeout
+    let docker = docker.clone().with_timeout(TRANSFER_TIMEOUT);
+    let mut progress = match action {
+        ComposeAction::Push => docker.push_image(name, Some(bollard::image::PushImageOptions { tag }), login).map(|step| step.map(drop)).boxed(),
+        _ => {
+            let options = bollard::image::CreateImageOptions { from_image: name, tag, ..Default::default() };
+            docker.create_image(Some(options), None, login).map(|step| step.map(drop)).boxed()
+        }
+    };
+    while let Some(step) = progress.next().await {
+        step?;
+    }
+    Ok(())
+}
+
+/// Name and tag (or digest) of an image reference, which the API takes apart.
+fn split_reference(image: &str) -> (&str, &str) {
+    match image.split_once('@') {
+        Some((name, digest)) => (name, digest),
+        None => match image.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, tag),
+            _ => (image, "latest"),
+        },
+    }
+}
+
+/// Wait until `container` can serve its dependents: its monitored health `endpoint` passes when there is
+/// one, otherwise Docker reports it healthy, or just running when it has no healthcheck.
+async fn wait_ready(docker: &Docker, checker: &HealthChecker, container: &str, endpoint: Option<&str>, timeout: Duration) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let ready = match endpoint {
+            Some(url) => checker.check_health(url).await.is_ok(),
+            None => {
+                let state = docker.inspect_container(container, None).await?.state.unwrap_or_default();
+                if state.running != Some(true) {
+                    anyhow::bail!("{container} is {}", state.status.map(|s| s.to_string()).unwrap_or_else(|| "not running".into()));
+                }
+                match state.health.and_then(|h| h.status) {
+                    Some(bollard::models::HealthStatusEnum::HEALTHY) | Some(bollard::models::HealthStatusEnum::NONE) | Some(bollard::models::HealthStatusEnum::EMPTY) | None => true,
+                    Some(_) => false,
+                }
+            }
+        };
+        if ready { return Ok(()); }
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!("{container} not ready after {}s", timeout.as_secs());
+        }
+        tokio::time::sleep(READY_POLL).await;
+    }
+}
+
+/// Names compose can interpolate: letters, digits and underscores, not starting with a digit.
+fn is_variable_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Docker label compose stamps on every container it creates.
+pub const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+pub const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+
+const COMPOSE_CONFIG_FILES_LABEL: &str = "com.docker.compose.project.config_files";
+const COMPOSE_WORKING_DIR_LABEL: &str = "com.docker.compose.project.working_dir";
+
+/// Remove `container` and create it anew from the compose definition it was created from, leaving
+/// its dependencies alone. `host_args` are the global `docker` options reaching the daemon it runs on
+/// (see [`crate::docker_hosts::cli_args`]). With `fresh_volumes` its anonymous volumes are replaced too.
+pub async fn recreate_container(docker: &Docker, host_args: &[String], container: &str, fresh_volumes: bool, stop_timeout_seconds: u64) -> Result<String> {
+    let labels = docker.inspect_container(container, None).await?.config.and_then(|c| c.labels).unwrap_or_default();
+    let args = recreate_args(container, &labels, fresh_volumes, stop_timeout_seconds)?;
+    debug!(?args, "Recreating container through the compose CLI");
+    let mut cmd = tokio::process::Command::new("docker");
+    cmd.args(host_args).args(&args).kill_on_drop(true);
+    let output = cmd.output().await.map_err(|e| anyhow!("Failed to invoke docker: {e}"))?;
+    if !output.status.success() {
+        return Err(anyhow!("docker compose up --force-recreate exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(format!("Recreated {container} from compose service {}", labels[COMPOSE_SERVICE_LABEL]))
+}
+
+/// `docker compose up --force-recreate` arguments for the container carrying `labels`.
+fn recreate_args(container: &str, labels: &HashMap<String, String>, fresh_volumes: bool, stop_timeout_seconds: u64) -> Result<Vec<String>> {
+    let (Some(project), Some(service)) = (labels.get(COMPOSE_PROJECT_LABEL), labels.get(COMPOSE_SERVICE_LABEL)) else {
+        return Err(anyhow!("container {container} was not created by compose, so there is no definition to recreate it from"));
+    };
+    let mut args: Vec<String> = vec!["compose".into(), "-p".into(), project.clone()];
+    if let Some(dir) = labels.get(COMPOSE_WORKING_DIR_LABEL) { args.extend(["--project-directory".into(), dir.clone()]); }
+    for file in labels.get(COMPOSE_CONFIG_FILES_LABEL).into_iter().flat_map(|files| files.split(',')).filter(|f| !f.is_empty()) {
+        args.extend(["-f".into(), file.to_string()]);
+    }
+    args.extend(["up", "-d", "--force-recreate", "--no-deps"].map(String::from));
+    if fresh_volumes { args.push("--renew-anon-volumes".into()); }
+    args.extend(["--timeout".into(), stop_timeout_seconds.to_string(), service.clone()]);
+    Ok(args)
+}
+
+/// List options restricted to containers of the given compose project (if any).
+pub fn project_list_options(project: Option<&str>) -> Option<bollard::container::ListContainersOptions<String>> {
+    let project = project.filter(|p| !p.is_empty())?;
+    let mut filters = std::collections::HashMap::new();
+    filters.insert("label".to_string(), vec![format!("{COMPOSE_PROJECT_LABEL}={project}")]);
+    Some(bollard::container::ListContainersOptions { filters, ..Default::default() })
+}
+
+/// Resolve a compose service name to its container within the project; falls back to the container the
+/// compose file names for it, then to treating it as a container name.
+async fn resolve_service_container(docker: &Docker, project: Option<&str>, service: &str, definitions: Option<&ComposeProject>) -> String {
+    if let Some(mut opts) = project_list_options(project) {
+        opts.filters.entry("label".into()).or_default().push(format!("{COMPOSE_SERVICE_LABEL}={service}"));
+        if let Ok(found) = docker.list_containers(Some(opts)).await {
+            if let Some(name) = found.first().and_then(|c| c.names.as_ref()).and_then(|n| n.first()) {
+                return name.trim_start_matches('/').to_string();
+            }
+        }
+    }
+    match definitions {
+        Some(definitions) if definitions.service(service).is_some() => definitions.container(service),
+        _ => service.to_string(),
+    }
+}
+
+/// List running containers (optionally scoped to a compose project and filtered by service name substring) with inspect details.
+pub async fn list_containers(docker: &Docker, project: Option<&str>, services: &[String]) -> Result<Vec<ContainerInfo>> {
+    let summaries = docker.list_containers(project_list_options(project)).await.map_err(|e| anyhow!("list containers: {e}"))?;
+    let mut out = Vec::new();
+    for c in summaries {
+        let name = c.names.as_ref().and_then(|n| n.first()).map(|n| n.trim_start_matches('/').to_string()).unwrap_or_default();
+        if !services.is_empty() && !services.iter().any(|s| name.contains(s.as_str())) { continue; }
+        let id = c.id.clone().unwrap_or_default();
+        // Inspect is best effort; fall back to summary fields when it fails
+        let inspect = docker.inspect_container(&id, None).await.ok();
+        let state = inspect.as_ref().and_then(|i| i.state.as_ref());
+        let health = state
+            .and_then(|s| s.health.as_ref())
+            .and_then(|h| h.status.as_ref())
+            .map(|h| h.to_string())
+            .filter(|h| !h.is_empty() && h != "none");
+        let uptime_seconds = state
+            .filter(|s| s.running == Some(true))
+            .and_then(|s| s.started_at.as_deref())
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| chrono::Utc::now().signed_duration_since(t).num_seconds());
+        let ports = c.ports.as_deref().unwrap_or_default().iter().map(|p| {
+            let proto = p.typ.as_ref().map(|t| t.to_string()).unwrap_or_else(|| "tcp".into());
+            match p.public_port {
+                Some(public) => format!("{}:{}->{}/{}", p.ip.as_deref().unwrap_or("0.0.0.0"), public, p.private_port, proto),
+                None => format!("{}/{}", p.private_port, proto),
+            }
+        }).collect();
+        out.push(ContainerInfo {
+            id: id.chars().take(12).collect(),
+            name,
+            image: c.image.clone().unwrap_or_default(),
+            state: state.and_then(|s| s.status.as_ref()).map(|s| s.to_string()).or(c.state.clone()).unwrap_or_default(),
+            health,
+            ports,
+            uptime_seconds,
+        });
+    }
+    Ok(out)
+}
+
+/// Render a `docker compose ps`-style table.
+fn format_ps_table(containers: &[ContainerInfo]) -> String {
+    let mut table = String::from("NAME\tIMAGE\tSTATE\tHEALTH\tUPTIME\tPORTS\n");
+    for c in containers {
+        let uptime = c.uptime_seconds.map(|s| format!("{s}s")).unwrap_or_else(|| "-".into());
+        table.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\n", c.name, c.image, c.state, c.health.as_deref().unwrap_or("-"), uptime, c.ports.join(", ")));
+    }
+    table
+}
+
+/// `docker compose` arguments for `req`.
+fn cli_args(req: &ComposeRequest) -> Vec<String> {
     let mut args: Vec<String> = vec!["compose".into(), "-f".into(), req.file.clone()];
+    if let Some(env_file) = &req.env_file { args.push("--env-file".into()); args.push(env_file.clone()); }
     if let Some(project) = req.project.clone().filter(|p| !p.is_empty()) { args.push("-p".into()); args.push(project); }
-    let action_str = req.action.as_str();
-    args.push(action_str.into());
+    args.push(req.action.as_str().into());
+    // Specific flags per action
     match req.action {
-        ComposeAction::Up => { if req.detach { args.push("-d".into()); } }
-        ComposeAction::Logs => { if req.detach { args.push("-f".into()); } if let Some(t)=req.tail { args.push("--tail".into()); args.push(t.to_string()); } }
+        ComposeAction::Up if req.detach => { args.push("-d".into()); }
+        ComposeAction::Down => args.extend(req.down.args()),
+        ComposeAction::Build => {
+            if req.no_cache { args.push("--no-cache".into()); }
+            for (k, v) in &req.build_args { args.push("--build-arg".into()); args.push(format!("{k}={v}")); }
+        }
+        ComposeAction::Logs => {
+            if req.detach { args.push("-f".into()); } // follow
+            if let Some(t)=req.tail { args.push("--tail".into()); args.push(t.to_string()); }
+        }
+        // Output is captured, so no TTY
+        ComposeAction::Exec => args.push("-T".into()),
         _ => {}
     }
+    // Add services last, followed by the command for exec
     for s in &req.services { args.push(s.clone()); }
+    if req.action == ComposeAction::Exec { args.extend(req.command.iter().cloned()); }
+    args
+}
+
+async fn run_compose_cli(req: &ComposeRequest) -> Result<ComposeResult> {
+    use std::process::Command;
+    let start_time = std::time::Instant::now();
+    let action_str = req.action.as_str();
+    let args = cli_args(req);
     debug!(?args, "Fallback docker compose CLI execution");
-    let output = Command::new("docker").args(&args).output().map_err(|e| anyhow!("Failed to invoke docker: {e}"))?;
+    let output = Command::new("docker").args(&args).envs(&req.env).output().map_err(|e| anyhow!("Failed to invoke docker: {e}"))?;
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
     let success = output.status.success();
@@ -170,48 +848,21 @@ async fn run_compose_cli(req: &ComposeRequest) -> Result<ComposeResult> {
     crate::metrics::observe_compose_action_duration(action_str, elapsed);
     if success { info!(action=action_str, services=?req.services, elapsed=?elapsed, "Compose CLI action ok"); } else { warn!(action=action_str, services=?req.services, stderr, elapsed=?elapsed, "Compose CLI action failed"); }
     metrics::increment_compose_action(action_str, success);
-    Ok(ComposeResult { action: action_str.into(), services: req.services.clone(), success, status_code: code, stdout, stderr })
+    Ok(ComposeResult { action: action_str.into(), services: req.services.clone(), success, status_code: code, stdout, stderr, containers: None, queue_position: None })
 }
 
 
-pub fn run_compose(
-    file: &str,
-    project: Option<&str>,
-    action: ComposeAction,
-    services: &[String],
-    detach: bool,
-    json: bool,
-    tail: Option<u32>,
-) -> Result<i32> {
-    let mut args: Vec<String> = vec!["compose".into(), "-f".into(), file.into()];
-    if let Some(project) = project.filter(|p| !p.is_empty()) {
-        args.push("-p".into());
-        args.push(project.into());
-    }
-
-    let action_str = action.as_str();
-    args.push(action_str.into());
-
-    // Specific flags per action
-    match action {
-        ComposeAction::Up => {
-            if detach { args.push("-d".into()); }
-        }
-        ComposeAction::Logs => {
-            if detach { args.push("-f".into()); } // follow
-            if let Some(t) = tail { args.push("--tail".into()); args.push(t.to_string()); }
-        }
-        _ => {}
-    }
-
-    // Add services last
-    for s in services { args.push(s.clone()); }
+/// Run `req` through the compose CLI for the `compose` subcommand, printing the result.
+pub fn run_compose(req: &ComposeRequest, json: bool) -> Result<i32> {
+    let (action_str, services) = (req.action.as_str(), &req.services);
+    let args = cli_args(req);
 
     debug!(?args, "Executing docker compose command");
 
     let start_time = std::time::Instant::now();
     let output = std::process::Command::new("docker")
         .args(&args)
+        .envs(&req.env)
         .output()
         .map_err(|e| anyhow!("Failed to invoke docker: {e}"))?;
 
@@ -227,7 +878,7 @@ pub fn run_compose(
     crate::metrics::observe_compose_action_duration(action_str, elapsed);
     metrics::increment_compose_action(action_str, success);
     if json {
-        let result = ComposeResult { action: action_str.into(), services: services.to_vec(), success, status_code: code, stdout, stderr };
+        let result = ComposeResult { action: action_str.into(), services: services.to_vec(), success, status_code: code, stdout, stderr, containers: None, queue_position: None };
         println!("{}", serde_json::to_string_pretty(&result)?);
     } else {
         println!("[compose:{action_str}] success={success} code={:?}\nSTDOUT:\n{}\nSTDERR:\n{}", code, stdout, stderr);
@@ -235,3 +886,142 @@ pub fn run_compose(
 
     Ok(code.unwrap_or(if success {0} else {1}))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_files_list_services_in_order() {
+        let yaml = r#"
+services:
+  db:
+    image: postgres:16
+    container_name: fks_postgres
+    healthcheck:
+      test: ["CMD", "pg_isready"]
+  api:
+    image: fks/api
+    depends_on:
+      db: { condition: service_healthy }
+    healthcheck:
+      test: ["NONE"]
+  worker:
+    depends_on: [api, db]
+"#;
+        let project = ComposeProject::parse(yaml, "fks").unwrap();
+        assert_eq!(project.names(), ["db", "api", "worker"]);
+        assert_eq!(project.container("db"), "fks_postgres");
+        assert_eq!(project.container("worker"), "fks-worker-1");
+        assert_eq!(project.service("api").unwrap().dependencies(), ["db"]);
+        assert_eq!(project.service("worker").unwrap().dependencies(), ["api", "db"]);
+        assert!(project.service("db").unwrap().healthcheck_test().is_some());
+        assert!(project.service("api").unwrap().healthcheck_test().is_none(), "NONE disables the image's healthcheck");
+        let all = project.names();
+        assert_eq!(project.start_order(&all).unwrap(), ["db", "api", "worker"]);
+        let listed: Vec<String> = ["worker", "db", "api"].map(String::from).to_vec();
+        assert_eq!(project.start_order(&listed).unwrap(), ["db", "api", "worker"], "dependencies move ahead of their dependents");
+        let partial: Vec<String> = ["worker", "api"].map(String::from).to_vec();
+        assert_eq!(project.start_order(&partial).unwrap(), ["api", "worker"], "unlisted dependencies are not waited for");
+        let cyclic = ComposeProject::parse("services:\n  a:\n    depends_on: [b]\n  b:\n    depends_on: [a]\n", "fks").unwrap();
+        assert!(cyclic.start_order(&cyclic.names()).unwrap_err().to_string().contains("dependency cycle between a, b"));
+        assert!(ComposeProject::parse("services:\n  api:\n    ports: 8080\n", "fks").is_err());
+    }
+
+    #[test]
+    fn down_flags_reach_the_compose_cli() {
+        let body = r#"{"action":"down","file":"stack.yml","project":"fks","volumes":true,"remove_orphans":true,"timeout":30}"#;
+        let req: ComposeRequest = serde_json::from_str(body).unwrap();
+        assert_eq!(req.down, DownOptions { volumes: true, remove_orphans: true, timeout: Some(30) });
+        assert_eq!(cli_args(&req), ["compose", "-f", "stack.yml", "-p", "fks", "down", "--volumes", "--remove-orphans", "--timeout", "30"]);
+        let plain: ComposeRequest = serde_json::from_str(r#"{"action":"down"}"#).unwrap();
+        assert_eq!(cli_args(&plain), ["compose", "-f", "docker-compose.yml", "down"]);
+    }
+
+    #[tokio::test]
+    async fn env_overlays_reach_the_compose_cli() {
+        let body = r#"{"action":"up","detach":true,"env_file":"envs/paper.env","env":{"TRADING_MODE":"paper"},"dry_run":true}"#;
+        let req: ComposeRequest = serde_json::from_str(body).unwrap();
+        assert_eq!(cli_args(&req), ["compose", "-f", "docker-compose.yml", "--env-file", "envs/paper.env", "up", "-d"]);
+        assert_eq!(req.env["TRADING_MODE"], "paper");
+        let bad: ComposeRequest = serde_json::from_str(r#"{"action":"up","env":{"BAD NAME":"x"},"dry_run":true}"#).unwrap();
+        assert!(bad.execute().await.unwrap_err().to_string().contains("`BAD NAME` is not a valid environment variable name"));
+        assert!(is_variable_name("_FKS_1") && !is_variable_name("1FKS") && !is_variable_name("FKS-MODE") && !is_variable_name(""));
+    }
+
+    #[test]
+    fn build_sections_take_both_forms() {
+        std::env::set_var("FKS_TEST_BUILD_SHA", "abc123");
+        let yaml = r#"
+services:
+  api:
+    build: ./api
+  worker:
+    image: registry.example.com/fks/worker:dev
+    build:
+      context: ./worker
+      dockerfile: docker/Dockerfile
+      target: runtime
+      args: ["PYTHON=3.12", "FKS_TEST_BUILD_SHA", "FKS_TEST_UNSET_ARG"]
+  web:
+    build:
+      args: { NODE: 20, DEBUG: null, FKS_TEST_BUILD_SHA: }
+  redis:
+    image: redis:7
+"#;
+        let project = ComposeProject::parse(yaml, "fks").unwrap();
+        let build = |svc: &str| project.service(svc).unwrap().build.clone();
+        assert_eq!(build("api"), Some(ComposeBuild { context: "./api".into(), ..Default::default() }));
+        let worker = build("worker").unwrap();
+        assert_eq!((worker.dockerfile.as_deref(), worker.target.as_deref()), (Some("docker/Dockerfile"), Some("runtime")));
+        assert_eq!(worker.args, BTreeMap::from([("PYTHON".into(), "3.12".into()), ("FKS_TEST_BUILD_SHA".into(), "abc123".into())]));
+        let web = build("web").unwrap();
+        assert_eq!(web.context, ".");
+        assert_eq!(web.args, BTreeMap::from([("NODE".into(), "20".into()), ("FKS_TEST_BUILD_SHA".into(), "abc123".into())]));
+        assert_eq!(build("redis"), None);
+        assert_eq!((project.image("api"), project.image("worker")), ("fks-api".into(), "registry.example.com/fks/worker:dev".into()));
+
+        let body = r#"{"action":"build","services":["api"],"no_cache":true,"build_args":{"GIT_SHA":"abc123"},"target":"test"}"#;
+        let req: ComposeRequest = serde_json::from_str(body).unwrap();
+        assert_eq!(cli_args(&req), ["compose", "-f", "docker-compose.yml", "build", "--no-cache", "--build-arg", "GIT_SHA=abc123", "api"]);
+        assert_eq!(req.target.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn pulls_split_image_references() {
+        let body = r#"{"action":"pull","services":["fks_api"],"registries":[{"host":"evil.example.com","token":"x"}]}"#;
+        let req: ComposeRequest = serde_json::from_str(body).unwrap();
+        assert!(req.registries.is_empty(), "logins come from the config, not the request");
+        assert_eq!(split_reference("registry.example.com:5000/fks/api:1.2"), ("registry.example.com:5000/fks/api", "1.2"));
+        assert_eq!(split_reference("registry.example.com:5000/fks/api"), ("registry.example.com:5000/fks/api", "latest"));
+        assert_eq!(split_reference("nginx@sha256:abc"), ("nginx", "sha256:abc"));
+    }
+
+    #[tokio::test]
+    async fn exec_runs_a_command_in_one_service() {
+        let body = r#"{"action":"exec","services":["fks_api"],"command":["alembic","upgrade","head"],"dry_run":true}"#;
+        let req: ComposeRequest = serde_json::from_str(body).unwrap();
+        assert_eq!(cli_args(&req), ["compose", "-f", "docker-compose.yml", "exec", "-T", "fks_api", "alembic", "upgrade", "head"]);
+        assert!(req.execute().await.unwrap().success);
+        for body in [r#"{"action":"exec","services":["fks_api"],"dry_run":true}"#, r#"{"action":"exec","command":["ls"],"dry_run":true}"#] {
+            let req: ComposeRequest = serde_json::from_str(body).unwrap();
+            assert!(req.execute().await.unwrap_err().to_string().contains("exactly one service and a command"));
+        }
+    }
+
+    #[test]
+    fn containers_are_recreated_from_the_definition_they_were_created_from() {
+        let mut labels: HashMap<String, String> = [
+            (COMPOSE_PROJECT_LABEL, "fks"),
+            (COMPOSE_SERVICE_LABEL, "api"),
+            (COMPOSE_WORKING_DIR_LABEL, "/srv/fks"),
+            (COMPOSE_CONFIG_FILES_LABEL, "/srv/fks/docker-compose.yml,/srv/fks/docker-compose.prod.yml"),
+        ].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(recreate_args("fks_api", &labels, true, 10).unwrap(), [
+            "compose", "-p", "fks", "--project-directory", "/srv/fks", "-f", "/srv/fks/docker-compose.yml", "-f", "/srv/fks/docker-compose.prod.yml",
+            "up", "-d", "--force-recreate", "--no-deps", "--renew-anon-volumes", "--timeout", "10", "api",
+        ]);
+        labels.remove(COMPOSE_SERVICE_LABEL);
+        assert!(recreate_args("fks_api", &labels, false, 10).unwrap_err().to_string().contains("not created by compose"));
+    }
+}
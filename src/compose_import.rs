@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::compose::{ComposeProject, ComposeService};
+use crate::config::Config;
+use crate::models::{ProbeConfig, ProbeMode, ServiceConfig, ServiceType};
+
+/// Build `ServiceConfig` entries from a compose file. Container names follow compose's
+/// `<project>-<service>-1` unless `container_name` is set.
+pub fn services_from_project(file: &ComposeProject) -> Vec<ServiceConfig> {
+    let names = file.names();
+    let mut services = Vec::new();
+    for (id, svc) in &file.services {
+        let id = id.as_str();
+        let depends_on = svc.dependencies().into_iter().filter(|d| names.contains(d)).collect();
+        // A healthcheck that is not an HTTP call (pg_isready, redis-cli ping, ...) is read through Docker instead
+        let (health_endpoint, probes) = match svc.healthcheck_test() {
+            Some(test) if healthcheck_url(test).is_none() => (String::new(), vec![ProbeConfig::Docker { container: None, name: Some("compose_healthcheck".into()) }]),
+            _ => (guess_health_endpoint(id, svc), vec![]),
+        };
+        services.push(ServiceConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            health_endpoint,
+            service_type: guess_service_type(id, svc.image.as_deref()),
+            docker_container: Some(file.container(id)),
+            expected_response_time_ms: 500,
+            critical: false,
+            check_interval_seconds: None,
+            heartbeat_window_seconds: None,
+            depends_on,
+            tags: vec![],
+            probes,
+            probe_mode: ProbeMode::All,
+            labels: Default::default(),
+            group: None,
+            metadata: Default::default(),
+            auto_update: false,
+            kubernetes: None,
+            systemd_unit: None,
+            auto_restart: None,
+            drain: None,
+            metrics: None,
+            docker_host: None,
+        });
+    }
+    services
+}
+
+/// Render a starting monitor.toml: default monitoring/alert settings plus the imported services.
+pub fn import_compose(path: &Path, project: Option<&str>) -> Result<String> {
+    let services = services_from_project(&ComposeProject::load(path, project)?);
+    let config = Config { services, ..Config::default() };
+    let body = toml::to_string_pretty(&config).context("rendering monitor.toml")?;
+    Ok(format!(
+        "# Generated by `fks_master config import --compose {}`.\n# Health endpoints and service types are guesses: review them, then set `critical` and latency budgets.\n\n{body}",
+        path.display()
+    ))
+}
+
+/// Prefer the URL probed by the compose healthcheck; otherwise `/health` on the first container port.
+fn guess_health_endpoint(id: &str, svc: &ComposeService) -> String {
+    if let Some((port, path)) = svc.healthcheck.as_ref().and_then(|h| h.test.as_ref()).and_then(healthcheck_url) {
+        return match port {
+            Some(port) => format!("http://{id}:{port}{path}"),
+            None => format!("http://{id}{path}"),
+        };
+    }
+    match svc.ports.iter().chain(&svc.expose).find_map(container_port) {
+        Some(port) => format!("http://{id}:{port}/health"),
+        None => format!("http://{id}/health"),
+    }
+}
+
+/// Extract (port, path) from an http URL in a healthcheck `test` (string or CMD list).
+fn healthcheck_url(test: &serde_yaml::Value) -> Option<(Option<u16>, String)> {
+    let command = match test {
+        serde_yaml::Value::String(s) => s.clone(),
+        serde_yaml::Value::Sequence(parts) => parts.iter().filter_map(|p| p.as_str()).collect::<Vec<_>>().join(" "),
+        _ => return None,
+    };
+    let start = command.find("http://").or_else(|| command.find("https://"))?;
+    let url = command[start..].split(|c: char| c.is_whitespace() || c == '"' || c == '\'').next()?;
+    let rest = url.split_once("://")?.1;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], rest[i..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    let port = authority.rsplit_once(':').and_then(|(_, p)| p.parse().ok());
+    Some((port, path))
+}
+
+/// Container-side port from short (`"8080:80"`, `"127.0.0.1:8080:80/tcp"`, `"80"`) or long (`target: 80`) syntax.
+fn container_port(value: &serde_yaml::Value) -> Option<u16> {
+    match value {
+        serde_yaml::Value::Number(n) => n.as_u64().and_then(|p| u16::try_from(p).ok()),
+        serde_yaml::Value::String(s) => {
+            let spec = s.split('/').next()?;
+            let container = spec.rsplit(':').next()?;
+            // Ranges ("8000-8010") use their first port
+            container.split('-').next()?.parse().ok()
+        }
+        serde_yaml::Value::Mapping(m) => m.get("target").and_then(container_port),
+        _ => None,
+    }
+}
+
+fn guess_service_type(id: &str, image: Option<&str>) -> ServiceType {
+    let hay = format!("{} {}", id, image.unwrap_or_default()).to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| hay.contains(w));
+    if has(&["nginx", "traefik", "haproxy", "caddy"]) {
+        ServiceType::Nginx
+    } else if has(&["postgres", "mysql", "mariadb", "redis", "mongo", "timescale", "database", "_db", "-db"]) {
+        ServiceType::Database
+    } else if has(&["auth", "keycloak"]) {
+        ServiceType::Auth
+    } else if has(&["worker", "celery", "queue"]) {
+        ServiceType::Worker
+    } else if has(&["web", "ui", "frontend", "dashboard"]) {
+        ServiceType::Web
+    } else if has(&["engine"]) {
+        ServiceType::Engine
+    } else if has(&["train"]) {
+        ServiceType::Training
+    } else if has(&["transform"]) {
+        ServiceType::Transformer
+    } else if has(&["execution", "executor"]) {
+        ServiceType::Execution
+    } else if has(&["config"]) {
+        ServiceType::Config
+    } else {
+        ServiceType::Api
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPOSE: &str = r#"
+services:
+  fks_api:
+    image: fks/api:latest
+    ports: ["8000:8000"]
+    depends_on:
+      fks_db: { condition: service_healthy }
+      external_thing: { condition: service_started }
+    healthcheck:
+      test: ["CMD-SHELL", "curl -f http://localhost:8000/api/health || exit 1"]
+  fks_db:
+    image: postgres:16
+    container_name: fks_postgres
+    expose: ["5432"]
+  fks_cache:
+    image: redis:7
+    healthcheck:
+      test: ["CMD", "redis-cli", "ping"]
+  fks_worker:
+    image: fks/worker
+    depends_on: [fks_api, fks_db]
+  proxy:
+    image: nginx:alpine
+    ports:
+      - target: 80
+        published: 8080
+"#;
+
+    #[test]
+    fn derives_services_from_compose() {
+        let services = services_from_project(&ComposeProject::parse(COMPOSE, "fks").unwrap());
+        let ids: Vec<&str> = services.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["fks_api", "fks_db", "fks_cache", "fks_worker", "proxy"], "compose order is kept");
+
+        let api = &services[0];
+        assert_eq!(api.health_endpoint, "http://fks_api:8000/api/health");
+        assert_eq!(api.docker_container.as_deref(), Some("fks-fks_api-1"));
+        assert_eq!(api.depends_on, vec!["fks_db"], "dependencies outside the file are dropped");
+        assert!(matches!(api.service_type, ServiceType::Api));
+
+        let db = &services[1];
+        assert_eq!(db.docker_container.as_deref(), Some("fks_postgres"));
+        assert_eq!(db.health_endpoint, "http://fks_db:5432/health");
+        assert!(matches!(db.service_type, ServiceType::Database));
+
+        let cache = &services[2];
+        assert_eq!(cache.health_endpoint, "", "non-HTTP healthchecks are read through Docker");
+        assert_eq!(cache.probes, vec![ProbeConfig::Docker { container: None, name: Some("compose_healthcheck".into()) }]);
+
+        assert_eq!(services[3].depends_on, vec!["fks_api", "fks_db"]);
+        assert!(matches!(services[3].service_type, ServiceType::Worker));
+        assert_eq!(services[4].health_endpoint, "http://proxy:80/health");
+        assert!(matches!(services[4].service_type, ServiceType::Nginx));
+    }
+
+    #[test]
+    fn generated_toml_loads_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("docker-compose.yml");
+        std::fs::write(&path, COMPOSE).unwrap();
+        let rendered = import_compose(&path, Some("fks")).unwrap();
+        let config = Config::from_toml_str(&rendered).unwrap();
+        assert_eq!(config.services.len(), 5);
+        assert_eq!(config.services[3].depends_on, vec!["fks_api", "fks_db"]);
+        assert_eq!(crate::config_check::check(&config), vec![]);
+    }
+}
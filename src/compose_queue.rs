@@ -0,0 +1,192 @@
+use crate::compose::ComposeAction;
+use crate::metrics;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Line of compose actions. At most `max_concurrent_actions` run at once, and actions that change a
+/// project (all but `ps`, `logs` and `exec`) run one at a time per project, in the order they came in.
+#[derive(Clone, Default)]
+pub struct ComposeQueue {
+    state: Arc<Mutex<State>>,
+    changed: Arc<Notify>,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    running: Vec<Entry>,
+    waiting: Vec<Entry>,
+}
+
+/// An action that runs or waits, as listed by `GET /api/compose/queue`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    #[serde(skip)]
+    id: u64,
+    #[serde(skip)]
+    exclusive: bool,
+    pub action: ComposeAction,
+    pub project: String,
+    pub services: Vec<String>,
+    /// When it started running, or when it got in line
+    pub since: DateTime<Utc>,
+    /// 1-based place in line of a waiting action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    pub running: Vec<Entry>,
+    pub waiting: Vec<Entry>,
+}
+
+/// A running action's slot, given back when dropped.
+pub struct Permit {
+    queue: ComposeQueue,
+    id: u64,
+    position: Option<usize>,
+}
+
+impl Permit {
+    /// Place in line on arrival; `None` when the action could start right away.
+    pub fn queue_position(&self) -> Option<usize> {
+        self.position
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut state = self.queue.state.lock().unwrap();
+        state.running.retain(|e| e.id != self.id);
+        metrics::set_compose_actions_inflight(state.running.len());
+        drop(state);
+        self.queue.changed.notify_waiters();
+    }
+}
+
+/// Takes an abandoned request (e.g. the client went away) out of line.
+struct InLine<'a> {
+    queue: &'a ComposeQueue,
+    id: Option<u64>,
+}
+
+impl Drop for InLine<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.id else { return };
+        self.queue.state.lock().unwrap().waiting.retain(|e| e.id != id);
+        self.queue.changed.notify_waiters();
+    }
+}
+
+impl ComposeQueue {
+    /// Wait for a slot for `action` on `project`, with at most `limit` actions running.
+    pub async fn acquire(&self, action: ComposeAction, project: &str, services: &[String], limit: usize) -> Permit {
+        let (id, position) = {
+            let mut state = self.state.lock().unwrap();
+            let id = state.next_id;
+            state.next_id += 1;
+            let exclusive = !matches!(action, ComposeAction::Ps | ComposeAction::Logs | ComposeAction::Exec);
+            state.waiting.push(Entry { id, exclusive, action, project: project.to_string(), services: services.to_vec(), since: Utc::now(), position: None });
+            (id, state.waiting.len())
+        };
+        let mut in_line = InLine { queue: self, id: Some(id) };
+        let mut waited = false;
+        loop {
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.try_start(id, limit.max(1)) {
+                in_line.id = None;
+                return Permit { queue: self.clone(), id, position: waited.then_some(position) };
+            }
+            if !waited {
+                tracing::info!(action = action.as_str(), project, position, "Compose action waiting for a slot");
+                waited = true;
+            }
+            notified.await;
+        }
+    }
+
+    /// Move `id` from the line to the running actions if nothing holds it back.
+    fn try_start(&self, id: u64, limit: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(index) = state.waiting.iter().position(|e| e.id == id) else { return false };
+        let locked = |entry: &Entry, earlier: &[Entry]| entry.exclusive
+            && state.running.iter().chain(earlier).any(|other| other.exclusive && other.project == entry.project);
+        // Earlier arrivals that could start get the free slots first
+        let ahead = (0..index).filter(|&i| !locked(&state.waiting[i], &state.waiting[..i])).count();
+        if locked(&state.waiting[index], &state.waiting[..index]) || state.running.len() + ahead >= limit {
+            return false;
+        }
+        let mut entry = state.waiting.remove(index);
+        entry.since = Utc::now();
+        state.running.push(entry);
+        metrics::set_compose_actions_inflight(state.running.len());
+        true
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let state = self.state.lock().unwrap();
+        let waiting = state.waiting.iter().enumerate().map(|(i, e)| Entry { position: Some(i + 1), ..e.clone() }).collect();
+        Snapshot { running: state.running.clone(), waiting }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    #[tokio::test]
+    async fn conflicting_actions_on_a_project_run_in_turn() {
+        let queue = ComposeQueue::default();
+        let up = queue.acquire(ComposeAction::Up, "fks", &[], 4).await;
+        assert_eq!(up.queue_position(), None);
+
+        let waiting = queue.clone();
+        let down = tokio::spawn(async move { waiting.acquire(ComposeAction::Down, "fks", &[], 4).await.queue_position() });
+        settle().await;
+        // Reads and other projects are not held up
+        let ps = queue.acquire(ComposeAction::Ps, "fks", &[], 4).await;
+        let other = queue.acquire(ComposeAction::Up, "lab", &[], 4).await;
+        let snapshot = queue.snapshot();
+        assert_eq!(snapshot.running.len(), 3);
+        assert_eq!(snapshot.waiting.iter().map(|e| (e.action, e.position)).collect::<Vec<_>>(), [(ComposeAction::Down, Some(1))]);
+        drop((ps, other));
+        settle().await;
+        assert!(!down.is_finished(), "down waits for up");
+        drop(up);
+        assert_eq!(down.await.unwrap(), Some(1));
+        assert!(queue.snapshot().running.is_empty());
+    }
+
+    #[tokio::test]
+    async fn slots_go_to_earlier_arrivals() {
+        let queue = ComposeQueue::default();
+        let first = queue.acquire(ComposeAction::Restart, "a", &[], 1).await;
+        let mut later = Vec::new();
+        for project in ["b", "c"] {
+            let queue = queue.clone();
+            later.push(tokio::spawn(async move {
+                let permit = queue.acquire(ComposeAction::Restart, project, &[], 1).await;
+                settle().await;
+                (project, permit.queue_position())
+            }));
+            settle().await;
+        }
+        // A request that gives up leaves the line
+        let abandoned = tokio::time::timeout(Duration::from_millis(10), queue.acquire(ComposeAction::Pull, "d", &[], 1)).await;
+        assert!(abandoned.is_err());
+        assert_eq!(queue.snapshot().waiting.len(), 2);
+        drop(first);
+        assert_eq!(later.remove(0).await.unwrap(), ("b", Some(1)));
+        assert_eq!(later.remove(0).await.unwrap(), ("c", Some(2)));
+    }
+}
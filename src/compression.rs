@@ -0,0 +1,47 @@
+use axum::body::HttpBody;
+use axum::http::Response;
+use tower_http::compression::predicate::{And, DefaultPredicate, Predicate};
+use tower_http::compression::CompressionLayer;
+
+use crate::config::CompressionConfig;
+use crate::monitor::MonitorHandle;
+
+/// Endpoint a compression layer sits on, each with its own switch in `[compression]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    Services,
+    Events,
+    Metrics,
+}
+
+impl Route {
+    fn enabled(self, cfg: &CompressionConfig) -> bool {
+        cfg.enabled && match self {
+            Route::Services => cfg.services,
+            Route::Events => cfg.events,
+            Route::Metrics => cfg.metrics,
+        }
+    }
+}
+
+/// Compresses a response of `route` while the live config allows it and the body is at least
+/// `min_size_bytes`; bodies of unknown size (streams) are compressed too.
+#[derive(Clone)]
+pub struct Toggle {
+    monitor: MonitorHandle,
+    route: Route,
+}
+
+impl Predicate for Toggle {
+    fn should_compress<B: HttpBody>(&self, response: &Response<B>) -> bool {
+        let config = self.monitor.config();
+        let cfg = &config.compression;
+        self.route.enabled(cfg) && response.body().size_hint().exact().is_none_or(|size| size >= cfg.min_size_bytes)
+    }
+}
+
+/// gzip or brotli, whichever the client prefers, for responses of `route`. Images, gRPC and event
+/// streams are left alone as by default.
+pub fn layer(monitor: &MonitorHandle, route: Route) -> CompressionLayer<And<DefaultPredicate, Toggle>> {
+    CompressionLayer::new().compress_when(DefaultPredicate::new().and(Toggle { monitor: monitor.clone(), route }))
+}
@@ -1,18 +1,93 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use tokio::fs;
 
-use crate::models::{ServiceConfig, ServiceType};
+use crate::compose::ComposeRequest;
+use crate::healing::HealingExpr;
+use crate::models::{EventType, ProbeMode, ServiceConfig, ServiceType, Severity};
+use crate::rules::RuleExpr;
+use crate::schedule::CronSchedule;
+use crate::silence::MaintenanceWindow;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
+    /// May be empty when services come from discovery (e.g. `[discovery] compose_file`)
+    #[serde(default)]
     pub services: Vec<ServiceConfig>,
     pub monitoring: MonitoringConfig,
     pub alerts: AlertConfig,
+    #[serde(default)]
+    pub restart_storm: RestartStormConfig,
+    #[serde(default)]
+    pub restart_backoff: RestartBackoffConfig,
+    #[serde(default)]
+    pub restart_verification: RestartVerificationConfig,
+    #[serde(default)]
+    pub restart_rate_limit: RestartRateLimitConfig,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+    /// Periodic upload of history to S3-compatible storage (unset = no archiving)
+    #[serde(default)]
+    pub archive: Option<ArchiveConfig>,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    /// Registry checks for newer builds of the images services run
+    #[serde(default)]
+    pub image_updates: ImageUpdatesConfig,
+    /// API access for services with a `kubernetes` target
+    #[serde(default)]
+    pub kubernetes: KubernetesConfig,
+    /// Remote Docker daemons, referenced by name from a service's `docker_host`
+    #[serde(default)]
+    pub hosts: HashMap<String, DockerHostConfig>,
+    /// Limits of `/api/compose`
+    #[serde(default)]
+    pub compose: ComposeConfig,
+    /// Arm-then-confirm for restarts and other destructive API actions
+    #[serde(default)]
+    pub confirmation: ConfirmationConfig,
+    /// Credentials for private registries, used by compose pulls/pushes and image update checks
+    #[serde(default)]
+    pub registries: Vec<RegistryConfig>,
+    /// Remediation run automatically on matching events
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+    /// Restarts run on a cron schedule
+    #[serde(default)]
+    pub schedules: Vec<ScheduleConfig>,
+    /// Self-healing rules the monitor evaluates alongside the alert rules
+    #[serde(default)]
+    pub healing: Vec<HealingRule>,
+    /// Prometheus server behind `GET /api/promql` (unset = endpoint disabled)
+    #[serde(default)]
+    pub prometheus: Option<PrometheusConfig>,
+    /// Swagger UI over the OpenAPI document served at `/api/openapi.json`
+    #[serde(default)]
+    pub openapi: OpenApiConfig,
+    /// GraphiQL over the GraphQL endpoint at `/api/graphql`
+    #[serde(default)]
+    pub graphql: GraphqlConfig,
+    /// Services' own metrics re-exposed under `/metrics/federated`
+    #[serde(default)]
+    pub federation: FederationConfig,
+    /// How services and their dependencies weigh into `healthScore` in `/health/aggregate`
+    #[serde(default)]
+    pub health_score: HealthScoreConfig,
+    /// gzip/brotli for the large read endpoints, for clients that send `Accept-Encoding`
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    /// Delivery of events to subscriptions registered through `POST /api/webhooks`
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct MonitoringConfig {
     pub check_interval_seconds: u64,
     pub timeout_seconds: u64,
@@ -20,29 +95,1088 @@ pub struct MonitoringConfig {
     pub batch_size: usize,
     #[serde(default = "default_enable_docker_stats")]
     pub enable_docker_stats: bool,
+    /// Seconds a container gets to stop gracefully when it is restarted before Docker kills it
+    #[serde(default = "default_restart_stop_timeout_seconds")]
+    pub restart_stop_timeout_seconds: u64,
+    /// Follow the Docker event stream so container exits, OOM kills and healthcheck changes show up immediately
+    #[serde(default = "default_docker_events")]
+    pub docker_events: bool,
+    /// Compose project whose containers are in scope (matched via the `com.docker.compose.project` label)
+    #[serde(default)]
+    pub compose_project: Option<String>,
+    /// Probe every endpoint once at startup and log a reachable/unreachable report
+    #[serde(default)]
+    pub validate_endpoints_on_startup: bool,
+    /// File backing the status transitions behind `/api/services/{id}/history` (unset = in-memory only)
+    #[serde(default)]
+    pub uptime_history_path: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AlertConfig {
     pub enable_notifications: bool,
     pub high_latency_threshold_ms: u64,
     pub consecutive_failures_threshold: u32,
+    /// May use alert placeholders, e.g. `https://n8n.local/webhook/fks?team={label.team}&severity={severity}`
     pub webhook_url: Option<String>,
+    /// JSON body template posted instead of the alert payload; placeholders in string values are filled in
+    #[serde(default)]
+    pub webhook_body: Option<serde_json::Value>,
+    #[serde(default)]
+    pub slack: Option<SlackConfig>,
+    #[serde(default)]
+    pub email: Option<EmailConfig>,
+    /// File backing the undelivered-alert queue (unset = in-memory only)
+    #[serde(default = "default_dead_letter_path")]
+    pub dead_letter_path: Option<String>,
+    /// File backing the alert history served by `/api/alerts/history` (unset = in-memory only)
+    #[serde(default = "default_history_path")]
+    pub history_path: Option<String>,
+    /// Alerts kept in the history; the oldest are dropped beyond this
+    #[serde(default = "default_history_capacity")]
+    pub history_capacity: usize,
+    /// Threshold rules evaluated against per-service metrics (see [`AlertRule`])
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+    #[serde(default = "default_rule_evaluation_interval_seconds")]
+    pub rule_evaluation_interval_seconds: u64,
+    #[serde(default)]
+    pub throttle: AlertThrottleConfig,
+    #[serde(default)]
+    pub pagerduty: Option<PagerDutyConfig>,
+    /// Escalation steps for critical services that stay Unhealthy after the first notification
+    #[serde(default)]
+    pub escalation: Vec<EscalationStep>,
+    /// Re-notify every N minutes while a service stays Unhealthy (unset = no reminders)
+    #[serde(default)]
+    pub reminder_interval_minutes: Option<u64>,
+    /// Recurring windows during which matching services produce no notifications
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    /// Severity/tag based routing; alerts matching no route use the default channel selection
+    #[serde(default)]
+    pub routes: Vec<AlertRoute>,
+}
+
+/// Sends alerts matching all given criteria (empty = any) to `channels`. Routes are tried in
+/// order and the first match wins unless it sets `continue = true`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AlertRoute {
+    #[serde(default)]
+    pub severity: Vec<Severity>,
+    /// Service must carry at least one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Channel names: "webhook", "slack", "email", "pagerduty"
+    pub channels: Vec<String>,
+    /// Post Slack messages for this route to a different channel (e.g. a low-noise one)
+    #[serde(default)]
+    pub slack_channel: Option<String>,
+    /// Webhook URL template for this route (overrides `alerts.webhook_url`)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Webhook body template for this route (overrides `alerts.webhook_body`)
+    #[serde(default)]
+    pub webhook_body: Option<serde_json::Value>,
+    #[serde(default, rename = "continue")]
+    pub continue_matching: bool,
+}
+
+impl AlertRoute {
+    pub fn matches(&self, severity: Severity, service: Option<&ServiceConfig>) -> bool {
+        (self.severity.is_empty() || self.severity.contains(&severity))
+            && (self.tags.is_empty() || service.is_some_and(|s| s.tags.iter().any(|t| self.tags.contains(t))))
+            && (self.services.is_empty() || service.is_some_and(|s| self.services.contains(&s.id)))
+    }
+}
+
+/// One escalation step: notify `channels` once the outage has lasted `after_minutes`
+/// since the first notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct EscalationStep {
+    pub after_minutes: u64,
+    /// Channel names: "webhook", "slack", "email", "pagerduty"
+    pub channels: Vec<String>,
+}
+
+/// PagerDuty Events API v2; only used as an escalation target.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PagerDutyConfig {
+    pub routing_key: String,
+    #[serde(default = "default_pagerduty_events_url")]
+    pub events_url: String,
+}
+
+/// Deduplication, per-service cooldown and burst batching applied before notifications go out.
+/// Setting a value to 0 disables that mechanism.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AlertThrottleConfig {
+    /// Repeats of a service's previous alert within this window are dropped
+    #[serde(default = "default_dedup_window_seconds")]
+    pub dedup_window_seconds: u64,
+    /// After notifying about a service, further alerts for it are held for this long
+    #[serde(default = "default_service_cooldown_seconds")]
+    pub service_cooldown_seconds: u64,
+    /// Notifications within one batch window beyond which new alerts are held
+    #[serde(default = "default_burst_threshold")]
+    pub burst_threshold: usize,
+    /// Held alerts are released as one summary this long after the first was held
+    #[serde(default = "default_batch_window_seconds")]
+    pub batch_window_seconds: u64,
+}
+
+impl Default for AlertThrottleConfig {
+    fn default() -> Self {
+        Self {
+            dedup_window_seconds: default_dedup_window_seconds(),
+            service_cooldown_seconds: default_service_cooldown_seconds(),
+            burst_threshold: default_burst_threshold(),
+            batch_window_seconds: default_batch_window_seconds(),
+        }
+    }
+}
+
+/// Metric threshold rule raising a `SystemAlert` while its condition holds, e.g.
+/// `expr = "error_rate > 5/min for 3m"`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AlertRule {
+    pub name: String,
+    /// `<metric> <op> <threshold> [for <duration>]`
+    #[schemars(with = "String")]
+    pub expr: RuleExpr,
+    /// Service ids the rule applies to (empty = all services)
+    #[serde(default)]
+    pub services: Vec<String>,
+    #[serde(default = "default_rule_severity")]
+    pub severity: String,
+}
+
+/// Slack notifications via an incoming webhook, or a bot token posting with `chat.postMessage`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SlackConfig {
+    pub webhook_url: Option<String>,
+    pub bot_token: Option<String>,
+    /// Default channel (required with bot_token; optional override for webhooks)
+    pub channel: Option<String>,
+    /// Per-service channel overrides keyed by service id
+    #[serde(default)]
+    pub channel_overrides: std::collections::HashMap<String, String>,
+    /// Base dashboard URL linked from messages
+    pub dashboard_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Upgrade a plain connection with STARTTLS (typically port 587)
+    #[default]
+    Starttls,
+    /// Implicit TLS (typically port 465)
+    Tls,
+    /// Plaintext (local relays / testing only)
+    None,
+}
+
+/// SMTP email notifications for critical-service outages plus an optional daily digest.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    pub smtp_port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Placeholders: {event_type} {service_id} {service_name} {old_status} {new_status} {message} {timestamp}
+    #[serde(default = "default_email_subject")]
+    pub subject_template: String,
+    #[serde(default = "default_email_body")]
+    pub body_template: String,
+    /// UTC hour (0-23) at which the daily health summary is sent (unset = no digest)
+    pub digest_hour_utc: Option<u32>,
+}
+
+impl SlackConfig {
+    pub fn channel_for(&self, service_id: Option<&str>) -> Option<&str> {
+        service_id
+            .and_then(|id| self.channel_overrides.get(id))
+            .or(self.channel.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// Fleet-wide restart storm detection: many distinct services restarting in a short window
+/// usually points at a host or shared dependency problem rather than the services themselves.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RestartStormConfig {
+    /// Distinct services restarted within the window that constitute a storm
+    #[serde(default = "default_storm_threshold")]
+    pub threshold: usize,
+    #[serde(default = "default_storm_window_seconds")]
+    pub window_seconds: u64,
+    /// How long automatic restarts stay suppressed after a storm is detected
+    #[serde(default = "default_storm_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+impl Default for RestartStormConfig {
+    fn default() -> Self {
+        Self {
+            threshold: default_storm_threshold(),
+            window_seconds: default_storm_window_seconds(),
+            cooldown_seconds: default_storm_cooldown_seconds(),
+        }
+    }
+}
+
+/// Per-service restart backoff, for manual and automatic restarts alike: each restart that doesn't
+/// keep the service up doubles the wait before the next one, and after `max_restarts` the circuit
+/// opens and restarts are refused until someone resets it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RestartBackoffConfig {
+    /// Wait after the first restart; doubled after each further one
+    #[serde(default = "default_backoff_initial_delay_seconds")]
+    pub initial_delay_seconds: u64,
+    #[serde(default = "default_backoff_max_delay_seconds")]
+    pub max_delay_seconds: u64,
+    /// Restarts without recovery after which the circuit opens
+    #[serde(default = "default_backoff_max_restarts")]
+    pub max_restarts: u32,
+    /// How long a service must stay healthy after a restart for its count to start over
+    #[serde(default = "default_backoff_reset_after_seconds")]
+    pub reset_after_seconds: u64,
+}
+
+impl Default for RestartBackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay_seconds: default_backoff_initial_delay_seconds(),
+            max_delay_seconds: default_backoff_max_delay_seconds(),
+            max_restarts: default_backoff_max_restarts(),
+            reset_after_seconds: default_backoff_reset_after_seconds(),
+        }
+    }
+}
+
+/// After a successful restart the service is checked until it is Healthy again, or until
+/// `timeout_seconds`; a restart that did not bring it back raises an alert.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RestartVerificationConfig {
+    #[serde(default = "default_restart_verification_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_restart_verification_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Time between checks while waiting
+    #[serde(default = "default_restart_verification_poll_interval_seconds")]
+    pub poll_interval_seconds: u64,
+}
+
+impl Default for RestartVerificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_restart_verification_enabled(),
+            timeout_seconds: default_restart_verification_timeout_seconds(),
+            poll_interval_seconds: default_restart_verification_poll_interval_seconds(),
+        }
+    }
+}
+
+/// A cap on restarts per service, however they are asked for, so a misbehaving dashboard or script
+/// can't keep restarting a service. Restarts beyond it are refused until the oldest leaves the window.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RestartRateLimitConfig {
+    /// Restarts allowed per service within the window (0 = no limit)
+    #[serde(default)]
+    pub max_restarts: u32,
+    #[serde(default = "default_restart_rate_limit_window_seconds")]
+    pub window_seconds: u64,
+}
+
+impl Default for RestartRateLimitConfig {
+    fn default() -> Self {
+        Self { max_restarts: 0, window_seconds: default_restart_rate_limit_window_seconds() }
+    }
+}
+
+/// The interactive API reference at `/api/docs`. The OpenAPI document itself is always served.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OpenApiConfig {
+    #[serde(default = "default_swagger_ui")]
+    pub swagger_ui: bool,
+    /// Where the page loads the Swagger UI scripts and styles from; point it at a local copy of
+    /// `swagger-ui-dist` on hosts without internet access
+    #[serde(default = "default_swagger_ui_assets")]
+    pub swagger_ui_assets: String,
+}
+
+impl Default for OpenApiConfig {
+    fn default() -> Self {
+        Self { swagger_ui: default_swagger_ui(), swagger_ui_assets: default_swagger_ui_assets() }
+    }
+}
+
+/// The GraphiQL explorer served by `GET /api/graphql`. Queries and subscriptions are always served.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GraphqlConfig {
+    #[serde(default = "default_graphiql")]
+    pub graphiql: bool,
+}
+
+impl Default for GraphqlConfig {
+    fn default() -> Self {
+        Self { graphiql: default_graphiql() }
+    }
+}
+
+/// `/metrics/federated`, which scrapes the services with a `metrics` endpoint on every request.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FederationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Per scrape; a service that does not answer in time shows as `fks_federation_up 0`
+    #[serde(default = "default_federation_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Series passed on per service, so one chatty exporter cannot flood the response
+    #[serde(default = "default_federation_max_series_per_service")]
+    pub max_series_per_service: usize,
+}
+
+fn default_federation_timeout_seconds() -> u64 { 5 }
+fn default_federation_max_series_per_service() -> usize { 1000 }
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout_seconds: default_federation_timeout_seconds(),
+            max_series_per_service: default_federation_max_series_per_service(),
+        }
+    }
+}
+
+/// Which responses are compressed; read on every response, so a reload applies right away.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CompressionConfig {
+    #[serde(default = "default_compression_enabled")]
+    pub enabled: bool,
+    /// Smaller responses are sent as they are; compressing them costs more than it saves
+    #[serde(default = "default_compression_min_size_bytes")]
+    pub min_size_bytes: u64,
+    /// `GET /api/services`
+    #[serde(default = "default_compression_enabled")]
+    pub services: bool,
+    /// `GET /api/events`
+    #[serde(default = "default_compression_enabled")]
+    pub events: bool,
+    /// `/metrics` and `/metrics/federated`
+    #[serde(default = "default_compression_enabled")]
+    pub metrics: bool,
+}
+
+fn default_compression_enabled() -> bool { true }
+fn default_compression_min_size_bytes() -> u64 { 1024 }
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_compression_enabled(),
+            min_size_bytes: default_compression_min_size_bytes(),
+            services: default_compression_enabled(),
+            events: default_compression_enabled(),
+            metrics: default_compression_enabled(),
+        }
+    }
+}
+
+/// How events reach webhook subscriptions. Subscriptions themselves are registered through the API,
+/// not here.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebhooksConfig {
+    /// Where subscriptions are kept across restarts (unset = in memory only)
+    #[serde(default = "default_webhooks_store_path")]
+    pub store_path: Option<String>,
+    /// Attempts per event, the first included
+    #[serde(default = "default_webhooks_max_attempts")]
+    pub max_attempts: u32,
+    /// Wait before the first retry; it doubles for each further one
+    #[serde(default = "default_webhooks_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_webhooks_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+    #[serde(default = "default_webhooks_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_webhooks_store_path() -> Option<String> { Some("data/webhooks.json".into()) }
+fn default_webhooks_max_attempts() -> u32 { 5 }
+fn default_webhooks_initial_backoff_ms() -> u64 { 1000 }
+fn default_webhooks_max_backoff_secs() -> u64 { 60 }
+fn default_webhooks_timeout_seconds() -> u64 { 10 }
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            store_path: default_webhooks_store_path(),
+            max_attempts: default_webhooks_max_attempts(),
+            initial_backoff_ms: default_webhooks_initial_backoff_ms(),
+            max_backoff_secs: default_webhooks_max_backoff_secs(),
+            timeout_seconds: default_webhooks_timeout_seconds(),
+        }
+    }
+}
+
+/// The scoring model behind `healthScore` (see `health_score.rs`). Statuses count as 1 when
+/// healthy and 0 when unhealthy; paused services are left out.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HealthScoreConfig {
+    #[serde(default = "default_health_score_degraded")]
+    pub degraded: f64,
+    #[serde(default = "default_health_score_unknown")]
+    pub unknown: f64,
+    #[serde(default = "default_health_score_weight")]
+    pub weight: f64,
+    #[serde(default = "default_health_score_critical_weight")]
+    pub critical_weight: f64,
+    /// Per service id, overriding `weight` / `critical_weight`
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
+    /// Share of a dependency's shortfall passed on to each service depending on it: 0 ignores
+    /// `depends_on`, 1 makes a down dependency take its dependents down with it
+    #[serde(default = "default_health_score_dependency_impact")]
+    pub dependency_impact: f64,
+}
+
+fn default_health_score_degraded() -> f64 { 0.5 }
+fn default_health_score_unknown() -> f64 { 0.5 }
+fn default_health_score_weight() -> f64 { 1.0 }
+fn default_health_score_critical_weight() -> f64 { 3.0 }
+fn default_health_score_dependency_impact() -> f64 { 0.5 }
+
+impl Default for HealthScoreConfig {
+    fn default() -> Self {
+        Self {
+            degraded: default_health_score_degraded(),
+            unknown: default_health_score_unknown(),
+            weight: default_health_score_weight(),
+            critical_weight: default_health_score_critical_weight(),
+            weights: HashMap::new(),
+            dependency_impact: default_health_score_dependency_impact(),
+        }
+    }
+}
+
+/// Reconnect hints sent to WebSocket clients in the `server_shutdown` frame before the server closes
+/// their sockets, so dashboards can fail over instead of showing a disconnect.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebSocketConfig {
+    /// Suggested delay before reconnecting; clients should add jitter
+    #[serde(default = "default_ws_reconnect_after_ms")]
+    pub reconnect_after_ms: u64,
+    /// Other instance of an HA pair to connect to meanwhile, e.g. `wss://master-b.example.com/ws`
+    #[serde(default)]
+    pub alternate_endpoint: Option<String>,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self { reconnect_after_ms: default_ws_reconnect_after_ms(), alternate_endpoint: None }
+    }
+}
+
+/// Archiver uploading events, incidents and availability rollups to an S3-compatible bucket as
+/// gzipped NDJSON under `<prefix>/<kind>/year=YYYY/month=MM/day=DD/`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArchiveConfig {
+    /// e.g. `https://s3.eu-west-1.amazonaws.com` or `http://minio:9000`; objects are addressed path-style
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_archive_region")]
+    pub region: String,
+    #[serde(default = "default_archive_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_archive_interval_minutes")]
+    pub interval_minutes: u64,
+    /// Defaults to `AWS_ACCESS_KEY_ID`
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    /// Defaults to `AWS_SECRET_ACCESS_KEY`
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+}
+
+/// Prometheus server the dashboard's history queries are proxied to.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrometheusConfig {
+    /// e.g. `http://prometheus:9090`
+    pub url: String,
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+    /// Extra named queries (or overrides of the built-in ones); `$service` expands to the service matcher
+    #[serde(default)]
+    pub queries: std::collections::BTreeMap<String, String>,
+}
+
+/// Services registered from the compose file or running containers in addition to `[[services]]`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DiscoveryConfig {
+    /// Register every running container labelled `<label_prefix>.enable=true`
+    #[serde(default)]
+    pub docker_labels: bool,
+    #[serde(default = "default_discovery_label_prefix")]
+    pub label_prefix: String,
+    /// How often containers are rescanned and the compose file is checked for changes
+    #[serde(default = "default_discovery_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Compose file whose services are monitored (`--compose-file` takes precedence); container names
+    /// use `monitoring.compose_project`, or the file's directory name
+    #[serde(default)]
+    pub compose_file: Option<String>,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            docker_labels: false,
+            label_prefix: default_discovery_label_prefix(),
+            interval_seconds: default_discovery_interval_seconds(),
+            compose_file: None,
+        }
+    }
+}
+
+/// Periodic comparison of each container's image digest with the registry's digest for the same tag.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImageUpdatesConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_image_updates_interval_minutes")]
+    pub interval_minutes: u64,
+    /// Registries (`host[:port]`) reached over plain HTTP instead of HTTPS
+    #[serde(default)]
+    pub insecure_registries: Vec<String>,
+}
+
+impl Default for ImageUpdatesConfig {
+    fn default() -> Self {
+        Self { enabled: false, interval_minutes: default_image_updates_interval_minutes(), insecure_registries: vec![] }
+    }
+}
+
+/// Kubernetes API access. Inside a cluster every field defaults to the pod's service account.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct KubernetesConfig {
+    /// API server URL (default: `https://$KUBERNETES_SERVICE_HOST:$KUBERNETES_SERVICE_PORT`)
+    #[serde(default)]
+    pub api_server: Option<String>,
+    /// Bearer token (default: the service account token, re-read every minute since it rotates)
+    #[serde(default)]
+    pub token: Option<String>,
+    /// CA bundle of the API server (default: the service account's `ca.crt`, when present)
+    #[serde(default)]
+    pub ca_file: Option<String>,
+    #[serde(default)]
+    pub insecure_skip_tls_verify: bool,
+}
+
+/// A Docker daemon other than the local one.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DockerHostConfig {
+    /// `tcp://host:2375`, `http://host:2375` or `unix:///path/to/docker.sock`
+    pub url: String,
+    /// Client certificate for daemons started with `--tlsverify`
+    #[serde(default)]
+    pub tls: Option<DockerTlsConfig>,
+}
+
+/// PEM files, as in `DOCKER_CERT_PATH` (`ca.pem`, `cert.pem`, `key.pem`).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DockerTlsConfig {
+    pub ca_file: String,
+    pub cert_file: String,
+    pub key_file: String,
+}
+
+/// Login to a registry, independent of any `docker login` state on the host. Either `username` with
+/// `password`, an identity `token`, or a `credential_helper` (`docker-credential-<name>`) asked per use.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RegistryConfig {
+    /// Registry host as it appears in image references, e.g. `registry.example.com:5000` or `docker.io`
+    pub host: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Identity (refresh) token, as `docker login` stores for registries with token logins
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Name of a credential helper on the `PATH`, e.g. `ecr-login` or `pass`
+    #[serde(default)]
+    pub credential_helper: Option<String>,
+}
+
+/// A remediation hook: `run` is carried out whenever one of `events` is recorded for one of `services`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HookConfig {
+    pub name: String,
+    /// Event types that trigger the hook, e.g. `["ServiceDown", "HighLatency"]`
+    pub events: Vec<EventType>,
+    /// Services whose events trigger it; empty = any service, and system-wide events too
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+    pub run: HookAction,
+    #[serde(default = "default_hook_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// The hook runs at most once per service within this many seconds
+    #[serde(default = "default_hook_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+/// What a hook does. `{placeholder}`s (as in alert templates) are filled in from the event.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HookAction {
+    /// Program and arguments, run without a shell; the event is also passed in `FKS_*` variables
+    Command {
+        command: Vec<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+    },
+    /// Request to `url`; the body defaults to the event as an alert payload
+    Http {
+        url: String,
+        #[serde(default = "default_hook_method")]
+        method: String,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<serde_json::Value>,
+    },
+    /// Compose action, with the same fields as a `POST /api/compose` body
+    Compose(Box<ComposeRequest>),
+}
+
+/// Self-healing rule, e.g. `rule = "when status == Unhealthy for 2m and restarts_last_hour < 3 then restart"`.
+/// Until `enforce` is set it only logs what it would do.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HealingRule {
+    pub name: String,
+    /// `when <condition> [and <condition> ...] then <action>`
+    #[schemars(with = "String")]
+    pub rule: HealingExpr,
+    /// Service ids the rule applies to (empty = all services)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+    /// Carry the action out; otherwise it is only logged (dry run)
+    #[serde(default)]
+    pub enforce: bool,
+    /// The rule acts at most once per service within this many seconds
+    #[serde(default = "default_healing_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+/// Restarts `services` whenever `cron` fires.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScheduleConfig {
+    pub name: String,
+    /// Five-field cron expression in UTC (minute hour day-of-month month day-of-week), e.g. `"0 3 * * *"`
+    #[schemars(with = "String")]
+    pub cron: CronSchedule,
+    pub services: Vec<String>,
+    #[serde(default = "default_schedule_enabled")]
+    pub enabled: bool,
+    /// Leave out services that are silenced or in a maintenance window when the schedule fires
+    #[serde(default = "default_schedule_skip_when_silenced")]
+    pub skip_when_silenced: bool,
+}
+
+/// Compose actions run through the API.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ComposeConfig {
+    /// Actions that run at once across all projects; more wait in line
+    #[serde(default = "default_compose_max_concurrent_actions")]
+    pub max_concurrent_actions: usize,
+}
+
+impl Default for ComposeConfig {
+    fn default() -> Self {
+        Self { max_concurrent_actions: default_compose_max_concurrent_actions() }
+    }
+}
+
+/// Two-step confirmation of restarts, rollbacks, container actions and compose actions that change
+/// something: the first call returns a one-time token and only a second call carrying it runs the action.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConfirmationConfig {
+    #[serde(default)]
+    pub required: bool,
+    /// How long a token stays valid
+    #[serde(default = "default_confirmation_ttl_seconds")]
+    pub ttl_seconds: u64,
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self { required: false, ttl_seconds: default_confirmation_ttl_seconds() }
+    }
+}
+
+/// Self watchdog: detects stalled internal loops and reports them via `/health/ready`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WatchdogConfig {
+    #[serde(default = "default_watchdog_enabled")]
+    pub enabled: bool,
+    /// A loop is stalled once it has not progressed for this long (or 3x its own interval, if longer)
+    #[serde(default = "default_watchdog_stall_seconds")]
+    pub stall_threshold_seconds: u64,
+    #[serde(default = "default_watchdog_check_interval_seconds")]
+    pub check_interval_seconds: u64,
+    /// Exit the process on a stall so the orchestrator restarts it
+    #[serde(default)]
+    pub exit_on_stall: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stall_threshold_seconds: default_watchdog_stall_seconds(),
+            check_interval_seconds: default_watchdog_check_interval_seconds(),
+            exit_on_stall: false,
+        }
+    }
 }
 
 impl Config {
+    /// Load `path`, a config file (plus its `include`s) or a directory of config files. Files are
+    /// TOML, YAML (`.yaml`/`.yml`) or JSON (`.json`) by extension. A missing path falls back to the
+    /// default configuration.
     pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = fs::read_to_string(&path).await;
-        
-        match content {
-            Ok(content) => {
-                let config: Config = toml::from_str(&content)?;
-                Ok(config)
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            tracing::warn!("Config file not found, using default configuration");
+            return Ok(Self::default());
+        }
+        Self::load_strict(path.as_ref())
+    }
+
+    /// Like [`Config::load`], but a missing path is an error.
+    pub fn load_strict(path: &Path) -> Result<Self> {
+        let mut root = toml::Table::new();
+        let mut defined_in: HashMap<String, PathBuf> = HashMap::new();
+        for (file, table) in read_config_files(path)? {
+            let ids = table.get("services").and_then(|s| s.as_array()).into_iter().flatten();
+            for id in ids.filter_map(|s| s.get("id")).filter_map(|id| id.as_str()) {
+                if let Some(first) = defined_in.insert(id.to_string(), file.clone()) {
+                    anyhow::bail!("service `{id}` is defined in both {} and {}", first.display(), file.display());
+                }
             }
-            Err(_) => {
-                tracing::warn!("Config file not found, using default configuration");
-                Ok(Self::default())
+            merge_config(&mut root, table);
+        }
+        Self::from_table(root)
+    }
+
+    /// Parse a TOML document, applying `[defaults]` to every service and expanding `[templates.<name>]`
+    /// into services that set `template = "<name>"`.
+    #[allow(dead_code)] // library/test API
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        Self::parse(content, ConfigFormat::Toml)
+    }
+
+    /// Parse a single document in `format`. There is no file to resolve them against, so `include`
+    /// is rejected.
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self> {
+        let root = format.parse(content)?;
+        if root.contains_key("include") {
+            anyhow::bail!("`include` is only supported when loading config files");
+        }
+        Self::from_table(root)
+    }
+
+    fn from_table(mut root: toml::Table) -> Result<Self> {
+        expand_service_templates(&mut root)?;
+        crate::secrets::resolve_fields(&mut root)?;
+        Ok(toml::Value::Table(root).try_into()?)
+    }
+}
+
+/// JSON Schema of the config format (`fks_master config schema`). On top of the [`Config`] fields it
+/// describes `include`, `[defaults]` and `[templates.<name>]`; services only need an `id`, as the other
+/// fields may come from those.
+pub fn json_schema() -> serde_json::Value {
+    use serde_json::json;
+    let mut schema = schemars::schema_for!(Config).to_value();
+    schema["title"] = "fks_master monitor config".into();
+    let properties = &mut schema["properties"];
+    properties["include"] = json!({
+        "description": "Further config files, relative to this one; `*` and `?` wildcards match files in a directory",
+        "type": "array",
+        "items": { "type": "string" },
+    });
+    properties["templates"] = json!({
+        "description": "Named partial services; a service (or template) with `template = \"<name>\"` starts from it",
+        "type": "object",
+        "additionalProperties": { "type": "object" },
+    });
+    properties["defaults"] = json!({
+        "description": "Service fields every service starts from, below its template and its own keys",
+        "type": "object",
+    });
+    // Any other field can come from `[defaults]` or a template; completeness is checked after expansion
+    let service = &mut schema["$defs"]["ServiceConfig"];
+    service["required"] = json!(["id"]);
+    service["properties"]["template"] = json!({ "description": "Name of the template this service starts from", "type": "string" });
+    schema
+}
+
+/// Replace the services of the config file at `path` with `services`, keeping every other section.
+/// Only a single file without `include`s can be written back: in a directory or an include tree there
+/// is no one place the services belong. Comments are not preserved and services created from a
+/// template are written out in full.
+pub fn save_services(path: &Path, services: &[ServiceConfig]) -> Result<()> {
+    if path.is_dir() {
+        anyhow::bail!("{} is a directory; only a single config file can be updated", path.display());
+    }
+    let content = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let format = ConfigFormat::of(path).unwrap_or(ConfigFormat::Toml);
+    let mut table = format.parse(&content).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?;
+    if table.contains_key("include") {
+        anyhow::bail!("{} uses `include`; only a single config file can be updated", path.display());
+    }
+    table.insert("services".into(), toml::Value::try_from(services)?);
+    let rendered = match format {
+        ConfigFormat::Toml => toml::to_string_pretty(&table)?,
+        ConfigFormat::Yaml => serde_yaml::to_string(&table)?,
+        ConfigFormat::Json => serde_json::to_string_pretty(&table)? + "\n",
+    };
+    // Write next to the file and rename so a crash never leaves it half written
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, rendered).with_context(|| format!("writing {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("replacing {}", path.display()))?;
+    Ok(())
+}
+
+/// Files that make up the config at `path`, in load order (see [`Config::load`]).
+pub fn config_files(path: &Path) -> Result<Vec<PathBuf>> {
+    Ok(read_config_files(path)?.into_iter().map(|(file, _)| file).collect())
+}
+
+/// Read `path` and, recursively, what it includes: a file comes before its `include`s, and a
+/// directory contributes its `.toml`, `.yaml`, `.yml` and `.json` files in name order (hidden files
+/// are skipped).
+fn read_config_files(path: &Path) -> Result<Vec<(PathBuf, toml::Table)>> {
+    let mut files = Vec::new();
+    collect_config_files(path, &mut files)?;
+    Ok(files)
+}
+
+fn collect_config_files(path: &Path, files: &mut Vec<(PathBuf, toml::Table)>) -> Result<()> {
+    if path.is_dir() {
+        for file in matching_files(path, "*")?.into_iter().filter(|f| ConfigFormat::of(f).is_some()) {
+            collect_config_files(&file, files)?;
+        }
+        return Ok(());
+    }
+    let file = path.canonicalize().with_context(|| format!("reading {}", path.display()))?;
+    if files.iter().any(|(seen, _)| *seen == file) {
+        anyhow::bail!("{} is included more than once", file.display());
+    }
+    let content = std::fs::read_to_string(&file).with_context(|| format!("reading {}", file.display()))?;
+    let format = ConfigFormat::of(&file).unwrap_or(ConfigFormat::Toml);
+    let mut table = format.parse(&content).map_err(|e| anyhow::anyhow!("{}: {e}", file.display()))?;
+    let includes = match table.remove("include") {
+        None => Vec::new(),
+        Some(toml::Value::Array(items)) => items
+            .into_iter()
+            .map(|v| v.as_str().map(str::to_string).ok_or_else(|| anyhow::anyhow!("{}: `include` entries must be strings", file.display())))
+            .collect::<Result<Vec<_>>>()?,
+        Some(_) => anyhow::bail!("{}: `include` must be an array of paths", file.display()),
+    };
+    let base = file.parent().map(Path::to_path_buf).unwrap_or_default();
+    files.push((file, table));
+    for include in includes {
+        let pattern = base.join(&include);
+        let name = pattern.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if name.contains(['*', '?']) {
+            let dir = pattern.parent().unwrap_or(&base);
+            let matched = matching_files(dir, &name)?;
+            if matched.is_empty() { tracing::warn!("config include `{include}` matched no files"); }
+            for file in matched {
+                collect_config_files(&file, files)?;
             }
+        } else {
+            collect_config_files(&pattern, files)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Format by file extension; `None` for anything else (read as TOML when named explicitly).
+    pub fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Parse into the TOML data model shared by every format, so includes, merging and templates
+    /// work the same way. YAML/JSON nulls are dropped, i.e. treated like an omitted key.
+    fn parse(self, content: &str) -> Result<toml::Table> {
+        let value: serde_json::Value = match self {
+            ConfigFormat::Toml => return Ok(content.parse()?),
+            ConfigFormat::Yaml if content.trim().is_empty() => return Ok(toml::Table::new()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content)?,
+            ConfigFormat::Json => serde_json::from_str(content)?,
+        };
+        match without_nulls(value) {
+            Some(value @ serde_json::Value::Object(_)) => Ok(toml::Table::try_from(value)?),
+            _ => anyhow::bail!("expected a mapping of config sections at the top level"),
+        }
+    }
+}
+
+fn without_nulls(value: serde_json::Value) -> Option<serde_json::Value> {
+    use serde_json::Value;
+    match value {
+        Value::Null => None,
+        Value::Array(items) => Some(Value::Array(items.into_iter().filter_map(without_nulls).collect())),
+        Value::Object(map) => Some(Value::Object(map.into_iter().filter_map(|(k, v)| Some((k, without_nulls(v)?))).collect())),
+        other => Some(other),
+    }
+}
+
+/// Non-hidden files in `dir` whose name matches `pattern` (`*` and `?` wildcards), sorted by name.
+fn matching_files(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut matched: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("reading directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| !n.starts_with('.') && wildcard_match(pattern.as_bytes(), n.as_bytes())))
+        .collect();
+    matched.sort();
+    Ok(matched)
+}
+
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => wildcard_match(&pattern[1..], name) || (!name.is_empty() && wildcard_match(pattern, &name[1..])),
+        (Some(b'?'), Some(_)) => wildcard_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => wildcard_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+/// Merge one config file into those read before it: tables merge key by key, arrays (such as
+/// `services` and `alerts.routes`) are concatenated, and any other value from the later file wins.
+fn merge_config(into: &mut toml::Table, from: toml::Table) {
+    for (key, value) in from {
+        match (into.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => merge_config(existing, table),
+            (Some(toml::Value::Array(existing)), toml::Value::Array(items)) => existing.extend(items),
+            (_, value) => { into.insert(key, value); }
+        }
+    }
+}
+
+/// The running configuration, shared by the monitor and alert dispatcher. A reload swaps in a
+/// whole new [`Config`]; readers hold on to the snapshot they loaded until they are done with it.
+#[derive(Clone)]
+pub struct SharedConfig(Arc<RwLock<Arc<Config>>>);
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        self.0.read().map(|c| c.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Install `config`, returning the one it replaced.
+    pub fn replace(&self, config: Config) -> Arc<Config> {
+        let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *current, Arc::new(config))
+    }
+}
+
+/// Merge each service over its template (and the template over its own `template`, if any).
+/// Keys set on the service win; nested tables merge key by key. String values may use `{id}` and
+/// `{name}`, e.g. `health_endpoint = "http://{id}:8000/health"` in a template.
+fn expand_service_templates(root: &mut toml::Table) -> Result<()> {
+    let templates = match root.remove("templates") {
+        Some(toml::Value::Table(t)) => t,
+        Some(_) => anyhow::bail!("`templates` must be a table of named templates"),
+        None => toml::Table::new(),
+    };
+    let defaults = match root.remove("defaults") {
+        Some(toml::Value::Table(t)) => t,
+        Some(_) => anyhow::bail!("`defaults` must be a table of service fields"),
+        None => toml::Table::new(),
+    };
+    if defaults.contains_key("template") {
+        anyhow::bail!("`defaults` cannot set `template`");
+    }
+    let Some(toml::Value::Array(services)) = root.get_mut("services") else { return Ok(()) };
+    for service in services.iter_mut().filter_map(toml::Value::as_table_mut) {
+        let template = service.remove("template");
+        if template.is_none() && defaults.is_empty() { continue }
+        // defaults < template chain < the service's own keys
+        let mut merged = defaults.clone();
+        if let Some(template) = template {
+            let template = template.as_str().ok_or_else(|| anyhow::anyhow!("service `template` must be a string"))?;
+            merge_tables(&mut merged, resolve_template(&templates, template, &mut Vec::new())?);
+        }
+        merge_tables(&mut merged, std::mem::take(service));
+        let id = merged.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let name = merged.get("name").and_then(|v| v.as_str()).unwrap_or(&id).to_string();
+        for (_, value) in merged.iter_mut() {
+            if let toml::Value::String(s) = value {
+                *s = s.replace("{id}", &id).replace("{name}", &name);
+            }
+        }
+        *service = merged;
+    }
+    Ok(())
+}
+
+fn resolve_template(templates: &toml::Table, name: &str, chain: &mut Vec<String>) -> Result<toml::Table> {
+    if chain.iter().any(|n| n == name) {
+        anyhow::bail!("template cycle: {} -> {name}", chain.join(" -> "));
+    }
+    chain.push(name.to_string());
+    let mut template = templates
+        .get(name)
+        .and_then(|t| t.as_table())
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("unknown service template `{name}`"))?;
+    match template.remove("template") {
+        Some(parent) => {
+            let parent = parent.as_str().ok_or_else(|| anyhow::anyhow!("template `{name}`: `template` must be a string"))?;
+            let mut base = resolve_template(templates, parent, chain)?;
+            merge_tables(&mut base, template);
+            Ok(base)
+        }
+        None => Ok(template),
+    }
+}
+
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(b)), toml::Value::Table(o)) => merge_tables(b, o),
+            (_, value) => { base.insert(key, value); }
         }
     }
 }
@@ -59,6 +1193,22 @@ impl Default for Config {
                     docker_container: Some("fks_api".to_string()),
                     expected_response_time_ms: 500,
                     critical: true,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_auth".to_string(),
@@ -69,6 +1219,22 @@ impl Default for Config {
                     docker_container: Some("fks_auth".to_string()),
                     expected_response_time_ms: 300,
                     critical: true,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_data".to_string(),
@@ -78,6 +1244,22 @@ impl Default for Config {
                     docker_container: Some("fks_data".to_string()),
                     expected_response_time_ms: 800,
                     critical: true,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_engine".to_string(),
@@ -87,6 +1269,22 @@ impl Default for Config {
                     docker_container: Some("fks_engine".to_string()),
                     expected_response_time_ms: 200,
                     critical: true,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_transformer".to_string(),
@@ -96,6 +1294,22 @@ impl Default for Config {
                     docker_container: Some("fks_transformer".to_string()),
                     expected_response_time_ms: 1000,
                     critical: false,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_training".to_string(),
@@ -105,6 +1319,22 @@ impl Default for Config {
                     docker_container: Some("fks_training".to_string()),
                     expected_response_time_ms: 2000,
                     critical: false,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_worker".to_string(),
@@ -114,6 +1344,22 @@ impl Default for Config {
                     docker_container: Some("fks_worker".to_string()),
                     expected_response_time_ms: 500,
                     critical: false,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_web".to_string(),
@@ -123,6 +1369,22 @@ impl Default for Config {
                     docker_container: Some("fks_web".to_string()),
                     expected_response_time_ms: 300,
                     critical: true,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_config".to_string(),
@@ -132,6 +1394,22 @@ impl Default for Config {
                     docker_container: Some("fks_config".to_string()),
                     expected_response_time_ms: 200,
                     critical: true,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_execution".to_string(),
@@ -141,6 +1419,22 @@ impl Default for Config {
                     docker_container: Some("fks_execution".to_string()),
                     expected_response_time_ms: 300,
                     critical: true,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
                 ServiceConfig {
                     id: "fks_nodes".to_string(),
@@ -150,6 +1444,22 @@ impl Default for Config {
                     docker_container: Some("fks_nodes".to_string()),
                     expected_response_time_ms: 400,
                     critical: false,
+                    check_interval_seconds: None,
+                    heartbeat_window_seconds: None,
+                    depends_on: vec![],
+                    tags: vec![],
+                    probes: vec![],
+                    probe_mode: ProbeMode::All,
+                    labels: Default::default(),
+                    group: None,
+                    metadata: Default::default(),
+                    auto_update: false,
+                    kubernetes: None,
+                    systemd_unit: None,
+                    auto_restart: None,
+                    drain: None,
+                    metrics: None,
+                    docker_host: None,
                 },
             ],
             monitoring: MonitoringConfig {
@@ -158,15 +1468,106 @@ impl Default for Config {
                 retry_attempts: 3,
                 batch_size: 5,
                 enable_docker_stats: true,
+                restart_stop_timeout_seconds: default_restart_stop_timeout_seconds(),
+                docker_events: true,
+                compose_project: None,
+                validate_endpoints_on_startup: false,
+                uptime_history_path: None,
             },
             alerts: AlertConfig {
                 enable_notifications: true,
                 high_latency_threshold_ms: 2000,
                 consecutive_failures_threshold: 3,
                 webhook_url: None,
+                webhook_body: None,
+                slack: None,
+                email: None,
+                dead_letter_path: default_dead_letter_path(),
+                history_path: default_history_path(),
+                history_capacity: default_history_capacity(),
+                rules: vec![],
+                rule_evaluation_interval_seconds: default_rule_evaluation_interval_seconds(),
+                throttle: AlertThrottleConfig::default(),
+                pagerduty: None,
+                escalation: vec![],
+                reminder_interval_minutes: None,
+                maintenance_windows: vec![],
+                routes: vec![],
             },
+            restart_storm: RestartStormConfig::default(),
+            restart_backoff: RestartBackoffConfig::default(),
+            restart_verification: RestartVerificationConfig::default(),
+            restart_rate_limit: RestartRateLimitConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            websocket: WebSocketConfig::default(),
+            archive: None,
+            discovery: DiscoveryConfig::default(),
+            image_updates: ImageUpdatesConfig::default(),
+            kubernetes: KubernetesConfig::default(),
+            hosts: HashMap::new(),
+            compose: ComposeConfig::default(),
+            confirmation: ConfirmationConfig::default(),
+            registries: vec![],
+            hooks: vec![],
+            schedules: vec![],
+            healing: vec![],
+            prometheus: None,
+            openapi: OpenApiConfig::default(),
+            graphql: GraphqlConfig::default(),
+            federation: FederationConfig::default(),
+            health_score: HealthScoreConfig::default(),
+            compression: CompressionConfig::default(),
+            webhooks: WebhooksConfig::default(),
         }
     }
 }
 
 fn default_enable_docker_stats() -> bool { true }
+fn default_docker_events() -> bool { true }
+fn default_restart_stop_timeout_seconds() -> u64 { 10 }
+fn default_storm_threshold() -> usize { 4 }
+fn default_storm_window_seconds() -> u64 { 300 }
+fn default_storm_cooldown_seconds() -> u64 { 900 }
+fn default_backoff_initial_delay_seconds() -> u64 { 30 }
+fn default_hook_timeout_seconds() -> u64 { 60 }
+fn default_hook_cooldown_seconds() -> u64 { 300 }
+fn default_healing_cooldown_seconds() -> u64 { 300 }
+fn default_hook_method() -> String { "POST".into() }
+fn default_backoff_max_delay_seconds() -> u64 { 900 }
+fn default_backoff_max_restarts() -> u32 { 5 }
+fn default_backoff_reset_after_seconds() -> u64 { 600 }
+fn default_restart_verification_enabled() -> bool { true }
+fn default_restart_verification_timeout_seconds() -> u64 { 60 }
+fn default_restart_verification_poll_interval_seconds() -> u64 { 2 }
+fn default_restart_rate_limit_window_seconds() -> u64 { 600 }
+fn default_swagger_ui() -> bool { true }
+fn default_swagger_ui_assets() -> String { "https://unpkg.com/swagger-ui-dist@5".into() }
+fn default_graphiql() -> bool { true }
+fn default_watchdog_enabled() -> bool { true }
+fn default_schedule_enabled() -> bool { true }
+fn default_schedule_skip_when_silenced() -> bool { true }
+fn default_compose_max_concurrent_actions() -> usize { 4 }
+fn default_confirmation_ttl_seconds() -> u64 { 120 }
+fn default_ws_reconnect_after_ms() -> u64 { 2_000 }
+fn default_archive_region() -> String { "us-east-1".into() }
+fn default_archive_prefix() -> String { "fks_master".into() }
+fn default_archive_interval_minutes() -> u64 { 60 }
+fn default_discovery_label_prefix() -> String { "fks.monitor".into() }
+fn default_discovery_interval_seconds() -> u64 { 30 }
+fn default_image_updates_interval_minutes() -> u64 { 360 }
+fn default_watchdog_stall_seconds() -> u64 { 180 }
+fn default_watchdog_check_interval_seconds() -> u64 { 10 }
+fn default_dead_letter_path() -> Option<String> { Some("data/alert_dead_letters.json".into()) }
+fn default_history_path() -> Option<String> { Some("data/alert_history.json".into()) }
+fn default_history_capacity() -> usize { 10_000 }
+fn default_rule_evaluation_interval_seconds() -> u64 { 15 }
+fn default_dedup_window_seconds() -> u64 { 300 }
+fn default_service_cooldown_seconds() -> u64 { 120 }
+fn default_burst_threshold() -> usize { 5 }
+fn default_batch_window_seconds() -> u64 { 60 }
+fn default_pagerduty_events_url() -> String { "https://events.pagerduty.com/v2/enqueue".into() }
+fn default_rule_severity() -> String { "warning".into() }
+fn default_email_subject() -> String { "[FKS] {event_type}: {service_name}".into() }
+fn default_email_body() -> String {
+    "{message}\n\nService: {service_name} ({service_id})\nStatus: {old_status} -> {new_status}\nTime: {timestamp}\n".into()
+}
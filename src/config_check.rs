@@ -0,0 +1,496 @@
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::config::{Config, HookAction};
+use crate::docker_hosts;
+use crate::models::ProbeConfig;
+use crate::orchestrator::Orchestrator;
+
+/// Channel names accepted by alert routes and escalation steps
+const CHANNELS: [&str; 4] = ["webhook", "slack", "email", "pagerduty"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// The config would misbehave (or not load); `valid` is false
+    Error,
+    /// Probably a mistake, but the config works
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    pub level: Level,
+    /// Where the problem is, e.g. `services[fks_api].health_endpoint` (empty for parse errors)
+    pub path: String,
+    pub message: String,
+}
+
+/// Result of `fks_master validate-config` and `POST /api/config/validate`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub valid: bool,
+    pub services: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn new(services: usize, issues: Vec<Issue>) -> Self {
+        Self { valid: !issues.iter().any(|i| i.level == Level::Error), services, issues }
+    }
+
+    /// The config could not be read or parsed at all.
+    pub fn unreadable(error: &anyhow::Error) -> Self {
+        Self::new(0, vec![Issue { level: Level::Error, path: String::new(), message: format!("{error:#}") }])
+    }
+}
+
+#[derive(Default)]
+struct Issues(Vec<Issue>);
+
+impl Issues {
+    fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Issue { level: Level::Error, path: path.into(), message: message.into() });
+    }
+
+    fn warn(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(Issue { level: Level::Warning, path: path.into(), message: message.into() });
+    }
+
+    fn positive(&mut self, path: &str, value: u64) {
+        if value == 0 { self.error(path, "must be greater than 0"); }
+    }
+
+    fn http_url(&mut self, path: &str, url: &str) {
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            Ok(parsed) => self.error(path, format!("unsupported scheme `{}` (expected http or https)", parsed.scheme())),
+            // Secret URLs (e.g. Slack webhooks) carry their token and must not be echoed
+            Err(e) if crate::secrets::is_secret_path(path) => self.error(path, format!("invalid URL: {e}")),
+            Err(e) => self.error(path, format!("invalid URL `{url}`: {e}")),
+        }
+    }
+
+    fn channels(&mut self, path: &str, channels: &[String]) {
+        for channel in channels.iter().filter(|c| !CHANNELS.contains(&c.as_str())) {
+            self.error(path, format!("unknown channel `{channel}` (expected one of {})", CHANNELS.join(", ")));
+        }
+    }
+}
+
+/// Check invariants serde cannot express: unique ids, usable URLs, positive thresholds and
+/// references between sections. Containers are checked separately by [`check_containers`].
+pub fn check(config: &Config) -> Vec<Issue> {
+    let mut issues = Issues::default();
+    let m = &config.monitoring;
+    issues.positive("monitoring.check_interval_seconds", m.check_interval_seconds);
+    issues.positive("monitoring.timeout_seconds", m.timeout_seconds);
+    issues.positive("monitoring.retry_attempts", m.retry_attempts.into());
+    issues.positive("monitoring.batch_size", m.batch_size as u64);
+
+    let ids: HashSet<&str> = config.services.iter().map(|s| s.id.as_str()).collect();
+    let mut seen = HashSet::new();
+    for (i, service) in config.services.iter().enumerate() {
+        let at = if service.id.is_empty() { format!("services[{i}]") } else { format!("services[{}]", service.id) };
+        if service.id.trim().is_empty() {
+            issues.error(format!("{at}.id"), "must not be empty");
+        } else if !seen.insert(service.id.as_str()) {
+            issues.error(format!("{at}.id"), format!("duplicate service id `{}`", service.id));
+        }
+        match service.heartbeat_window_seconds {
+            Some(window) => issues.positive(&format!("{at}.heartbeat_window_seconds"), window),
+            None => {
+                // Without a health endpoint the probes alone decide health
+                if !service.health_endpoint.is_empty() || service.probes.is_empty() {
+                    issues.http_url(&format!("{at}.health_endpoint"), &service.health_endpoint);
+                }
+                issues.positive(&format!("{at}.expected_response_time_ms"), service.expected_response_time_ms);
+            }
+        }
+        if let Some(every) = service.check_interval_seconds {
+            issues.positive(&format!("{at}.check_interval_seconds"), every);
+        }
+        if let Some(policy) = &service.auto_restart {
+            issues.positive(&format!("{at}.auto_restart.failure_threshold"), policy.failure_threshold.into());
+            issues.positive(&format!("{at}.auto_restart.max_attempts_per_hour"), policy.max_attempts_per_hour.into());
+        }
+        if let Some(drain) = &service.drain {
+            issues.http_url(&format!("{at}.drain.url"), &drain.url);
+            if let Some(url) = &drain.status_url { issues.http_url(&format!("{at}.drain.status_url"), url); }
+            if reqwest::Method::from_bytes(drain.method.to_ascii_uppercase().as_bytes()).is_err() {
+                issues.error(format!("{at}.drain.method"), format!("invalid method `{}`", drain.method));
+            }
+            issues.positive(&format!("{at}.drain.timeout_seconds"), drain.timeout_seconds);
+            if Orchestrator::of(service).is_none() { issues.warn(format!("{at}.drain"), "has no effect on a service that cannot be restarted"); }
+        }
+        if let Some(metrics) = &service.metrics {
+            issues.http_url(&format!("{at}.metrics.url"), &metrics.url);
+            if !config.federation.enabled { issues.warn(format!("{at}.metrics"), "not scraped unless `federation.enabled`"); }
+        }
+        for dep in service.depends_on.iter().filter(|d| !ids.contains(d.as_str())) {
+            issues.error(format!("{at}.depends_on"), format!("unknown service `{dep}`"));
+        }
+        for (p, probe) in service.probes.iter().enumerate() {
+            let path = format!("{at}.probes[{p}]");
+            match probe {
+                ProbeConfig::Http { url, .. } => issues.http_url(&path, url),
+                ProbeConfig::Tcp { address, .. } => {
+                    let port = address.rsplit_once(':').map(|(host, port)| (host, port.parse::<u16>()));
+                    if !matches!(port, Some((host, Ok(_))) if !host.is_empty()) {
+                        issues.error(path, format!("TCP address `{address}` must be host:port"));
+                    }
+                }
+                ProbeConfig::Docker { container, .. } => {
+                    if container.is_none() && service.docker_container.is_none() {
+                        issues.error(path, "docker probe needs `container` or the service's `docker_container`");
+                    }
+                }
+                ProbeConfig::Systemd { unit, .. } => {
+                    if unit.is_none() && service.systemd_unit.is_none() {
+                        issues.error(path, "systemd probe needs `unit` or the service's `systemd_unit`");
+                    }
+                }
+                ProbeConfig::Kubernetes { selector, .. } => {
+                    if selector.is_none() && service.kubernetes.is_none() {
+                        issues.error(path, "kubernetes probe needs `selector` or the service's `kubernetes` target");
+                    }
+                }
+            }
+        }
+        if let Some(target) = &service.kubernetes {
+            if target.selector.trim().is_empty() { issues.error(format!("{at}.kubernetes.selector"), "must not be empty"); }
+        }
+        if let Some(host) = service.docker_host.as_deref().filter(|h| !config.hosts.contains_key(*h)) {
+            if let Err(e) = docker_hosts::url(config, Some(host)) { issues.error(format!("{at}.docker_host"), format!("{e:#}")); }
+        }
+        if service.docker_host.is_some() && service.docker_container.is_none() {
+            issues.warn(format!("{at}.docker_host"), "has no effect without `docker_container`");
+        }
+        if service.systemd_unit.as_deref().is_some_and(|u| u.trim().is_empty()) {
+            issues.error(format!("{at}.systemd_unit"), "must not be empty");
+        }
+        let backends = [service.kubernetes.is_some(), service.docker_container.is_some(), service.systemd_unit.is_some()];
+        if let Some(orchestrator) = Orchestrator::of(service).filter(|_| backends.iter().filter(|set| **set).count() > 1) {
+            issues.warn(at.clone(), format!("more than one of `kubernetes`, `docker_container` and `systemd_unit` is set; restarts go to {}", orchestrator.describe()));
+        }
+    }
+
+    let a = &config.alerts;
+    issues.positive("alerts.high_latency_threshold_ms", a.high_latency_threshold_ms);
+    issues.positive("alerts.consecutive_failures_threshold", a.consecutive_failures_threshold.into());
+    issues.positive("alerts.history_capacity", a.history_capacity as u64);
+    issues.positive("alerts.rule_evaluation_interval_seconds", a.rule_evaluation_interval_seconds);
+    // Webhook URLs may be templates, so only the scheme is checked
+    if let Some(url) = &a.webhook_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            issues.error("alerts.webhook_url", "must start with http:// or https://");
+        }
+    }
+    for (i, route) in a.routes.iter().enumerate() {
+        let at = format!("alerts.routes[{i}]");
+        issues.channels(&format!("{at}.channels"), &route.channels);
+        for service in route.services.iter().filter(|s| !ids.contains(s.as_str())) {
+            issues.warn(format!("{at}.services"), format!("unknown service `{service}`"));
+        }
+        if route.webhook_url.as_deref().is_some_and(|u| !u.starts_with("http://") && !u.starts_with("https://")) {
+            issues.error(format!("{at}.webhook_url"), "must start with http:// or https://");
+        }
+    }
+    for (i, step) in a.escalation.iter().enumerate() {
+        issues.channels(&format!("alerts.escalation[{i}].channels"), &step.channels);
+    }
+    for rule in &a.rules {
+        for service in rule.services.iter().filter(|s| !ids.contains(s.as_str())) {
+            issues.warn(format!("alerts.rules[{}].services", rule.name), format!("unknown service `{service}`"));
+        }
+    }
+    if let Some(slack) = &a.slack {
+        if let Some(url) = &slack.webhook_url { issues.http_url("alerts.slack.webhook_url", url); }
+        if slack.bot_token.is_some() && slack.channel.is_none() {
+            issues.error("alerts.slack.channel", "required with bot_token");
+        }
+    }
+    if let Some(pd) = &a.pagerduty {
+        issues.http_url("alerts.pagerduty.events_url", &pd.events_url);
+    }
+
+    issues.positive("restart_storm.threshold", config.restart_storm.threshold as u64);
+    issues.positive("restart_storm.window_seconds", config.restart_storm.window_seconds);
+    issues.positive("restart_backoff.max_restarts", config.restart_backoff.max_restarts.into());
+    if config.restart_backoff.max_delay_seconds < config.restart_backoff.initial_delay_seconds {
+        issues.error("restart_backoff.max_delay_seconds", "must not be less than initial_delay_seconds");
+    }
+    if config.restart_rate_limit.max_restarts > 0 { issues.positive("restart_rate_limit.window_seconds", config.restart_rate_limit.window_seconds); }
+    if config.restart_verification.enabled { issues.positive("restart_verification.timeout_seconds", config.restart_verification.timeout_seconds); }
+    if config.watchdog.enabled {
+        issues.positive("watchdog.stall_threshold_seconds", config.watchdog.stall_threshold_seconds);
+        issues.positive("watchdog.check_interval_seconds", config.watchdog.check_interval_seconds);
+    }
+    if let Some(url) = &config.websocket.alternate_endpoint {
+        if !url.starts_with("ws://") && !url.starts_with("wss://") {
+            issues.error("websocket.alternate_endpoint", "must start with ws:// or wss://");
+        }
+    }
+    if let Some(archive) = &config.archive {
+        issues.http_url("archive.endpoint", &archive.endpoint);
+        if archive.bucket.trim().is_empty() { issues.error("archive.bucket", "must not be empty"); }
+        issues.positive("archive.interval_minutes", archive.interval_minutes);
+    }
+    if let Some(prometheus) = &config.prometheus {
+        issues.http_url("prometheus.url", &prometheus.url);
+    }
+    let score = &config.health_score;
+    for (field, value) in [("degraded", score.degraded), ("unknown", score.unknown), ("dependency_impact", score.dependency_impact)] {
+        if !(0.0..=1.0).contains(&value) { issues.error(format!("health_score.{field}"), "must be between 0 and 1"); }
+    }
+    for (field, value) in [("weight", score.weight), ("critical_weight", score.critical_weight)] {
+        if !(0.0..).contains(&value) { issues.error(format!("health_score.{field}"), "must not be negative"); }
+    }
+    for (id, weight) in &score.weights {
+        if !ids.contains(id.as_str()) { issues.warn(format!("health_score.weights.{id}"), "unknown service"); }
+        if !(0.0..).contains(weight) { issues.error(format!("health_score.weights.{id}"), "must not be negative"); }
+    }
+    if config.federation.enabled {
+        issues.positive("federation.timeout_seconds", config.federation.timeout_seconds);
+        issues.positive("federation.max_series_per_service", config.federation.max_series_per_service as u64);
+    }
+    if config.discovery.docker_labels {
+        issues.positive("discovery.interval_seconds", config.discovery.interval_seconds);
+        if config.discovery.label_prefix.trim().is_empty() { issues.error("discovery.label_prefix", "must not be empty"); }
+    }
+    if config.image_updates.enabled {
+        issues.positive("image_updates.interval_minutes", config.image_updates.interval_minutes);
+    }
+    for name in config.hosts.keys() {
+        if let Err(e) = docker_hosts::url(config, Some(name)) { issues.error(format!("hosts.{name}"), format!("{e:#}")); }
+    }
+    issues.positive("compose.max_concurrent_actions", config.compose.max_concurrent_actions as u64);
+    if config.confirmation.required { issues.positive("confirmation.ttl_seconds", config.confirmation.ttl_seconds); }
+    for (i, registry) in config.registries.iter().enumerate() {
+        if registry.host.trim().is_empty() { issues.error(format!("registries[{i}].host"), "must not be empty"); }
+        let login = registry.credential_helper.is_some() || registry.token.is_some() || (registry.username.is_some() && registry.password.is_some());
+        if !login { issues.error(format!("registries[{i}]"), "needs a username and password, a token or a credential_helper"); }
+    }
+    let mut hook_names = HashSet::new();
+    for (i, hook) in config.hooks.iter().enumerate() {
+        let at = if hook.name.is_empty() { format!("hooks[{i}]") } else { format!("hooks[{}]", hook.name) };
+        if hook.name.trim().is_empty() {
+            issues.error(format!("{at}.name"), "must not be empty");
+        } else if !hook_names.insert(hook.name.as_str()) {
+            issues.error(format!("{at}.name"), format!("duplicate hook name `{}`", hook.name));
+        }
+        if hook.events.is_empty() { issues.error(format!("{at}.events"), "must not be empty"); }
+        for service in hook.services.iter().filter(|s| !ids.contains(s.as_str())) {
+            issues.warn(format!("{at}.services"), format!("unknown service `{service}`"));
+        }
+        issues.positive(&format!("{at}.timeout_seconds"), hook.timeout_seconds);
+        match &hook.run {
+            HookAction::Command { command, .. } => {
+                if command.first().is_none_or(|program| program.trim().is_empty()) { issues.error(format!("{at}.run.command"), "must name a program"); }
+            }
+            // Templated URLs are only known once rendered
+            HookAction::Http { url, method, .. } => {
+                if !url.contains('{') { issues.http_url(&format!("{at}.run.url"), url); }
+                if reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes()).is_err() {
+                    issues.error(format!("{at}.run.method"), format!("invalid method `{method}`"));
+                }
+            }
+            HookAction::Compose(request) => {
+                for service in request.services.iter().filter(|s| !ids.contains(s.as_str())) {
+                    issues.warn(format!("{at}.run.services"), format!("unknown service `{service}`"));
+                }
+            }
+        }
+    }
+    let mut schedule_names = HashSet::new();
+    for (i, schedule) in config.schedules.iter().enumerate() {
+        let at = if schedule.name.is_empty() { format!("schedules[{i}]") } else { format!("schedules[{}]", schedule.name) };
+        if schedule.name.trim().is_empty() {
+            issues.error(format!("{at}.name"), "must not be empty");
+        } else if !schedule_names.insert(schedule.name.as_str()) {
+            issues.error(format!("{at}.name"), format!("duplicate schedule name `{}`", schedule.name));
+        }
+        if schedule.services.is_empty() { issues.error(format!("{at}.services"), "must not be empty"); }
+        for service in schedule.services.iter().filter(|s| !ids.contains(s.as_str())) {
+            issues.warn(format!("{at}.services"), format!("unknown service `{service}`"));
+        }
+        if schedule.enabled && schedule.cron.next_after(chrono::Utc::now()).is_none() {
+            issues.error(format!("{at}.cron"), format!("`{}` never fires", schedule.cron));
+        }
+    }
+    let mut healing_names = HashSet::new();
+    for (i, rule) in config.healing.iter().enumerate() {
+        let at = if rule.name.is_empty() { format!("healing[{i}]") } else { format!("healing[{}]", rule.name) };
+        if rule.name.trim().is_empty() {
+            issues.error(format!("{at}.name"), "must not be empty");
+        } else if !healing_names.insert(rule.name.as_str()) {
+            issues.error(format!("{at}.name"), format!("duplicate healing rule name `{}`", rule.name));
+        }
+        for service in rule.services.iter().filter(|s| !ids.contains(s.as_str())) {
+            issues.warn(format!("{at}.services"), format!("unknown service `{service}`"));
+        }
+    }
+    issues.0
+}
+
+/// Every `docker_container` (and docker probe container) must exist on its host, running or not. A
+/// Docker daemon that cannot be reached is a single warning rather than an error.
+pub async fn check_containers(config: &Config) -> Vec<Issue> {
+    let mut issues = Issues::default();
+    let wanted: Vec<(String, Option<&str>, &str)> = config.services.iter().flat_map(|s| {
+        let host = s.docker_host.as_deref().filter(|h| *h != docker_hosts::LOCAL);
+        let own = s.docker_container.as_deref().map(|c| (format!("services[{}].docker_container", s.id), host, c));
+        let probes = s.probes.iter().enumerate().filter_map(move |(p, probe)| match probe {
+            ProbeConfig::Docker { container: Some(c), .. } => Some((format!("services[{}].probes[{p}].container", s.id), host, c.as_str())),
+            _ => None,
+        });
+        own.into_iter().chain(probes)
+    }).collect();
+    let mut hosts: Vec<Option<&str>> = wanted.iter().map(|(_, host, _)| *host).collect();
+    hosts.sort();
+    hosts.dedup();
+
+    for host in hosts {
+        let options = bollard::container::ListContainersOptions::<String> { all: true, ..Default::default() };
+        let listed = match docker_hosts::connect(config, host) {
+            Ok(docker) => docker.list_containers(Some(options)).await.map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+        let containers = match listed {
+            Ok(containers) => containers,
+            Err(e) => {
+                let on = host.map(|h| format!(" on {h}")).unwrap_or_default();
+                issues.warn("", format!("containers{on} not checked: Docker unavailable ({e:#})"));
+                continue;
+            }
+        };
+        let names: HashSet<&str> = containers.iter().flat_map(|c| c.names.iter().flatten()).map(|n| n.trim_start_matches('/')).collect();
+        for (path, _, container) in wanted.iter().filter(|(_, h, _)| *h == host) {
+            if !names.contains(container) {
+                issues.error(path.clone(), format!("container `{container}` does not exist"));
+            }
+        }
+    }
+    issues.0
+}
+
+/// Run every check; `containers = false` skips the Docker lookup.
+pub async fn validate(config: &Config, containers: bool) -> Report {
+    let mut issues = check(config);
+    if containers {
+        issues.extend(check_containers(config).await);
+    }
+    Report::new(config.services.len(), issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(issues: &[Issue], level: Level) -> Vec<&str> {
+        issues.iter().filter(|i| i.level == level).map(|i| i.path.as_str()).collect()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(check(&Config::default()), vec![]);
+    }
+
+    #[test]
+    fn reports_broken_invariants_by_path() {
+        let mut config = Config::default();
+        config.monitoring.batch_size = 0;
+        config.services[1].id = config.services[0].id.clone();
+        config.services[2].health_endpoint = "ftp://fks_data/health".into();
+        config.services[3].depends_on = vec!["nope".into()];
+        config.services[4].heartbeat_window_seconds = Some(60);
+        config.services[4].health_endpoint = String::new();
+        config.alerts.routes.push(crate::config::AlertRoute {
+            severity: vec![], tags: vec![], services: vec!["ghost".into()], channels: vec!["sms".into()],
+            slack_channel: None, webhook_url: None, webhook_body: None, continue_matching: false,
+        });
+
+        let issues = check(&config);
+        let dup = format!("services[{}].id", config.services[0].id);
+        let scheme = format!("services[{}].health_endpoint", config.services[2].id);
+        let dep = format!("services[{}].depends_on", config.services[3].id);
+        assert_eq!(paths(&issues, Level::Error), vec!["monitoring.batch_size", &dup, &scheme, &dep, "alerts.routes[0].channels"]);
+        assert_eq!(paths(&issues, Level::Warning), vec!["alerts.routes[0].services"]);
+        assert!(!Report::new(config.services.len(), issues).valid);
+    }
+
+    #[test]
+    fn kubernetes_targets_need_a_selector() {
+        let mut config = Config::default();
+        let id = config.services[0].id.clone();
+        config.services[0].kubernetes = Some(crate::models::KubernetesTarget { namespace: "fks".into(), selector: " ".into(), workload: None });
+        config.services[1].docker_container = None;
+        config.services[1].probes = vec![ProbeConfig::Kubernetes { selector: None, namespace: None, name: None }];
+        let issues = check(&config);
+        let (probe, selector, both) = (format!("services[{}].probes[0]", config.services[1].id), format!("services[{id}].kubernetes.selector"), format!("services[{id}]"));
+        assert_eq!(paths(&issues, Level::Error), vec![&selector, &probe]);
+        assert_eq!(paths(&issues, Level::Warning), vec![&both], "restarts go to Kubernetes, not the container");
+    }
+
+    #[test]
+    fn docker_hosts_must_resolve() {
+        let mut config = Config::default();
+        config.hosts.insert("edge".into(), crate::config::DockerHostConfig { url: "tcp://10.0.0.7:2375".into(), tls: None });
+        config.hosts.insert("old".into(), crate::config::DockerHostConfig { url: "10.0.0.8:2375".into(), tls: None });
+        config.services[0].docker_host = Some("edge".into());
+        config.services[1].docker_host = Some("egde".into());
+        config.services[2].docker_host = Some("old".into());
+        config.services[3].docker_host = Some("tcp://10.0.0.9:2375".into());
+        config.services[3].docker_container = None;
+        let issues = check(&config);
+        let typo = format!("services[{}].docker_host", config.services[1].id);
+        let unused = format!("services[{}].docker_host", config.services[3].id);
+        assert_eq!(paths(&issues, Level::Error), vec![&typo, "hosts.old"], "a bad entry is reported once, at [hosts]");
+        assert_eq!(paths(&issues, Level::Warning), vec![&unused]);
+    }
+
+    #[test]
+    fn registry_logins_must_be_complete() {
+        let mut config = Config::default();
+        let registry = |host: &str, username: Option<&str>, helper: Option<&str>| crate::config::RegistryConfig {
+            host: host.into(), username: username.map(Into::into), password: None, token: None, credential_helper: helper.map(Into::into),
+        };
+        config.registries = vec![registry("registry.example.com", None, Some("pass")), registry("", Some("ci"), None)];
+        assert_eq!(paths(&check(&config), Level::Error), vec!["registries[1].host", "registries[1]"]);
+    }
+
+    #[test]
+    fn schedules_need_services_and_a_date_that_comes() {
+        let schedules = toml::from_str::<toml::Table>(r#"schedules = [
+            { name = "nightly", cron = "0 3 * * *", services = ["fks_api", "ghost"] },
+            { name = "nightly", cron = "0 0 30 2 *", services = [] },
+        ]"#).unwrap()["schedules"].clone().try_into().unwrap();
+        let issues = check(&Config { schedules, ..Default::default() });
+        assert_eq!(paths(&issues, Level::Error), vec!["schedules[nightly].name", "schedules[nightly].services", "schedules[nightly].cron"]);
+        assert_eq!(paths(&issues, Level::Warning), vec!["schedules[nightly].services"]);
+    }
+
+    #[test]
+    fn healing_rules_need_unique_names() {
+        let healing = toml::from_str::<toml::Table>(r#"healing = [
+            { name = "unstick", rule = "when status == Unhealthy for 2m then restart", services = ["ghost"] },
+            { name = "unstick", rule = "when memory_usage_mb > 900 then alert" },
+        ]"#).unwrap()["healing"].clone().try_into().unwrap();
+        let issues = check(&Config { healing, ..Default::default() });
+        assert_eq!(paths(&issues, Level::Error), vec!["healing[unstick].name"]);
+        assert_eq!(paths(&issues, Level::Warning), vec!["healing[unstick].services"]);
+    }
+
+    #[test]
+    fn hooks_need_a_name_events_and_a_runnable_action() {
+        let hooks = toml::from_str::<toml::Table>(r#"hooks = [
+            { name = "restart-api", events = ["ServiceDown"], services = ["ghost"], run = { type = "command", command = ["/usr/local/bin/fix", "{service_id}"] } },
+            { name = "restart-api", events = [], run = { type = "command", command = [] } },
+            { name = "page", events = ["HighLatency"], run = { type = "http", url = "{env_url}", method = "not a method" } },
+            { name = "redeploy", events = ["ServiceDown"], timeout_seconds = 0, run = { type = "compose", action = "restart", services = ["ghost"] } },
+        ]"#).unwrap()["hooks"].clone().try_into().unwrap();
+        let issues = check(&Config { hooks, ..Default::default() });
+        assert_eq!(paths(&issues, Level::Error), vec!["hooks[restart-api].name", "hooks[restart-api].events", "hooks[restart-api].run.command", "hooks[page].run.method", "hooks[redeploy].timeout_seconds"]);
+        assert_eq!(paths(&issues, Level::Warning), vec!["hooks[restart-api].services", "hooks[redeploy].run.services"]);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::config::ConfirmationConfig;
+use crate::problem::{ErrorCode, Problem};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Header that carries the token of an armed action
+pub const HEADER: &str = "x-confirmation-token";
+
+/// An armed action: what the first call gets back instead of running it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Challenge {
+    pub token: String,
+    /// What the token confirms, e.g. `restart fks_api`
+    pub action: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Why an action did not run (yet).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Gate {
+    Armed(Challenge),
+    Rejected(String),
+}
+
+impl Gate {
+    /// 202 with the challenge, or a 403 problem for a token that does not confirm this action.
+    pub fn response(self) -> Response {
+        match self {
+            Gate::Armed(challenge) => (StatusCode::ACCEPTED, Json(serde_json::json!({
+                "confirmation_required": true,
+                "message": format!("Repeat the request with {HEADER}: {} before {} to run it", challenge.token, challenge.expires_at.to_rfc3339()),
+                "token": challenge.token,
+                "action": challenge.action,
+                "expires_at": challenge.expires_at,
+            }))).into_response(),
+            Gate::Rejected(error) => Problem::new(ErrorCode::ConfirmationRejected, error).into_response(),
+        }
+    }
+}
+
+struct Armed {
+    key: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// One-time tokens of armed destructive actions (see `[confirmation]`).
+#[derive(Clone, Default)]
+pub struct Confirmations {
+    armed: Arc<Mutex<HashMap<String, Armed>>>,
+}
+
+impl Confirmations {
+    /// Whether the action identified by `key` (described to the caller as `action`) may run at `now`.
+    /// With confirmation required, a call without a token arms the action, and a call with the token
+    /// it got runs it. Tokens work once, for that action only.
+    pub fn check(&self, cfg: &ConfirmationConfig, key: &str, action: &str, token: Option<&str>, now: DateTime<Utc>) -> Result<(), Gate> {
+        if !cfg.required { return Ok(()); }
+        let mut armed = self.armed.lock().unwrap();
+        armed.retain(|_, a| a.expires_at > now);
+        match token {
+            Some(token) => match armed.remove(token) {
+                Some(a) if a.key == key => Ok(()),
+                Some(_) => Err(Gate::Rejected("confirmation token was issued for a different action".into())),
+                None => Err(Gate::Rejected("unknown or expired confirmation token".into())),
+            },
+            None => {
+                let challenge = Challenge {
+                    token: uuid::Uuid::new_v4().to_string(),
+                    action: action.to_string(),
+                    expires_at: now + Duration::seconds(cfg.ttl_seconds.min(i64::MAX as u64) as i64),
+                };
+                armed.insert(challenge.token.clone(), Armed { key: key.to_string(), expires_at: challenge.expires_at });
+                Err(Gate::Armed(challenge))
+            }
+        }
+    }
+}
+
+/// The confirmation token sent with a request, if any.
+pub fn token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(HEADER).and_then(|v| v.to_str().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_confirm_their_own_action_once_before_they_expire() {
+        let cfg = ConfirmationConfig { required: true, ttl_seconds: 60 };
+        let confirmations = Confirmations::default();
+        let now = Utc::now();
+        let arm = |key: &str| match confirmations.check(&cfg, key, key, None, now) {
+            Err(Gate::Armed(challenge)) => challenge,
+            other => panic!("{other:?}"),
+        };
+
+        let challenge = arm("restart fks_api");
+        assert_eq!((challenge.action.as_str(), challenge.expires_at), ("restart fks_api", now + Duration::seconds(60)));
+        assert_eq!(confirmations.check(&cfg, "restart fks_api", "", Some(&challenge.token), now), Ok(()));
+        assert!(matches!(confirmations.check(&cfg, "restart fks_api", "", Some(&challenge.token), now), Err(Gate::Rejected(_))), "used up");
+
+        let challenge = arm("restart fks_api");
+        assert_eq!(confirmations.check(&cfg, "stop fks_api", "", Some(&challenge.token), now), Err(Gate::Rejected("confirmation token was issued for a different action".into())));
+        let challenge = arm("restart fks_api");
+        assert!(matches!(confirmations.check(&cfg, "restart fks_api", "", Some(&challenge.token), now + Duration::seconds(60)), Err(Gate::Rejected(_))), "expired");
+
+        let off = ConfirmationConfig { required: false, ..cfg };
+        assert_eq!(confirmations.check(&off, "restart fks_api", "", None, now), Ok(()));
+    }
+}
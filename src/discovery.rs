@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+use crate::compose::ComposeProject;
+use crate::compose_import;
+use crate::config::DiscoveryConfig;
+use crate::models::{ProbeMode, ServiceConfig, ServiceType};
+use crate::monitor::MonitorHandle;
+
+/// Discovery sources, in order of precedence when both find the same service id
+pub const COMPOSE: &str = "compose";
+pub const DOCKER_LABELS: &str = "docker_labels";
+
+/// Compose sets this on every container; its value is a better default id than the container name
+const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+
+/// Build a service from the labels of `container`, or `None` if it is not labelled
+/// `<prefix>.enable=true`. Keys under the prefix mirror the `[[services]]` fields:
+/// `id` (default: compose service or container name), `name`, `health_endpoint`, `service_type`
+/// (default `Api`), `critical`, `expected_response_time_ms` (default 500), `check_interval_seconds`,
+/// `heartbeat_window_seconds`, `group`, `auto_update`, `tags` (comma separated) and `label.<key>`.
+pub fn service_from_labels(container: &str, labels: &HashMap<String, String>, prefix: &str) -> Result<Option<ServiceConfig>> {
+    let get = |key: &str| labels.get(&format!("{prefix}.{key}")).map(|v| v.trim()).filter(|v| !v.is_empty());
+    if !get("enable").is_some_and(|v| v.eq_ignore_ascii_case("true")) {
+        return Ok(None);
+    }
+    let number = |key: &str| -> Result<Option<u64>> {
+        get(key).map(|v| v.parse().with_context(|| format!("{prefix}.{key}: `{v}` is not a number"))).transpose()
+    };
+
+    let id = get("id").or_else(|| labels.get(COMPOSE_SERVICE_LABEL).map(String::as_str)).unwrap_or(container).to_string();
+    let heartbeat_window_seconds = number("heartbeat_window_seconds")?;
+    let health_endpoint = get("health_endpoint").unwrap_or_default().to_string();
+    if heartbeat_window_seconds.is_none() {
+        match reqwest::Url::parse(&health_endpoint) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            _ if health_endpoint.is_empty() => anyhow::bail!("{prefix}.health_endpoint is required"),
+            _ => anyhow::bail!("{prefix}.health_endpoint: `{health_endpoint}` is not an http(s) URL"),
+        }
+    }
+    let service_type: ServiceType = match get("service_type") {
+        Some(t) => serde_json::from_value(serde_json::Value::String(t.to_string())).with_context(|| format!("{prefix}.service_type: unknown type `{t}`"))?,
+        None => ServiceType::Api,
+    };
+    let flag = |key: &str| -> Result<bool> {
+        get(key).map(|v| v.parse().with_context(|| format!("{prefix}.{key}: `{v}` is not true or false"))).transpose().map(Option::unwrap_or_default)
+    };
+    let critical = flag("critical")?;
+    let label_prefix = format!("{prefix}.label.");
+    let metadata_prefix = format!("{prefix}.metadata.");
+    Ok(Some(ServiceConfig {
+        name: get("name").unwrap_or(&id).to_string(),
+        id,
+        health_endpoint,
+        service_type,
+        docker_container: Some(container.to_string()),
+        expected_response_time_ms: number("expected_response_time_ms")?.unwrap_or(500),
+        critical,
+        check_interval_seconds: number("check_interval_seconds")?,
+        heartbeat_window_seconds,
+        depends_on: vec![],
+        tags: get("tags").map(|t| t.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect()).unwrap_or_default(),
+        probes: vec![],
+        probe_mode: ProbeMode::All,
+        labels: labels.iter().filter_map(|(k, v)| k.strip_prefix(&label_prefix).map(|k| (k.to_string(), v.clone()))).collect(),
+        group: get("group").map(str::to_string),
+        metadata: labels.iter().filter_map(|(k, v)| k.strip_prefix(&metadata_prefix).map(|k| (k.to_string(), v.clone()))).collect(),
+        auto_update: flag("auto_update")?,
+        kubernetes: None,
+        systemd_unit: None,
+        auto_restart: None,
+        drain: None,
+        metrics: None,
+        docker_host: None,
+    }))
+}
+
+/// Services for the running containers labelled for monitoring. Containers with unusable labels are
+/// skipped with a warning.
+async fn discover(docker: &bollard::Docker, config: &DiscoveryConfig) -> Result<Vec<ServiceConfig>> {
+    let filters = HashMap::from([("label".to_string(), vec![format!("{}.enable=true", config.label_prefix)])]);
+    let options = bollard::container::ListContainersOptions { filters, ..Default::default() };
+    let mut services: Vec<ServiceConfig> = Vec::new();
+    for container in docker.list_containers(Some(options)).await? {
+        let Some(name) = container.names.iter().flatten().next().map(|n| n.trim_start_matches('/').to_string()) else { continue };
+        match service_from_labels(&name, &container.labels.unwrap_or_default(), &config.label_prefix) {
+            Ok(Some(service)) if services.iter().any(|s| s.id == service.id) => {
+                warn!(container = %name, service_id = %service.id, "Another discovered container already uses this service id; skipped");
+            }
+            Ok(Some(service)) => services.push(service),
+            Ok(None) => {}
+            Err(e) => warn!(container = %name, error = %e, "Ignoring container with invalid monitoring labels"),
+        }
+    }
+    services.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(services)
+}
+
+/// Start the discovery loops: the compose file (`compose_file`, e.g. from `--compose-file`, overrides
+/// `discovery.compose_file`) and Docker labels.
+pub fn spawn(monitor: MonitorHandle, compose_file: Option<PathBuf>) {
+    tokio::spawn(compose_loop(monitor.clone(), compose_file));
+    tokio::spawn(docker_labels_loop(monitor));
+}
+
+/// Apply a new set of services from `source`, checking new and changed ones right away.
+fn apply(monitor: &MonitorHandle, source: &'static str, services: Vec<ServiceConfig>) {
+    let diff = monitor.set_discovered(source, services);
+    if diff.is_empty() { return; }
+    info!(source, added = ?diff.added, removed = ?diff.removed, changed = ?diff.changed, "🔎 Discovered services updated");
+    for service_id in diff.added.iter().chain(&diff.changed) {
+        monitor.check_soon(service_id);
+    }
+}
+
+/// Read services from a compose file, with container names for `project` (default: the file's directory).
+pub fn services_from_compose_file(path: &Path, project: Option<&str>) -> Result<Vec<ServiceConfig>> {
+    Ok(compose_import::services_from_project(&ComposeProject::load(path, project)?))
+}
+
+/// Re-read the compose file whenever it (or the configured file or compose project) changes. A file that
+/// cannot be read or parsed is logged once and the services read before stay in place.
+async fn compose_loop(monitor: MonitorHandle, override_file: Option<PathBuf>) {
+    let mut seen: Option<(PathBuf, Option<String>, Option<SystemTime>)> = None;
+    loop {
+        let config = monitor.config();
+        let file = override_file.clone().or_else(|| config.discovery.compose_file.as_ref().map(PathBuf::from));
+        match file {
+            None if seen.take().is_some() => apply(&monitor, COMPOSE, Vec::new()),
+            None => {}
+            Some(file) => {
+                let modified = std::fs::metadata(&file).and_then(|m| m.modified()).ok();
+                let current = (file.clone(), config.monitoring.compose_project.clone(), modified);
+                if seen.as_ref() != Some(&current) {
+                    seen = Some(current);
+                    let project = config.monitoring.compose_project.clone();
+                    match tokio::task::spawn_blocking(move || services_from_compose_file(&file, project.as_deref())).await.map_err(anyhow::Error::from).and_then(|r| r) {
+                        Ok(services) => apply(&monitor, COMPOSE, services),
+                        Err(e) => warn!(error = format!("{e:#}"), "Compose discovery failed; keeping the services read before"),
+                    }
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(config.discovery.interval_seconds.max(1))).await;
+    }
+}
+
+/// Rescan containers every `discovery.interval_seconds` while `discovery.docker_labels` is on,
+/// registering new labelled containers and removing services whose container is gone. If Docker
+/// cannot be reached the previously discovered services are kept.
+async fn docker_labels_loop(monitor: MonitorHandle) {
+    let mut docker: Option<bollard::Docker> = None;
+    let mut current: Vec<ServiceConfig> = Vec::new();
+    loop {
+        let config = monitor.config().discovery.clone();
+        if !config.docker_labels {
+            if !current.is_empty() {
+                info!("🔎 Docker label discovery disabled; removing {} discovered services", current.len());
+                current.clear();
+                apply(&monitor, DOCKER_LABELS, Vec::new());
+            }
+            tokio::time::sleep(Duration::from_secs(config.interval_seconds.max(1))).await;
+            continue;
+        }
+        if docker.is_none() {
+            docker = bollard::Docker::connect_with_local_defaults().map_err(|e| warn!(error = %e, "Docker label discovery: cannot connect to Docker")).ok();
+        }
+        if let Some(client) = &docker {
+            match discover(client, &config).await {
+                Ok(found) if serde_json::to_value(&found).ok() != serde_json::to_value(&current).ok() => {
+                    apply(&monitor, DOCKER_LABELS, found.clone());
+                    current = found;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(error = %e, "Docker label discovery failed; keeping {} discovered services", current.len());
+                    docker = None;
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(config.interval_seconds.max(1))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn labelled_containers_become_services() {
+        let found = service_from_labels("fks-fks_ml-1", &labels(&[
+            ("fks.monitor.enable", "true"),
+            ("fks.monitor.health_endpoint", "http://fks_ml:8000/health"),
+            ("fks.monitor.service_type", "Training"),
+            ("fks.monitor.critical", "true"),
+            ("fks.monitor.auto_update", "true"),
+            ("fks.monitor.tags", "ml, gpu"),
+            ("fks.monitor.label.team", "data"),
+            (COMPOSE_SERVICE_LABEL, "fks_ml"),
+        ]), "fks.monitor").unwrap().unwrap();
+        assert_eq!(found.id, "fks_ml");
+        assert_eq!(found.name, "fks_ml");
+        assert_eq!(found.docker_container.as_deref(), Some("fks-fks_ml-1"));
+        assert!(matches!(found.service_type, ServiceType::Training));
+        assert!(found.critical);
+        assert!(found.auto_update);
+        assert_eq!(found.expected_response_time_ms, 500);
+        assert_eq!(found.tags, vec!["ml", "gpu"]);
+        assert_eq!(found.labels["team"], "data");
+    }
+
+    #[test]
+    fn unlabelled_or_broken_containers_are_not_registered() {
+        assert!(service_from_labels("db", &labels(&[("fks.monitor.enable", "false")]), "fks.monitor").unwrap().is_none());
+        assert!(service_from_labels("db", &labels(&[]), "fks.monitor").unwrap().is_none());
+        assert!(service_from_labels("db", &labels(&[("fks.monitor.enable", "true")]), "fks.monitor").is_err(), "needs a health endpoint");
+        let bad = labels(&[("fks.monitor.enable", "true"), ("fks.monitor.health_endpoint", "http://db/health"), ("fks.monitor.check_interval_seconds", "soon")]);
+        assert!(service_from_labels("db", &bad, "fks.monitor").is_err());
+        let heartbeat = labels(&[("fks.monitor.enable", "true"), ("fks.monitor.heartbeat_window_seconds", "3600"), ("fks.monitor.id", "backup")]);
+        assert_eq!(service_from_labels("backup-1", &heartbeat, "fks.monitor").unwrap().unwrap().id, "backup");
+    }
+
+    #[tokio::test]
+    async fn compose_file_services_follow_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let compose = dir.path().join("docker-compose.yml");
+        std::fs::write(&compose, "services:\n  fks_api:\n    ports: [\"8000:8000\"]\n  fks_web: {}\n").unwrap();
+        let mut config = crate::config::Config { services: vec![], ..Default::default() };
+        config.discovery.interval_seconds = 1;
+        config.monitoring.compose_project = Some("fks".into());
+        let monitor = crate::monitor::ServiceMonitor::new(config).await.unwrap().handle();
+        tokio::spawn(compose_loop(monitor.clone(), Some(compose.clone())));
+
+        let ids = |monitor: &MonitorHandle| monitor.config().services.iter().map(|s| s.id.clone()).collect::<Vec<_>>();
+        let wait_for = |expected: Vec<&'static str>| {
+            let monitor = monitor.clone();
+            async move {
+                tokio::time::timeout(Duration::from_secs(5), async {
+                    while ids(&monitor) != expected { tokio::time::sleep(Duration::from_millis(50)).await; }
+                }).await.unwrap_or_else(|_| panic!("services are {:?}", ids(&monitor)));
+            }
+        };
+        wait_for(vec!["fks_api", "fks_web"]).await;
+        let api = monitor.config().services[0].clone();
+        assert_eq!(api.docker_container.as_deref(), Some("fks-fks_api-1"));
+        assert_eq!(monitor.discovery_source("fks_api"), Some(COMPOSE));
+
+        // Make sure the modification time moves on coarse filesystems
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        std::fs::write(&compose, "services:\n  fks_api:\n    ports: [\"8000:8000\"]\n").unwrap();
+        wait_for(vec!["fks_api"]).await;
+
+        std::fs::write(&compose, "services: [not, a, mapping").unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(ids(&monitor), vec!["fks_api"], "a broken file keeps the last good services");
+    }
+}
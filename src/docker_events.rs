@@ -0,0 +1,98 @@
+use bollard::models::{EventMessage, EventMessageTypeEnum};
+use std::collections::HashMap;
+
+/// Container actions subscribed to; everything else is left to the regular checks.
+pub const ACTIONS: [&str; 4] = ["die", "oom", "start", "health_status"];
+
+/// A container state change reported by the Docker daemon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerEvent {
+    Died { exit_code: Option<i64> },
+    OomKilled,
+    Started,
+    /// Result of the container's own `HEALTHCHECK`
+    Health { healthy: bool },
+}
+
+impl ContainerEvent {
+    /// Why the service is down, for events that mean it is; `None` when it may have come back.
+    pub fn failure(&self, container: &str) -> Option<String> {
+        match self {
+            ContainerEvent::Died { exit_code: Some(code) } => Some(format!("container {container} exited with code {code}")),
+            ContainerEvent::Died { exit_code: None } => Some(format!("container {container} exited")),
+            ContainerEvent::OomKilled => Some(format!("container {container} was killed: out of memory")),
+            ContainerEvent::Health { healthy: false } => Some(format!("container {container} healthcheck reports unhealthy")),
+            ContainerEvent::Started | ContainerEvent::Health { healthy: true } => None,
+        }
+    }
+}
+
+/// `/events` filters for [`ACTIONS`] on containers, limited to `project` when one is configured.
+pub fn filters(project: Option<&str>) -> HashMap<String, Vec<String>> {
+    let mut filters = HashMap::from([
+        ("type".to_string(), vec!["container".to_string()]),
+        ("event".to_string(), ACTIONS.iter().map(|a| a.to_string()).collect()),
+    ]);
+    if let Some(project) = project.filter(|p| !p.is_empty()) {
+        filters.insert("label".into(), vec![format!("{}={project}", crate::compose::COMPOSE_PROJECT_LABEL)]);
+    }
+    filters
+}
+
+/// Container name and event of a daemon message; `None` for anything not in [`ACTIONS`].
+pub fn parse(message: &EventMessage) -> Option<(String, ContainerEvent)> {
+    if message.typ != Some(EventMessageTypeEnum::CONTAINER) { return None; }
+    let attributes = message.actor.as_ref()?.attributes.as_ref()?;
+    let name = attributes.get("name")?.trim_start_matches('/').to_string();
+    let event = match message.action.as_deref()? {
+        "die" => ContainerEvent::Died { exit_code: attributes.get("exitCode").and_then(|c| c.parse().ok()) },
+        "oom" => ContainerEvent::OomKilled,
+        "start" => ContainerEvent::Started,
+        // e.g. "health_status: unhealthy"
+        action => match action.strip_prefix("health_status:").map(str::trim) {
+            Some("healthy") => ContainerEvent::Health { healthy: true },
+            Some("unhealthy") => ContainerEvent::Health { healthy: false },
+            _ => return None,
+        },
+    };
+    Some((name, event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::EventActor;
+
+    fn message(action: &str, attributes: &[(&str, &str)]) -> EventMessage {
+        EventMessage {
+            typ: Some(EventMessageTypeEnum::CONTAINER),
+            action: Some(action.to_string()),
+            actor: Some(EventActor {
+                id: Some("3f2a".into()),
+                attributes: Some(attributes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn daemon_messages_become_container_events() {
+        let died = parse(&message("die", &[("name", "fks_api"), ("exitCode", "137")])).unwrap();
+        assert_eq!(died, ("fks_api".to_string(), ContainerEvent::Died { exit_code: Some(137) }));
+        assert_eq!(died.1.failure("fks_api").as_deref(), Some("container fks_api exited with code 137"));
+        assert_eq!(parse(&message("health_status: unhealthy", &[("name", "fks_api")])).unwrap().1, ContainerEvent::Health { healthy: false });
+        assert_eq!(parse(&message("health_status: healthy", &[("name", "fks_api")])).unwrap().1.failure("fks_api"), None);
+        assert_eq!(parse(&message("start", &[("name", "fks_api")])).unwrap().1, ContainerEvent::Started);
+        assert_eq!(parse(&message("exec_start: sh", &[("name", "fks_api")])), None);
+        assert_eq!(parse(&message("die", &[])), None, "no container name");
+        let network = EventMessage { typ: Some(EventMessageTypeEnum::NETWORK), ..message("die", &[("name", "fks_api")]) };
+        assert_eq!(parse(&network), None);
+    }
+
+    #[test]
+    fn filters_are_scoped_to_the_compose_project() {
+        assert!(!filters(None).contains_key("label"));
+        assert_eq!(filters(Some("fks"))["label"], vec!["com.docker.compose.project=fks".to_string()]);
+        assert_eq!(filters(Some("fks"))["event"].len(), ACTIONS.len());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::config::{Config, DockerTlsConfig};
+use crate::models::ServiceConfig;
+use anyhow::{bail, Result};
+use bollard::{Docker, API_DEFAULT_VERSION};
+use std::path::Path;
+use std::time::Duration;
+
+/// Host shown for services on the local daemon, in status and metrics labels
+pub const LOCAL: &str = "local";
+
+/// Request timeout of remote connections, as for the local default
+const TIMEOUT_SECONDS: u64 = 120;
+/// How long [`ping`] waits for a daemon to answer
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Name of the daemon `service`'s container runs on.
+pub fn label(service: &ServiceConfig) -> &str {
+    service.docker_host.as_deref().unwrap_or(LOCAL)
+}
+
+/// Host shown in `service`'s status; `None` for services without a container.
+pub fn status_label(service: &ServiceConfig) -> Option<String> {
+    service.docker_container.as_ref().map(|_| label(service).to_string())
+}
+
+/// Daemon URL of `host` (an entry of `[hosts]`, or a URL), normalized to `unix://` or `tcp://`;
+/// `None` for the local daemon.
+pub fn url(config: &Config, host: Option<&str>) -> Result<Option<String>> {
+    let Some(host) = host.filter(|h| *h != LOCAL) else { return Ok(None) };
+    let (url, tls) = match config.hosts.get(host) {
+        Some(entry) => (entry.url.as_str(), entry.tls.is_some()),
+        None if host.contains("://") => (host, false),
+        None => bail!("unknown Docker host `{host}`; add it to [hosts] or use a URL"),
+    };
+    match url.split_once("://") {
+        Some(("unix", _)) if tls => bail!("Docker host `{host}`: `tls` needs a tcp:// or https:// URL"),
+        Some(("unix", path)) if !path.is_empty() => Ok(Some(url.to_string())),
+        Some(("https", _)) if !tls => bail!("Docker host `{host}` uses TLS; add it to [hosts] with a `tls` section holding its certificates"),
+        Some(("tcp" | "http" | "https", address)) if !address.is_empty() => Ok(Some(format!("tcp://{}", address.trim_end_matches('/')))),
+        _ => bail!("Docker host `{host}`: `{url}` is not a unix://, tcp://, http:// or https:// URL"),
+    }
+}
+
+/// Certificates `host` is reached with, if its `[hosts]` entry has any.
+fn tls<'a>(config: &'a Config, host: Option<&str>) -> Option<&'a DockerTlsConfig> {
+    config.hosts.get(host?)?.tls.as_ref()
+}
+
+/// Client of the daemon `host` runs on (see [`url`]).
+pub fn connect(config: &Config, host: Option<&str>) -> Result<Docker> {
+    let docker = match (url(config, host)?, tls(config, host)) {
+        (None, _) => Docker::connect_with_local_defaults()?,
+        (Some(url), Some(tls)) => Docker::connect_with_ssl(
+            &url,
+            Path::new(&tls.key_file),
+            Path::new(&tls.cert_file),
+            Path::new(&tls.ca_file),
+            TIMEOUT_SECONDS,
+            API_DEFAULT_VERSION,
+        )?,
+        (Some(url), None) if url.starts_with("unix://") => Docker::connect_with_unix(&url, TIMEOUT_SECONDS, API_DEFAULT_VERSION)?,
+        (Some(url), None) => Docker::connect_with_http(&url, TIMEOUT_SECONDS, API_DEFAULT_VERSION)?,
+    };
+    Ok(docker)
+}
+
+/// Global `docker` CLI options reaching `host`, e.g. for `docker compose`; empty for the local daemon.
+pub fn cli_args(config: &Config, host: Option<&str>) -> Result<Vec<String>> {
+    let Some(url) = url(config, host)? else { return Ok(Vec::new()) };
+    let mut args = vec!["--host".to_string(), url];
+    if let Some(tls) = tls(config, host) {
+        args.extend(["--tlsverify", "--tlscacert", &tls.ca_file, "--tlscert", &tls.cert_file, "--tlskey", &tls.key_file].map(String::from));
+    }
+    Ok(args)
+}
+
+/// Check a host named in an API request: `local` or an entry of `[hosts]`. Unlike a service's
+/// `docker_host`, a request cannot name a raw URL.
+pub fn listed(config: &Config, host: Option<&str>) -> Result<()> {
+    match host {
+        Some(host) if host != LOCAL && !config.hosts.contains_key(host) => bail!("unknown Docker host `{host}`; expected `local` or an entry of [hosts]"),
+        _ => Ok(()),
+    }
+}
+
+/// Client of the daemon `service`'s container runs on.
+pub fn connect_for(config: &Config, service: &ServiceConfig) -> Result<Docker> {
+    connect(config, service.docker_host.as_deref())
+}
+
+/// Whether the daemon `host` runs on answers a ping within [`PING_TIMEOUT`].
+pub async fn ping(config: &Config, host: Option<&str>) -> Result<()> {
+    let docker = connect(config, host)?;
+    match tokio::time::timeout(PING_TIMEOUT, docker.ping()).await {
+        Ok(pinged) => pinged.map(drop).map_err(Into::into),
+        Err(_) => bail!("no answer within {}s", PING_TIMEOUT.as_secs()),
+    }
+}
+
+/// Hosts with at least one configured container, each once; `None` is the local daemon.
+pub fn in_use(config: &Config) -> Vec<Option<String>> {
+    let mut hosts: Vec<Option<String>> = config.services.iter()
+        .filter(|s| s.docker_container.is_some())
+        .map(|s| s.docker_host.clone().filter(|h| h != LOCAL))
+        .collect();
+    hosts.sort();
+    hosts.dedup();
+    hosts
+}
+
+/// Whether `service`'s container is `container` on `host`.
+pub fn runs(service: &ServiceConfig, host: Option<&str>, container: &str) -> bool {
+    service.docker_container.as_deref() == Some(container) && service.docker_host.as_deref().filter(|h| *h != LOCAL) == host
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DockerHostConfig, DockerTlsConfig};
+
+    #[test]
+    fn hosts_resolve_to_daemon_urls() {
+        let mut config = Config::default();
+        config.hosts.insert("edge".into(), DockerHostConfig { url: "http://10.0.0.7:2375/".into(), tls: None });
+        config.hosts.insert("gpu".into(), DockerHostConfig { url: "unix:///run/gpu-docker.sock".into(), tls: None });
+        let tls = DockerTlsConfig { ca_file: "ca.pem".into(), cert_file: "cert.pem".into(), key_file: "key.pem".into() };
+        config.hosts.insert("secure".into(), DockerHostConfig { url: "tcp://10.0.0.8:2376".into(), tls: Some(tls) });
+
+        assert_eq!(url(&config, None).unwrap(), None);
+        assert_eq!(url(&config, Some("local")).unwrap(), None);
+        assert_eq!(url(&config, Some("edge")).unwrap().as_deref(), Some("tcp://10.0.0.7:2375"));
+        assert_eq!(url(&config, Some("gpu")).unwrap().as_deref(), Some("unix:///run/gpu-docker.sock"));
+        assert_eq!(url(&config, Some("tcp://10.0.0.9:2375")).unwrap().as_deref(), Some("tcp://10.0.0.9:2375"));
+        assert_eq!(url(&config, Some("secure")).unwrap().as_deref(), Some("tcp://10.0.0.8:2376"));
+        assert!(url(&config, Some("https://10.0.0.9:2376")).unwrap_err().to_string().contains("add it to [hosts]"));
+        assert_eq!(cli_args(&config, Some("secure")).unwrap(), [
+            "--host", "tcp://10.0.0.8:2376", "--tlsverify", "--tlscacert", "ca.pem", "--tlscert", "cert.pem", "--tlskey", "key.pem",
+        ]);
+        assert_eq!(cli_args(&config, Some("edge")).unwrap(), ["--host", "tcp://10.0.0.7:2375"]);
+        assert!(cli_args(&config, None).unwrap().is_empty());
+        assert!(url(&config, Some("edgee")).unwrap_err().to_string().contains("unknown Docker host `edgee`"));
+        assert!(url(&config, Some("ssh://root@10.0.0.7")).is_err());
+        assert!(listed(&config, Some("edge")).is_ok() && listed(&config, Some("local")).is_ok());
+        assert!(listed(&config, Some("tcp://10.0.0.9:2375")).unwrap_err().to_string().contains("unknown Docker host"));
+    }
+
+    #[test]
+    fn containers_are_matched_per_host() {
+        let mut config = Config::default();
+        config.services[1].docker_host = Some("edge".into());
+        config.services[2].docker_host = Some("local".into());
+        assert_eq!(in_use(&config), vec![None, Some("edge".to_string())]);
+        assert!(runs(&config.services[0], None, "fks_api"));
+        assert!(!runs(&config.services[1], None, "fks_auth"));
+        assert!(runs(&config.services[1], Some("edge"), "fks_auth"));
+        assert!(runs(&config.services[2], None, "fks_data"));
+        assert_eq!(label(&config.services[1]), "edge");
+        assert_eq!(label(&config.services[0]), LOCAL);
+    }
+}
@@ -0,0 +1,109 @@
+use bollard::container::{MemoryStatsStats, Stats};
+
+/// Resource usage of one container, from a sample of the Docker stats stream. Fields the daemon does
+/// not report (stopped containers, the first sample's CPU) are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContainerStats {
+    pub name: String,
+    pub cpu_percent: Option<f64>,
+    pub memory_usage_bytes: Option<u64>,
+    pub memory_limit_bytes: Option<u64>,
+    pub net_in_bytes: Option<u64>,
+    pub net_out_bytes: Option<u64>,
+    pub block_read_bytes: Option<u64>,
+    pub block_write_bytes: Option<u64>,
+}
+
+impl ContainerStats {
+    pub fn memory_usage_mb(&self) -> Option<f64> {
+        self.memory_usage_bytes.map(|b| b as f64 / (1024.0 * 1024.0))
+    }
+
+    /// Compute usage the way `docker stats` does: CPU from the change in cgroup counters since the
+    /// previous sample, memory without the reclaimable page cache, network and block I/O summed.
+    pub fn from_api(name: &str, stats: &Stats) -> Self {
+        let networks = stats.networks.as_ref().map(|n| n.values().copied().collect::<Vec<_>>());
+        let block = |op: &str| {
+            let entries = stats.blkio_stats.io_service_bytes_recursive.as_ref()?;
+            Some(entries.iter().filter(|e| e.op.eq_ignore_ascii_case(op)).map(|e| e.value).sum())
+        };
+        ContainerStats {
+            name: name.to_string(),
+            cpu_percent: cpu_percent(stats),
+            memory_usage_bytes: memory_usage(stats),
+            memory_limit_bytes: stats.memory_stats.limit,
+            net_in_bytes: networks.as_ref().map(|n| n.iter().map(|s| s.rx_bytes).sum()),
+            net_out_bytes: networks.as_ref().map(|n| n.iter().map(|s| s.tx_bytes).sum()),
+            block_read_bytes: block("read"),
+            block_write_bytes: block("write"),
+        }
+    }
+}
+
+fn cpu_percent(stats: &Stats) -> Option<f64> {
+    let (cpu, pre) = (&stats.cpu_stats, &stats.precpu_stats);
+    let cpu_delta = cpu.cpu_usage.total_usage.checked_sub(pre.cpu_usage.total_usage)?;
+    let system_delta = cpu.system_cpu_usage?.checked_sub(pre.system_cpu_usage?)?;
+    if system_delta == 0 { return None; }
+    let online = cpu.online_cpus.filter(|n| *n > 0).or_else(|| cpu.cpu_usage.percpu_usage.as_ref().map(|p| p.len() as u64)).unwrap_or(1);
+    Some(cpu_delta as f64 / system_delta as f64 * online as f64 * 100.0)
+}
+
+fn memory_usage(stats: &Stats) -> Option<u64> {
+    let usage = stats.memory_stats.usage?;
+    let cache = match stats.memory_stats.stats {
+        Some(MemoryStatsStats::V1(v1)) => v1.total_inactive_file,
+        Some(MemoryStatsStats::V2(v2)) => v2.inactive_file,
+        None => 0,
+    };
+    Some(if cache < usage { usage - cache } else { usage })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(json: serde_json::Value) -> Stats {
+        let mut base = serde_json::json!({
+            "read": "2025-01-01T00:00:01Z", "preread": "2025-01-01T00:00:00Z", "num_procs": 0,
+            "pids_stats": {}, "memory_stats": {}, "blkio_stats": {}, "storage_stats": {},
+            "cpu_stats": {"cpu_usage": {"total_usage": 0, "usage_in_usermode": 0, "usage_in_kernelmode": 0},
+                          "throttling_data": {"periods": 0, "throttled_periods": 0, "throttled_time": 0}},
+        });
+        base["precpu_stats"] = base["cpu_stats"].clone();
+        for (key, value) in json.as_object().unwrap() {
+            base[key] = value.clone();
+        }
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn usage_is_computed_from_raw_counters() {
+        let cpu = |total: u64, system: u64| serde_json::json!({
+            "cpu_usage": {"total_usage": total, "usage_in_usermode": 0, "usage_in_kernelmode": 0},
+            "system_cpu_usage": system, "online_cpus": 4,
+            "throttling_data": {"periods": 0, "throttled_periods": 0, "throttled_time": 0},
+        });
+        let stats = ContainerStats::from_api("fks_api", &sample(serde_json::json!({
+            "cpu_stats": cpu(1_500_000_000, 20_000_000_000),
+            "precpu_stats": cpu(1_000_000_000, 10_000_000_000),
+            "memory_stats": {"usage": 200u64 << 20, "limit": 2u64 << 30},
+            "networks": {"eth0": {"rx_bytes": 1200, "tx_bytes": 648, "rx_dropped": 0, "rx_errors": 0, "rx_packets": 0, "tx_dropped": 0, "tx_errors": 0, "tx_packets": 0},
+                         "eth1": {"rx_bytes": 800, "tx_bytes": 2, "rx_dropped": 0, "rx_errors": 0, "rx_packets": 0, "tx_dropped": 0, "tx_errors": 0, "tx_packets": 0}},
+            "blkio_stats": {"io_service_bytes_recursive": [
+                {"major": 8, "minor": 0, "op": "read", "value": 4096}, {"major": 8, "minor": 0, "op": "write", "value": 100},
+                {"major": 8, "minor": 16, "op": "Write", "value": 4000}]},
+        })));
+        assert_eq!(stats.cpu_percent, Some(20.0), "0.5s of CPU over 10s of 4-CPU system time");
+        assert_eq!(stats.memory_usage_mb(), Some(200.0));
+        assert_eq!(stats.memory_limit_bytes, Some(2u64 << 30));
+        assert_eq!((stats.net_in_bytes, stats.net_out_bytes), (Some(2000), Some(650)));
+        assert_eq!((stats.block_read_bytes, stats.block_write_bytes), (Some(4096), Some(4100)));
+    }
+
+    #[test]
+    fn stopped_containers_report_nothing() {
+        let stats = ContainerStats::from_api("fks_worker", &sample(serde_json::json!({})));
+        assert_eq!(stats, ContainerStats { name: "fks_worker".into(), ..Default::default() });
+    }
+}
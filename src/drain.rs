@@ -0,0 +1,74 @@
+use crate::models::DrainConfig;
+use crate::outbound::{self, Destination};
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::StatusCode;
+use std::time::{Duration, Instant};
+
+/// Ask the service to drain at `cfg.url`, then wait until `cfg.status_url` reports it idle. Returns
+/// how long it took; an error when the request fails or the drain outlasts `timeout_seconds`.
+pub async fn drain(cfg: &DrainConfig) -> Result<Duration> {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(cfg.timeout_seconds);
+    tokio::time::timeout(timeout, wait_for_drain(cfg))
+        .await
+        .map_err(|_| anyhow!("still draining after {}s", cfg.timeout_seconds))??;
+    Ok(started.elapsed())
+}
+
+async fn wait_for_drain(cfg: &DrainConfig) -> Result<()> {
+    let client = reqwest::Client::new();
+    let method = reqwest::Method::from_bytes(cfg.method.to_ascii_uppercase().as_bytes()).with_context(|| format!("invalid method `{}`", cfg.method))?;
+    let status = outbound::send(Destination::Drain, client.request(method.clone(), &cfg.url)).await.context("drain request")?.status();
+    if !status.is_success() { bail!("{method} {} returned {status}", cfg.url); }
+    let Some(status_url) = &cfg.status_url else { return Ok(()) };
+    loop {
+        // A failed poll (e.g. the service is busy) is not the end of the drain
+        match outbound::send(Destination::Drain, client.get(status_url)).await {
+            Ok(response) if response.status() == StatusCode::OK => return Ok(()),
+            Ok(response) => tracing::debug!(url = %status_url, status = %response.status(), "Still draining"),
+            Err(e) => tracing::debug!(url = %status_url, error = format!("{e:#}"), "Drain status check failed"),
+        }
+        tokio::time::sleep(Duration::from_secs(cfg.poll_interval_seconds.max(1))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn waits_until_in_flight_work_is_done() {
+        use axum::{http::StatusCode, routing::{get, post}, Router};
+        let polls = Arc::new(AtomicU32::new(0));
+        let counter = polls.clone();
+        let app = Router::new()
+            .route("/admin/drain", post(|| async { StatusCode::ACCEPTED }))
+            .route("/admin/drain/status", get(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                async move { if n < 2 { StatusCode::CONFLICT } else { StatusCode::OK } }
+            }))
+            .route("/busy", get(|| async { StatusCode::CONFLICT }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap(); });
+
+        let mut cfg = DrainConfig {
+            url: format!("http://{addr}/admin/drain"),
+            method: "post".into(),
+            status_url: Some(format!("http://{addr}/admin/drain/status")),
+            timeout_seconds: 10,
+            poll_interval_seconds: 1,
+            force: false,
+        };
+        drain(&cfg).await.unwrap();
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+
+        cfg.status_url = Some(format!("http://{addr}/busy"));
+        cfg.timeout_seconds = 1;
+        assert_eq!(drain(&cfg).await.unwrap_err().to_string(), "still draining after 1s");
+        cfg.url = format!("http://{addr}/missing");
+        assert!(drain(&cfg).await.unwrap_err().to_string().ends_with("returned 404 Not Found"));
+    }
+}
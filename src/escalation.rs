@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::EscalationStep;
+
+/// Open escalation for a critical service that went down and has not recovered yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escalation {
+    pub service_id: String,
+    pub service_name: String,
+    /// When the first (ServiceDown) notification went out
+    pub since: DateTime<Utc>,
+    /// Number of escalation steps already fired
+    pub level: usize,
+    /// Channels notified by escalation steps so far
+    pub notified_channels: Vec<String>,
+    pub last_escalated_at: Option<DateTime<Utc>>,
+    /// When the next step fires if the service is still unhealthy (None = policy exhausted)
+    pub next_escalation_at: Option<DateTime<Utc>>,
+}
+
+impl Escalation {
+    pub fn new(service_id: &str, service_name: &str, since: DateTime<Utc>, policy: &[EscalationStep]) -> Self {
+        Self {
+            service_id: service_id.to_string(),
+            service_name: service_name.to_string(),
+            since,
+            level: 0,
+            notified_channels: Vec::new(),
+            last_escalated_at: None,
+            next_escalation_at: step_due_at(since, policy, 0),
+        }
+    }
+
+    /// Fire the next step if it is due, returning it. Steps are timed from `since`, so several
+    /// overdue steps are taken one per call.
+    pub fn advance(&mut self, policy: &[EscalationStep], now: DateTime<Utc>) -> Option<EscalationStep> {
+        let due = self.next_escalation_at?;
+        if now < due { return None; }
+        let step = policy.get(self.level)?.clone();
+        self.level += 1;
+        self.last_escalated_at = Some(now);
+        self.next_escalation_at = step_due_at(self.since, policy, self.level);
+        for channel in &step.channels {
+            if !self.notified_channels.contains(channel) { self.notified_channels.push(channel.clone()); }
+        }
+        Some(step)
+    }
+}
+
+fn step_due_at(since: DateTime<Utc>, policy: &[EscalationStep], level: usize) -> Option<DateTime<Utc>> {
+    policy.get(level).map(|s| since + chrono::Duration::minutes(s.after_minutes as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> Vec<EscalationStep> {
+        vec![
+            EscalationStep { after_minutes: 10, channels: vec!["pagerduty".into()] },
+            EscalationStep { after_minutes: 30, channels: vec!["email".into(), "pagerduty".into()] },
+        ]
+    }
+
+    #[test]
+    fn steps_fire_in_order_once_due() {
+        let t0 = Utc::now();
+        let at = |m| t0 + chrono::Duration::minutes(m);
+        let mut esc = Escalation::new("fks_api", "FKS API", t0, &policy());
+        assert_eq!(esc.next_escalation_at, Some(at(10)));
+        assert!(esc.advance(&policy(), at(9)).is_none());
+        assert_eq!(esc.advance(&policy(), at(10)).unwrap().channels, vec!["pagerduty"]);
+        assert!(esc.advance(&policy(), at(11)).is_none());
+        // Overdue steps still fire one at a time
+        assert_eq!(esc.advance(&policy(), at(45)).unwrap().after_minutes, 30);
+        assert_eq!(esc.level, 2);
+        assert_eq!(esc.notified_channels, vec!["pagerduty", "email"]);
+        assert!(esc.next_escalation_at.is_none());
+        assert!(esc.advance(&policy(), at(120)).is_none());
+    }
+}
@@ -0,0 +1,63 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::Value;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Weak ETag of `body`, leaving out the top-level `volatile` keys (e.g. a timestamp of the response
+/// itself) so it only changes with the state the body describes.
+pub fn of(body: &Value, volatile: &[&str]) -> String {
+    let mut hasher = DefaultHasher::new();
+    match body {
+        Value::Object(fields) => {
+            for (key, value) in fields.iter().filter(|(key, _)| !volatile.contains(&key.as_str())) {
+                key.hash(&mut hasher);
+                value.to_string().hash(&mut hasher);
+            }
+        }
+        other => other.to_string().hash(&mut hasher),
+    }
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+/// Whether `If-None-Match` in `headers` names `etag` (or is `*`); weak comparison, as for GET.
+pub fn matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    headers.get_all(header::IF_NONE_MATCH).iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+/// `body` as JSON with its ETag, or `304 Not Modified` without a body when the client already has it.
+/// Clients are asked to revalidate each time rather than cache blindly.
+pub fn conditional(headers: &HeaderMap, body: Value, volatile: &[&str]) -> Response {
+    let etag = of(&body, volatile);
+    let mut response = if matches(headers, &etag) { StatusCode::NOT_MODIFIED.into_response() } else { Json(body).into_response() };
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unchanged_state_is_not_modified() {
+        let body = json!({"overallStatus": "healthy", "lastUpdate": "2025-01-01T00:00:00Z"});
+        let etag = of(&body, &["lastUpdate"]);
+        assert_eq!(etag, of(&json!({"overallStatus": "healthy", "lastUpdate": "2025-01-01T00:00:05Z"}), &["lastUpdate"]));
+        assert_ne!(etag, of(&json!({"overallStatus": "degraded", "lastUpdate": "2025-01-01T00:00:05Z"}), &["lastUpdate"]));
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(conditional(&headers, body.clone(), &["lastUpdate"]).status(), StatusCode::OK);
+        headers.insert(header::IF_NONE_MATCH, format!("\"other\", {}", etag.trim_start_matches("W/")).parse().unwrap());
+        let response = conditional(&headers, body, &["lastUpdate"]);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+    }
+}
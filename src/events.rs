@@ -0,0 +1,139 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::{EventType, MonitorEvent};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+/// History key of events that belong to no service
+pub const SYSTEM: &str = "system";
+
+/// Filters of `GET /api/events`.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventQuery {
+    /// Service id, or `system` for events of no service
+    pub service: Option<String>,
+    /// Comma-separated event types, e.g. `ServiceDown,ServiceUp`
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EventPage {
+    /// Newest first
+    pub items: Vec<MonitorEvent>,
+    /// Pass as `cursor` for the next (older) page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Where a page starts: after the events newer than `before`, and the first `skip` events at exactly
+/// `before`, which earlier pages returned already.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cursor {
+    before: DateTime<Utc>,
+    skip: usize,
+}
+
+impl Cursor {
+    fn parse(raw: &str) -> Result<Self> {
+        let invalid = || anyhow!("invalid cursor `{raw}`");
+        let (nanos, skip) = raw.split_once('.').ok_or_else(invalid)?;
+        let before = DateTime::from_timestamp_nanos(nanos.parse().map_err(|_| invalid())?);
+        Ok(Cursor { before, skip: skip.parse().map_err(|_| invalid())? })
+    }
+
+    fn encode(&self) -> String {
+        format!("{}.{}", self.before.timestamp_nanos_opt().unwrap_or_default(), self.skip)
+    }
+}
+
+/// One page of `history` (events by service id, oldest first, as the monitor keeps them) matching `q`.
+pub fn page<'a>(history: impl IntoIterator<Item = (&'a str, &'a [MonitorEvent])>, q: &EventQuery) -> Result<EventPage> {
+    let limit = q.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let types = q.event_type.as_deref().map(parse_types).transpose()?;
+    let cursor = q.cursor.as_deref().map(Cursor::parse).transpose()?;
+
+    let mut matching: Vec<(&str, usize, &MonitorEvent)> = history.into_iter()
+        .filter(|(key, _)| q.service.as_deref().is_none_or(|s| s == *key))
+        .flat_map(|(key, events)| events.iter().enumerate().map(move |(i, e)| (key, i, e)))
+        .filter(|(_, _, e)| types.as_ref().is_none_or(|t| t.contains(&e.event_type)))
+        .filter(|(_, _, e)| q.since.is_none_or(|since| e.timestamp >= since))
+        .collect();
+    // Newest first; events at the same instant in a fixed order, so a cursor can count past them
+    matching.sort_by(|a, b| b.2.timestamp.cmp(&a.2.timestamp).then_with(|| a.0.cmp(b.0)).then_with(|| b.1.cmp(&a.1)));
+
+    let start = match cursor {
+        None => 0,
+        Some(c) => {
+            let newer = matching.iter().take_while(|(_, _, e)| e.timestamp > c.before).count();
+            let same = matching[newer..].iter().take_while(|(_, _, e)| e.timestamp == c.before).count();
+            newer + c.skip.min(same)
+        }
+    };
+    let end = (start + limit).min(matching.len());
+    let next_cursor = (end < matching.len()).then(|| {
+        let before = matching[end - 1].2.timestamp;
+        let skip = matching[..end].iter().rev().take_while(|(_, _, e)| e.timestamp == before).count();
+        Cursor { before, skip }.encode()
+    });
+    Ok(EventPage { items: matching[start..end].iter().map(|(_, _, e)| (*e).clone()).collect(), next_cursor })
+}
+
+/// Event types named in a comma-separated list, e.g. `ServiceDown,ServiceUp`.
+pub fn parse_types(raw: &str) -> Result<Vec<EventType>> {
+    raw.split(',').map(str::trim).filter(|t| !t.is_empty())
+        .map(|t| serde_json::from_value(serde_json::Value::String(t.to_string())).map_err(|_| anyhow!("unknown event type `{t}`")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: EventType, service: Option<&str>, second: i64) -> MonitorEvent {
+        MonitorEvent {
+            event_type,
+            service_id: service.map(str::to_string),
+            message: format!("{second}"),
+            timestamp: DateTime::from_timestamp(1_760_000_000 + second, 0).unwrap(),
+            data: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn pages_walk_back_through_the_filtered_history() {
+        let api = vec![
+            event(EventType::ServiceDown, Some("api"), 1),
+            event(EventType::ServiceUp, Some("api"), 2),
+            event(EventType::ServiceDown, Some("api"), 3),
+            event(EventType::ServiceUp, Some("api"), 3),
+        ];
+        let system = vec![event(EventType::SystemAlert, None, 3)];
+        let history = || [("api", api.as_slice()), (SYSTEM, system.as_slice())];
+        let messages = |page: &EventPage| page.items.iter().map(|e| format!("{:?}@{}", e.event_type, e.message)).collect::<Vec<_>>();
+
+        let first = page(history(), &EventQuery { limit: Some(2), ..Default::default() }).unwrap();
+        assert_eq!(messages(&first), ["ServiceUp@3", "ServiceDown@3"]);
+        let second = page(history(), &EventQuery { limit: Some(2), cursor: first.next_cursor.clone(), ..Default::default() }).unwrap();
+        assert_eq!(messages(&second), ["SystemAlert@3", "ServiceUp@2"], "continues among events of the same instant");
+        let last = page(history(), &EventQuery { limit: Some(2), cursor: second.next_cursor, ..Default::default() }).unwrap();
+        assert_eq!((messages(&last), last.next_cursor), (vec!["ServiceDown@1".to_string()], None));
+
+        let q = EventQuery { service: Some("api".into()), event_type: Some("ServiceDown, ServiceUp".into()), since: Some(api[1].timestamp), ..Default::default() };
+        assert_eq!(messages(&page(history(), &q).unwrap()), ["ServiceUp@3", "ServiceDown@3", "ServiceUp@2"]);
+        let q = EventQuery { service: Some(SYSTEM.into()), ..Default::default() };
+        assert_eq!(messages(&page(history(), &q).unwrap()), ["SystemAlert@3"]);
+
+        assert!(page(history(), &EventQuery { event_type: Some("Reboot".into()), ..Default::default() }).is_err());
+        assert!(page(history(), &EventQuery { cursor: Some("yesterday".into()), ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{bail, Result};
+use futures::future::join_all;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::config::Config;
+use crate::models::MetricsEndpoint;
+use crate::outbound::{self, Destination};
+
+/// Label naming the service a federated series came from; one the service set itself is kept as
+/// `exported_service_id`, like Prometheus does without `honor_labels`.
+const SERVICE_LABEL: &str = "service_id";
+
+/// A metric family: `# HELP`/`# TYPE` plus its samples (`_bucket`, `_sum`, ... included).
+#[derive(Debug, Default)]
+struct Family {
+    help: Option<String>,
+    kind: Option<String>,
+    samples: Vec<Sample>,
+}
+
+#[derive(Debug)]
+struct Sample {
+    name: String,
+    labels: Vec<(String, String)>,
+    /// Value and optional timestamp, as scraped
+    value: String,
+}
+
+/// Scrape every service with a `metrics` endpoint and render the series they select as one
+/// Prometheus text exposition, each labelled with its `service_id`. `fks_federation_up` tells which
+/// scrapes worked.
+pub async fn federate(config: &Config) -> String {
+    let cfg = &config.federation;
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(cfg.timeout_seconds)).build().unwrap_or_default();
+    let targets: Vec<_> = config.services.iter().filter_map(|s| s.metrics.as_ref().map(|m| (s.id.as_str(), m))).collect();
+    let scrapes = join_all(targets.iter().map(|(id, endpoint)| {
+        let client = &client;
+        async move {
+            let scraped = scrape(client, endpoint).await;
+            if let Err(e) = &scraped {
+                warn!(service_id = %id, error = format!("{e:#}"), "Federated metrics scrape failed");
+            }
+            (*id, *endpoint, scraped)
+        }
+    })).await;
+    merge(scrapes, cfg.max_series_per_service)
+}
+
+async fn scrape(client: &reqwest::Client, endpoint: &MetricsEndpoint) -> Result<String> {
+    // Ask for the classic text format rather than OpenMetrics or protobuf
+    let request = client.get(&endpoint.url).header(reqwest::header::ACCEPT, "text/plain;version=0.0.4");
+    let response = outbound::send(Destination::Federation, request).await?;
+    if !response.status().is_success() {
+        bail!("{} returned {}", endpoint.url, response.status());
+    }
+    Ok(response.text().await?)
+}
+
+fn merge(scrapes: Vec<(&str, &MetricsEndpoint, Result<String>)>, max_series: usize) -> String {
+    let mut families: BTreeMap<String, Family> = BTreeMap::new();
+    let mut up = Vec::new();
+    for (id, endpoint, scraped) in scrapes {
+        up.push((id, scraped.is_ok()));
+        let Ok(text) = scraped else { continue };
+        let mut series = 0;
+        'families: for (name, family) in parse(&text).into_iter().filter(|(name, _)| selected(&endpoint.include, name)) {
+            let merged = families.entry(name).or_default();
+            merged.help = merged.help.take().or(family.help);
+            merged.kind = merged.kind.take().or(family.kind);
+            for mut sample in family.samples {
+                if series == max_series {
+                    debug!(service_id = %id, max_series, "Federated series cut off");
+                    break 'families;
+                }
+                series += 1;
+                for (label, _) in sample.labels.iter_mut().filter(|(label, _)| label == SERVICE_LABEL) {
+                    *label = format!("exported_{SERVICE_LABEL}");
+                }
+                sample.labels.insert(0, (SERVICE_LABEL.to_string(), id.to_string()));
+                merged.samples.push(sample);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for (name, family) in families {
+        if let Some(help) = family.help { out.push_str(&format!("# HELP {name} {help}\n")); }
+        if let Some(kind) = family.kind { out.push_str(&format!("# TYPE {name} {kind}\n")); }
+        for sample in family.samples {
+            let labels: Vec<String> = sample.labels.iter().map(|(k, v)| format!("{k}=\"{v}\"")).collect();
+            out.push_str(&format!("{}{{{}}} {}\n", sample.name, labels.join(","), sample.value));
+        }
+    }
+    out.push_str("# HELP fks_federation_up Whether the last scrape of the service's metrics endpoint worked\n# TYPE fks_federation_up gauge\n");
+    for (id, ok) in up {
+        out.push_str(&format!("fks_federation_up{{{SERVICE_LABEL}=\"{id}\"}} {}\n", u8::from(ok)));
+    }
+    out
+}
+
+/// Whether `family` is among the `include` patterns: exact names or prefixes ending in `*`. No
+/// patterns selects everything.
+fn selected(include: &[String], family: &str) -> bool {
+    include.is_empty() || include.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => family.starts_with(prefix),
+        None => family == pattern,
+    })
+}
+
+/// Families of a text exposition by name. Samples following a `# TYPE`/`# HELP` belong to that
+/// family when named after it (`foo`, `foo_bucket`, `foo_sum`, ...); others form untyped families
+/// of their own. Lines that do not parse are skipped.
+fn parse(text: &str) -> BTreeMap<String, Family> {
+    let mut families: BTreeMap<String, Family> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, ' ');
+            let (Some(keyword @ ("HELP" | "TYPE")), Some(name)) = (parts.next(), parts.next()) else { continue };
+            let text = parts.next().unwrap_or_default().to_string();
+            let family = families.entry(name.to_string()).or_default();
+            if keyword == "HELP" { family.help = Some(text) } else { family.kind = Some(text) }
+            current = Some(name.to_string());
+            continue;
+        }
+        let Some(sample) = parse_sample(line) else { continue };
+        let name = match &current {
+            Some(family) if member_of(family, &sample.name) => family.clone(),
+            _ => sample.name.clone(),
+        };
+        families.entry(name).or_default().samples.push(sample);
+    }
+    families
+}
+
+fn member_of(family: &str, sample: &str) -> bool {
+    sample.strip_prefix(family).is_some_and(|suffix| matches!(suffix, "" | "_bucket" | "_sum" | "_count" | "_total" | "_created"))
+}
+
+/// `name{label="value",...} value [timestamp]`; label values keep their escapes.
+fn parse_sample(line: &str) -> Option<Sample> {
+    let name_end = line.find(|c: char| c == '{' || c.is_whitespace())?;
+    let (name, mut rest) = line.split_at(name_end);
+    let mut labels = Vec::new();
+    if let Some(inner) = rest.strip_prefix('{') {
+        let mut s = inner.trim_start();
+        rest = loop {
+            if let Some(after) = s.strip_prefix('}') { break after; }
+            let (label, value) = s.split_once('=')?;
+            let value = value.trim_start().strip_prefix('"')?;
+            let mut escaped = false;
+            let end = value.char_indices().find(|&(_, c)| {
+                let closing = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                closing
+            })?.0;
+            labels.push((label.trim().to_string(), value[..end].to_string()));
+            s = value[end + 1..].trim_start();
+            s = s.strip_prefix(',').unwrap_or(s).trim_start();
+        };
+    }
+    let value = rest.trim();
+    (!name.is_empty() && !value.is_empty()).then(|| Sample { name: name.to_string(), labels, value: value.to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API: &str = r#"# HELP http_requests_total Requests served
+# TYPE http_requests_total counter
+http_requests_total{method="GET",path="/a,b}"} 1027 1700000000000
+http_requests_total{method="POST",service_id="orders"} 3
+# HELP http_request_duration_seconds Latency
+# TYPE http_request_duration_seconds histogram
+http_request_duration_seconds_bucket{le="0.1"} 90
+http_request_duration_seconds_bucket{le="+Inf"} 100
+http_request_duration_seconds_sum 7.5
+http_request_duration_seconds_count 100
+# TYPE go_goroutines gauge
+go_goroutines 42
+orphan_metric 1
+"#;
+
+    fn endpoint(include: &[&str]) -> MetricsEndpoint {
+        MetricsEndpoint { url: "http://api:8000/metrics".into(), include: include.iter().map(|s| s.to_string()).collect() }
+    }
+
+    #[test]
+    fn selected_families_are_relabelled_and_merged() {
+        let api = endpoint(&["http_*"]);
+        let worker = endpoint(&[]);
+        let out = merge(vec![
+            ("api", &api, Ok(API.to_string())),
+            ("worker", &worker, Ok("# TYPE http_requests_total counter\nhttp_requests_total 5\n".to_string())),
+            ("billing", &worker, Err(anyhow::anyhow!("connection refused"))),
+        ], 1000);
+
+        assert_eq!(out.matches("# TYPE http_requests_total counter").count(), 1, "one family across services\n{out}");
+        assert!(out.contains("http_requests_total{service_id=\"api\",method=\"GET\",path=\"/a,b}\"} 1027 1700000000000\n"), "{out}");
+        assert!(out.contains("http_requests_total{service_id=\"api\",method=\"POST\",exported_service_id=\"orders\"} 3\n"));
+        assert!(out.contains("http_requests_total{service_id=\"worker\"} 5\n"));
+        assert!(out.contains("http_request_duration_seconds_bucket{service_id=\"api\",le=\"+Inf\"} 100\n"));
+        assert!(out.contains("http_request_duration_seconds_count{service_id=\"api\"} 100\n"));
+        assert!(!out.contains("go_goroutines") && !out.contains("orphan_metric"), "not selected");
+        assert!(out.contains("fks_federation_up{service_id=\"api\"} 1\nfks_federation_up{service_id=\"worker\"} 1\nfks_federation_up{service_id=\"billing\"} 0\n"));
+
+        let all = endpoint(&[]);
+        let capped = merge(vec![("api", &all, Ok(API.to_string()))], 2);
+        assert_eq!(capped.lines().filter(|l| !l.starts_with('#') && !l.starts_with("fks_federation_up")).count(), 2);
+        assert!(merge(vec![("api", &all, Ok(API.to_string()))], 1000).contains("orphan_metric{service_id=\"api\"} 1\n"));
+    }
+}
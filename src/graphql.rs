@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+
+use async_graphql::http::{WebSocket as GraphqlWebSocket, WebSocketProtocols, WsMessage};
+use async_graphql::{Context, EmptyMutation, Enum, Json, Object, Result, Schema, Subscription};
+use axum::extract::ws::{CloseFrame, Message, WebSocket};
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, Stream, StreamExt};
+use tokio::sync::broadcast;
+
+use crate::events::{self, EventQuery};
+use crate::models::{HealthCheck, MonitorEvent, ServiceHealth, ServiceMetrics, ServiceStatus, SystemMetrics};
+use crate::monitor::MonitorHandle;
+use crate::redact::Redact;
+
+/// Deepest selection a query may nest (services -> events -> ... is at most a handful)
+const MAX_DEPTH: usize = 10;
+
+pub type DashboardSchema = Schema<Query, EmptyMutation, Subscription>;
+
+/// Schema over `monitor`. Each request carries the [`Caller`] it is answered for.
+pub fn schema(monitor: MonitorHandle) -> DashboardSchema {
+    Schema::build(Query, EmptyMutation, Subscription)
+        .data(monitor)
+        .limit_depth(MAX_DEPTH)
+        .finish()
+}
+
+/// Who is asking: callers without the API key or a JWT get the same redacted view as from the REST API.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Caller {
+    pub authorized: bool,
+}
+
+fn visible<T: Redact>(ctx: &Context<'_>, value: T) -> T {
+    if ctx.data_opt::<Caller>().is_some_and(|c| c.authorized) { value } else { value.redact() }
+}
+
+fn monitor<'a>(ctx: &Context<'a>) -> &'a MonitorHandle {
+    ctx.data_unchecked::<MonitorHandle>()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::models::HealthStatus")]
+pub enum Status {
+    Healthy,
+    Degraded,
+    Unhealthy,
+    Unknown,
+    Paused,
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Services ordered by id; every filter given must match.
+    async fn services(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<Vec<Status>>,
+        tag: Option<String>,
+        group: Option<String>,
+        critical: Option<bool>,
+    ) -> Vec<Service> {
+        let mut services: Vec<ServiceStatus> = monitor(ctx).get_all_services().await.into_iter()
+            .filter(|s| status.as_ref().is_none_or(|wanted| wanted.contains(&s.status.clone().into())))
+            .filter(|s| tag.as_ref().is_none_or(|t| s.tags.contains(t)))
+            .filter(|s| group.is_none() || s.group == group)
+            .filter(|s| critical.is_none_or(|c| s.critical == c))
+            .collect();
+        services.sort_by(|a, b| a.id.cmp(&b.id));
+        services.into_iter().map(|s| Service(visible(ctx, s))).collect()
+    }
+
+    async fn service(&self, ctx: &Context<'_>, id: String) -> Option<Service> {
+        monitor(ctx).get_all_services().await.into_iter()
+            .find(|s| s.id == id)
+            .map(|s| Service(visible(ctx, s)))
+    }
+
+    async fn metrics(&self, ctx: &Context<'_>) -> SystemMetrics {
+        monitor(ctx).get_system_metrics().await
+    }
+
+    /// Recorded events, newest first. `service` is a service id, or `system` for events of no service.
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        service: Option<String>,
+        types: Option<Vec<String>>,
+        since: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Event>> {
+        recorded_events(ctx, service, types, since, limit)
+    }
+}
+
+fn recorded_events(ctx: &Context<'_>, service: Option<String>, types: Option<Vec<String>>, since: Option<DateTime<Utc>>, limit: Option<usize>) -> Result<Vec<Event>> {
+    let q = EventQuery { service, event_type: types.map(|t| t.join(",")), since, limit, cursor: None };
+    let page = monitor(ctx).events(&q)?;
+    Ok(page.items.into_iter().map(|e| Event(visible(ctx, e))).collect())
+}
+
+pub struct Service(ServiceStatus);
+
+#[Object]
+impl Service {
+    async fn id(&self) -> &str { &self.0.id }
+    async fn name(&self) -> &str { &self.0.name }
+    async fn status(&self) -> Status { self.0.status.clone().into() }
+    async fn last_check(&self) -> DateTime<Utc> { self.0.last_check }
+    async fn uptime_seconds(&self) -> Option<u64> { self.0.uptime_seconds }
+    async fn response_time_ms(&self) -> Option<u64> { self.0.response_time_ms }
+    async fn error_message(&self) -> Option<&str> { self.0.error_message.as_deref() }
+    /// As in the REST API, e.g. `Api` or `Database`
+    async fn service_type(&self) -> String { format!("{:?}", self.0.service_type) }
+    async fn critical(&self) -> bool { self.0.critical }
+    async fn host(&self) -> Option<&str> { self.0.host.as_deref() }
+    async fn silenced(&self) -> bool { self.0.silenced }
+    async fn tags(&self) -> &[String] { &self.0.tags }
+    async fn group(&self) -> Option<&str> { self.0.group.as_deref() }
+    async fn metadata(&self) -> Json<&BTreeMap<String, String>> { Json(&self.0.metadata) }
+
+    /// Latest health checks and resource usage; probe targets are redacted as in the REST API
+    async fn health(&self, ctx: &Context<'_>) -> Option<Health> {
+        monitor(ctx).get_service_health(&self.0.id).await.map(|h| Health(visible(ctx, h)))
+    }
+
+    /// Recorded events of this service, newest first
+    async fn events(&self, ctx: &Context<'_>, types: Option<Vec<String>>, since: Option<DateTime<Utc>>, limit: Option<usize>) -> Result<Vec<Event>> {
+        recorded_events(ctx, Some(self.0.id.clone()), types, since, limit)
+    }
+}
+
+pub struct Health(ServiceHealth);
+
+#[Object]
+impl Health {
+    async fn status(&self) -> Status { self.0.status.clone().into() }
+    async fn checks(&self) -> Vec<Check<'_>> { self.0.checks.iter().map(Check).collect() }
+    async fn metrics(&self) -> &ServiceMetrics { &self.0.metrics }
+    async fn last_updated(&self) -> DateTime<Utc> { self.0.last_updated }
+}
+
+pub struct Check<'a>(&'a HealthCheck);
+
+#[Object]
+impl Check<'_> {
+    async fn name(&self) -> &str { &self.0.name }
+    async fn status(&self) -> Status { self.0.status.clone().into() }
+    async fn response_time_ms(&self) -> u64 { self.0.response_time_ms }
+    async fn message(&self) -> Option<&str> { self.0.message.as_deref() }
+    async fn timestamp(&self) -> DateTime<Utc> { self.0.timestamp }
+}
+
+pub struct Event(MonitorEvent);
+
+#[Object]
+impl Event {
+    /// e.g. `ServiceDown`
+    async fn event_type(&self) -> String { format!("{:?}", self.0.event_type) }
+    async fn service_id(&self) -> Option<&str> { self.0.service_id.as_deref() }
+    async fn message(&self) -> &str { &self.0.message }
+    async fn timestamp(&self) -> DateTime<Utc> { self.0.timestamp }
+    async fn data(&self) -> Option<Json<&serde_json::Value>> { self.0.data.as_ref().map(Json) }
+    async fn metadata(&self) -> Json<&BTreeMap<String, String>> { Json(&self.0.metadata) }
+}
+
+pub struct Subscription;
+
+#[Subscription]
+impl Subscription {
+    /// Monitor events as they happen; every filter given must match.
+    async fn events(&self, ctx: &Context<'_>, service: Option<String>, types: Option<Vec<String>>) -> Result<impl Stream<Item = Event> + use<>> {
+        let types = types.map(|t| events::parse_types(&t.join(","))).transpose()?;
+        let redact = !ctx.data_opt::<Caller>().is_some_and(|c| c.authorized);
+        let live = futures::stream::unfold(monitor(ctx).subscribe_events(), |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => return Some((event, rx)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(live
+            .filter(move |e| futures::future::ready(
+                service.as_ref().is_none_or(|s| e.service_id.as_ref() == Some(s))
+                    && types.as_ref().is_none_or(|t| t.contains(&e.event_type)),
+            ))
+            .map(move |e| Event(if redact { e.redact() } else { e })))
+    }
+}
+
+/// Serve subscriptions over `socket` with the graphql-ws `protocol` the client asked for.
+pub async fn serve_websocket(socket: WebSocket, schema: DashboardSchema, protocol: WebSocketProtocols, caller: Caller) {
+    let (mut sink, stream) = socket.split();
+    let incoming = stream
+        .take_while(|msg| futures::future::ready(matches!(msg, Ok(m) if !matches!(m, Message::Close(_)))))
+        .filter_map(|msg| futures::future::ready(match msg {
+            Ok(Message::Text(text)) => Some(text.as_bytes().to_vec()),
+            Ok(Message::Binary(bytes)) => Some(bytes.to_vec()),
+            _ => None,
+        }));
+    let mut data = async_graphql::Data::default();
+    data.insert(caller);
+    let mut outgoing = GraphqlWebSocket::new(schema, incoming, protocol).connection_data(data);
+    while let Some(msg) = outgoing.next().await {
+        let msg = match msg {
+            WsMessage::Text(text) => Message::Text(text.into()),
+            WsMessage::Close(code, reason) => Message::Close(Some(CloseFrame { code, reason: reason.into() })),
+        };
+        if sink.send(msg).await.is_err() { break; }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EventType;
+
+    #[tokio::test]
+    async fn queries_nest_events_under_services_and_redact_for_anonymous_callers() {
+        let monitor = crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().handle();
+        let schema = schema(monitor.clone());
+        let id = monitor.get_all_services().await.into_iter().map(|s| s.id).min().expect("default config has services");
+        monitor.inject_event(MonitorEvent {
+            event_type: EventType::ServiceDown,
+            service_id: Some(id.clone()),
+            message: "connection refused by http://user:secret@db:5432".into(),
+            timestamp: Utc::now(),
+            data: None,
+            metadata: Default::default(),
+        });
+        tokio::task::yield_now().await;
+
+        let query = format!(r#"{{ service(id: "{id}") {{ id status events(types: ["ServiceDown"]) {{ eventType message }} }} metrics {{ totalServices }} }}"#);
+        let run = |authorized: bool| {
+            let request = async_graphql::Request::new(query.clone()).data(Caller { authorized });
+            schema.execute(request)
+        };
+        let response = run(true).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["service"]["id"], id.as_str());
+        assert_eq!(data["service"]["events"][0]["eventType"], "ServiceDown");
+        assert!(data["service"]["events"][0]["message"].as_str().unwrap().contains("secret"));
+        assert!(data["metrics"]["totalServices"].as_u64().unwrap() > 0);
+
+        let data = run(false).await.data.into_json().unwrap();
+        assert!(!data["service"]["events"][0]["message"].as_str().unwrap().contains("secret"));
+
+        let response = schema.execute(r#"{ events(types: ["Reboot"]) { message } }"#).await;
+        assert!(response.errors[0].message.contains("unknown event type"));
+    }
+
+    #[tokio::test]
+    async fn subscriptions_stream_matching_events() {
+        let monitor = crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().handle();
+        let schema = schema(monitor.clone());
+        let mut stream = schema.execute_stream(r#"subscription { events(service: "fks_api") { eventType serviceId } }"#);
+        let next = tokio::spawn(async move { stream.next().await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let ev = |service: &str| MonitorEvent { event_type: EventType::ServiceUp, service_id: Some(service.into()), message: String::new(), timestamp: Utc::now(), data: None, metadata: Default::default() };
+        monitor.inject_event(ev("fks_data"));
+        monitor.inject_event(ev("fks_api"));
+        let response = tokio::time::timeout(std::time::Duration::from_secs(1), next).await.unwrap().unwrap().unwrap();
+        assert_eq!(response.data.into_json().unwrap()["events"]["serviceId"], "fks_api");
+    }
+}
@@ -0,0 +1,270 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::config::HealingRule;
+use crate::models::{HealthStatus, ServiceConfig};
+use crate::rules::{Comparison, RuleExpr};
+
+/// What a healing rule does once its conditions hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealingAction {
+    Restart,
+    /// Restart with `mode=recreate`
+    Recreate,
+    Rollback,
+    /// Raise a `SystemAlert` and leave the service alone
+    Alert,
+}
+
+impl HealingAction {
+    const ALL: [HealingAction; 4] = [HealingAction::Restart, HealingAction::Recreate, HealingAction::Rollback, HealingAction::Alert];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealingAction::Restart => "restart",
+            HealingAction::Recreate => "recreate",
+            HealingAction::Rollback => "rollback",
+            HealingAction::Alert => "alert",
+        }
+    }
+}
+
+/// One `and`ed clause of a healing rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// `status == Unhealthy [for 2m]`, or `!=`
+    Status { negated: bool, status: HealthStatus, for_seconds: u64 },
+    /// `restarts_last_hour < 3`: restarts of the service however they were asked for
+    RestartsLastHour { op: Comparison, count: f64 },
+    /// Any alert rule metric, e.g. `memory_usage_mb > 900 for 5m`
+    Metric(RuleExpr),
+}
+
+impl Condition {
+    /// How long the clause must hold before it counts.
+    pub fn for_seconds(&self) -> u64 {
+        match self {
+            Condition::Status { for_seconds, .. } => *for_seconds,
+            Condition::RestartsLastHour { .. } => 0,
+            Condition::Metric(expr) => expr.for_seconds,
+        }
+    }
+
+    fn parse(tokens: &[&str]) -> Result<Self, String> {
+        match tokens {
+            ["status", op, status, rest @ ..] => {
+                let negated = match *op {
+                    "==" => false,
+                    "!=" => true,
+                    _ => return Err(format!("unknown status comparison `{op}` (expected == or !=)")),
+                };
+                let status = parse_status(status)?;
+                let for_seconds = match rest {
+                    [] => 0,
+                    ["for", duration] => crate::rules::parse_duration_secs(duration).ok_or_else(|| format!("invalid duration `{duration}`"))?,
+                    _ => return Err(format!("unexpected trailing `{}` (expected `for <duration>`)", rest.join(" "))),
+                };
+                Ok(Condition::Status { negated, status, for_seconds })
+            }
+            ["restarts_last_hour", op, count] => {
+                let op = Comparison::parse(op).ok_or_else(|| format!("unknown comparison `{op}` (expected >, >=, < or <=)"))?;
+                let count = count.parse::<u32>().map_err(|_| format!("invalid restart count `{count}`"))?;
+                Ok(Condition::RestartsLastHour { op, count: count.into() })
+            }
+            ["restarts_last_hour", ..] => Err("expected `restarts_last_hour <op> <count>`".into()),
+            _ => tokens.join(" ").parse().map(Condition::Metric),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Status { negated, status, for_seconds } => {
+                write!(f, "status {} {status:?}", if *negated { "!=" } else { "==" })?;
+                if *for_seconds > 0 { write!(f, " for {for_seconds}s")?; }
+                Ok(())
+            }
+            Condition::RestartsLastHour { op, count } => write!(f, "restarts_last_hour {} {count}", op.as_str()),
+            Condition::Metric(expr) => write!(f, "{expr}"),
+        }
+    }
+}
+
+fn parse_status(token: &str) -> Result<HealthStatus, String> {
+    [HealthStatus::Healthy, HealthStatus::Degraded, HealthStatus::Unhealthy, HealthStatus::Unknown]
+        .into_iter()
+        .find(|s| format!("{s:?}").eq_ignore_ascii_case(token))
+        .ok_or_else(|| format!("unknown status `{token}` (expected Healthy, Degraded, Unhealthy or Unknown)"))
+}
+
+/// Parsed healing rule: `when <condition> [and <condition> ...] then <action>`, e.g.
+/// `when status == Unhealthy for 2m and restarts_last_hour < 3 then restart`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HealingExpr {
+    pub conditions: Vec<Condition>,
+    pub action: HealingAction,
+}
+
+impl std::str::FromStr for HealingExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let usage = || format!("expected `when <condition> [and <condition> ...] then <action>`, got `{s}`");
+        let ["when", rest @ ..] = tokens.as_slice() else { return Err(usage()) };
+        let then = rest.iter().position(|t| *t == "then").ok_or_else(usage)?;
+        let action = match &rest[then + 1..] {
+            [action] => HealingAction::ALL.into_iter().find(|a| a.as_str() == *action).ok_or_else(|| {
+                let known: Vec<&str> = HealingAction::ALL.iter().map(|a| a.as_str()).collect();
+                format!("unknown action `{action}` (expected one of {})", known.join(", "))
+            })?,
+            _ => return Err(usage()),
+        };
+        let conditions = rest[..then]
+            .split(|t| *t == "and")
+            .map(|clause| if clause.is_empty() { Err(usage()) } else { Condition::parse(clause) })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { conditions, action })
+    }
+}
+
+impl TryFrom<String> for HealingExpr {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<HealingExpr> for String {
+    fn from(expr: HealingExpr) -> Self {
+        expr.to_string()
+    }
+}
+
+impl fmt::Display for HealingExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conditions: Vec<String> = self.conditions.iter().map(Condition::to_string).collect();
+        write!(f, "when {} then {}", conditions.join(" and "), self.action.as_str())
+    }
+}
+
+/// A healing rule whose conditions all held for a service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Firing {
+    pub rule: String,
+    pub service_id: String,
+    pub action: HealingAction,
+}
+
+/// Tracks how long each clause of each (rule, service) has held, and when each last fired so a rule
+/// acts at most once per `cooldown_seconds`.
+#[derive(Debug, Default)]
+pub struct HealingEngine {
+    holding_since: HashMap<(String, String, usize), DateTime<Utc>>,
+    last_fired: HashMap<(String, String), DateTime<Utc>>,
+}
+
+impl HealingEngine {
+    /// Rules that fire at `now`; `holds` says whether a clause holds for a service at this moment.
+    pub fn evaluate(
+        &mut self,
+        rules: &[HealingRule],
+        services: &[ServiceConfig],
+        now: DateTime<Utc>,
+        holds: impl Fn(&Condition, &str) -> bool,
+    ) -> Vec<Firing> {
+        let mut firings = Vec::new();
+        for rule in rules {
+            for service in services.iter().filter(|s| rule.services.is_empty() || rule.services.contains(&s.id)) {
+                let mut all_held = true;
+                for (i, condition) in rule.rule.conditions.iter().enumerate() {
+                    let key = (rule.name.clone(), service.id.clone(), i);
+                    if holds(condition, &service.id) {
+                        let since = *self.holding_since.entry(key).or_insert(now);
+                        all_held &= now.signed_duration_since(since).num_seconds() >= condition.for_seconds() as i64;
+                    } else {
+                        self.holding_since.remove(&key);
+                        all_held = false;
+                    }
+                }
+                if !all_held { continue; }
+                let key = (rule.name.clone(), service.id.clone());
+                if self.last_fired.get(&key).is_some_and(|at| now.signed_duration_since(*at).num_seconds() < rule.cooldown_seconds as i64) {
+                    continue;
+                }
+                self.last_fired.insert(key, now);
+                firings.push(Firing { rule: rule.name.clone(), service_id: service.id.clone(), action: rule.rule.action });
+            }
+        }
+        firings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleMetric;
+
+    #[test]
+    fn parses_healing_rules() {
+        let e: HealingExpr = "when status == Unhealthy for 2m and restarts_last_hour < 3 then restart".parse().unwrap();
+        assert_eq!(e.conditions, vec![
+            Condition::Status { negated: false, status: HealthStatus::Unhealthy, for_seconds: 120 },
+            Condition::RestartsLastHour { op: Comparison::Lt, count: 3.0 },
+        ]);
+        assert_eq!(e.action, HealingAction::Restart);
+        assert_eq!(e.to_string().parse::<HealingExpr>().unwrap(), e);
+
+        let e: HealingExpr = "when memory_usage_mb > 900 for 5m and status != healthy then recreate".parse().unwrap();
+        assert!(matches!(&e.conditions[0], Condition::Metric(m) if m.metric == RuleMetric::MemoryUsageMb && m.for_seconds == 300));
+        assert_eq!(e.action, HealingAction::Recreate);
+
+        for bad in [
+            "status == Unhealthy then restart",
+            "when status == Unhealthy",
+            "when status == Unhealthy then reboot",
+            "when status == Sick then restart",
+            "when status == Unhealthy and then restart",
+            "when restarts_last_hour < lots then restart",
+            "when disk > 5 then alert",
+        ] {
+            assert!(bad.parse::<HealingExpr>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn fires_once_every_clause_has_held_long_enough_then_cools_down() {
+        let rules = vec![HealingRule {
+            name: "unstick".into(),
+            rule: "when status == Unhealthy for 2m and restarts_last_hour < 3 then restart".parse().unwrap(),
+            services: vec![],
+            enforce: true,
+            cooldown_seconds: 300,
+        }];
+        let services = crate::config::Config::default().services;
+        let mut engine = HealingEngine::default();
+        let t0 = Utc::now();
+        let at = |secs| t0 + chrono::Duration::seconds(secs);
+        let api_down = |c: &Condition, id: &str| matches!(c, Condition::RestartsLastHour { .. }) || id == "fks_api";
+
+        assert!(engine.evaluate(&rules, &services, at(0), api_down).is_empty());
+        assert!(engine.evaluate(&rules, &services, at(60), api_down).is_empty());
+        assert_eq!(engine.evaluate(&rules, &services, at(120), api_down), vec![Firing { rule: "unstick".into(), service_id: "fks_api".into(), action: HealingAction::Restart }]);
+        assert!(engine.evaluate(&rules, &services, at(180), api_down).is_empty(), "cooling down");
+        assert_eq!(engine.evaluate(&rules, &services, at(420), api_down).len(), 1);
+
+        // Out of restarts: the rule holds back however long the service stays down
+        let exhausted = |c: &Condition, id: &str| !matches!(c, Condition::RestartsLastHour { .. }) && id == "fks_api";
+        assert!(engine.evaluate(&rules, &services, at(800), exhausted).is_empty());
+        // A recovery in between starts the `for` timer over
+        engine.evaluate(&rules, &services, at(900), |_, _| false);
+        assert!(engine.evaluate(&rules, &services, at(960), api_down).is_empty());
+        assert_eq!(engine.evaluate(&rules, &services, at(1080), api_down).len(), 1);
+    }
+}
@@ -3,6 +3,8 @@ use reqwest::Client;
 use std::time::{Duration, Instant};
 use tracing::{debug, Instrument};
 
+use crate::outbound::{self, Destination};
+
 pub struct HealthChecker {
     client: Client,
     retry_attempts: u32,
@@ -26,7 +28,7 @@ impl HealthChecker {
             
             let start_time = Instant::now();
             
-            let send_future = self.client.get(endpoint).send();
+            let send_future = outbound::send(Destination::HealthCheck, self.client.get(endpoint));
             match send_future.instrument(tracing::info_span!("health_http", %endpoint)).await {
                 Ok(response) => {
                     let elapsed = start_time.elapsed();
@@ -42,7 +44,7 @@ impl HealthChecker {
                 }
                 Err(err) => {
                     debug!("❌ Health check error for {}: {}", endpoint, err);
-                    last_error = Some(anyhow::anyhow!(err));
+                    last_error = Some(err);
                 }
             }
 
@@ -60,7 +62,7 @@ impl HealthChecker {
         pub async fn check_detailed_health(&self, endpoint: &str) -> Result<HealthCheckResult> {
     let start_time = Instant::now();
     let span = tracing::info_span!("health_detailed", %endpoint);
-    match self.client.get(endpoint).send().instrument(span).await {
+    match outbound::send(Destination::HealthCheck, self.client.get(endpoint)).instrument(span).await {
             Ok(response) => {
                 let elapsed = start_time.elapsed();
                 let status_code = response.status();
@@ -77,9 +79,7 @@ impl HealthChecker {
                     health_data,
                 })
             }
-            Err(err) => {
-                Err(anyhow::anyhow!(err))
-            }
+            Err(err) => Err(err),
         }
     }
 }
@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::config::HealthScoreConfig;
+use crate::models::{HealthStatus, ServiceConfig, ServiceStatus};
+
+/// One service's part in the health score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceScore {
+    /// 0–1 from the service's own status
+    pub own: f64,
+    /// `own` lowered by dependencies that are not fully healthy, 0–1
+    pub effective: f64,
+    pub weight: f64,
+    /// Direct dependencies that lowered `effective`
+    pub impacted_by: Vec<String>,
+}
+
+/// Effective health of every scored service. Paused services are left out, both as services and
+/// as dependencies.
+#[derive(Debug, Default)]
+pub struct Scores {
+    services: HashMap<String, ServiceScore>,
+}
+
+impl Scores {
+    pub fn compute(cfg: &HealthScoreConfig, services: &[ServiceConfig], statuses: &[ServiceStatus]) -> Self {
+        let deps: HashMap<&str, &[String]> = services.iter().map(|s| (s.id.as_str(), s.depends_on.as_slice())).collect();
+        let statuses: HashMap<&str, &ServiceStatus> = statuses.iter().map(|s| (s.id.as_str(), s)).collect();
+        let mut scores = Self::default();
+        for id in statuses.keys() {
+            scores.effective(cfg, id, &deps, &statuses, &mut HashSet::new());
+        }
+        scores
+    }
+
+    pub fn get(&self, service_id: &str) -> Option<&ServiceScore> {
+        self.services.get(service_id)
+    }
+
+    /// Weighted mean effective health of `services` as 0–100 (one decimal); 100 with nothing to score.
+    pub fn overall<'a>(&self, services: impl IntoIterator<Item = &'a ServiceStatus>) -> f64 {
+        let (sum, weights) = services.into_iter()
+            .filter_map(|s| self.get(&s.id))
+            .fold((0.0, 0.0), |(sum, weights), score| (sum + score.weight * score.effective, weights + score.weight));
+        if weights > 0.0 { percent(sum / weights) } else { 100.0 }
+    }
+
+    /// Each dependency below full health takes `dependency_impact` times its shortfall off what is
+    /// left, so a down dependency with the default 0.5 halves a healthy service. Dependencies count
+    /// with their own effective health, so the impact carries down a chain; a cycle is cut where it
+    /// closes.
+    fn effective(&mut self, cfg: &HealthScoreConfig, id: &str, deps: &HashMap<&str, &[String]>, statuses: &HashMap<&str, &ServiceStatus>, visiting: &mut HashSet<String>) -> Option<f64> {
+        if let Some(score) = self.services.get(id) {
+            return Some(score.effective);
+        }
+        let status = statuses.get(id)?;
+        let own = match status.status {
+            HealthStatus::Healthy => 1.0,
+            HealthStatus::Degraded => cfg.degraded,
+            HealthStatus::Unknown => cfg.unknown,
+            HealthStatus::Unhealthy => 0.0,
+            HealthStatus::Paused => return None,
+        };
+        if !visiting.insert(id.to_string()) {
+            return Some(own);
+        }
+        let mut effective = own;
+        let mut impacted_by = Vec::new();
+        for dep in deps.get(id).copied().unwrap_or_default() {
+            match self.effective(cfg, dep, deps, statuses, visiting) {
+                Some(health) if health < 1.0 => {
+                    effective *= 1.0 - cfg.dependency_impact * (1.0 - health);
+                    impacted_by.push(dep.clone());
+                }
+                _ => {}
+            }
+        }
+        visiting.remove(id);
+        let weight = cfg.weights.get(id).copied().unwrap_or(if status.critical { cfg.critical_weight } else { cfg.weight });
+        self.services.insert(id.to_string(), ServiceScore { own, effective, weight, impacted_by });
+        Some(effective)
+    }
+}
+
+/// 0–1 as a percentage with one decimal.
+pub fn percent(health: f64) -> f64 {
+    (health * 1000.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::ServiceType;
+    use chrono::Utc;
+
+    fn status(id: &str, status: HealthStatus, critical: bool) -> ServiceStatus {
+        ServiceStatus {
+            id: id.into(),
+            name: id.into(),
+            status,
+            last_check: Utc::now(),
+            uptime_seconds: None,
+            response_time_ms: None,
+            error_message: None,
+            service_type: ServiceType::Api,
+            critical,
+            host: None,
+            silenced: false,
+            tags: vec![],
+            group: None,
+            metadata: Default::default(),
+            restart_backoff: None,
+        }
+    }
+
+    #[test]
+    fn a_down_dependency_drags_its_dependents_down() {
+        let mut config = Config::default();
+        config.services.truncate(3);
+        let ids: Vec<String> = config.services.iter().map(|s| s.id.clone()).collect();
+        let (data, engine, web) = (&ids[0], &ids[1], &ids[2]);
+        for s in &mut config.services { s.depends_on.clear(); }
+        config.services[1].depends_on = vec![data.clone()];
+        config.services[2].depends_on = vec![engine.clone(), data.clone()];
+        let cfg = HealthScoreConfig::default();
+
+        let all_up = [status(data, HealthStatus::Healthy, true), status(engine, HealthStatus::Healthy, false), status(web, HealthStatus::Healthy, false)];
+        let scores = Scores::compute(&cfg, &config.services, &all_up);
+        assert_eq!(scores.overall(&all_up), 100.0);
+
+        let data_down = [status(data, HealthStatus::Unhealthy, true), status(engine, HealthStatus::Healthy, false), status(web, HealthStatus::Healthy, false)];
+        let scores = Scores::compute(&cfg, &config.services, &data_down);
+        let engine_score = scores.get(engine).unwrap();
+        assert_eq!((engine_score.own, engine_score.effective), (1.0, 0.5));
+        assert_eq!(&engine_score.impacted_by, std::slice::from_ref(data));
+        assert_eq!(scores.get(web).unwrap().effective, 0.75 * 0.5, "through engine and directly");
+        // data weighs 3 (critical): (3*0 + 0.5 + 0.375) / 5
+        assert_eq!(scores.overall(&data_down), 17.5);
+
+        let paused = [status(data, HealthStatus::Paused, true), status(engine, HealthStatus::Degraded, false)];
+        let scores = Scores::compute(&cfg, &config.services, &paused);
+        assert!(scores.get(data).is_none());
+        assert_eq!(scores.overall(&paused), 50.0, "paused services count for nothing");
+
+        config.services[0].depends_on = vec![web.clone()];
+        let scores = Scores::compute(&cfg, &config.services, &data_down);
+        assert!(scores.get(data).is_some(), "cycles end");
+    }
+}
@@ -0,0 +1,241 @@
+use crate::alerts::{self, AlertPayload};
+use crate::compose::ComposeRequest;
+use crate::compose_queue::ComposeQueue;
+use crate::config::{HookAction, HookConfig};
+use crate::metrics;
+use crate::models::{EventType, MonitorEvent};
+use crate::monitor::MonitorHandle;
+use crate::outbound::{self, Destination};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, error, info, warn};
+
+/// `data.kind` of the events hooks record; these never trigger hooks, so a failing hook can't set itself off
+const HOOK_EVENT_KIND: &str = "hook";
+/// Bytes of a failed command's output kept in the error
+const OUTPUT_LIMIT: usize = 1000;
+
+/// Run `[[hooks]]` on matching monitor events for as long as the monitor runs.
+pub fn spawn(monitor: MonitorHandle, compose_queue: ComposeQueue) {
+    let events = monitor.subscribe_events();
+    tokio::spawn(run(monitor, compose_queue, events));
+}
+
+async fn run(monitor: MonitorHandle, compose_queue: ComposeQueue, mut events: broadcast::Receiver<MonitorEvent>) {
+    let client = reqwest::Client::new();
+    let mut cooldowns = Cooldowns::default();
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!(missed, "Hooks fell behind the event stream; some events were skipped");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        let config = monitor.config();
+        let due: Vec<&HookConfig> = config.hooks.iter().filter(|hook| triggers(hook, &event)).collect();
+        if due.is_empty() { continue; }
+        if monitor.automation_frozen() {
+            info!(event = ?event.event_type, service = ?event.service_id, "Hooks skipped: automation is frozen");
+            continue;
+        }
+        let payload = AlertPayload::for_event(&event, &config);
+        for hook in due {
+            if !cooldowns.admit(hook, event.service_id.as_deref(), event.timestamp) {
+                debug!(hook = %hook.name, service = ?event.service_id, "Hook cooling down");
+                continue;
+            }
+            tokio::spawn(run_hook(hook.clone(), payload.clone(), monitor.clone(), compose_queue.clone(), client.clone()));
+        }
+    }
+}
+
+/// Whether `event` triggers `hook`.
+fn triggers(hook: &HookConfig, event: &MonitorEvent) -> bool {
+    hook.events.contains(&event.event_type)
+        && (hook.services.is_empty() || event.service_id.as_ref().is_some_and(|id| hook.services.contains(id)))
+        && event.data.as_ref().and_then(|d| d.get("kind")).and_then(|k| k.as_str()) != Some(HOOK_EVENT_KIND)
+}
+
+/// Last run of each hook per service.
+#[derive(Default)]
+struct Cooldowns {
+    last_run: HashMap<(String, String), DateTime<Utc>>,
+}
+
+impl Cooldowns {
+    /// Whether `hook` may run for `service_id` at `now`; if so, the run is noted.
+    fn admit(&mut self, hook: &HookConfig, service_id: Option<&str>, now: DateTime<Utc>) -> bool {
+        let key = (hook.name.clone(), service_id.unwrap_or("system").to_string());
+        if self.last_run.get(&key).is_some_and(|last| now.signed_duration_since(*last).num_seconds() < hook.cooldown_seconds as i64) {
+            return false;
+        }
+        self.last_run.insert(key, now);
+        true
+    }
+}
+
+async fn run_hook(hook: HookConfig, payload: AlertPayload, monitor: MonitorHandle, compose_queue: ComposeQueue, client: reqwest::Client) {
+    info!(hook = %hook.name, event = ?payload.event_type, service = ?payload.service_id, "🪝 Running hook");
+    let outcome = tokio::time::timeout(Duration::from_secs(hook.timeout_seconds), execute(&hook.run, &payload, &monitor, &compose_queue, &client))
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("timed out after {}s", hook.timeout_seconds)));
+    metrics::increment_hook_run(&hook.name, outcome.is_ok());
+    match outcome {
+        Ok(summary) => info!(hook = %hook.name, %summary, "Hook finished"),
+        Err(e) => {
+            error!(hook = %hook.name, error = format!("{e:#}"), "Hook failed");
+            monitor.inject_event(MonitorEvent {
+                event_type: EventType::SystemAlert,
+                service_id: payload.service_id.clone(),
+                message: format!("Hook {} failed: {e:#}", hook.name),
+                timestamp: monitor.now(),
+                data: Some(serde_json::json!({"kind": HOOK_EVENT_KIND, "hook": hook.name, "trigger": payload.event_type, "error": format!("{e:#}")})),
+                metadata: Default::default(),
+            });
+        }
+    }
+}
+
+/// Carry out `action` for the event in `payload`; returns a short summary of what happened.
+async fn execute(action: &HookAction, payload: &AlertPayload, monitor: &MonitorHandle, compose_queue: &ComposeQueue, client: &reqwest::Client) -> Result<String> {
+    match action {
+        HookAction::Command { command, env } => run_command(command, env, payload).await,
+        HookAction::Http { url, method, headers, body } => {
+            let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes()).with_context(|| format!("invalid method `{method}`"))?;
+            let mut request = client.request(method.clone(), alerts::render_url_template(url, payload));
+            for (name, value) in headers {
+                request = request.header(name, alerts::render_template(value, payload));
+            }
+            request = match body {
+                Some(body) => request.json(&alerts::render_json_template(body, payload)),
+                None => request.json(payload),
+            };
+            // The URL may carry a token, so errors name only the method
+            let status = outbound::send(Destination::Hook, request).await?.status();
+            if !status.is_success() { bail!("{method} returned {status}"); }
+            Ok(format!("{method} returned {status}"))
+        }
+        HookAction::Compose(request) => {
+            let config = monitor.config();
+            let request = ComposeRequest::clone(request).with_monitor_config(&config);
+            let _permit = compose_queue.acquire(request.action, &request.project_name(), &request.services, config.compose.max_concurrent_actions).await;
+            let result = request.execute().await?;
+            if !result.success { bail!("compose {} failed: {}", result.action, truncated(result.stderr.trim())); }
+            Ok(format!("compose {} succeeded", result.action))
+        }
+    }
+}
+
+/// Run `command` without a shell, its arguments and `env` values rendered from `payload`, which is
+/// also passed whole in `FKS_EVENT`.
+async fn run_command(command: &[String], env: &BTreeMap<String, String>, payload: &AlertPayload) -> Result<String> {
+    let rendered: Vec<String> = command.iter().map(|arg| alerts::render_template(arg, payload)).collect();
+    let Some((program, args)) = rendered.split_first() else { bail!("empty command") };
+    let mut cmd = tokio::process::Command::new(program);
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .env("FKS_EVENT_TYPE", format!("{:?}", payload.event_type))
+        .env("FKS_SEVERITY", payload.severity.as_str())
+        .env("FKS_SERVICE_ID", payload.service_id.as_deref().unwrap_or_default())
+        .env("FKS_SERVICE_NAME", payload.service_name.as_deref().unwrap_or_default())
+        .env("FKS_MESSAGE", &payload.message)
+        .env("FKS_EVENT", serde_json::to_string(payload)?);
+    for (name, value) in env {
+        cmd.env(name, alerts::render_template(value, payload));
+    }
+    let output = cmd.output().await.with_context(|| format!("running {program}"))?;
+    if !output.status.success() {
+        let text = String::from_utf8_lossy(if output.stderr.is_empty() { &output.stdout } else { &output.stderr }).into_owned();
+        bail!("{program} failed ({}): {}", output.status, truncated(text.trim()));
+    }
+    Ok(format!("{program} succeeded"))
+}
+
+fn truncated(text: &str) -> &str {
+    match text.char_indices().nth(OUTPUT_LIMIT) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn hooks(toml: &str) -> Vec<HookConfig> {
+        toml::from_str::<toml::Table>(toml).unwrap()["hooks"].clone().try_into().unwrap()
+    }
+
+    fn event(event_type: EventType, service_id: Option<&str>) -> MonitorEvent {
+        MonitorEvent { event_type, service_id: service_id.map(Into::into), message: "fks_api is down".into(), timestamp: Utc::now(), data: None, metadata: Default::default() }
+    }
+
+    #[test]
+    fn hooks_match_events_and_services_and_cool_down_per_service() {
+        let hook = hooks(r#"hooks = [{ name = "fix", events = ["ServiceDown", "SystemAlert"], services = ["fks_api"], cooldown_seconds = 60, run = { type = "command", command = ["true"] } }]"#).remove(0);
+        assert!(triggers(&hook, &event(EventType::ServiceDown, Some("fks_api"))));
+        assert!(!triggers(&hook, &event(EventType::ServiceUp, Some("fks_api"))));
+        assert!(!triggers(&hook, &event(EventType::ServiceDown, Some("fks_web"))));
+        assert!(!triggers(&hook, &event(EventType::SystemAlert, None)), "system events have no service");
+        let mut failed = event(EventType::SystemAlert, Some("fks_api"));
+        failed.data = Some(serde_json::json!({"kind": HOOK_EVENT_KIND}));
+        assert!(!triggers(&hook, &failed), "a failing hook must not trigger itself");
+
+        let mut cooldowns = Cooldowns::default();
+        let now = Utc::now();
+        assert!(cooldowns.admit(&hook, Some("fks_api"), now));
+        assert!(!cooldowns.admit(&hook, Some("fks_api"), now + chrono::Duration::seconds(59)));
+        assert!(cooldowns.admit(&hook, Some("fks_web"), now + chrono::Duration::seconds(59)));
+        assert!(cooldowns.admit(&hook, Some("fks_api"), now + chrono::Duration::seconds(60)));
+    }
+
+    #[tokio::test]
+    async fn hooks_run_commands_and_http_calls_with_the_event() {
+        use axum::{routing::post, Json, Router};
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<(Option<String>, serde_json::Value)>(1);
+        let app = Router::new().route("/fix", post(move |headers: axum::http::HeaderMap, Json(body): Json<serde_json::Value>| {
+            let tx = tx.clone();
+            async move { let _ = tx.send((headers.get("x-service").and_then(|v| v.to_str().ok()).map(Into::into), body)).await; }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap(); });
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let hooks = hooks(&format!(r#"hooks = [
+            {{ name = "record", events = ["ServiceDown"], run = {{ type = "command", command = ["sh", "-c", "echo \"$1 $FKS_SEVERITY\" > {out}", "sh", "{{service_id}}"] }} }},
+            {{ name = "call", events = ["ServiceDown"], run = {{ type = "http", url = "http://{addr}/fix", headers = {{ X-Service = "{{service_name}}" }}, body = {{ restart = "{{service_id}}" }} }} }},
+            {{ name = "broken", events = ["ServiceDown"], run = {{ type = "command", command = ["sh", "-c", "echo nope >&2; exit 3"] }} }},
+        ]"#, out = out.display()));
+        let monitor = crate::monitor::ServiceMonitor::new(Config { hooks, ..Default::default() }).await.unwrap().handle();
+        let mut events = monitor.subscribe_events();
+        tokio::spawn(run(monitor.clone(), ComposeQueue::default(), monitor.subscribe_events()));
+        monitor.inject_event(event(EventType::ServiceDown, Some("fks_api")));
+
+        let (header, body) = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(header.as_deref(), Some("FKS API Service"));
+        assert_eq!(body, serde_json::json!({"restart": "fks_api"}));
+        let failed = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let event = events.recv().await.unwrap();
+                if event.event_type == EventType::SystemAlert { return event; }
+            }
+        }).await.unwrap();
+        let data = failed.data.unwrap();
+        assert_eq!((data["kind"].as_str(), data["hook"].as_str()), (Some(HOOK_EVENT_KIND), Some("broken")));
+        assert!(data["error"].as_str().unwrap().contains("nope"), "{data}");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while std::fs::read_to_string(&out).unwrap_or_default().is_empty() { tokio::time::sleep(Duration::from_millis(20)).await; }
+        }).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "fks_api critical\n");
+    }
+}
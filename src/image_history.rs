@@ -0,0 +1,89 @@
+use crate::models::{DeployedImage, ImageRecord};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The image each containerized service runs and the one it ran before, so a bad deploy can be
+/// rolled back. Filled from what the daemon reports whenever a container starts.
+#[derive(Debug, Clone, Default)]
+pub struct ImageHistory {
+    services: Arc<Mutex<HashMap<String, ImageRecord>>>,
+}
+
+impl ImageHistory {
+    /// Note that `service_id` runs `image`. A different image than last time becomes the current one
+    /// and the last one its previous; returns whether it changed.
+    pub fn observe(&self, service_id: &str, image: DeployedImage) -> bool {
+        let mut services = self.services.lock().unwrap();
+        let record = services.entry(service_id.to_string()).or_default();
+        if record.current.as_ref().is_some_and(|current| current.image_id == image.image_id) {
+            return false;
+        }
+        record.previous = record.current.replace(image);
+        true
+    }
+
+    pub fn get(&self, service_id: &str) -> Option<ImageRecord> {
+        self.services.lock().unwrap().get(service_id).cloned()
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, ImageRecord> {
+        self.services.lock().unwrap().clone()
+    }
+
+    pub fn restore(&self, records: HashMap<String, ImageRecord>) {
+        self.services.lock().unwrap().extend(records);
+    }
+}
+
+/// What `container` runs now, according to the daemon.
+pub async fn inspect(docker: &bollard::Docker, container: &str, now: DateTime<Utc>) -> Result<DeployedImage> {
+    let inspect = docker.inspect_container(container, None).await?;
+    let image_id = inspect.image.context("container has no image")?;
+    let image = inspect.config.and_then(|c| c.image).unwrap_or_else(|| image_id.clone());
+    let repo_digests = docker.inspect_image(&image_id).await?.repo_digests.unwrap_or_default();
+    Ok(DeployedImage { repo_digest: repo_digest(&image, repo_digests), image, image_id, since: now })
+}
+
+/// The `RepoDigests` entry of `image`'s own repository, or else any, since they name the same content.
+fn repo_digest(image: &str, repo_digests: Vec<String>) -> Option<String> {
+    let name = image.split_once('@').map_or(image, |(name, _)| name);
+    let repository = match name.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => repository,
+        _ => name,
+    };
+    repo_digests.iter().find(|d| d.split_once('@').is_some_and(|(name, _)| name == repository)).cloned()
+        .or_else(|| repo_digests.into_iter().next())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployed(image_id: &str) -> DeployedImage {
+        DeployedImage { image: "nuniesmith/fks_api:latest".into(), image_id: image_id.into(), repo_digest: None, since: Utc::now() }
+    }
+
+    #[test]
+    fn a_new_image_pushes_the_current_one_back() {
+        let history = ImageHistory::default();
+        assert!(history.observe("api", deployed("sha256:1")));
+        assert!(!history.observe("api", deployed("sha256:1")), "a restart is not a deploy");
+        assert_eq!(history.get("api").unwrap().previous, None);
+        assert!(history.observe("api", deployed("sha256:2")));
+        let record = history.get("api").unwrap();
+        assert_eq!(record.current.unwrap().image_id, "sha256:2");
+        assert_eq!(record.previous.as_ref().unwrap().image_id, "sha256:1");
+        assert_eq!(record.previous.unwrap().pinned(), "sha256:1");
+        assert!(history.get("db").is_none());
+    }
+
+    #[test]
+    fn the_repo_digest_of_the_image_repository_is_preferred() {
+        let digests = vec!["mirror.example.com/fks_api@sha256:aa".to_string(), "nuniesmith/fks_api@sha256:aa".to_string()];
+        assert_eq!(repo_digest("nuniesmith/fks_api:latest", digests.clone()).as_deref(), Some("nuniesmith/fks_api@sha256:aa"));
+        assert_eq!(repo_digest("localhost:5000/other", digests).as_deref(), Some("mirror.example.com/fks_api@sha256:aa"));
+        assert_eq!(repo_digest("built-locally", vec![]), None);
+    }
+}
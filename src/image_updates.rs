@@ -0,0 +1,548 @@
+use crate::docker_hosts;
+use crate::metrics;
+use crate::models::{EventType, MonitorEvent, ServiceConfig};
+use crate::monitor::MonitorHandle;
+use crate::outbound::{self, Destination};
+use crate::registry_auth;
+use anyhow::{Context, Result};
+use bollard::auth::DockerCredentials;
+use bollard::container::{Config, CreateContainerOptions, NetworkingConfig, RemoveContainerOptions, RenameContainerOptions, StartContainerOptions, StopContainerOptions};
+use bollard::image::CreateImageOptions;
+use bollard::models::{ContainerInspectResponse, EndpointSettings};
+use bollard::network::ConnectNetworkOptions;
+use futures::StreamExt;
+use reqwest::header::{ACCEPT, WWW_AUTHENTICATE};
+use reqwest::StatusCode;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+const DOCKER_HUB: &str = "docker.io";
+/// Where Docker Hub's registry API actually lives
+const DOCKER_HUB_API: &str = "registry-1.docker.io";
+/// Asked for in this order, so multi-arch tags report the index digest `docker pull` records
+const MANIFEST_TYPES: [&str; 4] = [
+    "application/vnd.oci.image.index.v1+json",
+    "application/vnd.docker.distribution.manifest.list.v2+json",
+    "application/vnd.oci.image.manifest.v1+json",
+    "application/vnd.docker.distribution.manifest.v2+json",
+];
+/// How often settings and maintenance windows are looked at between registry checks
+const TICK: Duration = Duration::from_secs(60);
+/// Client timeout for an auto-update (pull plus recreate), on top of the stop timeout
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(900);
+/// Name suffix of a container being replaced, until its successor runs
+const REPLACED_SUFFIX: &str = "-fks-replaced";
+
+/// A tagged image reference split the way the registry API needs it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageRef {
+    /// `docker.io` for Docker Hub images
+    pub registry: String,
+    /// e.g. `library/nginx`
+    pub repository: String,
+    pub tag: String,
+}
+
+impl ImageRef {
+    /// Parse `[registry/]repository[:tag]`, with Docker Hub and `latest` as defaults. Images pinned by
+    /// digest (`repo@sha256:…`) cannot go stale and give `None`.
+    pub fn parse(image: &str) -> Option<Self> {
+        let image = image.trim();
+        if image.is_empty() || image.contains('@') { return None; }
+        let (name, tag) = match image.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, tag),
+            _ => (image, "latest"),
+        };
+        let (registry, repository) = match name.split_once('/') {
+            Some((host, rest)) if host.contains(['.', ':']) || host == "localhost" => (host.to_string(), rest.to_string()),
+            _ => (DOCKER_HUB.to_string(), name.to_string()),
+        };
+        let repository = if registry == DOCKER_HUB && !repository.contains('/') { format!("library/{repository}") } else { repository };
+        Some(Self { registry, repository, tag: tag.to_string() })
+    }
+
+    /// `true` for the same repository in the same registry, whatever the tag.
+    fn same_repository(&self, other: &ImageRef) -> bool {
+        self.registry == other.registry && self.repository == other.repository
+    }
+
+    fn manifest_url(&self, insecure_registries: &[String]) -> String {
+        let scheme = if insecure_registries.contains(&self.registry) { "http" } else { "https" };
+        let host = if self.registry == DOCKER_HUB { DOCKER_HUB_API } else { &self.registry };
+        format!("{scheme}://{host}/v2/{}/manifests/{}", self.repository, self.tag)
+    }
+}
+
+impl std::fmt::Display for ImageRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}:{}", self.registry, self.repository, self.tag)
+    }
+}
+
+/// The digest the registry currently serves for `image`'s tag. Registries that want a token get one
+/// from the realm in their `WWW-Authenticate` challenge, with `login`'s username and password when
+/// given and anonymously otherwise; registries that want basic auth get `login` directly.
+pub async fn remote_digest(client: &reqwest::Client, image: &ImageRef, insecure_registries: &[String], login: Option<&DockerCredentials>) -> Result<String> {
+    let url = image.manifest_url(insecure_registries);
+    let head = || client.head(&url).header(ACCEPT, MANIFEST_TYPES.join(", "));
+    let mut response = outbound::send(Destination::Registry, head()).await.with_context(|| format!("querying {url}"))?;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        let challenge = response.headers().get(WWW_AUTHENTICATE).and_then(|v| v.to_str().ok()).unwrap_or_default();
+        let authenticated = match login.and_then(registry_auth::basic) {
+            Some((username, password)) if challenge.starts_with("Basic") => head().basic_auth(username, Some(password)),
+            basic => {
+                let token = bearer_token(client, challenge, image, basic).await.with_context(|| format!("authenticating to {}", image.registry))?;
+                head().bearer_auth(token)
+            }
+        };
+        response = outbound::send(Destination::Registry, authenticated).await.with_context(|| format!("querying {url}"))?;
+    }
+    if !response.status().is_success() {
+        anyhow::bail!("{url}: HTTP {}", response.status());
+    }
+    response.headers().get("docker-content-digest").and_then(|v| v.to_str().ok()).map(str::to_string)
+        .with_context(|| format!("{url}: no Docker-Content-Digest header"))
+}
+
+/// `realm`, `service` and `scope` of a `Bearer` challenge.
+fn parse_challenge(header: &str) -> Option<HashMap<String, String>> {
+    let params = header.trim().strip_prefix("Bearer ")?;
+    let mut out = HashMap::new();
+    let mut rest = params.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let after = after.trim_start();
+        let (value, tail) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"')?,
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        out.insert(key.trim().to_string(), value.to_string());
+        rest = tail.trim_start_matches([',', ' ']);
+    }
+    out.contains_key("realm").then_some(out)
+}
+
+async fn bearer_token(client: &reqwest::Client, challenge: &str, image: &ImageRef, basic: Option<(&str, &str)>) -> Result<String> {
+    let params = parse_challenge(challenge).with_context(|| format!("unsupported challenge `{challenge}`"))?;
+    let scope = params.get("scope").cloned().unwrap_or_else(|| format!("repository:{}:pull", image.repository));
+    let mut query = vec![("scope", scope)];
+    if let Some(service) = params.get("service") {
+        query.push(("service", service.clone()));
+    }
+    let mut request = client.get(&params["realm"]).query(&query);
+    if let Some((username, password)) = basic {
+        request = request.basic_auth(username, Some(password));
+    }
+    let response = outbound::send(Destination::Registry, request).await?;
+    if !response.status().is_success() {
+        anyhow::bail!("token request: HTTP {}", response.status());
+    }
+    let body: serde_json::Value = response.json().await?;
+    body.get("token").or_else(|| body.get("access_token")).and_then(|t| t.as_str()).map(str::to_string).context("token response has no token")
+}
+
+/// What a container runs: the reference it was created from and the registry digests of its image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningImage {
+    pub image: ImageRef,
+    pub digests: Vec<String>,
+}
+
+impl RunningImage {
+    /// Whether `available` is a newer build; `None` when the image never came from the registry
+    /// (built locally), so there is nothing to compare.
+    pub fn is_stale(&self, available: &str) -> Option<bool> {
+        if self.digests.is_empty() { return None; }
+        Some(!self.digests.iter().any(|d| d == available))
+    }
+}
+
+/// The running image of `container`; `None` when it is pinned by digest or has no image reference.
+pub async fn running_image(docker: &bollard::Docker, container: &str) -> Result<Option<RunningImage>> {
+    let inspect = docker.inspect_container(container, None).await?;
+    let Some(image) = inspect.config.and_then(|c| c.image).as_deref().and_then(ImageRef::parse) else { return Ok(None) };
+    let Some(image_id) = inspect.image else { return Ok(None) };
+    let repo_digests = docker.inspect_image(&image_id).await?.repo_digests.unwrap_or_default();
+    Ok(Some(RunningImage { digests: matching_digests(&image, &repo_digests), image }))
+}
+
+/// Digests from `RepoDigests` (`repo@sha256:…`) recorded for `image`'s repository.
+fn matching_digests(image: &ImageRef, repo_digests: &[String]) -> Vec<String> {
+    repo_digests.iter()
+        .filter_map(|entry| entry.split_once('@'))
+        .filter(|(name, _)| ImageRef::parse(name).is_some_and(|r| r.same_repository(image)))
+        .map(|(_, digest)| digest.to_string())
+        .collect()
+}
+
+/// Pull `image` (logged in with `login` if given) and recreate `container` from it with the same
+/// configuration, host settings and networks. The old container is stopped and set aside under a temporary name until its successor
+/// runs, and is brought back if the new one cannot be created or started.
+pub async fn update_container(docker: bollard::Docker, container: &str, image: &ImageRef, login: Option<DockerCredentials>, stop_timeout_seconds: u64) -> Result<()> {
+    // Pulls and graceful stops both outlast the client's default timeout
+    let docker = docker.with_timeout(UPDATE_TIMEOUT + Duration::from_secs(stop_timeout_seconds));
+    pull(&docker, image, login).await.with_context(|| format!("pulling {image}"))?;
+    recreate_container(&docker, container, None, stop_timeout_seconds).await
+}
+
+/// Recreate `container` the way [`update_container`] does, from `image` (a local image, e.g. pinned by
+/// digest or id) instead of the reference it was created from, without pulling.
+pub async fn recreate_container(docker: &bollard::Docker, container: &str, image: Option<&str>, stop_timeout_seconds: u64) -> Result<()> {
+    let docker = docker.clone().with_timeout(UPDATE_TIMEOUT + Duration::from_secs(stop_timeout_seconds));
+    let old = docker.inspect_container(container, None).await?;
+    let id = old.id.clone().context("container has no id")?;
+    let name = old.name.as_deref().unwrap_or(container).trim_start_matches('/').to_string();
+    let mut replacement = Replacement::of(&old);
+    if let Some(image) = image {
+        replacement.config.image = Some(image.to_string());
+    }
+    docker.stop_container(&id, Some(StopContainerOptions { t: stop_timeout_seconds as i64 })).await?;
+    docker.rename_container(&id, RenameContainerOptions { name: format!("{name}{REPLACED_SUFFIX}") }).await?;
+    match replacement.start(&docker, &name).await {
+        Ok(()) => {
+            if let Err(e) = docker.remove_container(&id, None).await {
+                warn!(container = %name, error = %e, "Updated container is running but the old one could not be removed");
+            }
+            Ok(())
+        }
+        Err(e) => {
+            let _ = docker.remove_container(&name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+            let _ = docker.rename_container(&id, RenameContainerOptions { name: name.clone() }).await;
+            let _ = docker.start_container(&id, None::<StartContainerOptions<String>>).await;
+            Err(e.context("the previous container was restored"))
+        }
+    }
+}
+
+async fn pull(docker: &bollard::Docker, image: &ImageRef, login: Option<DockerCredentials>) -> Result<()> {
+    let options = CreateImageOptions { from_image: format!("{}/{}", image.registry, image.repository), tag: image.tag.clone(), ..Default::default() };
+    let mut progress = docker.create_image(Some(options), None, login);
+    while let Some(step) = progress.next().await {
+        step?;
+    }
+    Ok(())
+}
+
+/// What a recreated container is created with, taken from the inspected original.
+#[derive(Debug, Clone, Default)]
+struct Replacement {
+    config: Config<String>,
+    /// Joined after creation; the first network goes in the create request
+    extra_networks: Vec<(String, EndpointSettings)>,
+}
+
+impl Replacement {
+    fn of(old: &ContainerInspectResponse) -> Self {
+        let short_id: String = old.id.as_deref().unwrap_or_default().chars().take(12).collect();
+        let mut config: Config<String> = old.config.clone().unwrap_or_default().into();
+        // Docker defaults the hostname to the short id; the new container gets its own
+        if config.hostname.as_deref() == Some(short_id.as_str()) { config.hostname = None; }
+        config.host_config = old.host_config.clone();
+
+        let mut networks: Vec<(String, EndpointSettings)> = old.network_settings.as_ref().and_then(|n| n.networks.clone()).unwrap_or_default().into_iter()
+            .map(|(network, endpoint)| {
+                let aliases = endpoint.aliases.map(|a| a.into_iter().filter(|alias| *alias != short_id).collect());
+                (network, EndpointSettings { aliases, links: endpoint.links, ipam_config: endpoint.ipam_config, driver_opts: endpoint.driver_opts, ..Default::default() })
+            })
+            .collect();
+        networks.sort_by(|a, b| a.0.cmp(&b.0));
+        let mode = old.host_config.as_ref().and_then(|h| h.network_mode.clone()).unwrap_or_default();
+        let primary = networks.iter().position(|(n, _)| *n == mode).or((!networks.is_empty()).then_some(0));
+        if let Some(primary) = primary {
+            let (network, endpoint) = networks.remove(primary);
+            config.networking_config = Some(NetworkingConfig { endpoints_config: HashMap::from([(network, endpoint)]) });
+        }
+        Self { config, extra_networks: networks }
+    }
+
+    async fn start(self, docker: &bollard::Docker, name: &str) -> Result<()> {
+        docker.create_container(Some(CreateContainerOptions { name, platform: None }), self.config).await?;
+        for (network, endpoint_config) in self.extra_networks {
+            docker.connect_network(&network, ConnectNetworkOptions { container: name, endpoint_config }).await.with_context(|| format!("joining network {network}"))?;
+        }
+        docker.start_container(name, None::<StartContainerOptions<String>>).await?;
+        Ok(())
+    }
+}
+
+/// A service whose container lags behind its image tag.
+#[derive(Debug, Clone)]
+struct Stale {
+    image: ImageRef,
+    running_digest: Option<String>,
+    available: String,
+    /// An auto-update to `available` failed; it is tried again after the next registry check
+    failed: bool,
+}
+
+/// What the checker knows between rounds.
+#[derive(Default)]
+struct Tracker {
+    stale: HashMap<String, Stale>,
+    /// `(service_id, image, stale)` as last published
+    gauges: Vec<(String, String, bool)>,
+}
+
+impl Tracker {
+    fn mark_current(&mut self, service_id: &str) {
+        self.stale.remove(service_id);
+        for gauge in self.gauges.iter_mut().filter(|g| g.0 == service_id) { gauge.2 = false; }
+        metrics::set_image_staleness(&self.gauges);
+    }
+}
+
+/// Check every containerized service every `image_updates.interval_minutes` while enabled, and apply
+/// pending auto-updates as their maintenance windows open.
+pub fn spawn(monitor: MonitorHandle) {
+    tokio::spawn(run(monitor));
+}
+
+async fn run(monitor: MonitorHandle) {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build().expect("Failed to create HTTP client");
+    let mut tracker = Tracker::default();
+    let mut next_check = Instant::now();
+    loop {
+        let settings = monitor.config().image_updates.clone();
+        if !settings.enabled {
+            if !tracker.gauges.is_empty() || !tracker.stale.is_empty() {
+                tracker = Tracker::default();
+                metrics::set_image_staleness(&[]);
+            }
+            next_check = Instant::now();
+        } else {
+            if Instant::now() >= next_check {
+                check_all(&monitor, &client, &settings.insecure_registries, &mut tracker).await;
+                next_check = Instant::now() + Duration::from_secs(settings.interval_minutes.max(1) * 60);
+            }
+            apply_auto_updates(&monitor, &mut tracker).await;
+        }
+        tokio::time::sleep(TICK).await;
+    }
+}
+
+async fn check_all(monitor: &MonitorHandle, client: &reqwest::Client, insecure: &[String], tracker: &mut Tracker) {
+    let config = monitor.config();
+    // Services often share an image; ask the registry once per reference.
+    let mut remote: HashMap<ImageRef, Option<String>> = HashMap::new();
+    let mut gauges = Vec::new();
+    let mut stale = HashMap::new();
+    for service in &config.services {
+        let Some(container) = &service.docker_container else { continue };
+        let docker = match docker_hosts::connect_for(&config, service) {
+            Ok(docker) => docker,
+            Err(e) => {
+                warn!(service = %service.id, error = format!("{e:#}"), "Image update check: cannot connect to Docker");
+                continue;
+            }
+        };
+        let running = match running_image(&docker, container).await {
+            Ok(Some(running)) => running,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!(service = %service.id, error = format!("{e:#}"), "Image update check: cannot inspect container");
+                continue;
+            }
+        };
+        if !remote.contains_key(&running.image) {
+            let lookup = async {
+                let login = registry_auth::credentials(&config.registries, &running.image.registry).await?;
+                remote_digest(client, &running.image, insecure, login.as_ref()).await
+            };
+            let digest = lookup.await
+                .map_err(|e| warn!(image = %running.image, error = format!("{e:#}"), "Image update check: registry lookup failed"))
+                .ok();
+            remote.insert(running.image.clone(), digest);
+        }
+        let Some(available) = remote[&running.image].clone() else { continue };
+        let Some(is_stale) = running.is_stale(&available) else { continue };
+        gauges.push((service.id.clone(), running.image.to_string(), is_stale));
+        if !is_stale { continue; }
+        let announced = tracker.stale.get(&service.id).is_some_and(|s| s.available == available);
+        if !announced {
+            info!(service = %service.id, image = %running.image, digest = %available, "🆕 Newer image available");
+            monitor.inject_event(MonitorEvent {
+                event_type: EventType::ImageUpdateAvailable,
+                service_id: Some(service.id.clone()),
+                message: format!("Newer image available for {}: {}", service.name, running.image),
+                timestamp: monitor.now(),
+                data: Some(serde_json::json!({"image": running.image.to_string(), "running_digest": running.digests.first(), "available_digest": available})),
+                metadata: Default::default(),
+            });
+        }
+        stale.insert(service.id.clone(), Stale { running_digest: running.digests.first().cloned(), image: running.image, available, failed: false });
+    }
+    metrics::set_image_staleness(&gauges);
+    *tracker = Tracker { stale, gauges };
+}
+
+/// Recreate stale `auto_update` services whose maintenance window is open, unless automation is frozen.
+async fn apply_auto_updates(monitor: &MonitorHandle, tracker: &mut Tracker) {
+    if tracker.stale.is_empty() || monitor.automation_frozen() { return; }
+    let config = monitor.config();
+    let now = monitor.now();
+    let due: Vec<(ServiceConfig, Stale, String)> = tracker.stale.iter()
+        .filter(|(_, stale)| !stale.failed)
+        .filter_map(|(id, stale)| {
+            let service = config.services.iter().find(|s| &s.id == id && s.auto_update && s.docker_container.is_some())?;
+            let window = monitor.silences().maintenance_window(service, now)?;
+            Some((service.clone(), stale.clone(), window))
+        })
+        .collect();
+    for (service, stale, window) in due {
+        let container = service.docker_container.as_deref().unwrap_or_default();
+        info!(service = %service.id, image = %stale.image, window = %window, "⬆️ Auto-updating to the latest image");
+        let started = Instant::now();
+        let updated = async {
+            let docker = docker_hosts::connect_for(&config, &service)?;
+            let login = registry_auth::credentials(&config.registries, &stale.image.registry).await?;
+            update_container(docker, container, &stale.image, login, config.monitoring.restart_stop_timeout_seconds).await
+        }.await;
+        metrics::increment_service_restart(&service.id, &service.name, updated.is_ok());
+        metrics::observe_service_restart_duration(&service.id, started.elapsed().as_secs_f64());
+        let data = serde_json::json!({
+            "reason": "auto_update", "container": container, "image": stale.image.to_string(), "maintenance_window": window,
+            "old_digest": stale.running_digest, "new_digest": stale.available, "success": updated.is_ok(),
+        });
+        let (event_type, message) = match &updated {
+            Ok(()) => (EventType::ServiceRestarted, format!("{} updated to the latest {} build", service.name, stale.image)),
+            Err(e) => {
+                error!(service = %service.id, error = format!("{e:#}"), "Auto-update failed");
+                (EventType::SystemAlert, format!("Auto-update of {} failed: {e:#}", service.name))
+            }
+        };
+        monitor.inject_event(MonitorEvent { event_type, service_id: Some(service.id.clone()), message, timestamp: monitor.now(), data: Some(data), metadata: Default::default() });
+        match updated {
+            Ok(()) => {
+                tracker.mark_current(&service.id);
+                monitor.check_soon(&service.id);
+            }
+            Err(_) => {
+                if let Some(stale) = tracker.stale.get_mut(&service.id) { stale.failed = true; }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode as Status};
+    use axum::response::IntoResponse;
+
+    #[test]
+    fn references_default_to_docker_hub_and_latest() {
+        let hub = ImageRef::parse("nginx").unwrap();
+        assert_eq!((hub.registry.as_str(), hub.repository.as_str(), hub.tag.as_str()), ("docker.io", "library/nginx", "latest"));
+        assert_eq!(hub.manifest_url(&[]), "https://registry-1.docker.io/v2/library/nginx/manifests/latest");
+        let ghcr = ImageRef::parse("ghcr.io/nuniesmith/fks:api").unwrap();
+        assert_eq!((ghcr.registry.as_str(), ghcr.repository.as_str(), ghcr.tag.as_str()), ("ghcr.io", "nuniesmith/fks", "api"));
+        let local = ImageRef::parse("localhost:5000/fks/api").unwrap();
+        assert_eq!((local.registry.as_str(), local.tag.as_str()), ("localhost:5000", "latest"));
+        assert_eq!(local.manifest_url(&["localhost:5000".into()]), "http://localhost:5000/v2/fks/api/manifests/latest");
+        assert_eq!(ImageRef::parse("nuniesmith/fks:web").unwrap().repository, "nuniesmith/fks");
+        assert_eq!(ImageRef::parse("nginx@sha256:abc"), None, "pinned by digest");
+    }
+
+    #[test]
+    fn staleness_compares_the_repository_digests() {
+        let image = ImageRef::parse("nuniesmith/fks:api").unwrap();
+        let digests = matching_digests(&image, &["nuniesmith/fks@sha256:old".into(), "other/repo@sha256:new".into()]);
+        let running = RunningImage { image, digests };
+        assert_eq!(running.digests, vec!["sha256:old".to_string()]);
+        assert_eq!(running.is_stale("sha256:old"), Some(false));
+        assert_eq!(running.is_stale("sha256:new"), Some(true));
+        assert_eq!(RunningImage { digests: vec![], ..running }.is_stale("sha256:new"), None, "built locally");
+    }
+
+    #[test]
+    fn replacements_keep_settings_and_networks_but_not_the_old_identity() {
+        let old: ContainerInspectResponse = serde_json::from_value(serde_json::json!({
+            "Id": "0123456789abcdef", "Name": "/fks_api",
+            "Config": {"Hostname": "0123456789ab", "Image": "nuniesmith/fks:api", "Env": ["RUST_LOG=info"]},
+            "HostConfig": {"NetworkMode": "fks_default", "RestartPolicy": {"Name": "unless-stopped"}},
+            "NetworkSettings": {"Networks": {
+                "fks_default": {"Aliases": ["fks_api", "0123456789ab"], "IPAddress": "172.18.0.4", "NetworkID": "n1"},
+                "monitoring": {"Aliases": ["api"]},
+            }},
+        })).unwrap();
+        let replacement = Replacement::of(&old);
+        assert_eq!(replacement.config.hostname, None);
+        assert_eq!(replacement.config.image.as_deref(), Some("nuniesmith/fks:api"));
+        assert_eq!(replacement.config.env, Some(vec!["RUST_LOG=info".to_string()]));
+        assert_eq!(replacement.config.host_config.as_ref().and_then(|h| h.network_mode.as_deref()), Some("fks_default"));
+        let primary = &replacement.config.networking_config.as_ref().unwrap().endpoints_config["fks_default"];
+        assert_eq!(primary.aliases, Some(vec!["fks_api".to_string()]));
+        assert_eq!(primary.ip_address, None, "addresses are assigned anew");
+        assert_eq!(replacement.extra_networks.len(), 1);
+        assert_eq!(replacement.extra_networks[0].0, "monitoring");
+    }
+
+    #[test]
+    fn bearer_challenges_are_parsed() {
+        let params = parse_challenge(r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/nginx:pull""#).unwrap();
+        assert_eq!(params["realm"], "https://auth.docker.io/token");
+        assert_eq!(params["scope"], "repository:library/nginx:pull");
+        assert_eq!(parse_challenge("Basic realm=\"registry\""), None);
+    }
+
+    #[tokio::test]
+    async fn digests_are_fetched_with_an_anonymous_token() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let realm = format!("http://{addr}/token");
+        let app = axum::Router::new()
+            .route("/token", axum::routing::get(|| async { axum::Json(serde_json::json!({"token": "t0k"})) }))
+            .route("/v2/fks/api/manifests/{tag}", axum::routing::head(move |headers: HeaderMap| async move {
+                if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer t0k") {
+                    let challenge = format!(r#"Bearer realm="{realm}",service="test",scope="repository:fks/api:pull""#);
+                    return (Status::UNAUTHORIZED, [("www-authenticate", challenge)]).into_response();
+                }
+                (Status::OK, [("docker-content-digest", "sha256:new")]).into_response()
+            }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let image = ImageRef::parse(&format!("{addr}/fks/api:latest")).unwrap();
+        let insecure = vec![image.registry.clone()];
+        let digest = remote_digest(&reqwest::Client::new(), &image, &insecure, None).await.unwrap();
+        assert_eq!(digest, "sha256:new");
+        let missing = ImageRef::parse(&format!("{addr}/fks/web")).unwrap();
+        assert!(remote_digest(&reqwest::Client::new(), &missing, &insecure, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn private_registries_get_the_configured_login() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let realm = format!("http://{addr}/token");
+        let app = axum::Router::new()
+            .route("/token", axum::routing::get(|headers: HeaderMap| async move {
+                // ci:pw
+                if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("Basic Y2k6cHc=") {
+                    return Status::UNAUTHORIZED.into_response();
+                }
+                axum::Json(serde_json::json!({"access_token": "priv"})).into_response()
+            }))
+            .route("/v2/fks/api/manifests/{tag}", axum::routing::head(move |headers: HeaderMap| async move {
+                if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("Bearer priv") {
+                    return (Status::UNAUTHORIZED, [("www-authenticate", format!(r#"Bearer realm="{realm}""#))]).into_response();
+                }
+                (Status::OK, [("docker-content-digest", "sha256:private")]).into_response()
+            }))
+            .route("/v2/fks/web/manifests/{tag}", axum::routing::head(|headers: HeaderMap| async move {
+                if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some("Basic Y2k6cHc=") {
+                    return (Status::UNAUTHORIZED, [("www-authenticate", "Basic realm=\"registry\"")]).into_response();
+                }
+                (Status::OK, [("docker-content-digest", "sha256:basic")]).into_response()
+            }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let image = ImageRef::parse(&format!("{addr}/fks/api:latest")).unwrap();
+        let insecure = vec![image.registry.clone()];
+        let login = DockerCredentials { username: Some("ci".into()), password: Some("pw".into()), ..Default::default() };
+        let client = reqwest::Client::new();
+        assert!(remote_digest(&client, &image, &insecure, None).await.is_err(), "anonymous tokens are refused");
+        assert_eq!(remote_digest(&client, &image, &insecure, Some(&login)).await.unwrap(), "sha256:private");
+        let basic = ImageRef::parse(&format!("{addr}/fks/web")).unwrap();
+        assert_eq!(remote_digest(&client, &basic, &insecure, Some(&login)).await.unwrap(), "sha256:basic");
+    }
+}
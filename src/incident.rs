@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+/// Resolved incidents kept for `GET /api/incidents`; open ones are never dropped.
+const MAX_RESOLVED: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentState {
+    Open,
+    Acknowledged,
+    Resolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    pub by: String,
+    /// `by` was named in the request body rather than taken from a verified token
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub self_reported: bool,
+    pub at: DateTime<Utc>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Opened when a critical service goes down, closed when it recovers or someone resolves it.
+/// Every acknowledgement is kept, so on-call handoffs show up in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    pub id: String,
+    pub service_id: String,
+    pub service_name: String,
+    pub state: IncidentState,
+    pub opened_at: DateTime<Utc>,
+    #[serde(default)]
+    pub acknowledgements: Vec<Acknowledgement>,
+    #[serde(default)]
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// `None` when the service recovered by itself
+    #[serde(default)]
+    pub resolved_by: Option<String>,
+    /// `resolved_by` was named in the request body rather than taken from a verified token
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resolved_by_self_reported: bool,
+    #[serde(default)]
+    pub resolution_note: Option<String>,
+}
+
+/// Body of `POST /api/incidents/{id}/ack` and `/resolve`, both optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IncidentUpdate {
+    /// Who is acting, for callers without a JWT identity; recorded as self-reported. A bearer
+    /// token's subject always wins.
+    #[serde(default)]
+    pub by: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Who acknowledges or resolves an incident.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Actor {
+    pub name: String,
+    /// Named by the caller rather than taken from a verified token
+    pub self_reported: bool,
+}
+
+impl Actor {
+    pub fn verified(name: impl Into<String>) -> Self {
+        Self { name: name.into(), self_reported: false }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncidentError {
+    NotFound,
+    AlreadyResolved,
+}
+
+/// Incidents, oldest first.
+#[derive(Clone, Default)]
+pub struct Incidents {
+    list: Arc<RwLock<VecDeque<Incident>>>,
+}
+
+impl Incidents {
+    /// Open an incident for `service_id` unless one is still unresolved.
+    pub fn open(&self, service_id: &str, service_name: &str, now: DateTime<Utc>) -> Option<Incident> {
+        let mut list = self.list.write().ok()?;
+        if list.iter().any(|i| i.service_id == service_id && i.state != IncidentState::Resolved) {
+            return None;
+        }
+        let incident = Incident {
+            id: uuid::Uuid::new_v4().to_string(),
+            service_id: service_id.to_string(),
+            service_name: service_name.to_string(),
+            state: IncidentState::Open,
+            opened_at: now,
+            acknowledgements: Vec::new(),
+            resolved_at: None,
+            resolved_by: None,
+            resolved_by_self_reported: false,
+            resolution_note: None,
+        };
+        list.push_back(incident.clone());
+        Self::trim(&mut list);
+        Some(incident)
+    }
+
+    /// Record that `by` is on it. An acknowledged incident can be acknowledged again by whoever
+    /// takes over.
+    pub fn acknowledge(&self, id: &str, by: &Actor, note: Option<String>, now: DateTime<Utc>) -> Result<Incident, IncidentError> {
+        self.update(id, |incident| {
+            incident.state = IncidentState::Acknowledged;
+            incident.acknowledgements.push(Acknowledgement { by: by.name.clone(), self_reported: by.self_reported, at: now, note });
+        })
+    }
+
+    /// Close the incident by hand, whether or not the service is back.
+    pub fn resolve(&self, id: &str, by: &Actor, note: Option<String>, now: DateTime<Utc>) -> Result<Incident, IncidentError> {
+        self.update(id, |incident| {
+            incident.state = IncidentState::Resolved;
+            incident.resolved_at = Some(now);
+            incident.resolved_by = Some(by.name.clone());
+            incident.resolved_by_self_reported = by.self_reported;
+            incident.resolution_note = note;
+        })
+    }
+
+    /// Close the unresolved incident of `service_id` after the service recovered; `None` if there was none.
+    pub fn recovered(&self, service_id: &str, now: DateTime<Utc>) -> Option<Incident> {
+        let mut list = self.list.write().ok()?;
+        let incident = list.iter_mut().find(|i| i.service_id == service_id && i.state != IncidentState::Resolved)?;
+        incident.state = IncidentState::Resolved;
+        incident.resolved_at = Some(now);
+        Some(incident.clone())
+    }
+
+    /// Whether the unresolved incident of `service_id` was acknowledged, which stops escalations
+    /// and reminders about it.
+    pub fn acknowledged(&self, service_id: &str) -> bool {
+        self.list.read().is_ok_and(|list| list.iter().any(|i| i.service_id == service_id && i.state == IncidentState::Acknowledged))
+    }
+
+    /// Newest first, optionally only those in `state`.
+    pub fn list(&self, state: Option<IncidentState>) -> Vec<Incident> {
+        let Ok(list) = self.list.read() else { return Vec::new() };
+        list.iter().rev().filter(|i| state.is_none_or(|s| i.state == s)).cloned().collect()
+    }
+
+    /// Everything, oldest first, for an upgrade snapshot.
+    pub fn snapshot(&self) -> Vec<Incident> {
+        self.list.read().map(|list| list.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Carry incidents over from a previous process.
+    pub fn restore(&self, incidents: Vec<Incident>) {
+        if let Ok(mut list) = self.list.write() {
+            list.extend(incidents);
+            Self::trim(&mut list);
+        }
+    }
+
+    fn update(&self, id: &str, change: impl FnOnce(&mut Incident)) -> Result<Incident, IncidentError> {
+        let mut list = self.list.write().map_err(|_| IncidentError::NotFound)?;
+        let incident = list.iter_mut().find(|i| i.id == id).ok_or(IncidentError::NotFound)?;
+        if incident.state == IncidentState::Resolved {
+            return Err(IncidentError::AlreadyResolved);
+        }
+        change(incident);
+        Ok(incident.clone())
+    }
+
+    fn trim(list: &mut VecDeque<Incident>) {
+        let mut excess = list.iter().filter(|i| i.state == IncidentState::Resolved).count().saturating_sub(MAX_RESOLVED);
+        list.retain(|i| {
+            let drop = excess > 0 && i.state == IncidentState::Resolved;
+            if drop { excess -= 1; }
+            !drop
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acknowledgements_are_kept_until_the_incident_is_resolved() {
+        let incidents = Incidents::default();
+        let t0 = Utc::now();
+        let incident = incidents.open("db", "Database", t0).unwrap();
+        assert!(incidents.open("db", "Database", t0).is_none(), "one unresolved incident per service");
+        assert!(!incidents.acknowledged("db"));
+
+        incidents.acknowledge(&incident.id, &Actor::verified("alice"), None, t0).unwrap();
+        let bob = Actor { name: "bob".into(), self_reported: true };
+        let handed_over = incidents.acknowledge(&incident.id, &bob, Some("taking over".into()), t0).unwrap();
+        assert_eq!(handed_over.acknowledgements.iter().map(|a| (a.by.as_str(), a.self_reported)).collect::<Vec<_>>(), [("alice", false), ("bob", true)]);
+        assert!(incidents.acknowledged("db"));
+
+        let resolved = incidents.recovered("db", t0).unwrap();
+        assert_eq!((resolved.state, resolved.resolved_by), (IncidentState::Resolved, None));
+        assert!(!incidents.acknowledged("db"));
+        let carol = Actor::verified("carol");
+        assert_eq!(incidents.acknowledge(&incident.id, &carol, None, t0).unwrap_err(), IncidentError::AlreadyResolved);
+        assert_eq!(incidents.resolve("nope", &carol, None, t0).unwrap_err(), IncidentError::NotFound);
+
+        let next = incidents.open("db", "Database", t0).unwrap();
+        assert_eq!(incidents.list(None)[0].id, next.id, "newest first");
+        assert_eq!(incidents.list(Some(IncidentState::Resolved)).len(), 1);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::config::KubernetesConfig;
+use crate::metrics;
+use crate::outbound::{Destination, Outcome};
+use anyhow::{Context, Result};
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, DeleteParams, ListParams};
+use kube::config::{KubeConfigOptions, Kubeconfig};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Mounted into every pod that runs with a service account
+const SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Clients of the API server, kept by the monitor: probes and restarts share one connection until a
+/// reload changes the `[kubernetes]` settings.
+#[derive(Clone, Default)]
+pub struct Clients {
+    current: Arc<tokio::sync::Mutex<Option<(KubernetesConfig, kube::Client)>>>,
+}
+
+impl Clients {
+    /// Client of the API server `config` points at, each call through it limited to `timeout`.
+    pub async fn get(&self, config: &KubernetesConfig, timeout: Duration) -> Result<Client> {
+        let mut current = self.current.lock().await;
+        let client = match current.as_ref() {
+            Some((cached, client)) if cached == config => client.clone(),
+            _ => {
+                let client = connect(config).await?;
+                *current = Some((config.clone(), client.clone()));
+                client
+            }
+        };
+        Ok(Client { client, timeout })
+    }
+}
+
+/// The `[kubernetes]` settings as a kubeconfig, falling back to the pod's service account. kube-rs
+/// re-reads a token file as the kubelet rotates it.
+async fn connect(config: &KubernetesConfig) -> Result<kube::Client> {
+    let server = match &config.api_server {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => {
+            let host = std::env::var("KUBERNETES_SERVICE_HOST").context("kubernetes.api_server is not set and fks_master is not running in a cluster")?;
+            let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".into());
+            if host.contains(':') { format!("https://[{host}]:{port}") } else { format!("https://{host}:{port}") }
+        }
+    };
+    let mounted = |file: &str| Some(format!("{SERVICE_ACCOUNT_DIR}/{file}")).filter(|path| Path::new(path).exists());
+    let user = match &config.token {
+        Some(token) => serde_json::json!({ "token": token }),
+        None => serde_json::json!({ "tokenFile": mounted("token") }),
+    };
+    let kubeconfig: Kubeconfig = serde_json::from_value(serde_json::json!({
+        "clusters": [{ "name": "fks", "cluster": {
+            "server": server,
+            "certificate-authority": config.ca_file.clone().or_else(|| mounted("ca.crt")),
+            "insecure-skip-tls-verify": config.insecure_skip_tls_verify,
+        }}],
+        "users": [{ "name": "fks", "user": user }],
+        "contexts": [{ "name": "fks", "context": { "cluster": "fks", "user": "fks" } }],
+        "current-context": "fks",
+    }))?;
+    let options = KubeConfigOptions::default();
+    let client_config = kube::Config::from_custom_kubeconfig(kubeconfig, &options).await.context("loading the [kubernetes] settings")?;
+    Ok(kube::Client::try_from(client_config)?)
+}
+
+/// Kubernetes API access with the `[kubernetes]` settings; see [`Clients::get`].
+pub struct Client {
+    client: kube::Client,
+    /// Of every call made through this client
+    timeout: Duration,
+}
+
+impl Client {
+    /// Run `call` within the timeout, recording it with the other outbound requests.
+    async fn call<T>(&self, what: &str, call: impl Future<Output = kube::Result<T>>) -> Result<T> {
+        let started = Instant::now();
+        let result = tokio::time::timeout(self.timeout, call).await;
+        let outcome = match &result {
+            Ok(Ok(_)) => Outcome::Success,
+            Ok(Err(kube::Error::Api(e))) if e.code >= 500 => Outcome::ServerError,
+            Ok(Err(kube::Error::Api(_))) => Outcome::ClientError,
+            Ok(Err(kube::Error::HyperError(_) | kube::Error::Service(_))) => Outcome::Connect,
+            Ok(Err(_)) => Outcome::Error,
+            Err(_) => Outcome::Timeout,
+        };
+        metrics::record_outbound_request(Destination::Kubernetes.as_str(), outcome.as_str(), started.elapsed().as_secs_f64());
+        match result {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(kube::Error::Api(e))) => anyhow::bail!("Kubernetes API: {what}: HTTP {}: {}", e.code, e.message),
+            Ok(Err(e)) => Err(e).with_context(|| format!("Kubernetes API: {what}")),
+            Err(_) => anyhow::bail!("Kubernetes API: {what} timed out after {}s", self.timeout.as_secs()),
+        }
+    }
+
+    fn pod_api(&self, namespace: &str) -> Api<Pod> {
+        Api::namespaced(self.client.clone(), namespace)
+    }
+
+    /// Pods in `namespace` matching the label `selector`.
+    pub async fn pods(&self, namespace: &str, selector: &str) -> Result<Vec<Pod>> {
+        let pods = self.pod_api(namespace);
+        Ok(self.call("listing pods", pods.list(&ListParams::default().labels(selector))).await?.items)
+    }
+
+    /// What `kubectl rollout restart` does: stamp the pod template so the controller replaces every pod.
+    pub async fn rollout_restart(&self, namespace: &str, workload: &str) -> Result<()> {
+        let client = self.client.clone();
+        let what = format!("restarting {workload}");
+        match parse_workload(workload)? {
+            Workload::Deployment(name) => self.call(&what, Api::<Deployment>::namespaced(client, namespace).restart(name)).await.map(drop),
+            Workload::StatefulSet(name) => self.call(&what, Api::<StatefulSet>::namespaced(client, namespace).restart(name)).await.map(drop),
+            Workload::DaemonSet(name) => self.call(&what, Api::<DaemonSet>::namespaced(client, namespace).restart(name)).await.map(drop),
+        }
+    }
+
+    /// Delete the pods matching `selector`, each with `grace_period_seconds` to shut down; their
+    /// controller creates new ones. Returns how many were deleted, not counting pods already terminating.
+    pub async fn delete_pods(&self, namespace: &str, selector: &str, grace_period_seconds: u64) -> Result<usize> {
+        let current = self.pods(namespace, selector).await?.iter().filter(|p| p.metadata.deletion_timestamp.is_none()).count();
+        if current == 0 {
+            anyhow::bail!("no pods match `{selector}` in namespace {namespace}");
+        }
+        let params = DeleteParams { grace_period_seconds: Some(grace_period_seconds.min(u32::MAX as u64) as u32), ..Default::default() };
+        let pods = self.pod_api(namespace);
+        self.call("deleting pods", pods.delete_collection(&params, &ListParams::default().labels(selector))).await?;
+        Ok(current)
+    }
+}
+
+/// A workload `kubectl rollout restart` can restart.
+#[derive(Debug, PartialEq, Eq)]
+enum Workload<'a> {
+    Deployment(&'a str),
+    StatefulSet(&'a str),
+    DaemonSet(&'a str),
+}
+
+/// `name`, `deployment/name`, `statefulset/name` or `daemonset/name`.
+fn parse_workload(workload: &str) -> Result<Workload<'_>> {
+    let Some((kind, name)) = workload.split_once('/') else { return Ok(Workload::Deployment(workload)) };
+    match kind.to_ascii_lowercase().as_str() {
+        "deployment" | "deployments" | "deploy" => Ok(Workload::Deployment(name)),
+        "statefulset" | "statefulsets" | "sts" => Ok(Workload::StatefulSet(name)),
+        "daemonset" | "daemonsets" | "ds" => Ok(Workload::DaemonSet(name)),
+        other => anyhow::bail!("unsupported workload kind `{other}` (expected deployment, statefulset or daemonset)"),
+    }
+}
+
+fn is_ready(pod: &Pod) -> bool {
+    pod.status.as_ref().and_then(|s| s.conditions.as_ref()).is_some_and(|conditions| conditions.iter().any(|c| c.type_ == "Ready" && c.status == "True"))
+}
+
+/// Why `pod` is not ready: containers failing readiness, or crash looping after liveness failures.
+fn pod_problem(pod: &Pod) -> String {
+    let status = pod.status.as_ref();
+    let containers: Vec<String> = status.and_then(|s| s.container_statuses.as_ref()).into_iter().flatten().filter(|c| !c.ready).map(|c| {
+        let state = c.state.as_ref().and_then(|s| {
+            s.waiting.as_ref().and_then(|w| w.reason.clone()).or_else(|| s.terminated.as_ref().and_then(|t| t.reason.clone()))
+        }).unwrap_or_else(|| "not ready".into());
+        if c.restart_count > 0 { format!("{} {state}, {} restarts", c.name, c.restart_count) } else { format!("{} {state}", c.name) }
+    }).collect();
+    let detail = if containers.is_empty() { status.and_then(|s| s.phase.clone()).unwrap_or_else(|| "not ready".into()) } else { containers.join(", ") };
+    format!("{}: {detail}", pod.metadata.name.as_deref().unwrap_or_default())
+}
+
+/// How many of a service's pods are ready. Pods being deleted are left out, so a rollout does not
+/// count its outgoing pods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Readiness {
+    pub ready: usize,
+    pub total: usize,
+    /// One entry per pod that is not ready
+    pub problems: Vec<String>,
+}
+
+impl Readiness {
+    pub fn of(pods: &[Pod]) -> Self {
+        let current: Vec<&Pod> = pods.iter().filter(|p| p.metadata.deletion_timestamp.is_none()).collect();
+        Self {
+            ready: current.iter().filter(|p| is_ready(p)).count(),
+            total: current.len(),
+            problems: current.iter().filter(|p| !is_ready(p)).map(|p| pod_problem(p)).collect(),
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}/{} pods ready", self.ready, self.total);
+        if !self.problems.is_empty() {
+            summary.push_str(&format!(" ({})", self.problems.join("; ")));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, Query};
+    use axum::http::{header::CONTENT_TYPE, HeaderMap};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    fn pods() -> serde_json::Value {
+        serde_json::json!({"apiVersion": "v1", "kind": "PodList", "metadata": {}, "items": [
+            {"metadata": {"name": "fks-api-7d9-a"}, "status": {"phase": "Running",
+                "conditions": [{"type": "Ready", "status": "True"}],
+                "containerStatuses": [{"name": "api", "image": "fks_api", "imageID": "", "ready": true, "restartCount": 0, "state": {"running": {}}}]}},
+            {"metadata": {"name": "fks-api-7d9-b"}, "status": {"phase": "Running",
+                "conditions": [{"type": "Ready", "status": "False"}],
+                "containerStatuses": [{"name": "api", "image": "fks_api", "imageID": "", "ready": false, "restartCount": 4, "state": {"waiting": {"reason": "CrashLoopBackOff"}}}]}},
+            {"metadata": {"name": "fks-api-6c1-z", "deletionTimestamp": "2025-01-01T00:00:00Z"}, "status": {"phase": "Running"}},
+            {"metadata": {"name": "fks-api-7d9-c"}, "status": {"phase": "Pending"}},
+        ]})
+    }
+
+    #[test]
+    fn readiness_counts_current_pods_and_explains_the_rest() {
+        let pods: Vec<Pod> = serde_json::from_value(pods()["items"].clone()).unwrap();
+        let readiness = Readiness::of(&pods);
+        assert_eq!((readiness.ready, readiness.total), (1, 3), "the terminating pod is not counted");
+        assert_eq!(readiness.summary(), "1/3 pods ready (fks-api-7d9-b: api CrashLoopBackOff, 4 restarts; fks-api-7d9-c: Pending)");
+    }
+
+    #[test]
+    fn workloads_map_to_api_resources() {
+        assert_eq!(parse_workload("fks-api").unwrap(), Workload::Deployment("fks-api"));
+        assert_eq!(parse_workload("statefulset/postgres").unwrap(), Workload::StatefulSet("postgres"));
+        assert_eq!(parse_workload("ds/node-exporter").unwrap(), Workload::DaemonSet("node-exporter"));
+        assert!(parse_workload("cronjob/backup").is_err());
+    }
+
+    #[tokio::test]
+    async fn restarts_patch_the_workload_or_delete_pods() {
+        let calls = Arc::new(Mutex::new(Vec::<String>::new()));
+        let record = |calls: Arc<Mutex<Vec<String>>>, call: String| calls.lock().unwrap().push(call);
+        let app = {
+            let (list, patch, delete) = (calls.clone(), calls.clone(), calls.clone());
+            axum::Router::new()
+                .route("/api/v1/namespaces/{ns}/pods", axum::routing::get(move |Path(ns): Path<String>, Query(q): Query<HashMap<String, String>>, headers: HeaderMap| async move {
+                    record(list, format!("list {ns} {} {:?}", q["labelSelector"], headers.get("authorization")));
+                    axum::Json(pods())
+                }).delete(move |Query(q): Query<HashMap<String, String>>, body: String| async move {
+                    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                    record(delete, format!("delete {} grace={}", q["labelSelector"], body["gracePeriodSeconds"]));
+                    axum::Json(pods())
+                }))
+                .route("/apis/apps/v1/namespaces/{ns}/{kind}/{name}", axum::routing::patch(move |Path((ns, kind, name)): Path<(String, String, String)>, headers: HeaderMap, body: String| async move {
+                    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                    let stamped = body["spec"]["template"]["metadata"]["annotations"].as_object().is_some_and(|a| !a.is_empty());
+                    record(patch, format!("patch {kind}/{name} {:?} stamped={stamped}", headers[CONTENT_TYPE]));
+                    axum::Json(serde_json::json!({"apiVersion": "apps/v1", "kind": "StatefulSet", "metadata": {"name": name, "namespace": ns}}))
+                }))
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = KubernetesConfig { api_server: Some(format!("http://{}/", listener.local_addr().unwrap())), token: Some("sa-token".into()), ..Default::default() };
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = Clients::default().get(&config, Duration::from_secs(5)).await.unwrap();
+        client.rollout_restart("fks", "statefulset/postgres").await.unwrap();
+        assert_eq!(client.delete_pods("fks", "app=fks-api", 10).await.unwrap(), 3, "not the pod already terminating");
+        let calls = calls.lock().unwrap().clone();
+        assert_eq!(calls, [
+            "patch statefulsets/postgres \"application/merge-patch+json\" stamped=true",
+            "list fks app=fks-api Some(\"Bearer sa-token\")",
+            "delete app=fks-api grace=10",
+        ]);
+    }
+}
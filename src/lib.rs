@@ -1 +1 @@
-pub mod config; pub mod compose; pub mod metrics; pub mod models; pub mod monitor; pub mod websocket; pub mod health; pub mod auth;
+pub mod clock; pub mod config; pub mod confirmation; pub mod config_check; pub mod compression; pub mod compose; pub mod compose_import; pub mod compose_queue; pub mod discovery; pub mod docker_events; pub mod drain; pub mod docker_hosts; pub mod docker_stats; pub mod escalation; pub mod etag; pub mod events; pub mod federation; pub mod graphql; pub mod metrics; pub mod models; pub mod openapi; pub mod orchestrator; pub mod outbound; pub mod monitor; pub mod websocket; pub mod healing; pub mod health_score; pub mod health; pub mod hooks; pub mod image_history; pub mod image_updates; pub mod incident; pub mod kubernetes; pub mod logs; pub mod auth; pub mod alert_history; pub mod alert_manager; pub mod alertmanager_webhook; pub mod alerts; pub mod archive; pub mod auto_restart; pub mod automation; pub mod build_context; pub mod probe; pub mod problem; pub mod promql; pub mod redact; pub mod registry_auth; pub mod readiness; pub mod reload; pub mod reminder; pub mod restart_backoff; pub mod restart_limit; pub mod service_query; pub mod remote_config; pub mod replay; pub mod schedule; pub mod secrets; pub mod rules; pub mod silence; pub mod systemd; pub mod upgrade; pub mod uptime; pub mod validation; pub mod watchdog; pub mod webhooks;
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use bollard::container::{LogOutput, LogsOptions};
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+/// Lines returned when the request does not say how many
+pub const DEFAULT_TAIL: u32 = 200;
+/// Upper bound on `tail`, so one request cannot pull a container's whole history
+pub const MAX_TAIL: u32 = 10_000;
+
+/// One line of container output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogLine {
+    /// `stdout` or `stderr`
+    pub stream: &'static str,
+    pub line: String,
+}
+
+/// The last `tail` lines of `container`'s output on `docker`, then, with `follow`, new lines as they are
+/// written until the container stops or the stream is dropped. Fails up front if the container does not exist.
+pub async fn container_logs(docker: bollard::Docker, container: &str, tail: u32, follow: bool) -> Result<impl Stream<Item = Result<LogLine>> + Send + 'static> {
+    docker.inspect_container(container, None).await?;
+    let options = LogsOptions::<String> { follow, stdout: true, stderr: true, tail: tail.min(MAX_TAIL).to_string(), ..Default::default() };
+    let output = docker.logs(container, Some(options));
+    Ok(output.flat_map(|chunk| {
+        let lines: Vec<Result<LogLine>> = match chunk {
+            Ok(chunk) => split(chunk).into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e.into())],
+        };
+        futures::stream::iter(lines)
+    }))
+}
+
+/// A chunk can hold several lines; TTY containers report everything as `Console` output on stdout.
+fn split(chunk: LogOutput) -> Vec<LogLine> {
+    let (stream, message) = match chunk {
+        LogOutput::StdErr { message } => ("stderr", message),
+        LogOutput::StdOut { message } | LogOutput::Console { message } => ("stdout", message),
+        LogOutput::StdIn { .. } => return Vec::new(),
+    };
+    let text = String::from_utf8_lossy(&message);
+    text.strip_suffix('\n').unwrap_or(&text).split('\n').map(|line| LogLine { stream, line: line.trim_end_matches('\r').to_string() }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_split_into_lines_by_stream() {
+        let lines = split(LogOutput::StdErr { message: "panic: boom\r\n  at main.rs:3\n".into() });
+        assert_eq!(lines, vec![
+            LogLine { stream: "stderr", line: "panic: boom".into() },
+            LogLine { stream: "stderr", line: "  at main.rs:3".into() },
+        ]);
+        assert_eq!(split(LogOutput::Console { message: "partial".into() }), vec![LogLine { stream: "stdout", line: "partial".into() }]);
+        assert!(split(LogOutput::StdIn { message: "typed".into() }).is_empty());
+    }
+}
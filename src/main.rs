@@ -16,20 +16,80 @@ use opentelemetry::global as otel_global;
 use tower::ServiceBuilder;
 use axum::http::Request as HttpRequest;
 use std::time::Instant;
+use openapi::SecurityAddon;
 use tracing_subscriber::prelude::*;
 
+mod clock;
 mod config;
+mod confirmation;
+mod config_check;
+mod healing;
 mod health;
+mod health_score;
+mod hooks;
+mod image_history;
+mod image_updates;
+mod incident;
+mod kubernetes;
+mod logs;
 mod models;
+mod openapi;
 mod monitor;
+mod orchestrator;
+mod outbound;
 mod websocket;
 mod metrics;
 mod compose;
+mod compose_import;
+mod compose_queue;
+mod compression;
+mod discovery;
+mod docker_events;
+mod drain;
+mod docker_hosts;
+mod docker_stats;
+mod escalation;
+mod etag;
+mod events;
+mod federation;
+mod graphql;
 mod auth;
+mod alert_history;
+mod alert_manager;
+mod alertmanager_webhook;
+mod alerts;
+mod archive;
+mod auto_restart;
+mod automation;
+mod build_context;
+mod probe;
+mod problem;
+mod uptime;
+mod promql;
+mod redact;
+mod registry_auth;
+mod readiness;
+mod reload;
+mod reminder;
+mod restart_backoff;
+mod restart_limit;
+mod service_query;
+mod remote_config;
+mod replay;
+mod schedule;
+mod secrets;
+mod rules;
+mod silence;
+mod systemd;
+mod upgrade;
+mod validation;
+mod watchdog;
+mod webhooks;
 
 use crate::config::Config;
 use crate::monitor::ServiceMonitor;
 use crate::compose::{ComposeRequest};
+use crate::problem::{ErrorCode, Problem};
 
 #[derive(Parser)]
 #[command(name = "fks_master")]
@@ -44,20 +104,79 @@ struct Cli {
     /// Port to listen on (serve mode)
     #[arg(long, default_value = "9090")] 
     port: u16,
-    /// Path to monitor configuration file (serve mode)
+    /// Path to monitor configuration file (TOML, YAML or JSON), a directory of config files, or an
+    /// http(s)://, s3:// or consul:// URL to fetch it from (serve mode)
     #[arg(long, default_value = "config/monitor.toml")] 
     config: String,
+    /// How often a remote --config URL is fetched again
+    #[arg(long, default_value_t = remote_config::DEFAULT_REFRESH_SECONDS)]
+    config_refresh_seconds: u64,
+    /// Monitor the services of this compose file (overrides `[discovery] compose_file`)
+    #[arg(long)]
+    compose_file: Option<String>,
+    /// Reload the configuration file whenever it changes (SIGHUP always triggers a reload)
+    #[arg(long)]
+    watch_config: bool,
+    /// Start with automatic actions frozen (also `FKS_AUTOMATION_FREEZE=1`); lift with `POST /api/automation/resume`
+    #[arg(long)]
+    freeze_automation: bool,
+    /// Replay a recorded events/status JSONL file instead of probing services (alerts run dry)
+    #[arg(long)]
+    replay: Option<String>,
+    /// Playback speed multiplier for --replay (2.0 = twice as fast)
+    #[arg(long, default_value_t = 1.0)]
+    replay_speed: f64,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Run docker compose lifecycle commands
     Compose(ComposeCmd),
+    /// Monitor configuration helpers
+    #[command(subcommand)]
+    Config(ConfigCmd),
+    /// Check a config without starting the monitor; exits with 1 if it has errors
+    ValidateConfig {
+        /// Config file or directory to check
+        #[arg(long, default_value = "config/monitor.toml")]
+        config: String,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        /// Do not check that referenced Docker containers exist
+        #[arg(long)]
+        skip_containers: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCmd {
+    /// Generate a starting monitor.toml from a docker-compose file
+    Import {
+        /// Compose file to read services from
+        #[arg(long)]
+        compose: String,
+        /// Compose project name used for container names (default: the compose file's directory)
+        #[arg(long, short = 'p')]
+        project: Option<String>,
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<String>,
+        /// Overwrite --output if it exists
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the JSON Schema of the config format, for editors and CI validation
+    Schema {
+        /// Write to this file instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<String>,
+    },
 }
 
 #[derive(ClapArgs)]
 struct ComposeCmd {
-    /// Compose action (build, pull, up, start, stop, restart, push, ps, logs)
+    /// Compose action (build, pull, up, down, start, stop, restart, push, ps, logs, exec)
     #[arg(value_enum)]
     action: compose::ComposeAction,
     /// Optional service names (empty = all services defined in compose file)
@@ -77,6 +196,27 @@ struct ComposeCmd {
     /// Tail lines for logs action
     #[arg(long)]
     tail: Option<u32>,
+    #[command(flatten)]
+    down: compose::DownOptions,
+    /// Env file for variable interpolation in the compose file
+    #[arg(long)]
+    env_file: Option<String>,
+    /// Variable for the compose file, overriding the env file (repeatable)
+    #[arg(long = "env", short = 'e', value_name = "KEY=VALUE", value_parser = parse_env_pair)]
+    env: Vec<(String, String)>,
+    /// Build without cached layers
+    #[arg(long)]
+    no_cache: bool,
+    /// Build argument, overriding the compose file's (repeatable)
+    #[arg(long = "build-arg", value_name = "KEY=VALUE", value_parser = parse_env_pair)]
+    build_args: Vec<(String, String)>,
+    /// Command for exec, after `--` (e.g. `compose exec fks_api -- alembic upgrade head`)
+    #[arg(last = true)]
+    command: Vec<String>,
+}
+
+fn parse_env_pair(pair: &str) -> Result<(String, String), String> {
+    pair.split_once('=').map(|(k, v)| (k.to_string(), v.to_string())).ok_or_else(|| format!("`{pair}` is not KEY=VALUE"))
 }
 
 #[tokio::main]
@@ -90,22 +230,70 @@ async fn main() -> anyhow::Result<()> {
         match cmd {
             Commands::Compose(c) => {
                 // Just run compose action then exit
-                let code = compose::run_compose(
-                    &c.file,
-                    c.project.as_deref(),
-                    c.action,
-                    &c.services,
-                    c.detach,
-                    c.json,
-                    c.tail,
-                )?;
+                let req = ComposeRequest {
+                    action: c.action, services: c.services, file: c.file, project: c.project, detach: c.detach, tail: c.tail,
+                    stream: false, dry_run: false, down: c.down, env_file: c.env_file, env: c.env.into_iter().collect(), command: c.command, no_cache: c.no_cache, build_args: c.build_args.into_iter().collect(), target: None, ready_timeout_seconds: None, ready_endpoints: Default::default(), registries: vec![],
+                };
+                let code = compose::run_compose(&req, c.json)?;
                 std::process::exit(code);
             }
+            Commands::Config(ConfigCmd::Import { compose, project, output, force }) => {
+                let rendered = compose_import::import_compose(std::path::Path::new(&compose), project.as_deref())?;
+                match output {
+                    Some(path) => {
+                        if std::path::Path::new(&path).exists() && !force {
+                            anyhow::bail!("{path} already exists (use --force to overwrite)");
+                        }
+                        std::fs::write(&path, rendered)?;
+                        info!("📝 Wrote {path}");
+                    }
+                    None => print!("{rendered}"),
+                }
+                return Ok(());
+            }
+            Commands::Config(ConfigCmd::Schema { output }) => {
+                let rendered = serde_json::to_string_pretty(&config::json_schema())? + "\n";
+                match output {
+                    Some(path) => {
+                        std::fs::write(&path, rendered)?;
+                        info!("📝 Wrote {path}");
+                    }
+                    None => print!("{rendered}"),
+                }
+                return Ok(());
+            }
+            Commands::ValidateConfig { config, json, skip_containers } => {
+                let loaded = match remote_config::Source::parse(&config)? {
+                    Some(source) => remote_config::RemoteConfig::new(source)?.fetch().await.map(|c| c.unwrap_or_default()),
+                    None => Config::load_strict(std::path::Path::new(&config)),
+                };
+                let report = match loaded {
+                    Ok(loaded) => config_check::validate(&loaded, !skip_containers).await,
+                    Err(e) => config_check::Report::unreadable(&e),
+                };
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    for issue in &report.issues {
+                        let level = match issue.level { config_check::Level::Error => "error", config_check::Level::Warning => "warning" };
+                        let at = if issue.path.is_empty() { String::new() } else { format!("{}: ", issue.path) };
+                        println!("{level}: {at}{}", issue.message);
+                    }
+                    let errors = report.issues.iter().filter(|i| i.level == config_check::Level::Error).count();
+                    println!("{config}: {} services, {errors} errors, {} warnings", report.services, report.issues.len() - errors);
+                }
+                std::process::exit(if report.valid { 0 } else { 1 });
+            }
         }
     }
 
     // Default: serve monitoring API
-    let config = Config::load(&cli.config).await?;
+    let mut remote = remote_config::Source::parse(&cli.config)?.map(remote_config::RemoteConfig::new).transpose()?;
+    let config = match remote.as_mut() {
+        Some(remote) => remote.fetch().await?.unwrap_or_default(),
+        None => Config::load(&cli.config).await?,
+    };
+    let is_remote = remote.is_some();
     
     info!("🚀 Starting FKS Service Monitor");
     info!("📊 Monitoring {} services", config.services.len());
@@ -114,13 +302,100 @@ async fn main() -> anyhow::Result<()> {
     metrics::start_uptime_tracking();
     info!("📈 Prometheus metrics initialized");
 
-    // Initialize service monitor
-    let monitor = ServiceMonitor::new(config.clone()).await?;
-    let monitor_handle = monitor.start().await?;
+    // Initialize service monitor; in replay mode the recorded timeline replaces live probing
+    let mut monitor = ServiceMonitor::new(config.clone()).await?;
+    // Started by a predecessor during an in-place upgrade: pick up its in-memory state
+    if let Ok(path) = std::env::var(upgrade::STATE_SNAPSHOT_ENV) {
+        std::env::remove_var(upgrade::STATE_SNAPSHOT_ENV);
+        match tokio::fs::read(&path).await.map_err(anyhow::Error::from).and_then(|b| Ok(serde_json::from_slice::<monitor::MonitorSnapshot>(&b)?)) {
+            Ok(snapshot) => {
+                info!(path = %path, services = snapshot.services.len(), "♻️ Restored monitor state from previous process");
+                monitor = monitor.restore(snapshot);
+            }
+            Err(e) => tracing::warn!(path = %path, error = ?e, "failed to restore monitor snapshot; starting fresh"),
+        }
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+    let replay_records = match &cli.replay {
+        Some(path) => Some(replay::load(path).await?),
+        None => None,
+    };
+    let monitor_handle = if replay_records.is_some() { monitor.handle() } else { monitor.start().await? };
+    if cli.freeze_automation || automation::freeze_requested_by_env() {
+        monitor_handle.freeze_automation(automation::FreezeRequest { reason: Some("frozen at startup".into()), ..Default::default() });
+    }
+
+    // Forward alertable events to configured notification channels
+    let alert_dispatcher = std::sync::Arc::new(
+        alerts::AlertDispatcher::new(config.clone())
+            .with_shared_config(monitor_handle.shared_config())
+            .with_dry_run(replay_records.is_some())
+            .with_silences(monitor_handle.silences().clone()),
+    );
+    alert_dispatcher.clone().spawn(monitor_handle.subscribe_events());
+    let compose_queue = compose_queue::ComposeQueue::default();
+    let schedules = schedule::Schedules::default();
+    let webhooks = webhooks::WebhookRegistry::open(config.webhooks.store_path.as_ref().map(std::path::PathBuf::from));
+    if let Some(records) = replay_records {
+        tokio::spawn(replay::run(records, cli.replay_speed, monitor_handle.clone()));
+    } else {
+        alerts::spawn_daily_digest(&config, monitor_handle.clone());
+        archive::spawn(monitor_handle.clone());
+        discovery::spawn(monitor_handle.clone(), cli.compose_file.as_ref().map(std::path::PathBuf::from));
+        image_updates::spawn(monitor_handle.clone());
+        hooks::spawn(monitor_handle.clone(), compose_queue.clone());
+        webhooks::spawn(webhooks.clone(), monitor_handle.clone());
+        schedule::spawn(monitor_handle.clone(), schedules.clone());
+        match remote {
+            Some(remote) => {
+                tokio::spawn(remote_config::watch(remote, monitor_handle.clone(), std::time::Duration::from_secs(cli.config_refresh_seconds.max(1))));
+            }
+            None => {
+                tokio::spawn(reload::watch(
+                    std::path::PathBuf::from(&cli.config),
+                    monitor_handle.clone(),
+                    cli.watch_config.then_some(reload::POLL_INTERVAL),
+                ));
+            }
+        }
+    }
+
+    monitor_handle.watchdog().clone().spawn(monitor_handle.clone());
+
+    let endpoint_validation = std::sync::Arc::new(tokio::sync::RwLock::new(None));
+    if config.monitoring.validate_endpoints_on_startup && cli.replay.is_none() {
+        let (services, slot) = (config.services.clone(), endpoint_validation.clone());
+        let timeout = std::time::Duration::from_secs(config.monitoring.timeout_seconds);
+        tokio::spawn(async move {
+            let report = validation::validate_endpoints(&services, timeout).await;
+            validation::log_report(&report);
+            *slot.write().await = Some(report);
+        });
+    }
+
+    let api_key = secrets::env_var("FKS_MONITOR_API_KEY")?;
+    // Checked on every request; fail at startup rather than reject everyone later
+    secrets::env_var("FKS_WS_JWT_SECRET")?;
 
-    let api_key = std::env::var("FKS_MONITOR_API_KEY").ok();
+    let ws_hub = websocket::WsHub::start(monitor_handle.clone());
+    let confirmations = confirmation::Confirmations::default();
 
-    let state = AppState { monitor: monitor_handle.clone(), api_key };
+    // A remote config cannot be written back to
+    let config_path = (cli.replay.is_none() && !is_remote).then(|| std::path::PathBuf::from(&cli.config));
+    let state = AppState {
+        monitor: monitor_handle.clone(),
+        api_key,
+        alerts: alert_dispatcher.clone(),
+        ws_hub: ws_hub.clone(),
+        graphql: graphql::schema(monitor_handle.clone()),
+        endpoint_validation,
+        config_path,
+        service_changes: Default::default(),
+        compose_queue,
+        schedules,
+        confirmations,
+        webhooks,
+    };
 
     // Allow environment variable overrides for host/port (backward compatible with CLI flags)
     let env_host = std::env::var("FKS_MASTER_HOST").ok();
@@ -133,13 +408,59 @@ async fn main() -> anyhow::Result<()> {
         .route("/", get(dashboard_handler))
         .route("/health", get(health_handler))
     .route("/health/aggregate", get(aggregate_health_handler))
-        .route("/metrics", get(metrics_handler))
-        .route("/api/services", get(get_services_handler))
+        .route("/health/ready", get(ready_handler))
+        .route("/healthz", get(liveness_handler))
+        .route("/readyz", get(readiness_handler))
+        .route("/metrics", get(metrics_handler).layer(compression::layer(&monitor_handle, compression::Route::Metrics)))
+        .route("/metrics/federated", get(federated_metrics_handler).layer(compression::layer(&monitor_handle, compression::Route::Metrics)))
+        .route("/api/openapi.json", get(openapi_handler))
+        .route("/api/docs", get(swagger_ui_handler))
+        .route("/api/graphql", get(graphiql_handler).post(graphql_handler))
+        .route("/api/graphql/ws", get(graphql_ws_handler))
+        .route("/api/services", get(get_services_handler).layer(compression::layer(&monitor_handle, compression::Route::Services)).post(create_service_handler))
+        .route("/api/groups", get(groups_handler))
+        .route("/api/services/{service_id}", get(service_detail_handler).put(update_service_handler).delete(delete_service_handler))
     .route("/api/services/{service_id}/health", get(get_service_health_handler))
+    .route("/api/services/{service_id}/history", get(service_history_handler))
+    .route("/api/services/{service_id}/monitoring/pause", post(pause_monitoring_handler))
+    .route("/api/services/{service_id}/monitoring/resume", post(resume_monitoring_handler))
     .route("/api/services/{service_id}/restart", post(restart_service_handler))
+    .route("/api/services/{service_id}/restart/reset", post(reset_restart_backoff_handler))
+    .route("/api/services/{service_id}/rollback", post(rollback_service_handler))
+    .route("/api/services/{service_id}/logs", get(service_logs_handler))
+    .route("/api/services/{service_id}/{action}", post(container_action_handler))
+        .route("/api/heartbeat/{service_id}", post(heartbeat_handler))
+        .route("/api/events", get(events_handler).layer(compression::layer(&monitor_handle, compression::Route::Events)))
+        .route("/api/reports/uptime", get(uptime_report_handler))
+        .route("/api/events/stream", get(event_stream_handler))
+        .route("/api/alerts", get(alerts_overview_handler))
+        .route("/api/alerts/history", get(alert_history_handler))
+        .route("/api/alerts/dead-letters", get(dead_letters_handler))
+        .route("/api/alerts/redeliver", post(redeliver_alerts_handler))
+        .route("/api/alertmanager/webhook", post(alertmanager_webhook_handler))
+        .route("/api/silences", get(list_silences_handler).post(create_silence_handler))
+        .route("/api/silences/{silence_id}", axum::routing::delete(delete_silence_handler))
+        .route("/api/webhooks", get(list_webhooks_handler).post(create_webhook_handler))
+        .route("/api/webhooks/{subscription_id}", get(webhook_status_handler).delete(delete_webhook_handler))
+        .route("/api/maintenance", get(maintenance_status_handler).post(start_maintenance_handler).delete(end_maintenance_handler))
+        .route("/api/incidents", get(list_incidents_handler))
+        .route("/api/incidents/{incident_id}/ack", post(acknowledge_incident_handler))
+        .route("/api/incidents/{incident_id}/resolve", post(resolve_incident_handler))
+        .route("/api/automation", get(automation_status_handler))
+        .route("/api/schedules", get(schedules_handler))
+        .route("/api/automation/freeze", post(freeze_automation_handler))
+        .route("/api/automation/resume", post(resume_automation_handler))
+        .route("/api/config", get(effective_config_handler))
+        .route("/api/config/validation", get(endpoint_validation_handler))
+        .route("/api/config/validate", post(validate_config_handler))
         .route("/api/metrics", get(get_metrics_handler))
+        .route("/api/promql", get(promql_handler))
         .route("/api/compose", post(compose_handler))
+        .route("/api/compose/queue", get(compose_queue_handler))
+        .route("/api/containers", get(list_containers_handler))
         .route("/ws", get(websocket_handler))
+        .route("/api/ws/clients", get(ws_clients_handler))
+        .route("/api/ws/clients/{client_id}", axum::routing::delete(disconnect_ws_client_handler))
     .layer(
         ServiceBuilder::new()
             .layer(CorsLayer::permissive())
@@ -148,24 +469,159 @@ async fn main() -> anyhow::Result<()> {
         .with_state(state);
 
     let addr: SocketAddr = format!("{}:{}", bind_host, bind_port).parse()?;
-    let listener = TcpListener::bind(&addr).await?;
-    
+    // Reuse the socket handed down by an upgrading predecessor (or systemd) so no connection is refused
+    let listener = match upgrade::inherited_listener()? {
+        Some(inherited) => TcpListener::from_std(inherited)?,
+        None => TcpListener::bind(&addr).await?,
+    };
+    let addr = listener.local_addr()?;
+    #[cfg(unix)]
+    let listen_fd = std::os::fd::AsRawFd::as_raw_fd(&listener);
+
     info!("🌐 FKS Master listening on http://{}", addr);
     info!("📈 Dashboard: http://{}", addr);
     info!("� Prometheus metrics: http://{}/metrics", addr);
     info!("�🔗 WebSocket endpoint: ws://{}/ws", addr);
 
+    let upgraded = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let shutdown = {
+        let upgraded = upgraded.clone();
+        let (monitor_handle, ws_hub) = (monitor_handle.clone(), ws_hub.clone());
+        async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown_signal() => {
+                        ws_hub.announce_shutdown(websocket::ShutdownReason::Shutdown, &monitor_handle.config().websocket);
+                        return;
+                    }
+                    _ = upgrade::upgrade_requested() => {}
+                }
+                // Hand over before returning: the listener is closed once graceful shutdown begins
+                #[cfg(unix)]
+                match hand_over(&monitor_handle, listen_fd).await {
+                    Ok(pid) => {
+                        info!(pid, "♻️ Upgrade: successor started; no longer accepting connections");
+                        alert_dispatcher.pause();
+                        upgraded.store(true, std::sync::atomic::Ordering::Relaxed);
+                        return;
+                    }
+                    Err(e) => tracing::error!(error = ?e, "♻️ Upgrade failed; continuing to serve"),
+                }
+            }
+        }
+    };
+
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown)
         .await?;
 
+    if upgraded.load(std::sync::atomic::Ordering::Relaxed) {
+        let deadline = std::env::var("FKS_UPGRADE_DRAIN_SECONDS").ok().and_then(|s| s.parse().ok()).unwrap_or(60u64);
+        drain_websockets(std::time::Duration::from_secs(deadline)).await;
+        ws_hub.announce_shutdown(websocket::ShutdownReason::Upgrade, &monitor_handle.config().websocket);
+    }
+    // Give clients a moment to receive the `server_shutdown` frame before the process exits
+    drain_websockets(WS_CLOSE_GRACE).await;
     Ok(())
 }
 
+/// Write the monitor snapshot and start the new binary on the shared listening socket.
+#[cfg(unix)]
+async fn hand_over(monitor: &monitor::MonitorHandle, listen_fd: std::os::fd::RawFd) -> anyhow::Result<u32> {
+    let snapshot = upgrade::snapshot_path();
+    tokio::fs::write(&snapshot, serde_json::to_vec(&monitor.snapshot())?).await?;
+    upgrade::spawn_successor(listen_fd, &snapshot)
+}
+
+/// How long to wait for WebSocket clients to take their `server_shutdown` frame and close
+const WS_CLOSE_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Wait until every WebSocket client has disconnected or `deadline` passes. After an upgrade this
+/// keeps existing clients on this process for a while instead of cutting them off; they reconnect
+/// to the successor when they close (or are told to at the deadline).
+async fn drain_websockets(deadline: std::time::Duration) {
+    let started = Instant::now();
+    while metrics::active_websocket_connections() > 0 && started.elapsed() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let remaining = metrics::active_websocket_connections();
+    if remaining > 0 { info!(remaining, "closing remaining WebSocket connections"); }
+}
+
 async fn dashboard_handler() -> Html<&'static str> {
     Html(include_str!("../templates/dashboard.html"))
 }
 
+/// OpenAPI document of the services, health, restart, compose, events and metrics endpoints.
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    info(title = "FKS Master API", description = "Service monitoring and orchestration. Write calls take the `x-api-key` header or a JWT bearer token when auth is configured."),
+    paths(
+        health_handler, aggregate_health_handler, ready_handler, liveness_handler, readiness_handler,
+        get_services_handler, create_service_handler, update_service_handler, delete_service_handler,
+        service_detail_handler, get_service_health_handler, service_history_handler, uptime_report_handler, pause_monitoring_handler, resume_monitoring_handler,
+        restart_service_handler, reset_restart_backoff_handler, rollback_service_handler, service_logs_handler, container_action_handler,
+        compose_handler, compose_queue_handler, list_containers_handler, events_handler, event_stream_handler, metrics_handler, federated_metrics_handler, get_metrics_handler,
+    ),
+    // Schemas only query parameters refer to are not collected on their own
+    components(schemas(openapi::ProblemBody, models::RestartMode)),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "health", description = "Liveness and readiness of the monitor and the fleet"),
+        (name = "services", description = "Registered services and their status"),
+        (name = "restart", description = "Restarts, with backoff, rate limit and verification"),
+        (name = "compose", description = "docker compose actions"),
+        (name = "events", description = "Monitor event history"),
+        (name = "metrics", description = "Prometheus and summary metrics"),
+    )
+)]
+struct ApiDoc;
+
+async fn openapi_handler() -> Json<utoipa::openapi::OpenApi> {
+    Json(<ApiDoc as utoipa::OpenApi>::openapi())
+}
+
+/// Swagger UI over `/api/openapi.json`, unless `openapi.swagger_ui` is off.
+async fn swagger_ui_handler(State(state): State<AppState>) -> Result<axum::response::Html<String>, Problem> {
+    let config = state.monitor.config();
+    if !config.openapi.swagger_ui { return Err(Problem::new(ErrorCode::NotFound, "Swagger UI is disabled (openapi.swagger_ui = false)")); }
+    Ok(axum::response::Html(openapi::swagger_ui_page("/api/openapi.json", &config.openapi.swagger_ui_assets)))
+}
+
+/// GraphQL queries over services, health, metrics and events. Anonymous callers get the redacted view.
+async fn graphql_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let caller = graphql::Caller { authorized: is_authorized(&state, &headers) };
+    Json(state.graphql.execute(request.data(caller)).await)
+}
+
+/// GraphiQL over `/api/graphql`, unless `graphql.graphiql` is off.
+async fn graphiql_handler(State(state): State<AppState>) -> Result<axum::response::Html<String>, Problem> {
+    if !state.monitor.config().graphql.graphiql { return Err(Problem::new(ErrorCode::NotFound, "GraphiQL is disabled (graphql.graphiql = false)")); }
+    let page = async_graphql::http::GraphiQLSource::build().endpoint("/api/graphql").subscription_endpoint("/api/graphql/ws").title("FKS Master GraphQL").finish();
+    Ok(axum::response::Html(page))
+}
+
+/// GraphQL subscriptions over `graphql-transport-ws` or the older `graphql-ws` protocol.
+async fn graphql_ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Response, Problem> {
+    let protocol = headers.get(axum::http::header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').find_map(|p| p.trim().parse::<async_graphql::http::WebSocketProtocols>().ok()))
+        .ok_or_else(|| Problem::new(ErrorCode::InvalidRequest, "Sec-WebSocket-Protocol must offer graphql-transport-ws or graphql-ws"))?;
+    let caller = graphql::Caller { authorized: is_authorized(&state, &headers) };
+    let schema = state.graphql.clone();
+    Ok(ws.protocols(async_graphql::http::ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| graphql::serve_websocket(socket, schema, protocol, caller)))
+}
+
+#[utoipa::path(get, path = "/health", tag = "health", responses((status = 200, description = "The monitor itself is up")))]
 async fn health_handler() -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "status": "healthy",
@@ -174,43 +630,114 @@ async fn health_handler() -> Json<serde_json::Value> {
     }))
 }
 
-async fn aggregate_health_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+/// Supports `If-None-Match`: the ETag follows everything but `lastUpdate`.
+#[utoipa::path(get, path = "/health/aggregate", tag = "health", responses((status = 200, description = "Health counts overall, for external targets and per group, with every service's status"), (status = 304, description = "Unchanged since the ETag in `If-None-Match`")))]
+async fn aggregate_health_handler(State(state): State<AppState>, headers: axum::http::HeaderMap) -> Response {
+    etag::conditional(&headers, aggregate_health(&state).await, &["lastUpdate"])
+}
+
+async fn aggregate_health(state: &AppState) -> serde_json::Value {
     use serde_json::json;
     let services = state.monitor.get_all_services().await;
-    let mut healthy = 0usize;
-    let mut degraded = 0usize;
-    let mut unhealthy = 0usize;
-    let mut unknown = 0usize;
-    for s in &services { match s.status { crate::models::HealthStatus::Healthy => healthy+=1, crate::models::HealthStatus::Degraded => degraded+=1, crate::models::HealthStatus::Unhealthy => unhealthy+=1, crate::models::HealthStatus::Unknown => unknown+=1 } }
-    let overall_status = if unhealthy>0 { "critical" } else if degraded>0 || unknown>0 { "degraded" } else { "healthy" };
-    Json(json!({
-        "overallStatus": overall_status,
-        "totalServices": services.len(),
-        "healthyServices": healthy,
-        "warningServices": degraded, // map degraded -> warning
-        "errorServices": unhealthy,
-        "offlineServices": unknown,
+    let config = state.monitor.config();
+    let scores = health_score::Scores::compute(&config.health_score, &config.services, &services);
+    // Internal services drive overallStatus; third-party targets are reported separately
+    let (external, internal): (Vec<_>, Vec<_>) = services.iter().partition(|s| s.service_type.is_external());
+    let internal_counts = count_statuses(&internal);
+    let external_counts = count_statuses(&external);
+    let maintenance = state.monitor.maintenance();
+    json!({
+        "overallStatus": if maintenance.is_some() { "maintenance" } else { internal_counts.overall() },
+        "healthScore": scores.overall(internal.iter().copied()),
+        "maintenance": maintenance,
+        "totalServices": internal.len(),
+        "healthyServices": internal_counts.healthy,
+        "warningServices": internal_counts.degraded, // map degraded -> warning
+        "errorServices": internal_counts.unhealthy,
+        "offlineServices": internal_counts.unknown,
+        "pausedServices": internal_counts.paused,
+        "external": {
+            "overallStatus": external_counts.overall(),
+            "healthScore": scores.overall(external.iter().copied()),
+            "totalTargets": external.len(),
+            "healthyTargets": external_counts.healthy,
+            "warningTargets": external_counts.degraded,
+            "errorTargets": external_counts.unhealthy,
+            "offlineTargets": external_counts.unknown,
+            "pausedTargets": external_counts.paused,
+        },
+        "groups": group_summaries(&services, &scores),
+        "automation": automation_status(&state.monitor),
         "lastUpdate": chrono::Utc::now(),
         "services": services
             .into_iter()
             .map(|s| {
                 // Provide a lightweight frontend-oriented mapping (keep original enum serialization too)
-                let mapped = match s.status { crate::models::HealthStatus::Healthy => "healthy", crate::models::HealthStatus::Degraded => "warning", crate::models::HealthStatus::Unhealthy => "error", crate::models::HealthStatus::Unknown => "offline" };
+                let score = scores.get(&s.id);
+                let mapped = match s.status { crate::models::HealthStatus::Healthy => "healthy", crate::models::HealthStatus::Degraded => "warning", crate::models::HealthStatus::Unhealthy => "error", crate::models::HealthStatus::Unknown => "offline", crate::models::HealthStatus::Paused => "paused" };
                 json!({
                     "id": s.id,
                     "name": s.name,
                     "status": mapped,
                     "rawStatus": format!("{:?}", s.status),
+                    "healthScore": score.map(|score| health_score::percent(score.effective)),
+                    "impactedBy": score.map(|score| score.impacted_by.clone()).unwrap_or_default(),
                     "lastCheck": s.last_check,
                     "responseTimeMs": s.response_time_ms,
-                    "critical": s.critical
+                    "critical": s.critical,
+                    "group": s.group,
+                    "tags": s.tags,
+                    "metadata": s.metadata,
+                    "external": s.service_type.is_external()
                 })
             })
             .collect::<Vec<_>>()
-    }))
+    })
+}
+
+#[derive(Default)]
+struct StatusCounts { healthy: usize, degraded: usize, unhealthy: usize, unknown: usize, paused: usize }
+
+impl StatusCounts {
+    /// Paused services count toward nothing but `pausedServices`
+    fn overall(&self) -> &'static str {
+        if self.unhealthy>0 { "critical" } else if self.degraded>0 || self.unknown>0 { "degraded" } else { "healthy" }
+    }
+}
+
+fn count_statuses(services: &[&models::ServiceStatus]) -> StatusCounts {
+    let mut c = StatusCounts::default();
+    for s in services { match s.status { crate::models::HealthStatus::Healthy => c.healthy+=1, crate::models::HealthStatus::Degraded => c.degraded+=1, crate::models::HealthStatus::Unhealthy => c.unhealthy+=1, crate::models::HealthStatus::Unknown => c.unknown+=1, crate::models::HealthStatus::Paused => c.paused+=1 } }
+    c
+}
+
+/// Readiness from the self watchdog: 503 while the monitoring loop, metrics loop or event bus is stalled.
+#[utoipa::path(get, path = "/health/ready", tag = "health", responses((status = 200, body = watchdog::WatchdogReport), (status = 503, description = "A monitor loop is stalled", body = watchdog::WatchdogReport)))]
+async fn ready_handler(State(state): State<AppState>) -> (StatusCode, Json<watchdog::WatchdogReport>) {
+    let report = state.monitor.watchdog().report(state.monitor.now());
+    let code = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (code, Json(report))
+}
+
+/// Liveness: the process is up and serving requests, whatever state the monitoring is in.
+#[utoipa::path(get, path = "/healthz", tag = "health", responses((status = 200, description = "The process is alive")))]
+async fn liveness_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({"status": "alive", "timestamp": chrono::Utc::now()}))
 }
 
-async fn metrics_handler() -> String {
+/// Readiness with a status per component: `starting` until the loops complete their first pass,
+/// `not_ready` once something is broken.
+#[utoipa::path(get, path = "/readyz", tag = "health", responses((status = 200, body = readiness::ReadinessReport), (status = 503, description = "Starting or not ready", body = readiness::ReadinessReport)))]
+async fn readiness_handler(State(state): State<AppState>) -> (StatusCode, Json<readiness::ReadinessReport>) {
+    let report = readiness::check(&state.monitor).await;
+    let code = if report.status == readiness::Readiness::Ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (code, Json(report))
+}
+
+#[utoipa::path(get, path = "/metrics", tag = "metrics", responses((status = 200, description = "Prometheus text exposition", content_type = "text/plain")))]
+async fn metrics_handler(State(state): State<AppState>) -> String {
+    let statuses = state.monitor.get_all_services().await;
+    metrics::set_info_metrics(&state.monitor.config().services, &statuses, &state.monitor.active_incidents().await);
     let encoder = prometheus::TextEncoder::new();
     let metric_families = metrics::PROMETHEUS_REGISTRY.gather();
     encoder.encode_to_string(&metric_families).unwrap_or_else(|e| {
@@ -219,24 +746,278 @@ async fn metrics_handler() -> String {
     })
 }
 
+/// The services' own metrics, scraped now; 404 unless `[federation] enabled`.
+#[utoipa::path(get, path = "/metrics/federated", tag = "metrics", responses((status = 200, description = "Prometheus text exposition of the series selected from each service's `metrics` endpoint", content_type = "text/plain"), (status = 404, description = "Federation is disabled", body = openapi::ProblemBody)))]
+async fn federated_metrics_handler(State(state): State<AppState>) -> Result<String, Problem> {
+    let config = state.monitor.config();
+    if !config.federation.enabled {
+        return Err(Problem::new(ErrorCode::NotFound, "Federation is disabled ([federation] enabled = false)"));
+    }
+    Ok(federation::federate(&config).await)
+}
+
+#[utoipa::path(get, path = "/api/services", tag = "services", params(service_query::ServicesQuery), responses((status = 200, description = "Redacted for read-only callers; only the `fields` asked for", body = Vec<models::ServiceStatus>), (status = 304, description = "Unchanged since the ETag in `If-None-Match`"), (status = 400, description = "Unknown status, type or field", body = openapi::ProblemBody)))]
 async fn get_services_handler(
     State(state): State<AppState>,
-) -> Json<Vec<models::ServiceStatus>> {
-    Json(state.monitor.get_all_services().await)
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<service_query::ServicesQuery>,
+) -> Result<Response, Problem> {
+    let services = list_services(&state, &headers, &q).await?;
+    Ok(etag::conditional(&headers, serde_json::Value::Array(services), &[]))
+}
+
+async fn list_services(state: &AppState, headers: &axum::http::HeaderMap, q: &service_query::ServicesQuery) -> Result<Vec<serde_json::Value>, Problem> {
+    let services = redact_for_caller(state, headers, state.monitor.get_all_services().await);
+    service_query::apply(services, q).map_err(|e| Problem::new(ErrorCode::InvalidRequest, e.to_string()))
+}
+
+/// Group name used for services without a `group`
+const UNGROUPED: &str = "ungrouped";
+
+/// Health counts per service group, ordered by group name.
+fn group_summaries(services: &[models::ServiceStatus], scores: &health_score::Scores) -> Vec<serde_json::Value> {
+    let mut groups: std::collections::BTreeMap<&str, Vec<&models::ServiceStatus>> = std::collections::BTreeMap::new();
+    for s in services {
+        groups.entry(s.group.as_deref().unwrap_or(UNGROUPED)).or_default().push(s);
+    }
+    groups.into_iter().map(|(group, mut members)| {
+        members.sort_by(|a, b| a.id.cmp(&b.id));
+        let counts = count_statuses(&members);
+        serde_json::json!({
+            "group": group,
+            "overallStatus": counts.overall(),
+            "healthScore": scores.overall(members.iter().copied()),
+            "totalServices": members.len(),
+            "healthyServices": counts.healthy,
+            "warningServices": counts.degraded,
+            "errorServices": counts.unhealthy,
+            "offlineServices": counts.unknown,
+            "pausedServices": counts.paused,
+            "services": members.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(),
+        })
+    }).collect()
+}
+
+async fn groups_handler(State(state): State<AppState>) -> Json<Vec<serde_json::Value>> {
+    let services = state.monitor.get_all_services().await;
+    let config = state.monitor.config();
+    Json(group_summaries(&services, &health_score::Scores::compute(&config.health_score, &config.services, &services)))
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PersistQuery {
+    /// Also write the change back to the config file
+    #[serde(default)]
+    persist: bool,
+}
+
+/// Apply `edit` to the monitored services and switch the monitor to the result. The change is
+/// refused if it introduces config errors; with `persist` the config file is updated first, so a
+/// change that cannot be saved is not applied either. Added and updated services are checked right away.
+async fn change_services(
+    state: &AppState,
+    persist: bool,
+    edit: impl FnOnce(&mut Vec<models::ServiceConfig>) -> Result<(), Problem>,
+) -> Result<reload::ConfigDiff, Problem> {
+    let _serialized = state.service_changes.lock().await;
+    let current = state.monitor.configured();
+    let mut next = current.clone();
+    edit(&mut next.services)?;
+
+    let errors = |config: &Config| config_check::check(config).into_iter().filter(|i| i.level == config_check::Level::Error).collect::<Vec<_>>();
+    let existing = errors(&current);
+    let introduced: Vec<_> = errors(&next).into_iter().filter(|i| !existing.contains(i)).collect();
+    if !introduced.is_empty() {
+        return Err(Problem::new(ErrorCode::InvalidConfig, "The change would make the config invalid").with("issues", introduced));
+    }
+
+    if persist {
+        let Some(path) = state.config_path.clone() else {
+            return Err(Problem::new(ErrorCode::Conflict, "No config file to persist to"));
+        };
+        let services = next.services.clone();
+        let saved = tokio::task::spawn_blocking(move || config::save_services(&path, &services)).await.map_err(anyhow::Error::from).and_then(|r| r);
+        if let Err(e) = saved {
+            return Err(Problem::new(ErrorCode::Conflict, format!("Could not persist the change: {e:#}")));
+        }
+    }
+
+    let diff = state.monitor.reload(next);
+    for service_id in diff.added.iter().chain(&diff.changed) {
+        state.monitor.check_soon(service_id);
+    }
+    Ok(diff)
+}
+
+/// Discovered services follow their source; changing them through the API would not stick.
+fn refuse_discovered(state: &AppState, service_id: &str) -> Result<(), Problem> {
+    let Some(source) = state.monitor.discovery_source(service_id) else { return Ok(()) };
+    let source = if source == discovery::COMPOSE { "the compose file" } else { "Docker labels" };
+    Err(Problem::new(ErrorCode::Conflict, format!("Service {service_id} is discovered from {source}; change it there instead")))
+}
+
+/// Register a service at runtime; it is checked immediately and then on the usual schedule.
+#[utoipa::path(post, path = "/api/services", tag = "services", params(PersistQuery), request_body = models::ServiceConfig, security((), ("api_key" = []), ("bearer" = [])), responses((status = 201, description = "`{\"service\", \"persisted\"}`"), (status = 401, body = openapi::ProblemBody), (status = 409, description = "The id is taken", body = openapi::ProblemBody), (status = 422, description = "The service would make the config invalid; see `issues`", body = openapi::ProblemBody)))]
+async fn create_service_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<PersistQuery>,
+    Json(service): Json<models::ServiceConfig>,
+) -> Result<(StatusCode, Json<serde_json::Value>), Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized service registration attempt");
+        return Err(Problem::unauthorized());
+    }
+    refuse_discovered(&state, &service.id)?;
+    let created = service.clone();
+    change_services(&state, q.persist, |services| {
+        if services.iter().any(|s| s.id == service.id) {
+            return Err(Problem::new(ErrorCode::Conflict, format!("Service {} already exists", service.id)));
+        }
+        services.push(service);
+        Ok(())
+    }).await?;
+    info!(service_id = %created.id, persisted = q.persist, "➕ Service registered");
+    Ok((StatusCode::CREATED, Json(serde_json::json!({"service": created, "persisted": q.persist}))))
+}
+
+/// Replace a service's configuration; the body's `id` must match the path.
+#[utoipa::path(put, path = "/api/services/{service_id}", tag = "services", params(("service_id" = String, Path), PersistQuery), request_body = models::ServiceConfig, security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, description = "`{\"service\", \"persisted\"}`"), (status = 400, body = openapi::ProblemBody), (status = 401, body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody)))]
+async fn update_service_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<PersistQuery>,
+    Json(service): Json<models::ServiceConfig>,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, "unauthorized service update attempt");
+        return Err(Problem::unauthorized());
+    }
+    if service.id != service_id {
+        return Err(Problem::new(ErrorCode::InvalidRequest, "`id` in the body does not match the path"));
+    }
+    refuse_discovered(&state, &service_id)?;
+    let updated = service.clone();
+    change_services(&state, q.persist, |services| {
+        let slot = services.iter_mut().find(|s| s.id == service_id).ok_or_else(|| Problem::service_not_found(&service_id))?;
+        *slot = service;
+        Ok(())
+    }).await?;
+    info!(service_id = %updated.id, persisted = q.persist, "✏️ Service updated");
+    Ok(Json(serde_json::json!({"service": updated, "persisted": q.persist})))
+}
+
+/// Deregister a service: it stops being checked and its state is dropped (event history is kept).
+#[utoipa::path(delete, path = "/api/services/{service_id}", tag = "services", params(("service_id" = String, Path), PersistQuery), security((), ("api_key" = []), ("bearer" = [])), responses((status = 204), (status = 401, body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody)))]
+async fn delete_service_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<PersistQuery>,
+) -> Result<StatusCode, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, "unauthorized service removal attempt");
+        return Err(Problem::unauthorized());
+    }
+    refuse_discovered(&state, &service_id)?;
+    change_services(&state, q.persist, |services| {
+        let before = services.len();
+        services.retain(|s| s.id != service_id);
+        if services.len() == before { Err(Problem::service_not_found(&service_id)) } else { Ok(()) }
+    }).await?;
+    info!(%service_id, persisted = q.persist, "➖ Service deregistered");
+    Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(get, path = "/api/services/{service_id}/health", tag = "services", params(("service_id" = String, Path)), responses((status = 200, description = "Redacted for read-only callers", body = models::ServiceHealth), (status = 404, body = openapi::ProblemBody)))]
 async fn get_service_health_handler(
     axum::extract::Path(service_id): axum::extract::Path<String>,
     State(state): State<AppState>,
-) -> Json<Option<models::ServiceHealth>> {
-    Json(state.monitor.get_service_health(&service_id).await)
+    headers: axum::http::HeaderMap,
+) -> Result<Json<models::ServiceHealth>, Problem> {
+    let health = state.monitor.get_service_health(&service_id).await.ok_or_else(|| Problem::service_not_found(&service_id))?;
+    Ok(Json(redact_for_caller(&state, &headers, health)))
+}
+
+/// Availability, MTTR and outages per service or group over a period, as JSON or CSV.
+#[utoipa::path(get, path = "/api/reports/uptime", tag = "services", params(uptime::ReportQuery), responses((status = 200, description = "JSON, or `text/csv` with `format=csv`", body = uptime::UptimeReport), (status = 400, description = "Invalid period or format", body = openapi::ProblemBody)))]
+async fn uptime_report_handler(
+    State(state): State<AppState>,
+    axum::extract::Query(q): axum::extract::Query<uptime::ReportQuery>,
+) -> Result<Response, Problem> {
+    use axum::response::IntoResponse;
+    let bad_request = |e: String| Problem::new(ErrorCode::InvalidRequest, e);
+    let period = q.period().map_err(|e| bad_request(e.to_string()))?;
+    let report = state.monitor.uptime_report(period, q.group_by.unwrap_or_default());
+    match q.format.as_deref().unwrap_or("json") {
+        "json" => Ok(Json(report).into_response()),
+        "csv" => {
+            let filename = format!("attachment; filename=\"uptime-{}.csv\"", report.until.format("%Y-%m-%d"));
+            Ok(([(axum::http::header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()), (axum::http::header::CONTENT_DISPOSITION, filename)], report.to_csv()).into_response())
+        }
+        other => Err(bad_request(format!("unknown format `{other}`, expected `json` or `csv`"))),
+    }
+}
+
+/// Config, status, health, recent events and restarts, and availability of one service.
+#[utoipa::path(get, path = "/api/services/{service_id}", tag = "services", params(("service_id" = String, Path)), responses((status = 200, description = "Redacted for read-only callers", body = models::ServiceDetail), (status = 404, body = openapi::ProblemBody)))]
+async fn service_detail_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<models::ServiceDetail>, Problem> {
+    let detail = state.monitor.service_detail(&service_id).await.ok_or_else(|| Problem::service_not_found(&service_id))?;
+    Ok(Json(redact_for_caller(&state, &headers, detail)))
+}
+
+#[utoipa::path(get, path = "/api/services/{service_id}/history", tag = "services", params(("service_id" = String, Path), uptime::UptimeQuery), responses((status = 200, body = uptime::ServiceHistory), (status = 404, body = openapi::ProblemBody)))]
+async fn service_history_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    axum::extract::Query(q): axum::extract::Query<uptime::UptimeQuery>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<uptime::ServiceHistory>, Problem> {
+    let history = state.monitor.uptime_history(&service_id, &q).ok_or_else(|| Problem::service_not_found(&service_id))?;
+    Ok(Json(redact_for_caller(&state, &headers, history)))
+}
+
+/// `/pause` is taken by the container action, hence `/monitoring/pause`.
+#[utoipa::path(post, path = "/api/services/{service_id}/monitoring/pause", tag = "services", params(("service_id" = String, Path)), security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, body = models::ServiceStatus), (status = 401, body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody)))]
+async fn pause_monitoring_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<models::ServiceStatus>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, "unauthorized monitoring pause attempt");
+        return Err(Problem::unauthorized());
+    }
+    state.monitor.pause_monitoring(&service_id).map(Json).ok_or_else(|| Problem::service_not_found(&service_id))
+}
+
+#[utoipa::path(post, path = "/api/services/{service_id}/monitoring/resume", tag = "services", params(("service_id" = String, Path)), security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, body = models::ServiceStatus), (status = 401, body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody)))]
+async fn resume_monitoring_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<models::ServiceStatus>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, "unauthorized monitoring resume attempt");
+        return Err(Problem::unauthorized());
+    }
+    state.monitor.resume_monitoring(&service_id).map(Json).ok_or_else(|| Problem::service_not_found(&service_id))
 }
 
+#[utoipa::path(post, path = "/api/services/{service_id}/restart", tag = "restart", params(("service_id" = String, Path), models::RestartOptions), security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, body = models::RestartResult), (status = 202, description = "Armed: repeat with the returned confirmation token"), (status = 401, body = openapi::ProblemBody), (status = 403, description = "Invalid or used confirmation token", body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody), (status = 409, description = "Nothing to restart, or the restart circuit is open", body = openapi::ProblemBody), (status = 429, description = "Rate limited or backing off; see `Retry-After`", body = openapi::ProblemBody), (status = 502, description = "The restart failed; the `result` member has the details", body = openapi::ProblemBody), (status = 503, description = "The Docker daemon does not answer", body = openapi::ProblemBody)))]
 async fn restart_service_handler(
     axum::extract::Path(service_id): axum::extract::Path<String>,
     State(state): State<AppState>,
     headers: axum::http::HeaderMap,
-) -> Json<models::RestartResult> {
+    axum::extract::Query(options): axum::extract::Query<models::RestartOptions>,
+) -> Result<Json<models::RestartResult>, axum::response::Response> {
+    use axum::response::IntoResponse;
     let req_id = get_or_make_request_id(&headers);
     let parent_ctx = extract_traceparent(&headers);
     let span = tracing::info_span!("restart_service", %service_id, %req_id);
@@ -245,118 +1026,1151 @@ async fn restart_service_handler(
     if !is_authorized(&state, &headers) {
         crate::metrics::increment_restart_unauthorized();
         tracing::warn!("unauthorized restart attempt");
-        return Json(models::RestartResult { service_id, success: false, message: "unauthorized".into(), timestamp: chrono::Utc::now() });
+        return Err(Problem::unauthorized().into_response());
     }
-    let result = state.monitor.restart_service(&service_id).await;
+    let config = state.monitor.config();
+    let Some(service) = config.services.iter().find(|s| s.id == service_id) else {
+        return Err(Problem::service_not_found(&service_id).into_response());
+    };
+    if let Some(refusal) = state.monitor.restart_refusal(&service_id) {
+        tracing::warn!(%refusal, "restart refused");
+        let problem = match refusal {
+            restart_backoff::Refusal::CircuitOpen { .. } => Problem::new(ErrorCode::RestartCircuitOpen, refusal.to_string()),
+            restart_backoff::Refusal::BackingOff { until: retry_at, .. } | restart_backoff::Refusal::RateLimited { retry_at, .. } => {
+                Problem::new(ErrorCode::RateLimited, refusal.to_string())
+                    .with("retry_at", retry_at)
+                    .retry_after((retry_at - chrono::Utc::now()).num_seconds().max(1) as u64)
+            }
+        };
+        return Err(problem.with("service_id", &service_id).into_response());
+    }
+    let verb = if options.mode == models::RestartMode::Recreate { "recreate" } else { "restart" };
+    confirm(&state, &headers, &format!("{verb} {service_id}"), None).map_err(|gate| gate.response())?;
+    let result = state.monitor.restart_service_with(&service_id, &options).await;
     tracing::info!(success=%result.success, "restart result");
-    Json(result)
+    if !result.success {
+        let code = match orchestrator::Orchestrator::of(service) {
+            None => ErrorCode::Conflict,
+            Some(_) => docker_failure(&config, service, ErrorCode::RestartFailed).await,
+        };
+        return Err(Problem::new(code, result.message.clone()).with("result", result).into_response());
+    }
+    Ok(Json(result))
 }
 
-async fn get_metrics_handler(
+/// `code`, or `docker_unavailable` when `service` runs in a Docker container whose daemon does
+/// not answer, so callers can tell an outage of Docker from the operation itself failing.
+async fn docker_failure(config: &Config, service: &models::ServiceConfig, code: ErrorCode) -> ErrorCode {
+    let on_docker = matches!(orchestrator::Orchestrator::of(service), Some(orchestrator::Orchestrator::Docker { .. }));
+    if on_docker && docker_hosts::ping(config, service.docker_host.as_deref()).await.is_err() { ErrorCode::DockerUnavailable } else { code }
+}
+
+/// Close a service's restart circuit and forget its restarts, so it can be restarted right away.
+#[utoipa::path(post, path = "/api/services/{service_id}/restart/reset", tag = "restart", params(("service_id" = String, Path)), security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, description = "`{\"service_id\", \"cleared\"}`"), (status = 401, body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody)))]
+async fn reset_restart_backoff_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
     State(state): State<AppState>,
-) -> Json<models::SystemMetrics> {
-    Json(state.monitor.get_system_metrics().await)
+    headers: axum::http::HeaderMap,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, "unauthorized restart backoff reset");
+        return Err(Problem::unauthorized());
+    }
+    let cleared = state.monitor.reset_restart_backoff(&service_id).ok_or_else(|| Problem::service_not_found(&service_id))?;
+    Ok(Json(serde_json::json!({ "service_id": service_id, "cleared": cleared })))
 }
 
-async fn compose_handler(
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct LogsQuery {
+    /// Lines of history to start with
+    #[serde(default)]
+    tail: Option<u32>,
+    /// Keep the response open and stream new lines
+    #[serde(default)]
+    follow: bool,
+}
+
+/// The container's recent output as plain text, one line per line written; with `follow=true` the
+/// response stays open and new lines are streamed as they arrive.
+#[utoipa::path(get, path = "/api/services/{service_id}/logs", tag = "services", params(("service_id" = String, Path), LogsQuery), security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, description = "Log lines", content_type = "text/plain", body = String), (status = 401, body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody), (status = 409, description = "The service has no docker_container", body = openapi::ProblemBody), (status = 502, body = openapi::ProblemBody), (status = 503, description = "The Docker daemon does not answer", body = openapi::ProblemBody)))]
+async fn service_logs_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
     State(state): State<AppState>,
     headers: axum::http::HeaderMap,
-    Json(req): Json<ComposeRequest>
-) -> (StatusCode, Json<crate::compose::ComposeResult>) {
-    let req_id = get_or_make_request_id(&headers);
-    let parent_ctx = extract_traceparent(&headers);
-    let span = tracing::info_span!("compose_action", action=?req.action, services=?req.services, %req_id);
-    if let Some(ctx) = &parent_ctx { span.set_parent(ctx.clone()); }
-    let _guard = span.enter();
+    axum::extract::Query(q): axum::extract::Query<LogsQuery>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use futures::StreamExt;
     if !is_authorized(&state, &headers) {
-        crate::metrics::increment_compose_unauthorized();
-        tracing::warn!("unauthorized compose attempt");
-        return (StatusCode::UNAUTHORIZED, Json(crate::compose::ComposeResult { action: "error".into(), services: vec![], success: false, status_code: Some(401), stdout: String::new(), stderr: "unauthorized".into() }));
+        tracing::warn!(%service_id, "unauthorized logs request");
+        return Problem::unauthorized().into_response();
     }
-    let result = req.execute().await.unwrap_or_else(|e| crate::compose::ComposeResult { action: "error".into(), services: vec![], success: false, status_code: None, stdout: String::new(), stderr: e.to_string() });
-    let code = if result.success { StatusCode::OK } else { StatusCode::INTERNAL_SERVER_ERROR };
-    tracing::info!(success=result.success, status=?code, "compose completed");
-    (code, Json(result))
+    let config = state.monitor.config();
+    let Some(service) = config.services.iter().find(|s| s.id == service_id) else {
+        return Problem::service_not_found(&service_id).into_response();
+    };
+    let Some(container) = &service.docker_container else {
+        return no_container(&service_id).into_response();
+    };
+    let docker = match docker_hosts::connect_for(&config, service) {
+        Ok(docker) => docker,
+        Err(e) => return Problem::new(ErrorCode::DockerUnavailable, format!("{e:#}")).into_response(),
+    };
+    let lines = match logs::container_logs(docker, container, q.tail.unwrap_or(logs::DEFAULT_TAIL), q.follow).await {
+        Ok(lines) => lines,
+        Err(e) => return Problem::new(docker_failure(&config, service, ErrorCode::ActionFailed).await, format!("{e:#}")).into_response(),
+    };
+    let body = lines.map(|line| match line {
+        Ok(line) => Ok(axum::body::Bytes::from(line.line + "\n")),
+        Err(e) => Err(std::io::Error::other(format!("{e:#}"))),
+    });
+    ([(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")], axum::body::Body::from_stream(body)).into_response()
 }
 
-// ---------- HTTP Metrics Middleware ----------
-async fn http_metrics_middleware(
-    req: HttpRequest<axum::body::Body>,
-    next: axum::middleware::Next,
-) -> Result<axum::response::Response, axum::http::StatusCode> {
-    let method = req.method().to_string();
-    let raw_path = req.uri().path().to_string();
-    let start = Instant::now();
-    let resp = next.run(req).await;
-    let status = resp.status().as_u16();
-    // Attempt to use matched route path (avoids high cardinality) if available
-    let path = resp.extensions()
-        .get::<axum::extract::MatchedPath>()
-        .map(|p| p.as_str().to_string())
-        .unwrap_or(raw_path);
-    crate::metrics::record_http_request(&method, &path, status);
-    crate::metrics::observe_http_request_duration(&method, &path, start.elapsed().as_secs_f64());
-    Ok(resp)
+/// `POST /api/services/{id}/{stop|start|pause|unpause|kill}`. 502 when Docker refuses the action,
+/// 503 when it does not answer.
+#[utoipa::path(post, path = "/api/services/{service_id}/{action}", tag = "services", params(("service_id" = String, Path), ("action" = String, Path, description = "`stop`, `start`, `pause`, `unpause` or `kill`")), security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, body = models::RestartResult), (status = 202, description = "Armed: repeat with the returned confirmation token"), (status = 401, body = openapi::ProblemBody), (status = 403, description = "Invalid or used confirmation token", body = openapi::ProblemBody), (status = 404, description = "Unknown service or action", body = openapi::ProblemBody), (status = 409, description = "The service has no docker_container", body = openapi::ProblemBody), (status = 502, description = "Docker refused; the `result` member has the details", body = openapi::ProblemBody), (status = 503, description = "The Docker daemon does not answer", body = openapi::ProblemBody)))]
+async fn container_action_handler(
+    axum::extract::Path((service_id, action)): axum::extract::Path<(String, String)>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Response {
+    use axum::response::IntoResponse;
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, %action, "unauthorized container action attempt");
+        return Problem::unauthorized().into_response();
+    }
+    let Some(action) = models::ContainerAction::parse(&action) else {
+        return Problem::new(ErrorCode::NotFound, format!("Unknown action `{action}` (use stop, start, pause, unpause or kill)")).into_response();
+    };
+    let config = state.monitor.config();
+    let Some(service) = config.services.iter().find(|s| s.id == service_id) else {
+        return Problem::service_not_found(&service_id).into_response();
+    };
+    if service.docker_container.is_none() {
+        return no_container(&service_id).into_response();
+    }
+    if let Err(gate) = confirm(&state, &headers, &format!("{} {service_id}", action.as_str()), None) { return gate.response(); }
+    let result = state.monitor.container_action(&service_id, action).await;
+    if !result.success {
+        let code = docker_failure(&config, service, ErrorCode::ActionFailed).await;
+        return Problem::new(code, format!("{} {service_id} failed", action.as_str())).with("result", result).into_response();
+    }
+    Json(result).into_response()
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::compose::{ComposeRequest, ComposeAction};
-    use super::AppState;
-    use axum::http::HeaderMap;
-    use axum::http::StatusCode;
-    use axum::{Router, routing::{get}, middleware};
-    use tower::ServiceExt; // for oneshot
-    use axum::body::Body;
-    use axum::http::{Request};
-    use axum::body::to_bytes;
+fn no_container(service_id: &str) -> Problem {
+    Problem::new(ErrorCode::Conflict, format!("Service {service_id} has no docker_container"))
+}
 
-    #[tokio::test]
-    async fn compose_dry_run_returns_success() {
-    let req = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, dry_run: true };
-    let result = req.execute().await.unwrap();
-        assert!(result.success);
-        assert_eq!(result.stdout, "dry-run");
-    let families = crate::metrics::PROMETHEUS_REGISTRY.gather();
-    let compose = families.iter().find(|m| m.name()=="fks_compose_actions_total").expect("compose metric missing");
-    let count: f64 = compose.metric.iter().map(|mc| mc.get_counter().value()).sum();
-    assert!(count >= 1.0);
+/// Recreate the service's container from its previous image; 409 until a second image has been seen.
+#[utoipa::path(post, path = "/api/services/{service_id}/rollback", tag = "restart", params(("service_id" = String, Path)), security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, body = models::RestartResult), (status = 202, description = "Armed: repeat with the returned confirmation token"), (status = 401, body = openapi::ProblemBody), (status = 403, description = "Invalid or used confirmation token", body = openapi::ProblemBody), (status = 404, body = openapi::ProblemBody), (status = 409, description = "No docker_container, or no previous image recorded", body = openapi::ProblemBody), (status = 502, description = "The rollback failed; the `result` member has the details", body = openapi::ProblemBody), (status = 503, description = "The Docker daemon does not answer", body = openapi::ProblemBody)))]
+async fn rollback_service_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Response {
+    use axum::response::IntoResponse;
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, "unauthorized rollback attempt");
+        return Problem::unauthorized().into_response();
     }
+    let config = state.monitor.config();
+    let Some(service) = config.services.iter().find(|s| s.id == service_id) else {
+        return Problem::service_not_found(&service_id).into_response();
+    };
+    if service.docker_container.is_none() {
+        return no_container(&service_id).into_response();
+    }
+    if state.monitor.image_record(&service_id).is_none_or(|r| r.previous.is_none()) {
+        return Problem::new(ErrorCode::Conflict, format!("No previous image recorded for {service_id}")).into_response();
+    }
+    if let Err(gate) = confirm(&state, &headers, &format!("rollback {service_id}"), None) { return gate.response(); }
+    let result = state.monitor.rollback_service(&service_id).await;
+    if !result.success {
+        let code = docker_failure(&config, service, ErrorCode::ActionFailed).await;
+        return Problem::new(code, format!("Rollback of {service_id} failed")).with("result", result).into_response();
+    }
+    Json(result).into_response()
+}
 
-    #[tokio::test]
-    async fn unauthorized_check_blocks_without_header() {
-        let state = AppState { monitor: crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().start().await.unwrap(), api_key: Some("secret".into()) };
-        let mut headers = HeaderMap::new();
-        assert!(!super::is_authorized(&state, &headers));
-        headers.insert("x-api-key", "wrong".parse().unwrap());
-        assert!(!super::is_authorized(&state, &headers));
-        headers.insert("x-api-key", "secret".parse().unwrap());
-        assert!(super::is_authorized(&state, &headers));
+async fn heartbeat_handler(
+    axum::extract::Path(service_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<(StatusCode, Json<serde_json::Value>), Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%service_id, "unauthorized heartbeat");
+        return Err(Problem::unauthorized());
     }
+    let received_at = state.monitor.record_heartbeat(&service_id).ok_or_else(|| {
+        Problem::new(ErrorCode::ServiceNotFound, format!("No heartbeat service {service_id}")).with("service_id", &service_id)
+    })?;
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({"service_id": service_id, "received_at": received_at}))))
+}
 
-    #[tokio::test]
-    async fn unauthorized_compose_increments_metric() {
-        let state = AppState { monitor: crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().start().await.unwrap(), api_key: Some("k".into()) };
-        let headers = HeaderMap::new(); // no key
-        let before = current_counter("fks_compose_unauthorized_total");
-        let req = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, dry_run: true };
-    let (code, _resp) = super::compose_handler(axum::extract::State(state), headers, axum::Json(req)).await;
-        assert_eq!(code, StatusCode::UNAUTHORIZED);
-        let after = current_counter("fks_compose_unauthorized_total");
-        assert!(after >= before + 1.0);
+#[derive(serde::Serialize)]
+struct AlertsOverview {
+    escalations: Vec<escalation::Escalation>,
+    escalation_policy: Vec<config::EscalationStep>,
+    dead_letters: usize,
+}
+
+/// The policy is configuration and stays hidden from read-only callers.
+impl redact::Redact for AlertsOverview {
+    fn redact(mut self) -> Self {
+        self.escalations = self.escalations.redact();
+        self.escalation_policy.clear();
+        self
     }
+}
 
-    #[tokio::test]
-    async fn unauthorized_restart_increments_metric() {
-        let state = AppState { monitor: crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().start().await.unwrap(), api_key: Some("k".into()) };
+async fn alerts_overview_handler(State(state): State<AppState>, headers: axum::http::HeaderMap) -> Json<AlertsOverview> {
+    Json(redact_for_caller(&state, &headers, AlertsOverview {
+        escalations: state.monitor.escalations(),
+        escalation_policy: state.monitor.escalation_policy(),
+        dead_letters: state.alerts.dead_letters().len(),
+    }))
+}
+
+/// Recorded monitor events, newest first, filtered by service, type and time and paged with a cursor.
+#[utoipa::path(get, path = "/api/events", tag = "events", params(events::EventQuery), responses((status = 200, description = "Redacted for read-only callers", body = events::EventPage), (status = 400, description = "Unknown event type or malformed cursor", body = openapi::ProblemBody)))]
+async fn events_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<events::EventQuery>,
+) -> Result<Json<events::EventPage>, Problem> {
+    state.monitor.events(&q)
+        .map(|page| Json(redact_for_caller(&state, &headers, page)))
+        .map_err(|e| Problem::new(ErrorCode::InvalidRequest, e.to_string()))
+}
+
+/// The WebSocket feed as Server-Sent Events, for clients that only speak plain HTTP.
+#[utoipa::path(get, path = "/api/events/stream", tag = "events", params(websocket::StreamQuery), responses((status = 200, description = "`initial`, `update`, `event` and `server_shutdown` events, each carrying the JSON of the WebSocket frame of that type; redacted for read-only callers", content_type = "text/event-stream", body = String)))]
+async fn event_stream_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<websocket::StreamQuery>,
+) -> axum::response::Sse<impl futures::Stream<Item = Result<axum::response::sse::Event, std::convert::Infallible>>> {
+    let user_agent = headers.get(axum::http::header::USER_AGENT).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let authorized = is_authorized(&state, &headers);
+    axum::response::Sse::new(state.ws_hub.sse(state.monitor.clone(), &q, user_agent, authorized)).keep_alive(axum::response::sse::KeepAlive::default())
+}
+
+async fn alert_history_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<alert_history::HistoryQuery>,
+) -> Json<alert_history::HistoryPage> {
+    Json(redact_for_caller(&state, &headers, state.alerts.history(&q)))
+}
+
+async fn dead_letters_handler(State(state): State<AppState>, headers: axum::http::HeaderMap) -> Json<Vec<alerts::DeadLetter>> {
+    Json(redact_for_caller(&state, &headers, state.alerts.dead_letters()))
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct RedeliverRequest {
+    /// Dead-letter ids to retry (empty = all)
+    #[serde(default)]
+    ids: Vec<uuid::Uuid>,
+}
+
+async fn redeliver_alerts_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    body: Option<Json<RedeliverRequest>>,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized alert redelivery attempt");
+        return Err(Problem::unauthorized());
+    }
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    let report = state.alerts.redeliver(&req.ids).await;
+    tracing::info!(redelivered=report.redelivered, failed=report.failed, "alert redelivery completed");
+    Ok(Json(serde_json::json!(report)))
+}
+
+/// Receiver for Prometheus Alertmanager webhooks; alerts become `SystemAlert` events on the
+/// dashboard, WebSocket stream and notification channels.
+async fn alertmanager_webhook_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(message): Json<alertmanager_webhook::WebhookMessage>,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized alertmanager webhook");
+        return Err(Problem::unauthorized());
+    }
+    let events = alertmanager_webhook::to_events(&message, &state.monitor.config().services, state.monitor.now());
+    let accepted = events.len();
+    for event in events {
+        state.monitor.inject_event(event);
+    }
+    info!(receiver = %message.receiver, status = %message.status, accepted, "📥 Alertmanager webhook received");
+    Ok(Json(serde_json::json!({"accepted": accepted})))
+}
+
+#[derive(serde::Serialize)]
+struct SilencesOverview {
+    silences: Vec<silence::Silence>,
+    maintenance_windows: Vec<MaintenanceWindowStatus>,
+}
+
+#[derive(serde::Serialize)]
+struct MaintenanceWindowStatus {
+    #[serde(flatten)]
+    window: silence::MaintenanceWindow,
+    active: bool,
+}
+
+impl redact::Redact for SilencesOverview {
+    fn redact(mut self) -> Self {
+        self.silences = self.silences.redact();
+        self.maintenance_windows.iter_mut().for_each(|w| w.window = w.window.clone().redact());
+        self
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ValidationQuery {
+    /// Probe again instead of returning the stored report
+    #[serde(default)]
+    refresh: bool,
+}
+
+/// Report from the startup validation pass; probes now when none exists yet or `?refresh=true`
+/// (which needs write access).
+async fn endpoint_validation_handler(
+    State(state): State<AppState>,
+    axum::extract::Query(q): axum::extract::Query<ValidationQuery>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<validation::ValidationReport>, Problem> {
+    if q.refresh && !is_authorized(&state, &headers) {
+        return Err(Problem::unauthorized());
+    }
+    let stored = if q.refresh { None } else { state.endpoint_validation.read().await.clone() };
+    let report = match stored {
+        Some(report) => report,
+        None => {
+            let config = state.monitor.config();
+            let report = validation::validate_endpoints(&config.services, std::time::Duration::from_secs(config.monitoring.timeout_seconds)).await;
+            *state.endpoint_validation.write().await = Some(report.clone());
+            report
+        }
+    };
+    Ok(Json(redact_for_caller(&state, &headers, report)))
+}
+
+async fn list_silences_handler(State(state): State<AppState>, headers: axum::http::HeaderMap) -> Json<SilencesOverview> {
+    let now = chrono::Utc::now();
+    let silences = state.monitor.silences();
+    Json(redact_for_caller(&state, &headers, SilencesOverview {
+        silences: silences.list(now),
+        maintenance_windows: silences
+            .windows()
+            .into_iter()
+            .map(|w| MaintenanceWindowStatus { active: w.active_at(now), window: w })
+            .collect(),
+    }))
+}
+
+async fn create_silence_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<silence::SilenceRequest>,
+) -> Result<(StatusCode, Json<silence::Silence>), Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized silence creation attempt");
+        return Err(Problem::unauthorized());
+    }
+    let created = state.monitor.silences().add(req, chrono::Utc::now()).map_err(|reason| Problem::new(ErrorCode::InvalidRequest, reason))?;
+    info!(silence_id = %created.id, matchers = ?created.matchers, ends_at = %created.ends_at, "🔕 Silence created");
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// Register a URL for the monitor events it asks for. The response is the only one showing the
+/// signing secret.
+async fn create_webhook_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<webhooks::SubscriptionRequest>,
+) -> Result<(StatusCode, Json<webhooks::Subscription>), Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized webhook registration attempt");
+        return Err(Problem::unauthorized());
+    }
+    let subscription = state.webhooks.register(req, chrono::Utc::now()).map_err(|reason| Problem::new(ErrorCode::InvalidRequest, reason))?;
+    info!(subscription = %subscription.id, events = ?subscription.events, "🪝 Webhook subscription registered");
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+/// Subscription URLs can carry tokens, so even listing them needs credentials.
+async fn list_webhooks_handler(State(state): State<AppState>, headers: axum::http::HeaderMap) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        return Err(Problem::unauthorized());
+    }
+    Ok(Json(serde_json::json!({ "subscriptions": state.webhooks.list() })))
+}
+
+fn webhook_not_found(subscription_id: &str) -> Problem {
+    Problem::new(ErrorCode::NotFound, format!("Webhook subscription {subscription_id} not found")).with("subscription_id", subscription_id)
+}
+
+/// A subscription with its delivery counts and latest deliveries.
+async fn webhook_status_handler(
+    axum::extract::Path(subscription_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<webhooks::Subscription>, Problem> {
+    if !is_authorized(&state, &headers) {
+        return Err(Problem::unauthorized());
+    }
+    state.webhooks.get(&subscription_id).map(Json).ok_or_else(|| webhook_not_found(&subscription_id))
+}
+
+async fn delete_webhook_handler(
+    axum::extract::Path(subscription_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<StatusCode, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%subscription_id, "unauthorized webhook removal attempt");
+        return Err(Problem::unauthorized());
+    }
+    if !state.webhooks.remove(&subscription_id) {
+        return Err(webhook_not_found(&subscription_id));
+    }
+    info!(%subscription_id, "🪝 Webhook subscription removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn maintenance_status(monitor: &monitor::MonitorHandle) -> serde_json::Value {
+    let maintenance = monitor.maintenance();
+    serde_json::json!({ "active": maintenance.is_some(), "maintenance": maintenance })
+}
+
+async fn maintenance_status_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(maintenance_status(&state.monitor))
+}
+
+/// Put the whole system into maintenance until `ends_at` or for `duration_minutes`: checks keep
+/// running, but alerts, auto-remediation and `ServiceDown` events are suppressed.
+async fn start_maintenance_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<silence::MaintenanceRequest>,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized maintenance start attempt");
+        return Err(Problem::unauthorized());
+    }
+    state.monitor.start_maintenance(req).map_err(|reason| Problem::new(ErrorCode::InvalidRequest, reason))?;
+    Ok(Json(maintenance_status(&state.monitor)))
+}
+
+async fn end_maintenance_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized maintenance end attempt");
+        return Err(Problem::unauthorized());
+    }
+    let ended = state.monitor.end_maintenance();
+    Ok(Json(serde_json::json!({ "active": false, "ended": ended })))
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct IncidentsQuery {
+    #[serde(default)]
+    state: Option<incident::IncidentState>,
+}
+
+async fn list_incidents_handler(State(state): State<AppState>, axum::extract::Query(q): axum::extract::Query<IncidentsQuery>, headers: axum::http::HeaderMap) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "incidents": redact_for_caller(&state, &headers, state.monitor.incidents(q.state)) }))
+}
+
+/// Who acknowledged or resolved an incident: the bearer token's subject, else `by` from the body
+/// (marked self-reported), else `api`.
+fn incident_actor(headers: &axum::http::HeaderMap, by: Option<String>) -> incident::Actor {
+    let subject = headers.get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(|token| crate::auth::jwt_subject(token.trim()));
+    match (subject, by.filter(|b| !b.trim().is_empty())) {
+        (Some(subject), _) => incident::Actor::verified(subject),
+        (None, Some(by)) => incident::Actor { name: by, self_reported: true },
+        (None, None) => incident::Actor::verified("api"),
+    }
+}
+
+fn incident_problem(incident_id: &str, err: incident::IncidentError) -> Problem {
+    match err {
+        incident::IncidentError::NotFound => Problem::new(ErrorCode::NotFound, format!("Incident {incident_id} not found")),
+        incident::IncidentError::AlreadyResolved => Problem::new(ErrorCode::Conflict, format!("Incident {incident_id} is already resolved")),
+    }
+    .with("incident_id", incident_id)
+}
+
+/// Take on an open incident: its escalation and outage reminders stop until it is resolved.
+/// Acknowledging again records a handoff.
+async fn acknowledge_incident_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(incident_id): axum::extract::Path<String>,
+    headers: axum::http::HeaderMap,
+    body: Option<Json<incident::IncidentUpdate>>,
+) -> Result<Json<incident::Incident>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized incident acknowledgement attempt");
+        return Err(Problem::unauthorized());
+    }
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    let by = incident_actor(&headers, req.by);
+    state.monitor.acknowledge_incident(&incident_id, &by, req.note).map(Json).map_err(|e| incident_problem(&incident_id, e))
+}
+
+/// Close an incident by hand, e.g. when the outage is known and being dealt with elsewhere.
+async fn resolve_incident_handler(
+    State(state): State<AppState>,
+    axum::extract::Path(incident_id): axum::extract::Path<String>,
+    headers: axum::http::HeaderMap,
+    body: Option<Json<incident::IncidentUpdate>>,
+) -> Result<Json<incident::Incident>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized incident resolve attempt");
+        return Err(Problem::unauthorized());
+    }
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    let by = incident_actor(&headers, req.by);
+    state.monitor.resolve_incident(&incident_id, &by, req.note).map(Json).map_err(|e| incident_problem(&incident_id, e))
+}
+
+fn automation_status(monitor: &monitor::MonitorHandle) -> serde_json::Value {
+    let freeze = monitor.automation();
+    serde_json::json!({ "frozen": freeze.is_some(), "freeze": freeze })
+}
+
+async fn automation_status_handler(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(automation_status(&state.monitor))
+}
+
+/// Configured schedules with their next and last runs.
+async fn schedules_handler(State(state): State<AppState>) -> Json<Vec<schedule::ScheduleStatus>> {
+    Json(state.schedules.status(&state.monitor.config(), state.monitor.now()))
+}
+
+/// Suspend auto-restarts, playbooks, scheduled actions and deploy webhooks; monitoring and alerting keep running.
+async fn freeze_automation_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    body: Option<Json<automation::FreezeRequest>>,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized automation freeze attempt");
+        return Err(Problem::unauthorized());
+    }
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    state.monitor.freeze_automation(req);
+    Ok(Json(automation_status(&state.monitor)))
+}
+
+async fn resume_automation_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized automation resume attempt");
+        return Err(Problem::unauthorized());
+    }
+    let resumed = state.monitor.resume_automation();
+    Ok(Json(serde_json::json!({ "frozen": false, "resumed": resumed })))
+}
+
+/// Check a submitted config (TOML, or YAML/JSON by `Content-Type`) without applying it. 200 when
+/// valid, 422 with the same report otherwise.
+async fn validate_config_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    body: String,
+) -> Result<(StatusCode, Json<config_check::Report>), Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized config validation attempt");
+        return Err(Problem::unauthorized());
+    }
+    let content_type = headers.get(axum::http::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let format = if content_type.contains("json") {
+        config::ConfigFormat::Json
+    } else if content_type.contains("yaml") {
+        config::ConfigFormat::Yaml
+    } else {
+        config::ConfigFormat::Toml
+    };
+    let report = match Config::parse(&body, format) {
+        Ok(config) => config_check::validate(&config, true).await,
+        Err(e) => config_check::Report::unreadable(&e),
+    };
+    let code = if report.valid { StatusCode::OK } else { StatusCode::UNPROCESSABLE_ENTITY };
+    Ok((code, Json(report)))
+}
+
+/// The configuration the monitor is running with (defaults, templates and secret references applied,
+/// discovered services included) with secrets redacted, and what a reload of the config file would change.
+async fn effective_config_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized effective config request");
+        return Err(Problem::unauthorized());
+    }
+    use redact::Redact;
+    let config = (*state.monitor.config()).clone();
+    let discovered: serde_json::Map<String, serde_json::Value> = config
+        .services
+        .iter()
+        .filter_map(|s| state.monitor.discovery_source(&s.id).map(|source| (s.id.clone(), source.into())))
+        .collect();
+    // Discovered services are not in the file, so the comparison leaves them out
+    let (file_diff, file_error) = match &state.config_path {
+        Some(path) => match reload::read_config(path).await {
+            Ok(on_disk) => (Some(reload::ConfigDiff::between(&state.monitor.configured(), &on_disk)), None),
+            Err(e) => (None, Some(format!("{e:#}"))),
+        },
+        None => (None, None),
+    };
+    Ok(Json(serde_json::json!({
+        "file": state.config_path.as_ref().map(|p| p.display().to_string()),
+        "config": config.redact(),
+        "discovered": discovered,
+        "file_diff": file_diff,
+        "file_error": file_error,
+    })))
+}
+
+async fn delete_silence_handler(
+    axum::extract::Path(silence_id): axum::extract::Path<String>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<StatusCode, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!(%silence_id, "unauthorized silence removal attempt");
+        return Err(Problem::unauthorized());
+    }
+    if !state.monitor.silences().remove(&silence_id) {
+        return Err(Problem::new(ErrorCode::NotFound, format!("Silence {silence_id} not found")).with("silence_id", &silence_id));
+    }
+    info!(%silence_id, "🔔 Silence removed");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(get, path = "/api/metrics", tag = "metrics", responses((status = 200, body = models::SystemMetrics)))]
+async fn get_metrics_handler(
+    State(state): State<AppState>,
+) -> Json<models::SystemMetrics> {
+    Json(state.monitor.get_system_metrics().await)
+}
+
+#[derive(serde::Deserialize)]
+struct PromqlQuery {
+    /// Name of an allowlisted query, e.g. `response_time_p95`
+    query: String,
+    /// Limit to one service (default: all)
+    service: Option<String>,
+    /// How far back, e.g. `30m`, `6h` or `7d` (default 1h)
+    range: Option<String>,
+    /// Seconds between points (default: about 120 points over the range)
+    step: Option<u64>,
+}
+
+/// Proxy an allowlisted query to the configured Prometheus and return its series as
+/// `[unix_seconds, value]` points, so the dashboard needs no direct Prometheus access.
+async fn promql_handler(
+    State(state): State<AppState>,
+    axum::extract::Query(q): axum::extract::Query<PromqlQuery>,
+) -> Result<Json<serde_json::Value>, Problem> {
+    let config = state.monitor.config();
+    let Some(prometheus) = &config.prometheus else {
+        return Err(Problem::new(ErrorCode::PrometheusUnavailable, "No [prometheus] server configured"));
+    };
+    let queries = promql::allowed_queries(prometheus);
+    let Some(template) = queries.get(&q.query) else {
+        return Err(Problem::new(ErrorCode::InvalidRequest, format!("unknown query `{}`", q.query)).with("allowed", queries.keys().collect::<Vec<_>>()));
+    };
+    if let Some(service_id) = q.service.as_deref().filter(|id| !config.services.iter().any(|s| s.id == *id)) {
+        return Err(Problem::service_not_found(service_id));
+    }
+    let range = match q.range.as_deref().map(promql::parse_duration) {
+        None => std::time::Duration::from_secs(3600),
+        Some(Some(range)) if range <= promql::MAX_RANGE => range,
+        Some(_) => return Err(Problem::new(ErrorCode::InvalidRequest, "range must look like 30m, 6h or 7d and be at most 7d")),
+    };
+    let step = q.step.filter(|s| *s > 0).map(std::time::Duration::from_secs).unwrap_or_else(|| promql::default_step(range));
+    if range.as_secs() / step.as_secs() > 11_000 {
+        return Err(Problem::new(ErrorCode::InvalidRequest, "step too small for range"));
+    }
+    let end = chrono::Utc::now();
+    match promql::query_range(prometheus, &promql::render(template, q.service.as_deref()), end, range, step).await {
+        Ok(series) => Ok(Json(serde_json::json!({
+            "query": q.query,
+            "service": q.service,
+            "start": (end - chrono::Duration::from_std(range).unwrap_or_default()).timestamp(),
+            "end": end.timestamp(),
+            "step": step.as_secs(),
+            "series": series,
+        }))),
+        Err(e) => {
+            tracing::warn!(query = %q.query, error = %e, "Prometheus query failed");
+            Err(Problem::new(ErrorCode::QueryFailed, format!("{e:#}")))
+        }
+    }
+}
+
+#[utoipa::path(post, path = "/api/compose", tag = "compose", request_body = ComposeRequest, security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, description = "Finished, or with `stream: true` a stream of log lines", body = compose::ComposeResult), (status = 202, description = "Armed: repeat with the returned confirmation token"), (status = 400, body = openapi::ProblemBody), (status = 401, body = openapi::ProblemBody), (status = 403, description = "Invalid or used confirmation token", body = openapi::ProblemBody), (status = 502, description = "Compose failed; `stdout`, `stderr` and `status_code` come along", body = openapi::ProblemBody), (status = 503, description = "The Docker daemon does not answer", body = openapi::ProblemBody)))]
+async fn compose_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    Json(req): Json<ComposeRequest>
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use futures::StreamExt;
+    // The token confirms exactly what was asked for, not the monitor's additions
+    let confirmation_key = serde_json::to_string(&req).unwrap_or_default();
+    let req = req.with_monitor_config(&state.monitor.config());
+    let req_id = get_or_make_request_id(&headers);
+    let parent_ctx = extract_traceparent(&headers);
+    let span = tracing::info_span!("compose_action", action=?req.action, services=?req.services, %req_id);
+    if let Some(ctx) = &parent_ctx { span.set_parent(ctx.clone()); }
+    let _guard = span.enter();
+    if !is_authorized(&state, &headers) {
+        crate::metrics::increment_compose_unauthorized();
+        tracing::warn!("unauthorized compose attempt");
+        return Problem::unauthorized().into_response();
+    }
+    if let Err(e) = req.validate() {
+        return Problem::new(ErrorCode::InvalidRequest, format!("{e:#}")).into_response();
+    }
+    if !req.dry_run && !matches!(req.action, compose::ComposeAction::Ps | compose::ComposeAction::Logs) {
+        let action = format!("compose {} {}{}", req.action.as_str(), req.project_name(), if req.services.is_empty() { String::new() } else { format!(" {}", req.services.join(" ")) });
+        if let Err(gate) = confirm(&state, &headers, &action, Some(&confirmation_key)) { return gate.response(); }
+    }
+    if req.stream && req.action == compose::ComposeAction::Logs && !req.dry_run {
+        return match req.stream_logs().await {
+            Ok(lines) => {
+                let body = lines.map(|line| line.map(axum::body::Bytes::from).map_err(|e| std::io::Error::other(format!("{e:#}"))));
+                ([(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")], axum::body::Body::from_stream(body)).into_response()
+            }
+            Err(e) => Problem::new(ErrorCode::ComposeFailed, format!("{e:#}")).into_response(),
+        };
+    }
+    // Dry runs touch nothing, so they need no slot
+    let permit = match req.dry_run {
+        true => None,
+        false => {
+            let limit = state.monitor.config().compose.max_concurrent_actions;
+            Some(state.compose_queue.acquire(req.action, &req.project_name(), &req.services, limit).await)
+        }
+    };
+    let action = req.action;
+    let mut result = match req.execute().await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::warn!(error = format!("{e:#}"), "compose failed");
+            let code = if docker_hosts::ping(&state.monitor.config(), None).await.is_err() { ErrorCode::DockerUnavailable } else { ErrorCode::ComposeFailed };
+            return Problem::new(code, format!("{e:#}")).with("action", action.as_str()).into_response();
+        }
+    };
+    result.queue_position = permit.as_ref().and_then(|p| p.queue_position());
+    tracing::info!(success=result.success, "compose completed");
+    if !result.success {
+        let detail = format!("compose {} exited with {}", result.action, result.status_code.map_or_else(|| "an error".to_string(), |c| format!("status {c}")));
+        return Problem::new(ErrorCode::ComposeFailed, detail)
+            .with("action", &result.action)
+            .with("services", &result.services)
+            .with("status_code", result.status_code)
+            .with("stdout", &result.stdout)
+            .with("stderr", &result.stderr)
+            .into_response();
+    }
+    Json(result).into_response()
+}
+
+/// Compose actions running and waiting for a slot, with the configured limit. Needs the same
+/// credentials as `/api/compose`.
+#[utoipa::path(get, path = "/api/compose/queue", tag = "compose", security((), ("api_key" = []), ("bearer" = [])), responses((status = 200, description = "`{\"max_concurrent_actions\", \"running\", \"waiting\"}`"), (status = 401, body = openapi::ProblemBody)))]
+async fn compose_queue_handler(State(state): State<AppState>, headers: axum::http::HeaderMap) -> Result<Json<serde_json::Value>, Problem> {
+    if !is_authorized(&state, &headers) { return Err(Problem::unauthorized()); }
+    let snapshot = state.compose_queue.snapshot();
+    Ok(Json(serde_json::json!({
+        "max_concurrent_actions": state.monitor.config().compose.max_concurrent_actions,
+        "running": snapshot.running,
+        "waiting": snapshot.waiting,
+    })))
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ContainersQuery {
+    /// Comma separated service/container name filters
+    services: Option<String>,
+    /// Compose project override (defaults to monitoring.compose_project)
+    project: Option<String>,
+    /// Entry of `[hosts]` to list (defaults to the local daemon)
+    host: Option<String>,
+}
+
+/// Containers of the compose project on the local daemon or a `[hosts]` entry. Redacted for
+/// read-only callers.
+#[utoipa::path(get, path = "/api/containers", tag = "compose", params(ContainersQuery), responses((status = 200, description = "Redacted for read-only callers", body = Vec<compose::ContainerInfo>), (status = 400, description = "`host` is not an entry of `[hosts]`", body = openapi::ProblemBody), (status = 503, description = "The Docker daemon does not answer", body = openapi::ProblemBody)))]
+async fn list_containers_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(q): axum::extract::Query<ContainersQuery>,
+) -> Result<Json<Vec<crate::compose::ContainerInfo>>, Problem> {
+    let project = q.project.or_else(|| state.monitor.compose_project());
+    let services: Vec<String> = q.services
+        .map(|s| s.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect())
+        .unwrap_or_default();
+    let config = state.monitor.config();
+    docker_hosts::listed(&config, q.host.as_deref()).map_err(|e| Problem::new(ErrorCode::InvalidRequest, format!("{e:#}")))?;
+    // The client connects lazily, so an unreachable daemon usually shows up as a failed listing
+    let unavailable = |e: String| Problem::new(ErrorCode::DockerUnavailable, e);
+    let docker = docker_hosts::connect(&config, q.host.as_deref()).map_err(|e| unavailable(format!("Docker connect failed: {e:#}")))?;
+    let containers = crate::compose::list_containers(&docker, project.as_deref(), &services).await.map_err(|e| unavailable(format!("{e:#}")))?;
+    Ok(Json(redact_for_caller(&state, &headers, containers)))
+}
+
+// ---------- HTTP Metrics Middleware ----------
+async fn http_metrics_middleware(
+    req: HttpRequest<axum::body::Body>,
+    next: axum::middleware::Next,
+) -> Result<axum::response::Response, axum::http::StatusCode> {
+    let method = req.method().to_string();
+    let raw_path = req.uri().path().to_string();
+    let start = Instant::now();
+    let resp = next.run(req).await;
+    let status = resp.status().as_u16();
+    // Attempt to use matched route path (avoids high cardinality) if available
+    let path = resp.extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or(raw_path);
+    crate::metrics::record_http_request(&method, &path, status);
+    crate::metrics::observe_http_request_duration(&method, &path, start.elapsed().as_secs_f64());
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compose::{ComposeRequest, ComposeAction};
+    use super::AppState;
+    use crate::problem::ErrorCode;
+    use axum::http::HeaderMap;
+    use axum::http::StatusCode;
+    use axum::{Router, routing::{get}, middleware};
+    use tower::ServiceExt; // for oneshot
+    use axum::body::Body;
+    use axum::http::{Request};
+    use axum::body::to_bytes;
+
+    #[tokio::test]
+    async fn compose_dry_run_returns_success() {
+    let req = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, stream: false, dry_run: true, down: Default::default(), env_file: None, env: Default::default(), command: vec![], no_cache: false, build_args: Default::default(), target: None, ready_timeout_seconds: None, ready_endpoints: Default::default(), registries: vec![] };
+    let result = req.execute().await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.stdout, "dry-run");
+    let families = crate::metrics::PROMETHEUS_REGISTRY.gather();
+    let compose = families.iter().find(|m| m.name()=="fks_compose_actions_total").expect("compose metric missing");
+    let count: f64 = compose.metric.iter().map(|mc| mc.get_counter().value()).sum();
+    assert!(count >= 1.0);
+    }
+
+    #[tokio::test]
+    async fn unauthorized_check_blocks_without_header() {
+        let state = test_state(crate::config::Config::default(), Some("secret")).await;
+        let mut headers = HeaderMap::new();
+        assert!(!super::is_authorized(&state, &headers));
+        headers.insert("x-api-key", "wrong".parse().unwrap());
+        assert!(!super::is_authorized(&state, &headers));
+        headers.insert("x-api-key", "secret".parse().unwrap());
+        assert!(super::is_authorized(&state, &headers));
+    }
+
+    #[tokio::test]
+    async fn unauthorized_compose_increments_metric() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let headers = HeaderMap::new(); // no key
+        let before = current_counter("fks_compose_unauthorized_total");
+        let req = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, stream: false, dry_run: true, down: Default::default(), env_file: None, env: Default::default(), command: vec![], no_cache: false, build_args: Default::default(), target: None, ready_timeout_seconds: None, ready_endpoints: Default::default(), registries: vec![] };
+    let resp = super::compose_handler(axum::extract::State(state), headers, axum::Json(req)).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let after = current_counter("fks_compose_unauthorized_total");
+        assert!(after >= before + 1.0);
+    }
+
+    #[tokio::test]
+    async fn streamed_compose_logs_fail_before_the_body_starts() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let req: ComposeRequest = serde_json::from_str(r#"{"action":"logs","file":"no-such-compose.yml","stream":true,"follow":true}"#).unwrap();
+        assert!(req.detach, "follow is an alias of detach");
+        let resp = super::compose_handler(axum::extract::State(state), headers, axum::Json(req)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn compose_queue_lists_waiting_actions() {
+        let mut config = crate::config::Config::default();
+        config.compose.max_concurrent_actions = 1;
+        let state = test_state(config, Some("k")).await;
+        let queue = state.compose_queue.clone();
+        let _running = queue.acquire(ComposeAction::Up, "fks", &["fks_api".into()], 1).await;
+        let waiting = tokio::spawn(async move { queue.acquire(ComposeAction::Down, "fks", &[], 1).await.queue_position() });
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let denied = super::compose_queue_handler(axum::extract::State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(denied.unwrap_err().code, ErrorCode::Unauthorized);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let axum::Json(body) = super::compose_queue_handler(axum::extract::State(state), headers).await.unwrap();
+        assert_eq!(body["max_concurrent_actions"], 1);
+        assert_eq!(body["running"][0]["services"], serde_json::json!(["fks_api"]));
+        assert_eq!(body["waiting"][0]["action"], "down");
+        assert_eq!(body["waiting"][0]["position"], 1);
+        waiting.abort();
+    }
+
+    #[tokio::test]
+    async fn unauthorized_restart_increments_metric() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
         let headers = HeaderMap::new();
         let before = current_counter("fks_restart_unauthorized_total");
-        let result = super::restart_service_handler(axum::extract::Path("fks_api".to_string()), axum::extract::State(state), headers).await;
-        assert!(!result.success);
+        let response = super::restart_service_handler(axum::extract::Path("fks_api".to_string()), axum::extract::State(state), headers, axum::extract::Query(Default::default())).await.unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[axum::http::header::CONTENT_TYPE], crate::problem::CONTENT_TYPE);
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!((body["code"].as_str(), body["status"].as_u64()), (Some("unauthorized"), Some(401)));
         let after = current_counter("fks_restart_unauthorized_total");
         assert!(after >= before + 1.0);
     }
 
+    #[tokio::test]
+    async fn restarts_over_the_rate_limit_get_429_with_retry_after() {
+        use axum::response::IntoResponse;
+        let config = crate::config::Config {
+            restart_backoff: crate::config::RestartBackoffConfig { initial_delay_seconds: 0, max_delay_seconds: 0, max_restarts: 100, reset_after_seconds: 600 },
+            restart_rate_limit: crate::config::RestartRateLimitConfig { max_restarts: 1, window_seconds: 600 },
+            ..Default::default()
+        };
+        let state = test_state(config, Some("k")).await;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let restart = || super::restart_service_handler(axum::extract::Path("fks_api".to_string()), axum::extract::State(state.clone()), headers.clone(), axum::extract::Query(Default::default()));
+
+        // Without a Docker daemon the first restart fails, but it still counts
+        let _ = restart().await;
+        let response = restart().await.unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: i64 = response.headers()["retry-after"].to_str().unwrap().parse().unwrap();
+        assert!((590..=600).contains(&retry_after), "{retry_after}");
+    }
+
+    #[tokio::test]
+    async fn events_are_served_newest_first_and_redacted_for_viewers() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        for (event_type, message) in [(crate::models::EventType::ServiceDown, "down, see http://10.0.0.5:8000/health"), (crate::models::EventType::ServiceUp, "up")] {
+            state.monitor.inject_event(crate::models::MonitorEvent {
+                event_type, service_id: Some("fks_api".into()), message: message.into(), timestamp: chrono::Utc::now(), data: Some(serde_json::json!({"x": 1})), metadata: Default::default(),
+            });
+        }
+        let query = |q: &str| axum::extract::Query::<crate::events::EventQuery>::try_from_uri(&format!("/api/events?{q}").parse().unwrap()).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+
+        let axum::Json(page) = super::events_handler(axum::extract::State(state.clone()), headers, query("service=fks_api&type=ServiceDown,ServiceUp")).await.unwrap();
+        assert_eq!(page.items.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), ["up", "down, see http://10.0.0.5:8000/health"]);
+        let axum::Json(page) = super::events_handler(axum::extract::State(state.clone()), HeaderMap::new(), query("service=fks_api&type=ServiceDown")).await.unwrap();
+        assert!(!page.items[0].message.contains("10.0.0.5") && page.items[0].data.is_none());
+        let problem = super::events_handler(axum::extract::State(state), HeaderMap::new(), query("type=Reboot")).await.unwrap_err();
+        assert_eq!(problem.code, ErrorCode::InvalidRequest);
+    }
+
+    #[tokio::test]
+    async fn service_history_reports_the_timeline_of_known_services() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let service_id = state.monitor.get_all_services().await[0].id.clone();
+        let history = |id: &str| super::service_history_handler(axum::extract::Path(id.to_string()), axum::extract::State(state.clone()), axum::extract::Query(Default::default()), HeaderMap::new());
+
+        let axum::Json(h) = history(&service_id).await.unwrap();
+        assert_eq!(h.segments.first().map(|s| s.status.clone()), Some(crate::models::HealthStatus::Unknown), "starts out unknown until checked");
+        assert_eq!(history("nope").await.unwrap_err().code, crate::problem::ErrorCode::ServiceNotFound);
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
+    async fn readyz_reports_each_component_and_fails_with_503() {
+        let state = test_state(crate::config::Config::default(), None).await;
+        let (code, axum::Json(report)) = super::readiness_handler(axum::extract::State(state)).await;
+        let names: Vec<_> = report.components.iter().map(|c| c.name.as_str()).collect();
+        for name in ["config", "monitoring_loop", "event_channel"] { assert!(names.contains(&name), "{name} in {names:?}"); }
+        assert!(names.iter().any(|n| n.starts_with("docker")));
+        assert_eq!(code == StatusCode::OK, report.status == crate::readiness::Readiness::Ready);
+        assert_eq!(super::liveness_handler().await["status"], "alive");
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
+    async fn uptime_report_comes_as_json_or_csv() {
+        let state = test_state(crate::config::Config::default(), None).await;
+        let report = |q: &str| super::uptime_report_handler(axum::extract::State(state.clone()), axum::extract::Query::try_from_uri(&format!("/api/reports/uptime?{q}").parse().unwrap()).unwrap());
+
+        let response = report("period=24h&group_by=group&format=csv").await.unwrap();
+        assert_eq!(response.headers()[axum::http::header::CONTENT_TYPE], "text/csv; charset=utf-8");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).starts_with("key,availability,outages,"));
+        let response = report("").await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!((body["group_by"].as_str(), body["rows"].as_array().unwrap().len()), (Some("service"), state.monitor.config().services.len()));
+        for bad in ["period=90d", "period=soon", "format=xml"] {
+            assert_eq!(report(bad).await.unwrap_err().code, ErrorCode::InvalidRequest, "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn service_detail_combines_config_status_events_and_restarts() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let service_id = state.monitor.get_all_services().await[0].id.clone();
+        let event = |event_type, message: &str| crate::models::MonitorEvent { event_type, service_id: Some(service_id.clone()), message: message.into(), timestamp: chrono::Utc::now(), data: None, metadata: Default::default() };
+        state.monitor.inject_event(event(crate::models::EventType::ServiceDown, "down"));
+        state.monitor.inject_event(event(crate::models::EventType::ServiceRestarted, "restarted"));
+        let detail = |headers| super::service_detail_handler(axum::extract::Path(service_id.clone()), axum::extract::State(state.clone()), headers);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let axum::Json(d) = detail(headers).await.unwrap();
+        assert_eq!((d.config.id.as_str(), d.status.id.as_str()), (service_id.as_str(), service_id.as_str()));
+        assert!(d.config.health_endpoint.starts_with("http"));
+        assert_eq!(d.recent_events.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), ["restarted", "down"]);
+        assert_eq!(d.restarts.len(), 1);
+        assert!(d.health.is_some());
+
+        let axum::Json(d) = detail(HeaderMap::new()).await.unwrap();
+        assert!(!d.config.health_endpoint.starts_with("http"), "read-only callers don't see where the service lives");
+        let missing = super::service_detail_handler(axum::extract::Path("nope".into()), axum::extract::State(state.clone()), HeaderMap::new()).await.unwrap_err();
+        assert_eq!(missing.code, crate::problem::ErrorCode::ServiceNotFound);
+    }
+
+    #[tokio::test]
+    async fn paused_services_show_up_apart_in_the_aggregate() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let service_id = state.monitor.get_all_services().await[0].id.clone();
+        let pause = |headers| super::pause_monitoring_handler(axum::extract::Path(service_id.clone()), axum::extract::State(state.clone()), headers);
+
+        assert_eq!(pause(HeaderMap::new()).await.unwrap_err().code, crate::problem::ErrorCode::Unauthorized);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let axum::Json(paused) = pause(headers.clone()).await.unwrap();
+        assert_eq!(paused.status, crate::models::HealthStatus::Paused);
+
+        let aggregate = super::aggregate_health(&state).await;
+        assert_eq!(aggregate["pausedServices"], 1);
+        let entry = aggregate["services"].as_array().unwrap().iter().find(|s| s["id"] == service_id.as_str()).unwrap();
+        assert_eq!(entry["status"], "paused");
+
+        let axum::Json(resumed) = super::resume_monitoring_handler(axum::extract::Path(service_id.clone()), axum::extract::State(state.clone()), headers).await.unwrap();
+        assert_eq!(resumed.status, crate::models::HealthStatus::Unknown);
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
+    async fn openapi_document_describes_the_api_with_resolvable_schemas() {
+        let axum::Json(doc) = super::openapi_handler().await;
+        let doc = serde_json::to_value(doc).unwrap();
+        for path in [
+            "/api/services", "/api/services/{service_id}/restart", "/api/services/{service_id}/rollback", "/api/services/{service_id}/logs",
+            "/api/services/{service_id}/{action}", "/api/compose", "/api/compose/queue", "/api/containers", "/api/events", "/api/metrics", "/health/aggregate",
+        ] {
+            assert!(doc["paths"][path].is_object(), "{path} missing");
+        }
+        assert!(doc["paths"]["/api/services/{service_id}/restart"]["post"]["parameters"].as_array().unwrap().iter().any(|p| p["name"] == "mode"));
+        assert!(doc["components"]["securitySchemes"]["api_key"].is_object());
+
+        fn refs(value: &serde_json::Value, out: &mut Vec<String>) {
+            match value {
+                serde_json::Value::Object(map) => for (key, v) in map {
+                    if key == "$ref" { out.extend(v.as_str().map(str::to_string)); } else { refs(v, out); }
+                },
+                serde_json::Value::Array(items) => items.iter().for_each(|v| refs(v, out)),
+                _ => {}
+            }
+        }
+        let mut found = Vec::new();
+        refs(&doc, &mut found);
+        assert!(found.iter().any(|r| r.ends_with("/ServiceStatus")));
+        for r in found {
+            let name = r.trim_start_matches("#/components/schemas/");
+            assert!(doc["components"]["schemas"][name].is_object(), "unresolved {r}");
+        }
+
+        let state = test_state(crate::config::Config::default(), None).await;
+        assert!(super::swagger_ui_handler(axum::extract::State(state)).await.unwrap().0.contains("/api/openapi.json"));
+        let mut cfg = crate::config::Config::default();
+        cfg.openapi.swagger_ui = false;
+        let state = test_state(cfg, None).await;
+        assert_eq!(super::swagger_ui_handler(axum::extract::State(state)).await.unwrap_err().code, ErrorCode::NotFound);
+    }
+
+    #[tokio::test]
+    async fn silence_flags_service_until_removed() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let service_id = state.monitor.get_all_services().await[0].id.clone();
+        let req = || serde_json::from_value::<crate::silence::SilenceRequest>(serde_json::json!({"service_id": service_id, "duration_minutes": 30})).unwrap();
+
+        let denied = super::create_silence_handler(axum::extract::State(state.clone()), HeaderMap::new(), axum::Json(req())).await;
+        assert_eq!(denied.unwrap_err().code, ErrorCode::Unauthorized);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let (code, axum::Json(created)) = super::create_silence_handler(axum::extract::State(state.clone()), headers.clone(), axum::Json(req())).await.unwrap();
+        assert_eq!(code, StatusCode::CREATED);
+        let silence_id = created.id.to_string();
+
+        let silenced = |services: Vec<serde_json::Value>| services.into_iter().filter(|s| s["silenced"] == true).map(|s| s["id"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+        let services = super::list_services(&state, &headers, &Default::default()).await.unwrap();
+        assert_eq!(silenced(services), vec![service_id.clone()]);
+        let axum::Json(overview) = super::list_silences_handler(axum::extract::State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(overview.silences.len(), 1);
+
+        let code = super::delete_silence_handler(axum::extract::Path(silence_id.clone()), axum::extract::State(state.clone()), headers.clone()).await;
+        assert_eq!(code.unwrap(), StatusCode::NO_CONTENT);
+        let gone = super::delete_silence_handler(axum::extract::Path(silence_id), axum::extract::State(state.clone()), headers.clone()).await;
+        assert_eq!(gone.unwrap_err().code, ErrorCode::NotFound);
+        let services = super::list_services(&state, &headers, &Default::default()).await.unwrap();
+        assert!(silenced(services).is_empty());
+    }
+
+    async fn test_state(cfg: crate::config::Config, api_key: Option<&str>) -> AppState {
+        let alerts = std::sync::Arc::new(crate::alerts::AlertDispatcher::new(cfg.clone()));
+        let monitor = crate::monitor::ServiceMonitor::new(cfg).await.unwrap().start().await.unwrap();
+        let ws_hub = crate::websocket::WsHub::start(monitor.clone());
+        AppState {
+            graphql: crate::graphql::schema(monitor.clone()),
+            monitor,
+            api_key: api_key.map(str::to_string),
+            alerts,
+            ws_hub,
+            endpoint_validation: Default::default(),
+            config_path: None,
+            service_changes: Default::default(),
+            compose_queue: Default::default(),
+            schedules: Default::default(),
+            confirmations: Default::default(),
+            webhooks: Default::default(),
+        }
+    }
+
     fn current_counter(name: &str) -> f64 {
             let families = crate::metrics::PROMETHEUS_REGISTRY.gather();
             families.iter().find(|m| m.name()==name)
@@ -365,9 +2179,10 @@ mod tests {
         }
 
     #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
     async fn http_metrics_use_matched_path() {
         // Build minimal app with the existing middleware and target route
-        let state = AppState { monitor: crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().start().await.unwrap(), api_key: None };
+        let state = test_state(crate::config::Config::default(), None).await;
         let app = Router::new()
             .route("/api/services/{service_id}/health", get(super::get_service_health_handler))
             .layer(middleware::from_fn(super::http_metrics_middleware))
@@ -396,8 +2211,64 @@ mod tests {
     }
 
     #[tokio::test]
+    async fn heartbeat_rejects_non_heartbeat_service() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let problem = super::heartbeat_handler(axum::extract::Path("fks_api".to_string()), axum::extract::State(state), headers.clone()).await.unwrap_err();
+        assert_eq!(problem.code, crate::problem::ErrorCode::ServiceNotFound);
+
+        let mut cfg = crate::config::Config::default();
+        cfg.services[0].heartbeat_window_seconds = Some(60);
+        let id = cfg.services[0].id.clone();
+        let state = test_state(cfg, Some("k")).await;
+        let (code, _) = super::heartbeat_handler(axum::extract::Path(id), axum::extract::State(state), headers).await.unwrap();
+        assert_eq!(code, StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn endpoint_validation_is_redacted_and_refresh_needs_write_access() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        *state.endpoint_validation.write().await = Some(crate::validation::ValidationReport {
+            checked_at: chrono::Utc::now(),
+            reachable: 0,
+            unreachable: 1,
+            results: vec![crate::validation::EndpointResult {
+                service_id: "db".into(), name: "DB".into(), endpoint: "http://10.0.0.5:8080/health".into(),
+                outcome: crate::validation::Outcome::Unreachable, status_code: None, response_time_ms: None,
+                error: Some("error sending request for url (http://10.0.0.5:8080/health)".into()),
+            }],
+        });
+        let query = |refresh| axum::extract::Query(super::ValidationQuery { refresh });
+        let problem = super::endpoint_validation_handler(axum::extract::State(state.clone()), query(true), HeaderMap::new()).await.unwrap_err();
+        assert_eq!(problem.code, ErrorCode::Unauthorized);
+
+        let axum::Json(report) = super::endpoint_validation_handler(axum::extract::State(state.clone()), query(false), HeaderMap::new()).await.unwrap();
+        assert_eq!(report.results[0].endpoint, "[redacted]");
+        assert!(!report.results[0].error.as_deref().unwrap().contains("10.0.0.5"));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let axum::Json(report) = super::endpoint_validation_handler(axum::extract::State(state), query(false), headers).await.unwrap();
+        assert_eq!(report.results[0].endpoint, "http://10.0.0.5:8080/health");
+    }
+
+    #[tokio::test]
+    async fn services_redacted_for_unauthorized_callers() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let mut status = state.monitor.get_all_services().await.remove(0);
+        status.error_message = Some("error sending request for url (http://fks_api:8000/health)".into());
+        let redacted = super::redact_for_caller(&state, &HeaderMap::new(), vec![status.clone()]);
+        assert!(!redacted[0].error_message.as_deref().unwrap().contains("fks_api:8000"));
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let full = super::redact_for_caller(&state, &headers, vec![status]);
+        assert!(full[0].error_message.as_deref().unwrap().contains("fks_api:8000"));
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
     async fn aggregate_health_endpoint_returns_overall() {
-        let state = AppState { monitor: crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().start().await.unwrap(), api_key: None };
+        let state = test_state(crate::config::Config::default(), None).await;
         let app = Router::new()
             .route("/health/aggregate", get(super::aggregate_health_handler))
             .with_state(state);
@@ -411,22 +2282,751 @@ mod tests {
         let v: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
     assert!(v.get("overallStatus").is_some());
     assert!(v.get("services").and_then(|s| s.as_array()).is_some());
+    assert_eq!(v["automation"]["frozen"], false);
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
+    async fn metrics_are_compressed_until_turned_off() {
+        let state = test_state(crate::config::Config::default(), None).await;
+        let app = Router::new()
+            .route("/metrics", get(super::metrics_handler).layer(crate::compression::layer(&state.monitor, crate::compression::Route::Metrics)))
+            .with_state(state.clone());
+        let scrape = |encoding: &'static str| app.clone().oneshot(Request::builder().uri("/metrics").header("accept-encoding", encoding).body(Body::empty()).unwrap());
+        let encoding = |response: &axum::response::Response| response.headers().get(axum::http::header::CONTENT_ENCODING).map(|v| v.to_str().unwrap().to_string());
+
+        assert_eq!(encoding(&scrape("gzip").await.unwrap()).as_deref(), Some("gzip"));
+        assert_eq!(encoding(&scrape("br;q=1.0, gzip;q=0.5").await.unwrap()).as_deref(), Some("br"));
+        assert_eq!(encoding(&scrape("identity").await.unwrap()), None);
+
+        let mut config = state.monitor.configured();
+        config.compression.metrics = false;
+        state.monitor.reload(config);
+        assert_eq!(encoding(&scrape("gzip").await.unwrap()), None, "applies without a restart");
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
+    async fn polls_with_an_unchanged_etag_get_304() {
+        let state = test_state(crate::config::Config::default(), None).await;
+        let service_id = state.monitor.get_all_services().await[0].id.clone();
+        let app = Router::new()
+            .route("/health/aggregate", get(super::aggregate_health_handler))
+            .route("/api/services", get(super::get_services_handler))
+            .with_state(state.clone());
+        let poll = |uri: &'static str, etag: Option<axum::http::HeaderValue>| {
+            let mut req = Request::builder().uri(uri);
+            if let Some(etag) = etag { req = req.header(axum::http::header::IF_NONE_MATCH, etag); }
+            app.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        for uri in ["/health/aggregate", "/api/services"] {
+            let first = poll(uri, None).await.unwrap();
+            assert_eq!(first.status(), StatusCode::OK);
+            let etag = first.headers()[axum::http::header::ETAG].clone();
+            let again = poll(uri, Some(etag.clone())).await.unwrap();
+            assert_eq!(again.status(), StatusCode::NOT_MODIFIED, "{uri}");
+            assert!(to_bytes(again.into_body(), usize::MAX).await.unwrap().is_empty());
+
+            state.monitor.pause_monitoring(&service_id);
+            assert_eq!(poll(uri, Some(etag)).await.unwrap().status(), StatusCode::OK, "{uri} changed");
+            state.monitor.resume_monitoring(&service_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_config_endpoint_reports_without_applying() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        headers.insert(axum::http::header::CONTENT_TYPE, "application/json".parse().unwrap());
+        let body = r#"{"monitoring": {"check_interval_seconds": 0, "timeout_seconds": 5, "retry_attempts": 1, "batch_size": 5},
+            "alerts": {"enable_notifications": false, "high_latency_threshold_ms": 1000, "consecutive_failures_threshold": 3},
+            "services": [{"id": "x", "name": "X", "service_type": "Api", "health_endpoint": "http://x/health", "expected_response_time_ms": 100, "critical": false}]}"#;
+        let (code, axum::Json(report)) = super::validate_config_handler(axum::extract::State(state.clone()), headers.clone(), body.to_string()).await.unwrap();
+        assert_eq!(code, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(report.issues[0].path, "monitoring.check_interval_seconds");
+        assert_ne!(state.monitor.config().services.len(), 1, "nothing applied");
+
+        let (code, axum::Json(report)) = super::validate_config_handler(axum::extract::State(state), headers, "{not json".into()).await.unwrap();
+        assert_eq!(code, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(!report.valid);
+    }
+
+    #[tokio::test]
+    async fn container_actions_are_authorized_and_recorded() {
+        let mut cfg = crate::config::Config::default();
+        cfg.services[0].docker_container = Some("fks-test-no-such-container".into());
+        cfg.services[1].docker_container = None;
+        let (with_container, without_container) = (cfg.services[0].id.clone(), cfg.services[1].id.clone());
+        let state = test_state(cfg, Some("k")).await;
+        let mut events = state.monitor.subscribe_events();
+        let app = Router::new()
+            .route("/api/services/{service_id}/restart", axum::routing::post(super::restart_service_handler))
+            .route("/api/services/{service_id}/{action}", axum::routing::post(super::container_action_handler))
+            .with_state(state);
+        let send = |path: String, key: Option<&str>| {
+            let mut req = Request::builder().uri(path).method("POST");
+            if let Some(key) = key { req = req.header("x-api-key", key); }
+            app.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(send(format!("/api/services/{with_container}/stop"), None).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send(format!("/api/services/{with_container}/explode"), Some("k")).await.unwrap().status(), StatusCode::NOT_FOUND);
+        let missing = send("/api/services/nope/stop".into(), Some("k")).await.unwrap();
+        assert_eq!(missing.headers()[axum::http::header::CONTENT_TYPE], crate::problem::CONTENT_TYPE);
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(missing.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!((body["code"].as_str(), body["service_id"].as_str()), (Some("service_not_found"), Some("nope")));
+        assert_eq!(send(format!("/api/services/{without_container}/pause"), Some("k")).await.unwrap().status(), StatusCode::CONFLICT);
+        // The static restart route still wins over `{action}`; the restart itself fails without the container
+        let failed = |status| matches!(status, StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE);
+        assert!(failed(send(format!("/api/services/{with_container}/restart"), Some("k")).await.unwrap().status()));
+
+        assert!(failed(send(format!("/api/services/{with_container}/kill"), Some("k")).await.unwrap().status()));
+        let event = std::iter::from_fn(|| events.try_recv().ok()).find(|e| e.event_type == crate::models::EventType::ContainerAction).unwrap();
+        assert_eq!(event.service_id.as_deref(), Some(with_container.as_str()));
+        assert_eq!(event.data.unwrap(), serde_json::json!({"action": "kill", "container": "fks-test-no-such-container", "success": false}));
+    }
+
+    #[tokio::test]
+    async fn restarts_back_off_until_reset() {
+        let mut cfg = crate::config::Config::default();
+        cfg.services[0].docker_container = Some("fks-test-no-such-container".into());
+        let id = cfg.services[0].id.clone();
+        let app = Router::new()
+            .route("/api/services/{service_id}/restart", axum::routing::post(super::restart_service_handler))
+            .route("/api/services/{service_id}/restart/reset", axum::routing::post(super::reset_restart_backoff_handler))
+            .with_state(test_state(cfg, Some("k")).await);
+        let send = |path: String, key: Option<&str>| {
+            let mut req = Request::builder().uri(path).method("POST");
+            if let Some(key) = key { req = req.header("x-api-key", key); }
+            app.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+        let restart = || async {
+            let body = send(format!("/api/services/{id}/restart"), Some("k")).await.unwrap().into_body();
+            serde_json::from_slice::<serde_json::Value>(&axum::body::to_bytes(body, usize::MAX).await.unwrap()).unwrap()
+        };
+
+        // The failed attempt still reached Docker, so the next one has to wait
+        assert_eq!(restart().await["result"]["success"], false);
+        let refused = restart().await;
+        assert_eq!(refused["code"], "rate_limited");
+        assert!(refused["detail"].as_str().unwrap().starts_with("Restart backing off until"), "{refused}");
+
+        assert_eq!(send(format!("/api/services/{id}/restart/reset"), None).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send("/api/services/nope/restart/reset".into(), Some("k")).await.unwrap().status(), StatusCode::NOT_FOUND);
+        let reset = send(format!("/api/services/{id}/restart/reset"), Some("k")).await.unwrap();
+        assert_eq!(reset.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(&axum::body::to_bytes(reset.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["cleared"]["restarts"], 1);
+        assert_ne!(restart().await["code"], "rate_limited");
+    }
+
+    #[tokio::test]
+    async fn destructive_actions_wait_for_confirmation_when_required() {
+        let mut cfg = crate::config::Config::default();
+        cfg.confirmation.required = true;
+        cfg.services[0].docker_container = None;
+        let id = cfg.services[0].id.clone();
+        let app = Router::new()
+            .route("/api/services/{service_id}/restart", axum::routing::post(super::restart_service_handler))
+            .route("/api/compose", axum::routing::post(super::compose_handler))
+            .with_state(test_state(cfg, Some("k")).await);
+        let send = |path: &str, body: &str, token: Option<&str>| {
+            let mut req = Request::builder().uri(path).method("POST").header("x-api-key", "k").header("content-type", "application/json");
+            if let Some(token) = token { req = req.header(crate::confirmation::HEADER, token); }
+            let response = app.clone().oneshot(req.body(Body::from(body.to_string())).unwrap());
+            async move {
+                let response = response.await.unwrap();
+                let status = response.status();
+                (status, serde_json::from_slice::<serde_json::Value>(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap())
+            }
+        };
+        let restart = format!("/api/services/{id}/restart");
+
+        let (status, armed) = send(&restart, "", None).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(armed["action"], format!("restart {id}"));
+        let token = armed["token"].as_str().unwrap();
+        let (status, ran) = send(&restart, "", Some(token)).await;
+        assert_eq!(status, StatusCode::CONFLICT, "ran, with nothing to restart");
+        assert_eq!(ran["result"]["service_id"], id.as_str(), "{ran}");
+        assert_eq!(send(&restart, "", Some(token)).await.0, StatusCode::FORBIDDEN, "tokens work once");
+
+        // Compose tokens are bound to the whole request; read-only actions and dry runs need none
+        let (status, armed) = send("/api/compose", r#"{"action":"down","file":"no-such-compose.yml","project":"fks"}"#, None).await;
+        assert_eq!((status, armed["action"].as_str()), (StatusCode::ACCEPTED, Some("compose down fks")));
+        let with_volumes = r#"{"action":"down","file":"no-such-compose.yml","project":"fks","volumes":true}"#;
+        assert_eq!(send("/api/compose", with_volumes, armed["token"].as_str()).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(send("/api/compose", r#"{"action":"down","file":"no-such-compose.yml","dry_run":true}"#, None).await.0, StatusCode::OK);
+        assert_ne!(send("/api/compose", r#"{"action":"ps","file":"no-such-compose.yml"}"#, None).await.0, StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn rollbacks_need_a_previous_image() {
+        let mut cfg = crate::config::Config::default();
+        cfg.services[0].docker_container = Some("fks-test-no-such-container".into());
+        cfg.services[1].docker_container = None;
+        let (with_container, without_container) = (cfg.services[0].id.clone(), cfg.services[1].id.clone());
+        let app = Router::new()
+            .route("/api/services/{service_id}/rollback", axum::routing::post(super::rollback_service_handler))
+            .with_state(test_state(cfg, Some("k")).await);
+        let send = |path: String, key: Option<&str>| {
+            let mut req = Request::builder().uri(path).method("POST");
+            if let Some(key) = key { req = req.header("x-api-key", key); }
+            app.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(send(format!("/api/services/{with_container}/rollback"), None).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(send("/api/services/nope/rollback".into(), Some("k")).await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(send(format!("/api/services/{without_container}/rollback"), Some("k")).await.unwrap().status(), StatusCode::CONFLICT);
+        // Nothing has been deployed since the monitor started
+        assert_eq!(send(format!("/api/services/{with_container}/rollback"), Some("k")).await.unwrap().status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn service_logs_need_a_known_container() {
+        let mut cfg = crate::config::Config::default();
+        cfg.services[0].docker_container = Some("fks-test-no-such-container".into());
+        cfg.services[1].docker_container = None;
+        let (with_container, without_container) = (cfg.services[0].id.clone(), cfg.services[1].id.clone());
+        let app = Router::new()
+            .route("/api/services/{service_id}/logs", get(super::service_logs_handler))
+            .with_state(test_state(cfg, Some("k")).await);
+        let get_logs = |path: String, key: Option<&str>| {
+            let mut req = Request::builder().uri(path);
+            if let Some(key) = key { req = req.header("x-api-key", key); }
+            app.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        assert_eq!(get_logs(format!("/api/services/{with_container}/logs"), None).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(get_logs("/api/services/nope/logs".into(), Some("k")).await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(get_logs(format!("/api/services/{without_container}/logs?tail=5"), Some("k")).await.unwrap().status(), StatusCode::CONFLICT);
+        // Checked before streaming starts, so the failure is a status rather than a cut-off body
+        let missing = get_logs(format!("/api/services/{with_container}/logs?tail=5&follow=true"), Some("k")).await.unwrap();
+        assert!(matches!(missing.status(), StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE), "{}", missing.status());
+    }
+
+    #[tokio::test]
+    async fn effective_config_is_redacted_and_diffed_against_the_file() {
+        use axum::extract::State;
+        let mut document = toml::Table::try_from(crate::config::Config::default()).unwrap();
+        document["alerts"].as_table_mut().unwrap().insert("webhook_url".into(), "https://hooks.example.com/T0/secret".into());
+        let service = |id: &str| toml::Value::Table(toml::from_str(&format!(r#"
+            id = "{id}"
+            name = "{id}"
+            service_type = "Api"
+            health_endpoint = "http://127.0.0.1:9/health"
+            expected_response_time_ms = 500
+            critical = false
+        "#)).unwrap());
+        document.insert("services".into(), toml::Value::Array(vec![service("fks_api")]));
+        let running = crate::config::Config::parse(&toml::to_string(&document).unwrap(), crate::config::ConfigFormat::Toml).unwrap();
+        document.insert("services".into(), toml::Value::Array(vec![service("fks_api"), service("fks_extra")]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitor.toml");
+        std::fs::write(&path, toml::to_string(&document).unwrap()).unwrap();
+
+        let mut state = test_state(running, Some("k")).await;
+        let denied = super::effective_config_handler(State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(denied.unwrap_err().code, ErrorCode::Unauthorized);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let axum::Json(body) = super::effective_config_handler(State(state.clone()), headers.clone()).await.unwrap();
+        assert_eq!(body["config"]["alerts"]["webhook_url"], "[redacted]");
+        assert_eq!(body["config"]["services"][0]["id"], "fks_api");
+        assert!(body["file_diff"].is_null(), "no config file");
+
+        state.config_path = Some(path.clone());
+        let axum::Json(body) = super::effective_config_handler(State(state.clone()), headers.clone()).await.unwrap();
+        assert_eq!(body["file_diff"]["added"], serde_json::json!(["fks_extra"]));
+        assert_eq!(body["file_diff"]["settings_changed"], false);
+        std::fs::write(&path, "services = [").unwrap();
+        let axum::Json(body) = super::effective_config_handler(State(state), headers).await.unwrap();
+        assert!(body["file_diff"].is_null() && body["file_error"].is_string(), "{body}");
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
+    async fn services_filter_by_tag_and_aggregate_by_group() {
+        use axum::extract::State;
+        let services: toml::Table = toml::from_str(r#"
+            [[services]]
+            id = "fks_api"
+            name = "API"
+            service_type = "Api"
+            health_endpoint = "http://127.0.0.1:9/health"
+            expected_response_time_ms = 500
+            critical = false
+            group = "trading"
+            tags = ["trading", "public"]
+
+            [[services]]
+            id = "fks_engine"
+            name = "Engine"
+            service_type = "Engine"
+            health_endpoint = "http://127.0.0.1:9/health"
+            expected_response_time_ms = 500
+            critical = false
+            group = "trading"
+            tags = ["trading"]
+
+            [[services]]
+            id = "fks_web"
+            name = "Web"
+            service_type = "Web"
+            health_endpoint = "http://127.0.0.1:9/health"
+            expected_response_time_ms = 500
+            critical = false
+            tags = ["public"]
+        "#).unwrap();
+        let cfg = crate::config::Config { services: services["services"].clone().try_into().unwrap(), ..Default::default() };
+        let state = test_state(cfg, None).await;
+        let query = |tag: Option<&str>, group: Option<&str>| crate::service_query::ServicesQuery { tag: tag.map(str::to_string), group: group.map(str::to_string), ..Default::default() };
+        let ids = |services: Vec<serde_json::Value>| services.into_iter().map(|s| s["id"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+
+        let all = super::list_services(&state, &HeaderMap::new(), &query(None, None)).await.unwrap();
+        assert_eq!(all.len(), 3);
+        let api: crate::models::ServiceStatus = serde_json::from_value(all[0].clone()).unwrap();
+        assert_eq!((api.id.as_str(), api.group.as_deref(), api.tags.clone()), ("fks_api", Some("trading"), vec!["trading".to_string(), "public".to_string()]));
+        let tagged = super::list_services(&state, &HeaderMap::new(), &query(Some("public"), None)).await.unwrap();
+        assert_eq!(ids(tagged), ["fks_api", "fks_web"]);
+        let grouped = super::list_services(&state, &HeaderMap::new(), &query(Some("public"), Some("trading"))).await.unwrap();
+        assert_eq!(ids(grouped), ["fks_api"]);
+
+        let axum::Json(groups) = super::groups_handler(State(state.clone())).await;
+        let names: Vec<_> = groups.iter().map(|g| g["group"].as_str().unwrap()).collect();
+        assert_eq!(names, ["trading", super::UNGROUPED]);
+        assert_eq!(groups[0]["totalServices"], 2);
+        assert_eq!(groups[0]["services"], serde_json::json!(["fks_api", "fks_engine"]));
+        let health = super::aggregate_health(&state).await;
+        assert_eq!(health["groups"][0]["services"], groups[0]["services"]);
+        assert_eq!(health["healthScore"], 50.0, "nothing checked yet, so all unknown");
+        assert_eq!(health["groups"][0]["healthScore"], groups[0]["healthScore"]);
+    }
+
+    #[tokio::test]
+    async fn services_can_be_registered_updated_and_removed_at_runtime() {
+        use axum::extract::{Path, Query, State};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, Router::new().route("/health", get(|| async { "ok" }))).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitor.toml");
+        std::fs::write(&path, toml::to_string_pretty(&crate::config::Config::default()).unwrap()).unwrap();
+        let mut state = test_state(crate::config::Config::default(), Some("k")).await;
+        state.config_path = Some(path.clone());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let service: crate::models::ServiceConfig = serde_json::from_value(serde_json::json!({
+            "id": "fks_new", "name": "New", "service_type": "Api", "health_endpoint": format!("http://{addr}/health"),
+            "docker_container": null, "expected_response_time_ms": 500, "critical": false,
+        })).unwrap();
+        let persist = |persist| Query(super::PersistQuery { persist });
+
+        let denied = super::create_service_handler(State(state.clone()), HeaderMap::new(), persist(false), axum::Json(service.clone())).await;
+        assert_eq!(denied.unwrap_err().code, crate::problem::ErrorCode::Unauthorized);
+        let (code, _) = super::create_service_handler(State(state.clone()), headers.clone(), persist(true), axum::Json(service.clone())).await.unwrap();
+        assert_eq!(code, StatusCode::CREATED);
+        let saved = crate::config::Config::load_strict(&path).unwrap();
+        assert!(saved.services.iter().any(|s| s.id == "fks_new"), "written back to the config file");
+
+        // Checked right away rather than after the 30s interval
+        let healthy = async {
+            while state.monitor.get_service_health("fks_new").await.map(|h| h.status) != Some(crate::models::HealthStatus::Healthy) {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(5), healthy).await.expect("new service checked immediately");
+
+        let taken = super::create_service_handler(State(state.clone()), headers.clone(), persist(false), axum::Json(service.clone())).await;
+        assert_eq!(taken.unwrap_err().code, crate::problem::ErrorCode::Conflict);
+        let mut broken = service.clone();
+        broken.health_endpoint = "not a url".into();
+        let invalid = super::update_service_handler(Path("fks_new".into()), State(state.clone()), headers.clone(), persist(false), axum::Json(broken)).await.unwrap_err();
+        assert_eq!(invalid.code, crate::problem::ErrorCode::InvalidConfig);
+        assert_eq!(invalid.extensions["issues"][0]["path"], "services[fks_new].health_endpoint");
+        let mismatched = super::update_service_handler(Path("other".into()), State(state.clone()), headers.clone(), persist(false), axum::Json(service.clone())).await;
+        assert_eq!(mismatched.unwrap_err().code, crate::problem::ErrorCode::InvalidRequest);
+        let mut renamed = service.clone();
+        renamed.name = "Renamed".into();
+        assert!(super::update_service_handler(Path("fks_new".into()), State(state.clone()), headers.clone(), persist(false), axum::Json(renamed)).await.is_ok());
+        assert_eq!(state.monitor.get_all_services().await.iter().find(|s| s.id == "fks_new").unwrap().name, "Renamed");
+
+        let removed = super::delete_service_handler(Path("fks_new".into()), State(state.clone()), headers.clone(), persist(true)).await;
+        assert_eq!(removed.unwrap(), StatusCode::NO_CONTENT);
+        assert!(state.monitor.get_all_services().await.iter().all(|s| s.id != "fks_new"));
+        assert!(crate::config::Config::load_strict(&path).unwrap().services.iter().all(|s| s.id != "fks_new"));
+        let missing = super::delete_service_handler(Path("fks_new".into()), State(state), headers, persist(false)).await;
+        assert_eq!(missing.unwrap_err().code, crate::problem::ErrorCode::ServiceNotFound);
+    }
+
+    #[tokio::test]
+    #[serial_test::parallel(jwt_env)]
+    async fn promql_proxy_only_runs_allowlisted_queries() {
+        use axum::extract::{Query, State};
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let recorded = seen.clone();
+        let prometheus = Router::new().route("/api/v1/query_range", get(move |Query(params): Query<std::collections::HashMap<String, String>>| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(params["query"].clone());
+                axum::Json(serde_json::json!({"status": "success", "data": {"resultType": "matrix", "result": [
+                    {"metric": {"service_id": "fks_api"}, "values": [[1700000000, "0.12"], [1700000060, "0.2"]]}]}}))
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, prometheus).await.unwrap() });
+
+        let mut cfg = crate::config::Config::default();
+        let query = |query: &str, service: Option<&str>, range: Option<&str>| Query(super::PromqlQuery {
+            query: query.into(), service: service.map(str::to_string), range: range.map(str::to_string), step: None,
+        });
+        let problem = super::promql_handler(State(test_state(cfg.clone(), None).await), query("health_status", None, None)).await.unwrap_err();
+        assert_eq!(problem.code, ErrorCode::PrometheusUnavailable);
+
+        cfg.prometheus = Some(crate::config::PrometheusConfig { url: format!("http://{addr}"), bearer_token: None, queries: Default::default() });
+        let state = test_state(cfg, None).await;
+        let axum::Json(body) = super::promql_handler(State(state.clone()), query("response_time_p95", Some("fks_api"), Some("6h"))).await.unwrap();
+        assert_eq!(body["step"], 180);
+        assert_eq!(body["series"][0]["points"][1], serde_json::json!([1700000060.0, 0.2]));
+        assert!(seen.lock().unwrap()[0].contains(r#"service_id=~"fks_api""#));
+
+        let problem = super::promql_handler(State(state.clone()), query("up or vector(1)", None, None)).await.unwrap_err();
+        assert_eq!(problem.code, ErrorCode::InvalidRequest);
+        assert!(problem.extensions["allowed"].as_array().unwrap().contains(&serde_json::json!("health_status")));
+        let problem = super::promql_handler(State(state.clone()), query("health_status", Some("nope"), None)).await.unwrap_err();
+        assert_eq!(problem.code, ErrorCode::ServiceNotFound);
+        let problem = super::promql_handler(State(state), query("health_status", None, Some("30d"))).await.unwrap_err();
+        assert_eq!(problem.code, ErrorCode::InvalidRequest);
+        assert_eq!(seen.lock().unwrap().len(), 1, "rejected queries never reach Prometheus");
+    }
+
+    #[tokio::test]
+    async fn automation_freeze_shows_in_aggregate_health() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let denied = super::freeze_automation_handler(axum::extract::State(state.clone()), HeaderMap::new(), None).await;
+        assert_eq!(denied.unwrap_err().code, ErrorCode::Unauthorized);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let req = crate::automation::FreezeRequest { reason: Some("manual db failover".into()), ..Default::default() };
+        assert!(super::freeze_automation_handler(axum::extract::State(state.clone()), headers.clone(), Some(axum::Json(req))).await.is_ok());
+        let health = super::aggregate_health(&state).await;
+        assert_eq!(health["automation"]["frozen"], true);
+        assert_eq!(health["automation"]["freeze"]["reason"], "manual db failover");
+        assert!(state.monitor.get_system_metrics().await.automation_frozen);
+
+        let axum::Json(resumed) = super::resume_automation_handler(axum::extract::State(state.clone()), headers).await.unwrap();
+        assert_eq!(resumed["resumed"]["reason"], "manual db failover");
+        assert!(!state.monitor.automation_frozen());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial(jwt_env)]
+    async fn incidents_are_acknowledged_then_resolved_by_their_actors() {
+        std::env::set_var("FKS_WS_JWT_SECRET", "testsecret");
+        let cfg = crate::config::Config::default();
+        let open = crate::incident::Incidents::default();
+        let incident = open.open(&cfg.services[0].id, &cfg.services[0].name, chrono::Utc::now()).unwrap();
+        let snapshot = crate::monitor::MonitorSnapshot { incidents: open.snapshot(), ..Default::default() };
+        let monitor = crate::monitor::ServiceMonitor::new(cfg.clone()).await.unwrap().restore(snapshot).handle();
+        let state = AppState { monitor, ..test_state(cfg, Some("k")).await };
+        let app = Router::new()
+            .route("/api/incidents", get(super::list_incidents_handler))
+            .route("/api/incidents/{incident_id}/ack", axum::routing::post(super::acknowledge_incident_handler))
+            .route("/api/incidents/{incident_id}/resolve", axum::routing::post(super::resolve_incident_handler))
+            .with_state(state);
+        let claims = crate::auth::Claims { sub: "carol".into(), exp: 2_000_000_000, iat: None, iss: None, aud: None, roles: Some(vec!["admin".into()]) };
+        let jwt = jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &jsonwebtoken::EncodingKey::from_secret(b"testsecret")).unwrap();
+        let call = |uri: String, key: Option<&str>, body: &str| {
+            let mut req = Request::builder().uri(uri).method("POST").header("content-type", "application/json");
+            match key {
+                Some(jwt) if jwt.contains('.') => req = req.header("authorization", format!("Bearer {jwt}")),
+                Some(key) => req = req.header("x-api-key", key),
+                None => {}
+            }
+            app.clone().oneshot(req.body(Body::from(body.to_string())).unwrap())
+        };
+        let json = |res: axum::response::Response| async { serde_json::from_slice::<serde_json::Value>(&to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap() };
+        let ack = format!("/api/incidents/{}/ack", incident.id);
+        let resolve = format!("/api/incidents/{}/resolve", incident.id);
+
+        assert_eq!(call(ack.clone(), None, "{}").await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        let missing = call("/api/incidents/nope/ack".into(), Some("k"), "{}").await.unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        assert_eq!(json(missing).await["code"], "not_found");
+
+        let acked = json(call(ack.clone(), Some("k"), r#"{"by": "alice", "note": "looking"}"#).await.unwrap()).await;
+        assert_eq!((acked["state"].as_str(), acked["acknowledgements"][0]["by"].as_str()), (Some("acknowledged"), Some("alice")));
+        assert_eq!(acked["acknowledgements"][0]["self_reported"], true, "no token vouches for alice");
+        let acked = json(call(ack.clone(), Some("k"), "{}").await.unwrap()).await;
+        assert_eq!(acked["acknowledgements"][1]["by"], "api", "no actor given");
+        let acked = json(call(ack.clone(), Some(&jwt), r#"{"by": "mallory"}"#).await.unwrap()).await;
+        assert_eq!(acked["acknowledgements"][2]["by"], "carol", "the token subject wins over the body");
+        assert!(acked["acknowledgements"][2].get("self_reported").is_none());
+
+        let resolved = json(call(resolve.clone(), Some("k"), r#"{"by": "bob"}"#).await.unwrap()).await;
+        assert_eq!((resolved["state"].as_str(), resolved["resolved_by"].as_str()), (Some("resolved"), Some("bob")));
+        assert_eq!(resolved["resolved_by_self_reported"], true);
+        let again = call(resolve, Some("k"), "{}").await.unwrap();
+        assert_eq!(again.status(), StatusCode::CONFLICT);
+
+        let listed = app.clone().oneshot(Request::builder().uri("/api/incidents?state=resolved").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(json(listed).await["incidents"][0]["id"], incident.id.as_str());
+        std::env::remove_var("FKS_WS_JWT_SECRET");
+    }
+
+    #[tokio::test]
+    async fn webhooks_are_registered_listed_and_removed() {
+        let app = Router::new()
+            .route("/api/webhooks", get(super::list_webhooks_handler).post(super::create_webhook_handler))
+            .route("/api/webhooks/{subscription_id}", get(super::webhook_status_handler).delete(super::delete_webhook_handler))
+            .with_state(test_state(crate::config::Config::default(), Some("k")).await);
+        let call = |method: &str, uri: String, key: Option<&str>, body: &str| {
+            let mut req = Request::builder().uri(uri).method(method).header("content-type", "application/json");
+            if let Some(key) = key { req = req.header("x-api-key", key); }
+            app.clone().oneshot(req.body(Body::from(body.to_string())).unwrap())
+        };
+        let json = |res: axum::response::Response| async { serde_json::from_slice::<serde_json::Value>(&to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap() };
+        let body = r#"{"url": "http://127.0.0.1:9/hook", "events": ["ServiceDown", "IncidentOpened"]}"#;
+
+        assert_eq!(call("POST", "/api/webhooks".into(), None, body).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        let invalid = call("POST", "/api/webhooks".into(), Some("k"), r#"{"url": "not a url"}"#).await.unwrap();
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+        let created = call("POST", "/api/webhooks".into(), Some("k"), body).await.unwrap();
+        assert_eq!(created.status(), StatusCode::CREATED);
+        let created = json(created).await;
+        assert!(created["secret"].as_str().is_some_and(|s| s.len() >= 32));
+        let id = created["id"].as_str().unwrap().to_string();
+
+        let listed = json(call("GET", "/api/webhooks".into(), Some("k"), "").await.unwrap()).await;
+        assert_eq!(listed["subscriptions"][0]["events"], serde_json::json!(["ServiceDown", "IncidentOpened"]));
+        assert!(listed["subscriptions"][0].get("secret").is_none());
+        let status = json(call("GET", format!("/api/webhooks/{id}"), Some("k"), "").await.unwrap()).await;
+        assert_eq!(status["delivery"]["delivered"], 0);
+
+        assert_eq!(call("DELETE", format!("/api/webhooks/{id}"), Some("k"), "").await.unwrap().status(), StatusCode::NO_CONTENT);
+        let gone = call("GET", format!("/api/webhooks/{id}"), Some("k"), "").await.unwrap();
+        assert_eq!(gone.status(), StatusCode::NOT_FOUND);
+        assert_eq!(json(gone).await["code"], "not_found");
+    }
+
+    #[tokio::test]
+    #[serial_test::serial(jwt_env)]
+    async fn websocket_upgrades_need_credentials() {
+        std::env::remove_var("FKS_WS_JWT_SECRET");
+        let app = Router::new().route("/ws", get(super::websocket_handler)).with_state(test_state(crate::config::Config::default(), Some("k")).await);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let upgrade = |query: &str, header: Option<(&str, &str)>| {
+            let mut req = reqwest::Client::new().get(format!("http://{addr}/ws{query}"))
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", "13")
+                .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==");
+            if let Some((name, value)) = header { req = req.header(name, value); }
+            async move { req.send().await.unwrap() }
+        };
+
+        // API key only: tokens are never taken as JWTs
+        assert_eq!(upgrade("", None).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(upgrade("?token=wrong", None).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(upgrade("", Some(("authorization", "Bearer wrong"))).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(upgrade("?token=k", None).await.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(upgrade("", Some(("x-api-key", "k"))).await.status(), StatusCode::SWITCHING_PROTOCOLS);
+        let by_protocol = upgrade("", Some(("sec-websocket-protocol", "bearer, bearer.k"))).await;
+        assert_eq!(by_protocol.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(by_protocol.headers()["sec-websocket-protocol"], "bearer");
+
+        // With a JWT secret, tokens with an allowed role get in too
+        std::env::set_var("FKS_WS_JWT_SECRET", "testsecret");
+        let jwt = |role: &str| {
+            let claims = crate::auth::Claims { sub: "alice".into(), exp: 2_000_000_000, iat: None, iss: None, aud: None, roles: Some(vec![role.into()]) };
+            jsonwebtoken::encode(&jsonwebtoken::Header::default(), &claims, &jsonwebtoken::EncodingKey::from_secret(b"testsecret")).unwrap()
+        };
+        let (admin, viewer) = (jwt("admin"), jwt("viewer"));
+        assert_eq!(upgrade(&format!("?token={admin}"), None).await.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(upgrade("", Some(("sec-websocket-protocol", &format!("bearer, bearer.{admin}")))).await.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(upgrade(&format!("?token={viewer}"), None).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(upgrade("?token=wrong", None).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(upgrade("?token=k", None).await.status(), StatusCode::SWITCHING_PROTOCOLS);
+        std::env::remove_var("FKS_WS_JWT_SECRET");
+    }
+
+    #[tokio::test]
+    async fn event_stream_is_redacted_for_read_only_callers() {
+        use futures::StreamExt;
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let app = Router::new().route("/api/events/stream", get(super::event_stream_handler)).with_state(state.clone());
+        let open = |key: Option<&str>| {
+            let mut req = Request::builder().uri("/api/events/stream?service=db");
+            if let Some(key) = key { req = req.header("x-api-key", key); }
+            let app = app.clone();
+            let req = req.body(Body::empty()).unwrap();
+            async move { app.oneshot(req).await.unwrap().into_body().into_data_stream() }
+        };
+        // Reads frames until the first `event` one
+        async fn next_event(body: &mut (impl futures::Stream<Item = Result<axum::body::Bytes, axum::Error>> + Unpin)) -> serde_json::Value {
+            loop {
+                let chunk = tokio::time::timeout(std::time::Duration::from_secs(2), body.next()).await.expect("frame").unwrap().unwrap();
+                let text = String::from_utf8(chunk.to_vec()).unwrap();
+                if let Some(data) = text.strip_prefix("event: event\ndata: ") {
+                    return serde_json::from_str(data.trim_end()).unwrap();
+                }
+            }
+        }
+        let (mut anonymous, mut operator) = (open(None).await, open(Some("k")).await);
+        state.monitor.inject_event(crate::models::MonitorEvent {
+            event_type: crate::models::EventType::ServiceDown,
+            service_id: Some("db".into()),
+            message: "probe of http://db.internal:5432/health failed".into(),
+            timestamp: chrono::Utc::now(),
+            data: Some(serde_json::json!({"endpoint": "http://db.internal:5432/health"})),
+            metadata: Default::default(),
+        });
+
+        let redacted = next_event(&mut anonymous).await;
+        assert!(!redacted["event"]["message"].as_str().unwrap().contains("db.internal"));
+        assert!(redacted["event"]["data"].is_null());
+        let full = next_event(&mut operator).await;
+        assert!(full["event"]["message"].as_str().unwrap().contains("db.internal"));
+        assert_eq!(full["event"]["data"]["endpoint"], "http://db.internal:5432/health");
+    }
+
+    #[tokio::test]
+    async fn maintenance_shows_as_overall_status_until_ended() {
+        let state = test_state(crate::config::Config::default(), Some("k")).await;
+        let req = || axum::Json(crate::silence::MaintenanceRequest { reason: Some("rack move".into()), duration_minutes: Some(60), ..Default::default() });
+        let denied = super::start_maintenance_handler(axum::extract::State(state.clone()), HeaderMap::new(), req()).await;
+        assert_eq!(denied.unwrap_err().code, ErrorCode::Unauthorized);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "k".parse().unwrap());
+        let endless = super::start_maintenance_handler(axum::extract::State(state.clone()), headers.clone(), axum::Json(Default::default())).await;
+        assert_eq!(endless.unwrap_err().code, ErrorCode::InvalidRequest, "maintenance needs an end");
+        let axum::Json(started) = super::start_maintenance_handler(axum::extract::State(state.clone()), headers.clone(), req()).await.unwrap();
+        let health = super::aggregate_health(&state).await;
+        assert_eq!(health["overallStatus"], "maintenance");
+        assert_eq!(health["maintenance"]["until"], started["maintenance"]["until"]);
+        assert_eq!(health["maintenance"]["reason"], "rack move");
+
+        let axum::Json(ended) = super::end_maintenance_handler(axum::extract::State(state.clone()), headers).await.unwrap();
+        assert_eq!(ended["ended"]["reason"], "rack move");
+        let health = super::aggregate_health(&state).await;
+        assert_ne!(health["overallStatus"], "maintenance");
+        assert!(health["maintenance"].is_null());
     }
 }
 
 
+/// Subprotocol naming a handshake token as `bearer.<token>`, for browsers, which cannot set headers
+/// on a WebSocket; the bare `bearer` is echoed back.
+const WS_TOKEN_PROTOCOL: &str = "bearer";
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct WsHandshakeQuery {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Token sent with the handshake as `?token=` or a `bearer.<token>` subprotocol.
+fn ws_handshake_token(headers: &axum::http::HeaderMap, query: Option<String>) -> Option<String> {
+    query.filter(|t| !t.is_empty()).or_else(|| {
+        headers.get_all(axum::http::header::SEC_WEBSOCKET_PROTOCOL).iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .find_map(|p| p.trim().strip_prefix("bearer.").filter(|t| !t.is_empty()).map(str::to_string))
+    })
+}
+
+/// The HTTP rules: `x-api-key` or `Authorization: Bearer` headers, or a handshake token that is
+/// the API key or, when a JWT secret is set, a JWT.
+fn ws_authorized(state: &AppState, headers: &axum::http::HeaderMap, token: Option<&str>) -> bool {
+    if is_authorized(state, headers) { return true; }
+    let Some(token) = token else { return false };
+    state.api_key.as_deref().is_some_and(|key| subtle_equals(key, token))
+        || (crate::auth::jwt_configured() && crate::auth::authorize_jwt(Some(token)))
+}
+
+/// Upgrades are refused with 401 unless they carry the credentials HTTP requests need.
 async fn websocket_handler(
-    ws: WebSocketUpgrade,
     State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<WsHandshakeQuery>,
+    ws: WebSocketUpgrade,
 ) -> Response {
+    use axum::response::IntoResponse;
+    let token = ws_handshake_token(&headers, query.token);
+    if !ws_authorized(&state, &headers, token.as_deref()) {
+        tracing::warn!("unauthorized WebSocket upgrade attempt");
+        return Problem::unauthorized().into_response();
+    }
+    let bearer = headers.get(axum::http::header::AUTHORIZATION).and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("Bearer ")).map(str::to_string);
+    let identity = token.or(bearer).and_then(|t| crate::auth::jwt_subject(t.trim()));
     let monitor = state.monitor.clone();
-    ws.on_upgrade(|socket| websocket::handle_websocket(socket, monitor))
+    let (hub, confirmations) = (state.ws_hub.clone(), state.confirmations.clone());
+    let user_agent = headers.get(axum::http::header::USER_AGENT).and_then(|v| v.to_str().ok()).map(str::to_string);
+    ws.protocols([WS_TOKEN_PROTOCOL])
+        // Only authorized upgrades get this far
+        .on_upgrade(move |socket| websocket::handle_websocket(socket, monitor, hub, confirmations, user_agent, true, identity))
+}
+
+async fn ws_clients_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<Vec<websocket::ClientInfo>>, Problem> {
+    if !is_authorized(&state, &headers) { return Err(Problem::unauthorized()); }
+    Ok(Json(state.ws_hub.clients()))
+}
+
+async fn disconnect_ws_client_handler(
+    axum::extract::Path(client_id): axum::extract::Path<uuid::Uuid>,
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> Result<StatusCode, Problem> {
+    if !is_authorized(&state, &headers) {
+        tracing::warn!("unauthorized WebSocket disconnect attempt");
+        return Err(Problem::unauthorized());
+    }
+    if !state.ws_hub.disconnect(client_id) {
+        return Err(Problem::new(ErrorCode::NotFound, format!("No WebSocket client {client_id} is connected")).with("client_id", client_id));
+    }
+    info!(%client_id, "🔌 WebSocket client disconnected by administrator");
+    Ok(StatusCode::NO_CONTENT)
 }
 
 #[derive(Clone)]
 struct AppState {
     monitor: monitor::MonitorHandle,
     api_key: Option<String>,
+    alerts: std::sync::Arc<alerts::AlertDispatcher>,
+    ws_hub: websocket::WsHub,
+    graphql: graphql::DashboardSchema,
+    /// Latest endpoint validation report (startup pass or on-demand refresh)
+    endpoint_validation: std::sync::Arc<tokio::sync::RwLock<Option<validation::ValidationReport>>>,
+    /// Config file that `?persist=true` service changes are written back to (none in replay mode)
+    config_path: Option<std::path::PathBuf>,
+    /// Serializes runtime service changes so concurrent requests do not overwrite each other
+    service_changes: std::sync::Arc<tokio::sync::Mutex<()>>,
+    /// Slots and per-project locks of `/api/compose` actions
+    compose_queue: compose_queue::ComposeQueue,
+    /// Last runs of `[[schedules]]`
+    schedules: schedule::Schedules,
+    /// Armed actions awaiting confirmation
+    confirmations: confirmation::Confirmations,
+    /// Subscriptions registered through `/api/webhooks`
+    webhooks: webhooks::WebhookRegistry,
+}
+
+/// Two-step confirmation of a destructive `action` when `[confirmation]` requires it; `key` (default:
+/// the action itself) is what the token is bound to.
+fn confirm(state: &AppState, headers: &axum::http::HeaderMap, action: &str, key: Option<&str>) -> Result<(), confirmation::Gate> {
+    let key = key.map_or_else(|| action.to_string(), |key| format!("{action}\n{key}"));
+    state.confirmations.check(&state.monitor.config().confirmation, &key, action, confirmation::token(headers), state.monitor.now())
 }
 
 fn is_authorized(state: &AppState, headers: &axum::http::HeaderMap) -> bool {
@@ -437,16 +3037,22 @@ fn is_authorized(state: &AppState, headers: &axum::http::HeaderMap) -> bool {
         }
         // Fall through to JWT if present
     }
-    // 2. JWT Bearer token (if secret configured)
-    if headers.get(axum::http::header::AUTHORIZATION).and_then(|v| v.to_str().ok()).map(|s| s.to_string()).map(|s| {
+    // 2. JWT Bearer token (if secret configured; without one every token would pass)
+    if crate::auth::jwt_configured() && headers.get(axum::http::header::AUTHORIZATION).and_then(|v| v.to_str().ok()).map(|s| s.to_string()).map(|s| {
         let parts: Vec<&str> = s.split_whitespace().collect();
         if parts.len()==2 && parts[0].eq_ignore_ascii_case("Bearer") { crate::auth::authorize_jwt(Some(parts[1])) } else { false }
     }).unwrap_or(false) { return true; }
     // 3. If neither API key nor secret configured -> open
-    if state.api_key.is_none() && std::env::var("FKS_WS_JWT_SECRET").is_err() { return true; }
+    if state.api_key.is_none() && !crate::auth::jwt_configured() { return true; }
     false
 }
 
+/// Read-only callers (viewer-role tokens, anonymous users when auth is configured) get a
+/// redacted view without internal endpoints, container names or hosts.
+fn redact_for_caller<T: redact::Redact>(state: &AppState, headers: &axum::http::HeaderMap, value: T) -> T {
+    if is_authorized(state, headers) { value } else { value.redact() }
+}
+
 fn subtle_equals(a: &str, b: &str) -> bool {
     if a.len() != b.len() { return false; }
     let mut diff = 0u8;
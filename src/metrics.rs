@@ -22,6 +22,18 @@ pub static PROMETHEUS_REGISTRY: Lazy<Registry> = Lazy::new(|| {
     registry
         .register(Box::new(SERVICE_RESTART_TOTAL.clone()))
         .expect("Failed to register service_restart_total");
+    registry
+        .register(Box::new(CONTAINER_ACTION_TOTAL.clone()))
+        .expect("Failed to register container_action_total");
+    registry
+        .register(Box::new(HOOK_RUNS_TOTAL.clone()))
+        .expect("Failed to register hook_runs_total");
+    registry
+        .register(Box::new(WEBHOOK_DELIVERIES_TOTAL.clone()))
+        .expect("Failed to register webhook_deliveries_total");
+    registry
+        .register(Box::new(HEALING_ACTIONS_TOTAL.clone()))
+        .expect("Failed to register healing_actions_total");
     registry
         .register(Box::new(MONITOR_UPTIME.clone()))
         .expect("Failed to register monitor_uptime");
@@ -49,9 +61,57 @@ pub static PROMETHEUS_REGISTRY: Lazy<Registry> = Lazy::new(|| {
     registry
         .register(Box::new(COMPOSE_ACTION_DURATION_SECONDS.clone()))
         .expect("Failed to register compose_action_duration_seconds");
+    registry
+        .register(Box::new(COMPOSE_ACTIONS_INFLIGHT.clone()))
+        .expect("Failed to register compose_actions_inflight");
     registry
         .register(Box::new(SERVICE_RESTART_DURATION_SECONDS.clone()))
         .expect("Failed to register service_restart_duration_seconds");
+    registry
+        .register(Box::new(ALERT_DELIVERY_FAILURES_TOTAL.clone()))
+        .expect("Failed to register alert_delivery_failures_total");
+    registry
+        .register(Box::new(ALERT_DELIVERIES_TOTAL.clone()))
+        .expect("Failed to register alert_deliveries_total");
+    registry
+        .register(Box::new(ALERT_DELIVERY_DURATION_SECONDS.clone()))
+        .expect("Failed to register alert_delivery_duration_seconds");
+    registry
+        .register(Box::new(ALERT_DELIVERY_RETRIES_TOTAL.clone()))
+        .expect("Failed to register alert_delivery_retries_total");
+    registry
+        .register(Box::new(ALERT_DROPPED_TOTAL.clone()))
+        .expect("Failed to register alert_dropped_total");
+    registry
+        .register(Box::new(ALERT_SUPPRESSED_TOTAL.clone()))
+        .expect("Failed to register alert_suppressed_total");
+    registry
+        .register(Box::new(WATCHDOG_STALLED.clone()))
+        .expect("Failed to register watchdog_stalled");
+    registry
+        .register(Box::new(SERVICE_RESTART_CIRCUIT_OPEN.clone()))
+        .expect("Failed to register service_restart_circuit_open");
+    registry
+        .register(Box::new(OUTBOUND_REQUESTS_TOTAL.clone()))
+        .expect("Failed to register outbound_requests_total");
+    registry
+        .register(Box::new(OUTBOUND_REQUEST_DURATION_SECONDS.clone()))
+        .expect("Failed to register outbound_request_duration_seconds");
+    registry
+        .register(Box::new(OUTBOUND_CIRCUIT_STATE.clone()))
+        .expect("Failed to register outbound_circuit_state");
+    registry
+        .register(Box::new(SERVICE_INFO.clone()))
+        .expect("Failed to register service_info");
+    registry
+        .register(Box::new(ACTIVE_INCIDENT_INFO.clone()))
+        .expect("Failed to register active_incident_info");
+    registry
+        .register(Box::new(SERVICES_BY_STATUS.clone()))
+        .expect("Failed to register services_by_status");
+    registry
+        .register(Box::new(SERVICE_IMAGE_STALE.clone()))
+        .expect("Failed to register service_image_stale");
     // Resource usage gauges
     registry.register(Box::new(SERVICE_CPU_PERCENT.clone())).ok();
     registry.register(Box::new(SERVICE_MEMORY_MB.clone())).ok();
@@ -63,12 +123,12 @@ pub static PROMETHEUS_REGISTRY: Lazy<Registry> = Lazy::new(|| {
     registry
 });
 
-// Service health status (0=unknown, 1=healthy, 2=degraded, 3=unhealthy)
+// Service health status (0=unknown, 1=healthy, 2=degraded, 3=unhealthy, 4=paused)
 pub static SERVICE_HEALTH_STATUS: Lazy<IntGaugeVec> = Lazy::new(|| {
     IntGaugeVec::new(
         prometheus::Opts::new(
             "fks_service_health_status",
-            "Current health status of FKS services (0=unknown, 1=healthy, 2=degraded, 3=unhealthy)"
+            "Current health status of FKS services (0=unknown, 1=healthy, 2=degraded, 3=unhealthy, 4=paused)"
         ),
         &["service_id", "service_name", "service_type", "critical"]
     ).expect("Failed to create service_health_status metric")
@@ -107,6 +167,47 @@ pub static SERVICE_RESTART_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
     ).expect("Failed to create service_restarts_total metric")
 });
 
+// Container lifecycle actions (stop, start, pause, unpause, kill)
+pub static CONTAINER_ACTION_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_container_actions_total",
+            "Total number of container lifecycle actions by action and outcome"
+        ),
+        &["service_id", "action", "success"]
+    ).expect("Failed to create container_actions_total metric")
+});
+
+pub static HOOK_RUNS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_hook_runs_total",
+            "Total number of remediation hook runs by hook and outcome"
+        ),
+        &["hook", "success"]
+    ).expect("Failed to create hook_runs_total metric")
+});
+
+pub static WEBHOOK_DELIVERIES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_webhook_deliveries_total",
+            "Total number of events delivered to webhook subscriptions by subscription and outcome"
+        ),
+        &["subscription", "success"]
+    ).expect("Failed to create webhook_deliveries_total metric")
+});
+
+pub static HEALING_ACTIONS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_healing_actions_total",
+            "Total number of healing rule firings by rule, action and outcome (enforced, dry_run or held)"
+        ),
+        &["rule", "action", "outcome"]
+    ).expect("Failed to create healing_actions_total metric")
+});
+
 // Monitor uptime
 pub static MONITOR_UPTIME: Lazy<IntCounter> = Lazy::new(|| {
     IntCounter::new(
@@ -188,6 +289,13 @@ pub static COMPOSE_ACTION_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     ).expect("compose_action_duration_seconds")
 });
 
+pub static COMPOSE_ACTIONS_INFLIGHT: Lazy<IntGauge> = Lazy::new(|| {
+    IntGauge::new(
+        "fks_compose_actions_inflight",
+        "Compose actions currently running (not counting those waiting for a slot)"
+    ).expect("compose_actions_inflight")
+});
+
 pub static SERVICE_RESTART_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     HistogramVec::new(
         prometheus::HistogramOpts::new(
@@ -198,6 +306,152 @@ pub static SERVICE_RESTART_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     ).expect("service_restart_duration_seconds")
 });
 
+pub static ALERT_DELIVERY_FAILURES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_alert_delivery_failures_total",
+            "Total number of alert notifications that could not be delivered after retries"
+        ),
+        &["channel"]
+    ).expect("Failed to create alert_delivery_failures_total metric")
+});
+
+pub static ALERT_DELIVERIES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_alert_deliveries_total",
+            "Total alert delivery attempts per channel (after retries)"
+        ),
+        &["channel", "success"]
+    ).expect("Failed to create alert_deliveries_total metric")
+});
+
+pub static ALERT_DELIVERY_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "fks_alert_delivery_duration_seconds",
+            "Time to deliver an alert to a channel, including retries"
+        ).buckets(vec![0.05,0.1,0.25,0.5,1.0,2.5,5.0,10.0,30.0]),
+        &["channel"]
+    ).expect("alert_delivery_duration_seconds")
+});
+
+pub static ALERT_DELIVERY_RETRIES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_alert_delivery_retries_total",
+            "Total alert delivery retry attempts per channel"
+        ),
+        &["channel"]
+    ).expect("Failed to create alert_delivery_retries_total metric")
+});
+
+pub static ALERT_DROPPED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_alert_dropped_total",
+            "Alerts dropped without delivery or dead-lettering (dispatcher lag, dead-letter overflow)"
+        ),
+        &["channel"]
+    ).expect("Failed to create alert_dropped_total metric")
+});
+
+pub static ALERT_SUPPRESSED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_alert_suppressed_total",
+            "Alerts not delivered individually (duplicate = dropped, held = folded into a batch summary)"
+        ),
+        &["reason"]
+    ).expect("Failed to create alert_suppressed_total metric")
+});
+
+// Internal loops the self watchdog considers stalled (1 = stalled)
+pub static SERVICE_RESTART_CIRCUIT_OPEN: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        prometheus::Opts::new(
+            "fks_service_restart_circuit_open",
+            "Whether restarts of a service have been given up on after repeated restarts without recovery"
+        ),
+        &["service_id", "service_name"]
+    ).expect("Failed to create service_restart_circuit_open metric")
+});
+
+pub static WATCHDOG_STALLED: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        prometheus::Opts::new(
+            "fks_watchdog_stalled",
+            "Whether an internal component (monitoring_loop, metrics_loop, event_bus) has stopped making progress"
+        ),
+        &["component"]
+    ).expect("Failed to create watchdog_stalled metric")
+});
+pub static OUTBOUND_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        prometheus::Opts::new(
+            "fks_outbound_requests_total",
+            "Outbound HTTP calls made by the monitor by destination class and outcome"
+        ),
+        &["class", "outcome"]
+    ).expect("outbound_requests_total")
+});
+pub static OUTBOUND_REQUEST_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "fks_outbound_request_duration_seconds",
+            "Duration of outbound HTTP calls made by the monitor"
+        ).buckets(vec![0.01,0.05,0.1,0.25,0.5,1.0,2.5,5.0,10.0]),
+        &["class", "outcome"]
+    ).expect("outbound_request_duration_seconds")
+});
+pub static OUTBOUND_CIRCUIT_STATE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        prometheus::Opts::new(
+            "fks_outbound_circuit_state",
+            "Circuit breaker state per outbound destination (0=closed, 1=half-open, 2=open)"
+        ),
+        &["class", "destination"]
+    ).expect("outbound_circuit_state")
+});
+
+// Info-style metrics (always 1) carrying metadata to join with the status metrics in PromQL
+pub static SERVICE_INFO: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        prometheus::Opts::new(
+            "fks_service_info",
+            "Monitored service metadata; version, owner and team come from the service's labels, tags are comma separated, host is the Docker daemon of its container (always 1)"
+        ),
+        &["service_id", "service_name", "service_type", "critical", "group", "tags", "version", "owner", "team", "host"]
+    ).expect("service_info")
+});
+pub static ACTIVE_INCIDENT_INFO: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        prometheus::Opts::new(
+            "fks_active_incident_info",
+            "Services currently down, with severity and start time (always 1)"
+        ),
+        &["service_id", "service_name", "severity", "since", "silenced"]
+    ).expect("active_incident_info")
+});
+pub static SERVICE_IMAGE_STALE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        prometheus::Opts::new(
+            "fks_service_image_stale",
+            "Whether the registry has a newer digest for the image tag a service's container runs (1 = stale)"
+        ),
+        &["service_id", "image"]
+    ).expect("service_image_stale")
+});
+pub static SERVICES_BY_STATUS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    IntGaugeVec::new(
+        prometheus::Opts::new(
+            "fks_services_by_status",
+            "Number of monitored services in each health status"
+        ),
+        &["status"]
+    ).expect("services_by_status")
+});
+
 static TOTAL_HTTP_REQUESTS: Lazy<AtomicU64> = Lazy::new(|| AtomicU64::new(0));
 
 // ----- Resource Usage Gauges -----
@@ -234,6 +488,7 @@ pub fn update_service_health_metric(
         crate::models::HealthStatus::Healthy => 1,
         crate::models::HealthStatus::Degraded => 2,
         crate::models::HealthStatus::Unhealthy => 3,
+        crate::models::HealthStatus::Paused => 4,
     };
     
     SERVICE_HEALTH_STATUS
@@ -241,6 +496,11 @@ pub fn update_service_health_metric(
         .set(status_value);
 }
 
+/// Drop the health series of a service removed by a config reload.
+pub fn forget_service_health(service_id: &str, service_name: &str, service_type: &str, critical: bool) {
+    let _ = SERVICE_HEALTH_STATUS.remove_label_values(&[service_id, service_name, service_type, &critical.to_string()]);
+}
+
 pub fn record_service_response_time(
     service_id: &str,
     service_name: &str,
@@ -272,6 +532,30 @@ pub fn increment_service_restart(
         .inc();
 }
 
+pub fn increment_container_action(service_id: &str, action: &str, success: bool) {
+    CONTAINER_ACTION_TOTAL
+        .with_label_values(&[service_id, action, &success.to_string()])
+        .inc();
+}
+
+pub fn increment_hook_run(hook: &str, success: bool) {
+    HOOK_RUNS_TOTAL
+        .with_label_values(&[hook, &success.to_string()])
+        .inc();
+}
+
+pub fn increment_webhook_delivery(subscription: &str, success: bool) {
+    WEBHOOK_DELIVERIES_TOTAL
+        .with_label_values(&[subscription, &success.to_string()])
+        .inc();
+}
+
+pub fn increment_healing_action(rule: &str, action: &str, outcome: &str) {
+    HEALING_ACTIONS_TOTAL
+        .with_label_values(&[rule, action, outcome])
+        .inc();
+}
+
 pub fn increment_websocket_connections() {
     ACTIVE_WEBSOCKET_CONNECTIONS.inc();
 }
@@ -280,6 +564,10 @@ pub fn decrement_websocket_connections() {
     ACTIVE_WEBSOCKET_CONNECTIONS.dec();
 }
 
+pub fn active_websocket_connections() -> i64 {
+    ACTIVE_WEBSOCKET_CONNECTIONS.get()
+}
+
 pub fn update_service_error_rate(
     service_id: &str,
     service_name: &str,
@@ -324,14 +612,115 @@ pub fn observe_compose_action_duration(action: &str, seconds: f64) {
         .observe(seconds);
 }
 
+pub fn set_compose_actions_inflight(count: usize) {
+    COMPOSE_ACTIONS_INFLIGHT.set(count as i64);
+}
+
 pub fn observe_service_restart_duration(service_id: &str, seconds: f64) {
     SERVICE_RESTART_DURATION_SECONDS
         .with_label_values(&[service_id])
         .observe(seconds);
 }
 
+pub fn increment_alert_delivery_failure(channel: &str) {
+    ALERT_DELIVERY_FAILURES_TOTAL
+        .with_label_values(&[channel])
+        .inc();
+}
+
+pub fn record_alert_delivery(channel: &str, success: bool, seconds: f64) {
+    ALERT_DELIVERIES_TOTAL
+        .with_label_values(&[channel, &success.to_string()])
+        .inc();
+    ALERT_DELIVERY_DURATION_SECONDS
+        .with_label_values(&[channel])
+        .observe(seconds);
+}
+
+pub fn increment_alert_delivery_retry(channel: &str) {
+    ALERT_DELIVERY_RETRIES_TOTAL
+        .with_label_values(&[channel])
+        .inc();
+}
+
+pub fn increment_alert_dropped(channel: &str) {
+    ALERT_DROPPED_TOTAL
+        .with_label_values(&[channel])
+        .inc();
+}
+
+pub fn increment_alert_suppressed(reason: &str) {
+    ALERT_SUPPRESSED_TOTAL
+        .with_label_values(&[reason])
+        .inc();
+}
+
+pub fn set_watchdog_stalled(component: &str, stalled: bool) {
+    WATCHDOG_STALLED
+        .with_label_values(&[component])
+        .set(stalled as i64);
+}
+
+pub fn set_restart_circuit_open(service_id: &str, service_name: &str, open: bool) {
+    SERVICE_RESTART_CIRCUIT_OPEN
+        .with_label_values(&[service_id, service_name])
+        .set(open as i64);
+}
+
+/// Replace the image staleness gauges with the latest check: `(service_id, image, stale)`.
+pub fn set_image_staleness(images: &[(String, String, bool)]) {
+    SERVICE_IMAGE_STALE.reset();
+    for (service_id, image, stale) in images {
+        SERVICE_IMAGE_STALE
+            .with_label_values(&[service_id.as_str(), image.as_str()])
+            .set(*stale as i64);
+    }
+}
+
+pub fn record_outbound_request(class: &str, outcome: &str, seconds: f64) {
+    OUTBOUND_REQUESTS_TOTAL
+        .with_label_values(&[class, outcome])
+        .inc();
+    OUTBOUND_REQUEST_DURATION_SECONDS
+        .with_label_values(&[class, outcome])
+        .observe(seconds);
+}
+
+pub fn set_outbound_circuit_state(class: &str, destination: &str, state: i64) {
+    OUTBOUND_CIRCUIT_STATE
+        .with_label_values(&[class, destination])
+        .set(state);
+}
+
+/// Rebuild the info-style metrics from the current services, statuses and incidents. Called on
+/// every scrape so removed services and resolved incidents disappear right away.
+pub fn set_info_metrics(services: &[crate::models::ServiceConfig], statuses: &[crate::models::ServiceStatus], incidents: &[crate::models::ActiveIncident]) {
+    SERVICE_INFO.reset();
+    for s in services {
+        let label = |key: &str| s.labels.get(key).map(String::as_str).unwrap_or_default();
+        SERVICE_INFO
+            .with_label_values(&[
+                &s.id, &s.name, &format!("{:?}", s.service_type), &s.critical.to_string(),
+                s.group.as_deref().unwrap_or_default(), &s.tags.join(","), label("version"), label("owner"), label("team"),
+                &crate::docker_hosts::status_label(s).unwrap_or_default(),
+            ])
+            .set(1);
+    }
+    ACTIVE_INCIDENT_INFO.reset();
+    for i in incidents {
+        ACTIVE_INCIDENT_INFO
+            .with_label_values(&[&i.service_id, &i.service_name, i.severity.as_str(), &i.since.to_rfc3339_opts(chrono::SecondsFormat::Secs, true), &i.silenced.to_string()])
+            .set(1);
+    }
+    for status in ["healthy", "degraded", "unhealthy", "unknown"] {
+        let count = statuses.iter().filter(|s| format!("{:?}", s.status).eq_ignore_ascii_case(status)).count();
+        SERVICES_BY_STATUS.with_label_values(&[status]).set(count as i64);
+    }
+}
+
 pub fn get_total_http_requests() -> u64 { TOTAL_HTTP_REQUESTS.load(Ordering::Relaxed) }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_service_resource_metrics(
     service_id: &str,
     service_name: &str,
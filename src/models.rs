@@ -1,18 +1,192 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use utoipa::{IntoParams, ToSchema};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, ToSchema)]
 pub struct ServiceConfig {
     pub id: String,
     pub name: String,
+    /// Not probed for heartbeat (push-based) services
+    #[serde(default)]
     pub health_endpoint: String,
     pub service_type: ServiceType,
     pub docker_container: Option<String>,
+    /// Entry of `[hosts]`, or a daemon URL, the container runs on (default: the local daemon)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docker_host: Option<String>,
     pub expected_response_time_ms: u64,
     pub critical: bool,
+    /// Per-service override of monitoring.check_interval_seconds (e.g. slower polling of third-party URLs)
+    #[serde(default)]
+    pub check_interval_seconds: Option<u64>,
+    /// Dead-man's switch: healthy only if POST /api/heartbeat/{id} was received within this window
+    #[serde(default)]
+    pub heartbeat_window_seconds: Option<u64>,
+    /// Ids of services this one needs (e.g. imported from compose `depends_on`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Free-form labels used by alert routing (e.g. "trading", "team-data")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Extra probes combined with the health endpoint according to `probe_mode`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub probes: Vec<ProbeConfig>,
+    #[serde(default, skip_serializing_if = "ProbeMode::is_all")]
+    pub probe_mode: ProbeMode,
+    /// Key/value labels exposed to alert templates as `{label.<key>}` (e.g. team = "data", environment = "prod")
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Dashboard/API grouping (e.g. "trading", "data-pipeline"); health is aggregated per group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Opaque key/values for downstream consumers (fks_web, runbook bots); passed through to statuses, events and alerts untouched
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Pull and recreate the container when its image tag gets a new build, during a maintenance window
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_update: bool,
+    /// Pods that run the service in Kubernetes, instead of a `docker_container`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kubernetes: Option<KubernetesTarget>,
+    /// Host systemd unit that runs the service (e.g. "nginx.service"), for daemons outside containers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub systemd_unit: Option<String>,
+    /// Restart the service after repeated failed checks (see `auto_restart.rs`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_restart: Option<AutoRestartPolicy>,
+    /// Asked to finish in-flight work before the service is restarted (see `drain.rs`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drain: Option<DrainConfig>,
+    /// The service's own Prometheus endpoint, re-exposed under `/metrics/federated` (see `federation.rs`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricsEndpoint>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a service exposes its own Prometheus metrics and which families to pass on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct MetricsEndpoint {
+    /// e.g. `http://fks_api:8000/metrics`
+    pub url: String,
+    /// Metric family names to pass on; a trailing `*` matches a prefix (`http_*`). Empty = all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+}
+
+/// When the monitor restarts a failing service on its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct AutoRestartPolicy {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Consecutive failed checks that trigger a restart
+    #[serde(default = "default_auto_restart_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Restarts allowed within any hour; past that the service is left alone and an alert raised
+    #[serde(default = "default_auto_restart_max_attempts")]
+    pub max_attempts_per_hour: u32,
+}
+
+fn default_true() -> bool { true }
+fn default_auto_restart_failure_threshold() -> u32 { 3 }
+fn default_auto_restart_max_attempts() -> u32 { 3 }
+
+/// How a service is drained before a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct DrainConfig {
+    /// Tells the service to stop taking new work, e.g. `http://fks_execution:8000/admin/drain`
+    pub url: String,
+    #[serde(default = "default_drain_method")]
+    pub method: String,
+    /// Polled with GET until it answers 200; any other status means work is still in flight. Unset =
+    /// drained as soon as `url` accepts the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_url: Option<String>,
+    /// Longest wait for the drain, requests included
+    #[serde(default = "default_drain_timeout_seconds")]
+    pub timeout_seconds: u64,
+    #[serde(default = "default_drain_poll_interval_seconds")]
+    pub poll_interval_seconds: u64,
+    /// Restart even when the drain fails or times out; by default the restart is called off
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force: bool,
+}
+
+fn default_drain_method() -> String { "POST".into() }
+fn default_drain_timeout_seconds() -> u64 { 120 }
+fn default_drain_poll_interval_seconds() -> u64 { 2 }
+
+/// Where a service runs in Kubernetes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct KubernetesTarget {
+    #[serde(default = "default_kubernetes_namespace")]
+    pub namespace: String,
+    /// Label selector of the service's pods, e.g. `app=fks-api`
+    pub selector: String,
+    /// Restarted with a rollout restart: `name` for a deployment, or `statefulset/name` / `daemonset/name`.
+    /// Without it a restart deletes the selected pods and lets their controller replace them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workload: Option<String>,
+}
+
+fn default_kubernetes_namespace() -> String { "default".into() }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProbeConfig {
+    /// GET must return 2xx
+    Http {
+        url: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// TCP connect to "host:port"
+    Tcp {
+        address: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Docker healthcheck (or running state) of `container`, defaulting to the service's `docker_container`
+    Docker {
+        #[serde(default)]
+        container: Option<String>,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Active state of a systemd `unit`, defaulting to the service's `systemd_unit`
+    Systemd {
+        #[serde(default)]
+        unit: Option<String>,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Readiness of the pods matching `selector` in `namespace`, defaulting to the service's `kubernetes` target
+    Kubernetes {
+        #[serde(default)]
+        selector: Option<String>,
+        #[serde(default)]
+        namespace: Option<String>,
+        #[serde(default)]
+        name: Option<String>,
+    },
+}
+
+/// How probe results combine: `all` must pass, `any` one must pass, `quorum` a strict majority must pass.
+/// A passing service with some failing probes is Degraded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbeMode {
+    #[default]
+    All,
+    Any,
+    Quorum,
+}
+
+impl ProbeMode {
+    pub fn is_all(&self) -> bool { *self == ProbeMode::All }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, ToSchema)]
 pub enum ServiceType {
     Api,
     Worker,
@@ -26,9 +200,11 @@ pub enum ServiceType {
     Web,
     Nginx,
     Master,
+    // Third-party / public URL probed as a blackbox target (no container to control)
+    External,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ServiceStatus {
     pub id: String,
     pub name: String,
@@ -39,26 +215,131 @@ pub struct ServiceStatus {
     pub error_message: Option<String>,
     pub service_type: ServiceType,
     pub critical: bool,
+    /// Docker daemon of the service's container (`local` or an entry of `[hosts]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Covered by a silence or maintenance window: still checked, but not notified
+    #[serde(default)]
+    pub silenced: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Restarts that did not bring the service back for good, and when the next one is allowed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_backoff: Option<RestartBackoff>,
+}
+
+/// Backoff of a service that keeps needing restarts (see `[restart_backoff]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
+pub struct RestartBackoff {
+    /// Restarts since the service last stayed healthy for `reset_after_seconds`
+    pub restarts: u32,
+    pub last_restart_at: DateTime<Utc>,
+    /// Earliest time another restart is allowed
+    pub next_restart_at: DateTime<Utc>,
+    /// Gave up after `max_restarts`: restarts are refused until the breaker is reset through the API
+    pub circuit_open: bool,
+}
+
+/// Alert severity, ordered from least to most urgent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "info" => Some(Severity::Info),
+            "warning" | "warn" => Some(Severity::Warning),
+            "critical" | "error" | "page" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub enum HealthStatus {
     Healthy,
     Degraded,
     Unhealthy,
     Unknown,
+    /// Monitoring paused through the API: not checked, not alerted on
+    Paused,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ServiceHealth {
     pub service_id: String,
     pub status: HealthStatus,
     pub checks: Vec<HealthCheck>,
     pub metrics: ServiceMetrics,
     pub last_updated: DateTime<Utc>,
+    /// Image the service's container runs and the one before it, the target of a rollback
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<ImageRecord>,
+}
+
+/// Everything about one service, served by `GET /api/services/{id}` for the dashboard's drill-down view.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ServiceDetail {
+    pub config: ServiceConfig,
+    pub status: ServiceStatus,
+    /// Latest probe results and resource usage
+    pub health: Option<ServiceHealth>,
+    /// Newest first
+    pub recent_events: Vec<MonitorEvent>,
+    /// `ServiceRestarted` events, newest first; the backoff is in `status.restart_backoff`
+    pub restarts: Vec<MonitorEvent>,
+    pub availability: crate::uptime::Availability,
+}
+
+/// An image a service's container ran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DeployedImage {
+    /// Reference the container was created from, e.g. `nuniesmith/fks_api:latest`
+    pub image: String,
+    /// Local image id (`sha256:…`)
+    pub image_id: String,
+    /// `repository@sha256:…` of the image when it came from a registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_digest: Option<String>,
+    /// When the container was first seen running it
+    pub since: DateTime<Utc>,
+}
+
+impl DeployedImage {
+    /// Reference that recreates exactly this image without pulling: the registry digest, or else the
+    /// local image id.
+    pub fn pinned(&self) -> &str {
+        self.repo_digest.as_deref().unwrap_or(&self.image_id)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ImageRecord {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<DeployedImage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<DeployedImage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct HealthCheck {
     pub name: String,
     pub status: HealthStatus,
@@ -67,7 +348,7 @@ pub struct HealthCheck {
     pub timestamp: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, async_graphql::SimpleObject)]
 pub struct ServiceMetrics {
     pub cpu_usage_percent: Option<f64>,
     pub memory_usage_mb: Option<u64>,
@@ -80,57 +361,174 @@ pub struct ServiceMetrics {
     pub block_write_bytes: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, async_graphql::SimpleObject)]
 pub struct SystemMetrics {
     pub total_services: u32,
     pub healthy_services: u32,
     pub unhealthy_services: u32,
     pub critical_services_down: u32,
     pub average_response_time_ms: f64,
+    /// Nearest-rank percentiles over the latest response time of each service
+    #[serde(default)]
+    pub p95_response_time_ms: Option<u64>,
+    #[serde(default)]
+    pub p99_response_time_ms: Option<u64>,
+    #[serde(default)]
+    pub slowest_services: Vec<SlowService>,
     pub system_load_average: Option<f64>,
     pub total_requests: u64,
     pub total_errors: u64,
+    // True while automatic restarts are suspended after a fleet-wide restart storm
+    #[serde(default)]
+    pub restart_storm_active: bool,
+    // True while the global automation freeze is on
+    #[serde(default)]
+    pub automation_frozen: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, async_graphql::SimpleObject)]
+pub struct SlowService {
+    pub id: String,
+    pub name: String,
+    pub response_time_ms: u64,
+}
+
+/// A service that is currently down, exported as `fks_active_incident_info`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveIncident {
+    pub service_id: String,
+    pub service_name: String,
+    pub severity: Severity,
+    /// When it went down (its latest `ServiceDown` event)
+    pub since: DateTime<Utc>,
+    pub silenced: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RestartResult {
     pub service_id: String,
     pub success: bool,
     pub message: String,
     pub timestamp: DateTime<Utc>,
+    /// After a successful restart: whether the service was seen Healthy again within
+    /// `restart_verification.timeout_seconds` (unset when not verified)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+    /// How long after the restart the service was first seen Healthy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovered_in_ms: Option<u64>,
+}
+
+/// How `POST /api/services/{id}/restart` restarts the service (query parameters).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RestartOptions {
+    #[serde(default)]
+    pub mode: RestartMode,
+    /// Recreate: also replace the container's anonymous volumes (`--renew-anon-volumes`)
+    #[serde(default)]
+    pub fresh_volumes: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RestartMode {
+    /// Stop and start the same container (or roll out the workload, or restart the unit)
+    #[default]
+    Restart,
+    /// Remove the container and create it anew from its compose definition
+    Recreate,
+}
+
+/// Container lifecycle operation besides restart, run through `POST /api/services/{id}/{action}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerAction {
+    Stop,
+    Start,
+    Pause,
+    Unpause,
+    Kill,
+}
+
+impl ContainerAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContainerAction::Stop => "stop",
+            ContainerAction::Start => "start",
+            ContainerAction::Pause => "pause",
+            ContainerAction::Unpause => "unpause",
+            ContainerAction::Kill => "kill",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "stop" => Some(ContainerAction::Stop),
+            "start" => Some(ContainerAction::Start),
+            "pause" => Some(ContainerAction::Pause),
+            "unpause" => Some(ContainerAction::Unpause),
+            "kill" => Some(ContainerAction::Kill),
+            _ => None,
+        }
+    }
+
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            ContainerAction::Stop => "stopped",
+            ContainerAction::Start => "started",
+            ContainerAction::Pause => "paused",
+            ContainerAction::Unpause => "unpaused",
+            ContainerAction::Kill => "killed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MonitorEvent {
     pub event_type: EventType,
     pub service_id: Option<String>,
     pub message: String,
     pub timestamp: DateTime<Utc>,
     pub data: Option<serde_json::Value>,
+    /// The service's `metadata`, stamped on when the event is recorded
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, ToSchema)]
 pub enum EventType {
     ServiceUp,
     ServiceDown,
     ServiceRestarted,
+    /// A service's container was stopped, started, paused, unpaused or killed on request; `data` has `action`, `container` and `success`
+    ContainerAction,
     HighLatency,
     SystemAlert,
     MetricsUpdate,
+    /// The config file was reloaded; `data` lists the added, removed and changed services
+    ConfigReloaded,
+    /// Automatic actions were frozen; `data` is the freeze (reason, until, frozen_by)
+    AutomationFrozen,
+    AutomationResumed,
+    /// Monitoring of a service was paused or resumed through the API; `data` has `paused`
+    MonitoringPaused,
+    MonitoringResumed,
+    /// Global maintenance started or was changed; `data` is the maintenance (until, reason, started_by)
+    MaintenanceStarted,
+    MaintenanceEnded,
+    /// The registry has a newer digest for the image tag a service runs; `data` has `image`, `running_digest` and `available_digest`
+    ImageUpdateAvailable,
+    /// A critical service went down; `data` is the incident
+    IncidentOpened,
+    /// Someone took on an incident; `data` is the incident with its acknowledgements
+    IncidentAcknowledged,
+    /// An incident was resolved by hand (`resolved_by` set) or by the service recovering
+    IncidentResolved,
 }
 
-impl Default for ServiceMetrics {
-    fn default() -> Self {
-        Self {
-            cpu_usage_percent: None,
-            memory_usage_mb: None,
-            disk_usage_percent: None,
-            network_in_bytes: None,
-            network_out_bytes: None,
-            request_count: None,
-            error_rate: None,
-            block_read_bytes: None,
-            block_write_bytes: None,
-        }
+impl ServiceType {
+    pub fn is_external(&self) -> bool {
+        matches!(self, ServiceType::External)
     }
 }
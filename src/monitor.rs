@@ -1,35 +1,166 @@
 use anyhow::Result;
 use chrono::Utc;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use futures::future::join_all;
 use std::sync::Arc;
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 use std::time::Duration;
-use tokio::time::interval;
 use tracing::{debug, error, info, warn};
 
-use crate::config::Config;
+use crate::auto_restart::{AutoRestarts, Decision};
+use crate::automation::{AutomationSwitch, Freeze, FreezeRequest};
+use crate::clock::{self, SharedClock, Ticker};
+use crate::config::{Config, EscalationStep, SharedConfig};
+use crate::docker_events::{self, ContainerEvent};
+use crate::drain;
+use crate::docker_hosts;
+use crate::docker_stats;
+use crate::escalation::Escalation;
+use crate::image_history::{self, ImageHistory};
+use crate::image_updates;
+use crate::incident::{Actor, Incident, IncidentError, IncidentState, Incidents};
+use crate::kubernetes;
+use crate::orchestrator::Orchestrator;
+use crate::reminder::Outage;
+use crate::restart_backoff::{self, Refusal};
+use crate::restart_limit::RestartLimiter;
+use crate::rules::{RuleEngine, RuleMetric, RuleTransition};
+use crate::events::{self, EventPage, EventQuery};
+use crate::healing::{Condition, HealingAction, HealingEngine};
+use crate::silence::{Maintenance, MaintenanceRequest, Silence, Silences};
+use crate::uptime::{GroupBy, ServiceHistory, Transition, UptimeHistory, UptimeQuery, UptimeReport};
+use crate::watchdog::{self, Watchdog};
 use crate::health::HealthChecker;
+use crate::probe;
+use crate::reload::ConfigDiff;
 use crate::models::*;
 use crate::metrics;
 
+/// Collect metrics every minute
+const METRICS_INTERVAL: Duration = Duration::from_secs(60);
+/// Services listed in `SystemMetrics::slowest_services`
+const SLOWEST_SERVICES: usize = 5;
+/// Events buffered for subscribers that have not received them yet
+pub const EVENT_CHANNEL_CAPACITY: usize = 100;
+/// Events and restarts listed in a `ServiceDetail`
+const DETAIL_EVENTS: usize = 20;
+/// How often the set of containers with an open stats stream is reconciled with the config
+const DOCKER_STATS_SYNC: Duration = Duration::from_secs(15);
+/// Reconnect delays for the Docker event stream, doubling from the first to the second
+const DOCKER_EVENTS_RETRY: (Duration, Duration) = (Duration::from_secs(5), Duration::from_secs(120));
+
 pub struct ServiceMonitor {
-    config: Config,
+    config: SharedConfig,
     health_checker: HealthChecker,
     service_states: Arc<DashMap<String, ServiceStatus>>,
     event_history: Arc<DashMap<String, Vec<MonitorEvent>>>,
     error_history: Arc<DashMap<String, Vec<chrono::DateTime<chrono::Utc>>>>,
     resource_metrics: Arc<DashMap<String, ServiceMetrics>>,
+    heartbeats: Arc<DashMap<String, chrono::DateTime<chrono::Utc>>>,
+    restart_tracker: Arc<std::sync::Mutex<RestartTracker>>,
+    restart_limiter: RestartLimiter,
+    /// Failed-check counts and recent restarts behind per-service `auto_restart` policies
+    auto_restarts: AutoRestarts,
+    escalations: Arc<DashMap<String, Escalation>>,
+    outages: Arc<DashMap<String, Outage>>,
+    /// Services that went down during global maintenance without a `ServiceDown`
+    down_in_maintenance: Arc<DashSet<String>>,
+    /// Latest per-probe results, surfaced as `ServiceHealth.checks`
+    checks: Arc<DashMap<String, Vec<HealthCheck>>>,
+    /// Current and previous image per containerized service, for rollbacks
+    image_history: ImageHistory,
+    /// Status transitions behind uptime timelines and availability
+    uptime: UptimeHistory,
+    silences: Silences,
+    /// Opened when critical services go down; an acknowledged one gets no escalations or reminders
+    incidents: Incidents,
+    automation: AutomationSwitch,
+    watchdog: Watchdog,
+    /// Services to check right away instead of on the next tick (e.g. just registered through the API)
+    check_queue: CheckQueue,
+    /// Services registered by discovery, by source, kept across reloads of the config file
+    discovered: Discovered,
+    /// Kubernetes API clients shared by pod probes and restarts
+    kubernetes: kubernetes::Clients,
+    /// Running dedicated check loops: service id -> (interval seconds, loop token)
+    dedicated_loops: Arc<DashMap<String, (u64, uuid::Uuid)>>,
+    started_at: chrono::DateTime<chrono::Utc>,
     event_tx: broadcast::Sender<MonitorEvent>,
+    clock: SharedClock,
 }
 
 #[derive(Clone)]
 pub struct MonitorHandle {
     service_states: Arc<DashMap<String, ServiceStatus>>,
     event_history: Arc<DashMap<String, Vec<MonitorEvent>>>,
-    config: Config,
+    config: SharedConfig,
     resource_metrics: Arc<DashMap<String, ServiceMetrics>>,
+    heartbeats: Arc<DashMap<String, chrono::DateTime<chrono::Utc>>>,
+    restart_tracker: Arc<std::sync::Mutex<RestartTracker>>,
+    restart_limiter: RestartLimiter,
+    escalations: Arc<DashMap<String, Escalation>>,
+    outages: Arc<DashMap<String, Outage>>,
+    /// Latest per-probe results, surfaced as `ServiceHealth.checks`
+    checks: Arc<DashMap<String, Vec<HealthCheck>>>,
+    /// Current and previous image per containerized service, for rollbacks
+    image_history: ImageHistory,
+    /// Status transitions behind uptime timelines and availability
+    uptime: UptimeHistory,
+    silences: Silences,
+    /// Opened when critical services go down; an acknowledged one gets no escalations or reminders
+    incidents: Incidents,
+    automation: AutomationSwitch,
+    watchdog: Watchdog,
+    check_queue: CheckQueue,
+    discovered: Discovered,
+    kubernetes: kubernetes::Clients,
     event_tx: broadcast::Sender<MonitorEvent>,
+    clock: SharedClock,
+}
+
+/// Discovered services by source (see `discovery.rs`). Earlier sources win when two discover the same id.
+type Discovered = Arc<std::sync::Mutex<std::collections::BTreeMap<&'static str, Vec<ServiceConfig>>>>;
+
+#[derive(Clone, Default)]
+struct CheckQueue {
+    pending: Arc<std::sync::Mutex<Vec<String>>>,
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl CheckQueue {
+    fn push(&self, service_id: &str) {
+        let mut pending = self.pending.lock().unwrap();
+        if !pending.iter().any(|id| id == service_id) { pending.push(service_id.to_string()); }
+        self.notify.notify_one();
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+}
+
+/// In-memory state carried across an in-place binary upgrade (see `upgrade.rs`).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MonitorSnapshot {
+    pub services: Vec<ServiceStatus>,
+    pub events: std::collections::HashMap<String, Vec<MonitorEvent>>,
+    pub heartbeats: std::collections::HashMap<String, chrono::DateTime<chrono::Utc>>,
+    pub escalations: Vec<Escalation>,
+    #[serde(default)]
+    pub silences: Vec<Silence>,
+    #[serde(default)]
+    pub outages: Vec<Outage>,
+    #[serde(default)]
+    pub automation: Option<Freeze>,
+    #[serde(default)]
+    pub maintenance: Option<Maintenance>,
+    #[serde(default)]
+    pub images: std::collections::HashMap<String, ImageRecord>,
+    #[serde(default)]
+    pub uptime: std::collections::HashMap<String, Vec<Transition>>,
+    #[serde(default)]
+    pub incidents: Vec<Incident>,
 }
 
 impl ServiceMonitor {
@@ -42,45 +173,135 @@ impl ServiceMonitor {
         let service_states = Arc::new(DashMap::new());
         let event_history = Arc::new(DashMap::new());
 
+        let uptime = UptimeHistory::open(config.monitoring.uptime_history_path.as_ref().map(std::path::PathBuf::from));
+
         // Initialize service states
         for service in &config.services {
-            service_states.insert(
-                service.id.clone(),
-                ServiceStatus {
-                    id: service.id.clone(),
-                    name: service.name.clone(),
-                    status: HealthStatus::Unknown,
-                    last_check: Utc::now(),
-                    uptime_seconds: None,
-                    response_time_ms: None,
-                    error_message: None,
-                    service_type: service.service_type.clone(),
-                    critical: service.critical,
-                },
-            );
+            let status = initial_status(service, Utc::now());
+            uptime.record(&service.id, &status.status, status.last_check);
+            service_states.insert(service.id.clone(), status);
         }
 
-        let (event_tx, _event_rx) = broadcast::channel(100);
+        let (event_tx, _event_rx) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let silences = Silences::new(config.alerts.maintenance_windows.clone());
+        let watchdog = Watchdog::new(config.watchdog.clone());
 
         Ok(Self {
-            config,
+            config: SharedConfig::new(config),
             health_checker,
             service_states,
             event_history,
             error_history: Arc::new(DashMap::new()),
             resource_metrics: Arc::new(DashMap::new()),
+            heartbeats: Arc::new(DashMap::new()),
+            restart_tracker: Arc::new(std::sync::Mutex::new(RestartTracker::default())),
+            restart_limiter: RestartLimiter::default(),
+            auto_restarts: AutoRestarts::default(),
+            escalations: Arc::new(DashMap::new()),
+            outages: Arc::new(DashMap::new()),
+            down_in_maintenance: Arc::new(DashSet::new()),
+            checks: Arc::new(DashMap::new()),
+            image_history: ImageHistory::default(),
+            uptime,
+            silences,
+            incidents: Incidents::default(),
+            automation: AutomationSwitch::default(),
+            watchdog,
+            check_queue: CheckQueue::default(),
+            discovered: Discovered::default(),
+            kubernetes: kubernetes::Clients::default(),
+            dedicated_loops: Arc::new(DashMap::new()),
+            started_at: Utc::now(),
             event_tx,
+            clock: clock::system(),
         })
     }
 
-    pub async fn start(self) -> Result<MonitorHandle> {
-        let handle = MonitorHandle {
+    /// Drive all timers and timestamps from `clock` (e.g. a [`crate::clock::MockClock`] in tests).
+    #[allow(dead_code)] // library/test API
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        let now = clock.now();
+        self.started_at = now;
+        for mut state in self.service_states.iter_mut() {
+            state.last_check = now;
+        }
+        self.clock = clock;
+        self
+    }
+
+    /// Seed state from a predecessor's snapshot. Services no longer in the config are dropped.
+    pub fn restore(self, snapshot: MonitorSnapshot) -> Self {
+        let known = |id: &str| self.service_states.contains_key(id);
+        for mut status in snapshot.services.into_iter().filter(|s| known(&s.id)) {
+            // Metadata comes from the current config, not the predecessor's
+            if let Some(current) = self.service_states.get(&status.id) {
+                status.tags = current.tags.clone();
+                status.group = current.group.clone();
+                status.metadata = current.metadata.clone();
+                status.host = current.host.clone();
+            }
+            if status.restart_backoff.as_ref().is_some_and(|b| b.circuit_open) {
+                metrics::set_restart_circuit_open(&status.id, &status.name, true);
+            }
+            self.service_states.insert(status.id.clone(), status);
+        }
+        for (id, events) in snapshot.events.into_iter().filter(|(id, _)| id == "system" || known(id)) {
+            self.event_history.insert(id, events);
+        }
+        for (id, at) in snapshot.heartbeats.into_iter().filter(|(id, _)| known(id)) {
+            self.heartbeats.insert(id, at);
+        }
+        for esc in snapshot.escalations.into_iter().filter(|e| known(&e.service_id)) {
+            self.escalations.insert(esc.service_id.clone(), esc);
+        }
+        for outage in snapshot.outages.into_iter().filter(|o| known(&o.service_id)) {
+            self.outages.insert(outage.service_id.clone(), outage);
+        }
+        self.silences.restore(snapshot.silences);
+        self.automation.restore(snapshot.automation);
+        self.silences.restore_maintenance(snapshot.maintenance);
+        self.image_history.restore(snapshot.images.into_iter().filter(|(id, _)| known(id)).collect());
+        self.uptime.restore(snapshot.uptime.into_iter().filter(|(id, _)| known(id)).collect());
+        self.incidents.restore(snapshot.incidents);
+        self
+    }
+
+    /// Handle sharing this monitor's state, without starting any background loops.
+    pub fn handle(&self) -> MonitorHandle {
+        MonitorHandle {
             service_states: self.service_states.clone(),
             event_history: self.event_history.clone(),
             config: self.config.clone(),
             resource_metrics: self.resource_metrics.clone(),
+            heartbeats: self.heartbeats.clone(),
+            restart_tracker: self.restart_tracker.clone(),
+            restart_limiter: self.restart_limiter.clone(),
+            escalations: self.escalations.clone(),
+            outages: self.outages.clone(),
+            checks: self.checks.clone(),
+            image_history: self.image_history.clone(),
+            uptime: self.uptime.clone(),
+            silences: self.silences.clone(),
+            incidents: self.incidents.clone(),
+            automation: self.automation.clone(),
+            watchdog: self.watchdog.clone(),
+            check_queue: self.check_queue.clone(),
+            discovered: self.discovered.clone(),
+            kubernetes: self.kubernetes.clone(),
             event_tx: self.event_tx.clone(),
-        };
+            clock: self.clock.clone(),
+        }
+    }
+
+    fn config(&self) -> Arc<Config> {
+        self.config.get()
+    }
+
+    pub async fn start(self) -> Result<MonitorHandle> {
+        let handle = self.handle();
+        let now = self.clock.now();
+        self.watchdog.register(watchdog::MONITORING_LOOP, Duration::from_secs(self.config().monitoring.check_interval_seconds), now);
+        self.watchdog.register(watchdog::METRICS_LOOP, METRICS_INTERVAL, now);
 
         let monitor = Arc::new(self);
 
@@ -90,31 +311,72 @@ impl ServiceMonitor {
             monitor_clone.monitoring_loop().await;
         });
 
+        monitor.sync_dedicated_loops(&monitor.config());
+
         // Start metrics collection loop  
         let monitor_clone = monitor.clone();
         tokio::spawn(async move {
             monitor_clone.metrics_loop().await;
         });
 
+        // Escalations, reminders and rules may be switched on by a config reload, so their loops always run
+        let monitor_clone = monitor.clone();
+        tokio::spawn(async move {
+            monitor_clone.escalation_loop().await;
+        });
+
+        let monitor_clone = monitor.clone();
+        tokio::spawn(async move {
+            monitor_clone.reminder_loop().await;
+        });
+
+        let monitor_clone = monitor.clone();
+        tokio::spawn(async move {
+            monitor_clone.rules_loop().await;
+        });
+
+        let monitor_clone = monitor.clone();
+        tokio::spawn(async move {
+            monitor_clone.check_queue_loop().await;
+        });
+
+        let monitor_clone = monitor.clone();
+        tokio::spawn(async move {
+            monitor_clone.docker_events_loop().await;
+        });
+
+        let monitor_clone = monitor.clone();
+        tokio::spawn(async move {
+            monitor_clone.docker_stats_loop().await;
+        });
+
         Ok(handle)
     }
 
     async fn monitoring_loop(self: Arc<Self>) {
-        let mut interval = interval(Duration::from_secs(self.config.monitoring.check_interval_seconds));
+        let mut every = self.config().monitoring.check_interval_seconds;
+        let mut interval = Ticker::new(self.clock.clone(), Duration::from_secs(every));
         info!("🔍 Starting service monitoring loop");
 
         loop {
             interval.tick().await;
-            debug!("Running health checks for {} services", self.config.services.len());
+            // Re-read every cycle so reloaded services and intervals take effect
+            let config = self.config();
+            if config.monitoring.check_interval_seconds != every {
+                every = config.monitoring.check_interval_seconds;
+                interval.set_period(Duration::from_secs(every));
+                self.watchdog.register(watchdog::MONITORING_LOOP, Duration::from_secs(every), self.clock.now());
+                info!("🔍 Check interval is now {}s", every);
+            }
+            self.sync_dedicated_loops(&config);
 
-            // Check services in batches to avoid overwhelming the system
-            let chunks: Vec<_> = self
-                .config
-                .services
-                .chunks(self.config.monitoring.batch_size)
-                .collect();
+            // Services with a dedicated interval are checked by their own loop
+            let services: Vec<&ServiceConfig> = config.services.iter().filter(|s| s.check_interval_seconds.is_none()).collect();
+            debug!("Running health checks for {} services", services.len());
 
-            for chunk in chunks {
+            // Check services in batches to avoid overwhelming the system
+            let batch_size = config.monitoring.batch_size.max(1);
+            for chunk in services.chunks(batch_size) {
                 let futures = chunk.iter().map(|service| {
                     self.check_service_health(service)
                 });
@@ -122,22 +384,184 @@ impl ServiceMonitor {
                 join_all(futures).await;
                 
                 // Small delay between batches
-                if chunk.len() == self.config.monitoring.batch_size {
-                    tokio::time::sleep(Duration::from_millis(100)).await;
+                if chunk.len() == batch_size {
+                    self.clock.sleep(Duration::from_millis(100)).await;
+                }
+            }
+            self.watchdog.beat(watchdog::MONITORING_LOOP, self.clock.now());
+        }
+    }
+
+    /// Check services queued through [`MonitorHandle::check_soon`] without waiting for their interval.
+    async fn check_queue_loop(self: Arc<Self>) {
+        loop {
+            self.check_queue.notify.notified().await;
+            let config = self.config();
+            let queued = self.check_queue.take();
+            let futures = queued.iter().filter_map(|id| config.services.iter().find(|s| &s.id == id)).map(|service| self.check_service_health(service));
+            join_all(futures).await;
+        }
+    }
+
+    /// Follow the Docker event stream of every daemon with a configured container while
+    /// `monitoring.docker_events` is on, so a crashed container is down right away instead of at its
+    /// next check. Streams of daemons no longer in use are closed.
+    async fn docker_events_loop(self: Arc<Self>) {
+        let mut streams: std::collections::HashMap<Option<String>, tokio::task::JoinHandle<()>> = std::collections::HashMap::new();
+        loop {
+            let config = self.config();
+            let wanted = if config.monitoring.docker_events { docker_hosts::in_use(&config) } else { Vec::new() };
+            streams.retain(|host, task| {
+                let keep = wanted.contains(host) && !task.is_finished();
+                if !keep { task.abort(); }
+                keep
+            });
+            for host in wanted {
+                if streams.contains_key(&host) { continue; }
+                let monitor = self.clone();
+                streams.insert(host.clone(), tokio::spawn(async move { monitor.follow_docker_events(host).await }));
+            }
+            tokio::time::sleep(DOCKER_EVENTS_RETRY.0).await;
+        }
+    }
+
+    /// Apply the event stream of `host`'s daemon (`None`: the local one). When the daemon cannot be
+    /// reached the stream is retried with backoff; the regular checks cover the gap.
+    async fn follow_docker_events(self: Arc<Self>, host: Option<String>) {
+        use futures::StreamExt;
+        let label = host.as_deref().unwrap_or(docker_hosts::LOCAL);
+        let mut retry = DOCKER_EVENTS_RETRY.0;
+        let mut warned = false;
+        loop {
+            let config = self.config();
+            let project = config.monitoring.compose_project.clone();
+            let url = docker_hosts::url(&config, host.as_deref()).ok().flatten();
+            let failure = match docker_hosts::connect(&config, host.as_deref()) {
+                Ok(docker) => {
+                    let options = bollard::system::EventsOptions::<String> { filters: docker_events::filters(project.as_deref()), ..Default::default() };
+                    let mut stream = docker.events(Some(options));
+                    // Starts missed while the stream was down would otherwise go unrecorded
+                    for service in config.services.iter().filter(|s| s.docker_container.as_deref().is_some_and(|c| docker_hosts::runs(s, host.as_deref(), c))) {
+                        self.record_image(&docker, service).await;
+                    }
+                    loop {
+                        let next = tokio::select! {
+                            next = stream.next() => next,
+                            // Pick up a reload that changes the project or the host's address
+                            _ = tokio::time::sleep(Duration::from_secs(30)) => {
+                                let current = self.config();
+                                if current.monitoring.compose_project == project && docker_hosts::url(&current, host.as_deref()).ok().flatten() == url { continue; }
+                                break None;
+                            }
+                        };
+                        match next {
+                            Some(Ok(message)) => {
+                                retry = DOCKER_EVENTS_RETRY.0;
+                                if let Some((container, event)) = docker_events::parse(&message) {
+                                    self.apply_container_event(host.as_deref(), &container, event).await;
+                                }
+                            }
+                            Some(Err(e)) => break Some(e.to_string()),
+                            None => break None,
+                        }
+                    }
+                }
+                Err(e) => Some(format!("{e:#}")),
+            };
+            if let Some(error) = failure {
+                if !warned {
+                    warn!(%error, host = label, "Docker event stream unavailable; container changes are noticed at the next check");
+                    warned = true;
+                } else {
+                    debug!(%error, host = label, "Docker event stream unavailable");
+                }
+                tokio::time::sleep(retry).await;
+                retry = (retry * 2).min(DOCKER_EVENTS_RETRY.1);
+            }
+        }
+    }
+
+    /// Mark the services running in `container` on `host` down when it exits, is OOM-killed or fails its
+    /// healthcheck; a start or passing healthcheck queues a check to confirm the recovery.
+    async fn apply_container_event(&self, host: Option<&str>, container: &str, event: ContainerEvent) {
+        let config = self.config();
+        for service in config.services.iter().filter(|s| docker_hosts::runs(s, host, container)) {
+            let Some(reason) = event.failure(container) else {
+                debug!(service = %service.id, ?event, "container event; checking now");
+                self.check_queue.push(&service.id);
+                if event == ContainerEvent::Started {
+                    if let Ok(docker) = docker_hosts::connect(&config, host) { self.record_image(&docker, service).await; }
+                }
+                continue;
+            };
+            // Docker sends `oom` right before `die`; keep the more telling message
+            let oom_killed = ContainerEvent::OomKilled.failure(container);
+            if matches!(event, ContainerEvent::Died { .. }) && self.service_states.get(&service.id).is_some_and(|s| s.error_message == oom_killed) {
+                continue;
+            }
+            self.record_failure(service, reason).await;
+        }
+    }
+
+    /// Note the image `service`'s container runs now; a new one keeps the last as the rollback target.
+    async fn record_image(&self, docker: &bollard::Docker, service: &ServiceConfig) {
+        let Some(container) = &service.docker_container else { return };
+        match image_history::inspect(docker, container, self.clock.now()).await {
+            Ok(image) => {
+                let (reference, image_id) = (image.image.clone(), image.image_id.clone());
+                if self.image_history.observe(&service.id, image) {
+                    info!(service = %service.id, image = %reference, %image_id, "Service runs a new image");
                 }
             }
+            Err(e) => debug!(service = %service.id, error = format!("{e:#}"), "Cannot inspect the service's image"),
+        }
+    }
+
+    /// Services with their own check interval (typically external targets) get a dedicated loop.
+    /// Loops of removed services, or whose interval changed, are retired and replaced.
+    fn sync_dedicated_loops(self: &Arc<Self>, config: &Config) {
+        let wanted: std::collections::HashMap<&str, u64> = config.services
+            .iter()
+            .filter_map(|s| s.check_interval_seconds.map(|every| (s.id.as_str(), every.max(1))))
+            .collect();
+        self.dedicated_loops.retain(|id, _| wanted.contains_key(id.as_str()));
+        for (service_id, every) in wanted {
+            if self.dedicated_loops.get(service_id).is_some_and(|running| running.0 == every) { continue; }
+            let token = uuid::Uuid::new_v4();
+            self.dedicated_loops.insert(service_id.to_string(), (every, token));
+            let monitor_clone = self.clone();
+            let service_id = service_id.to_string();
+            tokio::spawn(async move {
+                monitor_clone.dedicated_check_loop(service_id, every, token).await;
+            });
+        }
+    }
+
+    async fn dedicated_check_loop(self: Arc<Self>, service_id: String, every: u64, token: uuid::Uuid) {
+        let mut interval = Ticker::new(self.clock.clone(), Duration::from_secs(every));
+        info!("🌍 Starting dedicated check loop for {} every {}s", service_id, every);
+
+        loop {
+            interval.tick().await;
+            if self.dedicated_loops.get(&service_id).is_none_or(|running| running.1 != token) {
+                debug!("Dedicated check loop for {} retired", service_id);
+                return;
+            }
+            let config = self.config();
+            let Some(service) = config.services.iter().find(|s| s.id == service_id) else { return };
+            self.check_service_health(service).await;
         }
     }
 
     async fn metrics_loop(self: Arc<Self>) {
-        let mut interval = interval(Duration::from_secs(60)); // Collect metrics every minute
+        let mut interval = Ticker::new(self.clock.clone(), METRICS_INTERVAL);
         
         loop {
             interval.tick().await;
+            self.watchdog.beat(watchdog::METRICS_LOOP, self.clock.now());
             debug!("Collecting system metrics");
             
             // Here you would collect additional metrics like:
-            // - Docker container stats
             // - System resource usage
             // - Network metrics
             // - Custom application metrics
@@ -147,19 +571,17 @@ impl ServiceMonitor {
                 event_type: EventType::MetricsUpdate,
                 service_id: None,
                 message: "System metrics updated".to_string(),
-                timestamp: Utc::now(),
+                timestamp: self.clock.now(),
                 data: None,
+                metadata: Default::default(),
             }).await;
 
             // Update error rate (failures per minute over sliding window)
-            let window_secs = 300; // 5 minute window
-            let now = Utc::now();
-            for svc in &self.config.services {
-                let mut entry = self.error_history.entry(svc.id.clone()).or_insert_with(Vec::new);
-                // Retain only entries within window
-                entry.retain(|ts| (now.signed_duration_since(*ts).num_seconds() as i64) <= window_secs as i64);
-                let failures = entry.len() as f64;
-                let rate_per_min = failures / (window_secs as f64 / 60.0);
+            let now = self.clock.now();
+            let config = self.config();
+            for svc in &config.services {
+                let rate_per_min = self.error_rate_per_min(&svc.id, now);
+                self.resource_metrics.entry(svc.id.clone()).or_default().error_rate = Some(rate_per_min);
                 crate::metrics::update_service_error_rate(
                     &svc.id,
                     &svc.name,
@@ -167,32 +589,356 @@ impl ServiceMonitor {
                     rate_per_min,
                 );
             }
+        }
+    }
+
+    /// Failed checks per minute over the last 5 minutes (prunes older entries).
+    fn error_rate_per_min(&self, service_id: &str, now: chrono::DateTime<chrono::Utc>) -> f64 {
+        let window_secs = 300; // 5 minute window
+        let mut entry = self.error_history.entry(service_id.to_string()).or_default();
+        entry.retain(|ts| now.signed_duration_since(*ts).num_seconds() <= window_secs);
+        entry.len() as f64 / (window_secs as f64 / 60.0)
+    }
+
+    async fn escalation_loop(self: Arc<Self>) {
+        let mut interval = Ticker::new(self.clock.clone(), Duration::from_secs(15));
+        let steps = self.config().alerts.escalation.len();
+        if steps > 0 { info!("📟 Escalation policy active ({} steps)", steps); }
 
-            // Collect Docker resource stats if enabled (best effort)
-            if self.config.monitoring.enable_docker_stats {
-                if let Err(e) = self.collect_docker_stats().await { debug!(error=?e, "docker stats collection failed") }
+        loop {
+            interval.tick().await;
+            self.check_escalations().await;
+        }
+    }
+
+    /// Fire due escalation steps and close escalations whose service is no longer Unhealthy.
+    async fn check_escalations(&self) {
+        let now = self.clock.now();
+        let config = self.config();
+        let policy = &config.alerts.escalation;
+        let mut events = Vec::new();
+        let mut resolved = Vec::new();
+        for mut entry in self.escalations.iter_mut() {
+            let still_down = self.service_states.get(entry.key()).map(|s| s.status == HealthStatus::Unhealthy).unwrap_or(false);
+            if !still_down {
+                resolved.push(entry.key().clone());
+                continue;
+            }
+            if self.incidents.acknowledged(entry.key()) { continue; }
+            let esc = entry.value_mut();
+            if let Some(step) = esc.advance(policy, now) {
+                warn!("📟 Escalating {} to level {} ({})", esc.service_name, esc.level, step.channels.join(", "));
+                events.push(MonitorEvent {
+                    event_type: EventType::SystemAlert,
+                    service_id: Some(esc.service_id.clone()),
+                    message: format!(
+                        "Escalation level {}: {} still unhealthy after {} minutes",
+                        esc.level, esc.service_name, now.signed_duration_since(esc.since).num_minutes()
+                    ),
+                    timestamp: now,
+                    data: Some(serde_json::json!({
+                        "kind": "escalation",
+                        "state": format!("level_{}", esc.level),
+                        "severity": "critical",
+                        "level": esc.level,
+                        "channels": step.channels,
+                        "since": esc.since,
+                        "status": HealthStatus::Unhealthy,
+                    })),
+                    metadata: Default::default(),
+                });
             }
         }
+        for service_id in resolved {
+            let Some((_, esc)) = self.escalations.remove(&service_id) else { continue };
+            if esc.level == 0 { continue; }
+            info!("📟 Escalation for {} resolved", esc.service_name);
+            events.push(MonitorEvent {
+                event_type: EventType::SystemAlert,
+                service_id: Some(esc.service_id.clone()),
+                message: format!("Escalation resolved: {} recovered after {} minutes", esc.service_name, now.signed_duration_since(esc.since).num_minutes()),
+                timestamp: now,
+                data: Some(serde_json::json!({
+                    "kind": "escalation",
+                    "state": "resolved",
+                    "severity": "critical",
+                    "level": esc.level,
+                    "channels": esc.notified_channels,
+                    "since": esc.since,
+                })),
+                metadata: Default::default(),
+            });
+        }
+        for event in events {
+            self.emit_event(event).await;
+        }
     }
 
-    async fn check_service_health(&self, service: &ServiceConfig) {
-        
-        match self.health_checker.check_health(&service.health_endpoint).await {
-            Ok(response_time) => {
-                let mut current_status = self.service_states.get_mut(&service.id).unwrap();
-                let was_unhealthy = matches!(current_status.status, HealthStatus::Unhealthy);
-                
-                // Determine status based on response time
-                let status = if response_time.as_millis() > service.expected_response_time_ms as u128 {
-                    HealthStatus::Degraded
+    async fn reminder_loop(self: Arc<Self>) {
+        let mut interval = Ticker::new(self.clock.clone(), Duration::from_secs(30));
+        if let Some(minutes) = self.config().alerts.reminder_interval_minutes {
+            info!("🔁 Outage reminders every {} minutes", minutes);
+        }
+
+        loop {
+            interval.tick().await;
+            if let Some(minutes) = self.config().alerts.reminder_interval_minutes {
+                self.check_reminders(minutes).await;
+            }
+        }
+    }
+
+    /// Remind about services still Unhealthy; on recovery after a reminder, send a "resolved after" notice.
+    async fn check_reminders(&self, interval_minutes: u64) {
+        let now = self.clock.now();
+        let mut events = Vec::new();
+        let mut recovered = Vec::new();
+        for mut entry in self.outages.iter_mut() {
+            let still_down = self.service_states.get(entry.key()).map(|s| s.status == HealthStatus::Unhealthy).unwrap_or(false);
+            if !still_down {
+                recovered.push(entry.key().clone());
+                continue;
+            }
+            if self.incidents.acknowledged(entry.key()) { continue; }
+            let outage = entry.value_mut();
+            if let Some(n) = outage.remind(interval_minutes, now) {
+                let minutes = outage.duration_minutes(now);
+                warn!("🔁 Reminder {}: {} still unhealthy after {} minutes", n, outage.service_name, minutes);
+                events.push(MonitorEvent {
+                    event_type: EventType::SystemAlert,
+                    service_id: Some(outage.service_id.clone()),
+                    message: format!("Reminder: {} still unhealthy after {}m", outage.service_name, minutes),
+                    timestamp: now,
+                    data: Some(serde_json::json!({
+                        "kind": "reminder",
+                        "state": format!("reminder_{n}"),
+                        "severity": if outage.critical { "critical" } else { "warning" },
+                        "reminder": n,
+                        "since": outage.since,
+                        "outage_minutes": minutes,
+                        "status": HealthStatus::Unhealthy,
+                    })),
+                    metadata: Default::default(),
+                });
+            }
+        }
+        for service_id in recovered {
+            let Some((_, outage)) = self.outages.remove(&service_id) else { continue };
+            // Short outages are covered by the regular ServiceUp notification
+            if outage.reminders == 0 { continue; }
+            let minutes = outage.duration_minutes(now);
+            info!("🔁 {} resolved after {} minutes", outage.service_name, minutes);
+            events.push(MonitorEvent {
+                event_type: EventType::SystemAlert,
+                service_id: Some(outage.service_id.clone()),
+                message: format!("Resolved: {} recovered after {}m", outage.service_name, minutes),
+                timestamp: now,
+                data: Some(serde_json::json!({
+                    "kind": "reminder",
+                    "state": "resolved",
+                    "severity": "info",
+                    "reminder": outage.reminders,
+                    "since": outage.since,
+                    "outage_minutes": minutes,
+                })),
+                metadata: Default::default(),
+            });
+        }
+        for event in events {
+            self.emit_event(event).await;
+        }
+    }
+
+    async fn rules_loop(self: Arc<Self>) {
+        let mut every = self.config().alerts.rule_evaluation_interval_seconds.max(1);
+        let mut interval = Ticker::new(self.clock.clone(), Duration::from_secs(every));
+        let mut engine = RuleEngine::default();
+        let mut healing = HealingEngine::default();
+        let rules = self.config().alerts.rules.len();
+        if rules > 0 { info!("📏 Evaluating {} alert rules", rules); }
+        let healing_rules = self.config().healing.len();
+        if healing_rules > 0 { info!("🩹 Evaluating {} healing rules", healing_rules); }
+
+        loop {
+            interval.tick().await;
+            let current = self.config().alerts.rule_evaluation_interval_seconds.max(1);
+            if current != every {
+                every = current;
+                interval.set_period(Duration::from_secs(every));
+            }
+            self.evaluate_rules(&mut engine).await;
+            self.evaluate_healing(&mut healing).await;
+        }
+    }
+
+    async fn evaluate_rules(&self, engine: &mut RuleEngine) {
+        let now = self.clock.now();
+        let config = self.config();
+        let services = self.monitored_services(&config);
+        let transitions = engine.evaluate(&config.alerts.rules, &services, now, |metric, service_id| {
+            self.rule_sample(metric, service_id, now)
+        });
+        for transition in transitions {
+            let (rule_name, service_id, value, firing) = match &transition {
+                RuleTransition::Firing { rule, service_id, value, .. } => (rule, service_id, Some(*value), true),
+                RuleTransition::Resolved { rule, service_id, value } => (rule, service_id, *value, false),
+            };
+            let Some(rule) = config.alerts.rules.iter().find(|r| &r.name == rule_name) else { continue };
+            let shown = value.map(|v| format!("{v:.2}")).unwrap_or_else(|| "n/a".into());
+            let message = if firing {
+                warn!("📏 Alert rule {} firing for {}: {} = {}", rule.name, service_id, rule.expr.metric.as_str(), shown);
+                format!("Alert rule {} firing for {}: {} = {} ({})", rule.name, service_id, rule.expr.metric.as_str(), shown, rule.expr)
+            } else {
+                info!("📏 Alert rule {} resolved for {}", rule.name, service_id);
+                format!("Alert rule {} resolved for {}: {} = {}", rule.name, service_id, rule.expr.metric.as_str(), shown)
+            };
+            self.emit_event(MonitorEvent {
+                event_type: EventType::SystemAlert,
+                service_id: Some(service_id.clone()),
+                message,
+                timestamp: now,
+                data: Some(serde_json::json!({
+                    "kind": "alert_rule",
+                    "rule": rule.name,
+                    "state": if firing { "firing" } else { "resolved" },
+                    "severity": rule.severity,
+                    "expr": rule.expr,
+                    "metric": rule.expr.metric,
+                    "value": value,
+                    "threshold": rule.expr.threshold,
+                })),
+                metadata: Default::default(),
+            }).await;
+        }
+    }
+
+    /// Configured services whose monitoring is not paused.
+    fn monitored_services(&self, config: &Config) -> Vec<ServiceConfig> {
+        config.services.iter().filter(|s| !is_paused(&self.service_states, &s.id)).cloned().collect()
+    }
+
+    /// Carry out (or, for rules that are not enforced, log) the healing rules that fire.
+    async fn evaluate_healing(&self, engine: &mut HealingEngine) {
+        let now = self.clock.now();
+        let config = self.config();
+        let services = self.monitored_services(&config);
+        let firings = engine.evaluate(&config.healing, &services, now, |condition, service_id| match condition {
+            Condition::Status { negated, status, .. } => self.service_states.get(service_id).is_some_and(|s| (s.status == *status) != *negated),
+            Condition::RestartsLastHour { op, count } => {
+                op.holds(self.restart_limiter.restarts_since(service_id, now - chrono::Duration::hours(1)) as f64, *count)
+            }
+            Condition::Metric(expr) => self.rule_sample(expr.metric, service_id, now).is_some_and(|v| expr.op.holds(v, expr.threshold)),
+        });
+        let handle = self.handle();
+        for firing in firings {
+            let Some(rule) = config.healing.iter().find(|r| r.name == firing.rule) else { continue };
+            let action = firing.action.as_str();
+            if !rule.enforce {
+                info!("🩹 [dry run] Healing rule {} would {} {} ({})", rule.name, action, firing.service_id, rule.rule);
+                metrics::increment_healing_action(&rule.name, action, "dry_run");
+                continue;
+            }
+            let acts_on_service = firing.action != HealingAction::Alert;
+            if acts_on_service && (handle.automation_frozen() || handle.in_maintenance() || handle.auto_restart_suppressed()) {
+                debug!("Healing rule {} held for {}: automation frozen, maintenance or restarts suppressed", rule.name, firing.service_id);
+                metrics::increment_healing_action(&rule.name, action, "held");
+                continue;
+            }
+            warn!("🩹 Healing rule {} firing: {} {}", rule.name, action, firing.service_id);
+            metrics::increment_healing_action(&rule.name, action, "enforced");
+            let data = serde_json::json!({"reason": "healing", "rule": rule.name, "action": firing.action, "expr": rule.rule});
+            if firing.action == HealingAction::Alert {
+                self.emit_event(MonitorEvent {
+                    event_type: EventType::SystemAlert,
+                    service_id: Some(firing.service_id.clone()),
+                    message: format!("Healing rule {} matched {}: {}", rule.name, firing.service_id, rule.rule),
+                    timestamp: now,
+                    data: Some(data),
+                    metadata: Default::default(),
+                }).await;
+                continue;
+            }
+            let handle = handle.clone();
+            let rule_name = rule.name.clone();
+            tokio::spawn(async move {
+                let result = match firing.action {
+                    HealingAction::Rollback => handle.rollback_service(&firing.service_id).await,
+                    HealingAction::Recreate => handle.restart_service_with(&firing.service_id, &RestartOptions { mode: RestartMode::Recreate, fresh_volumes: false }).await,
+                    _ => handle.restart_service(&firing.service_id).await,
+                };
+                // A rollback records its own event
+                if firing.action == HealingAction::Rollback { return; }
+                let (event_type, message) = if result.success {
+                    (EventType::ServiceRestarted, format!("Service {} {} by healing rule {}", firing.service_id, if firing.action == HealingAction::Recreate { "recreated" } else { "restarted" }, rule_name))
                 } else {
-                    HealthStatus::Healthy
+                    (EventType::SystemAlert, format!("Healing rule {} could not {} {}: {}", rule_name, action, firing.service_id, result.message))
                 };
+                let mut data = data;
+                data["success"] = result.success.into();
+                handle.inject_event(MonitorEvent { event_type, service_id: Some(firing.service_id), message, timestamp: result.timestamp, data: Some(data), metadata: Default::default() });
+            });
+        }
+    }
+
+    fn rule_sample(&self, metric: RuleMetric, service_id: &str, now: chrono::DateTime<chrono::Utc>) -> Option<f64> {
+        if metric == RuleMetric::ErrorRate { return Some(self.error_rate_per_min(service_id, now)); }
+        if metric == RuleMetric::ResponseTimeMs {
+            return self.service_states.get(service_id).and_then(|s| s.response_time_ms).map(|v| v as f64);
+        }
+        let m = self.resource_metrics.get(service_id)?;
+        match metric {
+            RuleMetric::CpuUsagePercent => m.cpu_usage_percent,
+            RuleMetric::MemoryUsageMb => m.memory_usage_mb.map(|v| v as f64),
+            RuleMetric::NetworkInBytes => m.network_in_bytes.map(|v| v as f64),
+            RuleMetric::NetworkOutBytes => m.network_out_bytes.map(|v| v as f64),
+            RuleMetric::BlockReadBytes => m.block_read_bytes.map(|v| v as f64),
+            RuleMetric::BlockWriteBytes => m.block_write_bytes.map(|v| v as f64),
+            RuleMetric::ErrorRate | RuleMetric::ResponseTimeMs => None,
+        }
+    }
+
+    async fn check_service_health(&self, service: &ServiceConfig) {
+        if is_paused(&self.service_states, &service.id) { return; }
+        if let Some(window) = service.heartbeat_window_seconds {
+            self.check_heartbeat(service, window).await;
+            return;
+        }
+
+        let config = self.config();
+        let checks = probe::run_probes(service, &self.health_checker, &self.kubernetes, &config, Duration::from_secs(config.monitoring.timeout_seconds)).await;
+        let combined = probe::combine(service.probe_mode, &checks);
+        let probed = match combined {
+            HealthStatus::Unhealthy | HealthStatus::Unknown => Err(match checks.as_slice() {
+                [] => "no health endpoint or probes configured".to_string(),
+                [only] => only.message.clone().unwrap_or_default(),
+                _ => probe::failure_summary(&checks),
+            }),
+            // Reported latency is that of the slowest passing probe
+            _ => Ok(checks.iter().filter(|c| c.status != HealthStatus::Unhealthy).map(|c| c.response_time_ms).max().unwrap_or_default()),
+        };
+        // The service may have been removed by a reload while it was being probed
+        if !self.service_states.contains_key(&service.id) { return; }
+        self.checks.insert(service.id.clone(), checks);
 
-                current_status.status = status.clone();
-                current_status.last_check = Utc::now();
-                current_status.response_time_ms = Some(response_time.as_millis() as u64);
-                current_status.error_message = None;
+        match probed {
+            Ok(response_time_ms) => {
+                let response_time = Duration::from_millis(response_time_ms);
+                // Slow or partially failing probes make the service Degraded
+                let status = combined;
+
+                let previous_status = {
+                    let Some(mut current_status) = self.service_states.get_mut(&service.id) else { return };
+                    let previous_status = current_status.status.clone();
+                    // Paused while the probes ran
+                    if previous_status == HealthStatus::Paused { return; }
+                    current_status.status = status.clone();
+                    current_status.last_check = self.clock.now();
+                    current_status.response_time_ms = Some(response_time.as_millis() as u64);
+                    current_status.error_message = None;
+                    previous_status
+                };
+                self.uptime.record(&service.id, &status, self.clock.now());
+                let was_unhealthy = matches!(previous_status, HealthStatus::Unhealthy);
 
                 // Update Prometheus metrics
                 metrics::update_service_health_metric(
@@ -220,273 +966,1120 @@ impl ServiceMonitor {
                     },
                 );
 
+                self.passed(service);
+
                 // Emit event if service recovered
-                if was_unhealthy && matches!(status, HealthStatus::Healthy) {
+                if was_unhealthy && matches!(status, HealthStatus::Healthy) && self.recovery_announced(&service.id) {
                     self.emit_event(MonitorEvent {
                         event_type: EventType::ServiceUp,
                         service_id: Some(service.id.clone()),
                         message: format!("Service {} is now healthy", service.name),
-                        timestamp: Utc::now(),
-                        data: None,
+                        timestamp: self.clock.now(),
+                        data: Some(serde_json::json!({"previous_status": previous_status, "status": status})),
+                        metadata: Default::default(),
                     }).await;
                 }
 
                 // Check for high latency
-                if response_time.as_millis() > self.config.alerts.high_latency_threshold_ms as u128 {
+                if response_time.as_millis() > self.config().alerts.high_latency_threshold_ms as u128 {
                     warn!("High latency detected for {}: {}ms", service.name, response_time.as_millis());
                     self.emit_event(MonitorEvent {
                         event_type: EventType::HighLatency,
                         service_id: Some(service.id.clone()),
                         message: format!("High latency: {}ms", response_time.as_millis()),
-                        timestamp: Utc::now(),
-                        data: Some(serde_json::json!({"latency_ms": response_time.as_millis()})),
+                        timestamp: self.clock.now(),
+                        data: Some(serde_json::json!({"latency_ms": response_time.as_millis(), "status": status})),
+                        metadata: Default::default(),
                     }).await;
                 }
 
                 debug!("✅ {} healthy - {}ms", service.name, response_time.as_millis());
             }
             Err(err) => {
-                let mut current_status = self.service_states.get_mut(&service.id).unwrap();
-                let was_healthy = matches!(current_status.status, HealthStatus::Healthy | HealthStatus::Degraded);
-
-                current_status.status = HealthStatus::Unhealthy;
-                current_status.last_check = Utc::now();
-                current_status.response_time_ms = None;
-                current_status.error_message = Some(err.to_string());
+                self.record_failure(service, err).await;
+            }
+        }
+    }
 
-                // Update Prometheus metrics
+    async fn check_heartbeat(&self, service: &ServiceConfig, window_secs: u64) {
+        let now = self.clock.now();
+        let last_beat = self.heartbeats.get(&service.id).map(|t| *t);
+        let fresh = last_beat.map(|t| now.signed_duration_since(t).num_seconds() <= window_secs as i64);
+        match fresh {
+            Some(true) => {
+                let previous_status = {
+                    let Some(mut current_status) = self.service_states.get_mut(&service.id) else { return };
+                    let previous_status = current_status.status.clone();
+                    current_status.status = HealthStatus::Healthy;
+                    current_status.last_check = now;
+                    current_status.response_time_ms = None;
+                    current_status.error_message = None;
+                    previous_status
+                };
+                self.uptime.record(&service.id, &HealthStatus::Healthy, now);
                 metrics::update_service_health_metric(
                     &service.id,
                     &service.name,
                     &format!("{:?}", service.service_type),
                     service.critical,
-                    &HealthStatus::Unhealthy,
+                    &HealthStatus::Healthy,
                 );
+                metrics::increment_health_check(&service.id, &service.name, "healthy");
+                self.passed(service);
+                if matches!(previous_status, HealthStatus::Unhealthy) && self.recovery_announced(&service.id) {
+                    self.emit_event(MonitorEvent {
+                        event_type: EventType::ServiceUp,
+                        service_id: Some(service.id.clone()),
+                        message: format!("Service {} heartbeat resumed", service.name),
+                        timestamp: now,
+                        data: Some(serde_json::json!({"previous_status": previous_status, "status": HealthStatus::Healthy})),
+                        metadata: Default::default(),
+                    }).await;
+                }
+            }
+            Some(false) => {
+                let last = last_beat.map(|t| t.to_rfc3339()).unwrap_or_default();
+                self.record_failure(service, format!("no heartbeat within {}s (last at {})", window_secs, last)).await;
+            }
+            None => {
+                // Give freshly started monitors one full window before declaring a missed heartbeat
+                if now.signed_duration_since(self.started_at).num_seconds() > window_secs as i64 {
+                    self.record_failure(service, format!("no heartbeat received within {}s", window_secs)).await;
+                }
+            }
+        }
+    }
 
-                metrics::increment_health_check(
-                    &service.id,
-                    &service.name,
-                    "unhealthy",
-                );
+    /// Whether a recovery gets a `ServiceUp`: not when going down was held back by maintenance.
+    fn recovery_announced(&self, service_id: &str) -> bool {
+        self.down_in_maintenance.remove(service_id).is_none()
+    }
+
+    async fn record_failure(&self, service: &ServiceConfig, err: String) {
+        let previous_status = {
+            let Some(mut current_status) = self.service_states.get_mut(&service.id) else { return };
+            let previous_status = current_status.status.clone();
+            // Container events, and checks already running when the service was paused, still end up here
+            if previous_status == HealthStatus::Paused { return; }
+
+            current_status.status = HealthStatus::Unhealthy;
+            current_status.last_check = self.clock.now();
+            current_status.response_time_ms = None;
+            current_status.error_message = Some(err.clone());
+            previous_status
+        };
+        self.uptime.record(&service.id, &HealthStatus::Unhealthy, self.clock.now());
+        let was_healthy = matches!(previous_status, HealthStatus::Healthy | HealthStatus::Degraded);
+
+        // Update Prometheus metrics
+        metrics::update_service_health_metric(
+            &service.id,
+            &service.name,
+            &format!("{:?}", service.service_type),
+            service.critical,
+            &HealthStatus::Unhealthy,
+        );
+
+        metrics::increment_health_check(
+            &service.id,
+            &service.name,
+            "unhealthy",
+        );
 
-                // Emit event if service went down
-                if was_healthy {
-                    error!("❌ {} is unhealthy: {}", service.name, err);
+        // Emit event if service went down; global maintenance holds it back until maintenance is over
+        let went_down = if self.silences.maintenance(self.clock.now()).is_some() {
+            if was_healthy {
+                warn!("🚧 {} is unhealthy during maintenance: {}", service.name, err);
+                self.down_in_maintenance.insert(service.id.clone());
+            }
+            false
+        } else {
+            self.down_in_maintenance.remove(&service.id).is_some() || was_healthy
+        };
+        if went_down {
+            error!("❌ {} is unhealthy: {}", service.name, err);
+            let config = self.config();
+            if service.critical && !config.alerts.escalation.is_empty() {
+                let policy = &config.alerts.escalation;
+                let now = self.clock.now();
+                self.escalations
+                    .entry(service.id.clone())
+                    .or_insert_with(|| Escalation::new(&service.id, &service.name, now, policy));
+            }
+            if config.alerts.reminder_interval_minutes.is_some() {
+                let now = self.clock.now();
+                self.outages
+                    .entry(service.id.clone())
+                    .or_insert_with(|| Outage::new(&service.id, &service.name, service.critical, now));
+            }
+            self.emit_event(MonitorEvent {
+                event_type: EventType::ServiceDown,
+                service_id: Some(service.id.clone()),
+                message: format!("Service {} is unhealthy: {}", service.name, err),
+                timestamp: self.clock.now(),
+                data: Some(serde_json::json!({"error": err, "previous_status": previous_status, "status": HealthStatus::Unhealthy})),
+                metadata: Default::default(),
+            }).await;
+            if service.critical {
+                if let Some(incident) = self.incidents.open(&service.id, &service.name, self.clock.now()) {
                     self.emit_event(MonitorEvent {
-                        event_type: EventType::ServiceDown,
+                        event_type: EventType::IncidentOpened,
                         service_id: Some(service.id.clone()),
-                        message: format!("Service {} is unhealthy: {}", service.name, err),
-                        timestamp: Utc::now(),
-                        data: Some(serde_json::json!({"error": err.to_string()})),
+                        message: format!("Incident opened: {} is down", service.name),
+                        timestamp: incident.opened_at,
+                        data: serde_json::to_value(&incident).ok(),
+                        metadata: Default::default(),
                     }).await;
                 }
+            }
+        }
+
+        // Track failure timestamp for error rate calculations
+        self.error_history.entry(service.id.clone()).or_default().push(self.clock.now());
+
+        self.auto_restart(service).await;
+    }
+
+    /// A passing check starts the auto-restart count over and, once the service has stayed up long
+    /// enough, its restart backoff. An unresolved incident of the service is resolved.
+    fn passed(&self, service: &ServiceConfig) {
+        self.auto_restarts.success(&service.id);
+        if let Some(incident) = self.incidents.recovered(&service.id, self.clock.now()) {
+            info!("✅ Incident for {} resolved: service recovered", service.name);
+            record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+                event_type: EventType::IncidentResolved,
+                service_id: Some(service.id.clone()),
+                message: format!("Incident resolved: {} recovered", service.name),
+                timestamp: self.clock.now(),
+                data: serde_json::to_value(&incident).ok(),
+                metadata: Default::default(),
+            });
+        }
+        let cfg = &self.config().restart_backoff;
+        if let Some(mut status) = self.service_states.get_mut(&service.id) {
+            restart_backoff::healthy(cfg, &mut status.restart_backoff, self.clock.now());
+        }
+    }
 
-                // Track failure timestamp for error rate calculations
-                let mut failures = self.error_history.entry(service.id.clone()).or_insert_with(Vec::new);
-                failures.push(Utc::now());
+    /// Apply the service's `auto_restart` policy after a failed check. The restart runs in the
+    /// background so a slow container stop doesn't hold up the other checks.
+    async fn auto_restart(&self, service: &ServiceConfig) {
+        let Some(policy) = service.auto_restart.as_ref().filter(|p| p.enabled) else { return };
+        let handle = self.handle();
+        let held = handle.automation_frozen() || handle.in_maintenance() || handle.auto_restart_suppressed() || handle.restart_refusal(&service.id).is_some();
+        match self.auto_restarts.failure(&service.id, policy, self.clock.now(), held) {
+            Decision::Wait | Decision::GivenUp => {}
+            Decision::Held => debug!("Auto-restart of {} held: automation frozen, maintenance, restarts suppressed or backing off", service.name),
+            Decision::GiveUp { attempts } => {
+                error!("🛑 Giving up on auto-restarting {}: {} restarts within the hour did not help", service.name, attempts);
+                self.emit_event(MonitorEvent {
+                    event_type: EventType::SystemAlert,
+                    service_id: Some(service.id.clone()),
+                    message: format!("Service {} is still failing after {} automatic restarts within the hour; needs attention", service.name, attempts),
+                    timestamp: self.clock.now(),
+                    data: Some(serde_json::json!({"reason": "auto_restart_exhausted", "attempts": attempts, "max_attempts_per_hour": policy.max_attempts_per_hour})),
+                    metadata: Default::default(),
+                }).await;
+            }
+            Decision::Restart { consecutive_failures, attempt } => {
+                warn!("🔁 Auto-restarting {} after {} failed checks (attempt {}/{} this hour)", service.name, consecutive_failures, attempt, policy.max_attempts_per_hour);
+                let service = service.clone();
+                let max_attempts = policy.max_attempts_per_hour;
+                tokio::spawn(async move {
+                    let result = handle.restart_service(&service.id).await;
+                    let (event_type, message) = if result.success {
+                        (EventType::ServiceRestarted, format!("Service {} restarted automatically after {} failed checks", service.name, consecutive_failures))
+                    } else {
+                        (EventType::SystemAlert, format!("Auto-restart of {} failed: {}", service.name, result.message))
+                    };
+                    handle.inject_event(MonitorEvent {
+                        event_type,
+                        service_id: Some(service.id.clone()),
+                        message,
+                        timestamp: result.timestamp,
+                        data: Some(serde_json::json!({
+                            "reason": "auto_restart",
+                            "consecutive_failures": consecutive_failures,
+                            "attempt": attempt,
+                            "max_attempts_per_hour": max_attempts,
+                            "success": result.success,
+                        })),
+                        metadata: Default::default(),
+                    });
+                    if result.success { handle.check_soon(&service.id); }
+                });
             }
         }
     }
 
     async fn emit_event(&self, event: MonitorEvent) {
-        let service_id = event.service_id.clone().unwrap_or_else(|| "system".to_string());
-        
-        self.event_history
-            .entry(service_id.clone())
-            .or_insert_with(Vec::new)
-            .push(event.clone());
-            
-        // Keep only last 100 events per service
-    if let Some(mut events) = self.event_history.get_mut(&service_id) {
-            if events.len() > 100 {
-                let keep_count = 100;
-                let events_len = events.len();
-                events.drain(0..events_len - keep_count);
+        record_event(&self.event_history, &self.event_tx, &self.service_states, event);
+    }
+
+    /// Keep a stats stream open for every configured container while `enable_docker_stats` is on
+    /// (limited to `compose_project` when set), restarting streams that end, e.g. when a container is
+    /// recreated, and closing those of containers no longer monitored.
+    async fn docker_stats_loop(self: Arc<Self>) {
+        let mut streams: std::collections::HashMap<(Option<String>, String), tokio::task::JoinHandle<()>> = std::collections::HashMap::new();
+        loop {
+            let config = self.config();
+            let wanted = if config.monitoring.enable_docker_stats { self.stats_containers(&config).await } else { Ok(Vec::new()) };
+            match wanted {
+                Ok(wanted) => {
+                    streams.retain(|container, task| {
+                        let keep = wanted.contains(container) && !task.is_finished();
+                        if !keep { task.abort(); }
+                        keep
+                    });
+                    for key in wanted {
+                        if streams.contains_key(&key) { continue; }
+                        let (monitor, (host, name)) = (self.clone(), key.clone());
+                        streams.insert(key, tokio::spawn(async move { monitor.stream_container_stats(host, name).await }));
+                    }
+                }
+                Err(e) => debug!(error = ?e, "docker stats: cannot list containers"),
             }
+            tokio::time::sleep(DOCKER_STATS_SYNC).await;
         }
+    }
 
-    // Broadcast (ignore errors if no receivers)
-    let _ = self.event_tx.send(event);
-    }
-
-    async fn collect_docker_stats(&self) -> Result<()> {
-        // Build mapping container_name -> (service_id, service_name)
-        let mut name_to_meta = std::collections::HashMap::new();
-        for svc in &self.config.services {
-            if let Some(c) = &svc.docker_container { name_to_meta.insert(c.clone(), (svc.id.clone(), svc.name.clone())); }
-        }
-        if name_to_meta.is_empty() { return Ok(()); }
-        let output = tokio::process::Command::new("docker")
-            .args(["stats","--no-stream","--format","{{.Name}},{{.CPUPerc}},{{.MemUsage}},{{.NetIO}},{{.BlockIO}}"])
-            .output()
-            .await?;
-        if !output.status.success() { anyhow::bail!("docker stats failed: {}", String::from_utf8_lossy(&output.stderr)); }
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
-            let parts: Vec<&str> = line.split(',').collect();
-            if parts.len() < 4 { continue; }
-            let name = parts[0].trim().to_string(); // container name
-            if let Some((service_id, service_name)) = name_to_meta.get(&name) {
-                let cpu = parts[1].trim_end_matches('%').parse::<f64>().ok();
-                // MemUsage looks like "12.34MiB / 2.00GiB"
-                let mem_usage_part = parts[2].split('/').next().unwrap_or("").trim();
-                let mem_mb = parse_size_to_mb(mem_usage_part);
-                // NetIO like "123kB / 45kB"
-                let net_parts: Vec<&str> = parts[3].split('/').collect();
-                let net_in = net_parts.get(0).and_then(|v| parse_size_to_bytes(v.trim()));
-                let net_out = net_parts.get(1).and_then(|v| parse_size_to_bytes(v.trim()));
-                // BlockIO column (if present) like "12.3MB / 4.5MB"
-                let (blk_read, blk_write) = if parts.len() >=5 {
-                    let blk_parts: Vec<&str> = parts[4].split('/').collect();
-                    let r = blk_parts.get(0).and_then(|v| parse_size_to_bytes(v.trim()));
-                    let w = blk_parts.get(1).and_then(|v| parse_size_to_bytes(v.trim()));
-                    (r,w)
-                } else { (None, None) };
-                let mut entry = self.resource_metrics.entry(service_id.clone()).or_insert_with(ServiceMetrics::default);
-                if let Some(c) = cpu { entry.cpu_usage_percent = Some(c); }
-                if let Some(m) = mem_mb { entry.memory_usage_mb = Some(m as u64); }
-                if let Some(n_in) = net_in { entry.network_in_bytes = Some(n_in as u64); }
-                if let Some(n_out) = net_out { entry.network_out_bytes = Some(n_out as u64); }
-                if let Some(br) = blk_read { entry.block_read_bytes = Some(br as u64); }
-                if let Some(bw) = blk_write { entry.block_write_bytes = Some(bw as u64); }
-                crate::metrics::update_service_resource_metrics(
-                    service_id,
-                    service_name,
-                    entry.cpu_usage_percent,
-                    entry.memory_usage_mb,
-                    entry.network_in_bytes,
-                    entry.network_out_bytes,
-                    entry.block_read_bytes,
-                    entry.block_write_bytes,
-                );
+    /// Hosts and containers of the configured services, restricted to the compose project's when one is set.
+    async fn stats_containers(&self, config: &Config) -> Result<Vec<(Option<String>, String)>> {
+        let mut containers: Vec<(Option<String>, String)> = config.services.iter()
+            .filter_map(|s| Some((s.docker_host.clone().filter(|h| h != docker_hosts::LOCAL), s.docker_container.clone()?)))
+            .collect();
+        containers.sort();
+        containers.dedup();
+        if let Some(project) = config.monitoring.compose_project.as_deref().filter(|p| !p.is_empty()) {
+            let mut in_project = Vec::new();
+            for host in docker_hosts::in_use(config) {
+                let docker = docker_hosts::connect(config, host.as_deref())?;
+                let listed = docker.list_containers(crate::compose::project_list_options(Some(project))).await?;
+                in_project.extend(listed.iter().flat_map(|c| c.names.iter().flatten()).map(|n| (host.clone(), n.trim_start_matches('/').to_string())));
+            }
+            containers.retain(|c| in_project.contains(c));
+        }
+        Ok(containers)
+    }
+
+    /// Apply every sample of `container`'s stats stream on `host` until it ends or fails.
+    async fn stream_container_stats(&self, host: Option<String>, container: String) {
+        use futures::StreamExt;
+        let docker = match docker_hosts::connect(&self.config(), host.as_deref()) {
+            Ok(docker) => docker,
+            Err(e) => return debug!(error = %e, %container, "docker stats: cannot connect to Docker"),
+        };
+        let options = bollard::container::StatsOptions { stream: true, one_shot: false };
+        let mut samples = docker.stats(&container, Some(options));
+        while let Some(sample) = samples.next().await {
+            match sample {
+                Ok(sample) => self.record_container_stats(host.as_deref(), &docker_stats::ContainerStats::from_api(&container, &sample)),
+                Err(e) => return debug!(error = %e, %container, "docker stats stream ended"),
             }
         }
-        Ok(())
+    }
+
+    /// Update `resource_metrics` and the resource gauges of the services running in `stats.name` on `host`.
+    /// Values missing from the sample keep their previous reading.
+    fn record_container_stats(&self, host: Option<&str>, stats: &docker_stats::ContainerStats) {
+        let config = self.config();
+        for service in config.services.iter().filter(|s| docker_hosts::runs(s, host, &stats.name)) {
+            let mut entry = self.resource_metrics.entry(service.id.clone()).or_default();
+            if let Some(c) = stats.cpu_percent { entry.cpu_usage_percent = Some(c); }
+            if let Some(m) = stats.memory_usage_mb() { entry.memory_usage_mb = Some(m as u64); }
+            if let Some(n_in) = stats.net_in_bytes { entry.network_in_bytes = Some(n_in); }
+            if let Some(n_out) = stats.net_out_bytes { entry.network_out_bytes = Some(n_out); }
+            if let Some(br) = stats.block_read_bytes { entry.block_read_bytes = Some(br); }
+            if let Some(bw) = stats.block_write_bytes { entry.block_write_bytes = Some(bw); }
+            crate::metrics::update_service_resource_metrics(
+                &service.id,
+                &service.name,
+                entry.cpu_usage_percent,
+                entry.memory_usage_mb,
+                entry.network_in_bytes,
+                entry.network_out_bytes,
+                entry.block_read_bytes,
+                entry.block_write_bytes,
+            );
+        }
     }
 }
 
-fn parse_size_to_mb(input: &str) -> Option<f64> {
-    parse_size_to_bytes(input).map(|b| b as f64 / (1024.0 * 1024.0))
+/// Run a lifecycle `action` on `container`; stops get `stop_timeout_seconds` before Docker kills the container.
+async fn run_container_action(docker: bollard::Docker, container: &str, action: ContainerAction, stop_timeout_seconds: u64) -> Result<()> {
+    let docker = docker.with_timeout(Duration::from_secs(stop_timeout_seconds + 30));
+    match action {
+        ContainerAction::Stop => docker.stop_container(container, Some(bollard::container::StopContainerOptions { t: stop_timeout_seconds as i64 })).await?,
+        ContainerAction::Start => docker.start_container(container, None::<bollard::container::StartContainerOptions<String>>).await?,
+        ContainerAction::Pause => docker.pause_container(container).await?,
+        ContainerAction::Unpause => docker.unpause_container(container).await?,
+        ContainerAction::Kill => docker.kill_container(container, None::<bollard::container::KillContainerOptions<String>>).await?,
+    }
+    Ok(())
 }
 
-fn parse_size_to_bytes(input: &str) -> Option<u64> {
-    // Accept formats like "123kB", "12.3MiB", "1.2GiB"
-    let input = input.trim();
-    if input.is_empty() { return None; }
-    let (num_part, unit_part) = input.split_at(input.find(char::is_alphabetic).unwrap_or(input.len()));
-    let value: f64 = num_part.trim().replace(',', ".").parse().ok()?;
-    let unit = unit_part.trim().to_lowercase();
-    let bytes = if unit.starts_with("gib") || unit.starts_with("gb") { value * 1024.0 * 1024.0 * 1024.0 }
-        else if unit.starts_with("mib") || unit.starts_with("mb") { value * 1024.0 * 1024.0 }
-        else if unit.starts_with("kib") || unit.starts_with("kb") { value * 1024.0 }
-        else if unit.starts_with('g') { value * 1_000_000_000.0 }
-        else if unit.starts_with('m') { value * 1_000_000.0 }
-        else if unit.starts_with('k') { value * 1_000.0 }
-        else { value };
-    Some(bytes as u64)
+/// Status of a service that has not been checked yet.
+fn initial_status(service: &ServiceConfig, now: chrono::DateTime<chrono::Utc>) -> ServiceStatus {
+    ServiceStatus {
+        id: service.id.clone(),
+        name: service.name.clone(),
+        status: HealthStatus::Unknown,
+        last_check: now,
+        uptime_seconds: None,
+        response_time_ms: None,
+        error_message: None,
+        service_type: service.service_type.clone(),
+        critical: service.critical,
+        host: docker_hosts::status_label(service),
+        silenced: false,
+        tags: service.tags.clone(),
+        group: service.group.clone(),
+        metadata: service.metadata.clone(),
+        restart_backoff: None,
+    }
 }
 
-impl MonitorHandle {
-    pub async fn get_all_services(&self) -> Vec<ServiceStatus> {
-        self.service_states
-            .iter()
-            .map(|entry| entry.value().clone())
-            .collect()
+/// Append an event to the per-service history (bounded) and broadcast it to subscribers.
+fn is_paused(service_states: &DashMap<String, ServiceStatus>, service_id: &str) -> bool {
+    service_states.get(service_id).is_some_and(|s| s.status == HealthStatus::Paused)
+}
+
+fn record_event(
+    event_history: &DashMap<String, Vec<MonitorEvent>>,
+    event_tx: &broadcast::Sender<MonitorEvent>,
+    service_states: &DashMap<String, ServiceStatus>,
+    mut event: MonitorEvent,
+) {
+    if event.metadata.is_empty() {
+        if let Some(status) = event.service_id.as_ref().and_then(|id| service_states.get(id)) {
+            event.metadata = status.metadata.clone();
+        }
     }
+    let service_id = event.service_id.clone().unwrap_or_else(|| events::SYSTEM.to_string());
 
-    pub async fn get_service_health(&self, service_id: &str) -> Option<ServiceHealth> {
+    {
+        let mut events = event_history.entry(service_id).or_default();
+        events.push(event.clone());
+        // Keep only last 100 events per service
+        if events.len() > 100 {
+            let keep_count = 100;
+            let events_len = events.len();
+            events.drain(0..events_len - keep_count);
+        }
+    }
+
+    // Broadcast (ignore errors if no receivers)
+    let _ = event_tx.send(event);
+}
+
+/// Sliding window of recent restarts used for fleet-wide storm detection.
+#[derive(Debug, Default)]
+struct RestartTracker {
+    recent: std::collections::VecDeque<(chrono::DateTime<chrono::Utc>, String)>,
+    suppressed_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl RestartTracker {
+    /// Record a restart; returns the affected service set when this restart tips the fleet into a storm.
+    fn record(&mut self, cfg: &crate::config::RestartStormConfig, service_id: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Vec<String>> {
+        self.recent.push_back((now, service_id.to_string()));
+        while let Some((ts, _)) = self.recent.front() {
+            if now.signed_duration_since(*ts).num_seconds() > cfg.window_seconds as i64 { self.recent.pop_front(); } else { break; }
+        }
+        if self.is_suppressed(now) { return None; }
+        let mut affected: Vec<String> = self.recent.iter().map(|(_, id)| id.clone()).collect();
+        affected.sort();
+        affected.dedup();
+        if cfg.threshold > 0 && affected.len() >= cfg.threshold {
+            self.suppressed_until = Some(now + chrono::Duration::seconds(cfg.cooldown_seconds as i64));
+            Some(affected)
+        } else {
+            None
+        }
+    }
+
+    fn is_suppressed(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.suppressed_until.map(|until| now < until).unwrap_or(false)
+    }
+}
+
+impl MonitorHandle {
+    pub fn compose_project(&self) -> Option<String> {
+        self.config().monitoring.compose_project.clone().filter(|p| !p.is_empty())
+    }
+
+    /// Services currently Unhealthy, most recent first. The start is the service's latest
+    /// `ServiceDown` event, or its last check if that has been trimmed from history.
+    pub async fn active_incidents(&self) -> Vec<ActiveIncident> {
+        let mut incidents: Vec<ActiveIncident> = self.get_all_services().await
+            .into_iter()
+            .filter(|s| s.status == HealthStatus::Unhealthy)
+            .map(|s| {
+                let since = self.event_history.get(&s.id)
+                    .and_then(|events| events.iter().rev().find(|e| e.event_type == EventType::ServiceDown).map(|e| e.timestamp))
+                    .unwrap_or(s.last_check);
+                ActiveIncident {
+                    severity: if s.critical { Severity::Critical } else { Severity::Warning },
+                    service_id: s.id,
+                    service_name: s.name,
+                    since,
+                    silenced: s.silenced,
+                }
+            })
+            .collect();
+        incidents.sort_by(|a, b| b.since.cmp(&a.since).then_with(|| a.service_id.cmp(&b.service_id)));
+        incidents
+    }
+
+    pub async fn get_all_services(&self) -> Vec<ServiceStatus> {
+        let now = self.clock.now();
+        let config = self.config();
+        self.service_states
+            .iter()
+            .map(|entry| {
+                let mut status = entry.value().clone();
+                status.silenced = config.services.iter()
+                    .find(|s| s.id == status.id)
+                    .is_some_and(|s| self.silences.silenced_by(s, now).is_some());
+                status
+            })
+            .collect()
+    }
+
+    pub fn silences(&self) -> &Silences {
+        &self.silences
+    }
+
+    /// The current configuration; a later reload does not change the returned snapshot.
+    pub fn config(&self) -> Arc<Config> {
+        self.config.get()
+    }
+
+    pub fn shared_config(&self) -> SharedConfig {
+        self.config.clone()
+    }
+
+    /// Switch to a reloaded config. New services start out Unknown until their first check; removed
+    /// ones are dropped along with their probe results, heartbeats, escalations and outages. Event
+    /// history is kept. Loops pick up new intervals and thresholds on their next tick. Discovered
+    /// services are carried over unless the new config defines a service with the same id.
+    pub fn reload(&self, mut config: Config) -> ConfigDiff {
+        {
+            let mut discovered = self.discovered.lock().unwrap();
+            for (source, services) in discovered.iter_mut() {
+                services.retain(|d| {
+                    let configured = config.services.iter().any(|s| s.id == d.id);
+                    if configured { warn!(service_id = %d.id, source, "Discovered service is also configured; using the configured one"); }
+                    !configured
+                });
+            }
+            let first_discovered = config.services.len();
+            for service in discovered.values().flatten() {
+                if !config.services[first_discovered..].iter().any(|s| s.id == service.id) {
+                    config.services.push(service.clone());
+                }
+            }
+        }
+        let diff = ConfigDiff::between(&self.config(), &config);
+        let now = self.clock.now();
+        for service in &config.services {
+            self.service_states
+                .entry(service.id.clone())
+                .and_modify(|status| {
+                    status.name = service.name.clone();
+                    status.service_type = service.service_type.clone();
+                    status.critical = service.critical;
+                    status.tags = service.tags.clone();
+                    status.group = service.group.clone();
+                    status.metadata = service.metadata.clone();
+                    status.host = docker_hosts::status_label(service);
+                })
+                .or_insert_with(|| initial_status(service, now));
+        }
+        for service_id in &diff.removed {
+            if let Some((_, status)) = self.service_states.remove(service_id) {
+                metrics::forget_service_health(&status.id, &status.name, &format!("{:?}", status.service_type), status.critical);
+            }
+            self.checks.remove(service_id);
+            self.heartbeats.remove(service_id);
+            self.escalations.remove(service_id);
+            self.outages.remove(service_id);
+            self.resource_metrics.remove(service_id);
+        }
+        self.silences.set_windows(config.alerts.maintenance_windows.clone());
+        let services = config.services.len();
+        self.config.replace(config);
+
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::ConfigReloaded,
+            service_id: None,
+            message: format!(
+                "Configuration reloaded: {} services ({} added, {} removed, {} changed)",
+                services, diff.added.len(), diff.removed.len(), diff.changed.len()
+            ),
+            timestamp: now,
+            data: serde_json::to_value(&diff).ok(),
+            metadata: Default::default(),
+        });
+        diff
+    }
+
+    /// Replace the services found by discovery `source` (see `discovery.rs`) and apply the result
+    /// like a reload.
+    pub fn set_discovered(&self, source: &'static str, services: Vec<ServiceConfig>) -> ConfigDiff {
+        let config = self.configured();
+        self.discovered.lock().unwrap().insert(source, services);
+        self.reload(config)
+    }
+
+    /// The current config without discovered services, i.e. what the config file (plus runtime
+    /// changes) defines.
+    pub fn configured(&self) -> Config {
+        let mut config = (*self.config()).clone();
+        let discovered = self.discovered.lock().unwrap();
+        config.services.retain(|s| !discovered.values().flatten().any(|d| d.id == s.id));
+        config
+    }
+
+    /// The discovery source that registered `service_id`, if it was not configured.
+    pub fn discovery_source(&self, service_id: &str) -> Option<&'static str> {
+        let discovered = self.discovered.lock().unwrap();
+        discovered.iter().find(|(_, services)| services.iter().any(|s| s.id == service_id)).map(|(source, _)| *source)
+    }
+
+    pub fn watchdog(&self) -> &Watchdog {
+        &self.watchdog
+    }
+
+    pub fn clock(&self) -> SharedClock {
+        self.clock.clone()
+    }
+
+    pub fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.clock.now()
+    }
+
+    /// Check `service_id` as soon as possible rather than on the next monitoring tick.
+    pub fn check_soon(&self, service_id: &str) {
+        self.check_queue.push(service_id);
+    }
+
+    /// Events still queued for at least one subscriber.
+    pub fn event_backlog(&self) -> usize {
+        self.event_tx.len()
+    }
+
+    pub async fn get_service_health(&self, service_id: &str) -> Option<ServiceHealth> {
         let status = self.service_states.get(service_id)?;
         let metrics = self.resource_metrics.get(service_id).map(|m| m.value().clone()).unwrap_or_default();
         
         Some(ServiceHealth {
             service_id: service_id.to_string(),
             status: status.status.clone(),
-            checks: vec![], // TODO: Implement detailed health checks
+            checks: self.checks.get(service_id).map(|c| c.value().clone()).unwrap_or_default(),
             metrics,
             last_updated: status.last_check,
+            images: self.image_history.get(service_id),
+        })
+    }
+
+    /// Config, status, health, recent events and restarts, and availability of a known service.
+    pub async fn service_detail(&self, service_id: &str) -> Option<ServiceDetail> {
+        let config = self.config().services.iter().find(|s| s.id == service_id)?.clone();
+        let status = self.get_all_services().await.into_iter().find(|s| s.id == service_id)?;
+        let recent = |event_type: Option<&str>| {
+            let q = EventQuery { service: Some(service_id.to_string()), event_type: event_type.map(str::to_string), limit: Some(DETAIL_EVENTS), ..Default::default() };
+            self.events(&q).map(|page| page.items).unwrap_or_default()
+        };
+        Some(ServiceDetail {
+            config,
+            status,
+            health: self.get_service_health(service_id).await,
+            recent_events: recent(None),
+            restarts: recent(Some("ServiceRestarted")),
+            availability: self.uptime.history(service_id, &UptimeQuery::default(), self.clock.now()).availability,
         })
     }
 
+    /// Uptime timeline and availability of a known service.
+    pub fn uptime_history(&self, service_id: &str, q: &UptimeQuery) -> Option<ServiceHistory> {
+        self.service_states.contains_key(service_id).then(|| self.uptime.history(service_id, q, self.clock.now()))
+    }
+
+    /// Availability and outages of the configured services over `period`, per service or per group
+    /// (`ungrouped` for services without one).
+    pub fn uptime_report(&self, period: chrono::Duration, group_by: GroupBy) -> UptimeReport {
+        let config = self.config();
+        let services = config.services.iter().map(|s| (s.id.as_str(), match group_by {
+            GroupBy::Service => s.id.as_str(),
+            GroupBy::Group => s.group.as_deref().unwrap_or("ungrouped"),
+        }));
+        self.uptime.report(services, period, group_by, self.clock.now())
+    }
+
     pub async fn restart_service(&self, service_id: &str) -> RestartResult {
-    let start_time = std::time::Instant::now();
-        // Find the service configuration
-        let service_config = self.config.services
-            .iter()
-            .find(|s| s.id == service_id);
-
-        match service_config {
-            Some(config) => {
-                if let Some(container_name) = &config.docker_container {
-                    // Attempt to restart Docker container
-                    match std::process::Command::new("docker")
-                        .args(["restart", container_name])
-                        .output()
-                    {
-                        Ok(output) => {
-                            if output.status.success() {
-                                info!("🔄 Successfully restarted {}", container_name);
-                                
-                                // Update Prometheus metrics
-                                metrics::increment_service_restart(&service_id, &config.name, true);
-                                
-                                let elapsed = start_time.elapsed().as_secs_f64();
-                                crate::metrics::observe_service_restart_duration(service_id, elapsed);
-                                RestartResult {
-                                    service_id: service_id.to_string(),
-                                    success: true,
-                                    message: format!("Successfully restarted container {}", container_name),
-                                    timestamp: Utc::now(),
-                                }
-                            } else {
-                                let error = String::from_utf8_lossy(&output.stderr);
-                                error!("❌ Failed to restart {}: {}", container_name, error);
-                                
-                                // Update Prometheus metrics
-                                metrics::increment_service_restart(&service_id, &config.name, false);
-                                
-                                let elapsed = start_time.elapsed().as_secs_f64();
-                                crate::metrics::observe_service_restart_duration(service_id, elapsed);
-                                RestartResult {
-                                    service_id: service_id.to_string(),
-                                    success: false,
-                                    message: format!("Failed to restart container: {}", error),
-                                    timestamp: Utc::now(),
-                                }
-                            }
+        self.restart_service_with(service_id, &RestartOptions::default()).await
+    }
+
+    /// Restart the service the way `options` says; a recreate goes through the same backoff, rate
+    /// limit, drain and verification as a restart.
+    pub async fn restart_service_with(&self, service_id: &str, options: &RestartOptions) -> RestartResult {
+        if let Some(refusal) = self.restart_refusal(service_id) {
+            warn!(%service_id, "Restart refused: {}", refusal);
+            return RestartResult { service_id: service_id.to_string(), success: false, message: refusal.to_string(), timestamp: self.clock.now(), verified: None, recovered_in_ms: None };
+        }
+        if let Err(message) = self.drain_before_restart(service_id).await {
+            return RestartResult { service_id: service_id.to_string(), success: false, message, timestamp: self.clock.now(), verified: None, recovered_in_ms: None };
+        }
+        let mut result = self.restart_service_inner(service_id, options).await;
+        let config = self.config();
+        // Only restarts that reached Docker, Kubernetes or systemd count towards the backoff
+        if let Some(service) = config.services.iter().find(|s| s.id == service_id && Orchestrator::of(s).is_some()) {
+            self.note_restart_attempt(service, &config.restart_backoff);
+            self.restart_limiter.record(&config.restart_rate_limit, service_id, self.clock.now());
+        }
+        if result.success {
+            self.note_restart(service_id);
+            if config.restart_verification.enabled {
+                let recovered = self.verify_restart(service_id, &config.restart_verification).await;
+                result.verified = Some(recovered.is_some());
+                result.recovered_in_ms = recovered.map(|took| took.as_millis() as u64);
+            }
+        }
+        result
+    }
+
+    /// Check a freshly restarted service until a check that started after the restart finds it
+    /// Healthy, for up to `timeout_seconds`. Returns how long that took; a restart that did not bring
+    /// the service back raises a `SystemAlert`.
+    async fn verify_restart(&self, service_id: &str, cfg: &crate::config::RestartVerificationConfig) -> Option<std::time::Duration> {
+        let started = tokio::time::Instant::now();
+        let restarted_at = self.clock.now();
+        let timeout = std::time::Duration::from_secs(cfg.timeout_seconds);
+        let poll = std::time::Duration::from_secs(cfg.poll_interval_seconds.max(1));
+        let mut last_status = HealthStatus::Unknown;
+        loop {
+            self.check_soon(service_id);
+            tokio::time::sleep(poll.min(timeout.saturating_sub(started.elapsed()))).await;
+            if let Some(status) = self.service_states.get(service_id).filter(|s| s.last_check >= restarted_at) {
+                last_status = status.status.clone();
+            }
+            if last_status == HealthStatus::Healthy {
+                info!(%service_id, "✅ Restart verified: healthy again after {:.1}s", started.elapsed().as_secs_f64());
+                return Some(started.elapsed());
+            }
+            if started.elapsed() >= timeout { break; }
+        }
+        let name = self.config().services.iter().find(|s| s.id == service_id).map_or_else(|| service_id.to_string(), |s| s.name.clone());
+        error!(%service_id, "Restart did not bring {} back: still {:?} after {}s", name, last_status, cfg.timeout_seconds);
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::SystemAlert,
+            service_id: Some(service_id.to_string()),
+            message: format!("Service {name} was restarted but is still {last_status:?} after {}s", cfg.timeout_seconds),
+            timestamp: self.clock.now(),
+            data: Some(serde_json::json!({"reason": "restart_unverified", "status": last_status, "timeout_seconds": cfg.timeout_seconds})),
+            metadata: Default::default(),
+        });
+        None
+    }
+
+    /// Drain a service with a `drain` step before it is restarted or recreated. An error (the reason)
+    /// calls the restart off, unless the drain is `force`d.
+    async fn drain_before_restart(&self, service_id: &str) -> Result<(), String> {
+        let config = self.config();
+        let Some(service) = config.services.iter().find(|s| s.id == service_id && Orchestrator::of(s).is_some()) else { return Ok(()) };
+        let Some(cfg) = &service.drain else { return Ok(()) };
+        info!("🚰 Draining {} before restarting it", service.name);
+        match drain::drain(cfg).await {
+            Ok(took) => {
+                info!("Drained {} in {:.1}s", service.name, took.as_secs_f64());
+                Ok(())
+            }
+            Err(e) if cfg.force => {
+                warn!("Drain of {} failed, restarting anyway: {:#}", service.name, e);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Drain of {} failed; restart called off: {:#}", service.name, e);
+                Err(format!("Drain failed, restart called off: {e:#}"))
+            }
+        }
+    }
+
+    /// Why restarting the service right now would be refused by its restart backoff or the restart
+    /// rate limit, if it would.
+    pub fn restart_refusal(&self, service_id: &str) -> Option<Refusal> {
+        let now = self.clock.now();
+        let status = self.service_states.get(service_id)?;
+        if let Err(refusal) = restart_backoff::allow(status.restart_backoff.as_ref(), now) { return Some(refusal); }
+        let cfg = &self.config().restart_rate_limit;
+        self.restart_limiter.retry_at(cfg, service_id, now)
+            .map(|retry_at| Refusal::RateLimited { retry_at, max_restarts: cfg.max_restarts, window_seconds: cfg.window_seconds })
+    }
+
+    /// Forget a service's restarts and close its restart circuit; `None` for an unknown service,
+    /// otherwise the backoff that was cleared.
+    pub fn reset_restart_backoff(&self, service_id: &str) -> Option<Option<RestartBackoff>> {
+        let mut status = self.service_states.get_mut(service_id)?;
+        let cleared = status.restart_backoff.take();
+        metrics::set_restart_circuit_open(service_id, &status.name, false);
+        info!(%service_id, "Restart backoff reset");
+        Some(cleared)
+    }
+
+    fn note_restart_attempt(&self, service: &ServiceConfig, cfg: &crate::config::RestartBackoffConfig) {
+        let backoff = {
+            let Some(mut status) = self.service_states.get_mut(&service.id) else { return };
+            restart_backoff::record(cfg, &mut status.restart_backoff, self.clock.now())
+        };
+        if !backoff.circuit_open { return; }
+        metrics::set_restart_circuit_open(&service.id, &service.name, true);
+        error!("🛑 Giving up on restarting {} after {} restarts without recovery", service.name, backoff.restarts);
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::SystemAlert,
+            service_id: Some(service.id.clone()),
+            message: format!("Service {} did not recover after {} restarts; restarts are refused until the restart circuit is reset", service.name, backoff.restarts),
+            timestamp: self.clock.now(),
+            data: Some(serde_json::json!({"severity": "critical", "kind": "restart_circuit_open", "restarts": backoff.restarts})),
+            metadata: Default::default(),
+        });
+    }
+
+    /// Stop, start, pause, unpause or kill the service's container through the Docker API, recording
+    /// the outcome as a `ContainerAction` event. A started or unpaused service is checked right away.
+    pub async fn container_action(&self, service_id: &str, action: ContainerAction) -> RestartResult {
+        let current = self.config();
+        let failed = |message: &str| RestartResult { service_id: service_id.to_string(), success: false, message: message.to_string(), timestamp: self.clock.now(), verified: None, recovered_in_ms: None };
+        let Some(service) = current.services.iter().find(|s| s.id == service_id) else { return failed("Service not found") };
+        let Some(container) = &service.docker_container else { return failed("No Docker container configured for this service") };
+
+        let done = match docker_hosts::connect_for(&current, service) {
+            Ok(docker) => run_container_action(docker, container, action, current.monitoring.restart_stop_timeout_seconds).await,
+            Err(e) => Err(e),
+        };
+        metrics::increment_container_action(service_id, action.as_str(), done.is_ok());
+        let message = match &done {
+            Ok(()) => {
+                info!("🎛️ Container {} {}", container, action.past_tense());
+                format!("Container {} {}", container, action.past_tense())
+            }
+            Err(e) => {
+                error!("❌ Failed to {} {}: {:#}", action.as_str(), container, e);
+                format!("Failed to {} container {}: {:#}", action.as_str(), container, e)
+            }
+        };
+        let now = self.clock.now();
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::ContainerAction,
+            service_id: Some(service_id.to_string()),
+            message: message.clone(),
+            timestamp: now,
+            data: Some(serde_json::json!({"action": action, "container": container, "success": done.is_ok()})),
+            metadata: Default::default(),
+        });
+        if done.is_ok() && matches!(action, ContainerAction::Start | ContainerAction::Unpause) {
+            self.check_soon(service_id);
+        }
+        RestartResult { service_id: service_id.to_string(), success: done.is_ok(), message, timestamp: now, verified: None, recovered_in_ms: None }
+    }
+
+    /// The service's current and previous image, as recorded from container starts.
+    pub fn image_record(&self, service_id: &str) -> Option<ImageRecord> {
+        self.image_history.get(service_id)
+    }
+
+    /// Recreate the service's container from the image it ran before the current one, with the same
+    /// settings. The previous image is used as it is stored locally, so nothing is pulled.
+    pub async fn rollback_service(&self, service_id: &str) -> RestartResult {
+        let current = self.config();
+        let failed = |message: &str| RestartResult { service_id: service_id.to_string(), success: false, message: message.to_string(), timestamp: self.clock.now(), verified: None, recovered_in_ms: None };
+        let Some(service) = current.services.iter().find(|s| s.id == service_id) else { return failed("Service not found") };
+        let Some(container) = &service.docker_container else { return failed("No Docker container configured for this service") };
+        let Some(ImageRecord { current: Some(from), previous: Some(to) }) = self.image_history.get(service_id) else {
+            return failed("No previous image recorded for this service");
+        };
+
+        if let Err(message) = self.drain_before_restart(service_id).await { return failed(&message) };
+        warn!("⏪ Rolling {} back from {} to {} ({})", service.name, from.image, to.image, to.pinned());
+        let start_time = std::time::Instant::now();
+        let done = match docker_hosts::connect_for(&current, service) {
+            Ok(docker) => image_updates::recreate_container(&docker, container, Some(to.pinned()), current.monitoring.restart_stop_timeout_seconds).await,
+            Err(e) => Err(e),
+        };
+        metrics::increment_service_restart(service_id, &service.name, done.is_ok());
+        metrics::observe_service_restart_duration(service_id, start_time.elapsed().as_secs_f64());
+        let now = self.clock.now();
+        let (event_type, message) = match &done {
+            Ok(()) => (EventType::ServiceRestarted, format!("Service {} rolled back to {}", service.name, to.image)),
+            Err(e) => {
+                error!("❌ Failed to roll back {}: {:#}", service.name, e);
+                (EventType::SystemAlert, format!("Rollback of {} failed: {:#}", service.name, e))
+            }
+        };
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type,
+            service_id: Some(service_id.to_string()),
+            message: message.clone(),
+            timestamp: now,
+            data: Some(serde_json::json!({"reason": "rollback", "container": container, "from": from, "to": to, "success": done.is_ok()})),
+            metadata: Default::default(),
+        });
+        if done.is_ok() {
+            self.image_history.observe(service_id, DeployedImage { since: now, ..to });
+            self.check_soon(service_id);
+        }
+        RestartResult { service_id: service_id.to_string(), success: done.is_ok(), message, timestamp: now, verified: None, recovered_in_ms: None }
+    }
+
+    /// Whether automatic (policy-driven) restarts are currently paused by a restart storm.
+    pub fn auto_restart_suppressed(&self) -> bool {
+        self.restart_tracker.lock().map(|t| t.is_suppressed(self.clock.now())).unwrap_or(false)
+    }
+
+    /// Whether the global automation freeze is on. Anything that acts on services without a human
+    /// asking (auto-restarts, playbooks, scheduled actions, deploy webhooks) must check this first;
+    /// manual actions through the API are not affected.
+    pub fn automation_frozen(&self) -> bool {
+        self.automation.is_frozen(self.clock.now())
+    }
+
+    pub fn automation(&self) -> Option<Freeze> {
+        self.automation.current(self.clock.now())
+    }
+
+    pub fn freeze_automation(&self, req: FreezeRequest) -> Freeze {
+        let now = self.clock.now();
+        let freeze = self.automation.freeze(req, now);
+        warn!(reason = ?freeze.reason, until = ?freeze.until, "🧊 Automation frozen: automatic actions suspended");
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::AutomationFrozen,
+            service_id: None,
+            message: match &freeze.reason {
+                Some(reason) => format!("Automation frozen: {reason}"),
+                None => "Automation frozen".to_string(),
+            },
+            timestamp: now,
+            data: serde_json::to_value(&freeze).ok(),
+            metadata: Default::default(),
+        });
+        freeze
+    }
+
+    /// Lift the freeze; `None` if automation was not frozen.
+    pub fn resume_automation(&self) -> Option<Freeze> {
+        let now = self.clock.now();
+        let previous = self.automation.resume(now)?;
+        info!("▶️ Automation resumed");
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::AutomationResumed,
+            service_id: None,
+            message: "Automation resumed".to_string(),
+            timestamp: now,
+            data: serde_json::to_value(&previous).ok(),
+            metadata: Default::default(),
+        });
+        Some(previous)
+    }
+
+    /// Whether global maintenance is on: alerts are dropped, auto-remediation is held and services
+    /// going down raise no `ServiceDown`, while checks keep running.
+    pub fn in_maintenance(&self) -> bool {
+        self.maintenance().is_some()
+    }
+
+    pub fn maintenance(&self) -> Option<Maintenance> {
+        self.silences.maintenance(self.clock.now())
+    }
+
+    pub fn start_maintenance(&self, req: MaintenanceRequest) -> Result<Maintenance, String> {
+        let now = self.clock.now();
+        let maintenance = self.silences.start_maintenance(req, now)?;
+        warn!(reason = ?maintenance.reason, until = %maintenance.until, "🚧 Maintenance started: alerts and auto-remediation suspended");
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::MaintenanceStarted,
+            service_id: None,
+            message: match &maintenance.reason {
+                Some(reason) => format!("Maintenance until {}: {reason}", maintenance.until.to_rfc3339()),
+                None => format!("Maintenance until {}", maintenance.until.to_rfc3339()),
+            },
+            timestamp: now,
+            data: serde_json::to_value(&maintenance).ok(),
+            metadata: Default::default(),
+        });
+        Ok(maintenance)
+    }
+
+    /// End maintenance before it expires; `None` if there was none.
+    pub fn end_maintenance(&self) -> Option<Maintenance> {
+        let now = self.clock.now();
+        let previous = self.silences.end_maintenance(now)?;
+        info!("✅ Maintenance ended");
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::MaintenanceEnded,
+            service_id: None,
+            message: "Maintenance ended".to_string(),
+            timestamp: now,
+            data: serde_json::to_value(&previous).ok(),
+            metadata: Default::default(),
+        });
+        Some(previous)
+    }
+
+    /// Incidents, newest first, optionally only those in `state`.
+    pub fn incidents(&self, state: Option<IncidentState>) -> Vec<Incident> {
+        self.incidents.list(state)
+    }
+
+    /// `by` takes on the incident: its escalation stops advancing and no more reminders go out
+    /// while it is unresolved.
+    pub fn acknowledge_incident(&self, id: &str, by: &Actor, note: Option<String>) -> Result<Incident, IncidentError> {
+        let now = self.clock.now();
+        let incident = self.incidents.acknowledge(id, by, note, now)?;
+        let by = &by.name;
+        info!(by, "🙋 Incident for {} acknowledged", incident.service_name);
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::IncidentAcknowledged,
+            service_id: Some(incident.service_id.clone()),
+            message: format!("Incident for {} acknowledged by {by}", incident.service_name),
+            timestamp: now,
+            data: serde_json::to_value(&incident).ok(),
+            metadata: Default::default(),
+        });
+        Ok(incident)
+    }
+
+    /// Close the incident by hand; its escalation and outage reminders end even if the service is
+    /// still down.
+    pub fn resolve_incident(&self, id: &str, by: &Actor, note: Option<String>) -> Result<Incident, IncidentError> {
+        let now = self.clock.now();
+        let incident = self.incidents.resolve(id, by, note, now)?;
+        let by = &by.name;
+        self.escalations.remove(&incident.service_id);
+        self.outages.remove(&incident.service_id);
+        info!(by, "✅ Incident for {} resolved", incident.service_name);
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::IncidentResolved,
+            service_id: Some(incident.service_id.clone()),
+            message: format!("Incident for {} resolved by {by}", incident.service_name),
+            timestamp: now,
+            data: serde_json::to_value(&incident).ok(),
+            metadata: Default::default(),
+        });
+        Ok(incident)
+    }
+
+    /// Stop checking `service_id`, and so alerting on it, until [`Self::resume_monitoring`]; its open
+    /// escalation and outage reminders end. `None` if the service is unknown.
+    pub fn pause_monitoring(&self, service_id: &str) -> Option<ServiceStatus> {
+        let now = self.clock.now();
+        let status = {
+            let mut status = self.service_states.get_mut(service_id)?;
+            if status.status == HealthStatus::Paused { return Some(status.clone()); }
+            status.status = HealthStatus::Paused;
+            status.response_time_ms = None;
+            status.error_message = None;
+            status.clone()
+        };
+        self.escalations.remove(service_id);
+        self.outages.remove(service_id);
+        self.uptime.record(service_id, &HealthStatus::Paused, now);
+        metrics::update_service_health_metric(&status.id, &status.name, &format!("{:?}", status.service_type), status.critical, &status.status);
+        info!(%service_id, "⏸️ Monitoring paused");
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::MonitoringPaused,
+            service_id: Some(service_id.to_string()),
+            message: format!("Monitoring of {} paused", status.name),
+            timestamp: now,
+            data: Some(serde_json::json!({"paused": true})),
+            metadata: Default::default(),
+        });
+        Some(status)
+    }
+
+    /// Check `service_id` again, starting from `Unknown` as at startup. `None` if the service is unknown.
+    pub fn resume_monitoring(&self, service_id: &str) -> Option<ServiceStatus> {
+        let now = self.clock.now();
+        let status = {
+            let mut status = self.service_states.get_mut(service_id)?;
+            if status.status != HealthStatus::Paused { return Some(status.clone()); }
+            status.status = HealthStatus::Unknown;
+            status.clone()
+        };
+        self.uptime.record(service_id, &HealthStatus::Unknown, now);
+        metrics::update_service_health_metric(&status.id, &status.name, &format!("{:?}", status.service_type), status.critical, &status.status);
+        info!(%service_id, "▶️ Monitoring resumed");
+        record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+            event_type: EventType::MonitoringResumed,
+            service_id: Some(service_id.to_string()),
+            message: format!("Monitoring of {} resumed", status.name),
+            timestamp: now,
+            data: Some(serde_json::json!({"paused": false})),
+            metadata: Default::default(),
+        });
+        self.check_soon(service_id);
+        Some(status)
+    }
+
+    fn note_restart(&self, service_id: &str) {
+        let config = self.config();
+        let storm = match self.restart_tracker.lock() {
+            Ok(mut tracker) => tracker.record(&config.restart_storm, service_id, self.clock.now()),
+            Err(_) => None,
+        };
+        if let Some(affected) = storm {
+            error!("🌪️ Restart storm detected: {} services restarted within {}s", affected.len(), config.restart_storm.window_seconds);
+            record_event(&self.event_history, &self.event_tx, &self.service_states, MonitorEvent {
+                event_type: EventType::SystemAlert,
+                service_id: None,
+                message: format!(
+                    "Restart storm: {} services restarted within {}s; auto-restart suspended for {}s",
+                    affected.len(), config.restart_storm.window_seconds, config.restart_storm.cooldown_seconds
+                ),
+                timestamp: self.clock.now(),
+                data: Some(serde_json::json!({
+                    "severity": "critical",
+                    "kind": "restart_storm",
+                    "affected_services": affected,
+                    "auto_restart_suppressed_seconds": config.restart_storm.cooldown_seconds,
+                })),
+                metadata: Default::default(),
+            });
+        }
+    }
+
+    async fn restart_service_inner(&self, service_id: &str, options: &RestartOptions) -> RestartResult {
+        let start_time = std::time::Instant::now();
+        let current = self.config();
+        let outcome = match current.services.iter().find(|s| s.id == service_id) {
+            None => Err("Service not found".to_string()),
+            Some(config) => match Orchestrator::of(config) {
+                None => Err("No Docker container, Kubernetes target or systemd unit configured for this service".to_string()),
+                Some(orchestrator) => {
+                    let restarted = match options.mode {
+                        RestartMode::Restart => orchestrator.restart(&current, &self.kubernetes).await,
+                        RestartMode::Recreate => orchestrator.recreate(&current, options.fresh_volumes).await,
+                    };
+                    metrics::increment_service_restart(service_id, &config.name, restarted.is_ok());
+                    match restarted {
+                        Ok(message) => {
+                            info!("🔄 {}", message);
+                            Ok(message)
                         }
-                        Err(err) => {
-                            error!("❌ Error executing docker restart: {}", err);
-                            let elapsed = start_time.elapsed().as_secs_f64();
-                            crate::metrics::observe_service_restart_duration(service_id, elapsed);
-                            RestartResult {
-                                service_id: service_id.to_string(),
-                                success: false,
-                                message: format!("Error executing restart command: {}", err),
-                                timestamp: Utc::now(),
-                            }
+                        Err(e) => {
+                            let verb = if options.mode == RestartMode::Recreate { "recreate" } else { "restart" };
+                            error!("❌ Failed to {} {}: {:#}", verb, orchestrator.describe(), e);
+                            Err(format!("Failed to {verb} {}: {e:#}", orchestrator.describe()))
                         }
                     }
-                } else {
-                    let elapsed = start_time.elapsed().as_secs_f64();
-                    crate::metrics::observe_service_restart_duration(service_id, elapsed);
-                    RestartResult {
-                        service_id: service_id.to_string(),
-                        success: false,
-                        message: "No Docker container configured for this service".to_string(),
-                        timestamp: Utc::now(),
-                    }
                 }
-            }
-            None => {
-                let elapsed = start_time.elapsed().as_secs_f64();
-                crate::metrics::observe_service_restart_duration(service_id, elapsed);
-                RestartResult {
-                service_id: service_id.to_string(),
-                success: false,
-                message: "Service not found".to_string(),
-                timestamp: Utc::now(),
-            }}
-        }
+            },
+        };
+        crate::metrics::observe_service_restart_duration(service_id, start_time.elapsed().as_secs_f64());
+        let (success, message) = match outcome {
+            Ok(message) => (true, message),
+            Err(message) => (false, message),
+        };
+        RestartResult { service_id: service_id.to_string(), success, message, timestamp: self.clock.now(), verified: None, recovered_in_ms: None }
     }
 
     pub async fn get_system_metrics(&self) -> SystemMetrics {
@@ -502,10 +2095,17 @@ impl MonitorHandle {
             .filter(|s| s.critical && matches!(s.status, HealthStatus::Unhealthy))
             .count() as u32;
 
-        let response_times: Vec<u64> = services.iter()
+        let mut response_times: Vec<u64> = services.iter()
             .filter_map(|s| s.response_time_ms)
             .collect();
-        
+        response_times.sort_unstable();
+
+        let mut slowest_services: Vec<SlowService> = services.iter()
+            .filter_map(|s| s.response_time_ms.map(|ms| SlowService { id: s.id.clone(), name: s.name.clone(), response_time_ms: ms }))
+            .collect();
+        slowest_services.sort_by(|a, b| b.response_time_ms.cmp(&a.response_time_ms).then_with(|| a.id.cmp(&b.id)));
+        slowest_services.truncate(SLOWEST_SERVICES);
+
         let average_response_time_ms = if response_times.is_empty() {
             0.0
         } else {
@@ -520,15 +2120,87 @@ impl MonitorHandle {
             unhealthy_services,
             critical_services_down,
             average_response_time_ms,
+            p95_response_time_ms: percentile(&response_times, 95.0),
+            p99_response_time_ms: percentile(&response_times, 99.0),
+            slowest_services,
             system_load_average: load_avg,
-            total_requests: crate::metrics::get_total_http_requests() as u64,
+            total_requests: crate::metrics::get_total_http_requests(),
             total_errors,
+            restart_storm_active: self.auto_restart_suppressed(),
+            automation_frozen: self.automation_frozen(),
         }
     }
 
+    /// Record a dead-man's-switch heartbeat. Returns `None` if the service is unknown or not heartbeat-based.
+    pub fn record_heartbeat(&self, service_id: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        let config = self.config();
+        let service = config.services.iter().find(|s| s.id == service_id)?;
+        service.heartbeat_window_seconds?;
+        let now = self.clock.now();
+        self.heartbeats.insert(service_id.to_string(), now);
+        Some(now)
+    }
+
+    /// Publish an externally sourced event (e.g. replayed) through history and subscribers.
+    pub fn inject_event(&self, event: MonitorEvent) {
+        record_event(&self.event_history, &self.event_tx, &self.service_states, event);
+    }
+
+    /// Overwrite a service's current status (e.g. from a replayed status timeline).
+    pub fn apply_status(&self, status: ServiceStatus) {
+        metrics::update_service_health_metric(
+            &status.id,
+            &status.name,
+            &format!("{:?}", status.service_type),
+            status.critical,
+            &status.status,
+        );
+        self.service_states.insert(status.id.clone(), status);
+    }
+
+    pub fn snapshot(&self) -> MonitorSnapshot {
+        MonitorSnapshot {
+            services: self.service_states.iter().map(|s| s.value().clone()).collect(),
+            events: self.event_history.iter().map(|e| (e.key().clone(), e.value().clone())).collect(),
+            heartbeats: self.heartbeats.iter().map(|h| (h.key().clone(), *h.value())).collect(),
+            escalations: self.escalations(),
+            silences: self.silences.list(self.clock.now()),
+            outages: self.outages.iter().map(|o| o.value().clone()).collect(),
+            automation: self.automation.current(self.clock.now()),
+            maintenance: self.silences.maintenance(self.clock.now()),
+            images: self.image_history.snapshot(),
+            uptime: self.uptime.snapshot(),
+            incidents: self.incidents.snapshot(),
+        }
+    }
+
+    /// Open escalations for critical services that are still down.
+    pub fn escalations(&self) -> Vec<Escalation> {
+        let mut list: Vec<Escalation> = self.escalations.iter().map(|e| e.value().clone()).collect();
+        list.sort_by_key(|e| e.since);
+        list
+    }
+
+    pub fn escalation_policy(&self) -> Vec<EscalationStep> {
+        self.config().alerts.escalation.clone()
+    }
+
     pub fn subscribe_events(&self) -> broadcast::Receiver<MonitorEvent> {
         self.event_tx.subscribe()
     }
+
+    /// A page of the recorded events, newest first (see `GET /api/events`).
+    pub fn events(&self, q: &EventQuery) -> Result<EventPage> {
+        let history: Vec<(String, Vec<MonitorEvent>)> = self.event_history.iter().map(|e| (e.key().clone(), e.value().clone())).collect();
+        events::page(history.iter().map(|(id, events)| (id.as_str(), events.as_slice())), q)
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() { return None; }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
 }
 
 fn collect_load_and_errors(event_history: &Arc<DashMap<String, Vec<MonitorEvent>>>) -> (Option<f64>, u64) {
@@ -544,3 +2216,680 @@ fn collect_load_and_errors(event_history: &Arc<DashMap<String, Vec<MonitorEvent>
     }
     (load_avg, error_count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use crate::config::RestartStormConfig;
+    use proptest::prelude::*;
+
+    const WINDOW: u64 = 60;
+
+    fn heartbeat_service() -> ServiceConfig {
+        ServiceConfig {
+            id: "nightly_job".into(),
+            name: "Nightly Job".into(),
+            health_endpoint: String::new(),
+            service_type: ServiceType::Worker,
+            docker_container: None,
+            expected_response_time_ms: 1000,
+            critical: false,
+            check_interval_seconds: None,
+            heartbeat_window_seconds: Some(WINDOW),
+            depends_on: vec![],
+            tags: vec![],
+            probes: vec![],
+            probe_mode: ProbeMode::All,
+            labels: Default::default(),
+            group: None,
+            metadata: Default::default(),
+            auto_update: false,
+            kubernetes: None,
+            systemd_unit: None,
+            auto_restart: None,
+            drain: None,
+            metrics: None,
+            docker_host: None,
+        }
+    }
+
+    async fn mock_monitor() -> (ServiceMonitor, Arc<MockClock>) {
+        mock_monitor_with(|_| {}).await
+    }
+
+    async fn mock_monitor_with(customize: impl FnOnce(&mut Config)) -> (ServiceMonitor, Arc<MockClock>) {
+        let mut cfg = Config { services: vec![heartbeat_service()], ..Default::default() };
+        customize(&mut cfg);
+        cfg.monitoring.check_interval_seconds = 10;
+        cfg.monitoring.enable_docker_stats = false;
+        cfg.monitoring.docker_events = false;
+        let clock = MockClock::new(Utc::now());
+        let monitor = ServiceMonitor::new(cfg).await.unwrap().with_clock(clock.clone());
+        (monitor, clock)
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let times: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&times, 95.0), Some(95));
+        assert_eq!(percentile(&times, 99.0), Some(99));
+        // With few services the tail is the slowest one, not an average
+        assert_eq!(percentile(&[20, 30, 40, 2500], 95.0), Some(2500));
+        assert_eq!(percentile(&[], 95.0), None);
+    }
+
+    #[tokio::test]
+    async fn system_metrics_report_tail_latency_and_slowest_services() {
+        let (monitor, _clock) = mock_monitor_with(|cfg| cfg.services = Config::default().services).await;
+        let handle = monitor.handle();
+        for (i, mut state) in handle.service_states.iter_mut().enumerate() {
+            state.response_time_ms = Some(if state.id == "fks_api" { 3000 } else { 10 + i as u64 });
+        }
+        let metrics = handle.get_system_metrics().await;
+        assert_eq!(metrics.p99_response_time_ms, Some(3000));
+        assert!(metrics.average_response_time_ms < 3000.0);
+        assert_eq!(metrics.slowest_services.len(), SLOWEST_SERVICES);
+        assert_eq!(metrics.slowest_services[0].id, "fks_api");
+        assert!(metrics.slowest_services.windows(2).all(|w| w[0].response_time_ms >= w[1].response_time_ms));
+    }
+
+    #[tokio::test]
+    async fn reload_swaps_services_and_keeps_history() {
+        let (monitor, _clock) = mock_monitor().await;
+        let handle = monitor.handle();
+        handle.record_heartbeat("nightly_job").unwrap();
+        handle.inject_event(MonitorEvent {
+            event_type: EventType::ServiceDown,
+            service_id: Some("nightly_job".into()),
+            message: "missed".into(),
+            timestamp: handle.now(),
+            data: None,
+            metadata: Default::default(),
+        });
+        let mut events = handle.subscribe_events();
+
+        let mut cfg = (*handle.config()).clone();
+        let mut weekly = heartbeat_service();
+        weekly.id = "weekly_job".into();
+        cfg.services = vec![weekly];
+        cfg.alerts.high_latency_threshold_ms = 250;
+        let diff = handle.reload(cfg);
+
+        assert_eq!(diff, ConfigDiff { added: vec!["weekly_job".into()], removed: vec!["nightly_job".into()], changed: vec![], settings_changed: true });
+        let services = handle.get_all_services().await;
+        assert_eq!(services.iter().map(|s| (s.id.as_str(), s.status.clone())).collect::<Vec<_>>(), vec![("weekly_job", HealthStatus::Unknown)]);
+        assert!(handle.record_heartbeat("nightly_job").is_none());
+        assert!(handle.record_heartbeat("weekly_job").is_some());
+        assert_eq!(handle.config().alerts.high_latency_threshold_ms, 250);
+        assert_eq!(handle.event_history.get("nightly_job").map(|e| e.len()), Some(1), "history of removed services is kept");
+        assert_eq!(events.try_recv().unwrap().event_type, EventType::ConfigReloaded);
+    }
+
+    #[tokio::test]
+    async fn discovered_services_survive_reloads_and_yield_to_configured_ones() {
+        let (monitor, _clock) = mock_monitor().await;
+        let handle = monitor.handle();
+        let mut found = heartbeat_service();
+        found.id = "labelled_job".into();
+        let diff = handle.set_discovered("docker_labels", vec![found.clone()]);
+        assert_eq!(diff.added, vec!["labelled_job"]);
+        assert_eq!(handle.discovery_source("labelled_job"), Some("docker_labels"));
+        assert_eq!(handle.configured().services.len(), 1, "configured view leaves discovered services out");
+
+        // A reload of the file keeps them
+        let diff = handle.reload(handle.configured());
+        assert!(diff.is_empty(), "{diff:?}");
+        assert!(handle.config().services.iter().any(|s| s.id == "labelled_job"));
+
+        // A configured service with the same id wins
+        let mut cfg = handle.configured();
+        let mut configured = found.clone();
+        configured.name = "Configured".into();
+        cfg.services.push(configured);
+        handle.reload(cfg);
+        assert_eq!(handle.discovery_source("labelled_job"), None);
+        assert_eq!(handle.config().services.iter().filter(|s| s.id == "labelled_job").map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Configured"]);
+
+        let mut other = heartbeat_service();
+        other.id = "other_job".into();
+        handle.set_discovered("docker_labels", vec![other.clone()]);
+        // The same id from an earlier source takes over
+        let mut from_compose = other.clone();
+        from_compose.name = "From compose".into();
+        let diff = handle.set_discovered("compose", vec![from_compose]);
+        assert_eq!(diff.changed, vec!["other_job"]);
+        assert_eq!(handle.discovery_source("other_job"), Some("compose"));
+        handle.set_discovered("compose", vec![]);
+        assert_eq!(handle.config().services.iter().find(|s| s.id == "other_job").unwrap().name, other.name);
+        let diff = handle.set_discovered("docker_labels", vec![]);
+        assert_eq!(diff.removed, vec!["other_job"], "gone containers are removed");
+        assert_eq!(handle.config().services.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn active_incidents_are_exported_as_info_metrics() {
+        let (monitor, clock) = mock_monitor_with(|cfg| {
+            cfg.services[0].id = "info_job".into();
+            cfg.services[0].critical = true;
+            cfg.services[0].labels = [("team", "data"), ("owner", "alice"), ("version", "1.4.2")].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        }).await;
+        let handle = monitor.handle();
+        let service = monitor.config().services[0].clone();
+        assert!(handle.active_incidents().await.is_empty());
+
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        clock.advance(Duration::from_secs(WINDOW + 1));
+        let down_at = clock.now();
+        monitor.check_service_health(&service).await;
+        clock.advance(Duration::from_secs(60));
+        monitor.check_service_health(&service).await;
+
+        let incidents = handle.active_incidents().await;
+        assert_eq!(incidents, vec![ActiveIncident { service_id: "info_job".into(), service_name: service.name.clone(), severity: Severity::Critical, since: down_at, silenced: false }]);
+
+        metrics::set_info_metrics(&handle.config().services, &handle.get_all_services().await, &incidents);
+        let text = prometheus::TextEncoder::new().encode_to_string(&metrics::PROMETHEUS_REGISTRY.gather()).unwrap();
+        let line = |name: &str| text.lines().find(|l| l.starts_with(name) && l.contains(r#"service_id="info_job""#)).unwrap_or_default().to_string();
+        assert!(line("fks_service_info").contains(r#"owner="alice""#), "{text}");
+        assert!(line("fks_service_info").contains(r#"team="data""#));
+        assert!(line("fks_service_info").contains(r#"version="1.4.2""#));
+        let incident = line("fks_active_incident_info");
+        assert!(incident.contains(r#"severity="critical""#) && incident.ends_with(" 1"), "{incident}");
+        assert!(incident.contains(&format!(r#"since="{}""#, down_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))));
+    }
+
+    #[tokio::test]
+    async fn service_metadata_passes_through_statuses_events_and_alerts() {
+        let metadata: std::collections::BTreeMap<String, String> = [("runbook", "https://wiki/nightly"), ("fks_web.icon", "moon")].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let (monitor, clock) = mock_monitor_with(|cfg| cfg.services[0].metadata = metadata.clone()).await;
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        let service = monitor.config().services[0].clone();
+        assert_eq!(handle.get_all_services().await[0].metadata, metadata);
+
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        clock.advance(Duration::from_secs(WINDOW + 1));
+        monitor.check_service_health(&service).await;
+
+        let down = std::iter::from_fn(|| events.try_recv().ok()).find(|e| e.event_type == EventType::ServiceDown).unwrap();
+        assert_eq!(down.metadata, metadata);
+        let alert = crate::alerts::AlertPayload::from_event(&down, &handle.config()).unwrap();
+        assert_eq!(alert.metadata, metadata);
+        assert_eq!(serde_json::to_value(&alert).unwrap()["metadata"]["runbook"], "https://wiki/nightly");
+    }
+
+    #[tokio::test]
+    async fn maintenance_holds_back_service_down_until_it_is_over() {
+        let (monitor, clock) = mock_monitor().await;
+        let handle = monitor.handle();
+        let service = monitor.config().services[0].clone();
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        let mut events = handle.subscribe_events();
+        let mut types = || std::iter::from_fn(|| events.try_recv().ok()).map(|e| e.event_type).collect::<Vec<_>>();
+
+        handle.start_maintenance(MaintenanceRequest { duration_minutes: Some(10), ..Default::default() }).unwrap();
+        clock.advance(Duration::from_secs(WINDOW + 1));
+        monitor.check_service_health(&service).await;
+        assert_eq!(handle.get_all_services().await[0].status, HealthStatus::Unhealthy, "checks keep running");
+        assert!(handle.get_all_services().await[0].silenced);
+        assert_eq!(types(), [EventType::MaintenanceStarted]);
+
+        assert!(handle.end_maintenance().is_some());
+        monitor.check_service_health(&service).await;
+        assert_eq!(types(), [EventType::MaintenanceEnded, EventType::ServiceDown], "still down once maintenance is over");
+    }
+
+    #[tokio::test]
+    async fn container_events_take_services_down_immediately() {
+        let (monitor, _clock) = mock_monitor_with(|cfg| cfg.services[0].docker_container = Some("fks-nightly-1".into())).await;
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        handle.service_states.get_mut("nightly_job").unwrap().status = HealthStatus::Healthy;
+
+        monitor.apply_container_event(None, "some-other-container", ContainerEvent::Died { exit_code: Some(1) }).await;
+        monitor.apply_container_event(Some("edge"), "fks-nightly-1", ContainerEvent::Died { exit_code: Some(1) }).await;
+        assert_eq!(handle.get_all_services().await[0].status, HealthStatus::Healthy);
+        monitor.apply_container_event(None, "fks-nightly-1", ContainerEvent::OomKilled).await;
+        monitor.apply_container_event(None, "fks-nightly-1", ContainerEvent::Died { exit_code: Some(137) }).await;
+        let status = handle.get_all_services().await.remove(0);
+        assert_eq!(status.status, HealthStatus::Unhealthy);
+        assert_eq!(status.error_message.as_deref(), Some("container fks-nightly-1 was killed: out of memory"));
+        let down: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).filter(|e| e.event_type == EventType::ServiceDown).collect();
+        assert_eq!(down.len(), 1);
+
+        monitor.apply_container_event(None, "fks-nightly-1", ContainerEvent::Started).await;
+        assert_eq!(monitor.check_queue.take(), ["nightly_job"], "recovery is confirmed by a check");
+    }
+
+    #[tokio::test]
+    async fn container_stats_samples_update_resource_metrics() {
+        let (monitor, _clock) = mock_monitor_with(|cfg| cfg.services[0].docker_container = Some("fks-nightly-1".into())).await;
+        let sample = docker_stats::ContainerStats { name: "fks-nightly-1".into(), cpu_percent: Some(12.5), memory_usage_bytes: Some(64 << 20), ..Default::default() };
+        monitor.record_container_stats(None, &sample);
+        monitor.record_container_stats(Some("edge"), &docker_stats::ContainerStats { cpu_percent: Some(90.0), ..sample.clone() });
+        monitor.record_container_stats(None, &docker_stats::ContainerStats { cpu_percent: Some(30.0), ..sample.clone() });
+        monitor.record_container_stats(None, &docker_stats::ContainerStats { name: "fks-nightly-1".into(), ..Default::default() });
+        let metrics = monitor.resource_metrics.get("nightly_job").unwrap().clone();
+        assert_eq!((metrics.cpu_usage_percent, metrics.memory_usage_mb), (Some(30.0), Some(64)), "empty samples keep the last reading");
+    }
+
+    async fn settle() {
+        for _ in 0..20 { tokio::task::yield_now().await; }
+    }
+
+    #[tokio::test]
+    async fn heartbeat_loop_runs_on_mock_time() {
+        let (monitor, clock) = mock_monitor().await;
+        let handle = monitor.start().await.unwrap();
+        let mut events = handle.subscribe_events();
+        let status = |h: &MonitorHandle| h.service_states.get("nightly_job").unwrap().status.clone();
+
+        settle().await;
+        assert_eq!(status(&handle), HealthStatus::Unknown, "grace period covers the first window");
+
+        clock.advance(Duration::from_secs(WINDOW + 10));
+        settle().await;
+        assert_eq!(status(&handle), HealthStatus::Unhealthy);
+
+        handle.record_heartbeat("nightly_job").unwrap();
+        clock.advance(Duration::from_secs(10));
+        settle().await;
+        assert_eq!(status(&handle), HealthStatus::Healthy);
+        let up = std::iter::from_fn(|| events.try_recv().ok()).find(|e| e.event_type == EventType::ServiceUp);
+        assert!(up.is_some(), "recovery emits ServiceUp");
+    }
+
+    #[tokio::test]
+    async fn paused_services_are_neither_checked_nor_alerted_on() {
+        let (monitor, clock) = mock_monitor().await;
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        let service = heartbeat_service();
+        let status = |h: &MonitorHandle| h.service_states.get("nightly_job").unwrap().status.clone();
+
+        assert_eq!(handle.pause_monitoring("nightly_job").unwrap().status, HealthStatus::Paused);
+        assert_eq!(events.try_recv().unwrap().event_type, EventType::MonitoringPaused);
+        clock.advance(Duration::from_secs(WINDOW * 2));
+        monitor.check_service_health(&service).await;
+        monitor.record_failure(&service, "container died".into()).await;
+        assert_eq!(status(&handle), HealthStatus::Paused, "missed heartbeats and container events are ignored");
+        assert!(events.try_recv().is_err());
+        assert!(handle.pause_monitoring("nope").is_none());
+
+        assert_eq!(handle.resume_monitoring("nightly_job").unwrap().status, HealthStatus::Unknown);
+        assert_eq!(events.try_recv().unwrap().event_type, EventType::MonitoringResumed);
+        monitor.check_service_health(&service).await;
+        assert_eq!(status(&handle), HealthStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn rule_breach_emits_firing_and_resolved_alerts() {
+        let rule = crate::config::AlertRule {
+            name: "hot".into(),
+            expr: "cpu_usage_percent > 90 for 1m".parse().unwrap(),
+            services: vec![],
+            severity: "critical".into(),
+        };
+        let (monitor, clock) = mock_monitor_with(|cfg| cfg.alerts.rules = vec![rule]).await;
+        let mut events = monitor.event_tx.subscribe();
+        let mut engine = RuleEngine::default();
+        let set_cpu = |v| monitor.resource_metrics.entry("nightly_job".into()).or_default().cpu_usage_percent = Some(v);
+
+        set_cpu(97.0);
+        monitor.evaluate_rules(&mut engine).await;
+        clock.advance(Duration::from_secs(60));
+        monitor.evaluate_rules(&mut engine).await;
+        let fired = events.try_recv().unwrap();
+        assert_eq!(fired.event_type, EventType::SystemAlert);
+        assert_eq!(fired.data.as_ref().unwrap()["state"], "firing");
+        assert_eq!(fired.data.as_ref().unwrap()["severity"], "critical");
+        assert!(events.try_recv().is_err());
+
+        set_cpu(20.0);
+        monitor.evaluate_rules(&mut engine).await;
+        assert_eq!(events.try_recv().unwrap().data.unwrap()["state"], "resolved");
+    }
+
+    #[tokio::test]
+    async fn healing_rules_only_act_when_enforced() {
+        let rule = |name: &str, enforce| crate::config::HealingRule {
+            name: name.into(),
+            rule: "when cpu_usage_percent > 90 for 1m and restarts_last_hour < 1 then alert".parse().unwrap(),
+            services: vec![],
+            enforce,
+            cooldown_seconds: 300,
+        };
+        let (monitor, clock) = mock_monitor_with(|cfg| cfg.healing = vec![rule("watch", false), rule("act", true)]).await;
+        let mut events = monitor.event_tx.subscribe();
+        let mut engine = HealingEngine::default();
+        monitor.resource_metrics.entry("nightly_job".into()).or_default().cpu_usage_percent = Some(97.0);
+
+        monitor.evaluate_healing(&mut engine).await;
+        clock.advance(Duration::from_secs(60));
+        monitor.evaluate_healing(&mut engine).await;
+        let alert = events.try_recv().unwrap();
+        assert_eq!(alert.event_type, EventType::SystemAlert);
+        assert_eq!(alert.data.as_ref().unwrap()["rule"], "act");
+        assert!(events.try_recv().is_err(), "the dry run only logs");
+
+        // Restarted within the hour: the rule no longer holds
+        monitor.restart_limiter.record(&Default::default(), "nightly_job", clock.now());
+        clock.advance(Duration::from_secs(300));
+        monitor.evaluate_healing(&mut engine).await;
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_trigger_auto_restarts_until_the_hourly_budget_is_spent() {
+        let (monitor, clock) = mock_monitor_with(|cfg| {
+            cfg.services[0].auto_restart = Some(AutoRestartPolicy { enabled: true, failure_threshold: 2, max_attempts_per_hour: 1 });
+        }).await;
+        let mut events = monitor.handle().subscribe_events();
+        let service = monitor.config().services[0].clone();
+        clock.advance(Duration::from_secs(WINDOW + 1));
+        let mut auto_restart_events = async |checks: usize| {
+            for _ in 0..checks { monitor.check_heartbeat(&service, WINDOW).await; }
+            let mut seen = Vec::new();
+            while let Ok(Ok(event)) = tokio::time::timeout(Duration::from_millis(200), events.recv()).await {
+                if let Some(reason) = event.data.as_ref().and_then(|d| d["reason"].as_str()) {
+                    seen.push((event.event_type, reason.to_string()));
+                }
+            }
+            seen
+        };
+
+        assert!(auto_restart_events(1).await.is_empty(), "below the threshold");
+        // No container to restart here, so the attempt is reported as failed
+        assert_eq!(auto_restart_events(1).await, [(EventType::SystemAlert, "auto_restart".to_string())]);
+        assert_eq!(auto_restart_events(3).await, [(EventType::SystemAlert, "auto_restart_exhausted".to_string())]);
+
+        monitor.handle().freeze_automation(FreezeRequest::default());
+        clock.advance(Duration::from_secs(3600));
+        assert!(auto_restart_events(2).await.iter().all(|(_, reason)| reason != "auto_restart"), "frozen automation holds restarts");
+    }
+
+    #[tokio::test]
+    async fn rollbacks_recreate_from_the_previous_image() {
+        let (monitor, _clock) = mock_monitor_with(|cfg| cfg.services[0].docker_container = Some("fks-test-no-such-container".into())).await;
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        let deployed = |image_id: &str| DeployedImage { image: "nuniesmith/fks_api:latest".into(), image_id: image_id.into(), repo_digest: None, since: Utc::now() };
+
+        assert!(!handle.rollback_service("nightly_job").await.success, "nothing to roll back to yet");
+        monitor.image_history.observe("nightly_job", deployed("sha256:good"));
+        monitor.image_history.observe("nightly_job", deployed("sha256:bad"));
+        assert_eq!(handle.get_service_health("nightly_job").await.unwrap().images.unwrap().previous.unwrap().image_id, "sha256:good");
+
+        // There is no such container here, so the attempt fails and the record stays as it was
+        let result = handle.rollback_service("nightly_job").await;
+        assert!(!result.success);
+        let event = std::iter::from_fn(|| events.try_recv().ok()).find(|e| e.data.as_ref().is_some_and(|d| d["reason"] == "rollback")).unwrap();
+        assert_eq!(event.event_type, EventType::SystemAlert);
+        assert_eq!(event.data.as_ref().unwrap()["to"]["image_id"], "sha256:good");
+        assert_eq!(handle.image_record("nightly_job").unwrap().current.unwrap().image_id, "sha256:bad");
+        assert_eq!(handle.snapshot().images["nightly_job"].previous.as_ref().unwrap().image_id, "sha256:good");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn restarts_are_verified_by_a_healthy_check_after_them() {
+        let (monitor, clock) = mock_monitor_with(|_| {}).await;
+        let monitor = Arc::new(monitor);
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        let cfg = crate::config::RestartVerificationConfig { enabled: true, timeout_seconds: 10, poll_interval_seconds: 1 };
+        let service = monitor.config().services[0].clone();
+
+        assert_eq!(handle.verify_restart(&service.id, &cfg).await, None, "nothing checked it");
+        let alert = std::iter::from_fn(|| events.try_recv().ok()).find(|e| e.event_type == EventType::SystemAlert).unwrap();
+        assert_eq!(alert.data.unwrap()["reason"], "restart_unverified");
+
+        clock.advance(Duration::from_secs(1));
+        let checker = monitor.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(2500)).await;
+            checker.handle().record_heartbeat(&service.id).unwrap();
+            checker.check_service_health(&service).await;
+        });
+        let took = handle.verify_restart("nightly_job", &cfg).await.unwrap();
+        assert_eq!(took.as_secs(), 3, "seen at the next poll after the check");
+    }
+
+    #[tokio::test]
+    async fn a_failed_drain_calls_the_restart_off_unless_forced() {
+        let (monitor, _clock) = mock_monitor_with(|cfg| {
+            cfg.services[0].docker_container = Some("fks-test-no-such-container".into());
+            cfg.services[0].drain = Some(DrainConfig {
+                url: "http://127.0.0.1:9/admin/drain".into(), method: "POST".into(), status_url: None, timeout_seconds: 5, poll_interval_seconds: 1, force: false,
+            });
+        }).await;
+        let handle = monitor.handle();
+
+        let result = handle.restart_service("nightly_job").await;
+        assert!(result.message.starts_with("Drain failed, restart called off"), "{}", result.message);
+        let backoff = || monitor.service_states.get("nightly_job").unwrap().restart_backoff.clone();
+        assert_eq!(backoff(), None, "nothing reached Docker");
+
+        let mut config = (*monitor.config()).clone();
+        config.services[0].drain.as_mut().unwrap().force = true;
+        handle.reload(config);
+        let result = handle.restart_service("nightly_job").await;
+        assert!(!result.message.starts_with("Drain failed"), "went on to Docker: {}", result.message);
+        assert_eq!(backoff().unwrap().restarts, 1);
+    }
+
+    #[tokio::test]
+    async fn restarts_over_the_rate_limit_are_refused_until_the_window_moves_on() {
+        let (monitor, clock) = mock_monitor_with(|cfg| {
+            cfg.services[0].docker_container = Some("fks-test-no-such-container".into());
+            cfg.restart_backoff = crate::config::RestartBackoffConfig { initial_delay_seconds: 0, max_delay_seconds: 0, max_restarts: 100, reset_after_seconds: 600 };
+            cfg.restart_rate_limit = crate::config::RestartRateLimitConfig { max_restarts: 2, window_seconds: 60 };
+        }).await;
+        let handle = monitor.handle();
+
+        for _ in 0..2 {
+            assert!(!handle.restart_service("nightly_job").await.message.starts_with("Restart rate limit"));
+            clock.advance(Duration::from_secs(1));
+        }
+        let result = handle.restart_service("nightly_job").await;
+        assert!(result.message.starts_with("Restart rate limit of 2 per 60s reached"), "{}", result.message);
+        assert!(matches!(handle.restart_refusal("nightly_job"), Some(Refusal::RateLimited { .. })));
+        assert_eq!(monitor.service_states.get("nightly_job").unwrap().restart_backoff.as_ref().unwrap().restarts, 2, "refusals don't count");
+
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(handle.restart_refusal("nightly_job"), None);
+    }
+
+    #[tokio::test]
+    async fn critical_outage_escalates_until_recovery() {
+        let (monitor, clock) = mock_monitor_with(|cfg| {
+            cfg.services[0].critical = true;
+            cfg.alerts.escalation = vec![crate::config::EscalationStep { after_minutes: 5, channels: vec!["pagerduty".into()] }];
+        }).await;
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        let service = monitor.config().services[0].clone();
+        let escalation_states = |events: &mut broadcast::Receiver<MonitorEvent>| -> Vec<String> {
+            std::iter::from_fn(|| events.try_recv().ok())
+                .filter_map(|e| e.data)
+                .filter(|d| d["kind"] == "escalation")
+                .map(|d| d["state"].as_str().unwrap_or_default().to_string())
+                .collect()
+        };
+
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        clock.advance(Duration::from_secs(WINDOW + 1));
+        monitor.check_service_health(&service).await;
+        assert_eq!(handle.escalations().len(), 1, "ServiceDown opens an escalation");
+
+        clock.advance(Duration::from_secs(4 * 60));
+        monitor.check_escalations().await;
+        assert!(escalation_states(&mut events).is_empty());
+        clock.advance(Duration::from_secs(60));
+        monitor.check_escalations().await;
+        assert_eq!(escalation_states(&mut events), vec!["level_1"]);
+        assert_eq!(handle.escalations()[0].notified_channels, vec!["pagerduty"]);
+
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        monitor.check_escalations().await;
+        assert_eq!(escalation_states(&mut events), vec!["resolved"]);
+        assert!(handle.escalations().is_empty());
+    }
+
+    #[tokio::test]
+    async fn acknowledged_incidents_stop_escalating() {
+        let (monitor, clock) = mock_monitor_with(|cfg| {
+            cfg.services[0].critical = true;
+            cfg.alerts.escalation = vec![crate::config::EscalationStep { after_minutes: 5, channels: vec!["pagerduty".into()] }];
+            cfg.alerts.reminder_interval_minutes = Some(5);
+        }).await;
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        let service = monitor.config().services[0].clone();
+        let mut types = || std::iter::from_fn(|| events.try_recv().ok()).map(|e| e.event_type).collect::<Vec<_>>();
+
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        clock.advance(Duration::from_secs(WINDOW + 1));
+        monitor.check_service_health(&service).await;
+        assert_eq!(types(), [EventType::ServiceDown, EventType::IncidentOpened]);
+        let incident = handle.incidents(Some(IncidentState::Open)).remove(0);
+        assert_eq!(incident.service_id, service.id);
+
+        handle.acknowledge_incident(&incident.id, &Actor::verified("alice"), None).unwrap();
+        clock.advance(Duration::from_secs(10 * 60));
+        monitor.check_escalations().await;
+        monitor.check_reminders(5).await;
+        assert_eq!(types(), [EventType::IncidentAcknowledged], "no escalation or reminder once acknowledged");
+
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        assert!(types().contains(&EventType::IncidentResolved));
+        let resolved = handle.incidents(None).remove(0);
+        assert_eq!((resolved.state, resolved.acknowledgements[0].by.as_str()), (IncidentState::Resolved, "alice"));
+        assert_eq!(handle.resolve_incident(&incident.id, &Actor::verified("bob"), None).unwrap_err(), IncidentError::AlreadyResolved);
+    }
+
+    #[tokio::test]
+    async fn ongoing_outage_sends_reminders_then_resolved() {
+        let (monitor, clock) = mock_monitor_with(|cfg| cfg.alerts.reminder_interval_minutes = Some(30)).await;
+        let handle = monitor.handle();
+        let mut events = handle.subscribe_events();
+        let service = heartbeat_service();
+        let reminders = |events: &mut broadcast::Receiver<MonitorEvent>| -> Vec<(String, String)> {
+            std::iter::from_fn(|| events.try_recv().ok())
+                .filter(|e| e.data.as_ref().is_some_and(|d| d["kind"] == "reminder"))
+                .map(|e| (e.data.unwrap()["state"].as_str().unwrap_or_default().to_string(), e.message))
+                .collect()
+        };
+
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        clock.advance(Duration::from_secs(WINDOW + 1));
+        monitor.check_service_health(&service).await;
+        clock.advance(Duration::from_secs(29 * 60));
+        monitor.check_reminders(30).await;
+        assert!(reminders(&mut events).is_empty());
+        clock.advance(Duration::from_secs(60));
+        monitor.check_reminders(30).await;
+        assert_eq!(reminders(&mut events), vec![("reminder_1".into(), "Reminder: Nightly Job still unhealthy after 30m".into())]);
+        monitor.check_reminders(30).await;
+        assert!(reminders(&mut events).is_empty(), "one reminder per interval");
+
+        clock.advance(Duration::from_secs(15 * 60));
+        handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+        monitor.check_reminders(30).await;
+        assert_eq!(reminders(&mut events), vec![("resolved".into(), "Resolved: Nightly Job recovered after 45m".into())]);
+        assert!(monitor.outages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn snapshot_restores_state_into_successor() {
+        let (monitor, _clock) = mock_monitor().await;
+        let handle = monitor.handle();
+        let service = heartbeat_service();
+        let beat = handle.record_heartbeat(&service.id).unwrap();
+        monitor.check_service_health(&service).await;
+
+        let json = serde_json::to_vec(&handle.snapshot()).unwrap();
+        let mut snapshot: MonitorSnapshot = serde_json::from_slice(&json).unwrap();
+        snapshot.heartbeats.insert("removed_service".into(), beat);
+
+        let (successor, _clock) = mock_monitor().await;
+        let restored = successor.restore(snapshot).handle();
+        assert_eq!(restored.get_all_services().await[0].status, HealthStatus::Healthy);
+        assert_eq!(restored.snapshot().heartbeats.get(&service.id), Some(&beat));
+        assert!(!restored.snapshot().heartbeats.contains_key("removed_service"), "unknown services are dropped");
+        assert_eq!(restored.snapshot().events.get(&service.id).map(Vec::len), handle.snapshot().events.get(&service.id).map(Vec::len));
+    }
+
+    proptest! {
+        // Each case builds a monitor (and its HTTP client), so keep the case count modest
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // Heartbeat status machine: after any sequence of (advance, maybe beat, check) steps the
+        // status matches the window rule and ServiceUp fires exactly on Unhealthy -> Healthy.
+        #[test]
+        fn heartbeat_status_machine(steps in prop::collection::vec((0u64..150, any::<bool>()), 1..40)) {
+            let (monitor, clock) = futures::executor::block_on(mock_monitor());
+            let handle = monitor.handle();
+            let mut events = handle.subscribe_events();
+            let service = heartbeat_service();
+            let started = clock.now();
+            let mut last_beat = None;
+            let mut expected = HealthStatus::Unknown;
+
+            for (advance, beat) in steps {
+                clock.advance(Duration::from_secs(advance));
+                if beat { last_beat = handle.record_heartbeat(&service.id); }
+                futures::executor::block_on(monitor.check_service_health(&service));
+
+                let now = clock.now();
+                let previous = expected.clone();
+                expected = match last_beat {
+                    Some(t) if (now - t).num_seconds() <= WINDOW as i64 => HealthStatus::Healthy,
+                    Some(_) => HealthStatus::Unhealthy,
+                    None if (now - started).num_seconds() > WINDOW as i64 => HealthStatus::Unhealthy,
+                    None => previous.clone(),
+                };
+                let actual = handle.service_states.get(&service.id).unwrap().status.clone();
+                prop_assert_eq!(&actual, &expected);
+
+                let recovered = std::iter::from_fn(|| events.try_recv().ok()).any(|e| e.event_type == EventType::ServiceUp);
+                prop_assert_eq!(recovered, previous == HealthStatus::Unhealthy && expected == HealthStatus::Healthy);
+            }
+        }
+    }
+
+    #[test]
+    fn restart_storm_triggers_once_on_distinct_services() {
+        let cfg = RestartStormConfig { threshold: 3, window_seconds: 60, cooldown_seconds: 600 };
+        let mut tracker = RestartTracker::default();
+        let t0 = Utc::now();
+        assert!(tracker.record(&cfg, "a", t0).is_none());
+        // Same service restarting repeatedly is not a fleet storm
+        assert!(tracker.record(&cfg, "a", t0).is_none());
+        assert!(tracker.record(&cfg, "b", t0).is_none());
+        let affected = tracker.record(&cfg, "c", t0).expect("storm");
+        assert_eq!(affected, vec!["a", "b", "c"]);
+        assert!(tracker.is_suppressed(t0));
+        // Further restarts during cooldown do not re-alert
+        assert!(tracker.record(&cfg, "d", t0).is_none());
+        assert!(!tracker.is_suppressed(t0 + chrono::Duration::seconds(601)));
+    }
+
+    #[test]
+    fn restarts_outside_window_are_forgotten() {
+        let cfg = RestartStormConfig { threshold: 2, window_seconds: 60, cooldown_seconds: 600 };
+        let mut tracker = RestartTracker::default();
+        let t0 = Utc::now();
+        assert!(tracker.record(&cfg, "a", t0).is_none());
+        assert!(tracker.record(&cfg, "b", t0 + chrono::Duration::seconds(120)).is_none());
+    }
+}
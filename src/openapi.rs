@@ -0,0 +1,53 @@
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::OpenApi;
+use utoipa::Modify;
+
+/// Security scheme of the `x-api-key` header
+pub const API_KEY: &str = "api_key";
+/// Security scheme of `Authorization: Bearer <JWT>`
+pub const BEARER: &str = "bearer";
+
+/// Adds the two ways to authenticate write calls; either one is enough.
+pub struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(API_KEY, SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-api-key"))));
+        components.add_security_scheme(BEARER, SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()));
+    }
+}
+
+/// Swagger UI page rendering the document at `spec_url`, with scripts and styles from `assets`.
+pub fn swagger_ui_page(spec_url: &str, assets: &str) -> String {
+    let assets = assets.trim_end_matches('/');
+    format!(r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>FKS Master API</title>
+  <link rel="stylesheet" href="{assets}/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="{assets}/swagger-ui-bundle.js"></script>
+  <script>window.ui = SwaggerUIBundle({{ url: "{spec_url}", dom_id: "#swagger-ui" }});</script>
+</body>
+</html>
+"##)
+}
+
+/// `application/problem+json` body of error responses; extension members such as `retry_at` or
+/// `stderr` may come along.
+#[derive(utoipa::ToSchema)]
+#[allow(dead_code)] // only describes the bodies `problem::Problem` serializes
+pub struct ProblemBody {
+    /// `urn:fks:problem:<code>`
+    #[schema(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    /// e.g. `unauthorized`, `service_not_found`, `docker_unavailable`, `rate_limited`
+    pub code: String,
+    pub detail: String,
+}
@@ -0,0 +1,121 @@
+use crate::config::Config;
+use crate::compose;
+use crate::docker_hosts;
+use crate::kubernetes;
+use crate::systemd;
+use crate::models::{KubernetesTarget, ServiceConfig};
+use anyhow::{bail, Result};
+use std::time::Duration;
+
+/// How long a restarted systemd unit may take to start, on top of the stop timeout
+const SYSTEMD_START_SECONDS: u64 = 90;
+
+/// What runs a service, and so how it is restarted: a Docker container, Kubernetes pods or a systemd unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orchestrator<'a> {
+    /// `host` is an entry of `[hosts]` or a daemon URL, `None` the local daemon
+    Docker { container: &'a str, host: Option<&'a str> },
+    Kubernetes(&'a KubernetesTarget),
+    Systemd { unit: &'a str },
+}
+
+impl<'a> Orchestrator<'a> {
+    /// `None` for services that are only probed. With more than one set, a `kubernetes` target wins over
+    /// a `docker_container`, which wins over a `systemd_unit`.
+    pub fn of(service: &'a ServiceConfig) -> Option<Self> {
+        service.kubernetes.as_ref().map(Orchestrator::Kubernetes)
+            .or_else(|| service.docker_container.as_deref().map(|container| Orchestrator::Docker { container, host: service.docker_host.as_deref() }))
+            .or_else(|| service.systemd_unit.as_deref().map(|unit| Orchestrator::Systemd { unit }))
+    }
+
+    /// What is restarted, for log and result messages.
+    pub fn describe(&self) -> String {
+        match self {
+            Orchestrator::Docker { container, host: Some(host) } => format!("container {container} on {host}"),
+            Orchestrator::Docker { container, host: None } => format!("container {container}"),
+            Orchestrator::Kubernetes(KubernetesTarget { namespace, workload: Some(workload), .. }) => format!("{workload} in namespace {namespace}"),
+            Orchestrator::Kubernetes(KubernetesTarget { namespace, selector, .. }) => format!("pods {selector} in namespace {namespace}"),
+            Orchestrator::Systemd { unit } => format!("unit {unit}"),
+        }
+    }
+
+    /// Restart the workload, returning what was done. Containers and pods get
+    /// `monitoring.restart_stop_timeout_seconds` to stop before they are killed; systemd units stop
+    /// according to their own unit settings.
+    pub async fn restart(&self, config: &Config, kubernetes: &kubernetes::Clients) -> Result<String> {
+        let stop_timeout_seconds = config.monitoring.restart_stop_timeout_seconds;
+        match self {
+            Orchestrator::Docker { container, host } => {
+                restart_container(config, *host, container, stop_timeout_seconds).await?;
+                Ok(format!("Successfully restarted {}", self.describe()))
+            }
+            Orchestrator::Kubernetes(target) => {
+                let client = kubernetes.get(&config.kubernetes, Duration::from_secs(30)).await?;
+                match &target.workload {
+                    Some(workload) => {
+                        client.rollout_restart(&target.namespace, workload).await?;
+                        Ok(format!("Started a rollout restart of {workload} in namespace {}", target.namespace))
+                    }
+                    None => {
+                        let deleted = client.delete_pods(&target.namespace, &target.selector, stop_timeout_seconds).await?;
+                        Ok(format!("Deleted {deleted} pods matching {} in namespace {}", target.selector, target.namespace))
+                    }
+                }
+            }
+            Orchestrator::Systemd { unit } => {
+                systemd::restart_unit(unit, Duration::from_secs(stop_timeout_seconds + SYSTEMD_START_SECONDS)).await?;
+                Ok(format!("Successfully restarted unit {unit}"))
+            }
+        }
+    }
+
+    /// Remove the container and create it anew from its compose definition; only containers can be
+    /// recreated.
+    pub async fn recreate(&self, config: &Config, fresh_volumes: bool) -> Result<String> {
+        let Orchestrator::Docker { container, host } = self else {
+            bail!("only containers can be recreated; {} can only be restarted", self.describe());
+        };
+        let docker = docker_hosts::connect(config, *host)?;
+        let host_args = docker_hosts::cli_args(config, *host)?;
+        compose::recreate_container(&docker, &host_args, container, fresh_volumes, config.monitoring.restart_stop_timeout_seconds).await
+    }
+}
+
+/// Restart `container` on `host` through the Docker API, giving it `stop_timeout_seconds` to stop before it is killed.
+async fn restart_container(config: &Config, host: Option<&str>, container: &str, stop_timeout_seconds: u64) -> Result<()> {
+    // The request lasts as long as the stop takes, so it needs more than the client's default timeout
+    let docker = docker_hosts::connect(config, host)?.with_timeout(Duration::from_secs(stop_timeout_seconds + 30));
+    let options = bollard::container::RestartContainerOptions { t: stop_timeout_seconds as isize };
+    docker.restart_container(container, Some(options)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kubernetes_targets_take_over_restarts() {
+        let mut service = Config::default().services.remove(0);
+        assert_eq!(Orchestrator::of(&service), Some(Orchestrator::Docker { container: "fks_api", host: None }));
+        service.docker_host = Some("edge".into());
+        assert_eq!(Orchestrator::of(&service).unwrap().describe(), "container fks_api on edge");
+        let target = KubernetesTarget { namespace: "fks".into(), selector: "app=fks-api".into(), workload: None };
+        service.kubernetes = Some(target.clone());
+        assert_eq!(Orchestrator::of(&service).unwrap().describe(), "pods app=fks-api in namespace fks");
+        service.kubernetes = Some(KubernetesTarget { workload: Some("fks-api".into()), ..target });
+        assert_eq!(Orchestrator::of(&service).unwrap().describe(), "fks-api in namespace fks");
+        service.kubernetes = None;
+        service.docker_container = None;
+        assert_eq!(Orchestrator::of(&service), None);
+        service.systemd_unit = Some("nginx.service".into());
+        assert_eq!(Orchestrator::of(&service).unwrap().describe(), "unit nginx.service");
+    }
+
+    #[tokio::test]
+    async fn only_containers_can_be_recreated() {
+        let unit = Orchestrator::Systemd { unit: "nginx.service" };
+        let error = unit.recreate(&Config::default(), false).await.unwrap_err();
+        assert_eq!(error.to_string(), "only containers can be recreated; unit nginx.service can only be restarted");
+    }
+}
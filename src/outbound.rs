@@ -0,0 +1,260 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
+
+use crate::metrics;
+
+/// Consecutive connectivity failures that open a destination's circuit
+const FAILURE_THRESHOLD: u32 = 5;
+/// How long an open circuit fails fast before letting one trial request through
+const OPEN_FOR: Duration = Duration::from_secs(30);
+
+/// Kind of system fks_master is calling; the `class` label of the outbound metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Destination {
+    HealthCheck,
+    Validation,
+    Webhook,
+    Slack,
+    PagerDuty,
+    Archive,
+    Prometheus,
+    RemoteConfig,
+    Registry,
+    Kubernetes,
+    Hook,
+    Drain,
+    Federation,
+}
+
+impl Destination {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Destination::HealthCheck => "health_check",
+            Destination::Validation => "validation",
+            Destination::Webhook => "webhook",
+            Destination::Slack => "slack",
+            Destination::PagerDuty => "pagerduty",
+            Destination::Archive => "archive",
+            Destination::Prometheus => "prometheus",
+            Destination::RemoteConfig => "remote_config",
+            Destination::Registry => "registry",
+            Destination::Kubernetes => "kubernetes",
+            Destination::Hook => "hook",
+            Destination::Drain => "drain",
+            Destination::Federation => "federation",
+        }
+    }
+
+    /// Notification endpoints get a circuit breaker. Health checks do not: their failures are the
+    /// target's state, and failing fast would hide recoveries.
+    fn has_breaker(&self) -> bool {
+        matches!(self, Destination::Webhook | Destination::Slack | Destination::PagerDuty)
+    }
+
+    /// Notification and hook URLs can embed credentials (Slack webhooks, tokens in webhook query strings), so
+    /// errors for these destinations leave the URL out; they end up in logs, dead letters and history.
+    fn hides_url(&self) -> bool {
+        matches!(self, Destination::Webhook | Destination::Slack | Destination::PagerDuty | Destination::Hook)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    /// 4xx: reached the destination, which rejected the request
+    ClientError,
+    /// 5xx
+    ServerError,
+    Timeout,
+    /// DNS failure, refused or reset connection, TLS failure
+    Connect,
+    Error,
+    /// Not sent because the destination's circuit is open
+    CircuitOpen,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::ClientError => "client_error",
+            Outcome::ServerError => "server_error",
+            Outcome::Timeout => "timeout",
+            Outcome::Connect => "connect_error",
+            Outcome::Error => "error",
+            Outcome::CircuitOpen => "circuit_open",
+        }
+    }
+
+    pub fn of(result: &reqwest::Result<reqwest::Response>) -> Self {
+        match result {
+            Ok(resp) if resp.status().is_client_error() => Outcome::ClientError,
+            Ok(resp) if resp.status().is_server_error() => Outcome::ServerError,
+            Ok(_) => Outcome::Success,
+            Err(e) if e.is_timeout() => Outcome::Timeout,
+            Err(e) if e.is_connect() => Outcome::Connect,
+            Err(_) => Outcome::Error,
+        }
+    }
+
+    /// Whether this points at a problem reaching the destination (as opposed to the request itself).
+    fn is_failure(&self) -> bool {
+        matches!(self, Outcome::ServerError | Outcome::Timeout | Outcome::Connect | Outcome::Error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    HalfOpen,
+    Open,
+}
+
+impl CircuitState {
+    /// Gauge value: 0 closed, 1 half-open, 2 open
+    fn gauge(&self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    /// A half-open trial request is in flight (a stale one is given up after `OPEN_FOR`)
+    trial_since: Option<Instant>,
+}
+
+impl Breaker {
+    fn state(&self, now: Instant) -> CircuitState {
+        match self.open_until {
+            None => CircuitState::Closed,
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    fn admit(&mut self, now: Instant) -> bool {
+        match self.state(now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if self.trial_since.is_some_and(|since| now.duration_since(since) < OPEN_FOR) { return false; }
+                self.trial_since = Some(now);
+                true
+            }
+        }
+    }
+
+    fn record(&mut self, outcome: Outcome, now: Instant) {
+        self.trial_since = None;
+        if !outcome.is_failure() {
+            self.consecutive_failures = 0;
+            self.open_until = None;
+            return;
+        }
+        self.consecutive_failures += 1;
+        // A failed half-open trial reopens immediately
+        if self.consecutive_failures >= FAILURE_THRESHOLD || self.open_until.is_some() {
+            self.open_until = Some(now + OPEN_FOR);
+        }
+    }
+}
+
+static BREAKERS: Lazy<DashMap<(Destination, String), Breaker>> = Lazy::new(DashMap::new);
+
+/// Send `request`, recording its outcome and latency under `class`. For notification destinations
+/// the call goes through a circuit breaker per host and port and fails fast while the circuit is open.
+pub async fn send(class: Destination, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let url = request.url();
+    let authority = format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or_default());
+    let key = (class, authority);
+
+    if class.has_breaker() {
+        let admitted = BREAKERS.entry(key.clone()).or_default().admit(Instant::now());
+        if !admitted {
+            metrics::record_outbound_request(class.as_str(), Outcome::CircuitOpen.as_str(), 0.0);
+            anyhow::bail!("circuit open for {} ({}): failing fast after repeated errors", key.1, class.as_str());
+        }
+    }
+
+    let started = Instant::now();
+    let result = client.execute(request).await;
+    let outcome = Outcome::of(&result);
+    metrics::record_outbound_request(class.as_str(), outcome.as_str(), started.elapsed().as_secs_f64());
+
+    if class.has_breaker() {
+        let now = Instant::now();
+        let mut breaker = BREAKERS.entry(key.clone()).or_default();
+        let before = breaker.state(now);
+        breaker.record(outcome, now);
+        let after = breaker.state(now);
+        metrics::set_outbound_circuit_state(class.as_str(), &key.1, after.gauge());
+        if before != after {
+            tracing::warn!(class = class.as_str(), destination = %key.1, state = ?after, "outbound circuit state changed");
+        }
+    }
+    if class.hides_url() {
+        return result.map_err(|e| anyhow::Error::from(e.without_url()));
+    }
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaker_opens_after_repeated_failures_and_recovers_through_one_trial() {
+        let t0 = Instant::now();
+        let mut breaker = Breaker::default();
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            assert!(breaker.admit(t0));
+            breaker.record(Outcome::Timeout, t0);
+        }
+        breaker.record(Outcome::ClientError, t0);
+        assert_eq!(breaker.state(t0), CircuitState::Closed, "a 4xx resets the failure streak");
+
+        for _ in 0..FAILURE_THRESHOLD {
+            breaker.record(Outcome::Connect, t0);
+        }
+        assert_eq!(breaker.state(t0), CircuitState::Open);
+        assert!(!breaker.admit(t0 + Duration::from_secs(1)));
+
+        let later = t0 + OPEN_FOR;
+        assert_eq!(breaker.state(later), CircuitState::HalfOpen);
+        assert!(breaker.admit(later), "one trial request");
+        assert!(!breaker.admit(later), "only one");
+        breaker.record(Outcome::ServerError, later);
+        assert_eq!(breaker.state(later), CircuitState::Open, "failed trial reopens");
+
+        let retry = later + OPEN_FOR;
+        assert!(breaker.admit(retry));
+        breaker.record(Outcome::Success, retry);
+        assert_eq!(breaker.state(retry), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn open_circuit_fails_fast() {
+        // Reserve a port, then close it so every attempt is refused
+        let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let client = reqwest::Client::new();
+        let url = format!("http://{addr}/hook");
+        for _ in 0..FAILURE_THRESHOLD {
+            let err = send(Destination::Webhook, client.post(&url)).await.unwrap_err();
+            assert!(!err.to_string().contains("circuit open"));
+        }
+        let err = send(Destination::Webhook, client.post(&url)).await.unwrap_err();
+        assert!(err.to_string().contains("circuit open"), "{err}");
+        // Health checks are never short-circuited
+        assert!(!send(Destination::HealthCheck, client.get(&url)).await.unwrap_err().to_string().contains("circuit open"));
+    }
+}
@@ -0,0 +1,204 @@
+use bollard::models::HealthStatusEnum;
+use chrono::Utc;
+use futures::future::join_all;
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, KubernetesConfig};
+use crate::docker_hosts;
+use crate::health::HealthChecker;
+use crate::kubernetes::{self, Readiness};
+use crate::systemd;
+use crate::models::{HealthCheck, HealthStatus, ProbeConfig, ProbeMode, ServiceConfig};
+
+impl ProbeConfig {
+    pub fn name(&self) -> String {
+        match self {
+            ProbeConfig::Http { name: Some(n), .. } | ProbeConfig::Tcp { name: Some(n), .. } | ProbeConfig::Docker { name: Some(n), .. }
+            | ProbeConfig::Systemd { name: Some(n), .. } | ProbeConfig::Kubernetes { name: Some(n), .. } => n.clone(),
+            ProbeConfig::Http { url, .. } => format!("http:{url}"),
+            ProbeConfig::Tcp { address, .. } => format!("tcp:{address}"),
+            ProbeConfig::Docker { container, .. } => format!("docker:{}", container.as_deref().unwrap_or("service")),
+            ProbeConfig::Systemd { unit, .. } => format!("systemd:{}", unit.as_deref().unwrap_or("service")),
+            ProbeConfig::Kubernetes { selector, .. } => format!("kubernetes:{}", selector.as_deref().unwrap_or("service")),
+        }
+    }
+}
+
+/// The service's health endpoint (when set) followed by its extra probes.
+pub fn service_probes(service: &ServiceConfig) -> Vec<ProbeConfig> {
+    let mut probes = Vec::new();
+    if !service.health_endpoint.is_empty() {
+        probes.push(ProbeConfig::Http { url: service.health_endpoint.clone(), name: Some("health_endpoint".into()) });
+    }
+    probes.extend(service.probes.iter().cloned());
+    probes
+}
+
+/// Run all probes concurrently. A passing probe slower than the service's latency budget is Degraded.
+pub async fn run_probes(service: &ServiceConfig, checker: &HealthChecker, kubernetes: &kubernetes::Clients, config: &Config, timeout: Duration) -> Vec<HealthCheck> {
+    join_all(service_probes(service).iter().map(|p| run_probe(p, service, checker, kubernetes, config, timeout))).await
+}
+
+async fn run_probe(probe: &ProbeConfig, service: &ServiceConfig, checker: &HealthChecker, kubernetes: &kubernetes::Clients, config: &Config, timeout: Duration) -> HealthCheck {
+    let started = Instant::now();
+    // Passing, but with only some of its pods ready
+    let mut partial = false;
+    // Ok carries the measured latency (HTTP excludes retry backoff) and an optional note
+    let outcome: Result<(Duration, Option<String>), String> = match probe {
+        ProbeConfig::Http { url, .. } => checker.check_health(url).await.map(|rt| (rt, None)).map_err(|e| e.to_string()),
+        ProbeConfig::Tcp { address, .. } => match tokio::time::timeout(timeout, tokio::net::TcpStream::connect(address)).await {
+            Ok(Ok(_)) => Ok((started.elapsed(), None)),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("connect timed out after {}s", timeout.as_secs())),
+        },
+        ProbeConfig::Docker { container, .. } => match container.as_ref().or(service.docker_container.as_ref()) {
+            Some(container) => match docker_hosts::connect_for(config, service) {
+                Ok(docker) => docker_health(&docker, container, timeout).await.map(|note| (started.elapsed(), note)),
+                Err(e) => Err(format!("{e:#}")),
+            },
+            None => Err("no container configured".into()),
+        },
+        ProbeConfig::Systemd { unit, .. } => match unit.as_ref().or(service.systemd_unit.as_ref()) {
+            Some(unit) => match systemd::unit_state(unit, timeout).await {
+                Ok(state) => state.health(unit).map(|note| (started.elapsed(), note)),
+                Err(e) => Err(format!("{e:#}")),
+            },
+            None => Err("no systemd unit configured".into()),
+        },
+        ProbeConfig::Kubernetes { selector, namespace, .. } => {
+            let target = service.kubernetes.as_ref();
+            match selector.as_ref().or(target.map(|t| &t.selector)) {
+                Some(selector) => {
+                    let namespace = namespace.as_deref().or(target.map(|t| t.namespace.as_str())).unwrap_or("default");
+                    match pod_readiness(kubernetes, &config.kubernetes, namespace, selector, timeout).await {
+                        Ok(readiness) if readiness.total == 0 => Err(format!("no pods match `{selector}` in namespace {namespace}")),
+                        Ok(readiness) if readiness.ready == 0 => Err(readiness.summary()),
+                        Ok(readiness) => {
+                            partial = readiness.ready < readiness.total;
+                            Ok((started.elapsed(), partial.then(|| readiness.summary())))
+                        }
+                        Err(e) => Err(format!("{e:#}")),
+                    }
+                }
+                None => Err("no kubernetes selector configured".into()),
+            }
+        }
+    };
+    let (status, elapsed, message) = match outcome {
+        Ok((rt, note)) if partial => (HealthStatus::Degraded, rt, note),
+        Ok((rt, note)) if rt.as_millis() > service.expected_response_time_ms as u128 => {
+            (HealthStatus::Degraded, rt, note.or_else(|| Some(format!("slow: {}ms", rt.as_millis()))))
+        }
+        Ok((rt, note)) => (HealthStatus::Healthy, rt, note),
+        Err(e) => (HealthStatus::Unhealthy, started.elapsed(), Some(e)),
+    };
+    HealthCheck { name: probe.name(), status, response_time_ms: elapsed.as_millis() as u64, message, timestamp: Utc::now() }
+}
+
+async fn pod_readiness(clients: &kubernetes::Clients, config: &KubernetesConfig, namespace: &str, selector: &str, timeout: Duration) -> anyhow::Result<Readiness> {
+    let pods = clients.get(config, timeout).await?.pods(namespace, selector).await?;
+    Ok(Readiness::of(&pods))
+}
+
+/// Docker healthcheck status, or the container state when it has no healthcheck.
+async fn docker_health(docker: &bollard::Docker, container: &str, timeout: Duration) -> Result<Option<String>, String> {
+    let state = match tokio::time::timeout(timeout, docker.inspect_container(container, None)).await {
+        Ok(Ok(inspected)) => inspected.state.unwrap_or_default(),
+        Ok(Err(e)) => return Err(format!("docker inspect failed: {e}")),
+        Err(_) => return Err("docker inspect timed out".into()),
+    };
+    let status = match (state.health.and_then(|h| h.status), state.status) {
+        (Some(health), _) if health != HealthStatusEnum::EMPTY && health != HealthStatusEnum::NONE => health.to_string(),
+        (_, Some(status)) => status.to_string(),
+        _ => return Err("container state unknown".into()),
+    };
+    match status.as_str() {
+        "healthy" | "running" => Ok(None),
+        "starting" => Ok(Some("healthcheck starting".into())),
+        other => Err(format!("container {other}")),
+    }
+}
+
+/// Fold probe results into one status; see [`ProbeMode`].
+pub fn combine(mode: ProbeMode, checks: &[HealthCheck]) -> HealthStatus {
+    if checks.is_empty() { return HealthStatus::Unknown; }
+    let passing = checks.iter().filter(|c| c.status != HealthStatus::Unhealthy).count();
+    let passed = match mode {
+        ProbeMode::All => passing == checks.len(),
+        ProbeMode::Any => passing > 0,
+        ProbeMode::Quorum => passing * 2 > checks.len(),
+    };
+    if !passed {
+        HealthStatus::Unhealthy
+    } else if checks.iter().all(|c| c.status == HealthStatus::Healthy) {
+        HealthStatus::Healthy
+    } else {
+        HealthStatus::Degraded
+    }
+}
+
+/// One-line reason listing the failing probes, used as the service's error message.
+pub fn failure_summary(checks: &[HealthCheck]) -> String {
+    let failing: Vec<String> = checks
+        .iter()
+        .filter(|c| c.status == HealthStatus::Unhealthy)
+        .map(|c| format!("{}: {}", c.name, c.message.as_deref().unwrap_or("failed")))
+        .collect();
+    format!("{}/{} probes failing ({})", failing.len(), checks.len(), failing.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(status: HealthStatus) -> HealthCheck {
+        HealthCheck { name: "p".into(), status, response_time_ms: 1, message: None, timestamp: Utc::now() }
+    }
+
+    #[test]
+    fn modes_combine_probe_results() {
+        use HealthStatus::*;
+        let one_down = [check(Healthy), check(Healthy), check(Unhealthy)];
+        assert_eq!(combine(ProbeMode::All, &one_down), Unhealthy);
+        assert_eq!(combine(ProbeMode::Quorum, &one_down), Degraded);
+        assert_eq!(combine(ProbeMode::Any, &one_down), Degraded);
+
+        let two_down = [check(Healthy), check(Unhealthy), check(Unhealthy)];
+        assert_eq!(combine(ProbeMode::Quorum, &two_down), Unhealthy);
+        assert_eq!(combine(ProbeMode::Any, &two_down), Degraded);
+        assert_eq!(combine(ProbeMode::Quorum, &[check(Healthy), check(Unhealthy)]), Unhealthy, "a tie is not a majority");
+
+        assert_eq!(combine(ProbeMode::All, &[check(Healthy), check(Degraded)]), Degraded);
+        assert_eq!(combine(ProbeMode::All, &[check(Healthy), check(Healthy)]), Healthy);
+    }
+
+    #[tokio::test]
+    async fn tcp_and_http_probes_report_each_check() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap();
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let service: ServiceConfig = toml::from_str(&format!(
+            r#"
+            id = "fks_data"
+            name = "FKS Data"
+            health_endpoint = "http://{closed}/health"
+            service_type = "Api"
+            expected_response_time_ms = 1000
+            critical = true
+            probe_mode = "quorum"
+            probes = [
+                {{ type = "tcp", address = "{open}", name = "postgres" }},
+                {{ type = "tcp", address = "{open}" }},
+            ]
+            "#
+        )).unwrap();
+        let checker = HealthChecker::new(Duration::from_secs(1), 1);
+        let checks = run_probes(&service, &checker, &kubernetes::Clients::default(), &Config::default(), Duration::from_secs(1)).await;
+        let names: Vec<&str> = checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["health_endpoint", "postgres", &format!("tcp:{open}")]);
+        assert_eq!(checks[0].status, HealthStatus::Unhealthy);
+        assert_eq!(combine(service.probe_mode, &checks), HealthStatus::Degraded);
+        assert!(failure_summary(&checks).starts_with("1/3 probes failing (health_endpoint: "));
+        drop(listener);
+    }
+}
@@ -0,0 +1,161 @@
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Media type of [`Problem`] bodies (RFC 7807)
+pub const CONTENT_TYPE: &str = "application/problem+json";
+
+/// Machine-readable reason of a [`Problem`]; each has a fixed status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Unauthorized,
+    ServiceNotFound,
+    /// Anything else the path names that does not exist
+    NotFound,
+    DockerUnavailable,
+    RateLimited,
+    InvalidRequest,
+    /// The change would leave the config invalid
+    InvalidConfig,
+    Conflict,
+    ConfirmationRejected,
+    RestartCircuitOpen,
+    RestartFailed,
+    ComposeFailed,
+    /// A container action, rollback or similar ran but did not succeed
+    ActionFailed,
+    /// No `[prometheus]` server to proxy queries to
+    PrometheusUnavailable,
+    /// Prometheus answered a proxied query with an error, or not at all
+    QueryFailed,
+}
+
+impl ErrorCode {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::ServiceNotFound | ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::DockerUnavailable | ErrorCode::PrometheusUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::InvalidRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidConfig => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::Conflict | ErrorCode::RestartCircuitOpen => StatusCode::CONFLICT,
+            ErrorCode::ConfirmationRejected => StatusCode::FORBIDDEN,
+            ErrorCode::RestartFailed | ErrorCode::ComposeFailed | ErrorCode::ActionFailed | ErrorCode::QueryFailed => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ErrorCode::Unauthorized => "Missing or invalid credentials",
+            ErrorCode::ServiceNotFound => "Service not found",
+            ErrorCode::NotFound => "Not found",
+            ErrorCode::DockerUnavailable => "Docker daemon unavailable",
+            ErrorCode::RateLimited => "Too many requests",
+            ErrorCode::InvalidRequest => "Invalid request",
+            ErrorCode::InvalidConfig => "Invalid configuration",
+            ErrorCode::Conflict => "Conflicts with the current state",
+            ErrorCode::ConfirmationRejected => "Confirmation rejected",
+            ErrorCode::RestartCircuitOpen => "Restart circuit open",
+            ErrorCode::RestartFailed => "Restart failed",
+            ErrorCode::ComposeFailed => "Compose command failed",
+            ErrorCode::ActionFailed => "Action failed",
+            ErrorCode::PrometheusUnavailable => "Prometheus not configured",
+            ErrorCode::QueryFailed => "Prometheus query failed",
+        }
+    }
+
+    fn as_str(self) -> String {
+        serde_json::to_value(self).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+    }
+}
+
+/// An RFC 7807 error body: `type` is `urn:fks:problem:<code>`, `code` the same reason for clients
+/// that switch on it, and any extension members sit beside them.
+#[derive(Debug, Clone, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: &'static str,
+    pub status: u16,
+    pub code: ErrorCode,
+    pub detail: String,
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+    /// Sent as `Retry-After` too
+    #[serde(skip)]
+    retry_after: Option<u64>,
+}
+
+impl Problem {
+    pub fn new(code: ErrorCode, detail: impl Into<String>) -> Self {
+        Self {
+            problem_type: format!("urn:fks:problem:{}", code.as_str()),
+            title: code.title(),
+            status: code.status().as_u16(),
+            code,
+            detail: detail.into(),
+            extensions: Map::new(),
+            retry_after: None,
+        }
+    }
+
+    pub fn unauthorized() -> Self {
+        Self::new(ErrorCode::Unauthorized, "Send a valid x-api-key header or bearer token")
+    }
+
+    pub fn service_not_found(service_id: &str) -> Self {
+        Self::new(ErrorCode::ServiceNotFound, format!("Service {service_id} not found")).with("service_id", service_id)
+    }
+
+    /// Adds the extension member `key`.
+    pub fn with(mut self, key: &str, value: impl Serialize) -> Self {
+        self.extensions.insert(key.to_string(), serde_json::to_value(value).unwrap_or(Value::Null));
+        self
+    }
+
+    /// Answers with `Retry-After: secs`.
+    pub fn retry_after(mut self, secs: u64) -> Self {
+        self.retry_after = Some(secs);
+        self
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = self.code.status();
+        let retry_after = self.retry_after;
+        let mut response = (status, serde_json::to_string(&self).unwrap_or_default()).into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE));
+        if let Some(secs) = retry_after {
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn problems_carry_their_code_status_and_extensions() {
+        let response = Problem::new(ErrorCode::RateLimited, "Slow down").with("service_id", "api").retry_after(30).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], CONTENT_TYPE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body, serde_json::json!({
+            "type": "urn:fks:problem:rate_limited",
+            "title": "Too many requests",
+            "status": 429,
+            "code": "rate_limited",
+            "detail": "Slow down",
+            "service_id": "api",
+        }));
+    }
+}
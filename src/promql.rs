@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::config::PrometheusConfig;
+use crate::outbound::{self, Destination};
+
+/// Queries available without configuration. `$service` expands to a regex matching the requested
+/// service id, or every service when none is given.
+pub const BUILTIN_QUERIES: [(&str, &str); 5] = [
+    ("health_status", r#"fks_service_health_status{service_id=~"$service"}"#),
+    ("response_time_p95", r#"histogram_quantile(0.95, sum by (le, service_id) (rate(fks_service_response_time_seconds_bucket{service_id=~"$service"}[5m])))"#),
+    ("check_failure_ratio", r#"sum by (service_id) (rate(fks_health_checks_total{service_id=~"$service",status="unhealthy"}[5m])) / sum by (service_id) (rate(fks_health_checks_total{service_id=~"$service"}[5m]))"#),
+    ("cpu_usage_percent", r#"fks_service_cpu_usage_percent{service_id=~"$service"}"#),
+    ("memory_usage_mb", r#"fks_service_memory_usage_megabytes{service_id=~"$service"}"#),
+];
+
+/// Longest history a single request may ask for
+pub const MAX_RANGE: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Points per series when no step is given; plenty for a sparkline
+const TARGET_POINTS: u64 = 120;
+
+/// The allowlist: built-in queries plus `[prometheus.queries]`, which may override them.
+pub fn allowed_queries(config: &PrometheusConfig) -> BTreeMap<String, String> {
+    let mut queries: BTreeMap<String, String> = BUILTIN_QUERIES.iter().map(|(name, q)| (name.to_string(), q.to_string())).collect();
+    queries.extend(config.queries.clone());
+    queries
+}
+
+/// Substitute `$service` with an anchored regex for `service_id`, or `.+` for all services.
+pub fn render(template: &str, service_id: Option<&str>) -> String {
+    let pattern = match service_id {
+        Some(id) => id.chars().fold(String::new(), |mut out, c| {
+            // Escaped for the regex, then again for the PromQL string literal
+            if r"\.+*?()|[]{}^$".contains(c) { out.push_str(r"\\"); }
+            if c == '"' { out.push('\\'); }
+            out.push(c);
+            out
+        }),
+        None => ".+".into(),
+    };
+    template.replace("$service", &pattern)
+}
+
+/// Parse `90s`, `30m`, `6h`, `7d` or plain seconds.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let n: u64 = number.parse().ok()?;
+    let secs = match unit {
+        "s" => n,
+        "m" => n * 60,
+        "h" => n * 3600,
+        "d" => n * 86_400,
+        _ => return None,
+    };
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Step that yields about [`TARGET_POINTS`] points over `range`, at least 15s.
+pub fn default_step(range: Duration) -> Duration {
+    Duration::from_secs((range.as_secs() / TARGET_POINTS).max(15))
+}
+
+/// One series with its labels and `[unix_seconds, value]` points; NaN and infinite samples are dropped.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Series {
+    pub labels: BTreeMap<String, String>,
+    pub points: Vec<(f64, f64)>,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse {
+    status: String,
+    #[serde(default)]
+    data: Option<ApiData>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiData {
+    result: Vec<ApiSeries>,
+}
+
+#[derive(Deserialize)]
+struct ApiSeries {
+    #[serde(default)]
+    metric: BTreeMap<String, String>,
+    #[serde(default)]
+    values: Vec<(f64, String)>,
+}
+
+/// Turn a `query_range` response body into [`Series`].
+pub fn normalize(body: &str) -> Result<Vec<Series>> {
+    let response: ApiResponse = serde_json::from_str(body).context("unexpected Prometheus response")?;
+    if response.status != "success" {
+        anyhow::bail!("Prometheus error: {}", response.error.unwrap_or(response.status));
+    }
+    let result = response.data.map(|d| d.result).unwrap_or_default();
+    Ok(result.into_iter().map(|s| Series {
+        labels: s.metric,
+        points: s.values.into_iter().filter_map(|(t, v)| v.parse::<f64>().ok().filter(|v| v.is_finite()).map(|v| (t, v))).collect(),
+    }).collect())
+}
+
+/// Run `promql` over `[end - range, end]` against the configured server.
+pub async fn query_range(config: &PrometheusConfig, promql: &str, end: chrono::DateTime<chrono::Utc>, range: Duration, step: Duration) -> Result<Vec<Series>> {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+    let start = end - chrono::Duration::from_std(range)?;
+    let url = format!("{}/api/v1/query_range", config.url.trim_end_matches('/'));
+    let mut request = client.get(url).query(&[
+        ("query", promql.to_string()),
+        ("start", start.timestamp().to_string()),
+        ("end", end.timestamp().to_string()),
+        ("step", step.as_secs().to_string()),
+    ]);
+    if let Some(token) = &config.bearer_token {
+        request = request.bearer_auth(token);
+    }
+    let response = outbound::send(Destination::Prometheus, request).await?;
+    let status = response.status();
+    let body = response.text().await?;
+    // Prometheus reports bad queries as 400/422 with the usual error body
+    if !status.is_success() && !status.is_client_error() {
+        anyhow::bail!("Prometheus returned {status}");
+    }
+    normalize(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_ids_are_escaped_into_the_matcher() {
+        let template = r#"up{service_id=~"$service"}"#;
+        assert_eq!(render(template, None), r#"up{service_id=~".+"}"#);
+        assert_eq!(render(template, Some("fks_api")), r#"up{service_id=~"fks_api"}"#);
+        assert_eq!(render(template, Some("a.b")), r#"up{service_id=~"a\\.b"}"#);
+    }
+
+    #[test]
+    fn durations_and_steps() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("7d"), Some(MAX_RANGE));
+        assert_eq!(parse_duration("0h"), None);
+        assert_eq!(parse_duration("1w"), None);
+        assert_eq!(default_step(Duration::from_secs(3600)), Duration::from_secs(30));
+        assert_eq!(default_step(Duration::from_secs(600)), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn matrix_results_are_normalized() {
+        let body = r#"{"status":"success","data":{"resultType":"matrix","result":[
+            {"metric":{"service_id":"fks_api"},"values":[[1700000000,"0.25"],[1700000060,"NaN"],[1700000120,"0.5"]]}]}}"#;
+        let series = normalize(body).unwrap();
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].labels["service_id"], "fks_api");
+        assert_eq!(series[0].points, vec![(1_700_000_000.0, 0.25), (1_700_000_120.0, 0.5)]);
+
+        let err = normalize(r#"{"status":"error","errorType":"bad_data","error":"parse error"}"#).unwrap_err();
+        assert!(err.to_string().contains("parse error"));
+    }
+}
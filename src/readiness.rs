@@ -0,0 +1,124 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::docker_hosts;
+use crate::monitor::{MonitorHandle, EVENT_CHANNEL_CAPACITY};
+use crate::watchdog::WatchdogReport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Readiness {
+    Ready,
+    /// Nothing is broken, but a loop has not completed its first pass yet
+    Starting,
+    NotReady,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Ok,
+    Starting,
+    Failed,
+    /// Not needed by this config, e.g. Docker without any container configured
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ComponentStatus {
+    pub name: String,
+    pub status: ComponentState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ComponentStatus {
+    fn new(name: impl Into<String>, status: ComponentState, detail: impl Into<Option<String>>) -> Self {
+        Self { name: name.into(), status, detail: detail.into() }
+    }
+}
+
+/// Served by `/readyz`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReadinessReport {
+    pub status: Readiness,
+    pub components: Vec<ComponentStatus>,
+}
+
+/// Whether the monitor is doing its job: config loaded, loops ticking, Docker daemons reachable
+/// and the event channel draining.
+pub async fn check(monitor: &MonitorHandle) -> ReadinessReport {
+    let config = monitor.config();
+    let mut components = vec![ComponentStatus::new("config", ComponentState::Ok, format!("{} services", config.services.len()))];
+    components.extend(loops(&monitor.watchdog().report(monitor.now())));
+
+    let hosts = docker_hosts::in_use(&config);
+    if hosts.is_empty() {
+        components.push(ComponentStatus::new("docker", ComponentState::Skipped, "no containers configured".to_string()));
+    }
+    for host in hosts {
+        let name = host.as_ref().map_or_else(|| "docker".to_string(), |h| format!("docker:{h}"));
+        components.push(match docker_hosts::ping(&config, host.as_deref()).await {
+            Ok(()) => ComponentStatus::new(name, ComponentState::Ok, None),
+            Err(e) => ComponentStatus::new(name, ComponentState::Failed, format!("{e:#}")),
+        });
+    }
+
+    components.push(event_channel(monitor.event_backlog()));
+    ReadinessReport { status: overall(&components), components }
+}
+
+/// The watchdog's loops; one that has not progressed yet is starting rather than stalled.
+fn loops(report: &WatchdogReport) -> impl Iterator<Item = ComponentStatus> + '_ {
+    report.components.iter().map(|c| match (c.stalled, c.started) {
+        (true, _) => ComponentStatus::new(c.name, ComponentState::Failed, format!("no progress since {}", c.last_progress.to_rfc3339())),
+        (false, false) => ComponentStatus::new(c.name, ComponentState::Starting, None),
+        (false, true) => ComponentStatus::new(c.name, ComponentState::Ok, None),
+    })
+}
+
+/// Saturated once 90% of the channel holds events some subscriber has not received.
+fn event_channel(backlog: usize) -> ComponentStatus {
+    let detail = format!("{backlog}/{EVENT_CHANNEL_CAPACITY} events queued");
+    let status = if backlog * 10 >= EVENT_CHANNEL_CAPACITY * 9 { ComponentState::Failed } else { ComponentState::Ok };
+    ComponentStatus::new("event_channel", status, detail)
+}
+
+fn overall(components: &[ComponentStatus]) -> Readiness {
+    if components.iter().any(|c| c.status == ComponentState::Failed) {
+        Readiness::NotReady
+    } else if components.iter().any(|c| c.status == ComponentState::Starting) {
+        Readiness::Starting
+    } else {
+        Readiness::Ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WatchdogConfig;
+    use crate::watchdog::{Watchdog, MONITORING_LOOP};
+    use std::time::Duration;
+    use chrono::Utc;
+
+    #[test]
+    fn starting_loops_are_told_apart_from_stalled_ones() {
+        let dog = Watchdog::new(WatchdogConfig { stall_threshold_seconds: 60, ..Default::default() });
+        let t0 = Utc::now();
+        dog.register(MONITORING_LOOP, Duration::from_secs(10), t0);
+        let status = |at: i64| {
+            let mut components: Vec<_> = loops(&dog.report(t0 + chrono::Duration::seconds(at))).collect();
+            components.push(event_channel(0));
+            overall(&components)
+        };
+
+        assert_eq!(status(5), Readiness::Starting);
+        dog.beat(MONITORING_LOOP, t0 + chrono::Duration::seconds(10));
+        assert_eq!(status(20), Readiness::Ready);
+        assert_eq!(status(100), Readiness::NotReady);
+
+        assert_eq!(event_channel(89).status, ComponentState::Ok);
+        assert_eq!(event_channel(90).status, ComponentState::Failed);
+    }
+}
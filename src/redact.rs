@@ -0,0 +1,376 @@
+use crate::alert_history::{AlertRecord, HistoryPage};
+use crate::alerts::DeadLetter;
+use crate::compose::ContainerInfo;
+use crate::config::{Config, HookAction};
+use crate::escalation::Escalation;
+use crate::events::EventPage;
+use crate::incident::Incident;
+use crate::models::{HealthCheck, MonitorEvent, ServiceDetail, ServiceHealth, ServiceStatus};
+use crate::silence::{MaintenanceWindow, Silence};
+use crate::uptime::ServiceHistory;
+use crate::validation::ValidationReport;
+
+const REDACTED: &str = "[redacted]";
+
+/// Prefixes of the names [`ProbeConfig::name`](crate::models::ProbeConfig) generates from a probe's target.
+const PROBE_KINDS: [&str; 5] = ["http", "tcp", "docker", "systemd", "kubernetes"];
+
+/// Strip details that reveal internal topology (endpoints, container names, hosts) from
+/// API responses served to read-only callers. Status and latency stay visible.
+pub trait Redact {
+    fn redact(self) -> Self;
+}
+
+impl<T: Redact> Redact for Vec<T> {
+    fn redact(self) -> Self {
+        self.into_iter().map(Redact::redact).collect()
+    }
+}
+
+impl<T: Redact> Redact for Option<T> {
+    fn redact(self) -> Self {
+        self.map(Redact::redact)
+    }
+}
+
+impl Redact for ServiceStatus {
+    fn redact(mut self) -> Self {
+        self.error_message = self.error_message.map(|m| scrub_targets(&m));
+        self.host = self.host.take().map(|_| REDACTED.into());
+        self
+    }
+}
+
+/// Probe names and errors can quote the target, e.g. `tcp:10.0.0.5:5432`.
+impl Redact for HealthCheck {
+    fn redact(mut self) -> Self {
+        self.name = scrub_targets(&self.name);
+        self.message = self.message.map(|m| scrub_targets(&m));
+        self
+    }
+}
+
+/// Registry images stay hidden along with the probe targets.
+impl Redact for ServiceHealth {
+    fn redact(mut self) -> Self {
+        self.checks = self.checks.redact();
+        self.images = None;
+        self
+    }
+}
+
+/// Statuses and times only, which stay visible.
+impl Redact for ServiceHistory {
+    fn redact(self) -> Self {
+        self
+    }
+}
+
+impl Redact for Incident {
+    fn redact(mut self) -> Self {
+        for ack in &mut self.acknowledgements {
+            ack.note = ack.note.as_deref().map(scrub_targets);
+        }
+        self.resolution_note = self.resolution_note.as_deref().map(scrub_targets);
+        self
+    }
+}
+
+/// Who gets paged stays hidden; the service and how far it escalated are shown.
+impl Redact for Escalation {
+    fn redact(mut self) -> Self {
+        self.notified_channels.clear();
+        self
+    }
+}
+
+/// Matchers can name instances, e.g. Alertmanager's `instance = "10.0.0.5:9100"`.
+impl Redact for Silence {
+    fn redact(mut self) -> Self {
+        self.matchers.values_mut().for_each(|v| *v = scrub_targets(v));
+        self.comment = self.comment.as_deref().map(scrub_targets);
+        self
+    }
+}
+
+impl Redact for MaintenanceWindow {
+    fn redact(mut self) -> Self {
+        self.matchers.values_mut().for_each(|v| *v = scrub_targets(v));
+        self
+    }
+}
+
+impl Redact for ValidationReport {
+    fn redact(mut self) -> Self {
+        for result in &mut self.results {
+            result.endpoint = REDACTED.into();
+            result.error = result.error.as_deref().map(scrub_targets);
+        }
+        self
+    }
+}
+
+impl Redact for ContainerInfo {
+    fn redact(mut self) -> Self {
+        self.id = String::new();
+        self.name = REDACTED.into();
+        self.image = REDACTED.into();
+        self.ports = vec![];
+        self
+    }
+}
+
+impl Redact for DeadLetter {
+    fn redact(mut self) -> Self {
+        self.error = scrub_targets(&self.error);
+        self.payload.message = scrub_targets(&self.payload.message);
+        self.payload.data = None;
+        self
+    }
+}
+
+impl Redact for AlertRecord {
+    fn redact(mut self) -> Self {
+        self.message = scrub_targets(&self.message);
+        self.data = None;
+        for d in &mut self.deliveries {
+            d.error = d.error.as_deref().map(scrub_targets);
+        }
+        self
+    }
+}
+
+impl Redact for HistoryPage {
+    fn redact(mut self) -> Self {
+        self.items = self.items.redact();
+        self
+    }
+}
+
+/// Event payloads can carry raw probe output, so only the scrubbed message stays.
+impl Redact for MonitorEvent {
+    fn redact(mut self) -> Self {
+        self.message = scrub_targets(&self.message);
+        self.data = None;
+        self
+    }
+}
+
+/// Where the service lives stays hidden; thresholds and labels are shown.
+impl Redact for ServiceDetail {
+    fn redact(mut self) -> Self {
+        let config = &mut self.config;
+        config.health_endpoint = REDACTED.into();
+        config.probes.clear();
+        config.docker_container = config.docker_container.take().map(|_| REDACTED.into());
+        config.docker_host = config.docker_host.take().map(|_| REDACTED.into());
+        config.kubernetes = None;
+        config.systemd_unit = config.systemd_unit.take().map(|_| REDACTED.into());
+        if let Some(metrics) = &mut config.metrics { metrics.url = REDACTED.into(); }
+        self.status = self.status.redact();
+        self.health = self.health.redact();
+        self.recent_events = self.recent_events.redact();
+        self.restarts = self.restarts.redact();
+        self
+    }
+}
+
+impl Redact for EventPage {
+    fn redact(mut self) -> Self {
+        self.items = self.items.redact();
+        self
+    }
+}
+
+/// Blank out the [`SECRET_FIELDS`](crate::secrets::SECRET_FIELDS) of a config before it is echoed back.
+impl Redact for Config {
+    fn redact(mut self) -> Self {
+        let hide = |value: &mut Option<String>| if value.is_some() { *value = Some(REDACTED.into()) };
+        let alerts = &mut self.alerts;
+        hide(&mut alerts.webhook_url);
+        alerts.routes.iter_mut().for_each(|route| hide(&mut route.webhook_url));
+        if let Some(slack) = &mut alerts.slack {
+            hide(&mut slack.webhook_url);
+            hide(&mut slack.bot_token);
+        }
+        if let Some(email) = &mut alerts.email {
+            hide(&mut email.username);
+            hide(&mut email.password);
+        }
+        if let Some(pagerduty) = &mut alerts.pagerduty {
+            pagerduty.routing_key = REDACTED.into();
+        }
+        if let Some(archive) = &mut self.archive {
+            hide(&mut archive.access_key_id);
+            hide(&mut archive.secret_access_key);
+        }
+        if let Some(prometheus) = &mut self.prometheus {
+            hide(&mut prometheus.bearer_token);
+        }
+        hide(&mut self.kubernetes.token);
+        for registry in &mut self.registries {
+            hide(&mut registry.password);
+            hide(&mut registry.token);
+        }
+        for hook in &mut self.hooks {
+            if let HookAction::Http { url, .. } = &mut hook.run {
+                *url = REDACTED.into();
+            }
+        }
+        self
+    }
+}
+
+/// Replace what names an internal target in free text: `scheme://...` URLs (e.g. reqwest errors
+/// quoting the health endpoint), generated probe names like `tcp:10.0.0.5:5432` and bare `host:port`.
+pub fn scrub_targets(text: &str) -> String {
+    let text = scrub_urls(text);
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_whitespace() || matches!(c, ')' | '(' | '"' | '\'' | ',' | ';' | '>' | '<')).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(end);
+        out.push_str(&scrub_word(word));
+        let delimiter = after.chars().next().map_or(0, char::len_utf8);
+        out.push_str(&after[..delimiter]);
+        rest = &after[delimiter..];
+    }
+    out
+}
+
+/// `word` with a probe target or `host:port` replaced; a trailing `:` or `.` stays, as in `tcp:db:5432: refused`.
+fn scrub_word(word: &str) -> String {
+    let core = word.trim_end_matches([':', '.']);
+    let suffix = &word[core.len()..];
+    match core.split_once(':') {
+        Some((kind, target)) if PROBE_KINDS.contains(&kind) && !target.is_empty() => format!("{kind}:{REDACTED}{suffix}"),
+        _ if is_host_port(core) => format!("{REDACTED}{suffix}"),
+        _ => word.to_string(),
+    }
+}
+
+/// `db:5432`, `10.0.0.5:5432` or `[fd00::5]:5432`, but not a time of day like `12:30`.
+fn is_host_port(word: &str) -> bool {
+    let Some((host, port)) = word.rsplit_once(':') else { return false };
+    let ipv6 = host.len() > 2 && host.starts_with('[') && host.ends_with(']');
+    let name = !host.is_empty()
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        && host.chars().any(|c| c.is_ascii_alphabetic() || c == '.');
+    !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) && (ipv6 || name)
+}
+
+/// Replace any `scheme://...` URL in free text.
+fn scrub_urls(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find("://") {
+        // Walk back over the scheme
+        let scheme_start = rest[..idx]
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.'))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let after = &rest[idx + 3..];
+        let end = after
+            .find(|c: char| c.is_whitespace() || matches!(c, ')' | '(' | '"' | '\'' | ',' | '>' | '<'))
+            .unwrap_or(after.len());
+        out.push_str(&rest[..scheme_start]);
+        out.push_str(REDACTED);
+        rest = &after[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrubs_urls_in_error_text() {
+        let msg = "error sending request for url (http://fks_api:8000/health): connection refused";
+        assert_eq!(scrub_urls(msg), "error sending request for url ([redacted]): connection refused");
+        assert_eq!(scrub_urls("HTTP 503: Service Unavailable"), "HTTP 503: Service Unavailable");
+        assert_eq!(scrub_urls("a tcp://10.0.0.5:2375 b https://x/y"), "a [redacted] b [redacted]");
+    }
+
+    #[test]
+    fn scrubs_probe_targets_and_addresses() {
+        assert_eq!(scrub_targets("1/2 probes failing (tcp:db.internal:5432: Connection refused; http:http://api/health: HTTP 503)"),
+            "1/2 probes failing (tcp:[redacted]: Connection refused; http:[redacted] HTTP 503)");
+        assert_eq!(scrub_targets("dial 10.0.0.5:9100, [fd00::5]:80 and redis:6379."), "dial [redacted], [redacted] and [redacted].");
+        assert_eq!(scrub_targets("postgres: slow: 1200ms at 12:30"), "postgres: slow: 1200ms at 12:30");
+    }
+
+    #[test]
+    fn config_redaction_covers_every_secret_field() {
+        let mut config: Config = toml::from_str(&toml::to_string(&Config::default()).unwrap().replace("[alerts]", r#"
+            [archive]
+            endpoint = "http://minio:9000"
+            bucket = "fks"
+            access_key_id = "AKIA"
+            secret_access_key = "wJal"
+
+            [prometheus]
+            url = "http://prometheus:9090"
+            bearer_token = "prom-token"
+
+            [alerts.slack]
+            webhook_url = "https://hooks.slack.com/services/T/B/x"
+            bot_token = "xoxb-1"
+
+            [alerts.email]
+            smtp_host = "smtp"
+            username = "bot"
+            password = "hunter2"
+            from = "a@b"
+            to = ["c@d"]
+
+            [alerts.pagerduty]
+            routing_key = "R0UTING"
+
+            [alerts]"#)).unwrap();
+        config.alerts.webhook_url = Some("https://hooks.example.com/t0ken".into());
+        config.kubernetes.token = Some("eyJhbGciOi".into());
+        config.registries.push(crate::config::RegistryConfig { host: "registry.example.com".into(), username: Some("ci".into()), password: Some("hunter3".into()), token: Some("refresh".into()), credential_helper: None });
+        config.hooks = toml::from_str::<toml::Table>(r#"hooks = [{ name = "notify", events = ["ServiceDown"], run = { type = "http", url = "https://x/t0ken" } }, { name = "log", events = ["ServiceDown"], run = { type = "command", command = ["true"] } }]"#).unwrap()["hooks"].clone().try_into().unwrap();
+        config.alerts.routes = toml::from_str::<toml::Table>(r#"routes = [{ channels = ["webhook"], webhook_url = "https://x/t0ken" }]"#).unwrap()["routes"].clone().try_into().unwrap();
+
+        let mut redacted = toml::Table::try_from(config.redact()).unwrap();
+        for field in crate::secrets::SECRET_FIELDS {
+            let mut seen = 0;
+            crate::secrets::for_each_value(&mut redacted, &field.split('.').collect::<Vec<_>>(), &mut |value| {
+                seen += 1;
+                assert_eq!(value.as_str(), Some(REDACTED), "{field}");
+                Ok(())
+            }).unwrap();
+            assert_eq!(seen, 1, "{field} is set in the test config");
+        }
+        assert_eq!(redacted["prometheus"]["url"].as_str(), Some("http://prometheus:9090"), "non-secret fields stay");
+    }
+
+    #[test]
+    fn status_redaction_hides_the_docker_host() {
+        let status: ServiceStatus = serde_json::from_value(serde_json::json!({
+            "id": "api", "name": "API", "status": "Unhealthy", "last_check": "2024-01-01T00:00:00Z",
+            "uptime_seconds": null, "response_time_ms": 12, "error_message": "GET http://10.0.0.7:8000/health: refused",
+            "service_type": "Api", "critical": true, "host": "edge",
+        })).unwrap();
+        let r = status.clone().redact();
+        assert_eq!(r.host.as_deref(), Some(REDACTED));
+        assert_eq!(r.error_message.as_deref(), Some("GET [redacted] refused"));
+        assert_eq!(r.response_time_ms, Some(12));
+
+        let failing = ServiceStatus { error_message: Some("1/3 probes failing (tcp:10.0.0.5:5432: Connection refused)".into()), ..status };
+        assert_eq!(failing.redact().error_message.as_deref(), Some("1/3 probes failing (tcp:[redacted]: Connection refused)"));
+    }
+
+    #[test]
+    fn container_redaction_keeps_state() {
+        let c = ContainerInfo { id: "abc".into(), name: "fks_api-1".into(), image: "registry.local/fks_api".into(), state: "running".into(), health: Some("healthy".into()), ports: vec!["8000/tcp".into()], uptime_seconds: Some(5) };
+        let r = c.redact();
+        assert_eq!(r.name, REDACTED);
+        assert!(r.ports.is_empty());
+        assert_eq!(r.state, "running");
+        assert_eq!(r.health.as_deref(), Some("healthy"));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::config::RegistryConfig;
+use anyhow::{bail, Context, Result};
+use bollard::auth::DockerCredentials;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Registry of image references that name none
+const DOCKER_HUB: &str = "docker.io";
+/// Server address Docker Hub logins are stored under, by `docker login` and credential helpers alike
+const DOCKER_HUB_SERVER: &str = "https://index.docker.io/v1/";
+/// Username a credential helper returns along with an identity token
+const TOKEN_USERNAME: &str = "<token>";
+/// Longest a credential helper may take, e.g. to reach a cloud provider's token service
+const HELPER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Registry host of an image reference such as `registry.example.com:5000/fks/api:1.2`; `docker.io`
+/// when it names none.
+pub fn registry_of(image: &str) -> &str {
+    match image.split_once('/') {
+        Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => host,
+        _ => DOCKER_HUB,
+    }
+}
+
+/// `host` without scheme or path, with Docker Hub's aliases folded into `docker.io`.
+fn normalize(host: &str) -> &str {
+    let host = host.trim_start_matches("https://").trim_start_matches("http://");
+    match host.split('/').next().unwrap_or(host) {
+        "index.docker.io" | "registry-1.docker.io" => DOCKER_HUB,
+        host => host,
+    }
+}
+
+/// Credentials for `registry` (a host as returned by [`registry_of`]) from `[[registries]]`; `None`
+/// when it has no entry, so the request goes out anonymously.
+pub async fn credentials(registries: &[RegistryConfig], registry: &str) -> Result<Option<DockerCredentials>> {
+    let registry = normalize(registry);
+    let Some(entry) = registries.iter().find(|r| normalize(&r.host) == registry) else { return Ok(None) };
+    let serveraddress = Some(if registry == DOCKER_HUB { DOCKER_HUB_SERVER.to_string() } else { registry.to_string() });
+    let (username, secret) = match (&entry.credential_helper, &entry.token, &entry.username, &entry.password) {
+        (Some(helper), ..) => ask_helper(helper, serveraddress.as_deref().unwrap_or(registry)).await
+            .with_context(|| format!("credential helper `{helper}` for {registry}"))?,
+        (None, Some(token), ..) => (TOKEN_USERNAME.to_string(), token.clone()),
+        (None, None, Some(username), Some(password)) => (username.clone(), password.clone()),
+        _ => bail!("registry {registry} needs a username and password, a token or a credential_helper"),
+    };
+    Ok(Some(if username == TOKEN_USERNAME {
+        DockerCredentials { identitytoken: Some(secret), serveraddress, ..Default::default() }
+    } else {
+        DockerCredentials { username: Some(username), password: Some(secret), serveraddress, ..Default::default() }
+    }))
+}
+
+/// Credentials of every `[[registries]]` entry by server address, for builds whose base images may
+/// come from any of them.
+pub async fn all(registries: &[RegistryConfig]) -> Result<HashMap<String, DockerCredentials>> {
+    let mut logins = HashMap::new();
+    for entry in registries {
+        if let Some(login) = credentials(registries, &entry.host).await? {
+            logins.insert(login.serveraddress.clone().unwrap_or_default(), login);
+        }
+    }
+    Ok(logins)
+}
+
+/// Username and password of `credentials`, for registries that take HTTP basic auth.
+pub fn basic(credentials: &DockerCredentials) -> Option<(&str, &str)> {
+    Some((credentials.username.as_deref()?, credentials.password.as_deref()?))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct HelperReply {
+    username: String,
+    secret: String,
+}
+
+/// Run `docker-credential-<helper> get` for `server`, as the Docker CLI does.
+async fn ask_helper(helper: &str, server: &str) -> Result<(String, String)> {
+    let program = format!("docker-credential-{helper}");
+    let mut child = tokio::process::Command::new(&program).arg("get")
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn().with_context(|| format!("running {program}"))?;
+    {
+        let mut stdin = child.stdin.take().context("no stdin")?;
+        stdin.write_all(server.as_bytes()).await?;
+    }
+    let output = tokio::time::timeout(HELPER_TIMEOUT, child.wait_with_output()).await.context("timed out")??;
+    if !output.status.success() {
+        // Helpers report errors such as "credentials not found in native keychain" on stdout
+        let message = String::from_utf8_lossy(if output.stdout.is_empty() { &output.stderr } else { &output.stdout }).trim().to_string();
+        bail!("{program} failed: {message}");
+    }
+    let reply: HelperReply = serde_json::from_slice(&output.stdout).with_context(|| format!("unexpected reply from {program}"))?;
+    Ok((reply.username, reply.secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(host: &str) -> RegistryConfig {
+        RegistryConfig { host: host.into(), username: None, password: None, token: None, credential_helper: None }
+    }
+
+    #[test]
+    fn references_name_their_registry() {
+        assert_eq!(registry_of("registry.example.com:5000/fks/api:1.2"), "registry.example.com:5000");
+        assert_eq!(registry_of("localhost/fks/api"), "localhost");
+        assert_eq!(registry_of("nuniesmith/fks_api:latest"), "docker.io");
+        assert_eq!(registry_of("nginx"), "docker.io");
+    }
+
+    #[tokio::test]
+    async fn logins_come_from_the_matching_entry() {
+        let private = RegistryConfig { username: Some("ci".into()), password: Some("pw".into()), ..entry("registry.example.com") };
+        let hub = RegistryConfig { token: Some("refresh".into()), ..entry("index.docker.io") };
+        let registries = vec![private, hub, entry("incomplete.example.com")];
+
+        let login = credentials(&registries, "registry.example.com").await.unwrap().unwrap();
+        assert_eq!(basic(&login), Some(("ci", "pw")));
+        assert_eq!(login.serveraddress.as_deref(), Some("registry.example.com"));
+        let login = credentials(&registries, "docker.io").await.unwrap().unwrap();
+        assert_eq!(login.identitytoken.as_deref(), Some("refresh"));
+        assert_eq!(login.serveraddress.as_deref(), Some(DOCKER_HUB_SERVER));
+        assert!(basic(&login).is_none());
+        assert!(credentials(&registries, "ghcr.io").await.unwrap().is_none(), "no entry, no login");
+        assert!(credentials(&registries, "incomplete.example.com").await.is_err());
+        assert!(all(&registries).await.is_err(), "one bad entry fails the lot");
+        let logins = all(&registries[..2]).await.unwrap();
+        let mut servers: Vec<&String> = logins.keys().collect();
+        servers.sort();
+        assert_eq!(servers, [DOCKER_HUB_SERVER, "registry.example.com"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn credential_helpers_are_asked_per_registry() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("docker-credential-fks-test");
+        std::fs::write(&script, r#"#!/bin/sh
+read server
+case "$server" in
+  registry.example.com) echo '{"ServerURL":"registry.example.com","Username":"robot","Secret":"s3cret"}' ;;
+  *) echo "credentials not found in native keychain"; exit 1 ;;
+esac
+"#).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let path = std::env::var("PATH").unwrap_or_default();
+        std::env::set_var("PATH", format!("{}:{path}", dir.path().display()));
+
+        let registries = vec![
+            RegistryConfig { credential_helper: Some("fks-test".into()), ..entry("registry.example.com") },
+            RegistryConfig { credential_helper: Some("fks-test".into()), ..entry("other.example.com") },
+        ];
+        let login = credentials(&registries, "registry.example.com").await.unwrap().unwrap();
+        assert_eq!(basic(&login), Some(("robot", "s3cret")));
+        let err = format!("{:#}", credentials(&registries, "other.example.com").await.unwrap_err());
+        assert!(err.contains("credentials not found"), "{err}");
+    }
+}
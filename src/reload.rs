@@ -0,0 +1,158 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{error, info};
+
+use crate::config::Config;
+use crate::monitor::MonitorHandle;
+
+/// How often `--watch-config` checks the file's modification time
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What a reload changed, by service id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    /// Anything outside `[[services]]` (monitoring, alerts, restart storm, watchdog, websocket, archive, discovery, prometheus) differs
+    pub settings_changed: bool,
+}
+
+impl ConfigDiff {
+    pub fn between(old: &Config, new: &Config) -> Self {
+        let mut diff = Self::default();
+        for service in &new.services {
+            match old.services.iter().find(|s| s.id == service.id) {
+                None => diff.added.push(service.id.clone()),
+                Some(previous) if !same(previous, service) => diff.changed.push(service.id.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old.services.iter().filter(|s| !new.services.iter().any(|n| n.id == s.id)).map(|s| s.id.clone()).collect();
+        diff.settings_changed = !same(&old.monitoring, &new.monitoring)
+            || !same(&old.alerts, &new.alerts)
+            || !same(&old.restart_storm, &new.restart_storm)
+            || !same(&old.restart_backoff, &new.restart_backoff)
+            || !same(&old.restart_verification, &new.restart_verification)
+            || !same(&old.restart_rate_limit, &new.restart_rate_limit)
+            || !same(&old.watchdog, &new.watchdog)
+            || !same(&old.websocket, &new.websocket)
+            || !same(&old.archive, &new.archive)
+            || !same(&old.discovery, &new.discovery)
+            || !same(&old.healing, &new.healing)
+            || !same(&old.prometheus, &new.prometheus);
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty() && !self.settings_changed
+    }
+}
+
+/// Config types have no `PartialEq`; compare their serialized form instead.
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Read and parse the config (file, includes or directory). Unlike [`Config::load`], a missing file
+/// is an error rather than the default config, so a botched edit never wipes the monitored services.
+pub async fn read_config(path: &Path) -> Result<Config> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || Config::load_strict(&path)).await?
+}
+
+/// Re-read `path` and apply it to the running monitor (and, through the shared config, the alert dispatcher).
+pub async fn reload(path: &Path, monitor: &MonitorHandle) -> Result<ConfigDiff> {
+    let config = read_config(path).await?;
+    Ok(apply(config, monitor, &path.display().to_string()))
+}
+
+/// Swap in `config`, logging what changed; `source` names where it came from.
+pub fn apply(config: Config, monitor: &MonitorHandle, source: &str) -> ConfigDiff {
+    let diff = monitor.reload(config);
+    if diff.is_empty() {
+        info!("🔄 Configuration reloaded from {source} (no changes)");
+        return diff;
+    }
+    info!(
+        added = ?diff.added,
+        removed = ?diff.removed,
+        changed = ?diff.changed,
+        settings_changed = diff.settings_changed,
+        "🔄 Configuration reloaded from {source}"
+    );
+    diff
+}
+
+/// SIGHUP listener; never fires where signals are unsupported or the handler could not be installed.
+pub struct Hangups {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangups {
+    pub fn install() -> Self {
+        Self {
+            #[cfg(unix)]
+            signal: match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                Ok(signal) => Some(signal),
+                Err(e) => { tracing::warn!(error=?e, "failed to install SIGHUP handler; reload on SIGHUP disabled"); None }
+            },
+        }
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut() {
+            signal.recv().await;
+            return;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+/// Reload on SIGHUP and, when `poll` is set, whenever a config file changes, appears or goes away. A file
+/// that fails to read or parse is logged and the running configuration stays in place.
+pub async fn watch(path: PathBuf, monitor: MonitorHandle, poll: Option<Duration>) {
+    let mut hangups = Hangups::install();
+    if let Some(every) = poll {
+        info!("👀 Watching {} for changes every {}s", path.display(), every.as_secs());
+    }
+    let mut last_seen = fingerprint(&path).await;
+
+    loop {
+        let tick = async {
+            match poll {
+                Some(every) => tokio::time::sleep(every).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = hangups.recv() => info!("🔄 SIGHUP received, reloading {}", path.display()),
+            _ = tick => {
+                let current = fingerprint(&path).await;
+                if current == last_seen { continue; }
+                info!("🔄 {} changed, reloading", path.display());
+            }
+        }
+        last_seen = fingerprint(&path).await;
+        if let Err(e) = reload(&path, &monitor).await {
+            error!(error = ?e, "config reload failed; keeping the running configuration");
+        }
+    }
+}
+
+/// Modification times of every file making up the config; `None` while it cannot be resolved
+/// (e.g. an include is half written).
+async fn fingerprint(path: &Path) -> Option<Vec<(PathBuf, Option<SystemTime>)>> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let files = crate::config::config_files(&path).ok()?;
+        Some(files.into_iter().map(|f| { let modified = std::fs::metadata(&f).and_then(|m| m.modified()).ok(); (f, modified) }).collect())
+    })
+    .await
+    .ok()
+    .flatten()
+}
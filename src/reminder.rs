@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Ongoing outage of a service whose `ServiceDown` notification went out; drives periodic reminders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outage {
+    pub service_id: String,
+    pub service_name: String,
+    pub since: DateTime<Utc>,
+    pub critical: bool,
+    /// Reminders sent so far
+    pub reminders: u32,
+    pub last_notified_at: DateTime<Utc>,
+}
+
+impl Outage {
+    pub fn new(service_id: &str, service_name: &str, critical: bool, since: DateTime<Utc>) -> Self {
+        Self {
+            service_id: service_id.to_string(),
+            service_name: service_name.to_string(),
+            since,
+            critical,
+            reminders: 0,
+            last_notified_at: since,
+        }
+    }
+
+    /// Count a reminder if `interval_minutes` have passed since the last notification, returning its number.
+    pub fn remind(&mut self, interval_minutes: u64, now: DateTime<Utc>) -> Option<u32> {
+        if now.signed_duration_since(self.last_notified_at).num_minutes() < interval_minutes as i64 {
+            return None;
+        }
+        self.reminders += 1;
+        self.last_notified_at = now;
+        Some(self.reminders)
+    }
+
+    pub fn duration_minutes(&self, now: DateTime<Utc>) -> i64 {
+        now.signed_duration_since(self.since).num_minutes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reminds_once_per_interval() {
+        let t0 = Utc::now();
+        let at = |m| t0 + chrono::Duration::minutes(m);
+        let mut outage = Outage::new("fks_api", "FKS API", true, t0);
+        assert!(outage.remind(30, at(29)).is_none());
+        assert_eq!(outage.remind(30, at(30)), Some(1));
+        assert!(outage.remind(30, at(45)).is_none());
+        // A late check does not send a backlog of reminders
+        assert_eq!(outage.remind(30, at(100)), Some(2));
+        assert!(outage.remind(30, at(120)).is_none());
+        assert_eq!(outage.duration_minutes(at(120)), 120);
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use reqwest::header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::archive::{self, S3Object};
+use crate::config::{Config, ConfigFormat};
+use crate::monitor::MonitorHandle;
+use crate::outbound::{self, Destination};
+use crate::reload::{self, Hangups};
+
+/// How often a remote config is fetched again unless `--config-refresh-seconds` says otherwise
+pub const DEFAULT_REFRESH_SECONDS: u64 = 60;
+
+/// A `--config` location that is not a local path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// `http://` or `https://` URL
+    Http(String),
+    /// `s3://bucket/key`; `AWS_ENDPOINT_URL` points it at an S3-compatible store such as MinIO
+    S3 { endpoint: String, region: String, bucket: String, key: String },
+    /// `consul://host:port/path/to/key` in the Consul KV store
+    Consul { address: String, key: String },
+}
+
+impl Source {
+    /// `None` for a local path; an error for a URL that cannot be used.
+    pub fn parse(location: &str) -> Result<Option<Self>> {
+        let Some((scheme, rest)) = location.split_once("://") else { return Ok(None) };
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if authority.is_empty() || path.is_empty() {
+            anyhow::bail!("config URL {location} needs a host (or bucket) and a path");
+        }
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Ok(Some(match scheme {
+            "http" | "https" => Source::Http(location.to_string()),
+            "s3" => {
+                let region = env("AWS_REGION").or_else(|| env("AWS_DEFAULT_REGION")).unwrap_or_else(|| "us-east-1".into());
+                Source::S3 {
+                    endpoint: env("AWS_ENDPOINT_URL").unwrap_or_else(|| format!("https://s3.{region}.amazonaws.com")),
+                    region,
+                    bucket: authority.to_string(),
+                    key: path.to_string(),
+                }
+            }
+            "consul" => {
+                let tls = env("CONSUL_HTTP_SSL").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+                Source::Consul { address: format!("{}://{authority}", if tls { "https" } else { "http" }), key: path.to_string() }
+            }
+            other => anyhow::bail!("unsupported config URL scheme `{other}` (use http, https, s3 or consul)"),
+        }))
+    }
+
+    /// Format from the file extension of the URL path or key, if it has one.
+    fn format(&self) -> Option<ConfigFormat> {
+        let path = match self {
+            Source::Http(url) => url.split(['?', '#']).next().unwrap_or(url),
+            Source::S3 { key, .. } | Source::Consul { key, .. } => key,
+        };
+        ConfigFormat::of(Path::new(path))
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Http(url) => f.write_str(url),
+            Source::S3 { bucket, key, .. } => write!(f, "s3://{bucket}/{key}"),
+            Source::Consul { address, key } => write!(f, "consul {address}/{key}"),
+        }
+    }
+}
+
+/// A remote config and what was last fetched from it, so unchanged configs are neither
+/// downloaded (ETag) nor re-applied (same body) again.
+pub struct RemoteConfig {
+    source: Source,
+    client: reqwest::Client,
+    etag: Option<String>,
+    body: Option<String>,
+}
+
+impl RemoteConfig {
+    pub fn new(source: Source) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+        Ok(Self { source, client, etag: None, body: None })
+    }
+
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Fetch and parse the config; `None` if it has not changed since the last successful fetch.
+    pub async fn fetch(&mut self) -> Result<Option<Config>> {
+        let mut request = match &self.source {
+            Source::Http(url) => self.client.get(url),
+            Source::S3 { endpoint, region, bucket, key } => {
+                let credentials = archive::Credentials::from_env().context("s3:// configs need AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY")?;
+                let object = S3Object { endpoint, region, bucket, key };
+                archive::s3_request(&self.client, reqwest::Method::GET, &object, &credentials, None, Vec::new())?
+            }
+            Source::Consul { address, key } => {
+                let request = self.client.get(format!("{address}/v1/kv/{key}?raw"));
+                match std::env::var("CONSUL_HTTP_TOKEN") {
+                    Ok(token) if !token.is_empty() => request.header("x-consul-token", token),
+                    _ => request,
+                }
+            }
+        };
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = outbound::send(Destination::RemoteConfig, request).await.with_context(|| format!("fetching {}", self.source))?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("fetching {}: HTTP {}", self.source, response.status());
+        }
+        let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let (etag, content_type) = (header(ETAG), header(CONTENT_TYPE).unwrap_or_default());
+        let body = response.text().await?;
+        if self.body.as_deref() == Some(body.as_str()) {
+            self.etag = etag;
+            return Ok(None);
+        }
+        let format = self.source.format().unwrap_or(if content_type.contains("yaml") {
+            ConfigFormat::Yaml
+        } else if content_type.contains("json") {
+            ConfigFormat::Json
+        } else {
+            ConfigFormat::Toml
+        });
+        let config = Config::parse(&body, format).with_context(|| format!("parsing {}", self.source))?;
+        self.etag = etag;
+        self.body = Some(body);
+        Ok(Some(config))
+    }
+}
+
+/// Fetch `remote` every `every` and on SIGHUP, applying changes to the running monitor. A failed
+/// fetch or a config that does not parse is logged and the running configuration stays in place.
+pub async fn watch(mut remote: RemoteConfig, monitor: MonitorHandle, every: Duration) {
+    let mut hangups = Hangups::install();
+    info!("👀 Fetching {} every {}s", remote.source(), every.as_secs());
+    loop {
+        tokio::select! {
+            _ = hangups.recv() => info!("🔄 SIGHUP received, fetching {}", remote.source()),
+            _ = tokio::time::sleep(every) => {}
+        }
+        match remote.fetch().await {
+            Ok(Some(config)) => { reload::apply(config, &monitor, &remote.source().to_string()); }
+            Ok(None) => tracing::debug!("{} unchanged", remote.source()),
+            Err(e) => error!(error = ?e, "remote config refresh failed; keeping the running configuration"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn locations_are_parsed_by_scheme() {
+        assert_eq!(Source::parse("config/monitor.toml").unwrap(), None);
+        assert_eq!(Source::parse("https://cfg/monitor.yaml").unwrap(), Some(Source::Http("https://cfg/monitor.yaml".into())));
+        assert_eq!(Source::parse("https://cfg/monitor.yaml?v=2").unwrap().unwrap().format(), Some(ConfigFormat::Yaml));
+        let Some(Source::S3 { bucket, key, .. }) = Source::parse("s3://configs/fks/monitor.json").unwrap() else { panic!("s3") };
+        assert_eq!((bucket.as_str(), key.as_str()), ("configs", "fks/monitor.json"));
+        let consul = Source::parse("consul://consul:8500/fks/monitor").unwrap().unwrap();
+        assert!(matches!(&consul, Source::Consul { key, .. } if key == "fks/monitor"));
+        assert_eq!(consul.format(), None);
+        assert!(Source::parse("ftp://host/monitor.toml").is_err());
+        assert!(Source::parse("s3://bucket-only").is_err());
+    }
+
+    #[tokio::test]
+    async fn unchanged_configs_are_not_downloaded_or_reapplied() {
+        use axum::http::{HeaderMap, StatusCode};
+        let render = |interval| {
+            let mut config = Config::default();
+            config.monitoring.check_interval_seconds = interval;
+            toml::to_string(&config).unwrap()
+        };
+        // (version, body); the version doubles as the ETag
+        let body = Arc::new(std::sync::Mutex::new((1, render(30))));
+        let served = Arc::new(AtomicUsize::new(0));
+        let app = {
+            let (body, served) = (body.clone(), served.clone());
+            axum::Router::new().route("/monitor", axum::routing::get(move |headers: HeaderMap| async move {
+                let (version, body) = body.lock().unwrap().clone();
+                let etag = format!("\"v{version}\"");
+                if headers.get("if-none-match").and_then(|v| v.to_str().ok()) == Some(etag.as_str()) {
+                    return (StatusCode::NOT_MODIFIED, [("etag", etag)], String::new());
+                }
+                served.fetch_add(1, Ordering::SeqCst);
+                (StatusCode::OK, [("etag", etag)], body)
+            }))
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut remote = RemoteConfig::new(Source::parse(&format!("http://{addr}/monitor")).unwrap().unwrap()).unwrap();
+        let config = remote.fetch().await.unwrap().expect("first fetch yields the config");
+        assert_eq!(config.monitoring.check_interval_seconds, 30);
+        assert!(remote.fetch().await.unwrap().is_none(), "304 Not Modified");
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+
+        *body.lock().unwrap() = (2, render(15));
+        assert_eq!(remote.fetch().await.unwrap().unwrap().monitoring.check_interval_seconds, 15);
+        *body.lock().unwrap() = (3, "not = [valid".into());
+        assert!(remote.fetch().await.is_err());
+        *body.lock().unwrap() = (4, render(15));
+        assert!(remote.fetch().await.unwrap().is_none(), "new ETag, same config as the one applied");
+        assert_eq!(served.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::config::RestartBackoffConfig;
+use crate::models::RestartBackoff;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+
+/// Why a restart may not go ahead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refusal {
+    BackingOff { until: DateTime<Utc>, restarts: u32 },
+    CircuitOpen { restarts: u32 },
+    /// Over `[restart_rate_limit]`, which is not about recovery at all
+    RateLimited { retry_at: DateTime<Utc>, max_restarts: u32, window_seconds: u64 },
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::BackingOff { until, restarts } => write!(f, "Restart backing off until {} after {restarts} restarts without recovery", until.to_rfc3339()),
+            Refusal::CircuitOpen { restarts } => write!(f, "Gave up after {restarts} restarts without recovery; reset the restart circuit to try again"),
+            Refusal::RateLimited { retry_at, max_restarts, window_seconds } => write!(f, "Restart rate limit of {max_restarts} per {window_seconds}s reached; retry after {}", retry_at.to_rfc3339()),
+        }
+    }
+}
+
+/// Whether a service in `state` may be restarted at `now`.
+pub fn allow(state: Option<&RestartBackoff>, now: DateTime<Utc>) -> Result<(), Refusal> {
+    match state {
+        Some(b) if b.circuit_open => Err(Refusal::CircuitOpen { restarts: b.restarts }),
+        Some(b) if now < b.next_restart_at => Err(Refusal::BackingOff { until: b.next_restart_at, restarts: b.restarts }),
+        _ => Ok(()),
+    }
+}
+
+/// Count a restart attempt at `now`, doubling the wait before the next one. Opens the circuit at
+/// `max_restarts`.
+pub fn record(cfg: &RestartBackoffConfig, state: &mut Option<RestartBackoff>, now: DateTime<Utc>) -> RestartBackoff {
+    let restarts = state.as_ref().map_or(0, |b| b.restarts) + 1;
+    let delay = cfg.initial_delay_seconds.saturating_mul(2u64.saturating_pow(restarts - 1)).min(cfg.max_delay_seconds);
+    state.insert(RestartBackoff {
+        restarts,
+        last_restart_at: now,
+        next_restart_at: now + Duration::seconds(delay.min(i64::MAX as u64) as i64),
+        circuit_open: restarts >= cfg.max_restarts,
+    }).clone()
+}
+
+/// A passing check at `now`: once the service has stayed up `reset_after_seconds` since its last
+/// restart the count starts over. An open circuit stays open until it is reset.
+pub fn healthy(cfg: &RestartBackoffConfig, state: &mut Option<RestartBackoff>, now: DateTime<Utc>) {
+    if state.as_ref().is_some_and(|b| !b.circuit_open && now.signed_duration_since(b.last_restart_at).num_seconds() >= cfg.reset_after_seconds as i64) {
+        *state = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_double_until_the_circuit_opens() {
+        let cfg = RestartBackoffConfig { initial_delay_seconds: 30, max_delay_seconds: 100, max_restarts: 4, reset_after_seconds: 600 };
+        let start = Utc::now();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+        let mut state = None;
+
+        assert_eq!(allow(state.as_ref(), start), Ok(()));
+        assert_eq!(record(&cfg, &mut state, at(0)).next_restart_at, at(30));
+        assert!(matches!(allow(state.as_ref(), at(29)), Err(Refusal::BackingOff { restarts: 1, .. })));
+        assert_eq!(allow(state.as_ref(), at(30)), Ok(()));
+        assert_eq!(record(&cfg, &mut state, at(30)).next_restart_at, at(90));
+        // Capped at max_delay_seconds
+        assert_eq!(record(&cfg, &mut state, at(90)).next_restart_at, at(190));
+        // Healthy, but not for long enough to count as recovered
+        healthy(&cfg, &mut state, at(100));
+        let opened = record(&cfg, &mut state, at(190));
+        assert!(opened.circuit_open);
+        assert_eq!(allow(state.as_ref(), at(10_000)), Err(Refusal::CircuitOpen { restarts: 4 }));
+        healthy(&cfg, &mut state, at(10_000));
+        assert!(state.is_some(), "an open circuit needs a reset");
+
+        let mut state = None;
+        record(&cfg, &mut state, at(0));
+        healthy(&cfg, &mut state, at(600));
+        assert_eq!(state, None, "stayed up long enough");
+    }
+}
@@ -0,0 +1,72 @@
+use crate::config::RestartRateLimitConfig;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Restarts are remembered at least this long, for `restarts_last_hour` in healing rules
+const KEEP: Duration = Duration::hours(1);
+
+/// Recent restarts per service, behind `[restart_rate_limit]` and `restarts_last_hour`.
+#[derive(Debug, Clone, Default)]
+pub struct RestartLimiter {
+    recent: Arc<Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>>,
+}
+
+impl RestartLimiter {
+    /// When `service_id` may be restarted again, if it has used up its restarts for the window at `now`.
+    pub fn retry_at(&self, cfg: &RestartRateLimitConfig, service_id: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if cfg.max_restarts == 0 { return None; }
+        let recent = self.recent.lock().unwrap();
+        let window = window(cfg);
+        let in_window: Vec<DateTime<Utc>> = recent.get(service_id)?.iter().filter(|at| **at + window > now).copied().collect();
+        // The oldest restart that still counts has to leave the window for another to fit
+        in_window.len().checked_sub(cfg.max_restarts as usize).map(|excess| in_window[excess] + window)
+    }
+
+    /// Restarts of `service_id` after `since`, counting back no further than an hour (or the rate
+    /// limit's window, if longer).
+    pub fn restarts_since(&self, service_id: &str, since: DateTime<Utc>) -> usize {
+        self.recent.lock().unwrap().get(service_id).map_or(0, |restarts| restarts.iter().filter(|at| **at > since).count())
+    }
+
+    /// Count a restart of `service_id` at `now`.
+    pub fn record(&self, cfg: &RestartRateLimitConfig, service_id: &str, now: DateTime<Utc>) {
+        let mut recent = self.recent.lock().unwrap();
+        let restarts = recent.entry(service_id.to_string()).or_default();
+        restarts.push_back(now);
+        let keep = window(cfg).max(KEEP);
+        while restarts.front().is_some_and(|at| *at + keep <= now) { restarts.pop_front(); }
+    }
+}
+
+fn window(cfg: &RestartRateLimitConfig) -> Duration {
+    Duration::seconds(cfg.window_seconds.min(i64::MAX as u64) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restarts_beyond_the_limit_wait_for_the_oldest_to_leave_the_window() {
+        let cfg = RestartRateLimitConfig { max_restarts: 2, window_seconds: 60 };
+        let limiter = RestartLimiter::default();
+        let start = Utc::now();
+        let at = |seconds: i64| start + Duration::seconds(seconds);
+
+        assert_eq!(limiter.retry_at(&cfg, "api", at(0)), None);
+        limiter.record(&cfg, "api", at(0));
+        limiter.record(&cfg, "api", at(10));
+        assert_eq!(limiter.retry_at(&cfg, "api", at(20)), Some(at(60)));
+        assert_eq!(limiter.retry_at(&cfg, "db", at(20)), None, "counted per service");
+        assert_eq!(limiter.retry_at(&cfg, "api", at(60)), None);
+        limiter.record(&cfg, "api", at(60));
+        assert_eq!(limiter.retry_at(&cfg, "api", at(61)), Some(at(70)));
+
+        let off = RestartRateLimitConfig { max_restarts: 0, ..cfg };
+        assert_eq!(limiter.retry_at(&off, "api", at(61)), None);
+        assert_eq!(limiter.restarts_since("api", at(61) - Duration::hours(1)), 3, "kept for an hour whatever the window");
+        limiter.record(&off, "api", at(3605));
+        assert_eq!(limiter.restarts_since("api", at(3605) - Duration::hours(1)), 3);
+    }
+}
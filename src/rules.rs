@@ -0,0 +1,309 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::config::AlertRule;
+use crate::models::ServiceConfig;
+
+/// Per-service metric an alert rule can be evaluated against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleMetric {
+    /// Failed health checks per minute over the last 5 minutes
+    ErrorRate,
+    CpuUsagePercent,
+    MemoryUsageMb,
+    ResponseTimeMs,
+    NetworkInBytes,
+    NetworkOutBytes,
+    BlockReadBytes,
+    BlockWriteBytes,
+}
+
+impl RuleMetric {
+    const ALL: [RuleMetric; 8] = [
+        RuleMetric::ErrorRate,
+        RuleMetric::CpuUsagePercent,
+        RuleMetric::MemoryUsageMb,
+        RuleMetric::ResponseTimeMs,
+        RuleMetric::NetworkInBytes,
+        RuleMetric::NetworkOutBytes,
+        RuleMetric::BlockReadBytes,
+        RuleMetric::BlockWriteBytes,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleMetric::ErrorRate => "error_rate",
+            RuleMetric::CpuUsagePercent => "cpu_usage_percent",
+            RuleMetric::MemoryUsageMb => "memory_usage_mb",
+            RuleMetric::ResponseTimeMs => "response_time_ms",
+            RuleMetric::NetworkInBytes => "network_in_bytes",
+            RuleMetric::NetworkOutBytes => "network_out_bytes",
+            RuleMetric::BlockReadBytes => "block_read_bytes",
+            RuleMetric::BlockWriteBytes => "block_write_bytes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Comparison {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Comparison {
+    pub fn parse(token: &str) -> Option<Self> {
+        match token {
+            ">" => Some(Comparison::Gt),
+            ">=" => Some(Comparison::Ge),
+            "<" => Some(Comparison::Lt),
+            "<=" => Some(Comparison::Le),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+        }
+    }
+
+    pub fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Gt => value > threshold,
+            Comparison::Ge => value >= threshold,
+            Comparison::Lt => value < threshold,
+            Comparison::Le => value <= threshold,
+        }
+    }
+}
+
+/// Parsed rule expression: `<metric> <op> <threshold> [for <duration>]`, e.g. `error_rate > 5/min for 3m`
+/// or `cpu_usage_percent > 90 for 5m`. Rates accept `/s`, `/min` and `/h` and are normalized to per-minute;
+/// durations accept `s`, `m` and `h` suffixes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RuleExpr {
+    pub metric: RuleMetric,
+    pub op: Comparison,
+    pub threshold: f64,
+    pub for_seconds: u64,
+}
+
+impl std::str::FromStr for RuleExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let (metric, op, threshold, rest) = match tokens.as_slice() {
+            [metric, op, threshold, rest @ ..] => (*metric, *op, *threshold, rest),
+            _ => return Err(format!("expected `<metric> <op> <threshold> [for <duration>]`, got `{s}`")),
+        };
+        let metric = RuleMetric::ALL
+            .into_iter()
+            .find(|m| m.as_str() == metric)
+            .ok_or_else(|| {
+                let known: Vec<&str> = RuleMetric::ALL.iter().map(|m| m.as_str()).collect();
+                format!("unknown metric `{metric}` (expected one of {})", known.join(", "))
+            })?;
+        let op = Comparison::parse(op).ok_or_else(|| format!("unknown comparison `{op}` (expected >, >=, < or <=)"))?;
+        let threshold = parse_threshold(threshold, metric)?;
+        let for_seconds = match rest {
+            [] => 0,
+            ["for", duration] => parse_duration_secs(duration).ok_or_else(|| format!("invalid duration `{duration}`"))?,
+            _ => return Err(format!("unexpected trailing `{}` (expected `for <duration>`)", rest.join(" "))),
+        };
+        Ok(Self { metric, op, threshold, for_seconds })
+    }
+}
+
+impl TryFrom<String> for RuleExpr {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<RuleExpr> for String {
+    fn from(expr: RuleExpr) -> Self {
+        expr.to_string()
+    }
+}
+
+impl fmt::Display for RuleExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.metric.as_str(), self.op.as_str(), self.threshold)?;
+        if self.metric == RuleMetric::ErrorRate { write!(f, "/min")?; }
+        if self.for_seconds > 0 { write!(f, " for {}s", self.for_seconds)?; }
+        Ok(())
+    }
+}
+
+fn parse_threshold(token: &str, metric: RuleMetric) -> Result<f64, String> {
+    let (number, per_minute) = match token.split_once('/') {
+        Some((n, "s" | "sec")) => (n, Some(60.0)),
+        Some((n, "m" | "min")) => (n, Some(1.0)),
+        Some((n, "h" | "hour")) => (n, Some(1.0 / 60.0)),
+        Some((_, unit)) => return Err(format!("unknown rate unit `/{unit}` (expected /s, /min or /h)")),
+        None => (token, None),
+    };
+    if per_minute.is_some() && metric != RuleMetric::ErrorRate {
+        return Err(format!("rate units only apply to error_rate, not {}", metric.as_str()));
+    }
+    let number = number.strip_suffix('%').unwrap_or(number);
+    let value: f64 = number.parse().map_err(|_| format!("invalid threshold `{token}`"))?;
+    if !value.is_finite() { return Err(format!("invalid threshold `{token}`")); }
+    Ok(value * per_minute.unwrap_or(1.0))
+}
+
+pub fn parse_duration_secs(token: &str) -> Option<u64> {
+    let split = token.find(|c: char| !c.is_ascii_digit()).unwrap_or(token.len());
+    let (number, unit) = token.split_at(split);
+    let value: u64 = number.parse().ok()?;
+    let scale = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return None,
+    };
+    value.checked_mul(scale)
+}
+
+/// State change produced by [`RuleEngine::evaluate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleTransition {
+    Firing { rule: String, service_id: String, value: f64, since: DateTime<Utc> },
+    Resolved { rule: String, service_id: String, value: Option<f64> },
+}
+
+/// Tracks how long each (rule, service) condition has held so `for` clauses fire only once the breach
+/// has persisted, and resolution is reported exactly once.
+#[derive(Debug, Default)]
+pub struct RuleEngine {
+    pending_since: HashMap<(String, String), DateTime<Utc>>,
+    firing: HashSet<(String, String)>,
+}
+
+impl RuleEngine {
+    pub fn evaluate(
+        &mut self,
+        rules: &[AlertRule],
+        services: &[ServiceConfig],
+        now: DateTime<Utc>,
+        sample: impl Fn(RuleMetric, &str) -> Option<f64>,
+    ) -> Vec<RuleTransition> {
+        let mut transitions = Vec::new();
+        for rule in rules {
+            for service in services.iter().filter(|s| rule.services.is_empty() || rule.services.contains(&s.id)) {
+                let key = (rule.name.clone(), service.id.clone());
+                let value = sample(rule.expr.metric, &service.id);
+                match value.filter(|v| rule.expr.op.holds(*v, rule.expr.threshold)) {
+                    Some(v) => {
+                        let since = *self.pending_since.entry(key.clone()).or_insert(now);
+                        let held = now.signed_duration_since(since).num_seconds() >= rule.expr.for_seconds as i64;
+                        if held && self.firing.insert(key) {
+                            transitions.push(RuleTransition::Firing { rule: rule.name.clone(), service_id: service.id.clone(), value: v, since });
+                        }
+                    }
+                    None => {
+                        self.pending_since.remove(&key);
+                        if self.firing.remove(&key) {
+                            transitions.push(RuleTransition::Resolved { rule: rule.name.clone(), service_id: service.id.clone(), value });
+                        }
+                    }
+                }
+            }
+        }
+        transitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProbeMode, ServiceType};
+
+    #[test]
+    fn parses_rule_expressions() {
+        let e: RuleExpr = "error_rate > 5/min for 3m".parse().unwrap();
+        assert_eq!(e, RuleExpr { metric: RuleMetric::ErrorRate, op: Comparison::Gt, threshold: 5.0, for_seconds: 180 });
+        let e: RuleExpr = "cpu_usage_percent >= 90% for 30s".parse().unwrap();
+        assert_eq!((e.op, e.threshold, e.for_seconds), (Comparison::Ge, 90.0, 30));
+        let e: RuleExpr = "error_rate > 1/s".parse().unwrap();
+        assert_eq!((e.threshold, e.for_seconds), (60.0, 0));
+        assert_eq!(e.to_string().parse::<RuleExpr>().unwrap(), e);
+        assert!("disk > 5".parse::<RuleExpr>().unwrap_err().contains("unknown metric"));
+        assert!("cpu_usage_percent > 5/min".parse::<RuleExpr>().is_err());
+        assert!("cpu_usage_percent > 90 during 5m".parse::<RuleExpr>().is_err());
+        assert!("cpu_usage_percent > 90 for 5d".parse::<RuleExpr>().is_err());
+    }
+
+    fn service(id: &str) -> ServiceConfig {
+        ServiceConfig {
+            id: id.into(),
+            name: id.into(),
+            health_endpoint: String::new(),
+            service_type: ServiceType::Api,
+            docker_container: None,
+            expected_response_time_ms: 100,
+            critical: false,
+            check_interval_seconds: None,
+            heartbeat_window_seconds: None,
+            depends_on: vec![],
+            tags: vec![],
+            probes: vec![],
+            probe_mode: ProbeMode::All,
+            labels: Default::default(),
+            group: None,
+            metadata: Default::default(),
+            auto_update: false,
+            kubernetes: None,
+            systemd_unit: None,
+            auto_restart: None,
+            drain: None,
+            metrics: None,
+            docker_host: None,
+        }
+    }
+
+    #[test]
+    fn fires_after_for_duration_and_resolves_once() {
+        let rules = vec![AlertRule {
+            name: "high_cpu".into(),
+            expr: "cpu_usage_percent > 90 for 5m".parse().unwrap(),
+            services: vec![],
+            severity: "warning".into(),
+        }];
+        let services = vec![service("a"), service("b")];
+        let mut engine = RuleEngine::default();
+        let t0 = Utc::now();
+        let at = |secs| t0 + chrono::Duration::seconds(secs);
+        let hot_a = |_: RuleMetric, id: &str| Some(if id == "a" { 95.0 } else { 10.0 });
+
+        assert!(engine.evaluate(&rules, &services, at(0), hot_a).is_empty());
+        assert!(engine.evaluate(&rules, &services, at(240), hot_a).is_empty());
+        let fired = engine.evaluate(&rules, &services, at(300), hot_a);
+        assert!(matches!(fired.as_slice(), [RuleTransition::Firing { service_id, value, .. }] if service_id == "a" && *value == 95.0));
+        // Still breached: no repeat
+        assert!(engine.evaluate(&rules, &services, at(360), hot_a).is_empty());
+
+        let cool = |_: RuleMetric, _: &str| Some(10.0);
+        let resolved = engine.evaluate(&rules, &services, at(420), cool);
+        assert_eq!(resolved, vec![RuleTransition::Resolved { rule: "high_cpu".into(), service_id: "a".into(), value: Some(10.0) }]);
+        assert!(engine.evaluate(&rules, &services, at(480), cool).is_empty());
+
+        // A dip below the threshold restarts the `for` timer
+        engine.evaluate(&rules, &services, at(500), hot_a);
+        engine.evaluate(&rules, &services, at(700), cool);
+        assert!(engine.evaluate(&rules, &services, at(900), hot_a).is_empty());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::config::{Config, ScheduleConfig};
+use crate::models::{EventType, MonitorEvent};
+use crate::monitor::MonitorHandle;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, warn};
+
+/// How often schedules are looked at; cron has minute resolution
+const TICK: std::time::Duration = std::time::Duration::from_secs(15);
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A five-field cron expression (minute hour day-of-month month day-of-week) evaluated in UTC.
+/// Fields take `*`, values, ranges (`1-5`), steps (`*/15`, `0-30/10`) and lists of those; months and
+/// weekdays may be named (`jan`, `mon`) and Sunday is 0 or 7. `@hourly`, `@daily`, `@weekly`,
+/// `@monthly` and `@yearly` are shorthands. When both day fields are restricted either may match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    either_day: bool,
+}
+
+impl CronSchedule {
+    /// The first time after `after` (at minute resolution) the schedule fires; `None` for dates that
+    /// never come, such as February 30th.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(5 * 366);
+        while t <= limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?.and_utc();
+            } else if !self.day_matches(t.date_naive()) {
+                t = (t.date_naive() + Duration::days(1)).and_hms_opt(0, 0, 0)?.and_utc();
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.date_naive().and_hms_opt(t.hour(), 0, 0)?.and_utc() + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.either_day { day || weekday } else { day && weekday }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(expr: &str) -> Result<Self> {
+        let expr = expr.trim();
+        let expanded = match expr {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!("expected 5 fields (minute hour day-of-month month day-of-week), got {}", fields.len());
+        };
+        let mut weekdays = field(weekday, 0, 7, &WEEKDAYS).context("day of week")?;
+        // Sunday is 0 or 7
+        if weekdays & (1 << 7) != 0 { weekdays = (weekdays | 1) & !(1 << 7); }
+        Ok(Self {
+            expr: expr.to_string(),
+            minutes: field(minute, 0, 59, &[]).context("minute")?,
+            hours: field(hour, 0, 23, &[]).context("hour")?,
+            days: field(day, 1, 31, &[]).context("day of month")?,
+            months: field(month, 1, 12, &MONTHS).context("month")?,
+            weekdays,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+}
+
+/// Bit set of the values `text` selects within `min..=max`; `names[i]` stands for `min + i`.
+fn field(text: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |s: &str| -> Result<u32> {
+        let v = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(i) => min + i as u32,
+            None => s.parse().with_context(|| format!("invalid value `{s}`"))?,
+        };
+        if !(min..=max).contains(&v) { bail!("`{s}` is out of range {min}-{max}"); }
+        Ok(v)
+    };
+    let mut bits = 0;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<usize>().ok().filter(|s| *s > 0).with_context(|| format!("invalid step in `{part}`"))?)),
+            None => (part, None),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            None => {
+                let v = value(range)?;
+                (v, if step.is_some() { max } else { v })
+            }
+        };
+        if start > end { bail!("range `{range}` goes backwards"); }
+        for v in (start..=end).step_by(step.unwrap_or(1)) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+impl Serialize for CronSchedule {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.expr)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for CronSchedule {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(d)?;
+        raw.parse().map_err(|e: anyhow::Error| serde::de::Error::custom(format!("invalid cron expression `{raw}`: {e:#}")))
+    }
+}
+
+/// What a schedule did to one of its services.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Restarted,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceOutcome {
+    pub service_id: String,
+    pub outcome: Outcome,
+    pub message: String,
+}
+
+/// One firing of a schedule.
+#[derive(Debug, Clone, Serialize)]
+pub struct Run {
+    pub at: DateTime<Utc>,
+    pub services: Vec<ServiceOutcome>,
+}
+
+/// A configured schedule as listed by `GET /api/schedules`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    #[serde(flatten)]
+    pub schedule: ScheduleConfig,
+    /// Unset while disabled
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<Run>,
+}
+
+/// Last run of each schedule, by name.
+#[derive(Debug, Clone, Default)]
+pub struct Schedules {
+    last_runs: Arc<Mutex<HashMap<String, Run>>>,
+}
+
+impl Schedules {
+    pub fn status(&self, config: &Config, now: DateTime<Utc>) -> Vec<ScheduleStatus> {
+        let last_runs = self.last_runs.lock().unwrap();
+        config.schedules.iter().map(|schedule| ScheduleStatus {
+            next_run: schedule.enabled.then(|| schedule.cron.next_after(now)).flatten(),
+            last_run: last_runs.get(&schedule.name).cloned(),
+            schedule: schedule.clone(),
+        }).collect()
+    }
+}
+
+/// Fire `[[schedules]]` as they come due for as long as the monitor runs.
+pub fn spawn(monitor: MonitorHandle, schedules: Schedules) {
+    tokio::spawn(run(monitor, schedules));
+}
+
+async fn run(monitor: MonitorHandle, schedules: Schedules) {
+    let mut since = monitor.now();
+    loop {
+        tokio::time::sleep(TICK).await;
+        let now = monitor.now();
+        for (schedule, at) in due(&monitor.config(), since, now) {
+            tokio::spawn(fire(monitor.clone(), schedules.clone(), schedule, at));
+        }
+        since = now;
+    }
+}
+
+/// Enabled schedules that came due after `since` and no later than `now`, with when. A schedule
+/// that came due more than once in between (e.g. after the host slept) fires once.
+fn due(config: &Config, since: DateTime<Utc>, now: DateTime<Utc>) -> Vec<(ScheduleConfig, DateTime<Utc>)> {
+    config.schedules.iter()
+        .filter(|schedule| schedule.enabled)
+        .filter_map(|schedule| {
+            let at = schedule.cron.next_after(since).filter(|at| *at <= now)?;
+            Some((schedule.clone(), at))
+        })
+        .collect()
+}
+
+/// Restart the schedule's services one after another and remember how it went.
+async fn fire(monitor: MonitorHandle, schedules: Schedules, schedule: ScheduleConfig, at: DateTime<Utc>) -> Run {
+    info!(schedule = %schedule.name, cron = %schedule.cron, "⏰ Running scheduled restarts");
+    let mut services = Vec::new();
+    for service_id in &schedule.services {
+        services.push(restart(&monitor, &schedule, service_id).await);
+    }
+    let run = Run { at, services };
+    schedules.last_runs.lock().unwrap().insert(schedule.name.clone(), run.clone());
+    run
+}
+
+async fn restart(monitor: &MonitorHandle, schedule: &ScheduleConfig, service_id: &str) -> ServiceOutcome {
+    let skipped = |message: String| {
+        info!(schedule = %schedule.name, service = %service_id, "Scheduled restart skipped: {message}");
+        ServiceOutcome { service_id: service_id.to_string(), outcome: Outcome::Skipped, message }
+    };
+    let config = monitor.config();
+    let Some(service) = config.services.iter().find(|s| s.id == service_id) else {
+        return skipped("unknown service".into());
+    };
+    if monitor.automation_frozen() {
+        return skipped("automation is frozen".into());
+    }
+    if monitor.auto_restart_suppressed() {
+        return skipped("automatic restarts are suppressed after a restart storm".into());
+    }
+    if schedule.skip_when_silenced {
+        if let Some(by) = monitor.silences().silenced_by(service, monitor.now()) {
+            return skipped(format!("silenced by {by}"));
+        }
+    }
+    let result = monitor.restart_service(service_id).await;
+    let (event_type, message, outcome) = if result.success {
+        warn!(schedule = %schedule.name, service = %service_id, "🔁 Restarted on schedule");
+        (EventType::ServiceRestarted, format!("Service {} restarted on schedule {}", service.name, schedule.name), Outcome::Restarted)
+    } else {
+        error!(schedule = %schedule.name, service = %service_id, "Scheduled restart failed: {}", result.message);
+        (EventType::SystemAlert, format!("Scheduled restart of {} failed: {}", service.name, result.message), Outcome::Failed)
+    };
+    monitor.inject_event(MonitorEvent {
+        event_type,
+        service_id: Some(service_id.to_string()),
+        message,
+        timestamp: result.timestamp,
+        data: Some(serde_json::json!({"reason": "scheduled", "schedule": schedule.name, "cron": schedule.cron.to_string(), "success": result.success})),
+        metadata: Default::default(),
+    });
+    if result.success { monitor.check_soon(service_id); }
+    ServiceOutcome { service_id: service_id.to_string(), outcome, message: result.message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn cron(expr: &str) -> CronSchedule {
+        expr.parse().unwrap()
+    }
+
+    #[test]
+    fn cron_expressions_find_the_next_run() {
+        // 2026-10-18 is a Sunday
+        let now = at(2026, 10, 18, 12, 30);
+        assert_eq!(cron("0 3 * * *").next_after(now), Some(at(2026, 10, 19, 3, 0)));
+        assert_eq!(cron("*/20 * * * *").next_after(now), Some(at(2026, 10, 18, 12, 40)));
+        assert_eq!(cron("*/20 * * * *").next_after(at(2026, 10, 18, 12, 40)), Some(at(2026, 10, 18, 13, 0)), "strictly after");
+        assert_eq!(cron("30 2 * * mon-fri").next_after(now), Some(at(2026, 10, 19, 2, 30)));
+        assert_eq!(cron("0 0 * * 7").next_after(now), Some(at(2026, 10, 25, 0, 0)), "7 is Sunday");
+        assert_eq!(cron("0 4 1 jan,jul *").next_after(now), Some(at(2027, 1, 1, 4, 0)));
+        assert_eq!(cron("0 0 29 2 *").next_after(now), Some(at(2028, 2, 29, 0, 0)));
+        // Either restricted day field may match
+        assert_eq!(cron("0 0 1 * fri").next_after(now), Some(at(2026, 10, 23, 0, 0)));
+        assert_eq!(cron("@daily").next_after(now), Some(at(2026, 10, 19, 0, 0)));
+        assert_eq!(cron("0 0 30 2 *").next_after(now), None);
+
+        for bad in ["0 3 * *", "60 * * * *", "* * * * 8", "*/0 * * * *", "5-1 * * * *", "0 0 * foo *"] {
+            assert!(bad.parse::<CronSchedule>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn schedules_come_due_once_between_ticks() {
+        let config = Config { schedules: toml::from_str::<toml::Table>(r#"schedules = [
+            { name = "nightly", cron = "0 3 * * *", services = ["fks_transformer"] },
+            { name = "off", cron = "0 3 * * *", services = ["fks_api"], enabled = false },
+            { name = "often", cron = "* * * * *", services = ["fks_api"] },
+        ]"#).unwrap()["schedules"].clone().try_into().unwrap(), ..Default::default() };
+        let names = |since, now| due(&config, since, now).into_iter().map(|(s, at)| (s.name, at)).collect::<Vec<_>>();
+        assert_eq!(names(at(2026, 10, 18, 2, 59), at(2026, 10, 18, 3, 0)), vec![("nightly".to_string(), at(2026, 10, 18, 3, 0)), ("often".to_string(), at(2026, 10, 18, 3, 0))]);
+        assert!(names(at(2026, 10, 18, 3, 0), at(2026, 10, 18, 3, 0)).is_empty());
+        // Missed runs while asleep fire once
+        assert_eq!(names(at(2026, 10, 18, 1, 0), at(2026, 10, 18, 4, 0)).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn scheduled_restarts_skip_silenced_services_and_report_failures() {
+        let mut config = Config::default();
+        config.services.retain(|s| s.id == "fks_api" || s.id == "fks_data");
+        for service in &mut config.services { service.docker_container = None; }
+        config.schedules = toml::from_str::<toml::Table>(r#"schedules = [{ name = "nightly", cron = "0 3 * * *", services = ["fks_api", "fks_data", "ghost"] }]"#)
+            .unwrap()["schedules"].clone().try_into().unwrap();
+        let monitor = crate::monitor::ServiceMonitor::new(config).await.unwrap().handle();
+        let silence = serde_json::from_value(serde_json::json!({"service_id": "fks_api", "duration_minutes": 30})).unwrap();
+        monitor.silences().add(silence, Utc::now()).unwrap();
+        let mut events = monitor.subscribe_events();
+        let schedules = Schedules::default();
+
+        let schedule = monitor.config().schedules[0].clone();
+        let run = fire(monitor.clone(), schedules.clone(), schedule, at(2026, 10, 18, 3, 0)).await;
+        let outcomes: Vec<_> = run.services.iter().map(|s| (s.service_id.as_str(), s.outcome)).collect();
+        assert_eq!(outcomes, vec![("fks_api", Outcome::Skipped), ("fks_data", Outcome::Failed), ("ghost", Outcome::Skipped)]);
+        let failed = events.recv().await.unwrap();
+        assert_eq!((failed.event_type, failed.service_id.as_deref()), (EventType::SystemAlert, Some("fks_data")));
+        assert_eq!(failed.data.unwrap()["reason"], "scheduled");
+
+        let status = schedules.status(&monitor.config(), at(2026, 10, 18, 3, 0));
+        assert_eq!(status[0].next_run, Some(at(2026, 10, 19, 3, 0)));
+        assert_eq!(status[0].last_run.as_ref().unwrap().at, at(2026, 10, 18, 3, 0));
+        let listed = serde_json::to_value(&status).unwrap();
+        assert_eq!(listed[0]["cron"], "0 3 * * *");
+        assert_eq!(listed[0]["last_run"]["services"][0]["message"], "silenced by ".to_string() + &monitor.silences().list(Utc::now())[0].id);
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+
+/// Config fields that may hold credentials. They accept `env:` / `file:` references, resolved at load
+/// time, and are redacted wherever a config is echoed back. `[]` marks every element of an array.
+pub const SECRET_FIELDS: [&str; 14] = [
+    "alerts.webhook_url",
+    "alerts.routes[].webhook_url",
+    "alerts.slack.webhook_url",
+    "alerts.slack.bot_token",
+    "alerts.email.username",
+    "alerts.email.password",
+    "alerts.pagerduty.routing_key",
+    "archive.access_key_id",
+    "archive.secret_access_key",
+    "prometheus.bearer_token",
+    "kubernetes.token",
+    "registries[].password",
+    "registries[].token",
+    "hooks[].run.url",
+];
+
+/// Whether a config path like `alerts.routes[2].webhook_url` is one of the [`SECRET_FIELDS`].
+pub fn is_secret_path(path: &str) -> bool {
+    let mut normalized = String::with_capacity(path.len());
+    let mut in_index = false;
+    for c in path.chars() {
+        match c {
+            '[' => { in_index = true; normalized.push('['); }
+            ']' => { in_index = false; normalized.push(']'); }
+            _ if in_index => {}
+            c => normalized.push(c),
+        }
+    }
+    SECRET_FIELDS.contains(&normalized.as_str())
+}
+
+/// `env:NAME` is the environment variable, `file:/path` the file's content without trailing newlines;
+/// anything else is taken literally.
+pub fn resolve(value: &str) -> Result<String> {
+    if let Some(name) = value.strip_prefix("env:") {
+        return std::env::var(name).with_context(|| format!("environment variable {name} is not set"));
+    }
+    if let Some(path) = value.strip_prefix("file:") {
+        let content = std::fs::read_to_string(path).with_context(|| format!("reading secret file {path}"))?;
+        return Ok(content.trim_end_matches(['\r', '\n']).to_string());
+    }
+    Ok(value.to_string())
+}
+
+/// An environment variable that may itself be a reference (e.g. `FKS_MONITOR_API_KEY=file:/run/secrets/api_key`).
+/// `Ok(None)` when unset; an error when the reference cannot be resolved.
+pub fn env_var(name: &str) -> Result<Option<String>> {
+    match std::env::var(name) {
+        Ok(value) => resolve(&value).with_context(|| format!("resolving {name}")).map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Resolve references in the [`SECRET_FIELDS`] of a parsed config document.
+pub fn resolve_fields(root: &mut toml::Table) -> Result<()> {
+    for field in SECRET_FIELDS {
+        let path: Vec<&str> = field.split('.').collect();
+        for_each_value(root, &path, &mut |value| {
+            if let toml::Value::String(s) = value {
+                *s = resolve(s).context(field)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Call `f` on every value at `path` (see [`SECRET_FIELDS`] for the syntax) that is present.
+pub fn for_each_value(table: &mut toml::Table, path: &[&str], f: &mut impl FnMut(&mut toml::Value) -> Result<()>) -> Result<()> {
+    let Some((first, rest)) = path.split_first() else { return Ok(()) };
+    let (key, each) = match first.strip_suffix("[]") {
+        Some(key) => (key, true),
+        None => (*first, false),
+    };
+    let Some(value) = table.get_mut(key) else { return Ok(()) };
+    let targets: Vec<&mut toml::Value> = match value {
+        toml::Value::Array(items) if each => items.iter_mut().collect(),
+        _ if each => return Ok(()),
+        value => vec![value],
+    };
+    for target in targets {
+        match target {
+            value if rest.is_empty() => f(value)?,
+            toml::Value::Table(inner) => for_each_value(inner, rest, f)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_resolve_from_env_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("smtp_password");
+        std::fs::write(&file, "s3cret\n").unwrap();
+        std::env::set_var("FKS_TEST_SLACK_TOKEN", "xoxb-123");
+        let mut root: toml::Table = format!(r#"
+            [alerts]
+            webhook_url = "https://hooks.example.com/literal"
+            routes = [{{ channels = ["webhook"], webhook_url = "env:FKS_TEST_SLACK_TOKEN" }}, {{ channels = ["slack"] }}]
+            [alerts.slack]
+            bot_token = "env:FKS_TEST_SLACK_TOKEN"
+            channel = "env:NOT_A_SECRET_FIELD"
+            [alerts.email]
+            password = "file:{}"
+        "#, file.display()).parse().unwrap();
+        resolve_fields(&mut root).unwrap();
+        let alerts = &root["alerts"];
+        assert_eq!(alerts["webhook_url"].as_str(), Some("https://hooks.example.com/literal"));
+        assert_eq!(alerts["routes"][0]["webhook_url"].as_str(), Some("xoxb-123"));
+        assert_eq!(alerts["slack"]["bot_token"].as_str(), Some("xoxb-123"));
+        assert_eq!(alerts["slack"]["channel"].as_str(), Some("env:NOT_A_SECRET_FIELD"), "only secret fields are resolved");
+        assert_eq!(alerts["email"]["password"].as_str(), Some("s3cret"));
+
+        let mut missing: toml::Table = "[prometheus]\nurl = \"http://p\"\nbearer_token = \"env:FKS_TEST_UNSET_TOKEN\"".parse().unwrap();
+        let err = format!("{:#}", resolve_fields(&mut missing).unwrap_err());
+        assert!(err.contains("prometheus.bearer_token") && err.contains("FKS_TEST_UNSET_TOKEN"), "{err}");
+    }
+
+    #[test]
+    fn indexed_paths_match_secret_fields() {
+        assert!(is_secret_path("alerts.routes[3].webhook_url"));
+        assert!(is_secret_path("alerts.slack.webhook_url"));
+        assert!(!is_secret_path("alerts.pagerduty.events_url"));
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use utoipa::IntoParams;
+
+use crate::models::{HealthStatus, ServiceStatus, ServiceType};
+
+/// Filters, order and fields of `GET /api/services`. Every filter given must match.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ServicesQuery {
+    pub tag: Option<String>,
+    pub group: Option<String>,
+    /// Comma-separated statuses in any case, e.g. `unhealthy,degraded`
+    pub status: Option<String>,
+    pub critical: Option<bool>,
+    /// Comma-separated service types in any case, e.g. `Api,Worker`
+    #[serde(rename = "type")]
+    pub service_type: Option<String>,
+    /// Comma-separated fields to order by, `-` first for descending, e.g. `-response_time_ms,id`.
+    /// Services lacking a value come last; `status` orders from healthy to unhealthy.
+    pub sort: Option<String>,
+    /// Comma-separated fields to return, e.g. `id,status` (default: all)
+    pub fields: Option<String>,
+}
+
+/// `services` filtered, ordered (by id unless `sort` says otherwise) and cut down to the fields `q` asks for.
+pub fn apply(services: Vec<ServiceStatus>, q: &ServicesQuery) -> Result<Vec<Value>> {
+    let statuses: Option<Vec<HealthStatus>> = q.status.as_deref().map(|raw| list(raw).map(variant).collect()).transpose()?;
+    let types: Option<Vec<ServiceType>> = q.service_type.as_deref().map(|raw| list(raw).map(variant).collect()).transpose()?;
+    let known = known_fields();
+    let sort: Vec<(&str, bool)> = q.sort.as_deref().map(list).into_iter().flatten()
+        .map(|key| match key.strip_prefix('-') { Some(field) => (field, true), None => (key, false) })
+        .map(|(field, descending)| checked(&known, field).map(|field| (field, descending)))
+        .collect::<Result<_>>()?;
+    let fields: Option<Vec<&str>> = q.fields.as_deref().map(|raw| list(raw).map(|f| checked(&known, f)).collect()).transpose()?;
+
+    let mut services: Vec<(HealthStatus, Value)> = services.into_iter()
+        .filter(|s| q.tag.as_ref().is_none_or(|tag| s.tags.contains(tag)))
+        .filter(|s| q.group.as_ref().is_none_or(|group| s.group.as_ref() == Some(group)))
+        .filter(|s| statuses.as_ref().is_none_or(|wanted| wanted.contains(&s.status)))
+        .filter(|s| q.critical.is_none_or(|critical| s.critical == critical))
+        .filter(|s| types.as_ref().is_none_or(|wanted| wanted.iter().any(|t| std::mem::discriminant(t) == std::mem::discriminant(&s.service_type))))
+        .map(|s| { let status = s.status.clone(); serde_json::to_value(s).map(|v| (status, v)) })
+        .collect::<Result<_, _>>()?;
+
+    services.sort_by(|a, b| a.1["id"].as_str().cmp(&b.1["id"].as_str()));
+    services.sort_by(|(a_status, a), (b_status, b)| {
+        sort.iter().map(|&(field, descending)| match field {
+            "status" => direct(severity(a_status).cmp(&severity(b_status)), descending),
+            _ => compare(a.get(field), b.get(field), descending),
+        }).find(|o| o.is_ne()).unwrap_or(Ordering::Equal)
+    });
+
+    Ok(services.into_iter().map(|(_, service)| match (&fields, service) {
+        (Some(fields), Value::Object(mut all)) => {
+            Value::Object(fields.iter().filter_map(|f| all.remove_entry(*f)).collect::<Map<_, _>>())
+        }
+        (_, service) => service,
+    }).collect())
+}
+
+fn list(raw: &str) -> impl Iterator<Item = &str> {
+    raw.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// The enum variant named `raw` in any case (variants are single capitalized words).
+fn variant<T: DeserializeOwned>(raw: &str) -> Result<T> {
+    let mut name = raw.to_ascii_lowercase();
+    if let Some(first) = name.get_mut(..1) { first.make_ascii_uppercase(); }
+    serde_json::from_value(Value::String(name)).map_err(|_| anyhow!("unknown value `{raw}`"))
+}
+
+/// Fields of a service in the listing, from its schema so they cannot drift.
+fn known_fields() -> Vec<String> {
+    use utoipa::openapi::{RefOr, Schema};
+    match <ServiceStatus as utoipa::PartialSchema>::schema() {
+        RefOr::T(Schema::Object(object)) => object.properties.keys().cloned().collect(),
+        _ => vec![],
+    }
+}
+
+fn checked<'a>(known: &[String], field: &'a str) -> Result<&'a str> {
+    if known.iter().any(|k| k == field) { Ok(field) } else { Err(anyhow!("unknown field `{field}`")) }
+}
+
+fn severity(status: &HealthStatus) -> u8 {
+    match status {
+        HealthStatus::Healthy => 0,
+        HealthStatus::Paused => 1,
+        HealthStatus::Unknown => 2,
+        HealthStatus::Degraded => 3,
+        HealthStatus::Unhealthy => 4,
+    }
+}
+
+fn direct(ordering: Ordering, descending: bool) -> Ordering {
+    if descending { ordering.reverse() } else { ordering }
+}
+
+/// Order of two field values; missing and null values come last either way.
+fn compare(a: Option<&Value>, b: Option<&Value>, descending: bool) -> Ordering {
+    let (a, b) = (a.filter(|v| !v.is_null()), b.filter(|v| !v.is_null()));
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => direct(match (a, b) {
+            (Value::Number(x), Value::Number(y)) => x.as_f64().partial_cmp(&y.as_f64()).unwrap_or(Ordering::Equal),
+            (Value::String(x), Value::String(y)) => x.cmp(y),
+            (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+            _ => Ordering::Equal,
+        }, descending),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn service(id: &str, status: HealthStatus, service_type: ServiceType, critical: bool, response_time_ms: Option<u64>) -> ServiceStatus {
+        ServiceStatus {
+            id: id.into(),
+            name: id.into(),
+            status,
+            last_check: Utc::now(),
+            uptime_seconds: None,
+            response_time_ms,
+            error_message: None,
+            service_type,
+            critical,
+            host: None,
+            silenced: false,
+            tags: vec![],
+            group: None,
+            metadata: Default::default(),
+            restart_backoff: None,
+        }
+    }
+
+    fn fleet() -> Vec<ServiceStatus> {
+        vec![
+            service("web", HealthStatus::Healthy, ServiceType::Web, false, Some(20)),
+            service("db", HealthStatus::Unhealthy, ServiceType::Database, true, None),
+            service("api", HealthStatus::Unhealthy, ServiceType::Api, true, Some(900)),
+            service("auth", HealthStatus::Degraded, ServiceType::Api, true, Some(300)),
+        ]
+    }
+
+    fn ids(listing: &[Value]) -> Vec<&str> {
+        listing.iter().map(|s| s["id"].as_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn filters_sort_and_trim_the_listing() {
+        let q = |raw: &str| axum::extract::Query::<ServicesQuery>::try_from_uri(&format!("/api/services?{raw}").parse().unwrap()).unwrap().0;
+
+        assert_eq!(ids(&apply(fleet(), &q("")).unwrap()), ["api", "auth", "db", "web"], "by id by default");
+        assert_eq!(ids(&apply(fleet(), &q("status=UNHEALTHY,degraded&critical=true&type=api")).unwrap()), ["api", "auth"]);
+        assert_eq!(ids(&apply(fleet(), &q("sort=-response_time_ms")).unwrap()), ["api", "auth", "web", "db"], "missing values last");
+        assert_eq!(ids(&apply(fleet(), &q("sort=response_time_ms")).unwrap()), ["web", "auth", "api", "db"]);
+        assert_eq!(ids(&apply(fleet(), &q("sort=-status,-id")).unwrap()), ["db", "api", "auth", "web"]);
+
+        let trimmed = apply(fleet(), &q("type=Database&fields=id,status")).unwrap();
+        assert_eq!(trimmed, [serde_json::json!({"id": "db", "status": "Unhealthy"})]);
+
+        for bad in ["status=sleepy", "type=Mainframe", "sort=-speed", "fields=id,secret"] {
+            assert!(apply(fleet(), &q(bad)).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use crate::models::ServiceConfig;
+
+/// Labels a silence or maintenance window can match on: `service` (id), `name`, `type`
+/// (lowercase service type), `critical` ("true"/"false"), `container` when set, plus the
+/// service's own `labels` (which cannot shadow the built-in ones).
+pub fn service_labels(service: &ServiceConfig) -> BTreeMap<String, String> {
+    let mut labels = service.labels.clone();
+    labels.insert("service".to_string(), service.id.clone());
+    labels.insert("name".to_string(), service.name.clone());
+    labels.insert("type".to_string(), format!("{:?}", service.service_type).to_lowercase());
+    labels.insert("critical".to_string(), service.critical.to_string());
+    if let Some(container) = &service.docker_container {
+        labels.insert("container".to_string(), container.clone());
+    }
+    labels
+}
+
+fn matches(matchers: &BTreeMap<String, String>, service: &ServiceConfig) -> bool {
+    let labels = service_labels(service);
+    !matchers.is_empty() && matchers.iter().all(|(k, v)| labels.get(k) == Some(v))
+}
+
+/// Ad-hoc silence created through `POST /api/silences`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Silence {
+    pub id: String,
+    pub matchers: BTreeMap<String, String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub created_by: Option<String>,
+}
+
+impl Silence {
+    fn active_at(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SilenceRequest {
+    /// Shorthand for `matchers = { service = "<id>" }`
+    #[serde(default)]
+    pub service_id: Option<String>,
+    #[serde(default)]
+    pub matchers: BTreeMap<String, String>,
+    /// Defaults to now
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Alternative to `ends_at`
+    #[serde(default)]
+    pub duration_minutes: Option<u64>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub created_by: Option<String>,
+}
+
+/// What `silenced_by` reports while global maintenance is on.
+pub const MAINTENANCE: &str = "maintenance";
+
+/// Global maintenance started through `POST /api/maintenance`: every service counts as silenced,
+/// auto-remediation is held and services going down raise no `ServiceDown`. Checks keep running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Maintenance {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub started_by: Option<String>,
+}
+
+impl Maintenance {
+    fn active_at(&self, now: DateTime<Utc>) -> bool {
+        self.since <= now && now < self.until
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MaintenanceRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Alternative to `ends_at`
+    #[serde(default)]
+    pub duration_minutes: Option<u64>,
+    #[serde(default)]
+    pub started_by: Option<String>,
+}
+
+/// Recurring maintenance window from config, in UTC. Empty `days` means every day.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MaintenanceWindow {
+    pub name: String,
+    pub matchers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Start time of day, "HH:MM"
+    #[serde(with = "hh_mm")]
+    #[schemars(with = "String")]
+    pub start: NaiveTime,
+    pub duration_minutes: u64,
+}
+
+impl MaintenanceWindow {
+    /// Whether `now` falls in a window opened today or on an earlier day (windows may span midnight).
+    pub fn active_at(&self, now: DateTime<Utc>) -> bool {
+        let duration = Duration::minutes(self.duration_minutes as i64);
+        let days_back = self.duration_minutes.div_ceil(24 * 60) as i64;
+        (0..=days_back).any(|back| {
+            let day = now.date_naive() - Duration::days(back);
+            let opens = day.and_time(self.start).and_utc();
+            (self.days.is_empty() || self.days.contains(&day.weekday())) && opens <= now && now < opens + duration
+        })
+    }
+}
+
+mod hh_mm {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &NaiveTime, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&time.format("%H:%M").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<NaiveTime, D::Error> {
+        let raw = String::deserialize(d)?;
+        NaiveTime::parse_from_str(&raw, "%H:%M").map_err(|_| serde::de::Error::custom(format!("invalid time `{raw}` (expected HH:MM)")))
+    }
+}
+
+/// Active silences, configured maintenance windows and global maintenance. Silenced services are
+/// still checked; only their notifications are dropped.
+#[derive(Clone, Default)]
+pub struct Silences {
+    silences: Arc<RwLock<Vec<Silence>>>,
+    windows: Arc<RwLock<Vec<MaintenanceWindow>>>,
+    maintenance: Arc<RwLock<Option<Maintenance>>>,
+}
+
+impl Silences {
+    pub fn new(windows: Vec<MaintenanceWindow>) -> Self {
+        Self { windows: Arc::new(RwLock::new(windows)), ..Default::default() }
+    }
+
+    pub fn add(&self, req: SilenceRequest, now: DateTime<Utc>) -> Result<Silence, String> {
+        let mut matchers = req.matchers;
+        if let Some(id) = req.service_id {
+            matchers.insert("service".to_string(), id);
+        }
+        if matchers.is_empty() {
+            return Err("a silence needs `service_id` or at least one matcher".into());
+        }
+        let starts_at = req.starts_at.unwrap_or(now);
+        let ends_at = match (req.ends_at, req.duration_minutes) {
+            (Some(end), None) => end,
+            (None, Some(minutes)) => starts_at + Duration::minutes(minutes as i64),
+            _ => return Err("exactly one of `ends_at` or `duration_minutes` is required".into()),
+        };
+        if ends_at <= starts_at || ends_at <= now {
+            return Err("silence must end in the future and after it starts".into());
+        }
+        let silence = Silence { id: uuid::Uuid::new_v4().to_string(), matchers, starts_at, ends_at, comment: req.comment, created_by: req.created_by };
+        if let Ok(mut list) = self.silences.write() { list.push(silence.clone()); }
+        Ok(silence)
+    }
+
+    pub fn remove(&self, id: &str) -> bool {
+        let Ok(mut list) = self.silences.write() else { return false };
+        let before = list.len();
+        list.retain(|s| s.id != id);
+        list.len() != before
+    }
+
+    /// Current and upcoming silences; expired ones are dropped.
+    pub fn list(&self, now: DateTime<Utc>) -> Vec<Silence> {
+        let Ok(mut list) = self.silences.write() else { return Vec::new() };
+        list.retain(|s| s.ends_at > now);
+        list.clone()
+    }
+
+    /// Re-add silences carried over from a previous process.
+    pub fn restore(&self, silences: Vec<Silence>) {
+        if let Ok(mut list) = self.silences.write() { list.extend(silences); }
+    }
+
+    pub fn windows(&self) -> Vec<MaintenanceWindow> {
+        self.windows.read().map(|w| w.clone()).unwrap_or_default()
+    }
+
+    /// Swap in the maintenance windows of a reloaded config; ad-hoc silences are kept.
+    pub fn set_windows(&self, windows: Vec<MaintenanceWindow>) {
+        if let Ok(mut current) = self.windows.write() { *current = windows; }
+    }
+
+    /// Name of the configured maintenance window covering `service` at `now`; ad-hoc silences do not count.
+    pub fn maintenance_window(&self, service: &ServiceConfig, now: DateTime<Utc>) -> Option<String> {
+        self.windows.read().ok()?.iter().find(|w| w.active_at(now) && matches(&w.matchers, service)).map(|w| w.name.clone())
+    }
+
+    /// Put everything into maintenance (or change the running one). It must end in the future.
+    pub fn start_maintenance(&self, req: MaintenanceRequest, now: DateTime<Utc>) -> Result<Maintenance, String> {
+        let until = match (req.ends_at, req.duration_minutes) {
+            (Some(end), None) => end,
+            (None, Some(minutes)) => now + Duration::minutes(minutes as i64),
+            _ => return Err("exactly one of `ends_at` or `duration_minutes` is required".into()),
+        };
+        if until <= now {
+            return Err("maintenance must end in the future".into());
+        }
+        let maintenance = Maintenance { since: now, until, reason: req.reason.filter(|r| !r.trim().is_empty()), started_by: req.started_by };
+        if let Ok(mut current) = self.maintenance.write() { *current = Some(maintenance.clone()); }
+        Ok(maintenance)
+    }
+
+    /// End maintenance early. Returns the one that was running, if any.
+    pub fn end_maintenance(&self, now: DateTime<Utc>) -> Option<Maintenance> {
+        let previous = self.maintenance.write().ok()?.take();
+        previous.filter(|m| m.active_at(now))
+    }
+
+    /// The global maintenance in effect at `now`; an expired one counts as ended.
+    pub fn maintenance(&self, now: DateTime<Utc>) -> Option<Maintenance> {
+        self.maintenance.read().ok()?.clone().filter(|m| m.active_at(now))
+    }
+
+    /// Carry global maintenance over from a previous process.
+    pub fn restore_maintenance(&self, maintenance: Option<Maintenance>) {
+        if let (Some(maintenance), Ok(mut current)) = (maintenance, self.maintenance.write()) {
+            *current = Some(maintenance);
+        }
+    }
+
+    /// Id of the silence or name of the maintenance window covering `service` at `now`, or
+    /// [`MAINTENANCE`] while global maintenance is on.
+    pub fn silenced_by(&self, service: &ServiceConfig, now: DateTime<Utc>) -> Option<String> {
+        if self.maintenance(now).is_some() {
+            return Some(MAINTENANCE.to_string());
+        }
+        if let Some(silence) = self.silences.read().ok()?.iter().find(|s| s.active_at(now) && matches(&s.matchers, service)) {
+            return Some(silence.id.clone());
+        }
+        self.maintenance_window(service, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProbeMode, ServiceType};
+    use chrono::TimeZone;
+
+    fn service(id: &str, critical: bool) -> ServiceConfig {
+        ServiceConfig {
+            id: id.into(),
+            name: id.into(),
+            health_endpoint: String::new(),
+            service_type: ServiceType::Database,
+            docker_container: None,
+            expected_response_time_ms: 100,
+            critical,
+            check_interval_seconds: None,
+            heartbeat_window_seconds: None,
+            depends_on: vec![],
+            tags: vec![],
+            probes: vec![],
+            probe_mode: ProbeMode::All,
+            labels: Default::default(),
+            group: None,
+            metadata: Default::default(),
+            auto_update: false,
+            kubernetes: None,
+            systemd_unit: None,
+            auto_restart: None,
+            drain: None,
+            metrics: None,
+            docker_host: None,
+        }
+    }
+
+    #[test]
+    fn silences_match_by_service_or_labels_until_they_expire() {
+        let silences = Silences::new(vec![]);
+        let now = Utc::now();
+        let by_id = silences.add(SilenceRequest { service_id: Some("db".into()), duration_minutes: Some(30), ..Default::default() }, now).unwrap();
+        assert_eq!(silences.silenced_by(&service("db", false), now), Some(by_id.id.clone()));
+        assert!(silences.silenced_by(&service("api", false), now).is_none());
+
+        let matchers = BTreeMap::from([("type".to_string(), "database".to_string()), ("critical".to_string(), "true".to_string())]);
+        silences.add(SilenceRequest { matchers, duration_minutes: Some(10), ..Default::default() }, now).unwrap();
+        assert!(silences.silenced_by(&service("replica", true), now).is_some());
+        assert!(silences.silenced_by(&service("replica", false), now).is_none());
+
+        let later = now + Duration::minutes(31);
+        assert!(silences.silenced_by(&service("db", false), later).is_none());
+        assert!(silences.list(later).is_empty());
+        assert!(!silences.remove(&by_id.id));
+    }
+
+    #[test]
+    fn rejects_open_ended_or_selectorless_silences() {
+        let silences = Silences::new(vec![]);
+        let now = Utc::now();
+        assert!(silences.add(SilenceRequest { duration_minutes: Some(5), ..Default::default() }, now).is_err());
+        assert!(silences.add(SilenceRequest { service_id: Some("db".into()), ..Default::default() }, now).is_err());
+        assert!(silences.add(SilenceRequest { service_id: Some("db".into()), ends_at: Some(now - Duration::minutes(1)), ..Default::default() }, now).is_err());
+    }
+
+    #[test]
+    fn maintenance_windows_recur_and_span_midnight() {
+        let window: MaintenanceWindow = toml::from_str(
+            r#"
+            name = "weekend-backup"
+            matchers = { service = "db" }
+            days = ["Sat"]
+            start = "23:30"
+            duration_minutes = 60
+            "#,
+        ).unwrap();
+        // 2025-08-30 is a Saturday
+        let at = |d, h, m| Utc.with_ymd_and_hms(2025, 8, d, h, m, 0).unwrap();
+        assert!(window.active_at(at(30, 23, 45)));
+        assert!(window.active_at(at(31, 0, 15)), "window opened Saturday runs into Sunday");
+        assert!(!window.active_at(at(31, 0, 30)));
+        assert!(!window.active_at(at(31, 23, 45)), "not on Sundays");
+        assert!(!window.active_at(at(30, 23, 0)));
+
+        let silences = Silences::new(vec![window]);
+        assert_eq!(silences.silenced_by(&service("db", false), at(30, 23, 45)).as_deref(), Some("weekend-backup"));
+        silences.add(SilenceRequest { service_id: Some("db".into()), duration_minutes: Some(30), ..Default::default() }, at(30, 12, 0)).unwrap();
+        assert!(silences.maintenance_window(&service("db", false), at(30, 12, 5)).is_none(), "a silence is not a maintenance window");
+        assert_eq!(silences.maintenance_window(&service("db", false), at(30, 23, 45)).as_deref(), Some("weekend-backup"));
+    }
+
+    #[test]
+    fn global_maintenance_silences_everything_until_it_ends() {
+        let silences = Silences::new(vec![]);
+        let now = Utc::now();
+        assert!(silences.start_maintenance(MaintenanceRequest::default(), now).is_err(), "maintenance needs an end");
+        assert!(silences.start_maintenance(MaintenanceRequest { ends_at: Some(now), ..Default::default() }, now).is_err());
+
+        let started = silences.start_maintenance(MaintenanceRequest { reason: Some("network swap".into()), duration_minutes: Some(30), ..Default::default() }, now).unwrap();
+        assert_eq!(started.until, now + Duration::minutes(30));
+        assert_eq!(silences.silenced_by(&service("db", true), now).as_deref(), Some(MAINTENANCE));
+        assert!(silences.maintenance(now + Duration::minutes(30)).is_none(), "expired maintenance ends itself");
+
+        assert_eq!(silences.end_maintenance(now), Some(started));
+        assert!(silences.silenced_by(&service("db", true), now).is_none());
+        assert!(silences.end_maintenance(now).is_none());
+    }
+}
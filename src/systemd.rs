@@ -0,0 +1,136 @@
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use zbus::zvariant::OwnedObjectPath;
+
+/// Unit properties read from the system manager over D-Bus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitState {
+    /// `loaded`, or `not-found` for unknown units
+    pub load: String,
+    /// `active`, `activating`, `deactivating`, `inactive` or `failed`
+    pub active: String,
+    /// e.g. `running`, `dead`, `auto-restart`
+    pub sub: String,
+    /// Why the unit last stopped, e.g. `exit-code`, `signal`, `oom-kill`; empty for units other than services
+    pub result: String,
+}
+
+impl UnitState {
+    /// Passing (with a note while the unit is starting or reloading) or the reason it is down.
+    pub fn health(&self, unit: &str) -> Result<Option<String>, String> {
+        if self.load == "not-found" {
+            return Err(format!("unit {unit} not found"));
+        }
+        match self.active.as_str() {
+            "active" if self.sub == "reloading" => Ok(Some("reloading".into())),
+            "active" => Ok(None),
+            "activating" | "reloading" => Ok(Some(format!("{} ({})", self.active, self.sub))),
+            "failed" if !self.result.is_empty() && self.result != "success" => Err(format!("unit {unit} failed ({})", self.result)),
+            other => Err(format!("unit {unit} {other} ({})", self.sub)),
+        }
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.systemd1.Manager",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1"
+)]
+trait Manager {
+    fn get_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+    fn load_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+    fn restart_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+    fn subscribe(&self) -> zbus::Result<()>;
+    #[zbus(signal)]
+    fn job_removed(&self, id: u32, job: zbus::zvariant::ObjectPath<'_>, unit: &str, result: &str) -> zbus::Result<()>;
+}
+
+#[zbus::proxy(interface = "org.freedesktop.systemd1.Unit", default_service = "org.freedesktop.systemd1")]
+trait Unit {
+    #[zbus(property)]
+    fn load_state(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn active_state(&self) -> zbus::Result<String>;
+    #[zbus(property)]
+    fn sub_state(&self) -> zbus::Result<String>;
+}
+
+#[zbus::proxy(interface = "org.freedesktop.systemd1.Service", default_service = "org.freedesktop.systemd1")]
+trait Service {
+    #[zbus(property)]
+    fn result(&self) -> zbus::Result<String>;
+}
+
+/// System bus connection, opened on first use and shared by probes and restarts.
+static BUS: OnceCell<zbus::Connection> = OnceCell::const_new();
+
+async fn manager() -> Result<ManagerProxy<'static>> {
+    let bus = BUS.get_or_try_init(zbus::Connection::system).await.context("failed to connect to the system D-Bus")?;
+    Ok(ManagerProxy::new(bus).await?)
+}
+
+/// Current state of `unit` on the host's system manager.
+pub async fn unit_state(unit: &str, timeout: Duration) -> Result<UnitState> {
+    tokio::time::timeout(timeout, read_unit_state(unit)).await
+        .with_context(|| format!("reading the state of {unit} timed out after {}s", timeout.as_secs()))?
+}
+
+async fn read_unit_state(unit: &str) -> Result<UnitState> {
+    let manager = manager().await?;
+    let path = match manager.get_unit(unit).await {
+        Ok(path) => path,
+        // Not loaded right now, e.g. a stopped unit systemd has let go of: loading it tells
+        // whether it exists at all
+        Err(zbus::Error::MethodError(name, ..)) if name.as_str() == "org.freedesktop.systemd1.NoSuchUnit" => manager.load_unit(unit).await?,
+        Err(e) => return Err(e).with_context(|| format!("GetUnit {unit}")),
+    };
+    let proxy = UnitProxy::builder(manager.inner().connection()).path(path.clone())?.build().await?;
+    let service = ServiceProxy::builder(manager.inner().connection()).path(path)?.build().await?;
+    Ok(UnitState {
+        load: proxy.load_state().await?,
+        active: proxy.active_state().await?,
+        sub: proxy.sub_state().await?,
+        result: service.result().await.unwrap_or_default(),
+    })
+}
+
+/// Restart `unit` and wait for the job to finish, at most `timeout`.
+pub async fn restart_unit(unit: &str, timeout: Duration) -> Result<()> {
+    tokio::time::timeout(timeout, restart(unit)).await
+        .with_context(|| format!("restarting {unit} timed out after {}s", timeout.as_secs()))?
+}
+
+async fn restart(unit: &str) -> Result<()> {
+    let manager = manager().await?;
+    // Without a subscription systemd may not send job signals to this client
+    manager.subscribe().await.ok();
+    let mut removed = manager.receive_job_removed().await?;
+    let job = manager.restart_unit(unit, "replace").await.with_context(|| format!("RestartUnit {unit}"))?;
+    while let Some(signal) = removed.next().await {
+        let args = signal.args()?;
+        if args.job.as_str() != job.as_str() { continue; }
+        return match args.result {
+            "done" => Ok(()),
+            result => bail!("restart of {unit} ended with `{result}`"),
+        };
+    }
+    bail!("the system bus closed before the restart of {unit} finished")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_states_map_to_health() {
+        let state = |active: &str, sub: &str, result: &str| UnitState { load: "loaded".into(), active: active.into(), sub: sub.into(), result: result.into() };
+        assert_eq!(state("active", "running", "success").health("nginx.service"), Ok(None));
+        assert_eq!(state("activating", "start-pre", "success").health("nginx.service"), Ok(Some("activating (start-pre)".into())));
+        assert_eq!(state("failed", "failed", "oom-kill").health("postgresql.service"), Err("unit postgresql.service failed (oom-kill)".into()));
+        assert_eq!(state("inactive", "dead", "success").health("nginx.service"), Err("unit nginx.service inactive (dead)".into()));
+        let missing = UnitState { load: "not-found".into(), ..state("inactive", "dead", "success") };
+        assert_eq!(missing.health("ngnix.service"), Err("unit ngnix.service not found".into()));
+    }
+}
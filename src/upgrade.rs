@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+#[cfg(unix)]
+use std::path::Path;
+use tracing::info;
+
+/// Listening socket inherited from the previous process during an in-place upgrade.
+pub const LISTEN_FD_ENV: &str = "FKS_LISTEN_FD";
+/// JSON monitor snapshot (service states, event history, heartbeats) written by the previous process.
+pub const STATE_SNAPSHOT_ENV: &str = "FKS_STATE_SNAPSHOT";
+
+/// Take over a listening socket handed down by a previous fks_master (`FKS_LISTEN_FD`) or by
+/// systemd socket activation (`LISTEN_PID`/`LISTEN_FDS`, first fd is 3).
+#[cfg(unix)]
+pub fn inherited_listener() -> Result<Option<std::net::TcpListener>> {
+    use std::os::fd::FromRawFd;
+
+    let fd = if let Ok(raw) = std::env::var(LISTEN_FD_ENV) {
+        std::env::remove_var(LISTEN_FD_ENV);
+        Some(raw.parse::<i32>().with_context(|| format!("invalid {LISTEN_FD_ENV}={raw}"))?)
+    } else {
+        let for_us = std::env::var("LISTEN_PID").ok().and_then(|p| p.parse::<u32>().ok()) == Some(std::process::id());
+        let count = std::env::var("LISTEN_FDS").ok().and_then(|n| n.parse::<i32>().ok()).unwrap_or(0);
+        (for_us && count >= 1).then_some(3)
+    };
+    let Some(fd) = fd else { return Ok(None) };
+    // SAFETY: the fd was handed to this process for exclusive use as a listening socket.
+    let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+    listener.set_nonblocking(true).context("inherited listener is not a usable socket")?;
+    info!(fd, "♻️ Using inherited listening socket");
+    Ok(Some(listener))
+}
+
+#[cfg(not(unix))]
+pub fn inherited_listener() -> Result<Option<std::net::TcpListener>> {
+    Ok(None)
+}
+
+/// Start a new copy of the current binary (same arguments) that inherits the listening socket and
+/// restores `snapshot`. Returns the child's pid.
+#[cfg(unix)]
+pub fn spawn_successor(listen_fd: std::os::fd::RawFd, snapshot: &Path) -> Result<u32> {
+    // Let the fd survive exec in the child
+    // SAFETY: fcntl on an fd we own; only the FD_CLOEXEC flag is changed.
+    let flags = unsafe { libc::fcntl(listen_fd, libc::F_GETFD) };
+    if flags < 0 || unsafe { libc::fcntl(listen_fd, libc::F_SETFD, flags & !libc::FD_CLOEXEC) } < 0 {
+        anyhow::bail!("failed to clear FD_CLOEXEC on listener: {}", std::io::Error::last_os_error());
+    }
+    let exe = std::env::current_exe().context("resolving current executable")?;
+    let child = std::process::Command::new(&exe)
+        .args(std::env::args_os().skip(1))
+        .env(LISTEN_FD_ENV, listen_fd.to_string())
+        .env(STATE_SNAPSHOT_ENV, snapshot)
+        .spawn()
+        .with_context(|| format!("spawning {}", exe.display()))?;
+    Ok(child.id())
+}
+
+/// Resolves when an upgrade is requested (SIGUSR2). Never resolves where unsupported.
+pub async fn upgrade_requested() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::user_defined2()) {
+            Ok(mut usr2) => { usr2.recv().await; return; }
+            Err(e) => tracing::warn!(error=?e, "failed to install SIGUSR2 handler; in-place upgrades disabled"),
+        }
+    }
+    std::future::pending::<()>().await
+}
+
+pub fn snapshot_path() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("fks_master-{}.snapshot.json", std::process::id()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::fd::IntoRawFd;
+
+    #[test]
+    fn adopts_listener_named_by_env() {
+        let original = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = original.local_addr().unwrap();
+        std::env::set_var(LISTEN_FD_ENV, original.into_raw_fd().to_string());
+        let listener = inherited_listener().unwrap().expect("listener from env");
+        assert_eq!(listener.local_addr().unwrap(), addr);
+        assert!(std::env::var(LISTEN_FD_ENV).is_err(), "env var is consumed so children do not inherit it");
+        assert!(inherited_listener().unwrap().is_none());
+    }
+}
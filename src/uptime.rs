@@ -0,0 +1,314 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+use crate::models::HealthStatus;
+
+/// Transitions are kept this long, enough for the 30-day availability
+const RETENTION: Duration = Duration::days(30);
+/// Timeline shown when `since` is not given
+const DEFAULT_TIMELINE: Duration = Duration::hours(24);
+/// Period of an uptime report when `period` is not given
+const DEFAULT_REPORT_PERIOD: Duration = Duration::days(7);
+
+/// A service entering `status` at `at`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub status: HealthStatus,
+    pub at: DateTime<Utc>,
+}
+
+/// A stretch of time a service spent in one status.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Segment {
+    pub status: HealthStatus,
+    pub start: DateTime<Utc>,
+    /// Absent while the service is still in this status
+    pub end: Option<DateTime<Utc>>,
+    pub duration_seconds: i64,
+}
+
+/// Percent of the observed time a service was up (Healthy or Degraded). Time in `Unknown` or
+/// `Paused`, or before the service was first checked, is not counted; `None` means nothing was observed.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Availability {
+    pub last_24h: Option<f64>,
+    pub last_7d: Option<f64>,
+    pub last_30d: Option<f64>,
+}
+
+/// Served by `/api/services/{id}/history`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ServiceHistory {
+    pub service_id: String,
+    /// Oldest first, clipped to `since`
+    pub segments: Vec<Segment>,
+    pub availability: Availability,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UptimeQuery {
+    /// Start of the timeline (default 24 hours ago, at most 30 days back)
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Parameters of `GET /api/reports/uptime`.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportQuery {
+    /// How far back the report goes, e.g. `24h` or `7d` (default 7d, at most 30d)
+    pub period: Option<String>,
+    /// `service` (default) or `group`
+    pub group_by: Option<GroupBy>,
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    Service,
+    Group,
+}
+
+impl ReportQuery {
+    /// The requested period, checked against the retention.
+    pub fn period(&self) -> anyhow::Result<Duration> {
+        let Some(raw) = self.period.as_deref() else { return Ok(DEFAULT_REPORT_PERIOD) };
+        let period = crate::promql::parse_duration(raw)
+            .and_then(|d| Duration::from_std(d).ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid period `{raw}`, expected e.g. `24h` or `7d`"))?;
+        anyhow::ensure!(period <= RETENTION, "period `{raw}` is longer than the 30 days of history kept");
+        Ok(period)
+    }
+}
+
+/// Availability and outages of a service, or of all services in a group, over a report's period.
+/// An outage is a stretch of `Unhealthy`.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ReportRow {
+    /// Service id, or group name with `group_by=group`
+    pub key: String,
+    /// Percent, as in [`Availability`]
+    pub availability: Option<f64>,
+    /// Outages overlapping the period, including one still going on
+    pub outages: usize,
+    /// Mean time to recovery of the outages that ended in the period
+    pub mttr_seconds: Option<i64>,
+    pub longest_outage_seconds: Option<i64>,
+    pub downtime_seconds: i64,
+}
+
+impl ReportRow {
+    const CSV_HEADER: &'static str = "key,availability,outages,mttr_seconds,longest_outage_seconds,downtime_seconds";
+
+    fn csv(&self) -> String {
+        let opt = |v: Option<String>| v.unwrap_or_default();
+        let key = if self.key.contains([',', '"', '\n']) { format!("\"{}\"", self.key.replace('"', "\"\"")) } else { self.key.clone() };
+        format!("{key},{},{},{},{},{}",
+            opt(self.availability.map(|a| format!("{a:.3}"))), self.outages,
+            opt(self.mttr_seconds.map(|s| s.to_string())), opt(self.longest_outage_seconds.map(|s| s.to_string())), self.downtime_seconds)
+    }
+}
+
+/// Served by `/api/reports/uptime`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UptimeReport {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub group_by: GroupBy,
+    /// Ordered by key
+    pub rows: Vec<ReportRow>,
+}
+
+impl UptimeReport {
+    pub fn to_csv(&self) -> String {
+        std::iter::once(ReportRow::CSV_HEADER.to_string())
+            .chain(self.rows.iter().map(ReportRow::csv))
+            .map(|line| line + "\n")
+            .collect()
+    }
+}
+
+/// Observed and up time and the outages of one or more services.
+#[derive(Default)]
+struct Tally {
+    up: i64,
+    observed: i64,
+    /// Duration of each outage, and whether it ended
+    outages: Vec<(i64, bool)>,
+}
+
+impl Tally {
+    fn add(&mut self, transitions: &[Transition], since: DateTime<Utc>, now: DateTime<Utc>) {
+        for segment in segments(transitions, since, now) {
+            match segment.status {
+                HealthStatus::Healthy | HealthStatus::Degraded => { self.up += segment.duration_seconds; self.observed += segment.duration_seconds; }
+                HealthStatus::Unhealthy => {
+                    self.observed += segment.duration_seconds;
+                    self.outages.push((segment.duration_seconds, segment.end.is_some()));
+                }
+                HealthStatus::Unknown | HealthStatus::Paused => {}
+            }
+        }
+    }
+
+    fn row(self, key: String) -> ReportRow {
+        let resolved: Vec<i64> = self.outages.iter().filter(|(_, ended)| *ended).map(|(d, _)| *d).collect();
+        ReportRow {
+            key,
+            availability: (self.observed > 0).then(|| self.up as f64 * 100.0 / self.observed as f64),
+            outages: self.outages.len(),
+            mttr_seconds: (!resolved.is_empty()).then(|| resolved.iter().sum::<i64>() / resolved.len() as i64),
+            longest_outage_seconds: self.outages.iter().map(|(d, _)| *d).max(),
+            downtime_seconds: self.observed - self.up,
+        }
+    }
+}
+
+/// Status transitions per service for the last 30 days, optionally persisted as a JSON object
+/// of service id -> transitions.
+#[derive(Debug, Clone, Default)]
+pub struct UptimeHistory {
+    path: Option<PathBuf>,
+    services: Arc<Mutex<HashMap<String, Vec<Transition>>>>,
+}
+
+impl UptimeHistory {
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let services = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|raw| match serde_json::from_str(&raw) {
+                Ok(v) => Some(v),
+                Err(e) => { warn!(error=%e, "ignoring unreadable uptime history file"); None }
+            })
+            .unwrap_or_default();
+        Self { path, services: Arc::new(Mutex::new(services)) }
+    }
+
+    /// Note that `service_id` is in `status` at `at`; only changes are kept.
+    pub fn record(&self, service_id: &str, status: &HealthStatus, at: DateTime<Utc>) {
+        let mut services = self.services.lock().unwrap();
+        let transitions = services.entry(service_id.to_string()).or_default();
+        if transitions.last().is_some_and(|t| t.status == *status) { return; }
+        transitions.push(Transition { status: status.clone(), at });
+        // The last transition before the cutoff stays: it says what the status was at the cutoff
+        let expired = transitions.iter().take_while(|t| t.at <= at - RETENTION).count();
+        transitions.drain(..expired.saturating_sub(1));
+        self.persist(&services);
+    }
+
+    /// Timeline and availability of `service_id` at `now`.
+    pub fn history(&self, service_id: &str, q: &UptimeQuery, now: DateTime<Utc>) -> ServiceHistory {
+        let services = self.services.lock().unwrap();
+        let transitions = services.get(service_id).map(Vec::as_slice).unwrap_or_default();
+        let since = q.since.unwrap_or(now - DEFAULT_TIMELINE).max(now - RETENTION);
+        ServiceHistory {
+            service_id: service_id.to_string(),
+            segments: segments(transitions, since, now),
+            availability: Availability {
+                last_24h: availability(transitions, now - Duration::hours(24), now),
+                last_7d: availability(transitions, now - Duration::days(7), now),
+                last_30d: availability(transitions, now - RETENTION, now),
+            },
+        }
+    }
+
+    /// Report over `period` up to `now`, with a row per distinct key of `services` (service id -> key).
+    pub fn report<'a>(&self, services: impl IntoIterator<Item = (&'a str, &'a str)>, period: Duration, group_by: GroupBy, now: DateTime<Utc>) -> UptimeReport {
+        let history = self.services.lock().unwrap();
+        let since = now - period;
+        let mut tallies: BTreeMap<&str, Tally> = BTreeMap::new();
+        for (service_id, key) in services {
+            let transitions = history.get(service_id).map(Vec::as_slice).unwrap_or_default();
+            tallies.entry(key).or_default().add(transitions, since, now);
+        }
+        UptimeReport { since, until: now, group_by, rows: tallies.into_iter().map(|(key, tally)| tally.row(key.to_string())).collect() }
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, Vec<Transition>> {
+        self.services.lock().unwrap().clone()
+    }
+
+    pub fn restore(&self, services: HashMap<String, Vec<Transition>>) {
+        self.services.lock().unwrap().extend(services);
+    }
+
+    fn persist(&self, services: &HashMap<String, Vec<Transition>>) {
+        let Some(path) = &self.path else { return };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match serde_json::to_vec(services) {
+            Ok(bytes) => if let Err(e) = std::fs::write(path, bytes) { warn!(error=%e, path=%path.display(), "failed to persist uptime history") },
+            Err(e) => warn!(error=%e, "failed to serialize uptime history"),
+        }
+    }
+}
+
+/// `transitions` as segments overlapping `[since, now]`, clipped to it.
+fn segments(transitions: &[Transition], since: DateTime<Utc>, now: DateTime<Utc>) -> Vec<Segment> {
+    transitions.iter().enumerate().filter_map(|(i, t)| {
+        let end = transitions.get(i + 1).map(|next| next.at);
+        if end.is_some_and(|end| end <= since) { return None; }
+        let start = t.at.max(since);
+        Some(Segment { status: t.status.clone(), start, end, duration_seconds: (end.unwrap_or(now) - start).num_seconds() })
+    }).collect()
+}
+
+fn availability(transitions: &[Transition], since: DateTime<Utc>, now: DateTime<Utc>) -> Option<f64> {
+    let (mut up, mut observed) = (0, 0);
+    for segment in segments(transitions, since, now) {
+        match segment.status {
+            HealthStatus::Healthy | HealthStatus::Degraded => { up += segment.duration_seconds; observed += segment.duration_seconds; }
+            HealthStatus::Unhealthy => observed += segment.duration_seconds,
+            HealthStatus::Unknown | HealthStatus::Paused => {}
+        }
+    }
+    (observed > 0).then(|| up as f64 * 100.0 / observed as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_and_availability_follow_status_changes() {
+        let history = UptimeHistory::default();
+        let now = Utc::now();
+        let ago = |hours: i64| now - Duration::hours(hours);
+
+        history.record("api", &HealthStatus::Unknown, ago(48));
+        history.record("api", &HealthStatus::Healthy, ago(47));
+        history.record("api", &HealthStatus::Healthy, ago(30));
+        history.record("api", &HealthStatus::Unhealthy, ago(6));
+        history.record("api", &HealthStatus::Degraded, ago(3));
+
+        let h = history.history("api", &UptimeQuery::default(), now);
+        let statuses: Vec<_> = h.segments.iter().map(|s| (s.status.clone(), s.duration_seconds / 3600, s.end.is_some())).collect();
+        assert_eq!(statuses, [(HealthStatus::Healthy, 18, true), (HealthStatus::Unhealthy, 3, true), (HealthStatus::Degraded, 3, false)]);
+        assert_eq!(h.segments[0].start, ago(24), "clipped to the timeline");
+        assert_eq!(h.availability.last_24h, Some(87.5));
+        assert!((h.availability.last_7d.unwrap() - 100.0 * 44.0 / 47.0).abs() < 1e-9, "time in Unknown is not counted");
+
+        let report = history.report([("api", "trading"), ("db", "trading")], Duration::days(7), GroupBy::Group, now);
+        let row = &report.rows[0];
+        assert_eq!((row.key.as_str(), row.outages, row.mttr_seconds, row.downtime_seconds), ("trading", 1, Some(3 * 3600), 3 * 3600));
+        assert!(report.to_csv().starts_with("key,availability,outages,mttr_seconds,longest_outage_seconds,downtime_seconds\ntrading,93.617,1,10800,10800,10800\n"));
+
+        let h = history.history("api", &UptimeQuery { since: Some(ago(100)) }, now);
+        assert_eq!(h.segments.len(), 4);
+        assert_eq!(history.history("db", &UptimeQuery::default(), now).availability.last_30d, None);
+
+        // Transitions older than the retention go, except the one still in effect at the cutoff
+        history.record("api", &HealthStatus::Healthy, now + RETENTION);
+        history.record("api", &HealthStatus::Unhealthy, now + RETENTION + Duration::hours(1));
+        assert_eq!(history.snapshot()["api"].len(), 3);
+    }
+}
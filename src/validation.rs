@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::models::ServiceConfig;
+use crate::outbound::{self, Destination};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// Answered with a 2xx status
+    Reachable,
+    /// Answered, but with a non-2xx status (often a wrong path)
+    HttpError,
+    /// No answer: bad URL, DNS failure, connection refused or timeout
+    Unreachable,
+    /// Heartbeat services have no endpoint to probe
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointResult {
+    pub service_id: String,
+    pub name: String,
+    pub endpoint: String,
+    pub outcome: Outcome,
+    pub status_code: Option<u16>,
+    pub response_time_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub checked_at: DateTime<Utc>,
+    pub reachable: usize,
+    /// Endpoints that did not answer with a 2xx status
+    pub unreachable: usize,
+    pub results: Vec<EndpointResult>,
+}
+
+/// Probe every configured endpoint once (no retries), concurrently.
+pub async fn validate_endpoints(services: &[ServiceConfig], timeout: Duration) -> ValidationReport {
+    let client = reqwest::Client::builder().timeout(timeout).build().expect("Failed to create HTTP client");
+    let results: Vec<EndpointResult> = join_all(services.iter().map(|s| probe(&client, s))).await;
+    ValidationReport {
+        checked_at: Utc::now(),
+        reachable: results.iter().filter(|r| r.outcome == Outcome::Reachable).count(),
+        unreachable: results.iter().filter(|r| matches!(r.outcome, Outcome::HttpError | Outcome::Unreachable)).count(),
+        results,
+    }
+}
+
+async fn probe(client: &reqwest::Client, service: &ServiceConfig) -> EndpointResult {
+    let mut result = EndpointResult {
+        service_id: service.id.clone(),
+        name: service.name.clone(),
+        endpoint: service.health_endpoint.clone(),
+        outcome: Outcome::Skipped,
+        status_code: None,
+        response_time_ms: None,
+        error: None,
+    };
+    if service.heartbeat_window_seconds.is_some() {
+        return result;
+    }
+    let started = Instant::now();
+    match outbound::send(Destination::Validation, client.get(&service.health_endpoint)).await {
+        Ok(response) => {
+            let status = response.status();
+            result.response_time_ms = Some(started.elapsed().as_millis() as u64);
+            result.status_code = Some(status.as_u16());
+            if status.is_success() {
+                result.outcome = Outcome::Reachable;
+            } else {
+                result.outcome = Outcome::HttpError;
+                result.error = Some(format!("HTTP {}: {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown")));
+            }
+        }
+        Err(err) => {
+            result.outcome = Outcome::Unreachable;
+            result.error = Some(format!("{err:#}"));
+        }
+    }
+    result
+}
+
+/// Log one line per endpoint plus a summary; problems are logged as warnings.
+pub fn log_report(report: &ValidationReport) {
+    for r in &report.results {
+        match r.outcome {
+            Outcome::Reachable => info!(service = %r.service_id, endpoint = %r.endpoint, response_time_ms = r.response_time_ms, "✅ endpoint reachable"),
+            Outcome::Skipped => info!(service = %r.service_id, "⏭️ heartbeat service, nothing to probe"),
+            Outcome::HttpError | Outcome::Unreachable => warn!(
+                service = %r.service_id,
+                endpoint = %r.endpoint,
+                error = r.error.as_deref().unwrap_or_default(),
+                "❌ endpoint unreachable"
+            ),
+        }
+    }
+    info!(reachable = report.reachable, unreachable = report.unreachable, "🔎 Endpoint validation finished");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProbeMode, ServiceType};
+    use axum::{http::StatusCode, routing::get, Router};
+
+    fn service(id: &str, endpoint: String, heartbeat: bool) -> ServiceConfig {
+        ServiceConfig {
+            id: id.into(),
+            name: id.into(),
+            health_endpoint: endpoint,
+            service_type: ServiceType::Api,
+            docker_container: None,
+            expected_response_time_ms: 100,
+            critical: false,
+            check_interval_seconds: None,
+            heartbeat_window_seconds: heartbeat.then_some(60),
+            depends_on: vec![],
+            tags: vec![],
+            probes: vec![],
+            probe_mode: ProbeMode::All,
+            labels: Default::default(),
+            group: None,
+            metadata: Default::default(),
+            auto_update: false,
+            kubernetes: None,
+            systemd_unit: None,
+            auto_restart: None,
+            drain: None,
+            metrics: None,
+            docker_host: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn classifies_each_endpoint() {
+        let app = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/broken", get(|| async { StatusCode::SERVICE_UNAVAILABLE }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap(); });
+        // Reserve a port, then close it so nothing answers there
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+        let services = vec![
+            service("ok", format!("http://{addr}/health"), false),
+            service("wrong_path", format!("http://{addr}/missing"), false),
+            service("down", format!("http://{addr}/broken"), false),
+            service("refused", format!("http://{closed}/health"), false),
+            service("bad_url", "not a url".into(), false),
+            service("cron", String::new(), true),
+        ];
+        let report = validate_endpoints(&services, Duration::from_secs(2)).await;
+        let outcomes: Vec<Outcome> = report.results.iter().map(|r| r.outcome).collect();
+        assert_eq!(outcomes, vec![Outcome::Reachable, Outcome::HttpError, Outcome::HttpError, Outcome::Unreachable, Outcome::Unreachable, Outcome::Skipped]);
+        assert_eq!((report.reachable, report.unreachable), (1, 4));
+        assert_eq!(report.results[1].status_code, Some(404));
+        assert!(report.results[3].error.is_some());
+    }
+}
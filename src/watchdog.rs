@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::clock::Ticker;
+use crate::config::WatchdogConfig;
+use crate::metrics;
+use crate::monitor::MonitorHandle;
+
+pub const MONITORING_LOOP: &str = "monitoring_loop";
+pub const METRICS_LOOP: &str = "metrics_loop";
+pub const EVENT_BUS: &str = "event_bus";
+
+#[derive(Debug, Clone)]
+struct Component {
+    last_progress: DateTime<Utc>,
+    stall_after_seconds: i64,
+    started: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    components: BTreeMap<&'static str, Component>,
+    last_backlog: usize,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ComponentReport {
+    pub name: &'static str,
+    pub last_progress: DateTime<Utc>,
+    pub stall_after_seconds: i64,
+    pub stalled: bool,
+    /// Progressed at least once since it was first registered
+    pub started: bool,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct WatchdogReport {
+    pub ready: bool,
+    pub components: Vec<ComponentReport>,
+}
+
+/// Tracks progress of the monitor's internal loops. A component that stops reporting progress for
+/// longer than its threshold is stalled, which makes `/health/ready` fail.
+#[derive(Clone)]
+pub struct Watchdog {
+    config: WatchdogConfig,
+    state: Arc<Mutex<State>>,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self { config, state: Arc::default() }
+    }
+
+    /// Start tracking `name`, which is expected to progress at least every `interval`.
+    pub fn register(&self, name: &'static str, interval: Duration, now: DateTime<Utc>) {
+        if !self.config.enabled { return; }
+        let stall_after_seconds = self.config.stall_threshold_seconds.max(interval.as_secs() * 3) as i64;
+        if let Ok(mut state) = self.state.lock() {
+            // Re-registered after a reload: a component that already ran is not starting over
+            let started = state.components.get(name).is_some_and(|c| c.started);
+            state.components.insert(name, Component { last_progress: now, stall_after_seconds, started });
+        }
+    }
+
+    pub fn beat(&self, name: &'static str, now: DateTime<Utc>) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(c) = state.components.get_mut(name) { c.last_progress = now; c.started = true; }
+        }
+    }
+
+    /// The event bus progresses while its backlog (events not yet received by every subscriber)
+    /// is empty or shrinking; a backlog stuck at the same size means a consumer is wedged.
+    pub fn observe_event_backlog(&self, backlog: usize, now: DateTime<Utc>) {
+        let progressed = match self.state.lock() {
+            Ok(mut state) => {
+                let progressed = backlog == 0 || backlog < state.last_backlog;
+                state.last_backlog = backlog;
+                progressed
+            }
+            Err(_) => return,
+        };
+        if progressed { self.beat(EVENT_BUS, now); }
+    }
+
+    pub fn report(&self, now: DateTime<Utc>) -> WatchdogReport {
+        let components: Vec<ComponentReport> = self
+            .state
+            .lock()
+            .map(|state| {
+                state.components.iter().map(|(name, c)| ComponentReport {
+                    name,
+                    last_progress: c.last_progress,
+                    stall_after_seconds: c.stall_after_seconds,
+                    stalled: now.signed_duration_since(c.last_progress).num_seconds() > c.stall_after_seconds,
+                    started: c.started,
+                }).collect()
+            })
+            .unwrap_or_default();
+        WatchdogReport { ready: components.iter().all(|c| !c.stalled), components }
+    }
+
+    /// Periodically sample the event bus, publish stall gauges and, if configured, exit on a stall.
+    pub fn spawn(self, monitor: MonitorHandle) -> Option<tokio::task::JoinHandle<()>> {
+        if !self.config.enabled { return None; }
+        let every = Duration::from_secs(self.config.check_interval_seconds.max(1));
+        self.register(EVENT_BUS, every, monitor.now());
+        info!("🐕 Watchdog started");
+        Some(tokio::spawn(async move {
+            let mut tick = Ticker::new(monitor.clock(), every);
+            let mut was_ready = true;
+            loop {
+                tick.tick().await;
+                let now = monitor.now();
+                self.observe_event_backlog(monitor.event_backlog(), now);
+                let report = self.report(now);
+                for c in &report.components { metrics::set_watchdog_stalled(c.name, c.stalled); }
+                if was_ready && !report.ready {
+                    let stalled: Vec<&str> = report.components.iter().filter(|c| c.stalled).map(|c| c.name).collect();
+                    error!(?stalled, "🐕 Watchdog: internal components stalled; reporting not ready");
+                    if self.config.exit_on_stall {
+                        error!("🐕 Watchdog: exiting so the orchestrator restarts the monitor");
+                        std::process::exit(1);
+                    }
+                } else if !was_ready && report.ready {
+                    info!("🐕 Watchdog: all components progressing again");
+                }
+                was_ready = report.ready;
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stalls_are_reported_per_component() {
+        let dog = Watchdog::new(WatchdogConfig { stall_threshold_seconds: 60, ..Default::default() });
+        let t0 = Utc::now();
+        let at = |s| t0 + chrono::Duration::seconds(s);
+        dog.register(MONITORING_LOOP, Duration::from_secs(10), t0);
+        dog.register(METRICS_LOOP, Duration::from_secs(60), t0);
+        assert!(dog.report(at(60)).ready);
+
+        assert!(dog.report(at(60)).components.iter().all(|c| !c.started));
+
+        dog.beat(MONITORING_LOOP, at(55));
+        assert!(dog.report(at(100)).ready, "metrics loop gets 3x its 60s interval");
+        let stalled = |report: WatchdogReport| report.components.into_iter().filter(|c| c.stalled).map(|c| c.name).collect::<Vec<_>>();
+        assert_eq!(stalled(dog.report(at(116))), vec![MONITORING_LOOP]);
+        dog.beat(MONITORING_LOOP, at(170));
+        assert_eq!(stalled(dog.report(at(181))), vec![METRICS_LOOP]);
+
+        dog.register(MONITORING_LOOP, Duration::from_secs(20), at(200));
+        let started = dog.report(at(200)).components.into_iter().map(|c| (c.name, c.started)).collect::<Vec<_>>();
+        assert_eq!(started, [(METRICS_LOOP, false), (MONITORING_LOOP, true)], "re-registering keeps a component started");
+    }
+
+    #[test]
+    fn event_bus_stalls_only_while_backlog_is_stuck() {
+        let dog = Watchdog::new(WatchdogConfig { stall_threshold_seconds: 30, ..Default::default() });
+        let t0 = Utc::now();
+        let at = |s| t0 + chrono::Duration::seconds(s);
+        dog.register(EVENT_BUS, Duration::from_secs(10), t0);
+        dog.observe_event_backlog(5, at(10));
+        dog.observe_event_backlog(3, at(20)); // draining
+        dog.observe_event_backlog(3, at(40));
+        assert!(dog.report(at(45)).ready);
+        dog.observe_event_backlog(7, at(60));
+        assert!(!dog.report(at(60)).ready);
+        dog.observe_event_backlog(0, at(70));
+        assert!(dog.report(at(70)).ready);
+    }
+
+    #[test]
+    fn disabled_watchdog_is_always_ready() {
+        let dog = Watchdog::new(WatchdogConfig { enabled: false, ..Default::default() });
+        let t0 = Utc::now();
+        dog.register(MONITORING_LOOP, Duration::from_secs(10), t0);
+        assert!(dog.report(t0 + chrono::Duration::days(1)).ready);
+    }
+}
@@ -0,0 +1,376 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, warn};
+
+use crate::config::WebhooksConfig;
+use crate::metrics;
+use crate::models::{EventType, MonitorEvent};
+use crate::monitor::MonitorHandle;
+use crate::outbound::{self, Destination};
+
+/// `sha256=<hex>`: HMAC-SHA256 of `<timestamp>.<body>` under the subscription's secret
+pub const SIGNATURE_HEADER: &str = "x-fks-signature";
+/// Unix seconds the delivery was signed at; part of the signed content, so replays can be refused
+pub const TIMESTAMP_HEADER: &str = "x-fks-timestamp";
+/// Same for every attempt of one delivery, for receivers that deduplicate
+pub const DELIVERY_HEADER: &str = "x-fks-delivery";
+pub const EVENT_HEADER: &str = "x-fks-event";
+/// Deliveries kept per subscription for `GET /api/webhooks/{id}`
+const RECENT_DELIVERIES: usize = 20;
+
+/// Body of `POST /api/webhooks`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SubscriptionRequest {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<EventType>,
+    #[serde(default)]
+    pub services: Vec<String>,
+    /// Signing key; one is generated when left out
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// An external system's interest in monitor events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub id: String,
+    pub url: String,
+    /// Event types delivered; empty means all but `MetricsUpdate`
+    #[serde(default)]
+    pub events: Vec<EventType>,
+    /// Services whose events are delivered; empty means all, system events included
+    #[serde(default)]
+    pub services: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Only shown in the response that registers the subscription
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub secret: String,
+    #[serde(default)]
+    pub delivery: DeliveryStatus,
+}
+
+impl Subscription {
+    fn wants(&self, event: &MonitorEvent) -> bool {
+        let type_matches = if self.events.is_empty() { event.event_type != EventType::MetricsUpdate } else { self.events.contains(&event.event_type) };
+        type_matches && (self.services.is_empty() || event.service_id.as_ref().is_some_and(|id| self.services.contains(id)))
+    }
+
+    /// Without its secret, as listed by the API.
+    pub fn public(mut self) -> Self {
+        self.secret.clear();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeliveryStatus {
+    pub delivered: u64,
+    /// Deliveries given up on after all attempts
+    pub failed: u64,
+    pub consecutive_failures: u32,
+    #[serde(default)]
+    pub last_success_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_failure_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Newest first
+    #[serde(default)]
+    pub recent: VecDeque<Delivery>,
+}
+
+/// One event sent to a subscription, with all its attempts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub id: String,
+    pub event_type: EventType,
+    #[serde(default)]
+    pub service_id: Option<String>,
+    pub at: DateTime<Utc>,
+    pub attempts: u32,
+    pub success: bool,
+    /// Of the last attempt, when it got a response
+    #[serde(default)]
+    pub status_code: Option<u16>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Registered subscriptions, written to `store_path` whenever one is added or removed.
+#[derive(Clone, Default)]
+pub struct WebhookRegistry {
+    path: Option<PathBuf>,
+    subscriptions: Arc<RwLock<Vec<Subscription>>>,
+}
+
+impl WebhookRegistry {
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let subscriptions = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|raw| match serde_json::from_str::<Vec<Subscription>>(&raw) {
+                Ok(v) => Some(v),
+                Err(e) => { warn!(error=%e, "ignoring unreadable webhook subscription file"); None }
+            })
+            .unwrap_or_default();
+        Self { path, subscriptions: Arc::new(RwLock::new(subscriptions)) }
+    }
+
+    /// Add a subscription. The returned one carries the secret, which is not shown again.
+    pub fn register(&self, req: SubscriptionRequest, now: DateTime<Utc>) -> Result<Subscription, String> {
+        let url = reqwest::Url::parse(req.url.trim()).map_err(|e| format!("invalid `url`: {e}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err("`url` must be http or https".into());
+        }
+        let secret = match req.secret {
+            Some(secret) if secret.len() < 16 => return Err("`secret` must be at least 16 characters".into()),
+            Some(secret) => secret,
+            None => format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()),
+        };
+        let subscription = Subscription {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            events: req.events,
+            services: req.services,
+            description: req.description.filter(|d| !d.trim().is_empty()),
+            created_at: now,
+            secret,
+            delivery: DeliveryStatus::default(),
+        };
+        let Ok(mut list) = self.subscriptions.write() else { return Err("subscription registry unavailable".into()) };
+        list.push(subscription.clone());
+        self.persist(&list);
+        Ok(subscription)
+    }
+
+    pub fn remove(&self, id: &str) -> bool {
+        let Ok(mut list) = self.subscriptions.write() else { return false };
+        let before = list.len();
+        list.retain(|s| s.id != id);
+        let removed = list.len() != before;
+        if removed { self.persist(&list); }
+        removed
+    }
+
+    /// Subscriptions without their secrets, oldest first.
+    pub fn list(&self) -> Vec<Subscription> {
+        self.subscriptions.read().map(|list| list.iter().cloned().map(Subscription::public).collect()).unwrap_or_default()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Subscription> {
+        self.subscriptions.read().ok()?.iter().find(|s| s.id == id).cloned().map(Subscription::public)
+    }
+
+    fn matching(&self, event: &MonitorEvent) -> Vec<Subscription> {
+        self.subscriptions.read().map(|list| list.iter().filter(|s| s.wants(event)).cloned().collect()).unwrap_or_default()
+    }
+
+    /// Note how a delivery went; ignored once the subscription is gone.
+    fn record(&self, subscription_id: &str, delivery: Delivery) {
+        let Ok(mut list) = self.subscriptions.write() else { return };
+        let Some(subscription) = list.iter_mut().find(|s| s.id == subscription_id) else { return };
+        let status = &mut subscription.delivery;
+        if delivery.success {
+            status.delivered += 1;
+            status.consecutive_failures = 0;
+            status.last_success_at = Some(delivery.at);
+        } else {
+            status.failed += 1;
+            status.consecutive_failures += 1;
+            status.last_failure_at = Some(delivery.at);
+            status.last_error = delivery.error.clone();
+        }
+        status.recent.push_front(delivery);
+        status.recent.truncate(RECENT_DELIVERIES);
+    }
+
+    fn persist(&self, subscriptions: &[Subscription]) {
+        let Some(path) = &self.path else { return };
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match serde_json::to_vec_pretty(subscriptions) {
+            Ok(bytes) => if let Err(e) = std::fs::write(path, bytes) { warn!(error=%e, path=%path.display(), "failed to persist webhook subscriptions") },
+            Err(e) => warn!(error=%e, "failed to serialize webhook subscriptions"),
+        }
+    }
+}
+
+/// `SIGNATURE_HEADER` value for `body` sent at `timestamp`.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let mut ctx = ring::hmac::Context::with_key(&key);
+    ctx.update(format!("{timestamp}.").as_bytes());
+    ctx.update(body);
+    let hex: String = ctx.sign().as_ref().iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+/// Deliver monitor events to matching subscriptions for as long as the monitor runs.
+pub fn spawn(registry: WebhookRegistry, monitor: MonitorHandle) {
+    let events = monitor.subscribe_events();
+    tokio::spawn(run(registry, monitor, events));
+}
+
+async fn run(registry: WebhookRegistry, monitor: MonitorHandle, mut events: broadcast::Receiver<MonitorEvent>) {
+    let client = reqwest::Client::new();
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!(missed, "Webhook delivery fell behind the event stream; some events were skipped");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        let subscriptions = registry.matching(&event);
+        if subscriptions.is_empty() { continue; }
+        let cfg = monitor.config().webhooks.clone();
+        for subscription in subscriptions {
+            tokio::spawn(deliver(registry.clone(), subscription, event.clone(), cfg.clone(), client.clone()));
+        }
+    }
+}
+
+/// A failed attempt; `retry` is false when the receiver rejected the event itself.
+struct Failure {
+    status_code: Option<u16>,
+    error: String,
+    retry: bool,
+}
+
+/// POST `event` until it is accepted or `max_attempts` are used up, waiting `initial_backoff_ms`,
+/// then twice as long each time, up to `max_backoff_secs`. 4xx answers other than 408 and 429 are
+/// not retried.
+async fn deliver(registry: WebhookRegistry, subscription: Subscription, event: MonitorEvent, cfg: WebhooksConfig, client: reqwest::Client) {
+    let Ok(body) = serde_json::to_vec(&event) else { return };
+    let delivery_id = uuid::Uuid::new_v4().to_string();
+    let max_backoff = Duration::from_secs(cfg.max_backoff_secs);
+    let mut backoff = Duration::from_millis(cfg.initial_backoff_ms).min(max_backoff);
+    let mut attempts = 0;
+    let outcome = loop {
+        attempts += 1;
+        let outcome = attempt(&client, &subscription, &event, &delivery_id, &body, Duration::from_secs(cfg.timeout_seconds)).await;
+        match outcome {
+            Err(failure) if failure.retry && attempts < cfg.max_attempts => {
+                debug!(subscription = %subscription.id, attempt = attempts, error = %failure.error, "Webhook delivery failed; retrying");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(max_backoff);
+            }
+            outcome => break outcome,
+        }
+    };
+    let success = outcome.is_ok();
+    metrics::increment_webhook_delivery(&subscription.id, success);
+    let (status_code, error) = match outcome {
+        Ok(status) => (Some(status), None),
+        Err(failure) => {
+            warn!(subscription = %subscription.id, attempts, error = %failure.error, "Webhook delivery failed");
+            (failure.status_code, Some(failure.error))
+        }
+    };
+    registry.record(&subscription.id, Delivery {
+        id: delivery_id,
+        event_type: event.event_type,
+        service_id: event.service_id,
+        at: Utc::now(),
+        attempts,
+        success,
+        status_code,
+        error,
+    });
+}
+
+async fn attempt(client: &reqwest::Client, subscription: &Subscription, event: &MonitorEvent, delivery_id: &str, body: &[u8], timeout: Duration) -> Result<u16, Failure> {
+    let timestamp = Utc::now().timestamp();
+    let request = client.post(&subscription.url)
+        .timeout(timeout)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, sign(&subscription.secret, timestamp, body))
+        .header(TIMESTAMP_HEADER, timestamp)
+        .header(DELIVERY_HEADER, delivery_id)
+        .header(EVENT_HEADER, format!("{:?}", event.event_type))
+        .body(body.to_vec());
+    // The URL may carry a token, so errors leave it out
+    match outbound::send(Destination::Webhook, request).await {
+        Ok(resp) if resp.status().is_success() => Ok(resp.status().as_u16()),
+        Ok(resp) => {
+            let status = resp.status();
+            let retry = !status.is_client_error() || matches!(status.as_u16(), 408 | 429);
+            Err(Failure { status_code: Some(status.as_u16()), error: format!("receiver answered {status}"), retry })
+        }
+        Err(e) => Err(Failure { status_code: None, error: format!("{e:#}"), retry: true }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::{HeaderMap, StatusCode}, routing::post, Router};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn event(event_type: EventType, service_id: Option<&str>) -> MonitorEvent {
+        MonitorEvent {
+            event_type,
+            service_id: service_id.map(str::to_string),
+            message: "test".into(),
+            timestamp: Utc::now(),
+            data: None,
+            metadata: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn matching_events_arrive_signed_after_retries() {
+        // Fails the first attempt, then accepts
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(HeaderMap, Vec<u8>)>();
+        let calls = Arc::new(AtomicU32::new(0));
+        let app = Router::new().route("/hook", post(move |headers: HeaderMap, body: axum::body::Bytes| async move {
+            let _ = tx.send((headers, body.to_vec()));
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let registry = WebhookRegistry::default();
+        assert!(registry.register(SubscriptionRequest { url: "ftp://example.com".into(), ..Default::default() }, Utc::now()).is_err());
+        let subscription = registry.register(SubscriptionRequest {
+            url: format!("http://{addr}/hook"),
+            events: vec![EventType::ServiceDown],
+            services: vec!["api".into()],
+            secret: Some("0123456789abcdef".into()),
+            description: None,
+        }, Utc::now()).unwrap();
+        assert!(registry.list()[0].secret.is_empty(), "secret shown only once");
+
+        assert!(registry.matching(&event(EventType::ServiceUp, Some("api"))).is_empty());
+        assert!(registry.matching(&event(EventType::ServiceDown, Some("db"))).is_empty());
+        let down = event(EventType::ServiceDown, Some("api"));
+        let [matched] = registry.matching(&down).try_into().unwrap();
+
+        let cfg = WebhooksConfig { initial_backoff_ms: 10, ..Default::default() };
+        deliver(registry.clone(), matched, down, cfg, reqwest::Client::new()).await;
+        let (first, _) = rx.recv().await.unwrap();
+        let (headers, body) = rx.recv().await.unwrap();
+        assert_eq!(first[DELIVERY_HEADER], headers[DELIVERY_HEADER], "one delivery id across attempts");
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers[SIGNATURE_HEADER], sign("0123456789abcdef", timestamp, &body).as_str());
+        assert_eq!(headers[EVENT_HEADER], "ServiceDown");
+
+        let status = registry.get(&subscription.id).unwrap().delivery;
+        assert_eq!((status.delivered, status.failed), (1, 0));
+        assert_eq!((status.recent[0].attempts, status.recent[0].status_code), (2, Some(200)));
+        assert!(registry.remove(&subscription.id));
+        assert!(registry.list().is_empty());
+    }
+}
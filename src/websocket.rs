@@ -1,17 +1,333 @@
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{close_code, CloseFrame, Message, Utf8Bytes, WebSocket};
+use axum::response::sse::Event;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch, Notify};
 use tokio::time::interval;
 use tracing::{debug, error, warn};
+use utoipa::IntoParams;
 
+use crate::config::WebSocketConfig;
+use crate::confirmation::{Confirmations, Gate};
+use crate::models::MonitorEvent;
+use crate::redact::Redact;
 use crate::monitor::MonitorHandle;
 use crate::metrics;
-use crate::auth::authorize_jwt;
+use crate::auth::{authorize_jwt, jwt_subject};
+
+const UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Monitor event paired with its serialized `{"type":"event"}` frame, shared by every connection.
+#[derive(Debug)]
+struct SharedEvent {
+    event: MonitorEvent,
+    frame: Utf8Bytes,
+    /// The frame for read-only SSE callers
+    redacted: Utf8Bytes,
+}
+
+/// Serializes periodic updates and events once and fans the same bytes out to all WebSocket
+/// clients, instead of each connection building its own JSON per tick.
+#[derive(Clone)]
+pub struct WsHub {
+    updates: Arc<watch::Sender<Option<Utf8Bytes>>>,
+    /// `update` frames for read-only SSE callers
+    redacted_updates: Arc<watch::Sender<Option<Utf8Bytes>>>,
+    events: broadcast::Sender<Arc<SharedEvent>>,
+    clients: Arc<DashMap<uuid::Uuid, Arc<Client>>>,
+    /// `server_shutdown` frame, set once the server is going away
+    shutdown: Arc<watch::Sender<Option<Utf8Bytes>>>,
+}
+
+/// Why the server is closing connections; the `reason` of the `server_shutdown` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownReason {
+    /// The process is stopping
+    Shutdown,
+    /// An in-place upgrade: the same URL reaches the new process
+    Upgrade,
+}
+
+/// How a client receives the hub's feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    WebSocket,
+    /// `GET /api/events/stream`
+    Sse,
+}
+
+/// Bookkeeping for one connection, listed by `GET /api/ws/clients`.
+struct Client {
+    transport: Transport,
+    connected_at: DateTime<Utc>,
+    user_agent: Option<String>,
+    /// JWT subject of the handshake or of the last command carrying a valid token
+    identity: Mutex<Option<String>>,
+    filter: Mutex<Option<EventFilter>>,
+    sent: AtomicU64,
+    /// Events skipped because the client fell behind the hub
+    dropped: AtomicU64,
+    disconnect: Notify,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientInfo {
+    pub id: uuid::Uuid,
+    pub transport: Transport,
+    pub connected_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub identity: Option<String>,
+    /// Active `subscribe_events` filter (None = all events)
+    pub subscription: Option<EventFilter>,
+    pub messages_sent: u64,
+    pub messages_dropped: u64,
+}
+
+/// Removes the client from the hub's registry however the connection ends.
+struct Registration {
+    clients: Arc<DashMap<uuid::Uuid, Arc<Client>>>,
+    id: uuid::Uuid,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.clients.remove(&self.id);
+    }
+}
+
+impl WsHub {
+    pub fn start(monitor: MonitorHandle) -> Self {
+        let updates = Arc::new(watch::Sender::new(None));
+        let redacted_updates = Arc::new(watch::Sender::new(None));
+        let (events, _) = broadcast::channel(256);
+
+        let update_monitor = monitor.clone();
+        let updates_tx = updates.clone();
+        let redacted_tx = redacted_updates.clone();
+        tokio::spawn(async move {
+            let mut tick = interval(UPDATE_INTERVAL);
+            loop {
+                tick.tick().await;
+                // Nobody connected: skip building the snapshot
+                if updates_tx.receiver_count() == 0 && redacted_tx.receiver_count() == 0 { continue; }
+                let services = update_monitor.get_all_services().await;
+                let metrics = update_monitor.get_system_metrics().await;
+                let timestamp = chrono::Utc::now();
+                if redacted_tx.receiver_count() > 0 {
+                    let update = json!({ "type": "update", "services": services.clone().redact(), "metrics": &metrics, "timestamp": timestamp });
+                    redacted_tx.send_replace(Some(Utf8Bytes::from(update.to_string())));
+                }
+                let update = json!({ "type": "update", "services": services, "metrics": metrics, "timestamp": timestamp });
+                updates_tx.send_replace(Some(Utf8Bytes::from(update.to_string())));
+            }
+        });
+
+        let events_tx = events.clone();
+        let mut event_rx = monitor.subscribe_events();
+        tokio::spawn(async move {
+            loop {
+                match event_rx.recv().await {
+                    Ok(event) => {
+                        if events_tx.receiver_count() == 0 { continue; }
+                        let frame = Utf8Bytes::from(json!({ "type": "event", "event": &event }).to_string());
+                        let redacted = Utf8Bytes::from(json!({ "type": "event", "event": event.clone().redact() }).to_string());
+                        let _ = events_tx.send(Arc::new(SharedEvent { event, frame, redacted }));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => warn!(skipped, "WebSocket hub lagged; events dropped"),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Self { updates, redacted_updates, events, clients: Arc::default(), shutdown: Arc::new(watch::Sender::new(None)) }
+    }
+
+    fn register(&self, transport: Transport, user_agent: Option<String>) -> (Registration, Arc<Client>) {
+        let id = uuid::Uuid::new_v4();
+        let client = Arc::new(Client {
+            transport,
+            connected_at: Utc::now(),
+            user_agent,
+            identity: Mutex::new(None),
+            filter: Mutex::new(None),
+            sent: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            disconnect: Notify::new(),
+        });
+        self.clients.insert(id, client.clone());
+        (Registration { clients: self.clients.clone(), id }, client)
+    }
+
+    /// Connected clients, oldest first.
+    pub fn clients(&self) -> Vec<ClientInfo> {
+        let mut list: Vec<ClientInfo> = self.clients.iter().map(|entry| {
+            let c = entry.value();
+            ClientInfo {
+                id: *entry.key(),
+                transport: c.transport,
+                connected_at: c.connected_at,
+                user_agent: c.user_agent.clone(),
+                identity: c.identity.lock().ok().and_then(|i| i.clone()),
+                subscription: c.filter.lock().ok().and_then(|f| f.clone()),
+                messages_sent: c.sent.load(Ordering::Relaxed),
+                messages_dropped: c.dropped.load(Ordering::Relaxed),
+            }
+        }).collect();
+        list.sort_by_key(|c| c.connected_at);
+        list
+    }
+
+    /// Send every client (including any connecting from now on) a `server_shutdown` frame with
+    /// reconnect hints, then close their sockets with 1001 (going away).
+    pub fn announce_shutdown(&self, reason: ShutdownReason, config: &WebSocketConfig) {
+        let frame = json!({
+            "type": "server_shutdown",
+            "reason": reason,
+            "reconnect_after_ms": config.reconnect_after_ms,
+            "alternate_endpoint": config.alternate_endpoint,
+            "timestamp": Utc::now(),
+        });
+        self.shutdown.send_replace(Some(Utf8Bytes::from(frame.to_string())));
+    }
+
+    /// The WebSocket feed for `GET /api/events/stream`: `initial`, `update`, `event` and
+    /// `server_shutdown` events whose data is the JSON of the frame of the same type. Only events
+    /// matching `q` are sent. The client is listed and can be disconnected like a WebSocket one.
+    /// The feed as SSE events; `authorized` callers get it unredacted.
+    pub fn sse(&self, monitor: MonitorHandle, q: &StreamQuery, user_agent: Option<String>, authorized: bool) -> impl Stream<Item = Result<Event, Infallible>> + use<> {
+        futures::StreamExt::map(self.sse_frames(monitor, q, user_agent, authorized), |(name, data)| {
+            let event = Event::default().event(name).data(data.as_str());
+            // Tell EventSource to wait as long as the notice asks before reconnecting
+            let retry = (name == "server_shutdown")
+                .then(|| serde_json::from_str::<serde_json::Value>(data.as_str()).ok()?["reconnect_after_ms"].as_u64())
+                .flatten();
+            Ok(match retry { Some(ms) => event.retry(Duration::from_millis(ms)), None => event })
+        })
+    }
+
+    fn sse_frames(&self, monitor: MonitorHandle, q: &StreamQuery, user_agent: Option<String>, authorized: bool) -> impl Stream<Item = (&'static str, Utf8Bytes)> + use<> {
+        let (registration, client) = self.register(Transport::Sse, user_agent);
+        let filter = q.filter();
+        if let Ok(mut shared) = client.filter.lock() { shared.clone_from(&filter); }
+        let feed = SseFeed {
+            monitor,
+            _registration: registration,
+            client,
+            filter,
+            authorized,
+            updates: if authorized { self.updates.subscribe() } else { self.redacted_updates.subscribe() },
+            events: self.events.subscribe(),
+            shutdown: self.shutdown.subscribe(),
+            started: false,
+            done: false,
+        };
+        futures::stream::unfold(feed, SseFeed::next)
+    }
+
+    /// Ask a connection to close; false if no such client is connected.
+    pub fn disconnect(&self, id: uuid::Uuid) -> bool {
+        match self.clients.get(&id) {
+            Some(client) => { client.disconnect.notify_one(); true }
+            None => false,
+        }
+    }
+}
+
+/// Filters of `GET /api/events/stream`, like a `subscribe_events` command.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreamQuery {
+    pub service: Option<String>,
+    /// Comma-separated event types, e.g. `ServiceDown,ServiceUp`
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+}
+
+impl StreamQuery {
+    fn filter(&self) -> Option<EventFilter> {
+        let event_types = self.event_type.as_deref()
+            .map(|types| types.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect());
+        (self.service.is_some() || event_types.is_some()).then(|| EventFilter { service_id: self.service.clone(), event_types })
+    }
+}
+
+/// State of one SSE connection between frames.
+struct SseFeed {
+    monitor: MonitorHandle,
+    _registration: Registration,
+    client: Arc<Client>,
+    filter: Option<EventFilter>,
+    /// Whether frames go out unredacted
+    authorized: bool,
+    updates: watch::Receiver<Option<Utf8Bytes>>,
+    events: broadcast::Receiver<Arc<SharedEvent>>,
+    shutdown: watch::Receiver<Option<Utf8Bytes>>,
+    started: bool,
+    done: bool,
+}
+
+impl SseFeed {
+    async fn next(mut self) -> Option<((&'static str, Utf8Bytes), Self)> {
+        if self.done { return None; }
+        if !self.started {
+            self.started = true;
+            let frame = initial_frame(&self.monitor, self.authorized).await;
+            return Some(self.emit("initial", Utf8Bytes::from(frame.to_string())));
+        }
+        loop {
+            tokio::select! {
+                changed = self.updates.changed() => {
+                    if changed.is_err() { return None; }
+                    let frame = self.updates.borrow_and_update().clone();
+                    if let Some(frame) = frame { return Some(self.emit("update", frame)); }
+                }
+                evt = self.events.recv() => match evt {
+                    Ok(shared) if self.filter.as_ref().is_none_or(|f| f.matches(&shared.event)) => {
+                        let frame = if self.authorized { shared.frame.clone() } else { shared.redacted.clone() };
+                        return Some(self.emit("event", frame));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => { self.client.dropped.fetch_add(skipped, Ordering::Relaxed); }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                    Ok(_) => {}
+                },
+                frame = async { self.shutdown.wait_for(Option::is_some).await.ok().and_then(|frame| frame.clone()) } => {
+                    self.done = true;
+                    return frame.map(|frame| self.emit("server_shutdown", frame));
+                }
+                _ = self.client.disconnect.notified() => return None,
+            }
+        }
+    }
+
+    fn emit(self, name: &'static str, frame: Utf8Bytes) -> ((&'static str, Utf8Bytes), Self) {
+        self.client.sent.fetch_add(1, Ordering::Relaxed);
+        ((name, frame), self)
+    }
+}
+
+/// The `initial` frame: every service and the system metrics.
+async fn initial_frame(monitor: &MonitorHandle, authorized: bool) -> serde_json::Value {
+    let services = monitor.get_all_services().await;
+    json!({
+        "type": "initial",
+        "services": if authorized { services } else { services.redact() },
+        "metrics": monitor.get_system_metrics().await
+    })
+}
 
 // Claims struct & role logic moved to auth module
 
-#[derive(Debug, Clone)]
-struct EventFilter {
+#[derive(Debug, Clone, Serialize)]
+pub struct EventFilter {
     service_id: Option<String>,
     event_types: Option<Vec<String>>, // event type names matching EventType variants
 }
@@ -31,34 +347,84 @@ impl EventFilter {
 
 async fn authorize_ws_command(token: Option<&str>) -> bool { authorize_jwt(token) }
 
-pub async fn handle_websocket(mut socket: WebSocket, monitor: MonitorHandle) {
+/// Container log stream a client opened with `service_logs`. Its frames are sent from the connection
+/// loop; a new `service_logs`, `stop_logs` or the end of the connection stops it.
+struct LogFollow {
+    tx: mpsc::Sender<Utf8Bytes>,
+    task: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl LogFollow {
+    fn stop(&mut self) {
+        if let Some(task) = self.task.take() { task.abort(); }
+    }
+
+    fn start(&mut self, monitor: &MonitorHandle, service_id: String, tail: u32, follow: bool) {
+        self.stop();
+        let config = monitor.config();
+        let target = match config.services.iter().find(|s| s.id == service_id) {
+            None => Err("unknown service".to_string()),
+            Some(service) => match &service.docker_container {
+                None => Err("service has no docker_container".to_string()),
+                Some(container) => crate::docker_hosts::connect_for(&config, service).map(|docker| (docker, container.clone())).map_err(|e| format!("{e:#}")),
+            },
+        };
+        let tx = self.tx.clone();
+        self.task = Some(tokio::spawn(async move {
+            let error = forward_logs(&tx, &service_id, target, tail, follow).await.err();
+            let _ = tx.send(json!({"type": "log_end", "service_id": service_id, "error": error}).to_string().into()).await;
+        }));
+    }
+}
+
+/// Send `{"type":"log"}` frames for the service's container until its output ends or the client goes away.
+async fn forward_logs(tx: &mpsc::Sender<Utf8Bytes>, service_id: &str, target: Result<(bollard::Docker, String), String>, tail: u32, follow: bool) -> Result<(), String> {
+    use futures::StreamExt;
+    let (docker, container) = target?;
+    let lines = crate::logs::container_logs(docker, &container, tail, follow).await.map_err(|e| format!("{e:#}"))?;
+    let mut lines = std::pin::pin!(lines);
+    while let Some(line) = lines.next().await {
+        let line = line.map_err(|e| format!("{e:#}"))?;
+        let frame = json!({"type": "log", "service_id": service_id, "stream": line.stream, "line": line.line});
+        if tx.send(frame.to_string().into()).await.is_err() { return Ok(()); }
+    }
+    Ok(())
+}
+
+impl Drop for LogFollow {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Serve one `/ws` connection. `authenticated` means the handshake carried valid credentials,
+/// which then cover privileged commands too; `identity` is the JWT subject it carried.
+pub async fn handle_websocket(mut socket: WebSocket, monitor: MonitorHandle, hub: WsHub, confirmations: Confirmations, user_agent: Option<String>, authenticated: bool, identity: Option<String>) {
     debug!("🔌 WebSocket connection established");
+    let (_registration, client) = hub.register(Transport::WebSocket, user_agent);
+    if let Ok(mut slot) = client.identity.lock() { *slot = identity; }
     
     // Track connection in metrics
     metrics::increment_websocket_connections();
 
     // Send initial data
-    let services = monitor.get_all_services().await;
-    let metrics = monitor.get_system_metrics().await;
-    
-    let initial_data = json!({
-        "type": "initial",
-        "services": services,
-        "metrics": metrics
-    });
+    let initial_data = initial_frame(&monitor, true).await;
 
     if socket.send(Message::Text(initial_data.to_string().into())).await.is_err() {
         warn!("Failed to send initial data to WebSocket client");
+        metrics::decrement_websocket_connections();
         return;
     }
+    client.sent.fetch_add(1, Ordering::Relaxed);
 
-    // Subscribe to event stream
-    let mut event_rx = monitor.subscribe_events();
+    // Subscribe to the shared event stream and periodic snapshots
+    let mut event_rx = hub.events.subscribe();
+    let mut updates = hub.updates.subscribe();
+    let mut shutdown = hub.shutdown.subscribe();
     // Current subscription filter (None = all)
     let mut filter: Option<EventFilter> = None;
-
-    // Set up periodic updates
-    let mut update_interval = interval(Duration::from_secs(5));
+    let (log_tx, mut log_rx) = mpsc::channel(256);
+    let mut logs = LogFollow { tx: log_tx, task: None };
     
     loop {
         tokio::select! {
@@ -70,16 +436,26 @@ pub async fn handle_websocket(mut socket: WebSocket, monitor: MonitorHandle) {
                         
                         // Handle client commands
                         if let Ok(command) = serde_json::from_str::<ClientCommand>(&text) {
+                            if let Some(subject) = command.token.as_deref().and_then(jwt_subject) {
+                                if let Ok(mut identity) = client.identity.lock() { *identity = Some(subject); }
+                            }
                             // Authorization: if command requires privileged action and JWT invalid -> reject
-                            if command.command_type == "restart_service" {
-                                if !authorize_ws_command(command.token.as_deref()).await {
-                                    let resp = json!({"type":"error","reason":"unauthorized"});
-                                    let _ = socket.send(Message::Text(resp.to_string().into())).await;
-                                    crate::metrics::increment_restart_unauthorized();
+                            let privileged = matches!(command.command_type.as_str(), "restart_service" | "container_action" | "service_logs");
+                            if privileged && !(authenticated && command.token.is_none()) && !authorize_ws_command(command.token.as_deref()).await {
+                                let resp = json!({"type":"error","reason":"unauthorized"});
+                                let _ = socket.send(Message::Text(resp.to_string().into())).await;
+                                crate::metrics::increment_restart_unauthorized();
+                                continue;
+                            }
+                            if let Some(action) = destructive_action(&command) {
+                                let token = command.data.as_ref().and_then(|d| d["confirmation_token"].as_str());
+                                if let Err(gate) = confirmations.check(&monitor.config().confirmation, &action, &action, token, monitor.now()) {
+                                    let _ = socket.send(Message::Text(confirmation_frame(&command, gate).to_string().into())).await;
                                     continue;
                                 }
                             }
-                            handle_client_command(&mut socket, &monitor, &mut filter, command).await;
+                            handle_client_command(&mut socket, &monitor, &mut filter, &mut logs, command).await;
+                            if let Ok(mut shared) = client.filter.lock() { shared.clone_from(&filter); }
                         }
                     }
                     Some(Ok(Message::Close(_))) => {
@@ -95,31 +471,50 @@ pub async fn handle_websocket(mut socket: WebSocket, monitor: MonitorHandle) {
                 }
             }
             
-            // Send periodic updates
-            _ = update_interval.tick() => {
-                let services = monitor.get_all_services().await;
-                let metrics = monitor.get_system_metrics().await;
-                
-                let update = json!({
-                    "type": "update",
-                    "services": services,
-                    "metrics": metrics,
-                    "timestamp": chrono::Utc::now()
-                });
-
-                if socket.send(Message::Text(update.to_string().into())).await.is_err() {
-                    warn!("Failed to send update to WebSocket client");
-                    break;
+            // Send periodic updates (serialized once by the hub)
+            changed = updates.changed() => {
+                if changed.is_err() { break; }
+                let frame = updates.borrow_and_update().clone();
+                if let Some(frame) = frame {
+                    if socket.send(Message::Text(frame)).await.is_err() {
+                        warn!("Failed to send update to WebSocket client");
+                        break;
+                    }
+                    client.sent.fetch_add(1, Ordering::Relaxed);
                 }
             }
+            // Lines of a `service_logs` stream
+            Some(frame) = log_rx.recv() => {
+                if socket.send(Message::Text(frame)).await.is_err() { break; }
+                client.sent.fetch_add(1, Ordering::Relaxed);
+            }
             // Push monitor events to client
             evt = event_rx.recv() => {
-                if let Ok(ev) = evt {
-                    if filter.as_ref().map(|f| f.matches(&ev)).unwrap_or(true) {
-                        let msg = json!({ "type": "event", "event": ev });
-                        if socket.send(Message::Text(msg.to_string().into())).await.is_err() { break; }
+                match evt {
+                    Ok(shared) if filter.as_ref().map(|f| f.matches(&shared.event)).unwrap_or(true) => {
+                        if socket.send(Message::Text(shared.frame.clone())).await.is_err() { break; }
+                        client.sent.fetch_add(1, Ordering::Relaxed);
                     }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => { client.dropped.fetch_add(skipped, Ordering::Relaxed); }
+                    _ => {}
+                }
+            }
+            // Server going away: hand out reconnect hints before closing
+            frame = async { shutdown.wait_for(Option::is_some).await.ok().and_then(|frame| frame.clone()) } => {
+                if let Some(frame) = frame {
+                    debug!("🔌 Closing WebSocket client for server shutdown");
+                    let _ = socket.send(Message::Text(frame)).await;
+                    let close = CloseFrame { code: close_code::AWAY, reason: "server shutting down".into() };
+                    let _ = socket.send(Message::Close(Some(close))).await;
                 }
+                break;
+            }
+            // Force-disconnect requested through `DELETE /api/ws/clients/{id}`
+            _ = client.disconnect.notified() => {
+                debug!("🔌 Disconnecting WebSocket client on request");
+                let frame = CloseFrame { code: close_code::POLICY, reason: "disconnected by administrator".into() };
+                let _ = socket.send(Message::Close(Some(frame))).await;
+                break;
             }
         }
     }
@@ -134,6 +529,7 @@ async fn handle_client_command(
     socket: &mut WebSocket,
     monitor: &MonitorHandle,
     filter: &mut Option<EventFilter>,
+    logs: &mut LogFollow,
     command: ClientCommand,
 ) {
     debug!("🎛️  Handling client command: {:?}", command);
@@ -141,19 +537,49 @@ async fn handle_client_command(
     match command.command_type.as_str() {
         "restart_service" => {
             if let Some(service_id) = command.service_id {
-                let result = monitor.restart_service(&service_id).await;
+                let retry_at = match monitor.restart_refusal(&service_id) {
+                    Some(crate::restart_backoff::Refusal::RateLimited { retry_at, .. }) => Some(retry_at),
+                    _ => None,
+                };
+                // `data` may carry `mode` and `fresh_volumes`, as the HTTP endpoint's query does
+                let options: crate::models::RestartOptions = command.data.clone().and_then(|d| serde_json::from_value(d).ok()).unwrap_or_default();
+                let result = monitor.restart_service_with(&service_id, &options).await;
                 
-                let response = json!({
+                let mut response = json!({
                     "type": "restart_result",
                     "service_id": service_id,
                     "result": result
                 });
+                if let Some(retry_at) = retry_at { response["retry_at"] = json!(retry_at); }
 
                 if let Err(err) = socket.send(Message::Text(response.to_string().into())).await {
                     error!("Failed to send restart result: {}", err);
                 }
             }
         }
+        "container_action" => {
+            let action = command.data.as_ref().and_then(|d| d["action"].as_str()).and_then(crate::models::ContainerAction::parse);
+            if let (Some(service_id), Some(action)) = (command.service_id, action) {
+                let result = monitor.container_action(&service_id, action).await;
+                let response = json!({
+                    "type": "container_action_result",
+                    "service_id": service_id,
+                    "action": action,
+                    "result": result
+                });
+                if let Err(err) = socket.send(Message::Text(response.to_string().into())).await {
+                    error!("Failed to send container action result: {}", err);
+                }
+            }
+        }
+        "service_logs" => {
+            if let Some(service_id) = command.service_id {
+                let data = command.data.unwrap_or_default();
+                let tail = data["tail"].as_u64().map_or(crate::logs::DEFAULT_TAIL, |t| t.min(crate::logs::MAX_TAIL as u64) as u32);
+                logs.start(monitor, service_id, tail, data["follow"].as_bool().unwrap_or(false));
+            }
+        }
+        "stop_logs" => logs.stop(),
         "get_service_details" => {
             if let Some(service_id) = command.service_id {
                 let health = monitor.get_service_health(&service_id).await;
@@ -193,12 +619,41 @@ async fn handle_client_command(
     }
 }
 
+/// The action a command would take that needs confirming under `[confirmation]`, named like the
+/// HTTP API names it so either can confirm a token armed by the other.
+fn destructive_action(command: &ClientCommand) -> Option<String> {
+    let service_id = command.service_id.as_deref()?;
+    match command.command_type.as_str() {
+        "restart_service" if command.data.as_ref().is_some_and(|d| d["mode"] == "recreate") => Some(format!("recreate {service_id}")),
+        "restart_service" => Some(format!("restart {service_id}")),
+        "container_action" => {
+            let action = command.data.as_ref().and_then(|d| d["action"].as_str()).and_then(crate::models::ContainerAction::parse)?;
+            Some(format!("{} {service_id}", action.as_str()))
+        }
+        _ => None,
+    }
+}
+
+/// Reply to a command held back for confirmation; repeating it with `data.confirmation_token` runs it.
+fn confirmation_frame(command: &ClientCommand, gate: Gate) -> serde_json::Value {
+    match gate {
+        Gate::Armed(challenge) => json!({
+            "type": "confirmation_required",
+            "command_type": command.command_type,
+            "service_id": command.service_id,
+            "action": challenge.action,
+            "token": challenge.token,
+            "expires_at": challenge.expires_at,
+        }),
+        Gate::Rejected(error) => json!({"type": "error", "reason": "confirmation_rejected", "error": error}),
+    }
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct ClientCommand {
     command_type: String,
     service_id: Option<String>,
-    // Reserved for future command payloads
-    #[allow(dead_code)]
+    // Command payload, e.g. `{"action": "stop"}` for `container_action`
     data: Option<serde_json::Value>,
     token: Option<String>,
     event_types: Option<Vec<String>>,
@@ -213,7 +668,7 @@ mod tests {
     use chrono::Utc;
 
     fn ev(event_type: EventType, service_id: Option<&str>) -> MonitorEvent {
-        MonitorEvent { event_type, service_id: service_id.map(|s| s.to_string()), message: String::new(), timestamp: Utc::now(), data: None }
+        MonitorEvent { event_type, service_id: service_id.map(|s| s.to_string()), message: String::new(), timestamp: Utc::now(), data: None, metadata: Default::default() }
     }
 
     #[test]
@@ -230,5 +685,93 @@ mod tests {
         assert!(!f.matches(&ev(EventType::ServiceUp, Some("x"))));
     }
 
+    #[tokio::test]
+    async fn hub_shares_one_serialized_frame_across_clients() {
+        let monitor = crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().handle();
+        let hub = WsHub::start(monitor.clone());
+        let mut a = hub.events.subscribe();
+        let mut b = hub.events.subscribe();
+        tokio::task::yield_now().await;
+        monitor.inject_event(ev(EventType::ServiceDown, Some("fks_api")));
+        let (a, b) = (a.recv().await.unwrap(), b.recv().await.unwrap());
+        assert_eq!(a.frame.as_str().as_ptr(), b.frame.as_str().as_ptr(), "frame bytes are shared, not re-serialized");
+        let parsed: serde_json::Value = serde_json::from_str(a.frame.as_str()).unwrap();
+        assert_eq!(parsed["type"], "event");
+        assert_eq!(parsed["event"]["service_id"], "fks_api");
+    }
+
+    #[tokio::test]
+    async fn hub_lists_and_disconnects_clients() {
+        let monitor = crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().handle();
+        let hub = WsHub::start(monitor);
+        let (registration, client) = hub.register(Transport::WebSocket, Some("dashboard/1.0".into()));
+        client.sent.fetch_add(3, Ordering::Relaxed);
+        *client.filter.lock().unwrap() = Some(EventFilter { service_id: Some("fks_api".into()), event_types: None });
+
+        let listed = hub.clients();
+        assert_eq!(listed.len(), 1);
+        assert_eq!((listed[0].user_agent.as_deref(), listed[0].messages_sent), (Some("dashboard/1.0"), 3));
+        assert_eq!(listed[0].subscription.as_ref().and_then(|f| f.service_id.as_deref()), Some("fks_api"));
+
+        assert!(hub.disconnect(listed[0].id));
+        tokio::time::timeout(Duration::from_secs(1), client.disconnect.notified()).await.expect("disconnect signalled");
+        assert!(!hub.disconnect(uuid::Uuid::new_v4()));
+        drop(registration);
+        assert!(hub.clients().is_empty());
+    }
+
+    #[tokio::test]
+    async fn shutdown_notice_carries_reconnect_hints_for_late_subscribers() {
+        let monitor = crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().handle();
+        let hub = WsHub::start(monitor);
+        let config = WebSocketConfig { reconnect_after_ms: 1500, alternate_endpoint: Some("wss://master-b/ws".into()) };
+        hub.announce_shutdown(ShutdownReason::Shutdown, &config);
+
+        // A client connecting after the announcement still gets it straight away
+        let mut late = hub.shutdown.subscribe();
+        let frame = late.wait_for(Option::is_some).await.unwrap().clone().unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(frame.as_str()).unwrap();
+        assert_eq!(parsed["type"], "server_shutdown");
+        assert_eq!(parsed["reason"], "shutdown");
+        assert_eq!(parsed["reconnect_after_ms"], 1500);
+        assert_eq!(parsed["alternate_endpoint"], "wss://master-b/ws");
+    }
+
+    #[tokio::test]
+    async fn sse_feed_starts_with_a_snapshot_and_sends_matching_events() {
+        let monitor = crate::monitor::ServiceMonitor::new(crate::config::Config::default()).await.unwrap().handle();
+        let hub = WsHub::start(monitor.clone());
+        let q = StreamQuery { service: Some("fks_api".into()), event_type: Some("ServiceDown, ServiceUp".into()) };
+        let mut frames = Box::pin(hub.sse_frames(monitor.clone(), &q, Some("curl/8".into()), true));
+        // Periodic `update`s may come in between at any time
+        async fn next<S: Stream<Item = (&'static str, Utf8Bytes)> + Unpin>(frames: &mut S) -> Option<S::Item> {
+            loop {
+                let frame = tokio::time::timeout(Duration::from_secs(1), futures::StreamExt::next(frames)).await.expect("frame within a second");
+                if frame.as_ref().is_none_or(|(name, _)| *name != "update") { return frame; }
+            }
+        }
+
+        let (name, data) = next(&mut frames).await.unwrap();
+        assert_eq!(name, "initial");
+        assert_eq!(serde_json::from_str::<serde_json::Value>(data.as_str()).unwrap()["type"], "initial");
+        let listed = hub.clients();
+        assert_eq!((listed[0].transport, listed[0].messages_sent), (Transport::Sse, 1));
+
+        tokio::task::yield_now().await;
+        monitor.inject_event(ev(EventType::ServiceDown, Some("fks_data")));
+        monitor.inject_event(ev(EventType::HighLatency, Some("fks_api")));
+        monitor.inject_event(ev(EventType::ServiceUp, Some("fks_api")));
+        let (name, data) = next(&mut frames).await.unwrap();
+        assert_eq!(name, "event");
+        let parsed: serde_json::Value = serde_json::from_str(data.as_str()).unwrap();
+        assert_eq!((parsed["event"]["service_id"].as_str(), parsed["event"]["event_type"].as_str()), (Some("fks_api"), Some("ServiceUp")));
+
+        hub.announce_shutdown(ShutdownReason::Upgrade, &WebSocketConfig::default());
+        assert_eq!(next(&mut frames).await.unwrap().0, "server_shutdown");
+        assert!(next(&mut frames).await.is_none(), "the stream ends after the notice");
+        drop(frames);
+        assert!(hub.clients().is_empty());
+    }
+
     // Role auth logic covered in auth module tests
 }
@@ -1,27 +1,44 @@
 use fks_master::compose::{ComposeRequest, ComposeAction};
 
 #[tokio::test]
-async fn logs_without_services_errors() {
-    let req = ComposeRequest { action: ComposeAction::Logs, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: Some(5), dry_run: false };
-    // This will attempt docker API; if daemon not present, we treat that as skip.
-    match req.execute().await {
-        Ok(result) => {
-            // When no services specified we expect failure state (success=false)
-            assert!(!result.success, "logs with no services should not succeed");
-            assert!(result.stderr.contains("no services"));
-        }
-        Err(e) => {
-            // Accept daemon connection failures gracefully to keep CI portable
-            let msg = e.to_string();
-            assert!(msg.to_lowercase().contains("docker"), "unexpected error: {msg}");
-        }
-    }
+async fn logs_without_services_need_the_compose_file() {
+    let req = ComposeRequest { action: ComposeAction::Logs, services: vec![], file: "no-such-compose.yml".into(), project: None, detach: false, tail: Some(5), stream: false, dry_run: false, down: Default::default(), env_file: None, env: Default::default(), command: vec![], no_cache: false, build_args: Default::default(), target: None, ready_timeout_seconds: None, ready_endpoints: Default::default(), registries: vec![] };
+    // The compose file is read before any container is looked up, so no daemon is needed
+    let msg = format!("{:#}", req.execute().await.expect_err("nothing to enumerate"));
+    assert!(msg.starts_with("no services given and the compose file is unusable: reading no-such-compose.yml"), "unexpected error: {msg}");
 }
 
 #[tokio::test]
 async fn dry_run_short_circuits() {
-    let req = ComposeRequest { action: ComposeAction::Up, services: vec!["svc".into()], file: "docker-compose.yml".into(), project: Some("proj".into()), detach: true, tail: None, dry_run: true };
+    let req = ComposeRequest { action: ComposeAction::Up, services: vec!["svc".into()], file: "docker-compose.yml".into(), project: Some("proj".into()), detach: true, tail: None, stream: false, dry_run: true, down: Default::default(), env_file: None, env: Default::default(), command: vec![], no_cache: false, build_args: Default::default(), target: None, ready_timeout_seconds: None, ready_endpoints: Default::default(), registries: vec![] };
     let result = req.execute().await.expect("dry run should succeed");
     assert!(result.success);
     assert_eq!(result.stdout, "dry-run");
 }
+
+#[test]
+fn ps_result_serializes_structured_containers() {
+    use fks_master::compose::{ComposeResult, ContainerInfo};
+    let plain = ComposeResult { action: "stop".into(), success: true, ..Default::default() };
+    let v = serde_json::to_value(&plain).unwrap();
+    assert!(v.get("containers").is_none(), "non-ps results omit containers");
+
+    let ps = ComposeResult {
+        action: "ps".into(),
+        success: true,
+        containers: Some(vec![ContainerInfo { id: "abc123".into(), name: "fks_api".into(), image: "fks_api:latest".into(), state: "running".into(), health: Some("healthy".into()), ports: vec!["0.0.0.0:8000->8000/tcp".into()], uptime_seconds: Some(42) }]),
+        ..Default::default()
+    };
+    let v = serde_json::to_value(&ps).unwrap();
+    assert_eq!(v["containers"][0]["health"], "healthy");
+    assert_eq!(v["containers"][0]["uptime_seconds"], 42);
+}
+
+#[test]
+fn project_scope_filters_on_compose_label() {
+    use fks_master::compose::{project_list_options, COMPOSE_PROJECT_LABEL};
+    assert!(project_list_options(None).is_none());
+    assert!(project_list_options(Some("")).is_none());
+    let opts = project_list_options(Some("fks")).expect("project filter");
+    assert_eq!(opts.filters.get("label"), Some(&vec![format!("{COMPOSE_PROJECT_LABEL}=fks")]));
+}
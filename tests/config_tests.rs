@@ -54,3 +54,332 @@ async fn invalid_toml_errors() {
     let msg = err.to_string();
     assert!(msg.contains("expected"), "unexpected error message: {msg}");
 }
+
+#[tokio::test]
+async fn external_target_without_container() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("monitor.toml");
+    let toml = r#"
+        [monitoring]
+        check_interval_seconds = 30
+        timeout_seconds = 5
+        retry_attempts = 1
+        batch_size = 5
+
+        [alerts]
+        enable_notifications = false
+        high_latency_threshold_ms = 2000
+        consecutive_failures_threshold = 3
+
+        [[services]]
+        id = "status_page"
+        name = "Public Status Page"
+        health_endpoint = "https://status.example.com/"
+        service_type = "External"
+        expected_response_time_ms = 1500
+        critical = false
+        check_interval_seconds = 300
+    "#;
+    fs::write(&path, toml).unwrap();
+    let cfg = Config::load(&path).await.expect("parse external");
+    let svc = &cfg.services[0];
+    assert!(svc.service_type.is_external());
+    assert!(svc.docker_container.is_none());
+    assert_eq!(svc.check_interval_seconds, Some(300));
+}
+
+#[tokio::test]
+async fn alert_rules_parse_and_reject_bad_expressions() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("monitor.toml");
+    let base = r#"
+        services = []
+
+        [monitoring]
+        check_interval_seconds = 30
+        timeout_seconds = 5
+        retry_attempts = 1
+        batch_size = 5
+
+        [alerts]
+        enable_notifications = false
+        high_latency_threshold_ms = 2000
+        consecutive_failures_threshold = 3
+    "#;
+    let good = format!("{base}\n[[alerts.rules]]\nname = \"errors\"\nexpr = \"error_rate > 5/min for 3m\"\nservices = [\"fks_api\"]\n");
+    fs::write(&path, good).unwrap();
+    let cfg = Config::load(&path).await.expect("parse rules");
+    let rule = &cfg.alerts.rules[0];
+    assert_eq!(rule.expr.for_seconds, 180);
+    assert_eq!(rule.severity, "warning");
+    assert_eq!(cfg.alerts.rule_evaluation_interval_seconds, 15);
+
+    let bad = format!("{base}\n[[alerts.rules]]\nname = \"disk\"\nexpr = \"disk_free < 5\"\n");
+    fs::write(&path, bad).unwrap();
+    let err = Config::load(&path).await.unwrap_err().to_string();
+    assert!(err.contains("unknown metric"), "unexpected error message: {err}");
+}
+
+#[test]
+fn services_inherit_and_override_templates() {
+    let toml = r#"
+        [monitoring]
+        check_interval_seconds = 5
+        timeout_seconds = 2
+        retry_attempts = 1
+        batch_size = 2
+
+        [alerts]
+        enable_notifications = false
+        high_latency_threshold_ms = 1000
+        consecutive_failures_threshold = 2
+
+        [templates.base]
+        expected_response_time_ms = 500
+        critical = false
+
+        [templates.python-api]
+        template = "base"
+        service_type = "Api"
+        health_endpoint = "http://{id}:8000/health"
+        docker_container = "{id}"
+
+        [[services]]
+        id = "fks_data"
+        name = "FKS Data"
+        template = "python-api"
+
+        [[services]]
+        id = "fks_engine"
+        name = "FKS Engine"
+        template = "python-api"
+        critical = true
+        expected_response_time_ms = 200
+    "#;
+    let cfg = Config::from_toml_str(toml).expect("templates expand");
+    let data = &cfg.services[0];
+    assert_eq!(data.health_endpoint, "http://fks_data:8000/health");
+    assert_eq!(data.docker_container.as_deref(), Some("fks_data"));
+    assert_eq!((data.expected_response_time_ms, data.critical), (500, false));
+    let engine = &cfg.services[1];
+    assert_eq!((engine.expected_response_time_ms, engine.critical), (200, true));
+    assert_eq!(engine.health_endpoint, "http://fks_engine:8000/health");
+
+    let unknown = toml.replace("template = \"base\"", "template = \"missing\"");
+    assert!(Config::from_toml_str(&unknown).unwrap_err().to_string().contains("unknown service template `missing`"));
+    let cyclic = toml.replace("[templates.base]", "[templates.base]\ntemplate = \"python-api\"");
+    assert!(Config::from_toml_str(&cyclic).unwrap_err().to_string().contains("template cycle"));
+}
+
+#[test]
+fn services_start_from_defaults_below_their_template() {
+    let toml = r#"
+        [monitoring]
+        check_interval_seconds = 5
+        timeout_seconds = 2
+        retry_attempts = 1
+        batch_size = 2
+
+        [alerts]
+        enable_notifications = false
+        high_latency_threshold_ms = 1000
+        consecutive_failures_threshold = 2
+
+        [defaults]
+        service_type = "Api"
+        health_endpoint = "http://{id}:8000/health"
+        expected_response_time_ms = 500
+        critical = false
+        check_interval_seconds = 15
+        tags = ["fks"]
+        labels = { team = "platform" }
+
+        [templates.worker]
+        service_type = "Worker"
+        health_endpoint = "http://{id}:9000/health"
+
+        [[services]]
+        id = "fks_api"
+        name = "API"
+
+        [[services]]
+        id = "fks_worker"
+        name = "Worker"
+        template = "worker"
+        critical = true
+        tags = ["batch"]
+        labels = { owner = "alice" }
+    "#;
+    let cfg = Config::from_toml_str(toml).expect("defaults apply");
+    let api = &cfg.services[0];
+    assert_eq!(api.health_endpoint, "http://fks_api:8000/health");
+    assert_eq!((api.expected_response_time_ms, api.critical, api.check_interval_seconds), (500, false, Some(15)));
+    assert_eq!(api.tags, ["fks"]);
+    let worker = &cfg.services[1];
+    assert_eq!(format!("{:?}", worker.service_type), "Worker");
+    assert_eq!(worker.health_endpoint, "http://fks_worker:9000/health", "template beats defaults");
+    assert!(worker.critical, "service beats defaults");
+    assert_eq!(worker.tags, ["batch"], "lists are replaced, not appended");
+    assert_eq!(worker.labels.get("team").map(String::as_str), Some("platform"), "tables are merged");
+    assert_eq!(worker.labels.get("owner").map(String::as_str), Some("alice"));
+
+    let templated_defaults = toml.replace("[defaults]", "[defaults]\ntemplate = \"worker\"");
+    assert!(Config::from_toml_str(&templated_defaults).unwrap_err().to_string().contains("cannot set `template`"));
+}
+
+const SETTINGS: &str = r#"
+    [monitoring]
+    check_interval_seconds = 30
+    timeout_seconds = 5
+    retry_attempts = 1
+    batch_size = 5
+
+    [alerts]
+    enable_notifications = false
+    high_latency_threshold_ms = 2000
+    consecutive_failures_threshold = 3
+"#;
+
+fn service_file(id: &str, template: &str) -> String {
+    format!("[[services]]\nid = \"{id}\"\nname = \"{id}\"\ntemplate = \"{template}\"\n")
+}
+
+#[tokio::test]
+async fn includes_merge_services_from_globbed_files() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("services")).unwrap();
+    let main = format!(
+        "include = [\"services/*.toml\", \"alerts.toml\"]\n{SETTINGS}\n[templates.api]\nservice_type = \"Api\"\nhealth_endpoint = \"http://{{id}}:8000/health\"\nexpected_response_time_ms = 500\ncritical = false\n\n{}",
+        service_file("fks_api", "api")
+    );
+    fs::write(dir.path().join("monitor.toml"), main).unwrap();
+    fs::write(dir.path().join("services/data.toml"), service_file("fks_data", "api")).unwrap();
+    fs::write(dir.path().join("services/engine.toml"), service_file("fks_engine", "api")).unwrap();
+    fs::write(dir.path().join("services/notes.md"), "not config").unwrap();
+    fs::write(dir.path().join("alerts.toml"), "[alerts]\nhigh_latency_threshold_ms = 900\n\n[[alerts.routes]]\nchannels = [\"webhook\"]\n").unwrap();
+
+    let cfg = Config::load(dir.path().join("monitor.toml")).await.expect("includes load");
+    let ids: Vec<&str> = cfg.services.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, vec!["fks_api", "fks_data", "fks_engine"]);
+    assert_eq!(cfg.services[2].health_endpoint, "http://fks_engine:8000/health", "templates apply across files");
+    assert_eq!(cfg.alerts.high_latency_threshold_ms, 900, "later files override settings");
+    assert_eq!(cfg.alerts.consecutive_failures_threshold, 3);
+    assert_eq!(cfg.alerts.routes.len(), 1);
+
+    fs::write(dir.path().join("services/dup.toml"), service_file("fks_data", "api")).unwrap();
+    let err = Config::load(dir.path().join("monitor.toml")).await.unwrap_err().to_string();
+    assert!(err.contains("service `fks_data` is defined in both"), "{err}");
+}
+
+#[tokio::test]
+async fn config_directory_loads_every_toml_file() {
+    let dir = tempdir().unwrap();
+    let settings = format!("{SETTINGS}\n[templates.web]\nservice_type = \"Web\"\nhealth_endpoint = \"http://{{id}}/\"\nexpected_response_time_ms = 800\ncritical = true\n");
+    fs::write(dir.path().join("00-settings.toml"), settings).unwrap();
+    fs::write(dir.path().join("team-web.toml"), service_file("fks_web", "web")).unwrap();
+    fs::write(dir.path().join(".team-web.toml.swp"), "garbage = = =").unwrap();
+
+    let cfg = Config::load(dir.path()).await.expect("directory loads");
+    assert_eq!(cfg.services.len(), 1);
+    assert!(cfg.services[0].critical);
+    assert_eq!(fks_master::config::config_files(dir.path()).unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn yaml_and_json_configs_load_by_extension() {
+    let dir = tempdir().unwrap();
+    let yaml = r#"
+monitoring:
+  check_interval_seconds: 15
+  timeout_seconds: 5
+  retry_attempts: 1
+  batch_size: 5
+alerts:
+  enable_notifications: false
+  high_latency_threshold_ms: 1500
+  consecutive_failures_threshold: 2
+  webhook_url:            # null = not set
+include: ["extra.json"]
+services:
+  - id: fks_api
+    name: FKS API
+    service_type: Api
+    health_endpoint: http://fks_api:8000/health
+    expected_response_time_ms: 500
+    critical: true
+    labels: { team: core }
+"#;
+    fs::write(dir.path().join("monitor.yaml"), yaml).unwrap();
+    let json = r#"{"services": [{"id": "fks_web", "name": "FKS Web", "service_type": "Web",
+        "health_endpoint": "http://fks_web/", "expected_response_time_ms": 800, "critical": false, "docker_container": null}]}"#;
+    fs::write(dir.path().join("extra.json"), json).unwrap();
+
+    let cfg = Config::load(dir.path().join("monitor.yaml")).await.expect("yaml loads");
+    assert_eq!(cfg.monitoring.check_interval_seconds, 15);
+    assert!(cfg.alerts.webhook_url.is_none());
+    let ids: Vec<&str> = cfg.services.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, vec!["fks_api", "fks_web"]);
+    assert_eq!(cfg.services[0].labels["team"], "core");
+
+    // Directories pick up every supported extension
+    let conf_d = dir.path().join("conf.d");
+    fs::create_dir(&conf_d).unwrap();
+    fs::write(conf_d.join("a.yml"), "monitoring:\n  check_interval_seconds: 20\n").unwrap();
+    fs::write(conf_d.join("b.json"), json).unwrap();
+    fs::write(conf_d.join("c.toml"), SETTINGS).unwrap();
+    fs::write(conf_d.join("notes.txt"), "ignored").unwrap();
+    assert_eq!(fks_master::config::config_files(&conf_d).unwrap().len(), 3);
+    let cfg = Config::load(&conf_d).await.expect("mixed directory loads");
+    assert_eq!(cfg.monitoring.check_interval_seconds, 30, "later files win");
+    assert_eq!(cfg.services.len(), 1);
+
+    fs::write(dir.path().join("broken.yml"), "services: [unclosed").unwrap();
+    let err = Config::load(dir.path().join("broken.yml")).await.unwrap_err().to_string();
+    assert!(err.contains("broken.yml"), "{err}");
+}
+
+#[tokio::test]
+async fn save_services_rewrites_only_the_services_of_a_single_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("monitor.yaml");
+    fs::write(&path, "monitoring:\n  check_interval_seconds: 15\n  timeout_seconds: 5\n  retry_attempts: 1\n  batch_size: 5\nalerts:\n  enable_notifications: false\n  high_latency_threshold_ms: 1500\n  consecutive_failures_threshold: 2\nservices: []\n").unwrap();
+    let mut services = Config::default().services;
+    services.truncate(2);
+    fks_master::config::save_services(&path, &services).expect("saved");
+
+    let cfg = Config::load(&path).await.expect("still loads");
+    assert_eq!(cfg.monitoring.check_interval_seconds, 15, "other sections kept");
+    let ids: Vec<&str> = cfg.services.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, services.iter().map(|s| s.id.as_str()).collect::<Vec<_>>());
+
+    // Includes and directories have no single place to write services to
+    let with_include = dir.path().join("root.toml");
+    fs::write(&with_include, "include = [\"monitor.yaml\"]\n").unwrap();
+    assert!(fks_master::config::save_services(&with_include, &services).is_err());
+    assert!(fks_master::config::save_services(dir.path(), &services).is_err());
+}
+
+#[test]
+fn json_schema_describes_services_templates_and_includes() {
+    let schema = fks_master::config::json_schema();
+    assert_eq!(schema["title"], "fks_master monitor config");
+    let required: Vec<&str> = schema["required"].as_array().unwrap().iter().filter_map(|v| v.as_str()).collect();
+    assert!(required.contains(&"monitoring") && !required.contains(&"services"), "{required:?}");
+    for key in ["services", "monitoring", "alerts", "discovery", "prometheus", "include", "templates", "defaults"] {
+        assert!(schema["properties"].get(key).is_some(), "missing {key}");
+    }
+
+    let service = &schema["$defs"]["ServiceConfig"];
+    for field in ["health_endpoint", "probes", "labels", "group", "metadata"] {
+        assert!(service["properties"].get(field).is_some(), "ServiceConfig.{field}");
+    }
+    for field in ["docker_host", "heartbeat_window_seconds", "probes", "labels", "metadata", "kubernetes", "systemd_unit", "auto_restart", "drain", "metrics"] {
+        assert!(service["properties"][field]["description"].is_string(), "ServiceConfig.{field} has no description");
+    }
+    assert_eq!(service["properties"]["service_type"]["$ref"], "#/$defs/ServiceType");
+    let variants = serde_json::to_string(&schema["$defs"]["ServiceType"]).unwrap();
+    assert!(variants.contains("\"External\""), "{variants}");
+    // Everything but the id may come from [defaults] or a template
+    assert_eq!(service["required"], serde_json::json!(["id"]));
+    assert_eq!(service["properties"]["template"]["type"], "string");
+}
@@ -10,7 +10,7 @@ async fn compose_handler(headers: HeaderMap, Json(req): Json<ComposeRequest>) ->
     // Authorization copied (simplified) from main is_authorized logic
     if !is_authorized(&headers) {
         metrics::increment_compose_unauthorized();
-        return (StatusCode::UNAUTHORIZED, Json(fks_master::compose::ComposeResult { action: "error".into(), services: vec![], success: false, status_code: Some(401), stdout: String::new(), stderr: "unauthorized".into() }));
+        return (StatusCode::UNAUTHORIZED, Json(fks_master::compose::ComposeResult { action: "error".into(), services: vec![], success: false, status_code: Some(401), stdout: String::new(), stderr: "unauthorized".into(), containers: None, queue_position: None }));
     }
     let result = req.execute().await.unwrap();
     (StatusCode::OK, Json(result))
@@ -47,7 +47,7 @@ async fn compose_authorized_with_jwt() {
     std::env::set_var("FKS_WS_JWT_ALLOWED_ROLES", "admin,orchestrate");
     let app = Router::new().route("/api/compose", post(compose_handler));
     let token = token_for(&["admin"]);
-    let req_struct = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, dry_run: true };
+    let req_struct = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, stream: false, dry_run: true, down: Default::default(), env_file: None, env: Default::default(), command: vec![], no_cache: false, build_args: Default::default(), target: None, ready_timeout_seconds: None, ready_endpoints: Default::default(), registries: vec![] };
     let body_json = serde_json::to_string(&req_struct).unwrap();
     let req = Request::builder().method("POST").uri("/api/compose").header("Authorization", format!("Bearer {}", token)).header("content-type","application/json").body(axum::body::Body::from(body_json)).unwrap();
     let resp = app.clone().oneshot(req).await.unwrap();
@@ -61,7 +61,7 @@ async fn compose_unauthorized_with_bad_role() {
     std::env::set_var("FKS_WS_JWT_ALLOWED_ROLES", "admin");
     let app = Router::new().route("/api/compose", post(compose_handler));
     let token = token_for(&["viewer"]); // not allowed
-    let req_struct = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, dry_run: true };
+    let req_struct = ComposeRequest { action: ComposeAction::Build, services: vec![], file: "docker-compose.yml".into(), project: None, detach: false, tail: None, stream: false, dry_run: true, down: Default::default(), env_file: None, env: Default::default(), command: vec![], no_cache: false, build_args: Default::default(), target: None, ready_timeout_seconds: None, ready_endpoints: Default::default(), registries: vec![] };
     let body_json = serde_json::to_string(&req_struct).unwrap();
     let req = Request::builder().method("POST").uri("/api/compose").header("Authorization", format!("Bearer {}", token)).header("content-type","application/json").body(axum::body::Body::from(body_json)).unwrap();
     let resp = app.clone().oneshot(req).await.unwrap();